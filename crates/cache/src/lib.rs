@@ -13,6 +13,10 @@ use util::*;
 pub mod error;
 pub(crate) mod util;
 
+/// The prefix of the keys under which rpc responses are cached. Keys have the form
+/// `rpc.{method}.{chain_id}.{params_hash}`.
+pub const RPC_CACHE_PREFIX: &str = "rpc";
+
 /// Clap argument parser for the cache subcommand
 #[derive(Debug, Clone, Parser)]
 #[clap(
@@ -30,6 +34,14 @@ pub struct CacheArgs {
 #[derive(Debug, Clone, Parser)]
 pub struct NoArguments {}
 
+/// Arguments for the clean subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct CleanArgs {
+    /// Only remove the cached rpc responses of the given method, e.g. `get_code`.
+    #[clap(long, short)]
+    pub method: Option<String>,
+}
+
 /// Clap subcommand parser for cache subcommands
 #[derive(Debug, Clone, Parser)]
 #[clap(
@@ -39,8 +51,12 @@ pub struct NoArguments {}
 #[allow(clippy::large_enum_variant)]
 pub enum Subcommands {
     /// Clear the cache, removing all objects
-    #[clap(name = "clean", about = "Removes all cached objects in ~/.bifrost/cache")]
-    Clean(NoArguments),
+    #[clap(
+        name = "clean",
+        visible_alias = "clear",
+        about = "Removes all cached objects in ~/.bifrost/cache"
+    )]
+    Clean(CleanArgs),
 
     /// List all cached objects
     #[clap(name = "ls", about = "Lists all cached objects in ~/.bifrost/cache")]
@@ -49,6 +65,13 @@ pub enum Subcommands {
     /// Print the size of the cache in ~/.bifrost/cache
     #[clap(name = "size", about = "Prints the size of the cache in ~/.bifrost/cache")]
     Size(NoArguments),
+
+    /// Print the number and size of cached objects, grouped by kind
+    #[clap(
+        name = "stats",
+        about = "Prints the number and size of cached objects, grouped by kind and rpc method"
+    )]
+    Stats(NoArguments),
 }
 
 /// A simple cache object that stores a value and an expiry time \
@@ -105,6 +128,29 @@ pub fn clear_cache() -> Result<(), Error> {
     Ok(())
 }
 
+/// Remove the cached rpc responses of the given method, returning the number of removed objects
+///
+/// ```
+/// use heimdall_cache::{clear_method, keys, store_cache};
+///
+/// /// add a cached rpc response
+/// store_cache("rpc.clear_method_test.1.00", "value", None);
+///
+/// /// clear the method's cached responses
+/// assert_eq!(clear_method("clear_method_test").expect("!"), 1);
+/// assert!(!keys("*").expect("!").contains(&"rpc.clear_method_test.1.00".to_string()));
+/// ```
+pub fn clear_method(method: &str) -> Result<usize, Error> {
+    let prefix = format!("{RPC_CACHE_PREFIX}.{method}.");
+    let keys =
+        keys(&prefix)?.into_iter().filter(|key| key.starts_with(&prefix)).collect::<Vec<_>>();
+    for key in &keys {
+        delete_cache(key)?;
+    }
+
+    Ok(keys.len())
+}
+
 /// Check if a cached object exists
 ///
 /// ```
@@ -353,14 +399,59 @@ where
     }
 }
 
+/// The key and size in bytes of each cached object
+#[allow(deprecated)]
+fn sizes() -> Result<Vec<(String, u64)>, Error> {
+    let home = home_dir().ok_or_else(|| {
+        Error::Generic(
+            "failed to get home directory. does your os support `std::env::home_dir()`?"
+                .to_string(),
+        )
+    })?;
+    let cache_dir = home.join(".bifrost").join("cache");
+    let mut sizes = Vec::new();
+
+    for entry in cache_dir
+        .read_dir()
+        .map_err(|e| Error::Generic(format!("failed to read cache directory: {e:?}")))?
+    {
+        let entry =
+            entry.map_err(|e| Error::Generic(format!("failed to read cache entry: {e:?}")))?;
+        let metadata = entry
+            .metadata()
+            .map_err(|e| Error::Generic(format!("failed to get metadata: {e:?}")))?;
+        let key = entry.file_name().to_string_lossy().replace(".bin", "");
+        sizes.push((key, metadata.len()));
+    }
+
+    Ok(sizes)
+}
+
+/// The group a cached object is counted under by `heimdall cache stats`: `rpc.{method}` for rpc
+/// responses, otherwise the first component of the key, e.g. `selector`.
+fn stats_group(key: &str) -> String {
+    let mut parts = key.split('.');
+    match (parts.next(), parts.next()) {
+        (Some(RPC_CACHE_PREFIX), Some(method)) => format!("{RPC_CACHE_PREFIX}.{method}"),
+        (Some(kind), _) => kind.to_string(),
+        _ => key.to_string(),
+    }
+}
+
 /// Cache subcommand handler
 #[allow(deprecated)]
 pub fn cache(args: CacheArgs) -> Result<(), Error> {
     match args.sub {
-        Subcommands::Clean(_) => {
-            clear_cache()?;
-            println!("Cache cleared.")
-        }
+        Subcommands::Clean(args) => match args.method {
+            Some(method) => {
+                let removed = clear_method(&method)?;
+                println!("Removed {removed} cached '{method}' responses.")
+            }
+            None => {
+                clear_cache()?;
+                println!("Cache cleared.")
+            }
+        },
         Subcommands::Ls(_) => {
             let keys = keys("*")?;
             println!("Displaying {} cached objects:", keys.len());
@@ -370,30 +461,30 @@ pub fn cache(args: CacheArgs) -> Result<(), Error> {
             }
         }
         Subcommands::Size(_) => {
-            let home = home_dir().ok_or_else(|| {
-                Error::Generic(
-                    "failed to get home directory. does your os support `std::env::home_dir()`?"
-                        .to_string(),
-                )
-            })?;
-            let cache_dir = home.join(".bifrost").join("cache");
-            let mut size = 0;
-
-            for entry in cache_dir
-                .read_dir()
-                .map_err(|e| Error::Generic(format!("failed to read cache directory: {e:?}")))?
-            {
-                let entry = entry
-                    .map_err(|e| Error::Generic(format!("failed to read cache entry: {e:?}")))?;
-                let path = entry.path();
-                let metadata = std::fs::metadata(path)
-                    .map_err(|e| Error::Generic(format!("failed to get metadata: {e:?}")))?;
-                size += metadata.len();
-            }
+            let size = sizes()?.iter().map(|(_, size)| size).sum();
 
             println!("Cached objects: {}", keys("*")?.len());
             println!("Cache size: {}", prettify_bytes(size));
         }
+        Subcommands::Stats(_) => {
+            let mut groups: Vec<(String, usize, u64)> = Vec::new();
+            for (key, size) in sizes()? {
+                let group = stats_group(&key);
+                match groups.iter_mut().find(|(name, ..)| *name == group) {
+                    Some((_, count, total)) => {
+                        *count += 1;
+                        *total += size;
+                    }
+                    None => groups.push((group, 1, size)),
+                }
+            }
+            groups.sort();
+
+            println!("Displaying {} kinds of cached objects:", groups.len());
+            for (group, count, size) in groups {
+                println!("{group:>40} : {count:>6} objects, {}", prettify_bytes(size));
+            }
+        }
     }
 
     Ok(())
@@ -403,7 +494,7 @@ pub fn cache(args: CacheArgs) -> Result<(), Error> {
 #[allow(unused_must_use)]
 #[cfg(test)]
 mod tests {
-    use crate::{delete_cache, exists, keys, read_cache, store_cache};
+    use crate::{delete_cache, exists, keys, read_cache, stats_group, store_cache};
     use serde::{Deserialize, Serialize};
     use std::env::home_dir;
 
@@ -496,4 +587,11 @@ mod tests {
         assert!(exists("does_not_exist").expect("failed to check if key exists"));
         delete_cache("does_not_exist");
     }
+
+    #[test]
+    fn test_stats_group() {
+        assert_eq!(stats_group("rpc.get_code.1.abcdef"), "rpc.get_code");
+        assert_eq!(stats_group("selector.0x12345678"), "selector");
+        assert_eq!(stats_group("key"), "key");
+    }
 }
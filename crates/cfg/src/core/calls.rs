@@ -0,0 +1,413 @@
+use alloy::primitives::{Address, B256, U256};
+use eyre::eyre;
+use heimdall_common::utils::strings::encode_hex;
+use heimdall_vm::core::opcodes::{
+    opcode_name, CALL, CALLCODE, DELEGATECALL, JUMP, MUL, PUSH4, SHL, STATICCALL,
+};
+use petgraph::{
+    dot::{Config, Dot},
+    graph::NodeIndex,
+    visit::EdgeRef,
+    Graph,
+};
+use serde_json::{json, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt::{Display, Formatter},
+};
+
+use super::{jumps::JumpAnalysis, style_dot};
+use crate::{error::Error, CfgFormat};
+
+/// The deepest stack slot a call's return address is looked for in, below its target.
+const MAX_RETURN_DEPTH: usize = 16;
+
+/// A function of the call graph, or an external call one of them makes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CallNode {
+    /// A function dispatched by its selector
+    Public {
+        /// The function's selector
+        selector: String,
+        /// The pc the dispatcher jumps to for the function
+        entry: u128,
+    },
+    /// An internal function, which is jumped to with the label it returns to on the stack
+    Internal {
+        /// The pc of the function's entry
+        entry: u128,
+    },
+    /// A call to another contract
+    External {
+        /// The opcode making the call, e.g. `STATICCALL`
+        opcode: String,
+        /// The called address, if it's a constant
+        address: Option<Address>,
+        /// The called selector, if one was encoded by the calling function
+        selector: Option<String>,
+    },
+}
+
+impl CallNode {
+    fn kind(&self) -> &'static str {
+        match self {
+            CallNode::Public { .. } => "public",
+            CallNode::Internal { .. } => "internal",
+            CallNode::External { .. } => "external",
+        }
+    }
+
+    /// The color nodes of the kind are filled with.
+    fn fill(&self) -> &'static str {
+        match self {
+            CallNode::Public { .. } => "#dbe9ff",
+            CallNode::Internal { .. } => "#ffffff",
+            CallNode::External { .. } => "#ffe0cc",
+        }
+    }
+}
+
+impl Display for CallNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallNode::Public { selector, .. } => write!(f, "function {selector}"),
+            CallNode::Internal { entry } => write!(f, "internal {entry:#x}"),
+            CallNode::External { opcode, address, selector } => {
+                let address = address
+                    .map(|address| address.to_string())
+                    .unwrap_or_else(|| "<unknown>".to_string());
+                match selector {
+                    Some(selector) => write!(f, "{opcode} {address} {selector}"),
+                    None => write!(f, "{opcode} {address}"),
+                }
+            }
+        }
+    }
+}
+
+/// The call graph of a contract: its public functions, the internal functions they call, and
+/// the external calls each of them makes. Each edge is weighted by the number of its call sites.
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    /// The functions and external calls, and the calls between them
+    pub graph: Graph<CallNode, usize>,
+}
+
+impl CallGraph {
+    /// Returns the call graph rendered in the given format.
+    pub fn render(&self, format: CfgFormat) -> Result<String, Error> {
+        match format {
+            CfgFormat::Dot => Ok(self.as_dot()),
+            CfgFormat::Mermaid => Ok(self.as_mermaid()),
+            CfgFormat::Json => Ok(format!("{:#}", self.to_json())),
+            _ => Err(Error::Eyre(eyre!("call graphs can only be written as dot, mermaid or json"))),
+        }
+    }
+
+    /// Returns the call graph as a graphviz formatted string, with public functions filled blue
+    /// and external calls orange.
+    pub fn as_dot(&self) -> String {
+        let output = format!(
+            "{}",
+            Dot::with_attr_getters(
+                &self.graph,
+                &[Config::EdgeNoLabel],
+                &|_, edge| match edge.weight() {
+                    1 => String::new(),
+                    calls => format!("label = \"{calls} calls\" "),
+                },
+                &|_, (_, node)| {
+                    format!("style = \"rounded,filled\" fillcolor = \"{}\" ", node.fill())
+                },
+            )
+        );
+        style_dot(&output)
+    }
+
+    /// Returns the call graph as a mermaid flowchart.
+    fn as_mermaid(&self) -> String {
+        let mut lines = vec!["flowchart LR".to_string()];
+        for node in self.graph.node_indices() {
+            lines.push(format!("    n{}[\"{}\"]", node.index(), self.graph[node]));
+        }
+        for edge in self.graph.edge_references() {
+            let arrow = match edge.weight() {
+                1 => "-->".to_string(),
+                calls => format!("-- \"{calls} calls\" -->"),
+            };
+            lines.push(format!(
+                "    n{} {arrow} n{}",
+                edge.source().index(),
+                edge.target().index()
+            ));
+        }
+        for kind in ["public", "external"] {
+            let nodes = self
+                .graph
+                .node_indices()
+                .filter(|node| self.graph[*node].kind() == kind)
+                .collect::<Vec<_>>();
+            if let Some(first) = nodes.first() {
+                let nodes =
+                    nodes.iter().map(|node| format!("n{}", node.index())).collect::<Vec<_>>();
+                lines.push(format!("    classDef {kind} fill:{}", self.graph[*first].fill()));
+                lines.push(format!("    class {} {kind}", nodes.join(",")));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Returns the call graph as JSON, listing each node with its kind, and the calls between
+    /// them, which refer to nodes by their index.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "nodes": self
+                .graph
+                .node_indices()
+                .map(|node| {
+                    let mut value = json!({
+                        "id": node.index(),
+                        "kind": self.graph[node].kind(),
+                        "label": self.graph[node].to_string(),
+                    });
+                    match &self.graph[node] {
+                        CallNode::Public { selector, entry } => {
+                            value["selector"] = json!(selector);
+                            value["entry"] = json!(entry);
+                        }
+                        CallNode::Internal { entry } => value["entry"] = json!(entry),
+                        CallNode::External { opcode, address, selector } => {
+                            value["opcode"] = json!(opcode);
+                            value["address"] = json!(address);
+                            value["selector"] = json!(selector);
+                        }
+                    }
+                    value
+                })
+                .collect::<Vec<_>>(),
+            "edges": self
+                .graph
+                .edge_references()
+                .map(|edge| json!({
+                    "from": edge.source().index(),
+                    "to": edge.target().index(),
+                    "calls": edge.weight(),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// The node of the graph, adding it if it's new. Returns whether it was added.
+    fn node(
+        &mut self,
+        nodes: &mut HashMap<CallNode, NodeIndex>,
+        node: CallNode,
+    ) -> (NodeIndex, bool) {
+        match nodes.get(&node) {
+            Some(index) => (*index, false),
+            None => {
+                let index = self.graph.add_node(node.clone());
+                nodes.insert(node, index);
+                (index, true)
+            }
+        }
+    }
+
+    /// Adds a call site from `caller` to `callee`.
+    fn add_call(&mut self, caller: NodeIndex, callee: NodeIndex) {
+        match self.graph.find_edge(caller, callee) {
+            Some(edge) => self.graph[edge] += 1,
+            None => {
+                self.graph.add_edge(caller, callee, 1);
+            }
+        }
+    }
+}
+
+/// A jump to the entry of an internal function, made with the label it returns to on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InternalCall {
+    entry: u128,
+    return_to: u128,
+}
+
+/// The blocks reachable from a function's entry, without entering the functions it calls.
+#[derive(Debug, Default)]
+struct Body {
+    /// The entry of each internal function called, once per call site
+    callees: Vec<u128>,
+    /// The pc and opcode of each external call
+    calls: Vec<(u128, u8)>,
+    /// The selectors the function encodes for calls, by the pc they're pushed at
+    selectors: BTreeMap<u128, String>,
+}
+
+/// Extracts the call graph of the contract from its jump analysis, starting from the entry of
+/// each of its public functions, given by their selectors.
+pub(crate) fn call_graph(analysis: &JumpAnalysis, functions: &[(String, u128)]) -> CallGraph {
+    let calls = internal_calls(analysis);
+    let mut call_graph = CallGraph::default();
+    let mut nodes = HashMap::new();
+    let mut queue = VecDeque::new();
+    for (selector, entry) in functions {
+        let (node, _) = call_graph
+            .node(&mut nodes, CallNode::Public { selector: selector.clone(), entry: *entry });
+        queue.push_back((node, *entry));
+    }
+
+    // internal functions are walked once, when they're first called
+    while let Some((caller, entry)) = queue.pop_front() {
+        let body = walk(analysis, &calls, entry);
+        for callee in body.callees.iter().copied() {
+            let (node, added) = call_graph.node(&mut nodes, CallNode::Internal { entry: callee });
+            if added {
+                queue.push_back((node, callee));
+            }
+            call_graph.add_call(caller, node);
+        }
+        for (pc, opcode) in &body.calls {
+            let (node, _) =
+                call_graph.node(&mut nodes, external_call(analysis, &body, *pc, *opcode));
+            call_graph.add_call(caller, node);
+        }
+    }
+
+    call_graph
+}
+
+/// Finds the calls to internal functions. A call is an unconditional jump which pushes both its
+/// target and a label below it, which a computed jump elsewhere returns to.
+fn internal_calls(analysis: &JumpAnalysis) -> HashMap<u128, InternalCall> {
+    let mut returns = BTreeSet::new();
+    let mut candidates = Vec::new();
+    for instructions in analysis.blocks() {
+        let Some(jump) = instructions.last().filter(|instruction| instruction.opcode == JUMP)
+        else {
+            continue;
+        };
+        let Some(targets) = analysis.targets(jump.pc) else { continue };
+
+        // a jump whose target wasn't pushed by its block returns to a label pushed by its caller
+        let Some(target) = analysis.values(jump.pc, 0, true) else {
+            returns.extend(targets.iter().copied());
+            continue;
+        };
+        let [entry] =
+            target.iter().filter_map(|value| u128::try_from(*value).ok()).collect::<Vec<_>>()[..]
+        else {
+            continue;
+        };
+        let return_to = (1..=MAX_RETURN_DEPTH).find_map(|depth| {
+            let values = analysis.values(jump.pc, depth, true)?;
+            let [label] = values.iter().collect::<Vec<_>>()[..] else { return None };
+            u128::try_from(*label).ok().filter(|label| *label != entry && analysis.is_label(*label))
+        });
+        if let Some(return_to) = return_to {
+            candidates.push((jump.pc, InternalCall { entry, return_to }));
+        }
+    }
+
+    candidates.into_iter().filter(|(_, call)| returns.contains(&call.return_to)).collect()
+}
+
+/// Walks the blocks of the function starting at `entry`, continuing at the return label of each
+/// internal function it calls, and stopping at the jumps which return from it.
+fn walk(analysis: &JumpAnalysis, calls: &HashMap<u128, InternalCall>, entry: u128) -> Body {
+    let mut body = Body::default();
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![entry];
+    while let Some(start) = stack.pop() {
+        if !seen.insert(start) {
+            continue;
+        }
+        let Some(instructions) = analysis.block(start) else { continue };
+
+        for (i, instruction) in instructions.iter().enumerate() {
+            match instruction.opcode {
+                CALL | CALLCODE | DELEGATECALL | STATICCALL => {
+                    body.calls.push((instruction.pc, instruction.opcode))
+                }
+                // a selector is shifted into the first bytes of a call's calldata
+                PUSH4
+                    if instructions[i + 1..]
+                        .iter()
+                        .take(4)
+                        .any(|next| matches!(next.opcode, SHL | MUL)) =>
+                {
+                    let selector = instruction.operand.unwrap_or_default().to_be_bytes::<32>();
+                    body.selectors
+                        .insert(instruction.pc, format!("0x{}", encode_hex(&selector[28..])));
+                }
+                _ => {}
+            }
+        }
+
+        let Some(last) = instructions.last() else { continue };
+        match calls.get(&last.pc) {
+            Some(call) => {
+                body.callees.push(call.entry);
+                stack.push(call.return_to);
+            }
+            None if last.opcode == JUMP && analysis.values(last.pc, 0, true).is_none() => {}
+            None => stack.extend(analysis.successors(start).into_iter().map(|(next, _)| next)),
+        }
+    }
+
+    body
+}
+
+/// The external call made at `pc`, with its address if it's a constant, and the last selector
+/// its function encoded before it.
+fn external_call(analysis: &JumpAnalysis, body: &Body, pc: u128, opcode: u8) -> CallNode {
+    let address = analysis.values(pc, 1, false).and_then(|values| {
+        let [value] = values.iter().collect::<Vec<_>>()[..] else { return None };
+        (*value >> 160 == U256::ZERO).then(|| Address::from_word(B256::from(*value)))
+    });
+    let selector = body.selectors.range(..pc).next_back().map(|(_, selector)| selector.clone());
+
+    CallNode::External { opcode: opcode_name(opcode).to_string(), address, selector }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::strings::decode_hex;
+
+    fn analysis(bytecode: &str) -> JumpAnalysis {
+        JumpAnalysis::new(&decode_hex(bytecode).expect("invalid bytecode"))
+    }
+
+    #[test]
+    fn test_internal_calls() {
+        // an internal function at 0x0d, called from 0x04 and 0x0a
+        let call_graph = call_graph(
+            &analysis("6005600d565b600b600d565b005b56"),
+            &[("0x12345678".to_string(), 0)],
+        );
+
+        assert_eq!(call_graph.graph.node_count(), 2);
+        assert_eq!(call_graph.graph[NodeIndex::new(1)], CallNode::Internal { entry: 0x0d });
+        let edge = call_graph.graph.find_edge(NodeIndex::new(0), NodeIndex::new(1));
+        assert_eq!(edge.map(|edge| call_graph.graph[edge]), Some(2));
+        assert!(call_graph.as_dot().contains("label = \"2 calls\""));
+    }
+
+    #[test]
+    fn test_external_calls() {
+        // encodes `transfer`'s selector, then calls 0x1111...1111
+        let call_graph = call_graph(
+            &analysis("63a9059cbb60e01b600052600060006004600060007311111111111111111111111111111111111111115af100"),
+            &[("0x12345678".to_string(), 0)],
+        );
+
+        assert_eq!(
+            call_graph.graph[NodeIndex::new(1)],
+            CallNode::External {
+                opcode: "CALL".to_string(),
+                address: Some(Address::repeat_byte(0x11)),
+                selector: Some("0xa9059cbb".to_string()),
+            }
+        );
+        assert_eq!(call_graph.to_json()["nodes"][1]["kind"], "external");
+    }
+}
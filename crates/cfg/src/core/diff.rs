@@ -0,0 +1,422 @@
+use eyre::eyre;
+use petgraph::{
+    dot::{Config, Dot},
+    graph::NodeIndex,
+    visit::EdgeRef,
+    Direction, Graph,
+};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::info;
+
+use super::{cfg, edge_color, format, style_dot};
+use crate::{error::Error, CfgArgs, CfgFormat};
+
+/// Blocks whose opcodes are less similar than this aren't considered a changed version of each
+/// other.
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// How a block of the newer contract differs from the older one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockStatus {
+    /// The block is in both contracts, apart from the offsets of its jump targets
+    Unchanged,
+    /// The block is in both contracts, but its instructions differ
+    Changed,
+    /// The block is only in the newer contract
+    Added,
+    /// The block is only in the older contract
+    Removed,
+}
+
+impl BlockStatus {
+    fn name(&self) -> &'static str {
+        match self {
+            BlockStatus::Unchanged => "unchanged",
+            BlockStatus::Changed => "changed",
+            BlockStatus::Added => "added",
+            BlockStatus::Removed => "removed",
+        }
+    }
+
+    /// The color blocks with the status are filled with, if they're highlighted.
+    fn fill(&self) -> Option<&'static str> {
+        match self {
+            BlockStatus::Unchanged => None,
+            BlockStatus::Changed => Some("#fbe3a6"),
+            BlockStatus::Added => Some("#c8f7c5"),
+            BlockStatus::Removed => Some("#f7c5c5"),
+        }
+    }
+}
+
+/// A block of the diff, and where it starts in each contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDiff {
+    /// How the block differs between the contracts
+    pub status: BlockStatus,
+    /// The pc the block starts at in the older contract, unless it was added
+    pub old_pc: Option<u128>,
+    /// The pc the block starts at in the newer contract, unless it was removed
+    pub new_pc: Option<u128>,
+}
+
+/// The difference between the control flow graphs of two contracts, such as two versions of a
+/// proxy's implementation.
+#[derive(Debug, Clone)]
+pub struct CfgDiff {
+    /// The newer contract's graph, with the blocks which were removed from the older contract
+    pub graph: Graph<String, String>,
+    /// How each node of the graph differs between the contracts, by the node's index
+    pub blocks: Vec<BlockDiff>,
+}
+
+impl CfgDiff {
+    /// The number of blocks with the given status.
+    pub fn count(&self, status: BlockStatus) -> usize {
+        self.blocks.iter().filter(|block| block.status == status).count()
+    }
+
+    /// Returns the diff rendered in the given format, with added, removed and changed blocks
+    /// filled green, red and orange.
+    pub fn render(&self, format: CfgFormat, color_edges: bool) -> Result<String, Error> {
+        match format {
+            CfgFormat::Dot => Ok(self.as_dot(color_edges)),
+            CfgFormat::Mermaid => Ok(self.as_mermaid(color_edges)),
+            CfgFormat::Json => Ok(format!("{:#}", self.to_json())),
+            _ => Err(Error::Eyre(eyre!("cfg diffs can only be written as dot, mermaid or json"))),
+        }
+    }
+
+    /// Returns the diff as a graphviz formatted string.
+    pub fn as_dot(&self, color_edges: bool) -> String {
+        let output = format!(
+            "{}",
+            Dot::with_attr_getters(
+                &self.graph,
+                &[Config::EdgeNoLabel],
+                &|_, edge| edge_color(edge.weight(), color_edges).to_string(),
+                &|_, (node, _)| match self.blocks[node.index()].status.fill() {
+                    Some(fill) => format!("style = \"rounded,filled\" fillcolor = \"{fill}\" "),
+                    None => String::new(),
+                },
+            )
+        );
+        style_dot(&output)
+    }
+
+    /// Returns the diff as a mermaid flowchart.
+    fn as_mermaid(&self, color_edges: bool) -> String {
+        let mut output = format::to_mermaid(&self.graph, color_edges, None);
+        for status in [BlockStatus::Changed, BlockStatus::Added, BlockStatus::Removed] {
+            let nodes = self
+                .blocks
+                .iter()
+                .enumerate()
+                .filter(|(_, block)| block.status == status)
+                .map(|(i, _)| format!("n{i}"))
+                .collect::<Vec<_>>();
+            if let (false, Some(fill)) = (nodes.is_empty(), status.fill()) {
+                output.push_str(&format!("\n    classDef {} fill:{fill}", status.name()));
+                output.push_str(&format!("\n    class {} {}", nodes.join(","), status.name()));
+            }
+        }
+        output
+    }
+
+    /// Returns the diff as JSON, listing each block with its status and pcs, the edges between
+    /// them, and the number of blocks with each status.
+    pub fn to_json(&self) -> Value {
+        let mut document = format::to_json_document(&self.graph, None);
+        if let Some(blocks) = document["blocks"].as_array_mut() {
+            for (block, diff) in blocks.iter_mut().zip(&self.blocks) {
+                block["status"] = json!(diff.status.name());
+                block["old_pc"] = json!(diff.old_pc);
+                block["new_pc"] = json!(diff.new_pc);
+            }
+        }
+        document["summary"] = json!({
+            "unchanged": self.count(BlockStatus::Unchanged),
+            "changed": self.count(BlockStatus::Changed),
+            "added": self.count(BlockStatus::Added),
+            "removed": self.count(BlockStatus::Removed),
+        });
+        document
+    }
+
+    /// Returns a summary of the changes, listing each block which was changed, added or removed.
+    pub fn summary(&self) -> String {
+        let pc = |pc: Option<u128>| pc.map(|pc| format!("{pc:#x}")).unwrap_or_default();
+        let mut lines = vec![format!(
+            "{} blocks unchanged, {} changed, {} added, {} removed",
+            self.count(BlockStatus::Unchanged),
+            self.count(BlockStatus::Changed),
+            self.count(BlockStatus::Added),
+            self.count(BlockStatus::Removed),
+        )];
+        for block in &self.blocks {
+            match block.status {
+                BlockStatus::Unchanged => {}
+                BlockStatus::Changed => lines.push(format!(
+                    "  changed  {} (was {})",
+                    pc(block.new_pc),
+                    pc(block.old_pc)
+                )),
+                BlockStatus::Added => lines.push(format!("  added    {}", pc(block.new_pc))),
+                BlockStatus::Removed => lines.push(format!("  removed  {}", pc(block.old_pc))),
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Generates the control flow graphs of the two targets given with `--diff`, and aligns their
+/// blocks to find those which were changed, added or removed.
+pub async fn cfg_diff(args: CfgArgs) -> Result<CfgDiff, Error> {
+    let [old, new] = args.diff.as_slice() else {
+        return Err(Error::Eyre(eyre!("--diff takes exactly two targets")));
+    };
+
+    info!("generating cfg of '{}'", old);
+    let old = cfg(CfgArgs { target: old.clone(), diff: Vec::new(), ..args.clone() }).await?;
+    info!("generating cfg of '{}'", new);
+    let new = cfg(CfgArgs { target: new.clone(), diff: Vec::new(), ..args }).await?;
+
+    let diff = diff_graphs(&old.graph, &new.graph);
+    info!("{}", diff.summary().lines().next().unwrap_or_default());
+    Ok(diff)
+}
+
+/// A node of one of the graphs, normalized for alignment.
+struct Block<'a> {
+    start: Option<u128>,
+    /// The block's instructions, with the operands which are jump targets removed, as they
+    /// shift whenever code before them changes
+    key: String,
+    opcodes: Vec<&'a str>,
+}
+
+impl<'a> Block<'a> {
+    fn new(assembly: &'a str, labels: &HashSet<u128>) -> Self {
+        let instructions = format::instructions(assembly);
+        let key = instructions
+            .iter()
+            .map(|instruction| {
+                let operand = instruction.operand.map(|operand| {
+                    match u128::from_str_radix(operand.trim_start_matches("0x"), 16) {
+                        Ok(target) if labels.contains(&target) => "@label",
+                        _ => operand,
+                    }
+                });
+                format!("{} {}", instruction.opcode, operand.unwrap_or_default())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Self {
+            start: instructions.first().map(|instruction| instruction.pc),
+            opcodes: instructions.iter().map(|instruction| instruction.opcode).collect(),
+            key,
+        }
+    }
+}
+
+/// The normalized blocks of the graph, by node index.
+fn blocks(graph: &Graph<String, String>) -> Vec<Block<'_>> {
+    let labels = graph
+        .node_weights()
+        .flat_map(|assembly| format::instructions(assembly))
+        .filter(|instruction| instruction.opcode == "JUMPDEST")
+        .map(|instruction| instruction.pc)
+        .collect::<HashSet<_>>();
+    graph.node_weights().map(|assembly| Block::new(assembly, &labels)).collect()
+}
+
+/// How similar the opcodes of the blocks are, as the fraction of them in their longest common
+/// subsequence.
+fn similarity(a: &[&str], b: &[&str]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            lengths[i][j] = match a[i - 1] == b[j - 1] {
+                true => lengths[i - 1][j - 1] + 1,
+                false => lengths[i - 1][j].max(lengths[i][j - 1]),
+            };
+        }
+    }
+    2.0 * lengths[a.len()][b.len()] as f64 / (a.len() + b.len()) as f64
+}
+
+/// Aligns the blocks of the two graphs. Blocks with the same instructions, ignoring the offsets
+/// of jump targets, are matched first. The rest are matched to the most similar unmatched
+/// neighbor of a matched block, so changed blocks are found by where they sit in the graph.
+pub(crate) fn diff_graphs(old: &Graph<String, String>, new: &Graph<String, String>) -> CfgDiff {
+    let (old_blocks, new_blocks) = (blocks(old), blocks(new));
+
+    // match the blocks which are the same in both graphs
+    let mut unmatched: HashMap<&str, VecDeque<NodeIndex>> = HashMap::new();
+    for node in old.node_indices() {
+        unmatched.entry(old_blocks[node.index()].key.as_str()).or_default().push_back(node);
+    }
+    let mut matches: HashMap<NodeIndex, (NodeIndex, BlockStatus)> = HashMap::new();
+    for node in new.node_indices() {
+        if let Some(matched) = unmatched
+            .get_mut(new_blocks[node.index()].key.as_str())
+            .and_then(|nodes| nodes.pop_front())
+        {
+            matches.insert(node, (matched, BlockStatus::Unchanged));
+        }
+    }
+
+    // match the changed blocks by their neighbors, until no more are found
+    let mut matched_old = matches.values().map(|(node, _)| *node).collect::<HashSet<_>>();
+    loop {
+        let mut found = false;
+        for node in new.node_indices() {
+            if matches.contains_key(&node) {
+                continue;
+            }
+
+            // the old counterparts of this block's neighbors are adjacent to its own
+            let candidates = [Direction::Incoming, Direction::Outgoing]
+                .into_iter()
+                .flat_map(|direction| {
+                    new.neighbors_directed(node, direction)
+                        .filter_map(|neighbor| matches.get(&neighbor))
+                        .flat_map(|(counterpart, _)| {
+                            old.neighbors_directed(*counterpart, direction.opposite())
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .filter(|candidate| !matched_old.contains(candidate))
+                .collect::<HashSet<_>>();
+
+            let best = candidates
+                .into_iter()
+                .map(|candidate| {
+                    let score = similarity(
+                        &old_blocks[candidate.index()].opcodes,
+                        &new_blocks[node.index()].opcodes,
+                    );
+                    (candidate, score)
+                })
+                .filter(|(_, score)| *score >= MIN_SIMILARITY)
+                .max_by(|(a, a_score), (b, b_score)| a_score.total_cmp(b_score).then(b.cmp(a)));
+            if let Some((candidate, _)) = best {
+                matches.insert(node, (candidate, BlockStatus::Changed));
+                matched_old.insert(candidate);
+                found = true;
+            }
+        }
+        if !found {
+            break;
+        }
+    }
+
+    // the diff is the newer graph, with the blocks which were removed from the older one
+    let mut graph = new.clone();
+    let mut blocks = new
+        .node_indices()
+        .map(|node| match matches.get(&node) {
+            Some((counterpart, status)) => BlockDiff {
+                status: *status,
+                old_pc: old_blocks[counterpart.index()].start,
+                new_pc: new_blocks[node.index()].start,
+            },
+            None => BlockDiff {
+                status: BlockStatus::Added,
+                old_pc: None,
+                new_pc: new_blocks[node.index()].start,
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let mut old_nodes = matches
+        .iter()
+        .map(|(node, (counterpart, _))| (*counterpart, *node))
+        .collect::<HashMap<_, _>>();
+    for node in old.node_indices().filter(|node| !matched_old.contains(node)) {
+        old_nodes.insert(node, graph.add_node(old[node].clone()));
+        blocks.push(BlockDiff {
+            status: BlockStatus::Removed,
+            old_pc: old_blocks[node.index()].start,
+            new_pc: None,
+        });
+    }
+    for edge in old.edge_references() {
+        if !matched_old.contains(&edge.source()) || !matched_old.contains(&edge.target()) {
+            graph.update_edge(
+                old_nodes[&edge.source()],
+                old_nodes[&edge.target()],
+                edge.weight().clone(),
+            );
+        }
+    }
+
+    CfgDiff { graph, blocks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(blocks: &[&str], edges: &[(usize, usize)]) -> Graph<String, String> {
+        let mut graph = Graph::new();
+        let nodes =
+            blocks.iter().map(|block| graph.add_node(block.to_string())).collect::<Vec<_>>();
+        for (from, to) in edges {
+            graph.add_edge(nodes[*from], nodes[*to], "true".to_string());
+        }
+        graph
+    }
+
+    #[test]
+    fn test_diff_graphs() {
+        let old = graph(
+            &[
+                "0 PUSH1 0x80\n0x02 PUSH1 0x0a\n0x04 JUMPI \n",
+                "0x05 PUSH1 0x01\n0x07 PUSH1 0\n0x09 SSTORE \n",
+                "0x0a JUMPDEST \n0x0b STOP \n",
+            ],
+            &[(0, 1), (0, 2)],
+        );
+        // a block is inserted before the jumpdest, shifting it, and the sstore becomes an sload
+        let new = graph(
+            &[
+                "0 PUSH1 0x80\n0x02 PUSH1 0x0c\n0x04 JUMPI \n",
+                "0x05 PUSH1 0x01\n0x07 PUSH1 0\n0x09 SLOAD \n",
+                "0x0c JUMPDEST \n0x0d STOP \n",
+                "0x0e CALLER \n0x0f SELFDESTRUCT \n",
+            ],
+            &[(0, 1), (0, 2), (1, 3)],
+        );
+
+        let diff = diff_graphs(&old, &new);
+        let statuses = diff.blocks.iter().map(|block| block.status).collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                BlockStatus::Unchanged,
+                BlockStatus::Changed,
+                BlockStatus::Unchanged,
+                BlockStatus::Added
+            ]
+        );
+        assert_eq!(diff.blocks[2].old_pc, Some(0x0a));
+        assert_eq!(diff.blocks[2].new_pc, Some(0x0c));
+        assert!(diff.summary().starts_with("2 blocks unchanged, 1 changed, 1 added, 0 removed"));
+    }
+
+    #[test]
+    fn test_diff_removed() {
+        let old = graph(&["0 CALLVALUE \n0x01 JUMPI \n", "0x02 INVALID \n"], &[(0, 1)]);
+        let new = graph(&["0 CALLVALUE \n0x01 JUMPI \n"], &[]);
+
+        let diff = diff_graphs(&old, &new);
+        assert_eq!(diff.blocks[1].status, BlockStatus::Removed);
+        assert_eq!(diff.graph.edge_count(), 1);
+        assert!(diff.as_dot(false).contains("fillcolor = \"#f7c5c5\""));
+    }
+}
@@ -0,0 +1,140 @@
+use alloy::primitives::U256;
+use heimdall_common::utils::strings::encode_hex_reduced;
+use heimdall_vm::core::{
+    eof::{
+        eof_opcode_info, EofContainer, EofInstruction, CALLF, JUMPF, RETF, RETURNCONTRACT, RJUMP,
+        RJUMPI, RJUMPV,
+    },
+    opcodes::{INVALID, RETURN, REVERT, STOP},
+};
+use petgraph::{graph::NodeIndex, Graph};
+use std::collections::{BTreeSet, HashMap};
+
+/// Whether control never falls through the instruction to the next one.
+fn ends_flow(opcode: u8) -> bool {
+    matches!(opcode, RJUMP | RETF | JUMPF | STOP | RETURN | REVERT | INVALID | RETURNCONTRACT)
+}
+
+/// The assembly of a block's instruction, e.g. `0x1e RJUMPI 0xfff8`.
+fn assembly(instruction: &EofInstruction) -> String {
+    format!(
+        "{} {} {}\n",
+        encode_hex_reduced(U256::from(instruction.pc)),
+        eof_opcode_info(instruction.opcode).map(|info| info.name()).unwrap_or("unknown"),
+        match instruction.immediate.is_empty() {
+            true => String::new(),
+            false => encode_hex_reduced(U256::from_be_slice(&instruction.immediate)),
+        }
+    )
+}
+
+/// Builds the control flow graph of an EOF container's code sections. EOF's jumps are relative
+/// and its calls go to code sections, so the graph is built from the bytecode alone, without
+/// executing it. Jumps are labeled `true`, fallthroughs `false`, and the edges from a block to
+/// the code sections it calls with `CALLF` are labeled `call`.
+pub(crate) fn build_eof_cfg(container: &EofContainer) -> Graph<String, String> {
+    let mut graph = Graph::new();
+
+    // the blocks of each section, as their first instruction's pc and their instructions
+    let sections = container
+        .code_sections
+        .iter()
+        .map(|section| {
+            let instructions = section.instructions();
+            let mut leaders = instructions
+                .iter()
+                .flat_map(|instruction| instruction.relative_targets())
+                .collect::<BTreeSet<_>>();
+            leaders.extend(instructions.first().map(|instruction| instruction.pc));
+            leaders.extend(instructions.windows(2).filter_map(|pair| {
+                (ends_flow(pair[0].opcode) || matches!(pair[0].opcode, RJUMPI | RJUMPV))
+                    .then_some(pair[1].pc)
+            }));
+
+            let mut blocks: Vec<Vec<EofInstruction>> = Vec::new();
+            for instruction in instructions {
+                match (leaders.contains(&instruction.pc), blocks.last_mut()) {
+                    (false, Some(block)) => block.push(instruction),
+                    _ => blocks.push(vec![instruction]),
+                }
+            }
+            blocks
+        })
+        .collect::<Vec<_>>();
+
+    let nodes = sections
+        .iter()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .map(|block| {
+                    (block[0].pc, graph.add_node(block.iter().map(assembly).collect::<String>()))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let by_pc = nodes.iter().flatten().copied().collect::<HashMap<usize, NodeIndex>>();
+    let section_entry = |section: usize| nodes.get(section).and_then(|nodes| nodes.first());
+
+    for (blocks, nodes) in sections.iter().zip(&nodes) {
+        for (i, (block, (_, node))) in blocks.iter().zip(nodes).enumerate() {
+            let last = block.last().expect("blocks are never empty");
+
+            for target in last.relative_targets() {
+                if let Some(target) = by_pc.get(&target) {
+                    graph.update_edge(*node, *target, "true".to_string());
+                }
+            }
+            if last.opcode == JUMPF {
+                if let Some((_, entry)) = last.section_target().and_then(section_entry) {
+                    graph.update_edge(*node, *entry, "true".to_string());
+                }
+            }
+            if !ends_flow(last.opcode) {
+                if let Some((_, next)) = nodes.get(i + 1) {
+                    graph.update_edge(*node, *next, "false".to_string());
+                }
+            }
+            for call in block.iter().filter(|instruction| instruction.opcode == CALLF) {
+                if let Some((_, entry)) = call.section_target().and_then(section_entry) {
+                    graph.update_edge(*node, *entry, "call".to_string());
+                }
+            }
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::strings::decode_hex;
+    use petgraph::visit::EdgeRef;
+
+    #[test]
+    fn test_build_eof_cfg() {
+        // two code sections: the first calls the second and loops back, the second returns
+        let bytecode = decode_hex(
+            "ef0001010008020002000900020400020000800002010100016001e30001e1fff8005be4aabb",
+        )
+        .expect("invalid bytecode");
+        let container = EofContainer::parse(&bytecode).expect("invalid container");
+        let graph = build_eof_cfg(&container);
+
+        assert_eq!(
+            graph.node_weights().cloned().collect::<Vec<_>>(),
+            vec![
+                "0x19 PUSH1 0x01\n0x1b CALLF 0x01\n0x1e RJUMPI 0xfff8\n",
+                "0x21 STOP \n",
+                "0x22 JUMPDEST \n0x23 RETF \n",
+            ]
+        );
+        let mut edges = graph
+            .edge_references()
+            .map(|edge| (edge.source().index(), edge.target().index(), edge.weight().as_str()))
+            .collect::<Vec<_>>();
+        edges.sort();
+        assert_eq!(edges, vec![(0, 0, "true"), (0, 1, "false"), (0, 2, "call")]);
+    }
+}
@@ -0,0 +1,312 @@
+use petgraph::{visit::EdgeRef, Graph};
+use serde_json::{json, Value};
+
+use crate::core::{
+    gas::{heat, GasProfile},
+    loops::Loops,
+};
+
+/// The HTML page rendering a graph's JSON document, which replaces its `/*CFG_DATA*/` placeholder.
+const VIEWER: &str = include_str!("viewer.html");
+
+/// An instruction of a basic block, parsed from a line of the node's assembly, e.g.
+/// `0x21 PUSH2 0x58`.
+pub(crate) struct Instruction<'a> {
+    pub(crate) pc: u128,
+    pub(crate) opcode: &'a str,
+    pub(crate) operand: Option<&'a str>,
+}
+
+/// Parses the instructions of a node's assembly, skipping any lines which aren't instructions.
+pub(crate) fn instructions(block: &str) -> Vec<Instruction<'_>> {
+    block
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pc = u128::from_str_radix(parts.next()?.trim_start_matches("0x"), 16).ok()?;
+            Some(Instruction { pc, opcode: parts.next()?, operand: parts.next() })
+        })
+        .collect()
+}
+
+/// The condition under which an edge is taken, from its label. Edges into a `JUMPDEST` are
+/// labeled `true`, as they're taken by jumping, while the others fall through. The edges from an
+/// EOF block to the code sections it calls are labeled `call`.
+fn condition(label: &str) -> &'static str {
+    match label {
+        "true" => "jump",
+        "false" => "fallthrough",
+        "call" => "call",
+        _ => "unconditional",
+    }
+}
+
+/// Returns the control flow graph as a mermaid flowchart. If `color_edges` is set, jumps are
+/// colored green and fallthroughs red. The blocks of loops are filled, and the back edges closing
+/// them are dotted. If the graph's gas was annotated, each block is labeled with its gas, and
+/// filled by the gas spent in it if a transaction was profiled.
+pub(crate) fn to_mermaid(
+    graph: &Graph<String, String>,
+    color_edges: bool,
+    gas: Option<&GasProfile>,
+) -> String {
+    let loops = Loops::new(graph);
+    let max_spent = gas.map(|gas| gas.max_spent(graph.node_weights())).unwrap_or_default();
+    let mut lines = vec!["flowchart TD".to_string()];
+    let mut styles = Vec::new();
+    for node in graph.node_indices() {
+        let block = gas.map(|gas| gas.block(&graph[node]));
+        let label = block
+            .map(|block| block.label())
+            .into_iter()
+            .chain(graph[node].lines().map(|line| line.trim().replace('"', "#quot;")))
+            .collect::<Vec<_>>()
+            .join("<br/>");
+        lines.push(format!("    n{}[\"{label}\"]", node.index()));
+        if let Some(spent) = block.and_then(|block| block.gas_spent) {
+            styles.push(format!("    style n{} fill:{}", node.index(), heat(spent, max_spent)));
+        }
+    }
+
+    for (i, edge) in graph.edge_references().enumerate() {
+        lines.push(format!(
+            "    n{} {}|{}| n{}",
+            edge.source().index(),
+            if loops.is_back_edge(edge.id()) { "-.->" } else { "-->" },
+            condition(edge.weight()),
+            edge.target().index()
+        ));
+        if color_edges {
+            let color = match edge.weight().as_str() {
+                "true" => "green",
+                _ => "red",
+            };
+            lines.push(format!("    linkStyle {i} stroke:{color}"));
+        }
+    }
+
+    let headers = graph.node_indices().filter(|node| loops.is_header(*node)).collect::<Vec<_>>();
+    let cyclic = graph
+        .node_indices()
+        .filter(|node| loops.is_cyclic(*node) && !loops.is_header(*node))
+        .collect::<Vec<_>>();
+    for (class, fill, nodes) in [("loopHeader", "#ffe08a", headers), ("loop", "#fff3c4", cyclic)] {
+        if !nodes.is_empty() {
+            lines.push(format!("    classDef {class} fill:{fill}"));
+            lines.push(format!(
+                "    class {} {class}",
+                nodes.iter().map(|node| format!("n{}", node.index())).collect::<Vec<_>>().join(",")
+            ));
+        }
+    }
+
+    // the heat map is styled last, so it overrides the loops' classes
+    lines.extend(styles);
+    lines.join("\n")
+}
+
+/// Returns the control flow graph as a GraphML document. Each node carries its assembly, pc
+/// range, immediate dominator and whether it's on a loop, and each edge the condition under which
+/// it's taken and whether it closes a loop. If the graph's gas was annotated, each node also
+/// carries its gas.
+pub(crate) fn to_graphml(graph: &Graph<String, String>, gas: Option<&GasProfile>) -> String {
+    let loops = Loops::new(graph);
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#.to_string(),
+        r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#.to_string(),
+        r#"  <key id="start_pc" for="node" attr.name="start_pc" attr.type="long"/>"#.to_string(),
+        r#"  <key id="end_pc" for="node" attr.name="end_pc" attr.type="long"/>"#.to_string(),
+        r#"  <key id="dominator" for="node" attr.name="dominator" attr.type="string"/>"#
+            .to_string(),
+        r#"  <key id="loop_header" for="node" attr.name="loop_header" attr.type="boolean"/>"#
+            .to_string(),
+        r#"  <key id="cyclic" for="node" attr.name="cyclic" attr.type="boolean"/>"#.to_string(),
+        r#"  <key id="condition" for="edge" attr.name="condition" attr.type="string"/>"#
+            .to_string(),
+        r#"  <key id="back_edge" for="edge" attr.name="back_edge" attr.type="boolean"/>"#
+            .to_string(),
+        r#"  <key id="static_gas" for="node" attr.name="static_gas" attr.type="long"/>"#
+            .to_string(),
+        r#"  <key id="executions" for="node" attr.name="executions" attr.type="long"/>"#
+            .to_string(),
+        r#"  <key id="gas_spent" for="node" attr.name="gas_spent" attr.type="long"/>"#.to_string(),
+        r#"  <graph id="cfg" edgedefault="directed">"#.to_string(),
+    ];
+
+    for node in graph.node_indices() {
+        let instructions = instructions(&graph[node]);
+        lines.push(format!(r#"    <node id="n{}">"#, node.index()));
+        lines.push(format!(
+            r#"      <data key="label">{}</data>"#,
+            escape_xml(graph[node].trim_end())
+        ));
+        if let (Some(first), Some(last)) = (instructions.first(), instructions.last()) {
+            lines.push(format!(r#"      <data key="start_pc">{}</data>"#, first.pc));
+            lines.push(format!(r#"      <data key="end_pc">{}</data>"#, last.pc));
+        }
+        if let Some(dominator) = loops.dominator(node) {
+            lines.push(format!(r#"      <data key="dominator">n{}</data>"#, dominator.index()));
+        }
+        lines.push(format!(r#"      <data key="loop_header">{}</data>"#, loops.is_header(node)));
+        lines.push(format!(r#"      <data key="cyclic">{}</data>"#, loops.is_cyclic(node)));
+        if let Some(block) = gas.map(|gas| gas.block(&graph[node])) {
+            lines.push(format!(r#"      <data key="static_gas">{}</data>"#, block.static_gas));
+            if let (Some(executions), Some(spent)) = (block.executions, block.gas_spent) {
+                lines.push(format!(r#"      <data key="executions">{executions}</data>"#));
+                lines.push(format!(r#"      <data key="gas_spent">{spent}</data>"#));
+            }
+        }
+        lines.push("    </node>".to_string());
+    }
+
+    for (i, edge) in graph.edge_references().enumerate() {
+        lines.push(format!(
+            r#"    <edge id="e{i}" source="n{}" target="n{}">"#,
+            edge.source().index(),
+            edge.target().index()
+        ));
+        lines.push(format!(r#"      <data key="condition">{}</data>"#, condition(edge.weight())));
+        lines.push(format!(
+            r#"      <data key="back_edge">{}</data>"#,
+            loops.is_back_edge(edge.id())
+        ));
+        lines.push("    </edge>".to_string());
+    }
+
+    lines.push("  </graph>".to_string());
+    lines.push("</graphml>".to_string());
+    lines.join("\n")
+}
+
+/// Returns the control flow graph as JSON, listing each block's pc range, instructions and
+/// immediate dominator, the condition under which each edge is taken, and the graph's loops. If
+/// the graph's gas was annotated, each block also lists its gas.
+pub(crate) fn to_json_document(graph: &Graph<String, String>, gas: Option<&GasProfile>) -> Value {
+    let loops = Loops::new(graph);
+    let mut document = json!({
+        "blocks": graph
+            .node_indices()
+            .map(|node| {
+                let instructions = instructions(&graph[node]);
+                json!({
+                    "id": node.index(),
+                    "start_pc": instructions.first().map(|instruction| instruction.pc),
+                    "end_pc": instructions.last().map(|instruction| instruction.pc),
+                    "dominator": loops.dominator(node).map(|dominator| dominator.index()),
+                    "loop_header": loops.is_header(node),
+                    "cyclic": loops.is_cyclic(node),
+                    "instructions": instructions
+                        .iter()
+                        .map(|instruction| json!({
+                            "pc": instruction.pc,
+                            "opcode": instruction.opcode,
+                            "operand": instruction.operand,
+                        }))
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>(),
+        "edges": graph
+            .edge_references()
+            .map(|edge| json!({
+                "from": edge.source().index(),
+                "to": edge.target().index(),
+                "condition": condition(edge.weight()),
+                "back_edge": loops.is_back_edge(edge.id()),
+            }))
+            .collect::<Vec<_>>(),
+        "loops": loops
+            .natural_loops()
+            .iter()
+            .map(|(header, blocks)| json!({
+                "header": header.index(),
+                "blocks": blocks.iter().map(|node| node.index()).collect::<Vec<_>>(),
+            }))
+            .collect::<Vec<_>>(),
+        "components": loops
+            .components()
+            .iter()
+            .map(|component| component.iter().map(|node| node.index()).collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+    });
+
+    // the gas of each block is only included if it was annotated
+    if let (Some(gas), Some(blocks)) = (gas, document["blocks"].as_array_mut()) {
+        for (block, assembly) in blocks.iter_mut().zip(graph.node_weights()) {
+            let annotations = gas.block(assembly);
+            block["static_gas"] = json!(annotations.static_gas);
+            block["executions"] = json!(annotations.executions);
+            block["gas_spent"] = json!(annotations.gas_spent);
+        }
+    }
+    document
+}
+
+/// Returns the control flow graph as a self-contained HTML page for exploring it, embedding the
+/// graph's JSON document in the viewer. If `color_edges` is set, jumps are colored green and
+/// fallthroughs red.
+pub(crate) fn to_html(
+    graph: &Graph<String, String>,
+    color_edges: bool,
+    gas: Option<&GasProfile>,
+) -> String {
+    // the document is embedded in a script, which a `</script>` in it would otherwise end
+    let document = to_json_document(graph, gas).to_string().replace("</", "<\\/");
+    VIEWER
+        .replace("/*CFG_DATA*/", &document)
+        .replace("/*COLOR_EDGES*/", if color_edges { "true" } else { "false" })
+}
+
+/// Escapes the characters which can't appear in XML text or attributes.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<String, String> {
+        let mut graph = Graph::new();
+        let entry = graph.add_node("0x00 PUSH1 0x80\n0x02 PUSH1 0x0a\n0x04 JUMPI \n".to_string());
+        let fallthrough = graph.add_node("0x05 STOP \n".to_string());
+        let target = graph.add_node("0x0a JUMPDEST \n0x0b STOP \n".to_string());
+        graph.add_edge(entry, fallthrough, "false".to_string());
+        graph.add_edge(entry, target, "true".to_string());
+        graph
+    }
+
+    #[test]
+    fn test_json_document() {
+        let document = to_json_document(&graph(), None);
+        assert_eq!(document["blocks"][0]["start_pc"], 0);
+        assert_eq!(document["blocks"][0]["end_pc"], 4);
+        assert_eq!(document["blocks"][0]["instructions"][1]["operand"], "0x0a");
+        assert_eq!(document["blocks"][2]["instructions"][0]["operand"], Value::Null);
+        assert_eq!(document["edges"][1]["condition"], "jump");
+        assert_eq!(document["blocks"][2]["dominator"], 0);
+        assert_eq!(document["loops"], json!([]));
+    }
+
+    #[test]
+    fn test_mermaid_and_graphml() {
+        let mermaid = to_mermaid(&graph(), true, None);
+        assert!(mermaid.starts_with("flowchart TD"));
+        assert!(mermaid.contains("n0[\"0x00 PUSH1 0x80<br/>0x02 PUSH1 0x0a<br/>0x04 JUMPI\"]"));
+        assert!(mermaid.contains("n0 -->|jump| n2"));
+        assert!(mermaid.contains("linkStyle 1 stroke:green"));
+
+        let graphml = to_graphml(&graph(), None);
+        assert!(graphml.contains(r#"<data key="start_pc">10</data>"#));
+        assert!(graphml.contains(r#"<edge id="e0" source="n0" target="n1">"#));
+    }
+
+    #[test]
+    fn test_html() {
+        let html = to_html(&graph(), false, None);
+        assert!(!html.contains("/*CFG_DATA*/"));
+        assert!(html.contains(r#""opcode":"JUMPDEST""#));
+        assert!(html.contains("const COLOR_EDGES = false;"));
+    }
+}
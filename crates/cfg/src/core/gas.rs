@@ -0,0 +1,161 @@
+use alloy::rpc::types::trace::parity::VmTrace;
+use heimdall_vm::core::opcodes::{opcode_name, OpCodeInfo};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+use crate::core::format::instructions;
+
+lazy_static! {
+    /// The minimum gas of each opcode, by its name.
+    static ref MIN_GAS: HashMap<&'static str, u64> = (0..=u8::MAX)
+        .map(|opcode| (opcode_name(opcode), OpCodeInfo::from(opcode).min_gas() as u64))
+        .collect();
+}
+
+/// How often an instruction was executed by a transaction, and the gas it was charged in total.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PcUsage {
+    /// The number of times the instruction was executed
+    pub executions: u64,
+    /// The gas charged for the instruction's executions. The gas charged for a call includes
+    /// the gas it forwards.
+    pub gas: u64,
+}
+
+/// The gas annotations of a graph's blocks: their static gas cost, and the gas a transaction
+/// spent in them if one was profiled with `--tx`.
+#[derive(Debug, Clone, Default)]
+pub struct GasProfile {
+    /// The usage of each pc of the contract by the profiled transaction, if any
+    pub usage: Option<HashMap<u128, PcUsage>>,
+}
+
+/// The gas annotations of a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct BlockGas {
+    /// The sum of the minimum gas of the block's instructions
+    pub(crate) static_gas: u64,
+    /// The number of times the profiled transaction entered the block
+    pub(crate) executions: Option<u64>,
+    /// The gas the profiled transaction spent in the block
+    pub(crate) gas_spent: Option<u64>,
+}
+
+impl BlockGas {
+    /// A short description of the block's gas, e.g. `gas 42, 3 runs, 126 spent`.
+    pub(crate) fn label(&self) -> String {
+        match (self.executions, self.gas_spent) {
+            (Some(executions), Some(spent)) => {
+                format!("gas {}, {executions} runs, {spent} spent", self.static_gas)
+            }
+            _ => format!("gas {}", self.static_gas),
+        }
+    }
+}
+
+impl GasProfile {
+    /// Profiles the gas the trace spent executing the contract's bytecode, in every call which
+    /// executed it.
+    pub fn from_trace(trace: &VmTrace, bytecode: &[u8]) -> Self {
+        let mut usage = HashMap::new();
+        profile_frame(trace, bytecode, &mut usage);
+        Self { usage: Some(usage) }
+    }
+
+    /// The gas annotations of the block with the given assembly.
+    pub(crate) fn block(&self, assembly: &str) -> BlockGas {
+        let instructions = instructions(assembly);
+        let static_gas = instructions
+            .iter()
+            .map(|instruction| MIN_GAS.get(instruction.opcode).copied().unwrap_or_default())
+            .sum();
+        let Some(usage) = &self.usage else {
+            return BlockGas { static_gas, ..Default::default() };
+        };
+
+        let usage_of = |pc| usage.get(&pc).copied().unwrap_or_default();
+        BlockGas {
+            static_gas,
+            executions: Some(
+                instructions
+                    .first()
+                    .map(|instruction| usage_of(instruction.pc).executions)
+                    .unwrap_or_default(),
+            ),
+            gas_spent: Some(
+                instructions.iter().map(|instruction| usage_of(instruction.pc).gas).sum(),
+            ),
+        }
+    }
+
+    /// The most gas the profiled transaction spent in any of the blocks.
+    pub(crate) fn max_spent<'a>(&self, blocks: impl Iterator<Item = &'a String>) -> u64 {
+        blocks.filter_map(|assembly| self.block(assembly).gas_spent).max().unwrap_or_default()
+    }
+}
+
+/// Adds the usage of each instruction of the frame and its sub-calls which executed the bytecode.
+fn profile_frame(trace: &VmTrace, bytecode: &[u8], usage: &mut HashMap<u128, PcUsage>) {
+    let executes_bytecode = trace.code.as_ref() == bytecode;
+    for op in &trace.ops {
+        if executes_bytecode {
+            let pc = usage.entry(op.pc as u128).or_default();
+            pc.executions += 1;
+            pc.gas += op.cost;
+        }
+        if let Some(sub) = &op.sub {
+            profile_frame(sub, bytecode, usage);
+        }
+    }
+}
+
+/// The color of a block which the profiled transaction spent `spent` gas in, from white for
+/// none to red for the most spent in any block.
+pub(crate) fn heat(spent: u64, max: u64) -> String {
+    let ratio = match max {
+        0 => 0.0,
+        _ => spent as f64 / max as f64,
+    };
+    let channel = 178.0f64.mul_add(-ratio, 255.0).round() as u8;
+    format!("#ff{channel:02x}{channel:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{primitives::Bytes, rpc::types::trace::parity::VmInstruction};
+
+    fn op(pc: usize, cost: u64, sub: Option<VmTrace>) -> VmInstruction {
+        VmInstruction { pc, cost, ex: None, sub, op: None, idx: None }
+    }
+
+    #[test]
+    fn test_gas_profile() {
+        let code = Bytes::from_static(&[0x60, 0x01, 0x56]);
+        let trace = VmTrace {
+            code: code.clone(),
+            ops: vec![
+                op(0, 3, None),
+                op(
+                    2,
+                    8,
+                    Some(VmTrace { code: Bytes::from_static(&[0x00]), ops: vec![op(0, 0, None)] }),
+                ),
+                op(0, 3, None),
+            ],
+        };
+
+        let profile = GasProfile::from_trace(&trace, &code);
+        let block = profile.block("0 PUSH1 0x01\n0x02 JUMP \n");
+        assert_eq!(block, BlockGas { static_gas: 11, executions: Some(2), gas_spent: Some(14) });
+        assert_eq!(block.label(), "gas 11, 2 runs, 14 spent");
+        assert_eq!(GasProfile::default().block("0 PUSH1 0x01\n").label(), "gas 3");
+    }
+
+    #[test]
+    fn test_heat() {
+        assert_eq!(heat(0, 10), "#ffffff");
+        assert_eq!(heat(10, 10), "#ff4d4d");
+        assert_eq!(heat(0, 0), "#ffffff");
+    }
+}
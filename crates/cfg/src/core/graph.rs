@@ -6,7 +6,7 @@ use heimdall_vm::{
     ext::exec::VMTrace,
 };
 use petgraph::{matrix_graph::NodeIndex, Graph};
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 /// convert a symbolic execution [`VMTrace`] into a [`Graph`] of blocks, illustrating the
 /// control-flow graph found by the symbolic execution engine.
@@ -15,7 +15,7 @@ pub(crate) fn build_cfg(
     contract_cfg: &mut Graph<String, String>,
     parent_node: Option<NodeIndex<u32>>,
     jump_taken: bool,
-    seen_nodes: &mut HashSet<String>,
+    seen_nodes: &mut HashMap<String, NodeIndex<u32>>,
 ) -> Result<()> {
     let mut cfg_node: String = String::new();
     let mut parent_node = parent_node;
@@ -46,14 +46,17 @@ pub(crate) fn build_cfg(
         cfg_node.push_str(&format!("{}\n", &assembly));
     }
 
-    // check if this node has been seen before
-    if seen_nodes.contains(&cfg_node) {
+    // check if this node has been seen before, in which case the path loops back to it
+    if let Some(node_index) = seen_nodes.get(&cfg_node) {
+        if let Some(parent_node) = parent_node {
+            contract_cfg.update_edge(parent_node, *node_index, jump_taken.to_string());
+        }
         return Ok(());
     }
-    seen_nodes.insert(cfg_node.clone());
 
     // add the node to the graph
-    let node_index = contract_cfg.add_node(cfg_node);
+    let node_index = contract_cfg.add_node(cfg_node.clone());
+    seen_nodes.insert(cfg_node, node_index);
     if let Some(parent_node) = parent_node {
         contract_cfg.update_edge(parent_node, node_index, jump_taken.to_string());
     }
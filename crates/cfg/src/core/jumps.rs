@@ -0,0 +1,432 @@
+use alloy::primitives::U256;
+use heimdall_common::utils::strings::encode_hex_reduced;
+use heimdall_vm::core::opcodes::{
+    opcode_name, OpCodeInfo, ADD, AND, DUP1, DUP16, JUMP, JUMPDEST, JUMPI, PUSH0, PUSH32, SUB,
+    SWAP1, SWAP16,
+};
+use petgraph::{graph::NodeIndex, Graph};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+use crate::core::format::instructions;
+
+/// The most values a stack slot is tracked with before it's considered unknown.
+const MAX_VALUES: usize = 16;
+
+/// The deepest stack which is tracked.
+const MAX_STACK_DEPTH: usize = 1024;
+
+/// The values a stack slot can hold, by the paths reaching it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Values {
+    Known(BTreeSet<U256>),
+    Unknown,
+}
+
+impl Values {
+    fn constant(value: U256) -> Self {
+        Self::Known(BTreeSet::from([value]))
+    }
+
+    /// The values either slot can hold.
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Known(a), Self::Known(b)) => Self::bounded(a.union(b).copied().collect()),
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The values of `op` applied to each pair of the slots' values.
+    fn combine(&self, other: &Self, op: impl Fn(U256, U256) -> U256) -> Self {
+        match (self, other) {
+            (Self::Known(a), Self::Known(b)) if a.len() * b.len() <= MAX_VALUES => {
+                Self::Known(a.iter().flat_map(|a| b.iter().map(|b| op(*a, *b))).collect())
+            }
+            _ => Self::Unknown,
+        }
+    }
+
+    fn bounded(values: BTreeSet<U256>) -> Self {
+        match values.len() > MAX_VALUES {
+            true => Self::Unknown,
+            false => Self::Known(values),
+        }
+    }
+}
+
+/// The values of each stack slot, with the top of the stack last. Slots below the bottom of the
+/// stack are unknown, as the stack may be deeper on some paths.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct Stack(Vec<Values>);
+
+impl Stack {
+    fn push(&mut self, values: Values) {
+        self.0.push(values);
+        if self.0.len() > MAX_STACK_DEPTH {
+            self.0.remove(0);
+        }
+    }
+
+    fn pop(&mut self) -> Values {
+        self.0.pop().unwrap_or(Values::Unknown)
+    }
+
+    /// Makes sure the stack is at least `depth` slots deep, padding its bottom with unknowns.
+    fn reserve(&mut self, depth: usize) {
+        if self.0.len() < depth {
+            let padding = depth - self.0.len();
+            self.0.splice(0..0, std::iter::repeat_n(Values::Unknown, padding));
+        }
+    }
+
+    /// The values of each slot on either of the stacks, aligned at their tops.
+    fn join(&self, other: &Self) -> Self {
+        Self(
+            self.0
+                .iter()
+                .rev()
+                .zip(other.0.iter().rev())
+                .map(|(a, b)| a.join(b))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect(),
+        )
+    }
+}
+
+/// An instruction of the contract's bytecode.
+#[derive(Debug, Clone)]
+pub(crate) struct Instruction {
+    pub(crate) pc: u128,
+    pub(crate) opcode: u8,
+    pub(crate) operand: Option<U256>,
+}
+
+/// A basic block of the contract's bytecode, which starts at a `JUMPDEST` or after a jump or
+/// halt, and ends at the next.
+#[derive(Debug, Clone)]
+struct Block {
+    instructions: Vec<Instruction>,
+    /// Where the block falls through to, if it doesn't jump unconditionally or halt
+    fallthrough: Option<u128>,
+}
+
+impl Block {
+    fn last(&self) -> Option<&Instruction> {
+        self.instructions.last()
+    }
+
+    /// The block's assembly, formatted like the nodes built from the symbolic execution trace.
+    fn assembly(&self) -> String {
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                format!(
+                    "{} {} {}\n",
+                    encode_hex_reduced(U256::from(instruction.pc)),
+                    opcode_name(instruction.opcode),
+                    instruction.operand.map(encode_hex_reduced).unwrap_or_default()
+                )
+            })
+            .collect()
+    }
+}
+
+/// The targets of the contract's jumps, found by value-set analysis of its bytecode. Each stack
+/// slot is tracked as the set of constants it can hold, so computed jumps are resolved when their
+/// target is a label pushed elsewhere, such as the return address of solc's internal functions or
+/// one of the branches of a dispatch table.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct JumpAnalysis {
+    blocks: BTreeMap<u128, Block>,
+    targets: BTreeMap<u128, BTreeSet<u128>>,
+    /// The stack each reachable block is entered with, joined across the paths reaching it
+    entries: HashMap<u128, Stack>,
+}
+
+impl JumpAnalysis {
+    /// Analyzes the jumps of the bytecode, starting from its first instruction.
+    pub(crate) fn new(bytecode: &[u8]) -> Self {
+        let blocks = split_blocks(bytecode);
+        let mut targets: BTreeMap<u128, BTreeSet<u128>> = BTreeMap::new();
+        let mut entries: HashMap<u128, Stack> = HashMap::from([(0, Stack::default())]);
+        let mut queue = VecDeque::from([0]);
+
+        // the stacks only widen, so this reaches a fixed point, but bound it all the same
+        let mut budget = blocks.len().saturating_mul(MAX_VALUES * 4);
+        while let Some(start) = queue.pop_front() {
+            if budget == 0 {
+                break;
+            }
+            budget -= 1;
+
+            let Some(block) = blocks.get(&start) else { continue };
+            let mut stack = entries[&start].clone();
+            let mut successors = Vec::new();
+            for instruction in &block.instructions {
+                if let (JUMP | JUMPI, Values::Known(values)) =
+                    (instruction.opcode, stack.0.last().cloned().unwrap_or(Values::Unknown))
+                {
+                    let resolved = values
+                        .iter()
+                        .filter_map(|value| u128::try_from(*value).ok())
+                        .filter(|target| {
+                            blocks
+                                .get(target)
+                                .and_then(|block| block.instructions.first())
+                                .map(|instruction| instruction.opcode == JUMPDEST) ==
+                                Some(true)
+                        })
+                        .collect::<BTreeSet<_>>();
+                    successors.extend(resolved.iter().copied());
+                    targets.entry(instruction.pc).or_default().extend(resolved);
+                }
+                step(&mut stack, instruction);
+            }
+            successors.extend(block.fallthrough);
+
+            for successor in successors {
+                let joined = match entries.get(&successor) {
+                    Some(entry) => entry.join(&stack),
+                    None => stack.clone(),
+                };
+                if entries.get(&successor) != Some(&joined) {
+                    entries.insert(successor, joined);
+                    if !queue.contains(&successor) {
+                        queue.push_back(successor);
+                    }
+                }
+            }
+        }
+
+        Self { blocks, targets, entries }
+    }
+
+    /// The targets found for the jump at `pc`.
+    pub(crate) fn targets(&self, pc: u128) -> Option<&BTreeSet<u128>> {
+        self.targets.get(&pc)
+    }
+
+    /// The instructions of each block, in the order of their pcs.
+    pub(crate) fn blocks(&self) -> impl Iterator<Item = &[Instruction]> {
+        self.blocks.values().map(|block| block.instructions.as_slice())
+    }
+
+    /// The instructions of the block starting at `start`.
+    pub(crate) fn block(&self, start: u128) -> Option<&[Instruction]> {
+        self.blocks.get(&start).map(|block| block.instructions.as_slice())
+    }
+
+    /// The constants the stack slot `depth` below the top can hold before the instruction at
+    /// `pc` executes. If `local` is set, only the values pushed in the instruction's block are
+    /// known, so a jump's target is only known if the block pushed it.
+    pub(crate) fn values(&self, pc: u128, depth: usize, local: bool) -> Option<BTreeSet<U256>> {
+        let (start, block) = self.blocks.range(..=pc).next_back()?;
+        let mut stack = match local {
+            true => Stack::default(),
+            false => self.entries.get(start)?.clone(),
+        };
+        for instruction in block.instructions.iter().take_while(|instruction| instruction.pc < pc) {
+            step(&mut stack, instruction);
+        }
+
+        let slot = stack.0.len().checked_sub(depth + 1)?;
+        match &stack.0[slot] {
+            Values::Known(values) => Some(values.clone()),
+            Values::Unknown => None,
+        }
+    }
+
+    /// Whether `pc` is the first instruction of a block which starts with a `JUMPDEST`.
+    pub(crate) fn is_label(&self, pc: u128) -> bool {
+        self.block(pc).and_then(|instructions| instructions.first()).map(|first| first.opcode) ==
+            Some(JUMPDEST)
+    }
+
+    /// The blocks the block starting at `start` can continue to, and whether each is reached by
+    /// jumping.
+    pub(crate) fn successors(&self, start: u128) -> Vec<(u128, bool)> {
+        let Some(block) = self.blocks.get(&start) else { return Vec::new() };
+        let jumps = block
+            .last()
+            .filter(|instruction| matches!(instruction.opcode, JUMP | JUMPI))
+            .and_then(|instruction| self.targets(instruction.pc))
+            .into_iter()
+            .flatten()
+            .map(|target| (*target, true));
+        jumps.chain(block.fallthrough.map(|fallthrough| (fallthrough, false))).collect()
+    }
+}
+
+/// Splits the bytecode into its basic blocks, keyed by their first instruction's pc.
+fn split_blocks(bytecode: &[u8]) -> BTreeMap<u128, Block> {
+    let mut blocks = BTreeMap::new();
+    let mut current: Vec<Instruction> = Vec::new();
+    let mut pc = 0;
+
+    while pc < bytecode.len() {
+        let opcode = bytecode[pc];
+        let size = match opcode {
+            PUSH0..=PUSH32 => (opcode - PUSH0) as usize,
+            _ => 0,
+        };
+        let operand = (PUSH0..=PUSH32).contains(&opcode).then(|| {
+            let end = (pc + 1 + size).min(bytecode.len());
+            U256::from_be_slice(&bytecode[pc + 1..end])
+        });
+
+        // a jumpdest starts a new block, which the previous one falls through to
+        if opcode == JUMPDEST && !current.is_empty() {
+            let start = current[0].pc;
+            blocks.insert(start, Block { instructions: current, fallthrough: Some(pc as u128) });
+            current = Vec::new();
+        }
+        current.push(Instruction { pc: pc as u128, opcode, operand });
+        pc += 1 + size;
+
+        let halts = OpCodeInfo::from(opcode).terminating() || opcode == JUMP;
+        if halts || opcode == JUMPI {
+            let start = current[0].pc;
+            let fallthrough = (!halts && pc < bytecode.len()).then_some(pc as u128);
+            blocks.insert(start, Block { instructions: current, fallthrough });
+            current = Vec::new();
+        }
+    }
+
+    if let Some(first) = current.first() {
+        blocks.insert(first.pc, Block { instructions: current, fallthrough: None });
+    }
+
+    blocks
+}
+
+/// Applies the instruction to the values of the stack. Only constants and the instructions
+/// which move or mask them are tracked, while the results of every other instruction are
+/// unknown.
+fn step(stack: &mut Stack, instruction: &Instruction) {
+    let info = OpCodeInfo::from(instruction.opcode);
+    match instruction.opcode {
+        PUSH0..=PUSH32 => stack.push(Values::constant(instruction.operand.unwrap_or_default())),
+        DUP1..=DUP16 => {
+            let depth = (instruction.opcode - DUP1) as usize + 1;
+            stack.reserve(depth);
+            let values = stack.0[stack.0.len() - depth].clone();
+            stack.push(values);
+        }
+        SWAP1..=SWAP16 => {
+            let depth = (instruction.opcode - SWAP1) as usize + 1;
+            stack.reserve(depth + 1);
+            let top = stack.0.len() - 1;
+            stack.0.swap(top, top - depth);
+        }
+        AND | ADD | SUB => {
+            let (a, b) = (stack.pop(), stack.pop());
+            stack.push(match instruction.opcode {
+                AND => a.combine(&b, |a, b| a & b),
+                ADD => a.combine(&b, |a, b| a.wrapping_add(b)),
+                _ => a.combine(&b, |a, b| a.wrapping_sub(b)),
+            });
+        }
+        _ => {
+            for _ in 0..info.inputs() {
+                stack.pop();
+            }
+            for _ in 0..info.outputs() {
+                stack.push(Values::Unknown);
+            }
+        }
+    }
+}
+
+/// Connects the nodes of the graph which end in a jump the symbolic execution couldn't follow to
+/// the targets found by the analysis. Blocks which weren't reached are added to the graph, along
+/// with the blocks they continue to. Returns the number of jumps which were resolved.
+pub(crate) fn resolve_jumps(graph: &mut Graph<String, String>, analysis: &JumpAnalysis) -> usize {
+    let mut nodes: HashMap<u128, NodeIndex> = HashMap::new();
+    let mut dead_ends = Vec::new();
+    for node in graph.node_indices() {
+        let instructions = instructions(&graph[node]);
+        if let Some(first) = instructions.first() {
+            nodes.entry(first.pc).or_insert(node);
+        }
+        if let Some(last) = instructions.last() {
+            if matches!(last.opcode, "JUMP" | "JUMPI") && graph.neighbors(node).next().is_none() {
+                dead_ends.push((node, last.pc));
+            }
+        }
+    }
+
+    let mut resolved = 0;
+    let mut queue = VecDeque::new();
+    for (node, pc) in dead_ends {
+        let Some(targets) = analysis.targets(pc).filter(|targets| !targets.is_empty()) else {
+            continue;
+        };
+        resolved += 1;
+        for target in targets {
+            let target_node = block_node(graph, analysis, &mut nodes, &mut queue, *target);
+            graph.update_edge(node, target_node, true.to_string());
+        }
+    }
+
+    // the blocks which were added continue to blocks which may not be in the graph either
+    while let Some(start) = queue.pop_front() {
+        let node = nodes[&start];
+        for (successor, jump_taken) in analysis.successors(start) {
+            let successor_node = block_node(graph, analysis, &mut nodes, &mut queue, successor);
+            graph.update_edge(node, successor_node, jump_taken.to_string());
+        }
+    }
+
+    resolved
+}
+
+/// The node starting at `start`, adding the block starting there if no node does.
+fn block_node(
+    graph: &mut Graph<String, String>,
+    analysis: &JumpAnalysis,
+    nodes: &mut HashMap<u128, NodeIndex>,
+    queue: &mut VecDeque<u128>,
+    start: u128,
+) -> NodeIndex {
+    *nodes.entry(start).or_insert_with(|| {
+        queue.push_back(start);
+        graph.add_node(analysis.blocks.get(&start).map(Block::assembly).unwrap_or_default())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::strings::decode_hex;
+
+    // an internal function at 0x0d, called from 0x04 and 0x0a, which returns by jumping to the
+    // return address its callers pushed
+    const INTERNAL_CALLS: &str = "6005600d565b600b600d565b005b56";
+
+    #[test]
+    fn test_resolve_internal_function_returns() {
+        let analysis = JumpAnalysis::new(&decode_hex(INTERNAL_CALLS).expect("invalid bytecode"));
+        assert_eq!(analysis.targets(0x04), Some(&BTreeSet::from([0x0d])));
+        assert_eq!(analysis.targets(0x0e), Some(&BTreeSet::from([0x05, 0x0b])));
+    }
+
+    #[test]
+    fn test_resolve_dead_ends() {
+        let analysis = JumpAnalysis::new(&decode_hex(INTERNAL_CALLS).expect("invalid bytecode"));
+        let mut graph = Graph::new();
+        let entry = graph.add_node(
+            "0 PUSH1 0x05\n0x02 PUSH1 0x0d\n0x04 JUMP \n0x0d JUMPDEST \n0x0e JUMP \n".to_string(),
+        );
+
+        assert_eq!(resolve_jumps(&mut graph, &analysis), 1);
+        let successors = graph
+            .neighbors(entry)
+            .map(|node| instructions(&graph[node])[0].pc)
+            .collect::<BTreeSet<_>>();
+        assert_eq!(successors, BTreeSet::from([0x05, 0x0b]));
+
+        // the block at 0x05 calls the function again, which returns to 0x0b
+        assert_eq!(graph.node_count(), 4);
+    }
+}
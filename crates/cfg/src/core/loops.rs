@@ -0,0 +1,159 @@
+use petgraph::{
+    algo::{dominators::simple_fast, tarjan_scc},
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+    Direction, Graph,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// The dominators, loops and cycles of a control flow graph, found from its first node, which
+/// is the contract's entry point.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Loops {
+    /// The immediate dominator of each block reachable from the entry point
+    dominators: HashMap<NodeIndex, NodeIndex>,
+    /// The edges to a block which dominates their source, each closing a natural loop
+    back_edges: HashSet<EdgeIndex>,
+    /// The blocks of each natural loop, keyed by the loop's header
+    loops: BTreeMap<NodeIndex, BTreeSet<NodeIndex>>,
+    /// The strongly connected components which contain a cycle, including irreducible loops
+    /// which have no single header
+    components: Vec<BTreeSet<NodeIndex>>,
+}
+
+impl Loops {
+    /// Finds the loops of the graph.
+    pub(crate) fn new(graph: &Graph<String, String>) -> Self {
+        let Some(root) = graph.node_indices().next() else { return Self::default() };
+
+        let tree = simple_fast(graph, root);
+        let dominators = graph
+            .node_indices()
+            .filter_map(|node| Some((node, tree.immediate_dominator(node)?)))
+            .collect::<HashMap<_, _>>();
+
+        let mut back_edges = HashSet::new();
+        let mut loops: BTreeMap<NodeIndex, BTreeSet<NodeIndex>> = BTreeMap::new();
+        for edge in graph.edge_references() {
+            let dominates = tree
+                .dominators(edge.source())
+                .is_some_and(|mut dominators| dominators.any(|node| node == edge.target()));
+            if dominates {
+                back_edges.insert(edge.id());
+                loops.entry(edge.target()).or_default().extend(natural_loop(
+                    graph,
+                    edge.target(),
+                    edge.source(),
+                ));
+            }
+        }
+
+        let components = tarjan_scc(graph)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || graph.contains_edge(component[0], component[0])
+            })
+            .map(|component| component.into_iter().collect())
+            .collect();
+
+        Self { dominators, back_edges, loops, components }
+    }
+
+    /// The immediate dominator of the block, if it's reachable and isn't the entry point.
+    pub(crate) fn dominator(&self, node: NodeIndex) -> Option<NodeIndex> {
+        self.dominators.get(&node).copied()
+    }
+
+    /// Whether the edge closes a natural loop.
+    pub(crate) fn is_back_edge(&self, edge: EdgeIndex) -> bool {
+        self.back_edges.contains(&edge)
+    }
+
+    /// Whether the block is the header of a natural loop.
+    pub(crate) fn is_header(&self, node: NodeIndex) -> bool {
+        self.loops.contains_key(&node)
+    }
+
+    /// Whether the block is on a cycle, i.e. in a natural or irreducible loop.
+    pub(crate) fn is_cyclic(&self, node: NodeIndex) -> bool {
+        self.components.iter().any(|component| component.contains(&node))
+    }
+
+    /// The blocks of each natural loop, keyed by the loop's header.
+    pub(crate) fn natural_loops(&self) -> &BTreeMap<NodeIndex, BTreeSet<NodeIndex>> {
+        &self.loops
+    }
+
+    /// The strongly connected components which contain a cycle.
+    pub(crate) fn components(&self) -> &[BTreeSet<NodeIndex>] {
+        &self.components
+    }
+}
+
+/// The blocks of the natural loop closed by the edge from `tail` to `header`, which are those
+/// that reach `tail` without passing through `header`.
+fn natural_loop(
+    graph: &Graph<String, String>,
+    header: NodeIndex,
+    tail: NodeIndex,
+) -> BTreeSet<NodeIndex> {
+    let mut blocks = BTreeSet::from([header]);
+    let mut stack = vec![tail];
+    while let Some(node) = stack.pop() {
+        if blocks.insert(node) {
+            stack.extend(graph.neighbors_directed(node, Direction::Incoming));
+        }
+    }
+    blocks
+}
+
+/// Returns the subgraph of the blocks which are on a cycle, and the edges between them, for
+/// finding loops whose bounds an attacker may control.
+pub(crate) fn loops_only(graph: &Graph<String, String>) -> Graph<String, String> {
+    let loops = Loops::new(graph);
+    graph.filter_map(
+        |node, weight| loops.is_cyclic(node).then(|| weight.clone()),
+        |_, weight| Some(weight.clone()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // entry -> header <-> body, header -> exit
+    fn graph() -> (Graph<String, String>, [NodeIndex; 4]) {
+        let mut graph = Graph::new();
+        let entry = graph.add_node("0 PUSH1 0x80\n".to_string());
+        let header = graph.add_node("0x02 JUMPDEST \n".to_string());
+        let body = graph.add_node("0x03 PUSH1 0x02\n0x05 JUMP \n".to_string());
+        let exit = graph.add_node("0x06 STOP \n".to_string());
+        graph.add_edge(entry, header, "true".to_string());
+        graph.add_edge(header, body, "false".to_string());
+        graph.add_edge(body, header, "true".to_string());
+        graph.add_edge(header, exit, "false".to_string());
+        (graph, [entry, header, body, exit])
+    }
+
+    #[test]
+    fn test_loops() {
+        let (graph, [entry, header, body, exit]) = graph();
+        let loops = Loops::new(&graph);
+
+        assert_eq!(loops.dominator(body), Some(header));
+        assert_eq!(loops.dominator(exit), Some(header));
+        assert_eq!(loops.dominator(entry), None);
+        assert!(loops.is_header(header));
+        assert_eq!(loops.natural_loops()[&header], BTreeSet::from([header, body]));
+        let back_edge = graph.find_edge(body, header).expect("no back edge");
+        assert!(loops.is_back_edge(back_edge));
+        assert_eq!(graph.edge_indices().filter(|edge| loops.is_back_edge(*edge)).count(), 1);
+    }
+
+    #[test]
+    fn test_loops_only() {
+        let subgraph = loops_only(&graph().0);
+        assert_eq!(subgraph.node_count(), 2);
+        assert_eq!(subgraph.edge_count(), 2);
+    }
+}
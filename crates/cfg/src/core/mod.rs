@@ -1,53 +1,268 @@
+pub(crate) mod calls;
+pub(crate) mod diff;
+pub(crate) mod eof;
+pub(crate) mod format;
+pub(crate) mod gas;
 pub(crate) mod graph;
+pub(crate) mod jumps;
+pub(crate) mod loops;
 
-use alloy::primitives::Address;
+use alloy::primitives::{keccak256, Address};
 use eyre::eyre;
-use heimdall_common::{ether::compiler::detect_compiler, utils::strings::StringExt};
-use heimdall_vm::core::vm::VM;
-use std::collections::HashSet;
+use heimdall_common::{
+    ether::{compiler::detect_compiler, rpc::get_vm_trace},
+    utils::{
+        metrics::record_phase,
+        strings::{encode_hex, StringExt},
+    },
+};
+use heimdall_disassembler::{disassemble, DisassemblerArgsBuilder};
+use heimdall_vm::{
+    core::{eof::EofContainer, vm::VM},
+    ext::selectors::find_function_selectors,
+};
+use std::collections::{BTreeMap, HashMap};
 
-use petgraph::{dot::Dot, Graph};
+use petgraph::{
+    dot::{Config, Dot},
+    visit::EdgeRef,
+    Graph,
+};
+use serde_json::{json, Value};
 use std::time::{Duration, Instant};
 
-use super::CfgArgs;
+pub use calls::{CallGraph, CallNode};
+pub use diff::{cfg_diff, BlockDiff, BlockStatus, CfgDiff};
+pub use gas::{GasProfile, PcUsage};
 
-use crate::{core::graph::build_cfg, error::Error};
-use tracing::{debug, info};
+use super::{CfgArgs, CfgFormat};
+
+use crate::{
+    core::{
+        calls::call_graph,
+        eof::build_eof_cfg,
+        graph::build_cfg,
+        jumps::{resolve_jumps, JumpAnalysis},
+        loops::{loops_only, Loops},
+    },
+    error::Error,
+};
+use tracing::{debug, info, warn};
 
 /// The result of the cfg command. Contains the generated control flow graph.
 #[derive(Debug, Clone)]
 pub struct CfgResult {
-    /// The generated control flow graph of the contract.
+    /// The generated control flow graph of the contract, or only of the blocks reachable from
+    /// the function given with `--function`.
     pub graph: Graph<String, String>,
+    /// The control flow graph of each function, keyed by its selector, if generated with
+    /// `--per-function`.
+    pub functions: BTreeMap<String, Graph<String, String>>,
+    /// The gas annotations of the blocks, if requested with `--gas` or `--tx`.
+    pub gas: Option<GasProfile>,
+    /// The call graph of the contract, if extracted with `--call-graph`.
+    pub call_graph: Option<CallGraph>,
 }
 
 impl CfgResult {
     /// Returns the control flow graph as a graphviz formatted string.
     pub fn as_dot(&self, color_edges: bool) -> String {
-        let output = format!("{}", Dot::with_config(&self.graph, &[]));
+        graph_as_dot(&self.graph, color_edges, self.gas.as_ref())
+    }
 
-        // find regex matches and replace
-        let mut output = output.replace(
-            "digraph {",
-            "digraph G {\n    node [shape=box, style=\"rounded\", fontname=\"Helvetica\"];\n    edge [fontname=\"Helvetica\"];"
-        );
+    /// Returns the control flow graph of each function as a graphviz formatted string, keyed by
+    /// the function's selector.
+    pub fn functions_as_dot(&self, color_edges: bool) -> BTreeMap<String, String> {
+        self.functions
+            .iter()
+            .map(|(selector, graph)| {
+                (selector.clone(), graph_as_dot(graph, color_edges, self.gas.as_ref()))
+            })
+            .collect()
+    }
 
-        if color_edges {
-            // replace edge labels with colors
-            output = output.replace("[ label = \"true\" ]", "[ color = \"green\" ]");
-            output = output.replace("[ label = \"false\" ]", "[ color = \"red\" ]");
-        } else {
-            // remove edge labels
-            output = output.replace("[ label = \"true\" ]", "[]");
-            output = output.replace("[ label = \"false\" ]", "[]");
+    /// Keeps only the blocks of each graph which are on a loop, if `enabled` is set.
+    fn filter_loops(self, enabled: bool) -> Self {
+        match enabled {
+            true => Self {
+                graph: loops_only(&self.graph),
+                functions: self
+                    .functions
+                    .iter()
+                    .map(|(selector, graph)| (selector.clone(), loops_only(graph)))
+                    .collect(),
+                gas: self.gas,
+                call_graph: self.call_graph,
+            },
+            false => self,
         }
+    }
+
+    /// Returns the control flow graph rendered in the given format.
+    pub fn render(&self, format: CfgFormat, color_edges: bool) -> String {
+        render_graph(&self.graph, format, color_edges, self.gas.as_ref())
+    }
+
+    /// Returns the control flow graph of each function rendered in the given format, keyed by
+    /// the function's selector.
+    pub fn render_functions(
+        &self,
+        format: CfgFormat,
+        color_edges: bool,
+    ) -> BTreeMap<String, String> {
+        self.functions
+            .iter()
+            .map(|(selector, graph)| {
+                (selector.clone(), render_graph(graph, format, color_edges, self.gas.as_ref()))
+            })
+            .collect()
+    }
+
+    /// Returns the control flow graph as JSON, listing its nodes, i.e. the assembly of each basic
+    /// block, and the edges between them, which refer to nodes by their index. The graph of each
+    /// function is included if they were generated, the gas of each node if it was annotated, and
+    /// the call graph if it was extracted.
+    pub fn to_json(&self) -> Value {
+        let mut graph = graph_to_json(&self.graph, self.gas.as_ref());
+        if !self.functions.is_empty() {
+            graph["functions"] = self
+                .functions
+                .iter()
+                .map(|(selector, graph)| {
+                    (selector.clone(), graph_to_json(graph, self.gas.as_ref()))
+                })
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        if let Some(call_graph) = &self.call_graph {
+            graph["call_graph"] = call_graph.to_json();
+        }
+        graph
+    }
+}
+
+/// Returns the control flow graph rendered in the given format.
+fn render_graph(
+    graph: &Graph<String, String>,
+    format: CfgFormat,
+    color_edges: bool,
+    gas: Option<&GasProfile>,
+) -> String {
+    match format {
+        CfgFormat::Dot => graph_as_dot(graph, color_edges, gas),
+        CfgFormat::Mermaid => format::to_mermaid(graph, color_edges, gas),
+        CfgFormat::GraphMl => format::to_graphml(graph, gas),
+        CfgFormat::Json => format!("{:#}", format::to_json_document(graph, gas)),
+        CfgFormat::Html => format::to_html(graph, color_edges, gas),
+    }
+}
 
-        output = output.replace("[ label = \"\" ]", "[]");
+/// Returns the control flow graph as a graphviz formatted string. The blocks of loops are
+/// filled, and the back edges closing them are dashed. If the graph's gas was annotated, each
+/// block is labeled with its gas, and filled by the gas spent in it if a transaction was profiled.
+fn graph_as_dot(
+    graph: &Graph<String, String>,
+    color_edges: bool,
+    gas: Option<&GasProfile>,
+) -> String {
+    let loops = Loops::new(graph);
+    let max_spent = gas.map(|gas| gas.max_spent(graph.node_weights())).unwrap_or_default();
+    let output = format!(
+        "{}",
+        Dot::with_attr_getters(
+            graph,
+            &[Config::EdgeNoLabel],
+            &|_, edge| {
+                let mut attributes = edge_color(edge.weight(), color_edges).to_string();
+                if loops.is_back_edge(edge.id()) {
+                    attributes.push_str("style = \"dashed\" ");
+                }
+                attributes
+            },
+            &|_, (node, assembly)| {
+                let block = gas.map(|gas| gas.block(assembly));
+                let fill = match (block.and_then(|block| block.gas_spent), loops.is_header(node)) {
+                    (Some(spent), _) => Some(gas::heat(spent, max_spent)),
+                    (None, true) => Some("#ffe08a".to_string()),
+                    (None, false) if loops.is_cyclic(node) => Some("#fff3c4".to_string()),
+                    _ => None,
+                };
+                let xlabel = [
+                    loops.is_header(node).then(|| "loop header".to_string()),
+                    block.map(|block| block.label()),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ");
+
+                let mut attributes = String::new();
+                if let Some(fill) = fill {
+                    attributes
+                        .push_str(&format!("style = \"rounded,filled\" fillcolor = \"{fill}\" "));
+                }
+                if !xlabel.is_empty() {
+                    attributes.push_str(&format!("xlabel = \"{xlabel}\" "));
+                }
+                attributes
+            },
+        )
+    );
+
+    style_dot(&output)
+}
 
-        output
+/// The dot attribute coloring an edge by its label, if `color_edges` is set.
+fn edge_color(label: &str, color_edges: bool) -> &'static str {
+    match (color_edges, label) {
+        (true, "true") => "color = \"green\" ",
+        (true, "false") => "color = \"red\" ",
+        _ => "",
     }
 }
 
+/// Applies the graph's default styles to a dot rendered by petgraph, and cleans up the empty
+/// attribute lists of its edges.
+fn style_dot(output: &str) -> String {
+    output
+        .replace(
+            "digraph {",
+            "digraph G {\n    node [shape=box, style=\"rounded\", fontname=\"Helvetica\"];\n    edge [fontname=\"Helvetica\"];"
+        )
+        .replace("[ ]", "[]")
+}
+
+/// Returns the control flow graph as JSON, listing its nodes and the edges between them, and the
+/// gas of each node if it was annotated.
+fn graph_to_json(graph: &Graph<String, String>, gas: Option<&GasProfile>) -> Value {
+    let mut document = json!({
+        "nodes": graph.node_weights().collect::<Vec<_>>(),
+        "edges": graph
+            .edge_references()
+            .map(|edge| json!({
+                "from": edge.source().index(),
+                "to": edge.target().index(),
+                "label": edge.weight(),
+            }))
+            .collect::<Vec<_>>(),
+    });
+    if let Some(gas) = gas {
+        document["gas"] = graph
+            .node_weights()
+            .map(|assembly| {
+                let block = gas.block(assembly);
+                json!({
+                    "static_gas": block.static_gas,
+                    "executions": block.executions,
+                    "gas_spent": block.gas_spent,
+                })
+            })
+            .collect::<Vec<_>>()
+            .into();
+    }
+    document
+}
+
 /// Generates a control flow graph for the target contract.
 pub async fn cfg(args: CfgArgs) -> Result<CfgResult, Error> {
     // init
@@ -65,6 +280,7 @@ pub async fn cfg(args: CfgArgs) -> Result<CfgResult, Error> {
         .await
         .map_err(|e| Error::FetchError(format!("fetching target bytecode failed: {e}")))?;
     debug!("fetching target bytecode took {:?}", start_fetch_time.elapsed());
+    record_phase("cfg.fetch_bytecode", start_fetch_time.elapsed());
 
     if contract_bytecode.is_empty() {
         return Err(Error::Eyre(eyre!("contract bytecode is empty")));
@@ -73,6 +289,37 @@ pub async fn cfg(args: CfgArgs) -> Result<CfgResult, Error> {
     // perform versioning and compiler heuristics
     let (_compiler, _version) = detect_compiler(&contract_bytecode);
 
+    // annotate the blocks' gas, profiling the transaction's gas in them if one was given
+    let gas = match (&args.tx, args.gas) {
+        (Some(tx), _) => {
+            info!("profiling the gas of transaction '{}'", tx);
+            let trace = get_vm_trace(tx, &args.rpc_url).await.map_err(|e| {
+                Error::FetchError(format!("fetching transaction trace failed: {e}"))
+            })?;
+            Some(GasProfile::from_trace(&trace, &contract_bytecode))
+        }
+        (None, true) => Some(GasProfile::default()),
+        (None, false) => None,
+    };
+
+    // an EOF container's jumps are static, so its graph is built without executing it
+    if let Ok(container) = EofContainer::parse(&contract_bytecode) {
+        if args.function.is_some() || args.per_function || args.call_graph {
+            warn!("--function, --per-function and --call-graph are ignored for EOF containers");
+        }
+        info!(
+            "building cfg for the {} code sections of EOF container",
+            container.code_sections.len()
+        );
+        let graph = build_eof_cfg(&container);
+        debug!("cfg generated in {:?}", start_time.elapsed());
+        record_phase("cfg", start_time.elapsed());
+        info!("generated cfg successfully");
+
+        return Ok(CfgResult { graph, functions: BTreeMap::new(), gas, call_graph: None }
+            .filter_loops(args.loops_only));
+    }
+
     // create a new EVM instance. we will use this for finding function selectors,
     // performing symbolic execution, and more.
     let mut evm = VM::new(
@@ -86,6 +333,60 @@ pub async fn cfg(args: CfgArgs) -> Result<CfgResult, Error> {
     )
     .with_hardfork(hardfork);
 
+    // find the function selectors, if graphs of functions or calls were requested
+    let selectors = match args.function.is_some() || args.per_function || args.call_graph {
+        true => {
+            let assembly = disassemble(
+                DisassemblerArgsBuilder::new()
+                    .target(encode_hex(&contract_bytecode))
+                    .hardfork(hardfork)
+                    .build()
+                    .expect("impossible case: failed to build disassembly arguments"),
+            )
+            .await
+            .map_err(|e| Error::Eyre(eyre!("disassembling contract bytecode failed: {}", e)))?;
+            find_function_selectors(&evm, &assembly)
+        }
+        false => Default::default(),
+    };
+
+    // resolve the targets of computed jumps, which the symbolic execution may not follow
+    let start_analysis_time = Instant::now();
+    let jumps = JumpAnalysis::new(&contract_bytecode);
+    debug!("jump analysis took {:?}", start_analysis_time.elapsed());
+
+    // extract the calls between the functions, if requested
+    let call_graph = args.call_graph.then(|| {
+        let mut functions = selectors
+            .iter()
+            .map(|(selector, entry)| (format!("0x{}", normalize_selector(selector)), *entry))
+            .collect::<Vec<_>>();
+        functions.sort();
+        let call_graph = call_graph(&jumps, &functions);
+        info!("extracted call graph with {} functions", call_graph.graph.node_count());
+        call_graph
+    });
+
+    // only graph the blocks reachable from the function's dispatch branch, if one was given
+    if let Some(function) = &args.function {
+        let wanted = normalize_selector(function);
+        let (selector, entry_point) = selectors
+            .iter()
+            .find(|(selector, _)| normalize_selector(selector) == wanted)
+            .ok_or_else(|| {
+                Error::Eyre(eyre!("no function with selector '0x{}' was found", wanted))
+            })?;
+
+        info!("building cfg for function '0x{}'", wanted);
+        let graph = build_function_cfg(&mut evm, &jumps, selector, *entry_point, args.timeout)?;
+        debug!("cfg generated in {:?}", start_time.elapsed());
+        record_phase("cfg", start_time.elapsed());
+        info!("generated cfg successfully");
+
+        return Ok(CfgResult { graph, functions: BTreeMap::new(), gas, call_graph }
+            .filter_loops(args.loops_only));
+    }
+
     info!("performing symbolic execution on '{}'", args.target.truncate(64));
     let start_sym_exec_time = Instant::now();
     let (map, jumpdest_count) = evm
@@ -98,18 +399,71 @@ pub async fn cfg(args: CfgArgs) -> Result<CfgResult, Error> {
 
     debug!("'{}' has {} unique branches", args.target.truncate(64), jumpdest_count);
     debug!("symbolic execution took {:?}", start_sym_exec_time.elapsed());
+    record_phase("cfg.symbolic_execution", start_sym_exec_time.elapsed());
     info!("symbolically executed '{}'", args.target.truncate(64));
 
     // run cfg generation
     let start_cfg_time = Instant::now();
     info!("building cfg for '{}' from symbolic execution trace", args.target.truncate(64));
     let mut contract_cfg = Graph::new();
-    let mut seen_nodes: HashSet<String> = HashSet::new();
+    let mut seen_nodes = HashMap::new();
     build_cfg(&map, &mut contract_cfg, None, false, &mut seen_nodes)?;
+    let resolved = resolve_jumps(&mut contract_cfg, &jumps);
+    debug!("resolved {} jumps symbolic execution couldn't follow", resolved);
     debug!("building cfg took {:?}", start_cfg_time.elapsed());
 
+    // build a separate graph for each function, if requested
+    let mut functions = BTreeMap::new();
+    for (selector, entry_point) in selectors.into_iter().filter(|_| args.per_function) {
+        match build_function_cfg(&mut evm, &jumps, &selector, entry_point, args.timeout) {
+            Ok(graph) => {
+                functions.insert(format!("0x{}", normalize_selector(&selector)), graph);
+            }
+            Err(e) => warn!("failed to build cfg for function '{}': {}", selector, e),
+        }
+    }
+    if args.per_function {
+        info!("built cfgs for {} functions", functions.len());
+    }
+
     debug!("cfg generated in {:?}", start_time.elapsed());
+    record_phase("cfg", start_time.elapsed());
     info!("generated cfg successfully");
 
-    Ok(CfgResult { graph: contract_cfg })
+    Ok(CfgResult { graph: contract_cfg, functions, gas, call_graph }.filter_loops(args.loops_only))
+}
+
+/// Symbolically executes the function with the given selector from its entry point, building
+/// the graph of the blocks reachable from its dispatch branch, and connecting the jumps it
+/// couldn't follow to their resolved targets.
+fn build_function_cfg(
+    evm: &mut VM,
+    jumps: &JumpAnalysis,
+    selector: &str,
+    entry_point: u128,
+    timeout: u64,
+) -> Result<Graph<String, String>, Error> {
+    evm.reset();
+    let (map, jumpdest_count) = evm
+        .symbolic_exec_selector(
+            selector,
+            entry_point,
+            Instant::now().checked_add(Duration::from_millis(timeout)).expect("invalid timeout"),
+        )
+        .map_err(|e| Error::Eyre(eyre!("symbolic execution failed: {}", e)))?;
+    debug!("'{}' has {} unique branches", selector, jumpdest_count);
+
+    let mut function_cfg = Graph::new();
+    build_cfg(&map, &mut function_cfg, None, false, &mut HashMap::new())?;
+    resolve_jumps(&mut function_cfg, jumps);
+    Ok(function_cfg)
+}
+
+/// Normalizes a selector to lowercase hex without a `0x` prefix. A text signature, e.g.
+/// `transfer(address,uint256)`, is normalized to its selector.
+fn normalize_selector(function: &str) -> String {
+    match function.contains('(') {
+        true => encode_hex(&keccak256(function.trim().as_bytes())[..4]),
+        false => function.trim().trim_start_matches("0x").to_lowercase(),
+    }
 }
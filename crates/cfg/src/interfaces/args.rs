@@ -1,5 +1,5 @@
 use alloy::primitives::Address;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use derive_builder::Builder;
 use eyre::Result;
 use heimdall_common::ether::bytecode::get_bytecode_from_target;
@@ -11,15 +11,16 @@ use heimdall_vm::core::hardfork::HardFork;
 #[clap(
     about = "Generate a visual control flow graph for EVM bytecode",
     after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
-    override_usage = "heimdall cfg <TARGET> [OPTIONS]"
+    override_usage = "heimdall cfg <TARGET> [OPTIONS]\n       heimdall cfg --diff <OLD> <NEW> [OPTIONS]"
 )]
 pub struct CfgArgs {
     /// The target to generate a Cfg for, either a file, bytecode, contract address, or ENS name.
-    #[clap(required = true)]
+    #[clap(required_unless_present = "diff", default_value = "", hide_default_value = true)]
     pub target: String,
 
     /// The RPC provider to use for fetching target bytecode.
-    /// This can be an explicit URL or a reference to a MESC endpoint.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
     #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
     pub rpc_url: String,
 
@@ -32,7 +33,8 @@ pub struct CfgArgs {
     #[clap(long = "color-edges", short)]
     pub color_edges: bool,
 
-    /// The output directory to write the output to or 'print' to print to the console
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
     #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
     pub output: String,
 
@@ -52,6 +54,76 @@ pub struct CfgArgs {
     /// Etherscan API key for fetching contract creation block when using auto hardfork detection.
     #[clap(long, short = 'e', default_value = "", hide_default_value = true)]
     pub etherscan_api_key: String,
+
+    /// Only graph the blocks reachable from the dispatch branch of the function with this
+    /// selector, e.g. '0xa9059cbb', or signature, e.g. 'transfer(address,uint256)'.
+    #[clap(long, value_name = "SELECTOR", conflicts_with = "per_function")]
+    pub function: Option<String>,
+
+    /// Also generate a graph of each function's blocks, which are written to a 'cfg' directory
+    /// beside the whole contract's graph.
+    #[clap(long = "per-function")]
+    pub per_function: bool,
+
+    /// Only graph the blocks which are on a loop, for finding loops whose bounds may be
+    /// controlled by callers.
+    #[clap(long = "loops-only")]
+    pub loops_only: bool,
+
+    /// Diff the graphs of two targets, e.g. two versions of a proxy's implementation, rendering
+    /// the blocks which were changed, added or removed in distinct colors.
+    #[clap(long, num_args = 2, value_names = ["OLD", "NEW"], conflicts_with_all = ["target", "function", "per_function"])]
+    pub diff: Vec<String>,
+
+    /// Annotate each block with its static gas cost, the sum of its instructions' minimum gas.
+    #[clap(long)]
+    pub gas: bool,
+
+    /// Profile the gas spent in each block by the given transaction, labeling the blocks with
+    /// their executions and the gas spent in them, and heat-mapping the graph. Implies `--gas`.
+    #[clap(long, value_name = "HASH", conflicts_with = "diff")]
+    pub tx: Option<String>,
+
+    /// Also extract the call graph of the contract: its public functions, the internal functions
+    /// they call, and the external calls each makes, with their targets when they're constants.
+    /// The call graph is written to a 'call-graph' file beside the contract's graph.
+    #[clap(long = "call-graph", conflicts_with = "diff")]
+    pub call_graph: bool,
+
+    /// The format to write the graph in.
+    #[clap(long, value_enum, default_value = "dot")]
+    pub format: CfgFormat,
+}
+
+/// The formats the control flow graph can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CfgFormat {
+    /// A graphviz digraph.
+    Dot,
+    /// A mermaid flowchart, which can be embedded in Markdown.
+    Mermaid,
+    /// A GraphML document, which can be imported into Gephi or yEd.
+    #[clap(name = "graphml")]
+    GraphMl,
+    /// A JSON document listing each block's pc range and instructions, and the condition under
+    /// which each edge is taken.
+    Json,
+    /// A self-contained HTML page for exploring the graph, with pan and zoom, block search by pc
+    /// or opcode, and expandable instruction listings.
+    Html,
+}
+
+impl CfgFormat {
+    /// The extension of files written in the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CfgFormat::Dot => "dot",
+            CfgFormat::Mermaid => "mmd",
+            CfgFormat::GraphMl => "graphml",
+            CfgFormat::Json => "json",
+            CfgFormat::Html => "html",
+        }
+    }
 }
 
 impl CfgArgs {
@@ -122,6 +194,14 @@ impl CfgArgsBuilder {
             timeout: Some(10000),
             hardfork: Some(HardFork::Latest),
             etherscan_api_key: Some(String::new()),
+            function: Some(None),
+            per_function: Some(false),
+            loops_only: Some(false),
+            diff: Some(Vec::new()),
+            gas: Some(false),
+            tx: Some(None),
+            call_graph: Some(false),
+            format: Some(CfgFormat::Dot),
         }
     }
 }
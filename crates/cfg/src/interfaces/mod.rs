@@ -1,4 +1,4 @@
 mod args;
 
 // re-export the public interface
-pub use args::{CfgArgs, CfgArgsBuilder};
+pub use args::{CfgArgs, CfgArgsBuilder, CfgFormat};
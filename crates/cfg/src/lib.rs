@@ -7,7 +7,10 @@ mod core;
 mod interfaces;
 
 // re-export the public interface
-pub use core::{cfg, CfgResult};
+pub use core::{
+    cfg, cfg_diff, BlockDiff, BlockStatus, CallGraph, CallNode, CfgDiff, CfgResult, GasProfile,
+    PcUsage,
+};
 pub use error::Error;
 pub use heimdall_vm::core::hardfork::HardFork;
-pub use interfaces::{CfgArgs, CfgArgsBuilder};
+pub use interfaces::{CfgArgs, CfgArgsBuilder, CfgFormat};
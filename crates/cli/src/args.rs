@@ -1,13 +1,29 @@
 use clap::{Parser, Subcommand};
 
 use clap::{ArgAction, Args, ValueEnum};
+use eyre::Result;
 use heimdall_cache::CacheArgs;
+use heimdall_common::ether::{
+    chains::{lookup_chain, Chain},
+    fixture::{RecordingProviderFactory, ReplayProviderFactory},
+    fork::{set_default_fork, Fork},
+    labels::LabelsArgs,
+    multicall::set_default_multicall,
+    provider::{parse_rpc_header, set_default_headers, set_provider_factory},
+    rpc::{set_rate_limit, set_retry_policy, RetryPolicy},
+    sigdb::SigsArgs,
+    signatures::DEFAULT_SIGNATURE_CONCURRENCY,
+};
 use heimdall_config::ConfigArgs;
 use heimdall_core::{
-    heimdall_cfg::CfgArgs, heimdall_decoder::DecodeArgs, heimdall_decompiler::DecompilerArgs,
-    heimdall_disassembler::DisassemblerArgs, heimdall_dump::DumpArgs,
+    heimdall_cfg::CfgArgs,
+    heimdall_decoder::DecodeArgs,
+    heimdall_decompiler::{DecompilerArgs, DiffArgs},
+    heimdall_disassembler::{AssemblerArgs, DisassemblerArgs, SimilarArgs},
+    heimdall_dump::{DumpArgs, SnapshotTokenArgs},
     heimdall_inspect::InspectArgs,
 };
+use heimdall_server::ServeArgs;
 use heimdall_tracing::{
     tracing_subscriber::filter::Directive, FileWorkerGuard, HeimdallTracer, LayerInfo, LogFormat,
     Tracer,
@@ -15,17 +31,54 @@ use heimdall_tracing::{
 use std::{
     fmt::{self, Display},
     str::FromStr,
+    sync::Arc,
+    time::Duration,
 };
-use tracing::{level_filters::LevelFilter, Level};
+use tracing::{error, level_filters::LevelFilter, Level};
+
+use crate::json::is_json_output;
 
 #[derive(Debug, Parser)]
-#[clap(name = "heimdall", author = "Jonathan Becker <jonathan@jbecker.dev>", version)]
+#[clap(
+    name = "heimdall",
+    author = "Jonathan Becker <jonathan@jbecker.dev>",
+    version,
+    about = "A toolkit for EVM bytecode analysis"
+)]
 pub(crate) struct Arguments {
     #[clap(subcommand)]
     pub sub: Subcommands,
 
     #[clap(flatten)]
     pub logs: LogArgs,
+
+    #[clap(flatten)]
+    pub rpc: RpcArgs,
+
+    #[clap(flatten)]
+    pub fork: ForkArgs,
+
+    /// Disable all network access. Targets must be local bytecode or calldata, and only
+    /// previously cached rpc responses and signatures are used.
+    #[clap(long, global = true)]
+    pub offline: bool,
+
+    /// Write metrics describing this run, such as rpc request counts and latencies, cache hit
+    /// rates, and the time spent in each module phase, to the given file in the Prometheus text
+    /// format.
+    #[clap(long = "metrics-file", value_name = "PATH", global = true)]
+    pub metrics_file: Option<String>,
+
+    /// Resolve selectors with the given signature lookup service instead of openchain's. The
+    /// service must implement openchain's lookup API, i.e. respond to
+    /// `GET <URL>?function=0x<selector>` and `GET <URL>?event=0x<selector>`. Previously cached
+    /// resolutions are still used; run `heimdall cache clean` to resolve them again.
+    #[clap(long = "sig-resolver-url", value_name = "URL", global = true)]
+    pub sig_resolver_url: Option<String>,
+
+    /// The maximum number of selectors to resolve concurrently.
+    #[clap(long = "sig-concurrency", value_name = "N", default_value_t = DEFAULT_SIGNATURE_CONCURRENCY, global = true)]
+    pub sig_concurrency: usize,
 }
 
 #[derive(Debug, Subcommand)]
@@ -38,9 +91,21 @@ pub(crate) enum Subcommands {
     #[clap(name = "disassemble", about = "Disassemble EVM bytecode to assembly")]
     Disassemble(DisassemblerArgs),
 
+    #[clap(name = "assemble", about = "Assemble disassembled EVM bytecode back into bytecode")]
+    Assemble(AssemblerArgs),
+
+    #[clap(
+        name = "similar",
+        about = "Find the bytecodes in a local corpus which are most similar to a contract"
+    )]
+    Similar(SimilarArgs),
+
     #[clap(name = "decompile", about = "Decompile EVM bytecode to Solidity")]
     Decompile(DecompilerArgs),
 
+    #[clap(name = "diff", about = "Decompile two contracts and report how their functions differ")]
+    Diff(DiffArgs),
+
     #[clap(name = "cfg", about = "Generate a visual control flow graph for EVM bytecode")]
     Cfg(CfgArgs),
 
@@ -53,14 +118,67 @@ pub(crate) enum Subcommands {
     #[clap(name = "cache", about = "Manage heimdall-rs' cached files")]
     Cache(CacheArgs),
 
+    #[clap(name = "sigs", about = "Import and export heimdall-rs' local signature database")]
+    Sigs(SigsArgs),
+
+    #[clap(name = "labels", about = "Add, import and list the labels shown for addresses")]
+    Labels(LabelsArgs),
+
     #[clap(name = "dump", about = "Dump the value of all storage slots accessed by a contract")]
     Dump(DumpArgs),
 
+    #[clap(
+        name = "snapshot-token",
+        about = "Snapshot the balances of an ERC-20, or the owners of an ERC-721's tokens, at a block"
+    )]
+    SnapshotToken(SnapshotTokenArgs),
+
     #[clap(
         name = "inspect",
         about = "Detailed inspection of Ethereum transactions, including calldata & trace decoding, log visualization, and more"
     )]
     Inspect(InspectArgs),
+
+    #[clap(
+        name = "serve",
+        about = "Serve heimdall's core operations over HTTP, with JSON responses"
+    )]
+    Serve(ServeArgs),
+}
+
+impl Subcommands {
+    /// Whether the subcommand prints machine-readable JSON to stdout, i.e. with `--output json`,
+    /// or when decoding a `--batch` of targets without an output directory.
+    pub(crate) fn prints_json(&self) -> bool {
+        match self {
+            Subcommands::Decode(cmd) if cmd.batch.is_some() => {
+                cmd.output == "print" || is_json_output(&cmd.output)
+            }
+            _ => self.output().is_some_and(is_json_output),
+        }
+    }
+
+    /// The `--output` given to the subcommand, if it takes one.
+    pub(crate) fn output(&self) -> Option<&str> {
+        let output = match self {
+            Subcommands::Disassemble(cmd) => &cmd.output,
+            Subcommands::Assemble(cmd) => &cmd.output,
+            Subcommands::Similar(cmd) => &cmd.output,
+            Subcommands::Decompile(cmd) => &cmd.output,
+            Subcommands::Diff(cmd) => &cmd.output,
+            Subcommands::Cfg(cmd) => &cmd.output,
+            Subcommands::Decode(cmd) => &cmd.output,
+            Subcommands::Dump(cmd) => &cmd.output,
+            Subcommands::SnapshotToken(cmd) => &cmd.output,
+            Subcommands::Inspect(cmd) => &cmd.output,
+            Subcommands::Serve(_) |
+            Subcommands::Config(_) |
+            Subcommands::Cache(_) |
+            Subcommands::Sigs(_) |
+            Subcommands::Labels(_) => return None,
+        };
+        Some(output.as_str())
+    }
 }
 
 /// The log configuration.
@@ -114,11 +232,23 @@ impl LogArgs {
         )
     }
 
-    /// Initializes tracing with the configured options from cli args.
-    pub(crate) fn init_tracing(&self) -> eyre::Result<Option<FileWorkerGuard>> {
+    /// Initializes tracing with the configured options from cli args. If `silence_stdout`, no
+    /// logs are written to stdout, e.g. so that it only contains json output.
+    pub(crate) fn init_tracing(
+        &self,
+        silence_stdout: bool,
+    ) -> eyre::Result<Option<FileWorkerGuard>> {
         let mut tracer = HeimdallTracer::new();
 
-        let stdout = self.layer(self.log_stdout_format, self.log_stdout_filter.clone(), true);
+        let stdout = match silence_stdout {
+            true => LayerInfo::new(
+                self.log_stdout_format,
+                LevelFilter::OFF.to_string(),
+                String::new(),
+                None,
+            ),
+            false => self.layer(self.log_stdout_format, self.log_stdout_filter.clone(), true),
+        };
         tracer = tracer.with_stdout(stdout);
 
         if self.journald {
@@ -130,6 +260,174 @@ impl LogArgs {
     }
 }
 
+/// The rpc configuration.
+#[derive(Debug, Args)]
+#[clap(next_help_heading = "RPC")]
+pub(crate) struct RpcArgs {
+    /// The chain to connect to, by name (e.g. `polygon`) or chain id, using its default public
+    /// RPCs. Ignored if an rpc url is given.
+    #[clap(long = "chain", value_name = "CHAIN", global = true, value_parser = parse_chain)]
+    pub chain: Option<&'static Chain>,
+
+    /// The maximum number of times to retry a failed rpc request.
+    #[clap(long = "rpc-retries", value_name = "RETRIES", global = true, default_value_t = 2)]
+    pub rpc_retries: u32,
+
+    /// The delay before the first retry of a failed rpc request, in milliseconds. The delay
+    /// doubles after every retry.
+    #[clap(long = "rpc-backoff-ms", value_name = "MS", global = true, default_value_t = 50)]
+    pub rpc_backoff_ms: u64,
+
+    /// The maximum number of rpc requests to send per second, across all concurrent tasks.
+    /// Unlimited by default.
+    #[clap(long = "rpc-rps", value_name = "RPS", global = true)]
+    pub rpc_rps: Option<u32>,
+
+    /// The maximum number of times to try reconnecting a dropped websocket connection before
+    /// failing over to the next endpoint.
+    #[clap(
+        long = "rpc-ws-reconnects",
+        value_name = "ATTEMPTS",
+        global = true,
+        default_value_t = 10
+    )]
+    pub rpc_ws_reconnects: u32,
+
+    /// Record every rpc request and response to the given JSON fixture file, which is written
+    /// once heimdall finishes. Responses which are served from heimdall's cache are not recorded,
+    /// so consider clearing it first.
+    #[clap(long = "rpc-record", value_name = "PATH", global = true, conflicts_with = "rpc_replay")]
+    pub rpc_record: Option<String>,
+
+    /// Serve rpc responses from the given JSON fixture file, recorded with `--rpc-record`,
+    /// instead of contacting a node.
+    #[clap(long = "rpc-replay", value_name = "PATH", global = true)]
+    pub rpc_replay: Option<String>,
+
+    /// An extra HTTP header to send with every rpc request, e.g. `x-api-key:<KEY>`. May be
+    /// given multiple times.
+    #[clap(
+        long = "rpc-header",
+        value_name = "KEY:VALUE",
+        global = true,
+        value_parser = parse_header
+    )]
+    pub rpc_headers: Vec<(String, String)>,
+
+    /// A bearer token to send with every rpc request, as an `Authorization: Bearer <TOKEN>`
+    /// header.
+    #[clap(long = "rpc-bearer", value_name = "TOKEN", global = true)]
+    pub rpc_bearer: Option<String>,
+
+    /// Don't aggregate batches of read-only calls with Multicall3, making them one at a time
+    /// instead.
+    #[clap(long = "no-multicall", global = true)]
+    pub no_multicall: bool,
+}
+
+/// Parse a `--chain` argument.
+fn parse_chain(chain: &str) -> Result<&'static Chain, String> {
+    lookup_chain(chain).ok_or_else(|| format!("unknown chain '{chain}'"))
+}
+
+/// Parse a `--rpc-header` argument.
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    parse_rpc_header(header).map_err(|e| e.to_string())
+}
+
+impl RpcArgs {
+    /// The rpc url used when a subcommand isn't given one: the `--chain`'s default public RPCs,
+    /// falling back to the configured rpc url.
+    pub(crate) fn default_rpc_url(&self, configured_rpc_url: String) -> String {
+        match self.chain {
+            Some(chain) => chain.default_rpc_url(),
+            None => configured_rpc_url,
+        }
+    }
+
+    /// Applies the configured options from cli args to all rpc requests. The returned guard
+    /// writes the fixture recorded with `--rpc-record`, if any, when it's dropped.
+    pub(crate) fn init_rpc(&self) -> Result<RecordingGuard> {
+        set_retry_policy(
+            RetryPolicy::default()
+                .with_max_retries(self.rpc_retries)
+                .with_initial_backoff(Duration::from_millis(self.rpc_backoff_ms)),
+        );
+        set_rate_limit(self.rpc_rps);
+        RetryPolicy::set_default_reconnect(
+            RetryPolicy::reconnect().with_max_retries(self.rpc_ws_reconnects),
+        );
+
+        let mut headers = self.rpc_headers.clone();
+        if let Some(token) = &self.rpc_bearer {
+            headers.push(("Authorization".to_string(), format!("Bearer {token}")));
+        }
+        set_default_headers(headers);
+        set_default_multicall(!self.no_multicall);
+
+        let mut recording = None;
+        if let Some(path) = &self.rpc_record {
+            let factory = Arc::new(RecordingProviderFactory::new(path));
+            set_provider_factory(Some(factory.clone()));
+            recording = Some(factory);
+        } else if let Some(path) = &self.rpc_replay {
+            set_provider_factory(Some(Arc::new(ReplayProviderFactory::load(path)?)));
+        }
+
+        Ok(RecordingGuard(recording))
+    }
+}
+
+/// Writes the fixture recorded with `--rpc-record`, if any, when dropped, so that it's written
+/// once heimdall finishes, even if the subcommand failed.
+pub(crate) struct RecordingGuard(Option<Arc<RecordingProviderFactory>>);
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        if let Some(factory) = &self.0 {
+            if let Err(e) = factory.flush() {
+                error!("failed to write rpc fixture: {}", e);
+            }
+        }
+    }
+}
+
+// The local fork configuration. This is a plain comment, as clap would otherwise print the doc
+// comment of a flattened struct as heimdall's description.
+#[derive(Debug, Args)]
+#[clap(next_help_heading = "FORK")]
+pub(crate) struct ForkArgs {
+    /// Spawn an anvil fork of the target chain, and run state-mutating probes (i.e. simulating
+    /// decompiled functions with `decompile --validate-onchain`, and unmined transactions with
+    /// `inspect`) against it. Requires anvil to be installed.
+    #[clap(long = "fork", global = true, conflicts_with = "fork_url")]
+    pub fork: bool,
+
+    /// The block to fork the target chain at. Defaults to the latest block.
+    #[clap(long = "fork-block", value_name = "BLOCK", global = true, requires = "fork")]
+    pub fork_block: Option<u64>,
+
+    /// Attach to an already running anvil fork at the given url, instead of spawning one.
+    #[clap(long = "fork-url", value_name = "URL", global = true)]
+    pub fork_url: Option<String>,
+}
+
+impl ForkArgs {
+    /// Spawns or attaches to the configured fork, if any, forking `rpc_url`. State-mutating
+    /// probes are routed to the fork for as long as the returned [`Fork`] is alive.
+    pub(crate) async fn init_fork(&self, rpc_url: &str) -> Result<Option<Arc<Fork>>> {
+        let fork = match (&self.fork_url, self.fork) {
+            (Some(fork_url), _) => Fork::attach(fork_url).await?,
+            (None, true) => Fork::spawn(rpc_url, self.fork_block).await?,
+            (None, false) => return Ok(None),
+        };
+
+        let fork = Arc::new(fork);
+        set_default_fork(Some(&fork));
+        Ok(Some(fork))
+    }
+}
+
 /// The color mode for the cli.
 #[derive(Debug, Copy, Clone, ValueEnum, Eq, PartialEq)]
 pub(crate) enum ColorMode {
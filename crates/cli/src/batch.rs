@@ -0,0 +1,46 @@
+//! Reading the targets of a subcommand's `--batch`, from a file or stdin.
+
+use std::io::Read;
+
+use eyre::{eyre, Result};
+
+/// Reads the targets listed in the batch file, or stdin if it's `-`.
+pub(crate) fn read_targets(batch: &str) -> Result<Vec<String>> {
+    let listing = match batch {
+        "-" => {
+            let mut listing = String::new();
+            std::io::stdin()
+                .read_to_string(&mut listing)
+                .map_err(|e| eyre!("failed to read targets from stdin: {}", e))?;
+            listing
+        }
+        path => std::fs::read_to_string(path)
+            .map_err(|e| eyre!("failed to read targets from '{}': {}", path, e))?,
+    };
+    Ok(parse_targets(&listing))
+}
+
+/// The targets listed, one per line. Blank lines and `#` comments are skipped.
+fn parse_targets(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        let listing = "0x0000000000000000000000000000000000000001\n\n  # a comment\n  \
+                       ./contract.bin # trailing comment\n";
+        assert_eq!(
+            parse_targets(listing),
+            vec!["0x0000000000000000000000000000000000000001", "./contract.bin"]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Generates the control flow graphs of the cfg command, and writes their output.
+
+use eyre::{eyre, Result};
+use heimdall_common::utils::io::file::write_file;
+use heimdall_core::heimdall_cfg::{cfg, cfg_diff, CfgArgs};
+use serde_json::json;
+
+use crate::{
+    json::{is_json_output, print_json},
+    output::{build_output_path, print_with_less},
+};
+
+/// Generates the control flow graph of the target, and writes it.
+pub(crate) async fn cfg_target(cmd: CfgArgs) -> Result<()> {
+    // if the user has passed an output filename, override the default filename
+    let mut filename = format!("cfg.{}", cmd.format.extension());
+    let given_name = cmd.name.as_str();
+
+    if !given_name.is_empty() {
+        filename = format!("{given_name}-{filename}");
+    }
+    let cfg = cfg(cmd.clone()).await.map_err(|e| eyre!("failed to generate cfg: {}", e))?;
+
+    if is_json_output(&cmd.output) {
+        let mut graph = cfg.to_json();
+        graph["dot"] = json!(cfg.as_dot(cmd.color_edges));
+        for (selector, dot) in cfg.functions_as_dot(cmd.color_edges) {
+            graph["functions"][selector]["dot"] = json!(dot);
+        }
+        print_json("cfg", graph)?;
+    } else if cmd.output == "print" {
+        let mut output = cfg.render(cmd.format, cmd.color_edges);
+        for (selector, rendered) in cfg.render_functions(cmd.format, cmd.color_edges) {
+            output.push_str(&format!("\n// function {selector}\n{rendered}"));
+        }
+        if let Some(call_graph) = &cfg.call_graph {
+            let rendered = call_graph
+                .render(cmd.format)
+                .map_err(|e| eyre!("failed to render call graph: {}", e))?;
+            output.push_str(&format!("\n// call graph\n{rendered}"));
+        }
+        print_with_less(&output).await.map_err(|e| eyre!("failed to print cfg: {}", e))?;
+    } else {
+        let output_path = build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
+            .await
+            .map_err(|e| eyre!("failed to build output path: {}", e))?;
+        write_file(&output_path, &cfg.render(cmd.format, cmd.color_edges))
+            .map_err(|e| eyre!("failed to write cfg: {}", e))?;
+
+        // each function's graph is written to a directory beside the contract's graph
+        let extension = cmd.format.extension();
+        let function_dir = output_path.trim_end_matches(&format!(".{extension}"));
+        for (selector, rendered) in cfg.render_functions(cmd.format, cmd.color_edges) {
+            write_file(&format!("{function_dir}/{selector}.{extension}"), &rendered)
+                .map_err(|e| eyre!("failed to write cfg of {}: {}", selector, e))?;
+        }
+
+        // the call graph is written beside the contract's graph
+        if let Some(call_graph) = &cfg.call_graph {
+            let rendered = call_graph
+                .render(cmd.format)
+                .map_err(|e| eyre!("failed to render call graph: {}", e))?;
+            let call_graph_path = format!(
+                "{}{}",
+                output_path.trim_end_matches(&filename),
+                filename.replace(&format!("cfg.{extension}"), &format!("call-graph.{extension}"))
+            );
+            write_file(&call_graph_path, &rendered)
+                .map_err(|e| eyre!("failed to write call graph: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Diffs the control flow graphs of the two targets given with `--diff`, and writes the diff
+/// with a summary of its changes.
+pub(crate) async fn cfg_diff_target(cmd: CfgArgs) -> Result<()> {
+    // if the user has passed an output filename, override the default filename
+    let mut filename = format!("cfg-diff.{}", cmd.format.extension());
+    let given_name = cmd.name.as_str();
+
+    if !given_name.is_empty() {
+        filename = format!("{given_name}-{filename}");
+    }
+    let diff = cfg_diff(cmd.clone()).await.map_err(|e| eyre!("failed to diff cfgs: {}", e))?;
+
+    if is_json_output(&cmd.output) {
+        print_json("cfg-diff", diff.to_json())?;
+        return Ok(());
+    }
+
+    let rendered = diff
+        .render(cmd.format, cmd.color_edges)
+        .map_err(|e| eyre!("failed to render cfg diff: {}", e))?;
+    if cmd.output == "print" {
+        print_with_less(&format!("{}\n\n{rendered}", diff.summary()))
+            .await
+            .map_err(|e| eyre!("failed to print cfg diff: {}", e))?;
+    } else {
+        // the diff is written beside the newer target's output
+        let output_path = build_output_path(&cmd.output, &cmd.diff[1], &cmd.rpc_url, &filename)
+            .await
+            .map_err(|e| eyre!("failed to build output path: {}", e))?;
+        write_file(&output_path, &rendered)
+            .map_err(|e| eyre!("failed to write cfg diff: {}", e))?;
+
+        let summary_path = format!(
+            "{}.txt",
+            output_path.trim_end_matches(&format!(".{}", cmd.format.extension()))
+        );
+        write_file(&summary_path, &diff.summary())
+            .map_err(|e| eyre!("failed to write cfg diff summary: {}", e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Decodes the targets of the decode command, and writes their output.
+
+use std::io::Write;
+
+use eyre::{eyre, Result};
+use futures::{stream, StreamExt};
+use heimdall_common::utils::io::file::write_file;
+use heimdall_core::heimdall_decoder::{decode, decode_logs, DecodeArgs};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::{
+    batch::read_targets,
+    json::{envelope, is_json_output, print_json},
+    output::build_output_path,
+};
+
+/// Whether the target's event logs are decoded, rather than its calldata.
+fn decodes_logs(cmd: &DecodeArgs) -> bool {
+    !cmd.log.is_empty() || cmd.all_logs
+}
+
+/// Decodes the target, and writes its output.
+pub(crate) async fn decode_target(cmd: DecodeArgs) -> Result<()> {
+    // decode event logs, rather than calldata, if `--log` or `--all-logs` is given
+    if decodes_logs(&cmd) {
+        let result =
+            decode_logs(cmd.clone()).await.map_err(|e| eyre!("failed to decode logs: {}", e))?;
+
+        if is_json_output(&cmd.output) {
+            print_json("decode-logs", result.to_json())?;
+        } else if cmd.output == "print" {
+            result.display()
+        } else {
+            let output_path =
+                build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, "decoded-logs.json")
+                    .await
+                    .map_err(|e| eyre!("failed to build output path: {}", e))?;
+            write_file(&output_path, &serde_json::to_string_pretty(&result.to_json())?)
+                .map_err(|e| eyre!("failed to write decoded logs: {}", e))?;
+        }
+    } else {
+        let result =
+            decode(cmd.clone()).await.map_err(|e| eyre!("failed to decode calldata: {}", e))?;
+
+        if is_json_output(&cmd.output) {
+            print_json("decode", serde_json::from_str(&result.to_json()?)?)?;
+        } else if cmd.output == "print" {
+            result.display()
+        } else {
+            let output_path =
+                build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, "decoded.json")
+                    .await
+                    .map_err(|e| eyre!("failed to build output path: {}", e))?;
+            write_file(&output_path, &result.to_json()?)
+                .map_err(|e| eyre!("failed to write decoded output: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Decodes each target listed in the batch file, or stdin if it's `-`, several at a time. Each
+/// target's result is printed to stdout as a line of JSON as soon as it's decoded, in the order
+/// the targets are listed, or written to `decoded.ndjson` within the output directory if one is
+/// given. Signatures resolved for one target are cached for the rest.
+pub(crate) async fn decode_batch(cmd: DecodeArgs, batch: &str) -> Result<()> {
+    let targets = read_targets(batch)?;
+    info!("decoding {} targets", targets.len());
+
+    let mut results = stream::iter(targets)
+        .map(|target| {
+            let target_cmd = DecodeArgs { target: target.clone(), batch: None, ..cmd.clone() };
+            async move {
+                let result = decode_to_json(target_cmd).await;
+                if let Err(e) = &result {
+                    warn!("failed to decode '{}': {}", target, e);
+                }
+                batch_line(&target, result)
+            }
+        })
+        .buffered(cmd.threads.max(1));
+
+    let write_to_stdout = cmd.output == "print" || is_json_output(&cmd.output);
+    let mut lines = Vec::new();
+    let mut succeeded = 0;
+    while let Some(line) = results.next().await {
+        if line.get("error").is_none() {
+            succeeded += 1;
+        }
+
+        let line = serde_json::to_string(&line)?;
+        match write_to_stdout {
+            true => writeln!(std::io::stdout().lock(), "{line}")?,
+            false => lines.push(line),
+        }
+    }
+    info!("decoded {} targets successfully", succeeded);
+
+    if !write_to_stdout {
+        lines.push(String::new());
+        write_file(&format!("{}/decoded.ndjson", cmd.output), &lines.join("\n"))
+            .map_err(|e| eyre!("failed to write decoded output: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Decodes the target's calldata or event logs, returning the result wrapped in the envelope of
+/// the schema it follows.
+async fn decode_to_json(cmd: DecodeArgs) -> Result<Value> {
+    match decodes_logs(&cmd) {
+        true => Ok(envelope("decode-logs", decode_logs(cmd).await?.to_json())),
+        false => Ok(envelope("decode", serde_json::from_str(&decode(cmd).await?.to_json()?)?)),
+    }
+}
+
+/// The line of a batch's output for the target, which is its result, or the error it failed with.
+fn batch_line(target: &str, result: Result<Value>) -> Value {
+    match result {
+        Ok(mut result) => {
+            result["target"] = json!(target);
+            result
+        }
+        Err(e) => json!({ "target": target, "error": e.to_string() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_line() {
+        let line = batch_line("0x01", Ok(envelope("decode", json!({ "name": "transfer" }))));
+        assert_eq!(line["target"], "0x01");
+        assert_eq!(line["result"]["name"], "transfer");
+
+        let line = batch_line("0x02", Err(eyre!("calldata is empty")));
+        assert_eq!(line["target"], "0x02");
+        assert_eq!(line["error"], "calldata is empty");
+    }
+}
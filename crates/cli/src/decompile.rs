@@ -0,0 +1,345 @@
+//! Decompiles the targets of the decompile command, and writes their output.
+
+use std::time::Instant;
+
+use eyre::{eyre, Result};
+use futures::{stream, StreamExt};
+use heimdall_common::utils::io::file::write_file;
+use heimdall_core::heimdall_decompiler::{decompile, diff, DecompilerArgs, DiffArgs, OutputFormat};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::{
+    batch::read_targets,
+    json::{decompile_json, is_json_output, print_json},
+    output::{build_output_path, print_with_less},
+};
+
+/// Decompiles both targets of the diff command, and writes how they differ.
+pub(crate) async fn diff_targets(cmd: DiffArgs) -> Result<()> {
+    // if the user has passed an output filename, override the default filename
+    let mut filename = "diff.txt".to_string();
+    let given_name = cmd.name.as_str();
+
+    if !given_name.is_empty() {
+        filename = format!("{given_name}-{filename}");
+    }
+    let diff = diff(cmd.clone()).await.map_err(|e| eyre!("failed to diff contracts: {}", e))?;
+
+    if is_json_output(&cmd.output) {
+        print_json("diff", diff.to_json())?;
+    } else if cmd.output == "print" {
+        print_with_less(&diff.report()).await.map_err(|e| eyre!("failed to print diff: {}", e))?;
+    } else {
+        // the diff is written beside the newer target's output
+        let output_path = build_output_path(&cmd.output, &cmd.new_target, &cmd.rpc_url, &filename)
+            .await
+            .map_err(|e| eyre!("failed to build output path: {}", e))?;
+        write_file(&output_path, &diff.report())
+            .map_err(|e| eyre!("failed to write diff: {}", e))?;
+        info!("wrote diff to '{}'", output_path);
+    }
+
+    Ok(())
+}
+
+/// Decompiles the target, and writes its output.
+pub(crate) async fn decompile_target(cmd: DecompilerArgs) -> Result<()> {
+    // if the user has passed an output filename, override the default filename
+    let mut abi_filename: String = "abi.json".to_string();
+    let mut storage_layout_filename: String = "storage_layout.json".to_string();
+    let mut proxy_filename: String = "proxy.json".to_string();
+    let mut verification_filename: String = "verification.json".to_string();
+    let mut constructor_filename: String = "constructor.json".to_string();
+    let mut decompiled_output_filename: String = "decompiled".to_string();
+    let mut source_map_filename: String = "source_map.json".to_string();
+
+    let given_name = cmd.name.as_str();
+
+    if !given_name.is_empty() {
+        abi_filename = format!("{given_name}-{abi_filename}");
+        storage_layout_filename = format!("{given_name}-{storage_layout_filename}");
+        proxy_filename = format!("{given_name}-{proxy_filename}");
+        verification_filename = format!("{given_name}-{verification_filename}");
+        constructor_filename = format!("{given_name}-{constructor_filename}");
+        decompiled_output_filename = format!("{given_name}-{decompiled_output_filename}");
+        source_map_filename = format!("{given_name}-{source_map_filename}");
+    }
+
+    let result =
+        decompile(cmd.clone()).await.map_err(|e| eyre!("failed to decompile bytecode: {}", e))?;
+
+    // a proxy's implementation and a diamond's facets are written alongside the target's
+    // own output
+    let results = std::iter::once((String::new(), &result))
+        .chain(
+            result.implementation.as_deref().map(|result| ("implementation/".to_string(), result)),
+        )
+        .chain(result.facets.iter().map(|(address, result)| (format!("facets/{address}/"), result)))
+        .collect::<Vec<_>>();
+
+    if is_json_output(&cmd.output) {
+        print_json("decompile", decompile_json(&result))?;
+    } else if cmd.output == "print" {
+        let mut output_str = String::new();
+        if let Some(proxy) = &result.proxy {
+            output_str.push_str(&format!("Proxy:\n\n{}\n\n", proxy.summary().join("\n")));
+        }
+
+        for (prefix, result) in &results {
+            let label = match prefix.split('/').collect::<Vec<_>>().as_slice() {
+                ["implementation", ..] => "Implementation ".to_string(),
+                ["facets", address, ..] => format!("Facet {address} "),
+                _ => String::new(),
+            };
+            output_str.push_str(&format!(
+                "{label}ABI:\n\n{}\n",
+                serde_json::to_string_pretty(&result.abi)?
+            ));
+
+            if let Some(constructor) = &result.constructor {
+                output_str.push_str(&format!(
+                    "{label}Constructor:\n\n{}\n",
+                    serde_json::to_string_pretty(&constructor.to_json())?
+                ));
+            }
+
+            if let Some(source) = &result.source {
+                output_str.push_str(&format!("{label}Source:\n\n{source}\n"));
+            }
+
+            for (path, source) in result.verified_sources.iter().flatten() {
+                output_str.push_str(&format!("{label}Verified source ({path}):\n\n{source}\n"));
+            }
+
+            if let Some(verification) = &result.verification {
+                output_str.push_str(&format!(
+                    "{label}Verification:\n\n{}\n",
+                    serde_json::to_string_pretty(&verification.to_json())?
+                ));
+            }
+        }
+
+        print_with_less(&output_str)
+            .await
+            .map_err(|e| eyre!("failed to print decompiled bytecode: {}", e))?;
+    } else {
+        // write the proxy summary
+        if let Some(proxy) = &result.proxy {
+            let output_path =
+                build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &proxy_filename)
+                    .await
+                    .map_err(|e| eyre!("failed to build output path: {}", e))?;
+            write_file(&output_path, &serde_json::to_string_pretty(&proxy.to_json())?)
+                .map_err(|e| eyre!("failed to write proxy summary: {}", e))?;
+        }
+
+        for (prefix, result) in results {
+            // write the contract ABI
+            let output_path = build_output_path(
+                &cmd.output,
+                &cmd.target,
+                &cmd.rpc_url,
+                &format!("{prefix}{abi_filename}"),
+            )
+            .await
+            .map_err(|e| eyre!("failed to build output path: {}", e))?;
+
+            write_file(&output_path, &serde_json::to_string_pretty(&result.abi)?)
+                .map_err(|e| eyre!("failed to write ABI: {}", e))?;
+
+            // write the recovered storage layout
+            if !result.storage_layout.is_empty() {
+                let output_path = build_output_path(
+                    &cmd.output,
+                    &cmd.target,
+                    &cmd.rpc_url,
+                    &format!("{prefix}{storage_layout_filename}"),
+                )
+                .await
+                .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                write_file(
+                    &output_path,
+                    &serde_json::to_string_pretty(&result.storage_layout.to_solc_json())?,
+                )
+                .map_err(|e| eyre!("failed to write storage layout: {}", e))?;
+            }
+
+            // write the constructor recovered from the creation bytecode
+            if let Some(constructor) = &result.constructor {
+                let output_path = build_output_path(
+                    &cmd.output,
+                    &cmd.target,
+                    &cmd.rpc_url,
+                    &format!("{prefix}{constructor_filename}"),
+                )
+                .await
+                .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                write_file(&output_path, &serde_json::to_string_pretty(&constructor.to_json())?)
+                    .map_err(|e| eyre!("failed to write constructor: {}", e))?;
+            }
+
+            // write the verification report
+            if let Some(verification) = &result.verification {
+                let output_path = build_output_path(
+                    &cmd.output,
+                    &cmd.target,
+                    &cmd.rpc_url,
+                    &format!("{prefix}{verification_filename}"),
+                )
+                .await
+                .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                write_file(&output_path, &serde_json::to_string_pretty(&verification.to_json())?)
+                    .map_err(|e| eyre!("failed to write verification report: {}", e))?;
+            }
+
+            // write the foundry project wrapping the source, or the source itself
+            if let Some(project) = &result.project {
+                for (path, contents) in project {
+                    let output_path = build_output_path(
+                        &cmd.output,
+                        &cmd.target,
+                        &cmd.rpc_url,
+                        &format!("{prefix}{path}"),
+                    )
+                    .await
+                    .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                    write_file(&output_path, contents)
+                        .map_err(|e| eyre!("failed to write project file: {}", e))?;
+                }
+            } else if let Some(source) = &result.source {
+                let extension = match result.source_format {
+                    OutputFormat::Solidity | OutputFormat::Interface => "sol",
+                    OutputFormat::Vyper => "vy",
+                    _ => "yul",
+                };
+                let output_path = build_output_path(
+                    &cmd.output,
+                    &cmd.target,
+                    &cmd.rpc_url,
+                    &format!("{prefix}{decompiled_output_filename}.{extension}"),
+                )
+                .await
+                .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                write_file(&output_path, source)
+                    .map_err(|e| eyre!("failed to write source: {}", e))?;
+            }
+
+            // write the source map of the source (if requested)
+            if let Some(source_map) = result.source_map.as_ref().filter(|_| cmd.source_map) {
+                let output_path = build_output_path(
+                    &cmd.output,
+                    &cmd.target,
+                    &cmd.rpc_url,
+                    &format!("{prefix}{source_map_filename}"),
+                )
+                .await
+                .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                write_file(&output_path, &serde_json::to_string_pretty(&source_map.to_json())?)
+                    .map_err(|e| eyre!("failed to write source map: {}", e))?;
+            }
+
+            // write the verified source files, keeping their relative paths
+            for (path, source) in result.verified_sources.iter().flatten() {
+                let path = path
+                    .split(['/', '\\'])
+                    .filter(|component| !matches!(*component, "" | "." | ".."))
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let output_path = build_output_path(
+                    &cmd.output,
+                    &cmd.target,
+                    &cmd.rpc_url,
+                    &format!("{prefix}verified/{path}"),
+                )
+                .await
+                .map_err(|e| eyre!("failed to build output path: {}", e))?;
+                write_file(&output_path, source)
+                    .map_err(|e| eyre!("failed to write verified source: {}", e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decompiles each target listed in the batch file, or stdin if it's `-`, several at a time. Each
+/// target's output is written to its own directory within the output directory, alongside a
+/// summary of which targets succeeded or failed, and how long each took.
+pub(crate) async fn decompile_batch(cmd: DecompilerArgs, batch: &str) -> Result<()> {
+    if cmd.output == "print" || is_json_output(&cmd.output) {
+        return Err(eyre!("the output of a batch can't be printed"));
+    }
+
+    let targets = read_targets(batch)?;
+    info!("decompiling {} targets", targets.len());
+
+    let start_time = Instant::now();
+    let results = stream::iter(targets)
+        .map(|target| {
+            let output = format!("{}/{}", cmd.output, target_directory(&target));
+            let target_cmd = DecompilerArgs {
+                target: target.clone(),
+                batch: None,
+                output: output.clone(),
+                ..cmd.clone()
+            };
+            async move {
+                let start_time = Instant::now();
+                let result = decompile_target(target_cmd).await;
+                if let Err(e) = &result {
+                    warn!("failed to decompile '{}': {}", target, e);
+                }
+                json!({
+                    "target": target,
+                    "success": result.is_ok(),
+                    "error": result.err().map(|e| e.to_string()),
+                    "output": output,
+                    "elapsed_ms": start_time.elapsed().as_millis() as u64,
+                })
+            }
+        })
+        .buffered(cmd.threads.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+    let succeeded = results.iter().filter(|result| result["success"] == true).count();
+    info!("decompiled {} of {} targets", succeeded, results.len());
+
+    let summary = json!({
+        "succeeded": succeeded,
+        "failed": results.len() - succeeded,
+        "elapsed_ms": start_time.elapsed().as_millis() as u64,
+        "results": results,
+    });
+    write_file(&format!("{}/summary.json", cmd.output), &serde_json::to_string_pretty(&summary)?)
+        .map_err(|e| eyre!("failed to write batch summary: {}", e))?;
+
+    Ok(())
+}
+
+/// The directory a target's output is written to, which is the target with any characters which
+/// can't appear in a path replaced.
+fn target_directory(target: &str) -> String {
+    target
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+            true => c,
+            false => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_directory() {
+        assert_eq!(
+            target_directory("0x0000000000000000000000000000000000000001"),
+            "0x0000000000000000000000000000000000000001"
+        );
+        assert_eq!(target_directory("./contracts/a.bin"), "._contracts_a.bin");
+    }
+}
@@ -0,0 +1,426 @@
+//! Dumps the storage of the dump command's target, and writes it.
+
+use std::{fs::File, path::Path, sync::Arc};
+
+use alloy::primitives::FixedBytes;
+use eyre::{eyre, Result};
+use heimdall_common::utils::{hex::ToLowerHex, io::file::write_file};
+use heimdall_core::heimdall_dump::{
+    dump, watch, DumpArgs, DumpFormat, StorageDump, StorageVariable,
+};
+use parquet::{
+    basic::Compression,
+    data_type::{ByteArray, ByteArrayType},
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+use rusqlite::Connection;
+use tracing::info;
+
+use crate::{
+    json::{dump_json, envelope, is_json_output, print_json, storage_change_json, value_label},
+    output::{build_output_path, print_with_less},
+};
+
+/// The number of rows in each of a Parquet dump's row groups.
+const PARQUET_ROW_GROUP_SIZE: usize = 100_000;
+
+/// The columns of the dump's tabular formats, in order.
+const COLUMNS: [&str; 6] = ["slot", "value", "label", "variable", "type", "decoded"];
+
+/// Dumps the target's storage, and writes it.
+pub(crate) async fn dump_target(cmd: DumpArgs) -> Result<()> {
+    // if the user has passed an output filename, override the default filename
+    let mut filename = format!("dump.{}", cmd.format.extension());
+    let mut provenance_filename = "provenance.csv".to_string();
+    let given_name = cmd.name.as_str();
+
+    if !given_name.is_empty() {
+        filename = format!("{given_name}-{filename}");
+        provenance_filename = format!("{given_name}-{provenance_filename}");
+    }
+    if cmd.output == "print" && cmd.format != DumpFormat::Csv {
+        return Err(eyre!(
+            "{} dumps can't be printed, pass an output directory with `--output`",
+            cmd.format.extension()
+        ));
+    }
+
+    let mut result = dump(cmd.clone()).await.map_err(|e| eyre!("failed to dump storage: {}", e))?;
+    if is_json_output(&cmd.output) {
+        print_json("dump", dump_json(&result))?;
+    } else if cmd.output == "print" {
+        let mut output = dump_csv(&result);
+        if cmd.provenance {
+            output.push_str(&format!("\n\n{}", provenance_csv(&result)));
+        }
+        print_with_less(&output).await.map_err(|e| eyre!("failed to print dump: {}", e))?;
+    } else {
+        let output_path = build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
+            .await
+            .map_err(|e| eyre!("failed to build output path: {}", e))?;
+        match cmd.format {
+            DumpFormat::Csv => write_file(&output_path, &dump_csv(&result)),
+            DumpFormat::Parquet => write_parquet(&output_path, &result),
+            DumpFormat::Sqlite => write_sqlite(&output_path, &result),
+        }
+        .map_err(|e| eyre!("failed to write dump: {}", e))?;
+        info!("wrote {} storage slots to '{}'", result.storage.len(), output_path);
+
+        // the provenance report is written beside the dump
+        if cmd.provenance {
+            let provenance_path =
+                build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &provenance_filename)
+                    .await
+                    .map_err(|e| eyre!("failed to build output path: {}", e))?;
+            write_file(&provenance_path, &provenance_csv(&result))
+                .map_err(|e| eyre!("failed to write provenance: {}", e))?;
+            info!(
+                "wrote the provenance of {} slots to '{}'",
+                result.provenance.len(),
+                provenance_path
+            );
+        }
+    }
+
+    // stream the changes in new blocks as newline-delimited json
+    if cmd.watch {
+        watch(&cmd, &mut result, |change| {
+            println!("{}", envelope("dump-watch", storage_change_json(&change)));
+            Ok(())
+        })
+        .await
+        .map_err(|e| eyre!("failed to watch storage: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// A row of the dump's tabular output: a slot, and one of the state variables stored in it.
+pub(crate) struct DumpRow<'a> {
+    pub slot: &'a FixedBytes<32>,
+    pub value: &'a FixedBytes<32>,
+    /// The label of the address the slot holds, if it holds a labeled address
+    pub label: Option<String>,
+    /// The state variable, if it was recovered
+    pub variable: Option<&'a StorageVariable>,
+}
+
+/// The dump's rows, one for each variable stored in each slot, grouped by state variable. Each
+/// group is ordered by slot, and the slots whose variables weren't recovered come last.
+pub(crate) fn dump_rows(storage_dump: &StorageDump) -> Vec<DumpRow<'_>> {
+    let mut rows = storage_dump
+        .storage
+        .iter()
+        .flat_map(|(slot, value)| {
+            let label = value_label(value);
+            let variables = match storage_dump.variables.get(slot) {
+                Some(variables) => variables.iter().map(Some).collect(),
+                None => vec![None],
+            };
+            variables.into_iter().map(move |variable| DumpRow {
+                slot,
+                value,
+                label: label.clone(),
+                variable,
+            })
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| {
+        let group = |row: &DumpRow<'_>| row.variable.map(|variable| variable.variable.clone());
+        (group(a).is_none(), group(a), a.slot).cmp(&(group(b).is_none(), group(b), b.slot))
+    });
+    rows
+}
+
+impl DumpRow<'_> {
+    /// The row's fields, in the order of [`COLUMNS`]. The slot and value are always present.
+    fn fields(&self) -> [Option<String>; 6] {
+        [
+            Some(self.slot.to_lower_hex()),
+            Some(self.value.to_lower_hex()),
+            self.label.clone(),
+            self.variable.map(|variable| variable.name.clone()),
+            self.variable.and_then(|variable| variable.typ.clone()),
+            self.variable.map(|variable| variable.value.clone()),
+        ]
+    }
+}
+
+/// The dump as CSV, with a row for each variable stored in each slot.
+fn dump_csv(storage_dump: &StorageDump) -> String {
+    let quote = |field: &str| format!("\"{}\"", field.replace('"', "\"\""));
+    let mut lines = vec![COLUMNS.join(",")];
+    for row in dump_rows(storage_dump) {
+        let [slot, value, label, variable, typ, decoded] = row.fields();
+        lines.push(
+            [
+                slot.unwrap_or_default(),
+                value.unwrap_or_default(),
+                label.as_deref().map(quote).unwrap_or_default(),
+                variable.as_deref().map(quote).unwrap_or_default(),
+                typ.as_deref().map(quote).unwrap_or_default(),
+                decoded.as_deref().map(quote).unwrap_or_default(),
+            ]
+            .join(","),
+        );
+    }
+    lines.join("\n")
+}
+
+/// The provenance report as CSV, with a row for the first write to each slot, and another for its
+/// last write if it was written more than once. Rows are ordered by slot, and senders which
+/// couldn't be fetched are left empty.
+fn provenance_csv(storage_dump: &StorageDump) -> String {
+    let mut slots = storage_dump.provenance.iter().collect::<Vec<_>>();
+    slots.sort_by_key(|(slot, _)| **slot);
+
+    let value = |value: Option<FixedBytes<32>>| value.map(|v| v.to_lower_hex()).unwrap_or_default();
+    let mut lines = vec!["slot,variable,write,block,transaction,caller,from,to".to_string()];
+    for (slot, provenance) in slots {
+        let variable = storage_dump
+            .variables
+            .get(slot)
+            .map(|variables| {
+                variables
+                    .iter()
+                    .map(|variable| variable.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(";")
+            })
+            .unwrap_or_default();
+        let writes = match provenance.writes {
+            1 => vec![("first", &provenance.first)],
+            _ => vec![("first", &provenance.first), ("last", &provenance.last)],
+        };
+        for (kind, write) in writes {
+            lines.push(format!(
+                "{},\"{}\",{},{},{},{},{},{}",
+                slot.to_lower_hex(),
+                variable.replace('"', "\"\""),
+                kind,
+                write.block,
+                write.transaction.to_lower_hex(),
+                write.caller.map(|caller| caller.to_string()).unwrap_or_default(),
+                value(write.from),
+                value(write.to),
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Writes the dump to a Parquet file at `path`, with a string column for each of [`COLUMNS`].
+fn write_parquet(path: &str, storage_dump: &StorageDump) -> Result<()> {
+    let schema = Arc::new(parse_message_type(
+        "message storage {
+            REQUIRED BYTE_ARRAY slot (UTF8);
+            REQUIRED BYTE_ARRAY value (UTF8);
+            OPTIONAL BYTE_ARRAY label (UTF8);
+            OPTIONAL BYTE_ARRAY variable (UTF8);
+            OPTIONAL BYTE_ARRAY type (UTF8);
+            OPTIONAL BYTE_ARRAY decoded (UTF8);
+        }",
+    )?);
+    let properties =
+        Arc::new(WriterProperties::builder().set_compression(Compression::SNAPPY).build());
+    let mut writer = SerializedFileWriter::new(create_file(path)?, schema, properties)?;
+
+    let rows = dump_rows(storage_dump).iter().map(DumpRow::fields).collect::<Vec<_>>();
+    for chunk in rows.chunks(PARQUET_ROW_GROUP_SIZE) {
+        let mut row_group = writer.next_row_group()?;
+        let mut index = 0;
+        while let Some(mut column) = row_group.next_column()? {
+            // only the present values are written, and the definition levels mark which rows
+            // have one
+            let fields = chunk.iter().map(|fields| fields[index].as_deref()).collect::<Vec<_>>();
+            let values =
+                fields.iter().flatten().map(|field| ByteArray::from(*field)).collect::<Vec<_>>();
+            let levels = fields.iter().map(|field| field.is_some() as i16).collect::<Vec<_>>();
+            let levels = (index >= 2).then_some(levels.as_slice());
+            column.typed::<ByteArrayType>().write_batch(&values, levels, None)?;
+            column.close()?;
+            index += 1;
+        }
+        row_group.close()?;
+    }
+    writer.close()?;
+
+    Ok(())
+}
+
+/// Writes the dump to a SQLite database at `path`, replacing its `storage` table with a row for
+/// each variable stored in each slot.
+fn write_sqlite(path: &str, storage_dump: &StorageDump) -> Result<()> {
+    // the dump only replaces its own table, so any other tables in the database are kept
+    create_parent_dirs(path)?;
+    let mut connection = Connection::open(path)?;
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(
+        "DROP TABLE IF EXISTS storage;
+        CREATE TABLE storage (
+            slot TEXT NOT NULL,
+            value TEXT NOT NULL,
+            label TEXT,
+            variable TEXT,
+            type TEXT,
+            decoded TEXT
+        );",
+    )?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO storage (slot, value, label, variable, type, decoded) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for row in dump_rows(storage_dump) {
+            insert.execute(row.fields())?;
+        }
+    }
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/// Creates the file at `path`, and the directories it's in.
+fn create_file(path: &str) -> Result<File> {
+    create_parent_dirs(path)?;
+    Ok(File::create(path)?)
+}
+
+/// Creates the directories the file at `path` is in.
+fn create_parent_dirs(path: &str) -> Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+    use heimdall_core::heimdall_dump::{SlotProvenance, SlotWrite};
+
+    use super::*;
+
+    fn variable(variable: &str, name: &str, value: &str) -> StorageVariable {
+        StorageVariable {
+            variable: variable.to_string(),
+            name: name.to_string(),
+            typ: Some("uint256".to_string()),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dump_rows_are_grouped_by_variable() {
+        let (first, second, third) = (
+            FixedBytes::with_last_byte(1),
+            FixedBytes::repeat_byte(0x22),
+            FixedBytes::repeat_byte(0x33),
+        );
+        let storage_dump = StorageDump {
+            storage: [(first, first), (second, second), (third, third)].into_iter().collect(),
+            variables: [
+                (first, vec![variable("totalSupply", "totalSupply", "1")]),
+                (third, vec![variable("balanceOf", "balanceOf[1]", "51")]),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+
+        let rows = dump_rows(&storage_dump);
+        let names = rows
+            .iter()
+            .map(|row| row.variable.map(|variable| variable.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![Some("balanceOf[1]"), Some("totalSupply"), None]);
+
+        let csv = dump_csv(&storage_dump);
+        assert!(csv
+            .lines()
+            .nth(1)
+            .expect("no rows")
+            .ends_with(r#","balanceOf[1]","uint256","51""#));
+    }
+
+    #[test]
+    fn test_provenance_csv() {
+        let slot = FixedBytes::with_last_byte(1);
+        let write = |block: u128, to: u8| SlotWrite {
+            block,
+            transaction: FixedBytes::repeat_byte(to),
+            caller: (block == 1).then_some(Address::repeat_byte(0x11)),
+            from: None,
+            to: Some(FixedBytes::with_last_byte(to)),
+        };
+        let storage_dump = StorageDump {
+            storage: [(slot, FixedBytes::with_last_byte(2))].into_iter().collect(),
+            variables: [(slot, vec![variable("owner", "owner", "2")])].into_iter().collect(),
+            provenance: [(
+                slot,
+                SlotProvenance { first: write(1, 1), last: write(9, 2), writes: 4 },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+
+        let csv = provenance_csv(&storage_dump);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains(r#","owner",first,1,"#));
+        assert!(lines[1].contains("0x1111111111111111111111111111111111111111"));
+        assert!(lines[2].contains(",last,9,") && lines[2].contains(",,,0x"));
+    }
+
+    #[test]
+    fn test_write_parquet_and_sqlite() {
+        let slot = FixedBytes::with_last_byte(1);
+        let storage_dump = StorageDump {
+            storage: [(slot, slot), (FixedBytes::repeat_byte(0x22), slot)].into_iter().collect(),
+            variables: [(slot, vec![variable("totalSupply", "totalSupply", "1")])]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let directory = std::env::temp_dir().join("heimdall_test_dump_formats");
+        let path = |extension: &str| {
+            directory.join(format!("dump.{extension}")).to_str().expect("invalid path").to_string()
+        };
+
+        write_parquet(&path("parquet"), &storage_dump).expect("failed to write parquet");
+        let reader = parquet::file::reader::SerializedFileReader::new(
+            File::open(path("parquet")).expect("failed to open parquet"),
+        )
+        .expect("failed to read parquet");
+        let metadata = parquet::file::reader::FileReader::metadata(&reader);
+        assert_eq!(metadata.file_metadata().num_rows(), 2);
+        assert_eq!(metadata.file_metadata().schema_descr().num_columns(), COLUMNS.len());
+
+        // writing twice replaces the table, and keeps the database's other tables
+        write_sqlite(&path("sqlite"), &storage_dump).expect("failed to write sqlite");
+        Connection::open(path("sqlite"))
+            .and_then(|connection| {
+                connection.execute_batch(
+                    "CREATE TABLE notes (note TEXT); INSERT INTO notes VALUES ('kept');",
+                )
+            })
+            .expect("failed to add table");
+        write_sqlite(&path("sqlite"), &storage_dump).expect("failed to write sqlite");
+        let connection = Connection::open(path("sqlite")).expect("failed to open sqlite");
+        let note: String = connection
+            .query_row("SELECT note FROM notes", [], |row| row.get(0))
+            .expect("failed to query sqlite");
+        assert_eq!(note, "kept");
+        let (count, decoded): (i64, String) = connection
+            .query_row(
+                "SELECT COUNT(*), MAX(decoded) FROM storage WHERE variable IS NOT NULL",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("failed to query sqlite");
+        assert_eq!((count, decoded.as_str()), (1, "1"));
+
+        std::fs::remove_dir_all(directory).expect("failed to remove dumps");
+    }
+}
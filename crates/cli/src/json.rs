@@ -0,0 +1,400 @@
+//! Machine-readable output for `--output json`, which prints each subcommand's result to stdout
+//! wrapped in a versioned envelope. The schemas of each subcommand's envelope are published in
+//! `schemas/`, and a field is only ever removed or changed by bumping [`SCHEMA_VERSION`].
+
+use std::io::Write;
+
+use alloy::primitives::{Address, FixedBytes};
+use clap::ValueEnum;
+use eyre::Result;
+use heimdall_common::{ether::labels::lookup_label, utils::hex::ToLowerHex};
+use heimdall_core::{
+    heimdall_decompiler::DecompileResult,
+    heimdall_dump::{SlotWrite, StorageChange, StorageDump, StorageVariable, TokenSnapshot},
+    heimdall_inspect::{balance_changes, AccountChange, Asset, InspectResult},
+};
+use serde_json::{json, Value};
+
+use crate::dump::dump_rows;
+
+/// The version of the schemas the JSON output follows.
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+/// The `--output` which prints machine-readable JSON to stdout.
+pub(crate) const JSON_OUTPUT: &str = "json";
+
+/// Whether the `--output` asks for machine-readable JSON on stdout.
+pub(crate) fn is_json_output(output: &str) -> bool {
+    output == JSON_OUTPUT
+}
+
+/// Wraps the subcommand's result in the versioned envelope, which names the schema it follows.
+pub(crate) fn envelope(command: &str, result: Value) -> Value {
+    json!({
+        "$schema": format!(
+            "https://github.com/Jon-Becker/heimdall-rs/blob/main/schemas/v{SCHEMA_VERSION}/{command}.json"
+        ),
+        "schema_version": SCHEMA_VERSION,
+        "command": command,
+        "result": result,
+    })
+}
+
+/// Prints the subcommand's result to stdout, wrapped in the versioned envelope.
+pub(crate) fn print_json(command: &str, result: Value) -> Result<()> {
+    let output = serde_json::to_string_pretty(&envelope(command, result))?;
+    writeln!(std::io::stdout().lock(), "{output}")?;
+    Ok(())
+}
+
+/// The decompiled target as JSON, including its implementation and facets if it's a proxy.
+pub(crate) fn decompile_json(result: &DecompileResult) -> Value {
+    json!({
+        "source": result.source,
+        "source_format": result
+            .source_format
+            .to_possible_value()
+            .map(|format| format.get_name().to_string()),
+        "abi": result.abi,
+        "storage_layout": result.storage_layout.to_solc_json(),
+        "proxy": result.proxy.as_ref().map(|proxy| proxy.to_json()),
+        "constructor": result.constructor.as_ref().map(|constructor| constructor.to_json()),
+        "verification": result.verification.as_ref().map(|verification| verification.to_json()),
+        "metadata": result.metadata.as_ref().map(|metadata| json!({
+            "metadata_hash": metadata.metadata_hash,
+            "solc": metadata.solc,
+            "vyper": metadata.vyper,
+            "experimental": metadata.experimental,
+        })),
+        "implementation": result.implementation.as_deref().map(decompile_json),
+        "facets": result
+            .facets
+            .iter()
+            .map(|(address, facet)| (address.to_string(), decompile_json(facet)))
+            .collect::<serde_json::Map<_, _>>(),
+    })
+}
+
+/// The dumped storage as JSON, listing each slot and its value in order of slot, the values of
+/// each recovered state variable, and the transactions which wrote each slot if they were
+/// recorded.
+pub(crate) fn dump_json(storage_dump: &StorageDump) -> Value {
+    let mut slots = storage_dump.storage.iter().collect::<Vec<_>>();
+    slots.sort_by_key(|(slot, _)| **slot);
+
+    let mut variables = serde_json::Map::new();
+    for row in dump_rows(storage_dump) {
+        let Some(variable) = row.variable else { continue };
+        let mut value = json!({
+            "name": variable.name,
+            "slot": row.slot.to_lower_hex(),
+            "value": variable.value,
+        });
+        if let Some(typ) = &variable.typ {
+            value["type"] = json!(typ);
+        }
+        variables
+            .entry(variable.variable.clone())
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .expect("variable values are an array")
+            .push(value);
+    }
+
+    let mut json = json!({
+        "storage": slots
+            .into_iter()
+            .map(|(slot, value)| {
+                let mut slot = json!({
+                    "slot": slot.to_lower_hex(),
+                    "value": value.to_lower_hex(),
+                });
+                if let Some(label) = value_label(value) {
+                    slot["label"] = json!(label);
+                }
+                slot
+            })
+            .collect::<Vec<_>>(),
+        "variables": variables,
+    });
+    if !storage_dump.provenance.is_empty() {
+        let mut provenance = storage_dump.provenance.iter().collect::<Vec<_>>();
+        provenance.sort_by_key(|(slot, _)| **slot);
+        json["provenance"] = provenance
+            .into_iter()
+            .map(|(slot, provenance)| {
+                json!({
+                    "slot": slot.to_lower_hex(),
+                    "writes": provenance.writes,
+                    "first": slot_write_json(&provenance.first),
+                    "last": slot_write_json(&provenance.last),
+                })
+            })
+            .collect();
+    }
+    json
+}
+
+/// A transaction's write to a slot as JSON.
+fn slot_write_json(write: &SlotWrite) -> Value {
+    json!({
+        "block": write.block as u64,
+        "transaction": write.transaction.to_lower_hex(),
+        "caller": write.caller.map(|caller| caller.to_string()),
+        "from": write.from.map(|value| value.to_lower_hex()),
+        "to": write.to.map(|value| value.to_lower_hex()),
+    })
+}
+
+/// A change to the watched storage as JSON, printed as a line of newline-delimited JSON.
+pub(crate) fn storage_change_json(change: &StorageChange) -> Value {
+    json!({
+        "block": change.block as u64,
+        "slot": change.slot.to_lower_hex(),
+        "from": change.from.map(|value| value.to_lower_hex()),
+        "to": change.to.map(|value| value.to_lower_hex()),
+        "transactions": change.transactions.iter().map(|hash| hash.to_lower_hex()).collect::<Vec<_>>(),
+        "variables": change.variables.iter().map(variable_json).collect::<Vec<_>>(),
+    })
+}
+
+/// The token snapshot as JSON, listing each holder of an ERC-20 by balance, or each token of an
+/// ERC-721 by id. Amounts and token ids are decimal strings, as they may not fit in a JSON number.
+pub(crate) fn snapshot_token_json(snapshot: &TokenSnapshot) -> Value {
+    json!({
+        "standard": snapshot
+            .standard
+            .to_possible_value()
+            .map(|standard| standard.get_name().to_string()),
+        "block": snapshot.block as u64,
+        "balance_slot": snapshot
+            .balance_slot
+            .map(|slot| FixedBytes::<32>::from(slot.to_be_bytes::<32>()).to_lower_hex()),
+        "holders": snapshot.holders.iter().map(|holder| json!({
+            "holder": holder.holder.to_string(),
+            "balance": holder.balance.to_string(),
+            "log_balance": holder.log_balance.to_string(),
+            "storage_balance": holder.storage_balance.map(|balance| balance.to_string()),
+            "discrepancy": holder.is_discrepancy(),
+        })).collect::<Vec<_>>(),
+        "owners": snapshot.owners.iter().map(|token| json!({
+            "token_id": token.token_id.to_string(),
+            "owner": token.owner.to_string(),
+            "log_owner": token.log_owner.to_string(),
+            "storage_owner": token.storage_owner.map(|owner| owner.to_string()),
+            "discrepancy": token.is_discrepancy(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// The inspected transaction as JSON: its decoded call tree, the ether transferred within it,
+/// and the net change in each account's balance. Amounts are decimal strings of wei.
+pub(crate) fn inspect_json(result: &InspectResult) -> Value {
+    json!({
+        "decoded_trace": result.decoded_trace,
+        "value_flows": result.value_flows.iter().map(|flow| json!({
+            "trace_address": flow.trace_address,
+            "from": flow.from.to_string(),
+            "to": flow.to.to_string(),
+            "value": flow.value.to_string(),
+        })).collect::<Vec<_>>(),
+        "balance_changes": balance_changes(&result.value_flows)
+            .into_iter()
+            .map(|(address, change)| json!({
+                "address": address.to_string(),
+                "change": change.to_string(),
+            }))
+            .collect::<Vec<_>>(),
+        "token_transfers": result.token_transfers.iter().map(|transfer| {
+            let mut value = asset_json(&transfer.asset);
+            value["from"] = json!(transfer.from.to_string());
+            value["to"] = json!(transfer.to.to_string());
+            value["amount"] = json!(transfer.amount.to_string());
+            value
+        }).collect::<Vec<_>>(),
+        "balance_deltas": result.balance_deltas.iter().map(|delta| {
+            let mut value = asset_json(&delta.asset);
+            value["address"] = json!(delta.address.to_string());
+            value["delta"] = json!(delta.delta.to_string());
+            value
+        }).collect::<Vec<_>>(),
+        "state_diff": result.state_changes.iter().map(account_change_json).collect::<Vec<_>>(),
+        "gas_profile": result.gas_profile.as_ref().map(|profile| json!({
+            "frames": profile.frames.iter().map(|frame| json!({
+                "trace_address": frame.trace_address,
+                "stack": frame.stack,
+                "gas_used": frame.gas_used,
+                "self_gas": frame.self_gas,
+            })).collect::<Vec<_>>(),
+            "classes": profile.classes.iter().map(|class| json!({
+                "class": class.class,
+                "gas": class.gas,
+            })).collect::<Vec<_>>(),
+        })),
+    })
+}
+
+/// A change the inspected transaction made to an account as JSON. Values the transaction didn't
+/// change are left out, and sides of a change where the account or slot was empty are null.
+fn account_change_json(account: &AccountChange) -> Value {
+    let change = |from: Option<String>, to: Option<String>| json!({ "from": from, "to": to });
+    let mut value = json!({
+        "address": account.address.to_string(),
+        "storage": account.storage.iter().map(|slot| {
+            let mut value = change(
+                slot.from.map(|from| from.to_lower_hex()),
+                slot.to.map(|to| to.to_lower_hex()),
+            );
+            value["slot"] = json!(slot.slot.to_lower_hex());
+            value["variables"] = json!(slot.variables.iter().map(variable_json).collect::<Vec<_>>());
+            value
+        }).collect::<Vec<_>>(),
+    });
+    if let Some(balance) = &account.balance {
+        value["balance"] =
+            change(balance.from.map(|from| from.to_string()), balance.to.map(|to| to.to_string()));
+    }
+    if let Some(nonce) = &account.nonce {
+        value["nonce"] =
+            change(nonce.from.map(|from| from.to_string()), nonce.to.map(|to| to.to_string()));
+    }
+    if let Some(code) = &account.code {
+        value["code"] = change(
+            code.from.as_ref().map(|from| from.to_lower_hex()),
+            code.to.as_ref().map(|to| to.to_lower_hex()),
+        );
+    }
+    value
+}
+
+/// An asset as JSON: its standard, and its token contract and id, where it has them.
+fn asset_json(asset: &Asset) -> Value {
+    match asset {
+        Asset::Ether => json!({ "standard": "ether" }),
+        Asset::Erc20 { token } => json!({ "standard": "erc20", "token": token.to_string() }),
+        Asset::Erc721 { token, id } => {
+            json!({ "standard": "erc721", "token": token.to_string(), "id": id.to_string() })
+        }
+        Asset::Erc1155 { token, id } => {
+            json!({ "standard": "erc1155", "token": token.to_string(), "id": id.to_string() })
+        }
+    }
+}
+
+/// A state variable stored in a slot as JSON.
+fn variable_json(variable: &StorageVariable) -> Value {
+    let mut value = json!({
+        "variable": variable.variable,
+        "name": variable.name,
+        "value": variable.value,
+    });
+    if let Some(typ) = &variable.typ {
+        value["type"] = json!(typ);
+    }
+    value
+}
+
+/// The label of the address held in a storage slot, if the slot holds a labeled address.
+pub(crate) fn value_label(value: &FixedBytes<32>) -> Option<String> {
+    let is_address = value[..12].iter().all(|byte| *byte == 0) && value[12..] != [0u8; 20];
+    is_address.then(|| lookup_label(&Address::from_slice(&value[12..]))).flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let envelope = envelope("disassemble", json!({ "assembly": "" }));
+        assert_eq!(envelope["schema_version"], SCHEMA_VERSION);
+        assert_eq!(envelope["command"], "disassemble");
+        assert_eq!(envelope["result"]["assembly"], "");
+        assert!(envelope["$schema"].as_str().expect("no schema").ends_with("/v1/disassemble.json"));
+    }
+
+    #[test]
+    fn test_dump_json_is_sorted() {
+        let storage_dump = StorageDump {
+            storage: [
+                (FixedBytes::with_last_byte(2), FixedBytes::with_last_byte(1)),
+                (FixedBytes::with_last_byte(1), FixedBytes::with_last_byte(2)),
+            ]
+            .into_iter()
+            .collect(),
+            variables: [(
+                FixedBytes::with_last_byte(1),
+                vec![StorageVariable {
+                    variable: "owner".to_string(),
+                    name: "owner".to_string(),
+                    typ: Some("address".to_string()),
+                    value: "0x0000000000000000000000000000000000000002".to_string(),
+                }],
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        let slots = dump_json(&storage_dump)["storage"].as_array().expect("no storage").clone();
+        assert_eq!(slots.len(), 2);
+        assert!(slots[0]["slot"].as_str().expect("no slot").ends_with("01"));
+        assert!(slots[1]["value"].as_str().expect("no value").ends_with("01"));
+
+        let owner = &dump_json(&storage_dump)["variables"]["owner"][0];
+        assert_eq!(owner["type"], "address");
+        assert!(owner["slot"].as_str().expect("no slot").ends_with("01"));
+    }
+
+    #[test]
+    fn test_storage_change_json() {
+        let change = StorageChange {
+            block: 100,
+            slot: FixedBytes::with_last_byte(1),
+            from: None,
+            to: Some(FixedBytes::with_last_byte(2)),
+            transactions: vec![FixedBytes::repeat_byte(0xaa)],
+            variables: vec![StorageVariable {
+                variable: "owner".to_string(),
+                name: "owner".to_string(),
+                typ: None,
+                value: FixedBytes::<32>::with_last_byte(2).to_lower_hex(),
+            }],
+        };
+        let json = storage_change_json(&change);
+        assert_eq!(json["block"], 100);
+        assert!(json["from"].is_null());
+        assert!(json["to"].as_str().expect("no value").ends_with("02"));
+        assert_eq!(json["transactions"].as_array().expect("no transactions").len(), 1);
+        assert_eq!(json["variables"][0]["name"], "owner");
+        assert!(json["variables"][0].get("type").is_none());
+    }
+
+    #[test]
+    fn test_schemas_are_published() {
+        for command in [
+            "assemble",
+            "cfg",
+            "cfg-diff",
+            "decode",
+            "decode-logs",
+            "decompile",
+            "diff",
+            "disassemble",
+            "dump",
+            "dump-watch",
+            "inspect",
+            "similar",
+            "snapshot-token",
+        ] {
+            let path = format!(
+                "{}/../../schemas/v{SCHEMA_VERSION}/{command}.json",
+                env!("CARGO_MANIFEST_DIR")
+            );
+            let schema: Value = serde_json::from_str(
+                &std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing {path}")),
+            )
+            .unwrap_or_else(|_| panic!("{path} isn't json"));
+            assert_eq!(schema["properties"]["command"]["const"], command);
+        }
+    }
+}
@@ -1,53 +1,94 @@
 //! The Heimdall CLI is a command line interface for interacting with Heimdall modules.
 
 pub(crate) mod args;
+pub(crate) mod batch;
+pub(crate) mod cfg;
+pub(crate) mod decode;
+pub(crate) mod decompile;
+pub(crate) mod dump;
+pub(crate) mod json;
 pub(crate) mod output;
+pub(crate) mod token;
 
+use alloy::primitives::TxHash;
 use args::{Arguments, Subcommands};
+use cfg::{cfg_diff_target, cfg_target};
 use clap::Parser;
+use decode::{decode_batch, decode_target};
+use decompile::{decompile_batch, decompile_target, diff_targets};
+use dump::dump_target;
 use eyre::{eyre, Result};
 use heimdall_cache::cache;
+use json::{inspect_json, is_json_output, print_json};
 use output::{build_output_path, print_with_less};
+use serde_json::json;
+use token::snapshot_token_target;
 use tracing::info;
 
-use heimdall_common::utils::{
-    hex::ToLowerHex,
-    io::file::write_file,
-    version::{current_version, remote_nightly_version, remote_version},
+use heimdall_common::{
+    ether::{
+        labels::labels,
+        sigdb::sigs,
+        signatures::{set_signature_concurrency, set_signature_resolver_url},
+    },
+    utils::{
+        io::file::write_file,
+        metrics::write_metrics_file,
+        offline::{is_offline, set_offline},
+        version::{current_version, remote_nightly_version, remote_version},
+    },
 };
 use heimdall_config::{config, Configuration};
 use heimdall_core::{
-    heimdall_cfg::cfg, heimdall_decoder::decode, heimdall_decompiler::decompile,
-    heimdall_disassembler::disassemble, heimdall_dump::dump, heimdall_inspect::inspect,
+    heimdall_disassembler::{assemble, disassemble, format_matches, similar},
+    heimdall_inspect::inspect,
 };
+use heimdall_server::serve;
 
 #[allow(clippy::large_stack_frames)]
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Arguments::parse();
 
-    // setup logging
-    let _ = args.logs.init_tracing();
+    // setup logging. with `--output json`, stdout is reserved for the json output
+    let _ = args.logs.init_tracing(args.sub.prints_json());
+
+    // setup rpc retries, rate limiting and recording
+    let _recording = args.rpc.init_rpc()?;
+
+    // disable all network access if requested
+    set_offline(args.offline);
+
+    // resolve selectors with a custom signature lookup service, if given
+    set_signature_resolver_url(args.sig_resolver_url.clone());
+    set_signature_concurrency(args.sig_concurrency);
 
     // spawn a new tokio runtime to get remote version while the main runtime is running
     let current_version = current_version();
-    let remote_ver = if current_version.is_nightly() {
-        tokio::task::spawn(remote_nightly_version()).await??
+    let remote_ver = if is_offline() {
+        None
+    } else if current_version.is_nightly() {
+        Some(tokio::task::spawn(remote_nightly_version()).await??)
     } else {
-        tokio::task::spawn(remote_version()).await??
+        Some(tokio::task::spawn(remote_version()).await??)
     };
 
     let configuration =
         Configuration::load().map_err(|e| eyre!("failed to load configuration: {}", e))?;
+    let default_rpc_url = args.rpc.default_rpc_url(configuration.rpc_url.clone());
+
     match args.sub {
         Subcommands::Disassemble(mut cmd) => {
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
-                cmd.rpc_url = configuration.rpc_url;
+                cmd.rpc_url = default_rpc_url;
             }
 
             // if the user has passed an output filename, override the default filename
-            let mut filename: String = "disassembled.asm".to_string();
+            let mut filename = match cmd.strings {
+                true => format!("strings.{}", cmd.format.extension()),
+                false => format!("disassembled.{}", cmd.format.extension()),
+            };
             let given_name = cmd.name.as_str();
 
             if !given_name.is_empty() {
@@ -58,7 +99,9 @@ async fn main() -> Result<()> {
                 .await
                 .map_err(|e| eyre!("failed to disassemble bytecode: {}", e))?;
 
-            if cmd.output == "print" {
+            if is_json_output(&cmd.output) {
+                print_json("disassemble", json!({ "assembly": assembly }))?;
+            } else if cmd.output == "print" {
                 print_with_less(&assembly)
                     .await
                     .map_err(|e| eyre!("failed to print assembly: {}", e))?;
@@ -73,90 +116,82 @@ async fn main() -> Result<()> {
             }
         }
 
-        Subcommands::Decompile(mut cmd) => {
-            // if the user has not specified a rpc url, use the default
-            if cmd.rpc_url.as_str() == "" {
-                cmd.rpc_url = configuration.rpc_url;
+        Subcommands::Assemble(cmd) => {
+            // if the user has passed an output filename, override the default filename
+            let mut filename = "assembled.hex".to_string();
+            let given_name = cmd.name.as_str();
+
+            if !given_name.is_empty() {
+                filename = format!("{given_name}-{filename}");
             }
 
-            // if the user has not specified a openai api key, use the default
-            if cmd.openai_api_key.as_str() == "" {
-                cmd.openai_api_key = configuration.openai_api_key;
+            let bytecode = assemble(cmd.clone()).map_err(|e| eyre!("failed to assemble: {}", e))?;
+
+            if is_json_output(&cmd.output) {
+                print_json("assemble", json!({ "bytecode": bytecode }))?;
+            } else if cmd.output == "print" {
+                println!("{bytecode}");
+            } else {
+                let output_path = build_output_path(&cmd.output, &cmd.target, "", &filename)
+                    .await
+                    .map_err(|e| eyre!("failed to build output path: {}", e))?;
+
+                write_file(&output_path, &bytecode)
+                    .map_err(|e| eyre!("failed to write bytecode: {}", e))?;
+                info!("wrote bytecode to '{}'", output_path);
             }
+        }
 
-            // if the user has not specified an etherscan api key, use the default
-            if cmd.etherscan_api_key.as_str() == "" {
-                cmd.etherscan_api_key = configuration.etherscan_api_key;
+        Subcommands::Similar(mut cmd) => {
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = default_rpc_url;
             }
 
             // if the user has passed an output filename, override the default filename
-            let mut abi_filename: String = "abi.json".to_string();
-            let mut decompiled_output_filename: String = "decompiled".to_string();
-
+            let mut filename = "similar.txt".to_string();
             let given_name = cmd.name.as_str();
 
             if !given_name.is_empty() {
-                abi_filename = format!("{given_name}-{abi_filename}");
-                decompiled_output_filename = format!("{given_name}-{decompiled_output_filename}");
+                filename = format!("{given_name}-{filename}");
             }
 
-            let result = decompile(cmd.clone())
+            let matches = similar(cmd.clone())
                 .await
-                .map_err(|e| eyre!("failed to decompile bytecode: {}", e))?;
-
-            if cmd.output == "print" {
-                let mut output_str = String::new();
-                output_str
-                    .push_str(&format!("ABI:\n\n{}\n", serde_json::to_string_pretty(&result.abi)?));
-
-                if let Some(source) = &result.source {
-                    output_str.push_str(&format!("Source:\n\n{source}\n"));
-                }
-
-                print_with_less(&output_str)
-                    .await
-                    .map_err(|e| eyre!("failed to print decompiled bytecode: {}", e))?;
+                .map_err(|e| eyre!("failed to search for similar bytecode: {}", e))?;
+
+            if is_json_output(&cmd.output) {
+                let matches = matches
+                    .iter()
+                    .map(|found| {
+                        json!({
+                            "name": found.name,
+                            "score": found.similarity.score,
+                            "selectors": found.similarity.selectors,
+                            "shingles": found.similarity.shingles,
+                            "ngrams": found.similarity.ngrams,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                print_json("similar", json!({ "matches": matches }))?;
+            } else if cmd.output == "print" {
+                print!("{}", format_matches(&matches));
             } else {
-                // write the contract ABI
                 let output_path =
-                    build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &abi_filename)
+                    build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
                         .await
                         .map_err(|e| eyre!("failed to build output path: {}", e))?;
 
-                write_file(&output_path, &serde_json::to_string_pretty(&result.abi)?)
-                    .map_err(|e| eyre!("failed to write ABI: {}", e))?;
-
-                // write the contract source
-                if let Some(source) = &result.source {
-                    let output_path = if cmd.include_solidity {
-                        build_output_path(
-                            &cmd.output,
-                            &cmd.target,
-                            &cmd.rpc_url,
-                            &format!("{}.sol", &decompiled_output_filename),
-                        )
-                        .await
-                        .map_err(|e| eyre!("failed to build output path: {}", e))?
-                    } else {
-                        build_output_path(
-                            &cmd.output,
-                            &cmd.target,
-                            &cmd.rpc_url,
-                            &format!("{}.yul", &decompiled_output_filename,),
-                        )
-                        .await
-                        .map_err(|e| eyre!("failed to build output path: {}", e))?
-                    };
-                    write_file(&output_path, source)
-                        .map_err(|e| eyre!("failed to write source: {}", e))?;
-                }
+                write_file(&output_path, &format_matches(&matches))
+                    .map_err(|e| eyre!("failed to write matches: {}", e))?;
+                info!("wrote matches to '{}'", output_path);
             }
         }
 
-        Subcommands::Decode(mut cmd) => {
+        Subcommands::Decompile(mut cmd) => {
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
-                cmd.rpc_url = configuration.rpc_url;
+                cmd.rpc_url = default_rpc_url;
             }
 
             // if the user has not specified a openai api key, use the default
@@ -164,98 +199,116 @@ async fn main() -> Result<()> {
                 cmd.openai_api_key = configuration.openai_api_key;
             }
 
-            let result =
-                decode(cmd.clone()).await.map_err(|e| eyre!("failed to decode calldata: {}", e))?;
+            // if the user has not specified an etherscan api key, use the default
+            if cmd.etherscan_api_key.as_str() == "" {
+                cmd.etherscan_api_key = configuration.etherscan_api_key;
+            }
 
-            if cmd.output == "print" {
-                result.display()
-            } else {
-                let output_path =
-                    build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, "decoded.json")
-                        .await
-                        .map_err(|e| eyre!("failed to build output path: {}", e))?;
-                write_file(&output_path, &result.to_json()?)
-                    .map_err(|e| eyre!("failed to write decoded output: {}", e))?;
+            // if the user has not specified an explorer api url, use the default
+            if cmd.explorer_api_url.as_str() == "" {
+                cmd.explorer_api_url = configuration.explorer_api_url;
+            }
+
+            // spawn or attach to a local fork to simulate functions on, which is shut down when
+            // it is dropped
+            let _fork = match cmd.validate_onchain {
+                true => args
+                    .fork
+                    .init_fork(&cmd.rpc_url)
+                    .await
+                    .map_err(|e| eyre!("failed to set up fork: {}", e))?,
+                false => None,
+            };
+
+            match cmd.batch.clone() {
+                Some(batch) => decompile_batch(cmd, &batch).await?,
+                None => decompile_target(cmd).await?,
             }
         }
 
-        Subcommands::Cfg(mut cmd) => {
+        Subcommands::Decode(mut cmd) => {
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
-                cmd.rpc_url = configuration.rpc_url;
+                cmd.rpc_url = default_rpc_url;
             }
 
-            // if the user has passed an output filename, override the default filename
-            let mut filename = "cfg.dot".to_string();
-            let given_name = cmd.name.as_str();
+            // if the user has not specified a openai api key, use the default
+            if cmd.openai_api_key.as_str() == "" {
+                cmd.openai_api_key = configuration.openai_api_key;
+            }
 
-            if !given_name.is_empty() {
-                filename = format!("{given_name}-{filename}");
+            // if the user has not specified an etherscan api key, use the default
+            if cmd.etherscan_api_key.as_str() == "" {
+                cmd.etherscan_api_key = configuration.etherscan_api_key;
             }
-            let cfg = cfg(cmd.clone()).await.map_err(|e| eyre!("failed to generate cfg: {}", e))?;
-            let stringified_dot = cfg.as_dot(cmd.color_edges);
 
-            if cmd.output == "print" {
-                print_with_less(&stringified_dot)
-                    .await
-                    .map_err(|e| eyre!("failed to print cfg: {}", e))?;
-            } else {
-                let output_path =
-                    build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
-                        .await
-                        .map_err(|e| eyre!("failed to build output path: {}", e))?;
-                write_file(&output_path, &stringified_dot)
-                    .map_err(|e| eyre!("failed to write cfg: {}", e))?;
+            // if the user has not specified an explorer api url, use the default
+            if cmd.explorer_api_url.as_str() == "" {
+                cmd.explorer_api_url = configuration.explorer_api_url;
+            }
+
+            match cmd.batch.clone() {
+                Some(batch) => decode_batch(cmd, &batch).await?,
+                None => decode_target(cmd).await?,
             }
         }
 
-        Subcommands::Dump(mut cmd) => {
+        Subcommands::Diff(mut cmd) => {
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
-                cmd.rpc_url = configuration.rpc_url;
+                cmd.rpc_url = default_rpc_url;
             }
 
-            // if the user has passed an output filename, override the default filename
-            let mut filename = "dump.csv".to_string();
-            let given_name = cmd.name.as_str();
+            diff_targets(cmd).await?;
+        }
 
-            if !given_name.is_empty() {
-                filename = format!("{given_name}-{filename}");
+        Subcommands::Cfg(mut cmd) => {
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = default_rpc_url;
             }
 
-            let result =
-                dump(cmd.clone()).await.map_err(|e| eyre!("failed to dump storage: {}", e))?;
-            let mut lines = Vec::new();
-
-            // add header
-            lines.push(String::from("slot,value"));
+            match cmd.diff.is_empty() {
+                true => cfg_target(cmd).await?,
+                false => cfg_diff_target(cmd).await?,
+            }
+        }
 
-            // add rows
-            for (slot, value) in result {
-                lines.push(format!("{},{}", slot.to_lower_hex(), value.to_lower_hex()));
+        Subcommands::Dump(mut cmd) => {
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = default_rpc_url;
             }
 
-            if cmd.output == "print" {
-                print_with_less(&lines.join("\n"))
-                    .await
-                    .map_err(|e| eyre!("failed to print dump: {}", e))?;
-            } else {
-                let output_path =
-                    build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
-                        .await
-                        .map_err(|e| eyre!("failed to build output path: {}", e))?;
+            dump_target(cmd).await?;
+        }
 
-                write_file(&output_path, &lines.join("\n"))
-                    .map_err(|e| eyre!("failed to write dump: {}", e))?;
+        Subcommands::SnapshotToken(mut cmd) => {
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = default_rpc_url;
             }
+
+            snapshot_token_target(cmd).await?;
         }
 
         Subcommands::Inspect(mut cmd) => {
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
-                cmd.rpc_url = configuration.rpc_url;
+                cmd.rpc_url = default_rpc_url;
             }
 
+            // spawn or attach to a local fork to simulate the transaction on, which is shut down
+            // when it is dropped
+            let _fork = match cmd.is_simulation() {
+                true => args
+                    .fork
+                    .init_fork(&cmd.rpc_url)
+                    .await
+                    .map_err(|e| eyre!("failed to set up fork: {}", e))?,
+                false => None,
+            };
+
             // if the user has not specified a transpose api key, use the default
             if cmd.transpose_api_key.is_empty() {
                 cmd.transpose_api_key = configuration.transpose_api_key;
@@ -272,9 +325,11 @@ async fn main() -> Result<()> {
             let inspect_result = inspect(cmd.clone())
                 .await
                 .map_err(|e| eyre!("failed to inspect transaction: {}", e))?;
-            inspect_result.display();
 
-            if cmd.output == "print" {
+            if is_json_output(&cmd.output) {
+                print_json("inspect", inspect_json(&inspect_result))?;
+            } else if cmd.output == "print" {
+                inspect_result.display();
                 let mut output_str = String::new();
 
                 output_str.push_str(&format!(
@@ -286,6 +341,8 @@ async fn main() -> Result<()> {
                     .await
                     .map_err(|e| eyre!("failed to print decoded trace: {}", e))?;
             } else {
+                inspect_result.display();
+
                 // write decoded trace with serde
                 let output_path =
                     build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
@@ -298,6 +355,47 @@ async fn main() -> Result<()> {
                 )
                 .map_err(|e| eyre!("failed to write decoded trace: {}", e))?;
             }
+
+            // write the gas profile as a flamegraph, if requested
+            if let (Some(path), Some(gas_profile)) = (&cmd.flamegraph, &inspect_result.gas_profile)
+            {
+                // simulated transactions have no hash to title the flamegraph with
+                let target = match cmd.target.parse::<TxHash>() {
+                    Ok(_) => cmd.target.as_str(),
+                    Err(_) => "simulation",
+                };
+                let flamegraph = match path.ends_with(".svg") {
+                    true => gas_profile.flamegraph(&format!("heimdall inspect {target}")),
+                    false => gas_profile.folded(),
+                };
+                write_file(path, &flamegraph)
+                    .map_err(|e| eyre!("failed to write flamegraph: {}", e))?;
+                info!("wrote flamegraph to '{}'", path);
+            }
+        }
+
+        Subcommands::Serve(mut cmd) => {
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = default_rpc_url;
+            }
+
+            // if the user has not specified an etherscan api key, use the default
+            if cmd.etherscan_api_key.as_str() == "" {
+                cmd.etherscan_api_key = configuration.etherscan_api_key;
+            }
+
+            // if the user has not specified an explorer api url, use the default
+            if cmd.explorer_api_url.as_str() == "" {
+                cmd.explorer_api_url = configuration.explorer_api_url;
+            }
+
+            // if the user has not specified a transpose api key, use the default
+            if cmd.transpose_api_key.is_empty() {
+                cmd.transpose_api_key = configuration.transpose_api_key;
+            }
+
+            serve(cmd).await.map_err(|e| eyre!("failed to serve: {}", e))?;
         }
 
         Subcommands::Config(cmd) => {
@@ -307,15 +405,30 @@ async fn main() -> Result<()> {
         Subcommands::Cache(cmd) => {
             cache(cmd).map_err(|e| eyre!("failed to manage cache: {}", e))?;
         }
+
+        Subcommands::Sigs(cmd) => {
+            sigs(cmd).map_err(|e| eyre!("failed to manage signatures: {}", e))?;
+        }
+
+        Subcommands::Labels(cmd) => {
+            labels(cmd).map_err(|e| eyre!("failed to manage labels: {}", e))?;
+        }
+    }
+
+    // write the metrics recorded during this run, if requested
+    if let Some(path) = &args.metrics_file {
+        write_metrics_file(path).map_err(|e| eyre!("failed to write metrics: {}", e))?;
     }
 
     // check if the version is up to date
-    if current_version.is_nightly() && current_version.ne(&remote_ver) {
-        info!("great news! A new nightly build is available!");
-        info!("you can update now by running: `bifrost +nightly`");
-    } else if remote_ver.gt(&current_version) {
-        info!("great news! An update is available!");
-        info!("you can update now by running: `bifrost --version {}`", remote_ver);
+    if let Some(remote_ver) = remote_ver {
+        if current_version.is_nightly() && current_version.ne(&remote_ver) {
+            info!("great news! A new nightly build is available!");
+            info!("you can update now by running: `bifrost +nightly`");
+        } else if remote_ver.gt(&current_version) {
+            info!("great news! An update is available!");
+            info!("you can update now by running: `bifrost --version {}`", remote_ver);
+        }
     }
 
     Ok(())
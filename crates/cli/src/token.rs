@@ -0,0 +1,102 @@
+//! Snapshots the token of the snapshot-token command, and writes the snapshot.
+
+use eyre::{eyre, Result};
+use heimdall_common::utils::io::file::write_file;
+use heimdall_core::heimdall_dump::{snapshot_token, SnapshotTokenArgs, TokenSnapshot};
+use tracing::info;
+
+use crate::{
+    json::{is_json_output, print_json, snapshot_token_json},
+    output::{build_output_path, print_with_less},
+};
+
+/// Snapshots the target token's balances (or owners), and writes them.
+pub(crate) async fn snapshot_token_target(cmd: SnapshotTokenArgs) -> Result<()> {
+    // if the user has passed an output filename, override the default filename
+    let mut filename = "snapshot.csv".to_string();
+    let given_name = cmd.name.as_str();
+
+    if !given_name.is_empty() {
+        filename = format!("{given_name}-{filename}");
+    }
+
+    let snapshot =
+        snapshot_token(cmd.clone()).await.map_err(|e| eyre!("failed to snapshot token: {}", e))?;
+    if is_json_output(&cmd.output) {
+        print_json("snapshot-token", snapshot_token_json(&snapshot))?;
+    } else if cmd.output == "print" {
+        print_with_less(&snapshot_csv(&snapshot))
+            .await
+            .map_err(|e| eyre!("failed to print snapshot: {}", e))?;
+    } else {
+        let output_path = build_output_path(&cmd.output, &cmd.target, &cmd.rpc_url, &filename)
+            .await
+            .map_err(|e| eyre!("failed to build output path: {}", e))?;
+        write_file(&output_path, &snapshot_csv(&snapshot))
+            .map_err(|e| eyre!("failed to write snapshot: {}", e))?;
+        info!("wrote snapshot to '{}'", output_path);
+    }
+
+    Ok(())
+}
+
+/// The snapshot as CSV, with a row for each holder of an ERC-20, or each token of an ERC-721.
+/// Storage values which couldn't be read are left empty.
+fn snapshot_csv(snapshot: &TokenSnapshot) -> String {
+    let mut lines = Vec::new();
+    if snapshot.owners.is_empty() {
+        lines.push("holder,balance,log_balance,storage_balance,discrepancy".to_string());
+        lines.extend(snapshot.holders.iter().map(|holder| {
+            format!(
+                "{},{},{},{},{}",
+                holder.holder,
+                holder.balance,
+                holder.log_balance,
+                holder.storage_balance.map(|balance| balance.to_string()).unwrap_or_default(),
+                holder.is_discrepancy()
+            )
+        }));
+    } else {
+        lines.push("token_id,owner,log_owner,storage_owner,discrepancy".to_string());
+        lines.extend(snapshot.owners.iter().map(|token| {
+            format!(
+                "{},{},{},{},{}",
+                token.token_id,
+                token.owner,
+                token.log_owner,
+                token.storage_owner.map(|owner| owner.to_string()).unwrap_or_default(),
+                token.is_discrepancy()
+            )
+        }));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, I256, U256};
+    use heimdall_core::heimdall_dump::{TokenHolder, TokenStandard};
+
+    use super::*;
+
+    #[test]
+    fn test_snapshot_csv() {
+        let snapshot = TokenSnapshot {
+            standard: TokenStandard::Erc20,
+            block: 100,
+            balance_slot: Some(U256::ZERO),
+            holders: vec![TokenHolder {
+                holder: Address::repeat_byte(0x11),
+                balance: U256::from(99),
+                log_balance: I256::try_from(100).expect("invalid balance"),
+                storage_balance: Some(U256::from(99)),
+            }],
+            owners: Vec::new(),
+        };
+
+        let csv = snapshot_csv(&snapshot);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("holder,balance,log_balance,storage_balance,discrepancy"));
+        assert_eq!(lines.next(), Some("0x1111111111111111111111111111111111111111,99,100,99,true"));
+    }
+}
@@ -0,0 +1,183 @@
+//! Beacon API utilities for fetching the EIP-4844 blobs carried by blob transactions.
+
+use alloy::{eips::eip4844::kzg_to_versioned_hash, primitives::B256};
+use eyre::{bail, eyre, OptionExt, Result};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::utils::{http::get_json_from_url, strings::decode_hex};
+
+/// The number of seconds in each beacon chain slot.
+pub const SECONDS_PER_SLOT: u64 = 12;
+
+/// The number of bytes in each of a blob's field elements.
+const BYTES_PER_FIELD_ELEMENT: usize = 32;
+
+/// A blob carried by a blob transaction, as published in its block's sidecars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blob {
+    /// The index of the blob in its block.
+    pub index: u64,
+    /// The versioned hash of the blob's KZG commitment, as referenced by the transaction.
+    pub versioned_hash: B256,
+    /// The raw blob, i.e. each of its 4096 field elements.
+    pub data: Vec<u8>,
+}
+
+impl Blob {
+    /// The contents of the blob, as written by the sender. See [`blob_contents`].
+    pub fn contents(&self) -> Vec<u8> {
+        blob_contents(&self.data)
+    }
+}
+
+/// Unpacks the contents of a raw blob, as written by the sender.
+///
+/// Each field element must be less than the BLS modulus, so the high byte of each element is left
+/// empty and the contents are packed into the remaining 31 bytes. Blobs are padded to their full
+/// size with zeroes, which are trimmed.
+pub fn blob_contents(blob: &[u8]) -> Vec<u8> {
+    let mut contents = blob
+        .chunks(BYTES_PER_FIELD_ELEMENT)
+        .flat_map(|element| element.iter().skip(1))
+        .copied()
+        .collect::<Vec<_>>();
+    let length = contents.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+    contents.truncate(length);
+    contents
+}
+
+/// Get the slot of the block with the given timestamp.
+fn slot_at(genesis_time: u64, timestamp: u64) -> Result<u64> {
+    if timestamp < genesis_time {
+        bail!("block timestamp {} is before the beacon chain's genesis", timestamp);
+    }
+    Ok((timestamp - genesis_time) / SECONDS_PER_SLOT)
+}
+
+/// Parse a `/eth/v1/beacon/blob_sidecars` response, returning the blobs with the given versioned
+/// hashes, in the order they're given.
+fn parse_blob_sidecars(response: &Value, versioned_hashes: &[B256]) -> Result<Vec<Blob>> {
+    let sidecars = response
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or_eyre("beacon response has no blob sidecars")?;
+
+    let blobs = sidecars
+        .iter()
+        .map(|sidecar| {
+            let field = |name: &str| {
+                sidecar
+                    .get(name)
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| eyre!("blob sidecar has no {}", name))
+            };
+            let commitment = decode_hex(field("kzg_commitment")?)?;
+            if commitment.len() != 48 {
+                bail!("invalid kzg commitment length {}", commitment.len());
+            }
+
+            Ok(Blob {
+                index: field("index")?.parse()?,
+                versioned_hash: kzg_to_versioned_hash(&commitment),
+                data: decode_hex(field("blob")?)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    versioned_hashes
+        .iter()
+        .map(|versioned_hash| {
+            blobs
+                .iter()
+                .find(|blob| blob.versioned_hash == *versioned_hash)
+                .cloned()
+                .ok_or_else(|| eyre!("no blob sidecar found for {}", versioned_hash))
+        })
+        .collect()
+}
+
+/// Fetch the blobs with the given versioned hashes from the beacon node at `beacon_url`, given the
+/// timestamp of the block which included them.
+///
+/// Beacon nodes only keep blobs for around 18 days, so older blobs may no longer be available.
+///
+/// ```no_run
+/// use heimdall_common::ether::beacon::get_blobs;
+///
+/// // let blobs = get_blobs("http://localhost:5052", 1710338135, &[versioned_hash]).await;
+/// ```
+pub async fn get_blobs(
+    beacon_url: &str,
+    block_timestamp: u64,
+    versioned_hashes: &[B256],
+) -> Result<Vec<Blob>> {
+    let beacon_url = beacon_url.trim_end_matches('/');
+
+    let genesis = get_json_from_url(&format!("{beacon_url}/eth/v1/beacon/genesis"), 10)
+        .await?
+        .ok_or_eyre("failed to fetch genesis from beacon node")?;
+    let genesis_time = genesis
+        .pointer("/data/genesis_time")
+        .and_then(Value::as_str)
+        .ok_or_eyre("beacon response has no genesis time")?
+        .parse()?;
+
+    let slot = slot_at(genesis_time, block_timestamp)?;
+    debug!("fetching blob sidecars for slot {}", slot);
+    let sidecars = get_json_from_url(
+        &format!("{beacon_url}/eth/v1/beacon/blob_sidecars/{slot}"),
+        30,
+    )
+    .await?
+    .ok_or_else(|| eyre!("failed to fetch blob sidecars for slot {} from beacon node", slot))?;
+
+    parse_blob_sidecars(&sidecars, versioned_hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_blob_contents() {
+        let mut data = vec![0u8; 4 * BYTES_PER_FIELD_ELEMENT];
+        data[1..32].copy_from_slice(&[b'a'; 31]);
+        data[33..36].copy_from_slice(b"bcd");
+        let blob = Blob { index: 0, versioned_hash: B256::ZERO, data };
+
+        let mut expected = vec![b'a'; 31];
+        expected.extend_from_slice(b"bcd");
+        assert_eq!(blob.contents(), expected);
+    }
+
+    #[test]
+    fn test_slot_at() {
+        assert_eq!(slot_at(1606824023, 1606824023).expect("failed to get slot"), 0);
+        assert_eq!(slot_at(1606824023, 1606824023 + 25).expect("failed to get slot"), 2);
+        assert!(slot_at(1606824023, 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_blob_sidecars() {
+        let commitment = format!("0x{}", "ab".repeat(48));
+        let versioned_hash = kzg_to_versioned_hash(&[0xab; 48]);
+        let response = json!({
+            "data": [{
+                "index": "3",
+                "blob": "0x0061",
+                "kzg_commitment": commitment,
+            }]
+        });
+
+        let blobs =
+            parse_blob_sidecars(&response, &[versioned_hash]).expect("failed to parse sidecars");
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].index, 3);
+        assert_eq!(blobs[0].contents(), b"a");
+
+        assert!(parse_blob_sidecars(&response, &[B256::ZERO]).is_err());
+    }
+}
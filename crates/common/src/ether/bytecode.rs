@@ -1,12 +1,18 @@
 //! Functions for working with Ethereum bytecode.
 
-use crate::utils::strings::decode_hex;
-
-use super::{etherscan::get_creation_bytecode, rpc::get_code};
-use alloy::primitives::{bytes::Bytes, Address};
-use eyre::{eyre, Result};
-use std::fs;
-use tracing::{debug, info, warn};
+use alloy::primitives::bytes::Bytes;
+use eyre::Result;
+
+// fetching bytecode from the chain isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    super::{etherscan::get_creation_bytecode, rpc::get_code_at},
+    crate::utils::{offline::is_offline, strings::decode_hex},
+    alloy::{eips::BlockId, primitives::Address},
+    eyre::eyre,
+    std::fs,
+    tracing::{debug, info, warn},
+};
 
 /// Given a target, return bytecode of the target.
 ///
@@ -17,14 +23,34 @@ use tracing::{debug, info, warn};
 ///
 /// For self-destructed contracts, if an Etherscan API key is configured and the chain is supported,
 /// this function will attempt to fetch the creation bytecode from the deployment transaction.
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn get_bytecode_from_target(
     target: &str,
     rpc_url: &str,
     etherscan_api_key: &str,
+) -> Result<Vec<u8>> {
+    get_bytecode_from_target_at(target, None, rpc_url, etherscan_api_key).await
+}
+
+/// Given a target, return bytecode of the target as it existed at the given block.
+///
+/// This behaves like [`get_bytecode_from_target`], except that contract addresses are resolved
+/// at `block` rather than the latest block. `block` is ignored for raw bytecode and file targets.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn get_bytecode_from_target_at(
+    target: &str,
+    block: Option<BlockId>,
+    rpc_url: &str,
+    etherscan_api_key: &str,
 ) -> Result<Vec<u8>> {
     // If the target is an address, fetch the bytecode from the RPC provider.
     if let Ok(address) = target.parse::<Address>() {
-        if let Ok(bytecode) = get_code(address, rpc_url).await {
+        let bytecode = get_code_at(address, block, rpc_url).await;
+        if let (Err(e), true) = (&bytecode, is_offline()) {
+            return Err(eyre!("failed to get bytecode for {address} from the cache: {e}"));
+        }
+
+        if let Ok(bytecode) = bytecode {
             if !bytecode.is_empty() {
                 return Ok(bytecode);
             }
@@ -0,0 +1,300 @@
+//! A registry of known chains, with their default public RPCs, block explorers and native
+//! currencies.
+
+use crate::constants::*;
+
+/// The unified Etherscan V2 API endpoint, which serves every Etherscan-family explorer through
+/// its `chainid` parameter.
+pub const ETHERSCAN_V2_API_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// The native currency of a chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NativeCurrency {
+    /// The name of the currency, e.g. `Ether`.
+    pub name: &'static str,
+    /// The ticker symbol of the currency, e.g. `ETH`.
+    pub symbol: &'static str,
+    /// The number of decimals of the currency.
+    pub decimals: u8,
+}
+
+/// A known chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chain {
+    /// The chain id.
+    pub id: u64,
+    /// The canonical name of the chain, e.g. `polygon`.
+    pub name: &'static str,
+    /// Other names which the chain may be referred to by, e.g. `matic`.
+    pub aliases: &'static [&'static str],
+    /// Default public RPC urls, in order of preference.
+    pub rpc_urls: &'static [&'static str],
+    /// The url of the chain's block explorer.
+    pub explorer_url: &'static str,
+    /// The Etherscan-compatible API of the chain's block explorer, if any.
+    pub explorer_api_url: Option<&'static str>,
+    /// The chain's native currency.
+    pub native_currency: NativeCurrency,
+}
+
+impl Chain {
+    /// The default rpc url of the chain, as a comma-separated list so requests fail over to the
+    /// next public RPC when one errors.
+    pub fn default_rpc_url(&self) -> String {
+        self.rpc_urls.join(",")
+    }
+
+    /// Whether the chain is referred to by the given name, ignoring case.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name) ||
+            self.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+}
+
+/// All known chains.
+pub static CHAINS: &[Chain] = &[
+    Chain {
+        id: CHAIN_ID_ETHEREUM,
+        name: "ethereum",
+        aliases: &["mainnet", "eth"],
+        rpc_urls: &["https://eth.llamarpc.com", "https://ethereum-rpc.publicnode.com"],
+        explorer_url: "https://etherscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_SEPOLIA,
+        name: "sepolia",
+        aliases: &[],
+        rpc_urls: &["https://ethereum-sepolia-rpc.publicnode.com"],
+        explorer_url: "https://sepolia.etherscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Sepolia Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_HOLESKY,
+        name: "holesky",
+        aliases: &[],
+        rpc_urls: &["https://ethereum-holesky-rpc.publicnode.com"],
+        explorer_url: "https://holesky.etherscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Holesky Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_POLYGON,
+        name: "polygon",
+        aliases: &["matic"],
+        rpc_urls: &["https://polygon-rpc.com", "https://polygon-bor-rpc.publicnode.com"],
+        explorer_url: "https://polygonscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "POL", symbol: "POL", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_POLYGON_AMOY,
+        name: "polygon-amoy",
+        aliases: &["amoy"],
+        rpc_urls: &["https://rpc-amoy.polygon.technology"],
+        explorer_url: "https://amoy.polygonscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "POL", symbol: "POL", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_BSC,
+        name: "bsc",
+        aliases: &["bnb", "binance"],
+        rpc_urls: &["https://bsc-dataseed.bnbchain.org"],
+        explorer_url: "https://bscscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "BNB", symbol: "BNB", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_BSC_TESTNET,
+        name: "bsc-testnet",
+        aliases: &["bnb-testnet"],
+        rpc_urls: &["https://data-seed-prebsc-1-s1.bnbchain.org:8545"],
+        explorer_url: "https://testnet.bscscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Test BNB", symbol: "tBNB", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_ARBITRUM,
+        name: "arbitrum",
+        aliases: &["arbitrum-one", "arb"],
+        rpc_urls: &["https://arb1.arbitrum.io/rpc"],
+        explorer_url: "https://arbiscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_ARBITRUM_SEPOLIA,
+        name: "arbitrum-sepolia",
+        aliases: &[],
+        rpc_urls: &["https://sepolia-rollup.arbitrum.io/rpc"],
+        explorer_url: "https://sepolia.arbiscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_OPTIMISM,
+        name: "optimism",
+        aliases: &["op"],
+        rpc_urls: &["https://mainnet.optimism.io"],
+        explorer_url: "https://optimistic.etherscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_OPTIMISM_SEPOLIA,
+        name: "optimism-sepolia",
+        aliases: &[],
+        rpc_urls: &["https://sepolia.optimism.io"],
+        explorer_url: "https://sepolia-optimism.etherscan.io",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_AVALANCHE,
+        name: "avalanche",
+        aliases: &["avax"],
+        rpc_urls: &["https://api.avax.network/ext/bc/C/rpc"],
+        explorer_url: "https://snowscan.xyz",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Avalanche", symbol: "AVAX", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_AVALANCHE_FUJI,
+        name: "avalanche-fuji",
+        aliases: &["fuji"],
+        rpc_urls: &["https://api.avax-test.network/ext/bc/C/rpc"],
+        explorer_url: "https://testnet.snowscan.xyz",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Avalanche", symbol: "AVAX", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_FANTOM,
+        name: "fantom",
+        aliases: &["ftm"],
+        rpc_urls: &["https://rpc.ftm.tools"],
+        explorer_url: "https://ftmscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Fantom", symbol: "FTM", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_BASE,
+        name: "base",
+        aliases: &[],
+        rpc_urls: &["https://mainnet.base.org"],
+        explorer_url: "https://basescan.org",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_BASE_SEPOLIA,
+        name: "base-sepolia",
+        aliases: &[],
+        rpc_urls: &["https://sepolia.base.org"],
+        explorer_url: "https://sepolia.basescan.org",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_LINEA,
+        name: "linea",
+        aliases: &[],
+        rpc_urls: &["https://rpc.linea.build"],
+        explorer_url: "https://lineascan.build",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_SCROLL,
+        name: "scroll",
+        aliases: &[],
+        rpc_urls: &["https://rpc.scroll.io"],
+        explorer_url: "https://scrollscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_ZKSYNC,
+        name: "zksync",
+        aliases: &["zksync-era"],
+        rpc_urls: &["https://mainnet.era.zksync.io"],
+        explorer_url: "https://era.zksync.network",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+    Chain {
+        id: CHAIN_ID_POLYGON_ZKEVM,
+        name: "polygon-zkevm",
+        aliases: &[],
+        rpc_urls: &["https://zkevm-rpc.com"],
+        explorer_url: "https://zkevm.polygonscan.com",
+        explorer_api_url: Some(ETHERSCAN_V2_API_URL),
+        native_currency: NativeCurrency { name: "Ether", symbol: "ETH", decimals: 18 },
+    },
+];
+
+/// Get the known chain with the given chain id.
+///
+/// ```
+/// use heimdall_common::ether::chains::chain_by_id;
+///
+/// assert_eq!(chain_by_id(137).map(|chain| chain.name), Some("polygon"));
+/// assert!(chain_by_id(999999).is_none());
+/// ```
+pub fn chain_by_id(id: u64) -> Option<&'static Chain> {
+    CHAINS.iter().find(|chain| chain.id == id)
+}
+
+/// Get the known chain with the given name, alias or chain id, ignoring case.
+///
+/// ```
+/// use heimdall_common::ether::chains::lookup_chain;
+///
+/// assert_eq!(lookup_chain("Polygon").map(|chain| chain.id), Some(137));
+/// assert_eq!(lookup_chain("matic").map(|chain| chain.id), Some(137));
+/// assert_eq!(lookup_chain("8453").map(|chain| chain.name), Some("base"));
+/// assert!(lookup_chain("unknown").is_none());
+/// ```
+pub fn lookup_chain(name_or_id: &str) -> Option<&'static Chain> {
+    let name_or_id = name_or_id.trim();
+    match name_or_id.parse::<u64>() {
+        Ok(id) => chain_by_id(id),
+        Err(_) => CHAINS.iter().find(|chain| chain.is_named(name_or_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etherscan_chains_are_registered() {
+        for id in ETHERSCAN_SUPPORTED_CHAIN_IDS {
+            let chain = chain_by_id(id).expect("etherscan chain missing from registry");
+            assert_eq!(chain.explorer_api_url, Some(ETHERSCAN_V2_API_URL));
+        }
+    }
+
+    #[test]
+    fn test_chain_names_are_unique() {
+        for chain in CHAINS {
+            assert!(!chain.rpc_urls.is_empty());
+            for other in CHAINS.iter().filter(|other| other.id != chain.id) {
+                assert!(!other.is_named(chain.name));
+                assert!(!chain.aliases.iter().any(|alias| other.is_named(alias)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_rpc_url() {
+        let chain = lookup_chain("ethereum").expect("ethereum missing from registry");
+        assert_eq!(
+            chain.default_rpc_url(),
+            "https://eth.llamarpc.com,https://ethereum-rpc.publicnode.com"
+        );
+        assert_eq!(chain.native_currency.symbol, "ETH");
+    }
+}
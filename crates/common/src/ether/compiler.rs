@@ -2,7 +2,10 @@
 
 use std::fmt::Display;
 
-use crate::{ether::bytecode::remove_pushbytes_from_bytecode, utils::iter::ByteSliceExt};
+use crate::{
+    ether::{bytecode::remove_pushbytes_from_bytecode, metadata::decode_metadata},
+    utils::iter::ByteSliceExt,
+};
 use tracing::{debug, trace, warn};
 
 /// Compiler enum to represent the compiler used to compile the contract.
@@ -29,6 +32,27 @@ impl Display for Compiler {
     }
 }
 
+impl Compiler {
+    /// Whether contracts compiled with the given version of this compiler have their arithmetic
+    /// checked for overflows by the compiler. solc only does so since 0.8.0, before which contracts
+    /// checked it themselves, e.g. with SafeMath. Versions which aren't exact are assumed to.
+    ///
+    /// ```
+    /// use heimdall_common::ether::compiler::Compiler;
+    ///
+    /// assert!(Compiler::Solc.checks_arithmetic("0.8.28"));
+    /// assert!(!Compiler::Solc.checks_arithmetic("0.6.12"));
+    /// assert!(Compiler::Solc.checks_arithmetic("0.4.22+"));
+    /// ```
+    pub fn checks_arithmetic(&self, version: &str) -> bool {
+        let parts = version.split('.').map(|part| part.parse::<u64>()).collect::<Vec<_>>();
+        match (self, parts.as_slice()) {
+            (Compiler::Solc, [Ok(major), Ok(minor), Ok(_)]) => (*major, *minor) >= (0, 8),
+            _ => true,
+        }
+    }
+}
+
 /// returns the compiler version used to compile the contract.
 /// for example: (solc, 0.8.10) or (vyper, 0.2.16)
 ///
@@ -50,6 +74,9 @@ pub fn detect_compiler(bytecode: &[u8]) -> (Compiler, String) {
     } else if bytecode.starts_with(&[0x34, 0x15, 0x61, 0x00, 0x0a]) {
         compiler = Compiler::Vyper;
         version = "0.2.5-0.2.8".to_string();
+    } else if bytecode.starts_with(&[0x5f, 0x35, 0x60, 0xe0, 0x1c]) {
+        compiler = Compiler::Vyper;
+        version = "0.3.8+".to_string();
     } else if bytecode.starts_with(&[0x73, 0x1b, 0xf7, 0x97]) {
         compiler = Compiler::Solc;
         version = "0.4.10-0.4.24".to_string();
@@ -110,10 +137,19 @@ pub fn detect_compiler(bytecode: &[u8]) -> (Compiler, String) {
         compiler = Compiler::Vyper;
     }
 
-    // Previously known heuristic: check for cbor encoded compiler metadata
-    // check for cbor encoded compiler metadata
+    // prefer the version in the metadata trailer, which is exact, falling back to searching the
+    // bytecode for cbor encoded compiler metadata
     // https://cbor.io
-    if bytecode.contains_slice(&[0x73, 0x6f, 0x6c, 0x63, 0x43]) {
+    let metadata = decode_metadata(bytecode);
+    if let Some(solc) = metadata.as_ref().and_then(|m| m.solc.clone()) {
+        compiler = Compiler::Solc;
+        version = solc;
+        trace!("exact compiler version match found due to metadata trailer: {}", version);
+    } else if let Some(vyper) = metadata.as_ref().and_then(|m| m.vyper.clone()) {
+        compiler = Compiler::Vyper;
+        version = vyper;
+        trace!("exact compiler version match found due to metadata trailer: {}", version);
+    } else if bytecode.contains_slice(&[0x73, 0x6f, 0x6c, 0x63, 0x43]) {
         let compiler_version = bytecode.split_by_slice(&[0x73, 0x6f, 0x6c, 0x63, 0x43]);
 
         if compiler_version.len() > 1 {
@@ -205,6 +241,13 @@ mod test_compiler {
         assert_eq!(detect_compiler(bytecode), expected_result);
     }
 
+    #[test]
+    fn test_detect_compiler_vyper_push0_dispatcher() {
+        let bytecode = &[0x5f, 0x35, 0x60, 0xe0, 0x1c];
+        let expected_result = (Compiler::Vyper, "0.3.8+".to_string());
+        assert_eq!(detect_compiler(bytecode), expected_result);
+    }
+
     #[test]
     fn test_detect_compiler_vyper() {
         let bytecode = &[0x76, 0x79, 0x70, 0x65, 0x72];
@@ -239,4 +282,15 @@ mod test_compiler {
         let expected_result = (Compiler::Vyper, "49.53.53".to_string());
         assert_eq!(detect_compiler(bytecode), expected_result);
     }
+
+    #[test]
+    fn test_detect_compiler_metadata_trailer() {
+        // PUSH1 0x80 PUSH1 0x40 MSTORE, followed by {"solc": 0x000611}
+        let bytecode = &[
+            0x60, 0x80, 0x60, 0x40, 0x52, 0xa1, 0x64, 0x73, 0x6f, 0x6c, 0x63, 0x43, 0x00, 0x06,
+            0x11, 0x00, 0x0a,
+        ];
+        let expected_result = (Compiler::Solc, "0.6.17".to_string());
+        assert_eq!(detect_compiler(bytecode), expected_result);
+    }
 }
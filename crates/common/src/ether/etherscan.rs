@@ -1,7 +1,7 @@
 //! Etherscan API utilities for fetching contract information.
 
-use super::rpc::get_transaction;
-use crate::constants::ETHERSCAN_SUPPORTED_CHAIN_IDS;
+use super::{chains::chain_by_id, rpc::get_transaction};
+use crate::utils::offline::ensure_online;
 use alloy::{
     consensus::Transaction,
     primitives::{Address, TxHash},
@@ -27,9 +27,14 @@ struct EtherscanContractCreation {
     tx_hash: String,
 }
 
-/// Check if the chain ID is supported by Etherscan V2 API
+/// Check if the chain ID has an Etherscan-compatible explorer API in the chain registry
 pub fn is_supported_chain(chain_id: u64) -> bool {
-    ETHERSCAN_SUPPORTED_CHAIN_IDS.contains(&chain_id)
+    explorer_api_url(chain_id).is_some()
+}
+
+/// The Etherscan-compatible explorer API of the given chain, if any
+fn explorer_api_url(chain_id: u64) -> Option<&'static str> {
+    chain_by_id(chain_id).and_then(|chain| chain.explorer_api_url)
 }
 
 /// Fetch the contract creation transaction hash from Etherscan V2 API.
@@ -41,16 +46,16 @@ async fn get_contract_creation_tx(
     chain_id: u64,
     api_key: &str,
 ) -> Result<TxHash> {
-    if !is_supported_chain(chain_id) {
+    let Some(api_url) = explorer_api_url(chain_id) else {
         return Err(eyre!("etherscan API not supported for chain ID {}", chain_id));
-    }
+    };
 
-    // Use Etherscan V2 API - unified endpoint for all supported chains
     let url = format!(
-        "https://api.etherscan.io/v2/api?chainid={}&module=contract&action=getcontractcreation&contractaddresses={}&apikey={}",
-        chain_id, address, api_key
+        "{}?chainid={}&module=contract&action=getcontractcreation&contractaddresses={}&apikey={}",
+        api_url, chain_id, address, api_key
     );
 
+    ensure_online("query etherscan")?;
     let response: EtherscanContractCreationResponse = reqwest::get(&url).await?.json().await?;
 
     if response.status != "1" {
@@ -114,7 +119,10 @@ pub async fn get_contract_creation_block(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::{CHAIN_ID_ARBITRUM, CHAIN_ID_BASE, CHAIN_ID_ETHEREUM, CHAIN_ID_POLYGON};
+    use crate::constants::{
+        CHAIN_ID_ARBITRUM, CHAIN_ID_BASE, CHAIN_ID_ETHEREUM, CHAIN_ID_POLYGON,
+        ETHERSCAN_SUPPORTED_CHAIN_IDS,
+    };
 
     #[test]
     fn test_is_supported_chain() {
@@ -0,0 +1,820 @@
+//! Record rpc responses to a JSON fixture, and replay them later without a node.
+//!
+//! A [`RecordingProvider`] forwards every request to a real provider and records the request and
+//! its response, writing them to a fixture file when flushed. A [`ReplayProvider`] serves the
+//! responses in a fixture back, which makes bug reports and end-to-end tests reproducible without
+//! hitting a live node.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, Bytes, TxHash, U256},
+    rpc::types::{
+        trace::{
+            geth::{GethDebugTracingOptions, GethTrace, TraceResult},
+            parity::{TraceResults, TraceResultsWithTransactionHash, TraceType},
+        },
+        Filter, Log, Transaction, TransactionRequest,
+    },
+};
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use hashbrown::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{debug, error};
+
+use crate::{
+    ether::provider::{
+        Capabilities, HeimdallProvider, MultiTransportProvider, ProviderFactory, TraceNamespace,
+    },
+    utils::io::file::{read_file, write_file},
+};
+
+/// The recorded response to a single request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureResponse {
+    /// The request succeeded with the given result.
+    Result(Value),
+    /// The request failed with the given error message.
+    Error(String),
+}
+
+/// A single recorded request and its response.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureEntry {
+    /// The [`HeimdallProvider`] method which was called, e.g. `get_code_at`.
+    pub method: String,
+    /// The parameters the method was called with.
+    pub params: Value,
+    /// The response to the request.
+    #[serde(flatten)]
+    pub response: FixtureResponse,
+}
+
+/// A list of recorded requests and responses, in the order they were made.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// The recorded entries.
+    pub entries: Vec<FixtureEntry>,
+}
+
+impl Fixture {
+    /// Load a fixture from the given JSON file.
+    pub fn load(path: &str) -> Result<Self> {
+        let contents =
+            read_file(path).map_err(|e| eyre!("failed to read fixture '{}': {}", path, e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| eyre!("failed to parse fixture '{}': {}", path, e))
+    }
+
+    /// Save the fixture to the given JSON file.
+    pub fn save(&self, path: &str) -> Result<()> {
+        write_file(path, &serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// A fixture file whose recorded requests are kept in memory, and only written when flushed or
+/// dropped, so that recording many requests doesn't rewrite the file after each one.
+#[derive(Debug)]
+struct Recorder {
+    path: String,
+    fixture: Mutex<Fixture>,
+}
+
+impl Recorder {
+    fn new(path: &str) -> Self {
+        Self { path: path.to_string(), fixture: Default::default() }
+    }
+
+    /// Record the given entry, returning its index in the fixture.
+    fn push(&self, entry: FixtureEntry) -> usize {
+        let mut fixture = self.fixture.lock().unwrap_or_else(|e| e.into_inner());
+        fixture.entries.push(entry);
+        fixture.entries.len() - 1
+    }
+
+    /// Append the given value to the result of the entry at `index`, if its result is an array.
+    fn append(&self, index: usize, value: Value) {
+        let mut fixture = self.fixture.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(FixtureEntry {
+            response: FixtureResponse::Result(Value::Array(values)), ..
+        }) = fixture.entries.get_mut(index)
+        {
+            values.push(value);
+        }
+    }
+
+    fn flush(&self) -> Result<()> {
+        let fixture = self.fixture.lock().unwrap_or_else(|e| e.into_inner());
+        fixture.save(&self.path)
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!("failed to write fixture '{}': {}", self.path, e);
+        }
+    }
+}
+
+/// [`RecordingProvider`] forwards every request to an inner [`HeimdallProvider`] and records the
+/// request and response to a fixture file.
+#[derive(Clone, Debug)]
+pub struct RecordingProvider {
+    inner: Arc<dyn HeimdallProvider>,
+    recorder: Arc<Recorder>,
+}
+
+impl RecordingProvider {
+    /// Record all requests sent to `inner` to a new fixture at `path`, overwriting any existing
+    /// file.
+    pub fn new(inner: Arc<dyn HeimdallProvider>, path: &str) -> Self {
+        Self { inner, recorder: Arc::new(Recorder::new(path)) }
+    }
+
+    /// Write the requests recorded so far to the fixture file. They're also written once the
+    /// last provider sharing the fixture is dropped.
+    pub fn flush(&self) -> Result<()> {
+        self.recorder.flush()
+    }
+
+    /// Get a copy of the requests recorded so far.
+    pub fn fixture(&self) -> Fixture {
+        self.recorder.fixture.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Record the given request and its result, then return the result.
+    fn record<T: Serialize>(&self, method: &str, params: Value, result: Result<T>) -> Result<T> {
+        let response = match &result {
+            Ok(value) => FixtureResponse::Result(serde_json::to_value(value)?),
+            Err(e) => FixtureResponse::Error(e.to_string()),
+        };
+        self.recorder.push(FixtureEntry { method: method.to_string(), params, response });
+        result
+    }
+}
+
+/// The recorded parameters of a batch of calls, with each call's calldata as a hex string.
+fn call_batch_params(calls: &[(Address, Vec<u8>)], block: Option<BlockId>) -> Value {
+    let calls = calls
+        .iter()
+        .map(|(address, calldata)| (address, Bytes::copy_from_slice(calldata)))
+        .collect::<Vec<_>>();
+    json!([calls, block])
+}
+
+/// Convert the result of a single call in a batch to its recorded response.
+fn call_response(result: &Result<Vec<u8>>) -> FixtureResponse {
+    match result {
+        Ok(returndata) => FixtureResponse::Result(json!(Bytes::copy_from_slice(returndata))),
+        Err(e) => FixtureResponse::Error(e.to_string()),
+    }
+}
+
+/// Convert the recorded response of a single call in a batch back to its result.
+fn call_result(response: FixtureResponse) -> Result<Vec<u8>> {
+    match response {
+        FixtureResponse::Result(value) => Ok(serde_json::from_value::<Bytes>(value)?.into()),
+        FixtureResponse::Error(e) => Err(eyre!(e)),
+    }
+}
+
+#[async_trait]
+impl HeimdallProvider for RecordingProvider {
+    async fn get_chainid(&self) -> Result<u64> {
+        self.record("get_chainid", json!([]), self.inner.get_chainid().await)
+    }
+
+    async fn get_block_number(&self) -> Result<u64> {
+        self.record("get_block_number", json!([]), self.inner.get_block_number().await)
+    }
+
+    async fn get_block_timestamp(&self, block_number: u64) -> Result<u64> {
+        let result = self.inner.get_block_timestamp(block_number).await;
+        self.record("get_block_timestamp", json!([block_number]), result)
+    }
+
+    async fn get_code_at(&self, address: Address, block: Option<BlockId>) -> Result<Vec<u8>> {
+        // record the code as a hex string rather than an array of bytes
+        let result = self.inner.get_code_at(address, block).await.map(Bytes::from);
+        self.record("get_code_at", json!([address, block]), result).map(Vec::from)
+    }
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        let result = self.inner.get_storage_at(address, slot, block).await;
+        self.record("get_storage_at", json!([address, slot, block]), result)
+    }
+
+    async fn call(
+        &self,
+        address: Address,
+        calldata: &[u8],
+        block: Option<BlockId>,
+    ) -> Result<Vec<u8>> {
+        let calldata = Bytes::copy_from_slice(calldata);
+        let result = self.inner.call(address, &calldata, block).await.map(Bytes::from);
+        self.record("call", json!([address, calldata, block]), result).map(Vec::from)
+    }
+
+    async fn call_batch(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        block: Option<BlockId>,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        // each call's result is recorded on its own, since a call which reverts doesn't fail the
+        // others
+        let params = call_batch_params(calls, block);
+        match self.inner.call_batch(calls, block).await {
+            Ok(results) => {
+                let responses = results.iter().map(call_response).collect::<Vec<_>>();
+                self.record("call_batch", params, Ok(responses))?;
+                Ok(results)
+            }
+            Err(e) => self
+                .record::<Vec<FixtureResponse>>("call_batch", params, Err(e))
+                .map(|_| Vec::new()),
+        }
+    }
+
+    async fn get_transaction_by_hash(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
+        let result = self.inner.get_transaction_by_hash(tx_hash).await;
+        self.record("get_transaction_by_hash", json!([tx_hash]), result)
+    }
+
+    async fn get_transactions_batch(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<Option<Transaction>>> {
+        let result = self.inner.get_transactions_batch(tx_hashes).await;
+        self.record("get_transactions_batch", json!([tx_hashes]), result)
+    }
+
+    async fn get_storage_batch(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>> {
+        let result = self.inner.get_storage_batch(slots).await;
+        self.record("get_storage_batch", json!([slots]), result)
+    }
+
+    async fn trace_replay_transaction(
+        &self,
+        tx_hash: &str,
+        trace_type: &[TraceType],
+    ) -> Result<TraceResults> {
+        let result = self.inner.trace_replay_transaction(tx_hash, trace_type).await;
+        self.record("trace_replay_transaction", json!([tx_hash, trace_type]), result)
+    }
+
+    async fn trace_replay_block_transactions(
+        &self,
+        block_number: u64,
+        trace_type: &[TraceType],
+    ) -> Result<Vec<TraceResultsWithTransactionHash>> {
+        let result = self.inner.trace_replay_block_transactions(block_number, trace_type).await;
+        self.record("trace_replay_block_transactions", json!([block_number, trace_type]), result)
+    }
+
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: &str,
+        options: &GethDebugTracingOptions,
+    ) -> Result<GethTrace> {
+        let result = self.inner.debug_trace_transaction(tx_hash, options).await;
+        self.record("debug_trace_transaction", json!([tx_hash, options]), result)
+    }
+
+    async fn trace_call(
+        &self,
+        request: &TransactionRequest,
+        trace_type: &[TraceType],
+        block: Option<BlockId>,
+    ) -> Result<TraceResults> {
+        let result = self.inner.trace_call(request, trace_type, block).await;
+        self.record("trace_call", json!([request, trace_type, block]), result)
+    }
+
+    async fn debug_trace_call(
+        &self,
+        request: &TransactionRequest,
+        options: &GethDebugTracingOptions,
+        block: Option<BlockId>,
+    ) -> Result<GethTrace> {
+        let result = self.inner.debug_trace_call(request, options, block).await;
+        self.record("debug_trace_call", json!([request, options, block]), result)
+    }
+
+    async fn debug_trace_block(
+        &self,
+        block_number: u64,
+        options: &GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        let result = self.inner.debug_trace_block(block_number, options).await;
+        self.record("debug_trace_block", json!([block_number, options]), result)
+    }
+
+    async fn supports_trace_namespace(&self, namespace: TraceNamespace) -> Result<bool> {
+        let result = self.inner.supports_trace_namespace(namespace).await;
+        self.record("supports_trace_namespace", json!([format!("{namespace:?}")]), result)
+    }
+
+    async fn supports_archive_state(&self) -> Result<bool> {
+        let result = self.inner.supports_archive_state().await;
+        self.record("supports_archive_state", json!([]), result)
+    }
+
+    async fn capabilities(&self) -> Result<Capabilities> {
+        self.record("capabilities", json!([]), self.inner.capabilities().await)
+    }
+
+    async fn get_logs(&self, filter: &Filter, block: Option<BlockId>) -> Result<Vec<Log>> {
+        let result = self.inner.get_logs(filter, block).await;
+        self.record("get_logs", json!([filter, block]), result)
+    }
+
+    async fn subscribe_block_numbers(&self) -> Result<Option<BoxStream<'static, u64>>> {
+        let (response, result) = match self.inner.subscribe_block_numbers().await {
+            Ok(Some(subscription)) => (FixtureResponse::Result(json!([])), Ok(Some(subscription))),
+            Ok(None) => (FixtureResponse::Result(Value::Null), Ok(None)),
+            Err(e) => (FixtureResponse::Error(e.to_string()), Err(e)),
+        };
+        let index = self.recorder.push(FixtureEntry {
+            method: "subscribe_block_numbers".to_string(),
+            params: json!([]),
+            response,
+        });
+
+        // record each block number as it's received, so that the subscription can be replayed
+        let recorder = self.recorder.clone();
+        result.map(|subscription| {
+            subscription.map(|subscription| {
+                subscription.inspect(move |number| recorder.append(index, json!(number))).boxed()
+            })
+        })
+    }
+}
+
+/// The recorded responses for each `(method, params)` request, in the order they were recorded.
+type RecordedResponses = HashMap<(String, String), VecDeque<FixtureResponse>>;
+
+/// [`ReplayProvider`] serves the responses recorded in a [`Fixture`] instead of contacting a node.
+///
+/// Requests are matched by method and parameters. If the same request was recorded several
+/// times, the responses are served in the order they were recorded, and the last response is
+/// repeated once the others have been served.
+#[derive(Clone, Debug)]
+pub struct ReplayProvider {
+    responses: Arc<Mutex<RecordedResponses>>,
+}
+
+impl ReplayProvider {
+    /// Serve the responses in the given fixture.
+    pub fn new(fixture: Fixture) -> Self {
+        let mut responses = RecordedResponses::new();
+        for entry in fixture.entries {
+            responses
+                .entry((entry.method, entry.params.to_string()))
+                .or_default()
+                .push_back(entry.response);
+        }
+        Self { responses: Arc::new(Mutex::new(responses)) }
+    }
+
+    /// Serve the responses in the fixture at the given path.
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(Self::new(Fixture::load(path)?))
+    }
+
+    /// Look up the recorded response to the given request.
+    fn respond<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = responses
+            .get_mut(&(method.to_string(), params.to_string()))
+            .ok_or_else(|| eyre!("no recorded response for '{}' with params {}", method, params))?;
+        let response = match queue.len() {
+            1 => queue.front().cloned(),
+            _ => queue.pop_front(),
+        }
+        .ok_or_else(|| eyre!("no recorded response for '{}' with params {}", method, params))?;
+
+        debug!("replaying recorded response for '{}' with params {}", method, params);
+        match response {
+            FixtureResponse::Result(value) => Ok(serde_json::from_value(value)?),
+            FixtureResponse::Error(e) => Err(eyre!(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl HeimdallProvider for ReplayProvider {
+    async fn get_chainid(&self) -> Result<u64> {
+        self.respond("get_chainid", json!([]))
+    }
+
+    async fn get_block_number(&self) -> Result<u64> {
+        self.respond("get_block_number", json!([]))
+    }
+
+    async fn get_block_timestamp(&self, block_number: u64) -> Result<u64> {
+        self.respond("get_block_timestamp", json!([block_number]))
+    }
+
+    async fn get_code_at(&self, address: Address, block: Option<BlockId>) -> Result<Vec<u8>> {
+        self.respond::<Bytes>("get_code_at", json!([address, block])).map(Vec::from)
+    }
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        self.respond("get_storage_at", json!([address, slot, block]))
+    }
+
+    async fn call(
+        &self,
+        address: Address,
+        calldata: &[u8],
+        block: Option<BlockId>,
+    ) -> Result<Vec<u8>> {
+        let calldata = Bytes::copy_from_slice(calldata);
+        self.respond::<Bytes>("call", json!([address, calldata, block])).map(Vec::from)
+    }
+
+    async fn call_batch(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        block: Option<BlockId>,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        let params = call_batch_params(calls, block);
+        let responses = self.respond::<Vec<FixtureResponse>>("call_batch", params)?;
+        Ok(responses.into_iter().map(call_result).collect())
+    }
+
+    async fn get_transaction_by_hash(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
+        self.respond("get_transaction_by_hash", json!([tx_hash]))
+    }
+
+    async fn get_transactions_batch(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<Option<Transaction>>> {
+        self.respond("get_transactions_batch", json!([tx_hashes]))
+    }
+
+    async fn get_storage_batch(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>> {
+        self.respond("get_storage_batch", json!([slots]))
+    }
+
+    async fn trace_replay_transaction(
+        &self,
+        tx_hash: &str,
+        trace_type: &[TraceType],
+    ) -> Result<TraceResults> {
+        self.respond("trace_replay_transaction", json!([tx_hash, trace_type]))
+    }
+
+    async fn trace_replay_block_transactions(
+        &self,
+        block_number: u64,
+        trace_type: &[TraceType],
+    ) -> Result<Vec<TraceResultsWithTransactionHash>> {
+        self.respond("trace_replay_block_transactions", json!([block_number, trace_type]))
+    }
+
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: &str,
+        options: &GethDebugTracingOptions,
+    ) -> Result<GethTrace> {
+        self.respond("debug_trace_transaction", json!([tx_hash, options]))
+    }
+
+    async fn trace_call(
+        &self,
+        request: &TransactionRequest,
+        trace_type: &[TraceType],
+        block: Option<BlockId>,
+    ) -> Result<TraceResults> {
+        self.respond("trace_call", json!([request, trace_type, block]))
+    }
+
+    async fn debug_trace_call(
+        &self,
+        request: &TransactionRequest,
+        options: &GethDebugTracingOptions,
+        block: Option<BlockId>,
+    ) -> Result<GethTrace> {
+        self.respond("debug_trace_call", json!([request, options, block]))
+    }
+
+    async fn debug_trace_block(
+        &self,
+        block_number: u64,
+        options: &GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        self.respond("debug_trace_block", json!([block_number, options]))
+    }
+
+    async fn supports_trace_namespace(&self, namespace: TraceNamespace) -> Result<bool> {
+        self.respond("supports_trace_namespace", json!([format!("{namespace:?}")]))
+    }
+
+    async fn supports_archive_state(&self) -> Result<bool> {
+        self.respond("supports_archive_state", json!([]))
+    }
+
+    async fn capabilities(&self) -> Result<Capabilities> {
+        self.respond("capabilities", json!([]))
+    }
+
+    async fn get_logs(&self, filter: &Filter, block: Option<BlockId>) -> Result<Vec<Log>> {
+        self.respond("get_logs", json!([filter, block]))
+    }
+
+    async fn subscribe_block_numbers(&self) -> Result<Option<BoxStream<'static, u64>>> {
+        // serve the block numbers the recorded subscription received, then end it
+        let numbers = self.respond::<Option<Vec<u64>>>("subscribe_block_numbers", json!([]))?;
+        Ok(numbers.map(|numbers| stream::iter(numbers).boxed()))
+    }
+}
+
+/// A [`ProviderFactory`] which records every request made through the providers it connects to
+/// a single fixture file.
+#[derive(Debug)]
+pub struct RecordingProviderFactory {
+    recorder: Arc<Recorder>,
+}
+
+impl RecordingProviderFactory {
+    /// Record all requests to a new fixture at `path`, overwriting any existing file.
+    pub fn new(path: &str) -> Self {
+        Self { recorder: Arc::new(Recorder::new(path)) }
+    }
+
+    /// Write the requests recorded so far, by every provider the factory connected to, to the
+    /// fixture file.
+    pub fn flush(&self) -> Result<()> {
+        self.recorder.flush()
+    }
+}
+
+#[async_trait]
+impl ProviderFactory for RecordingProviderFactory {
+    async fn connect(&self, rpc_url: &str) -> Result<Arc<dyn HeimdallProvider>> {
+        let inner = Arc::new(MultiTransportProvider::connect(rpc_url).await?);
+        Ok(Arc::new(RecordingProvider { inner, recorder: self.recorder.clone() }))
+    }
+}
+
+/// A [`ProviderFactory`] which serves the responses in a fixture for every rpc url.
+#[derive(Debug)]
+pub struct ReplayProviderFactory {
+    provider: ReplayProvider,
+}
+
+impl ReplayProviderFactory {
+    /// Serve the responses in the fixture at the given path.
+    pub fn load(path: &str) -> Result<Self> {
+        Ok(Self { provider: ReplayProvider::load(path)? })
+    }
+}
+
+#[async_trait]
+impl ProviderFactory for ReplayProviderFactory {
+    async fn connect(&self, _rpc_url: &str) -> Result<Arc<dyn HeimdallProvider>> {
+        Ok(Arc::new(self.provider.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::rpc::types::trace::geth::CallFrame;
+
+    /// A provider which only knows its chain id and the code of the zero address.
+    #[derive(Debug)]
+    struct StaticProvider;
+
+    #[async_trait]
+    impl HeimdallProvider for StaticProvider {
+        async fn get_chainid(&self) -> Result<u64> {
+            Ok(1)
+        }
+
+        async fn get_block_number(&self) -> Result<u64> {
+            Err(eyre!("node is syncing"))
+        }
+
+        async fn get_block_timestamp(&self, _: u64) -> Result<u64> {
+            Err(eyre!("node is syncing"))
+        }
+
+        async fn get_code_at(&self, address: Address, _: Option<BlockId>) -> Result<Vec<u8>> {
+            match address {
+                Address::ZERO => Ok(vec![0x60, 0x80, 0x60, 0x40]),
+                _ => Ok(Vec::new()),
+            }
+        }
+
+        async fn get_storage_at(&self, _: Address, _: U256, _: Option<BlockId>) -> Result<U256> {
+            Ok(U256::from(1))
+        }
+
+        async fn call(&self, address: Address, _: &[u8], _: Option<BlockId>) -> Result<Vec<u8>> {
+            match address {
+                Address::ZERO => Ok(U256::from(18).to_be_bytes_vec()),
+                _ => Err(eyre!("execution reverted")),
+            }
+        }
+
+        async fn get_transaction_by_hash(&self, _: TxHash) -> Result<Option<Transaction>> {
+            Ok(None)
+        }
+
+        async fn trace_replay_transaction(&self, _: &str, _: &[TraceType]) -> Result<TraceResults> {
+            Err(eyre!("not supported"))
+        }
+
+        async fn trace_replay_block_transactions(
+            &self,
+            _: u64,
+            _: &[TraceType],
+        ) -> Result<Vec<TraceResultsWithTransactionHash>> {
+            Err(eyre!("not supported"))
+        }
+
+        async fn debug_trace_transaction(
+            &self,
+            _: &str,
+            _: &GethDebugTracingOptions,
+        ) -> Result<GethTrace> {
+            Ok(GethTrace::CallTracer(CallFrame { gas: U256::from(21000), ..Default::default() }))
+        }
+
+        async fn trace_call(
+            &self,
+            _: &TransactionRequest,
+            _: &[TraceType],
+            _: Option<BlockId>,
+        ) -> Result<TraceResults> {
+            Err(eyre!("not supported"))
+        }
+
+        async fn debug_trace_call(
+            &self,
+            _: &TransactionRequest,
+            _: &GethDebugTracingOptions,
+            _: Option<BlockId>,
+        ) -> Result<GethTrace> {
+            Err(eyre!("not supported"))
+        }
+
+        async fn debug_trace_block(
+            &self,
+            _: u64,
+            _: &GethDebugTracingOptions,
+        ) -> Result<Vec<TraceResult>> {
+            Err(eyre!("not supported"))
+        }
+
+        async fn supports_trace_namespace(&self, namespace: TraceNamespace) -> Result<bool> {
+            Ok(namespace == TraceNamespace::Geth)
+        }
+
+        async fn get_logs(&self, _: &Filter, _: Option<BlockId>) -> Result<Vec<Log>> {
+            Ok(Vec::new())
+        }
+
+        async fn subscribe_block_numbers(&self) -> Result<Option<BoxStream<'static, u64>>> {
+            Ok(Some(stream::iter([1, 2, 3]).boxed()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let path = std::env::temp_dir().join("heimdall-test-record-and-replay.json");
+        let path = path.to_str().expect("invalid temp dir");
+
+        let recorder = RecordingProvider::new(Arc::new(StaticProvider), path);
+        let code = recorder.get_code_at(Address::ZERO, None).await.unwrap();
+        let block_number = recorder.get_block_number().await;
+        let decimals = recorder.call(Address::ZERO, &[0x31, 0x3c, 0xe5, 0x67], None).await.unwrap();
+        let trace = recorder
+            .debug_trace_transaction("0x00", &GethDebugTracingOptions::default())
+            .await
+            .unwrap()
+            .try_into_call_frame()
+            .unwrap();
+        assert!(recorder.supports_trace_namespace(TraceNamespace::Geth).await.unwrap());
+        assert_eq!(recorder.fixture().entries.len(), 5);
+        recorder.flush().unwrap();
+
+        let replay = ReplayProvider::load(path).unwrap();
+        assert_eq!(replay.get_code_at(Address::ZERO, None).await.unwrap(), code);
+        assert_eq!(
+            replay.get_block_number().await.unwrap_err().to_string(),
+            block_number.unwrap_err().to_string()
+        );
+        assert_eq!(
+            replay.call(Address::ZERO, &[0x31, 0x3c, 0xe5, 0x67], None).await.unwrap(),
+            decimals
+        );
+        assert_eq!(
+            replay
+                .debug_trace_transaction("0x00", &GethDebugTracingOptions::default())
+                .await
+                .unwrap()
+                .try_into_call_frame()
+                .unwrap(),
+            trace
+        );
+        assert!(replay.supports_trace_namespace(TraceNamespace::Geth).await.unwrap());
+
+        // requests which weren't recorded can't be replayed
+        assert!(replay.get_chainid().await.is_err());
+        assert!(replay.get_code_at(Address::repeat_byte(1), None).await.is_err());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay_overridable_methods() {
+        let path = std::env::temp_dir().join("heimdall-test-record-and-replay-overridable.json");
+        let path = path.to_str().expect("invalid temp dir");
+
+        let calls =
+            [(Address::ZERO, vec![0x31, 0x3c, 0xe5, 0x67]), (Address::repeat_byte(1), vec![])];
+        let slots = [(Address::ZERO, U256::from(1))];
+        let recorder = RecordingProvider::new(Arc::new(StaticProvider), path);
+        let results = recorder.call_batch(&calls, None).await.unwrap();
+        let transactions = recorder.get_transactions_batch(&[TxHash::ZERO]).await.unwrap();
+        let values = recorder.get_storage_batch(&slots).await.unwrap();
+        let archive = recorder.supports_archive_state().await.unwrap();
+        let capabilities = recorder.capabilities().await.unwrap();
+        let numbers: Vec<u64> =
+            recorder.subscribe_block_numbers().await.unwrap().unwrap().collect().await;
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!(recorder.fixture().entries.len(), 6);
+        recorder.flush().unwrap();
+
+        let replay = ReplayProvider::load(path).unwrap();
+        let replayed = replay.call_batch(&calls, None).await.unwrap();
+        assert_eq!(replayed[0].as_ref().unwrap(), results[0].as_ref().unwrap());
+        assert_eq!(
+            replayed[1].as_ref().unwrap_err().to_string(),
+            results[1].as_ref().unwrap_err().to_string()
+        );
+        assert_eq!(replay.get_transactions_batch(&[TxHash::ZERO]).await.unwrap(), transactions);
+        assert_eq!(replay.get_storage_batch(&slots).await.unwrap(), values);
+        assert_eq!(replay.supports_archive_state().await.unwrap(), archive);
+        assert_eq!(replay.capabilities().await.unwrap(), capabilities);
+        let replayed: Vec<u64> =
+            replay.subscribe_block_numbers().await.unwrap().unwrap().collect().await;
+        assert_eq!(replayed, numbers);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_replay_serves_responses_in_order() {
+        let entry = |block: u64| FixtureEntry {
+            method: "get_block_number".to_string(),
+            params: json!([]),
+            response: FixtureResponse::Result(json!(block)),
+        };
+        let replay = ReplayProvider::new(Fixture { entries: vec![entry(1), entry(2)] });
+
+        assert_eq!(replay.respond::<u64>("get_block_number", json!([])).unwrap(), 1);
+        assert_eq!(replay.respond::<u64>("get_block_number", json!([])).unwrap(), 2);
+        assert_eq!(replay.respond::<u64>("get_block_number", json!([])).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_fixture_format() {
+        let fixture: Fixture = serde_json::from_str(
+            r#"{"entries":[
+                {"method":"get_chainid","params":[],"result":1},
+                {"method":"get_block_number","params":[],"error":"node is syncing"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(fixture.entries[0].response, FixtureResponse::Result(json!(1)));
+        assert_eq!(fixture.entries[1].response, FixtureResponse::Error("node is syncing".into()));
+    }
+}
@@ -0,0 +1,248 @@
+//! Local anvil forks of a chain, which state-mutating probes can be run against without
+//! touching the forked chain.
+
+use std::{
+    net::TcpListener,
+    process::Stdio,
+    sync::{Arc, RwLock, Weak},
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    network::{Ethereum, TransactionBuilder},
+    primitives::{Address, TxHash, U256},
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::{
+        trace::{geth::GethTrace, parity::StateDiff},
+        TransactionReceipt, TransactionRequest,
+    },
+};
+use eyre::{bail, eyre, OptionExt, Result};
+use tokio::process::{Child, Command};
+use tracing::{debug, info};
+
+use crate::{
+    ether::{geth, provider::parse_rpc_urls, rpc::prestate_diff_tracer_options},
+    utils::offline::ensure_online,
+};
+
+/// How long to wait for a spawned anvil to start serving requests.
+pub const DEFAULT_SPAWN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The balance given to impersonated senders, so that they can always pay for gas (2^80 wei, or
+/// about 1.2 million ether).
+const SENDER_BALANCE: U256 = U256::from_limbs([0, 1 << 16, 0, 0]);
+
+/// The fork used for state-mutating probes, if one was set with [`set_default_fork`]. Only a
+/// weak reference is held, so a spawned anvil is shut down once its owner drops it.
+static DEFAULT_FORK: RwLock<Option<Weak<Fork>>> = RwLock::new(None);
+
+/// Route all subsequent state-mutating probes to the given fork, or disable them if `None` is
+/// given. The caller must keep the fork alive for as long as it should be used.
+pub fn set_default_fork(fork: Option<&Arc<Fork>>) {
+    *DEFAULT_FORK.write().unwrap_or_else(|e| e.into_inner()) = fork.map(Arc::downgrade);
+}
+
+/// Get the fork used for state-mutating probes, if one was set and is still alive.
+pub fn default_fork() -> Option<Arc<Fork>> {
+    DEFAULT_FORK.read().unwrap_or_else(|e| e.into_inner()).as_ref().and_then(Weak::upgrade)
+}
+
+/// The outcome of a transaction simulated with [`Fork::simulate`].
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// Whether the transaction succeeded.
+    pub success: bool,
+    /// The gas used by the transaction.
+    pub gas_used: u64,
+    /// The state changed by the transaction, if the fork supports Geth's `prestateTracer`.
+    pub state_diff: Option<StateDiff>,
+}
+
+/// [`Fork`] is an anvil node forking a chain, which is either spawned by heimdall with
+/// [`Fork::spawn`], or already running and attached to with [`Fork::attach`].
+///
+/// A spawned anvil is shut down when the [`Fork`] is dropped.
+#[derive(Debug)]
+pub struct Fork {
+    rpc_url: String,
+    provider: RootProvider<Ethereum>,
+    child: Option<Child>,
+}
+
+impl Fork {
+    /// Spawn an anvil node forking the chain served by `fork_url`, optionally at the given
+    /// block, and wait for it to start serving requests.
+    ///
+    /// `fork_url` may be a comma-separated list of RPC URLs, in which case the first is forked.
+    /// The `anvil` binary must be on the `PATH`, see <https://getfoundry.sh>.
+    pub async fn spawn(fork_url: &str, block: Option<u64>) -> Result<Self> {
+        ensure_online("spawn an anvil fork")?;
+        let fork_url =
+            *parse_rpc_urls(fork_url).first().ok_or_eyre("cannot fork, rpc_url is empty")?;
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+
+        let mut command = Command::new("anvil");
+        command
+            .args(["--fork-url", fork_url, "--port", &port.to_string(), "--silent"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        if let Some(block) = block {
+            command.args(["--fork-block-number", &block.to_string()]);
+        }
+        let child = command
+            .spawn()
+            .map_err(|e| eyre!("failed to spawn anvil, is foundry installed? {}", e))?;
+
+        let mut fork = Self::connect(&format!("http://127.0.0.1:{port}"), Some(child)).await?;
+        fork.wait_until_ready(DEFAULT_SPAWN_TIMEOUT).await?;
+        info!("spawned anvil fork at '{}'", fork.rpc_url);
+        Ok(fork)
+    }
+
+    /// Attach to an already running anvil node at `rpc_url`.
+    pub async fn attach(rpc_url: &str) -> Result<Self> {
+        let fork = Self::connect(rpc_url, None).await?;
+        fork.provider
+            .get_chain_id()
+            .await
+            .map_err(|e| eyre!("failed to attach to fork at '{}': {}", rpc_url, e))?;
+        info!("attached to fork at '{}'", rpc_url);
+        Ok(fork)
+    }
+
+    async fn connect(rpc_url: &str, child: Option<Child>) -> Result<Self> {
+        let provider = ProviderBuilder::new().connect(rpc_url).await?.root().clone();
+        Ok(Self { rpc_url: rpc_url.to_string(), provider, child })
+    }
+
+    /// Wait until the spawned anvil serves requests, failing if it exits or `timeout` elapses.
+    async fn wait_until_ready(&mut self, timeout: Duration) -> Result<()> {
+        let start_time = Instant::now();
+        loop {
+            if let Some(status) = self.child.as_mut().map(Child::try_wait).transpose()?.flatten() {
+                bail!("anvil exited before serving requests: {}", status);
+            }
+            if self.provider.get_chain_id().await.is_ok() {
+                debug!("anvil fork ready after {:?}", start_time.elapsed());
+                return Ok(());
+            }
+            if start_time.elapsed() > timeout {
+                bail!("anvil did not serve requests within {:?}", timeout);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// The RPC URL of the fork. Read-only requests may be sent to it like any other RPC.
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Whether the fork was spawned by heimdall, rather than attached to.
+    pub fn is_spawned(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Snapshot the fork's state, returning an id which may be passed to [`Fork::revert`].
+    pub async fn snapshot(&self) -> Result<U256> {
+        Ok(self.provider.raw_request("evm_snapshot".into(), ()).await?)
+    }
+
+    /// Revert the fork's state to the given snapshot.
+    pub async fn revert(&self, snapshot: U256) -> Result<()> {
+        let reverted: bool = self.provider.raw_request("evm_revert".into(), (snapshot,)).await?;
+        if !reverted {
+            bail!("failed to revert fork to snapshot {}", snapshot);
+        }
+        Ok(())
+    }
+
+    /// Send a transaction from `from` to `to` with the given calldata, impersonating and
+    /// funding the sender.
+    pub async fn send_transaction(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: &[u8],
+    ) -> Result<TransactionReceipt> {
+        let _: serde_json::Value =
+            self.provider.raw_request("anvil_impersonateAccount".into(), (from,)).await?;
+        let _: serde_json::Value =
+            self.provider.raw_request("anvil_setBalance".into(), (from, SENDER_BALANCE)).await?;
+
+        let transaction =
+            TransactionRequest::default().with_from(from).with_to(to).with_input(calldata.to_vec());
+        let tx_hash: TxHash =
+            self.provider.raw_request("eth_sendTransaction".into(), (transaction,)).await?;
+        self.provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| eyre!("fork did not mine transaction {}", tx_hash))
+    }
+
+    /// Simulate a transaction from `from` to `to` with the given calldata, returning its outcome
+    /// and the state it changed. The fork's state is reverted afterwards.
+    pub async fn simulate(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: &[u8],
+    ) -> Result<Simulation> {
+        let snapshot = self.snapshot().await?;
+        let simulation = self.simulate_in_snapshot(from, to, calldata).await;
+        self.revert(snapshot).await?;
+        simulation
+    }
+
+    async fn simulate_in_snapshot(
+        &self,
+        from: Address,
+        to: Address,
+        calldata: &[u8],
+    ) -> Result<Simulation> {
+        let receipt = self.send_transaction(from, to, calldata).await?;
+        let state_diff = match self
+            .provider
+            .raw_request::<_, GethTrace>(
+                "debug_traceTransaction".into(),
+                (receipt.transaction_hash, prestate_diff_tracer_options()),
+            )
+            .await
+        {
+            Ok(trace) => {
+                trace.try_into_pre_state_frame()?.as_diff().cloned().map(geth::to_state_diff)
+            }
+            Err(e) => {
+                debug!("fork does not support the prestate tracer: {}", e);
+                None
+            }
+        };
+
+        Ok(Simulation { success: receipt.status(), gas_used: receipt.gas_used, state_diff })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_default_fork_is_weak() {
+        let fork = Arc::new(Fork::connect("http://127.0.0.1:1", None).await.unwrap());
+        set_default_fork(Some(&fork));
+        assert_eq!(default_fork().unwrap().rpc_url(), "http://127.0.0.1:1");
+        assert!(!fork.is_spawned());
+
+        // the default fork doesn't outlive its owner
+        drop(fork);
+        assert!(default_fork().is_none());
+        set_default_fork(None);
+    }
+
+    #[tokio::test]
+    async fn test_attach_unreachable_fork() {
+        assert!(Fork::attach("http://127.0.0.1:1").await.is_err());
+    }
+}
@@ -0,0 +1,518 @@
+//! Conversions from Geth-style `debug_trace*` responses into their Parity-style equivalents.
+//!
+//! Most hosted nodes only expose the `debug_` namespace, so traces produced by the `callTracer`
+//! and `prestateTracer` are converted into [`TraceResults`], allowing the rest of heimdall to
+//! consume a single trace format.
+
+use std::collections::{BTreeSet, HashMap};
+
+use alloy::{
+    primitives::{Address, Bytes, B256, U256, U64},
+    rpc::types::{
+        trace::{
+            geth::{AccountState, CallFrame, CallLogFrame, DiffMode, StructLog},
+            parity::{
+                AccountDiff, Action, CallAction, CallOutput, CallType, ChangedType, CreateAction,
+                CreateOutput, CreationMethod, Delta, SelfdestructAction, StateDiff, TraceOutput,
+                TraceResults, TransactionTrace, VmInstruction, VmTrace,
+            },
+        },
+        Log,
+    },
+};
+
+/// Convert a `callTracer` frame and an optional `prestateTracer` diff into [`TraceResults`].
+///
+/// The returned [`VmTrace`] is a skeleton which only contains the `LOG*` operations and
+/// sub-calls of each frame, in execution order. This is enough to attribute logs to the call
+/// which emitted them, but contains no opcode-level information.
+pub fn to_trace_results(frame: &CallFrame, diff: Option<DiffMode>) -> TraceResults {
+    TraceResults {
+        output: frame.output.clone().unwrap_or_default(),
+        state_diff: diff.map(to_state_diff),
+        trace: to_transaction_traces(frame),
+        vm_trace: Some(to_vm_trace(frame)),
+    }
+}
+
+/// Flatten a `callTracer` frame into Parity-style [`TransactionTrace`]s, in depth-first order.
+pub fn to_transaction_traces(frame: &CallFrame) -> Vec<TransactionTrace> {
+    let mut traces = Vec::new();
+    flatten_call_frame(frame, Vec::new(), &mut traces);
+    traces
+}
+
+fn flatten_call_frame(
+    frame: &CallFrame,
+    trace_address: Vec<usize>,
+    traces: &mut Vec<TransactionTrace>,
+) {
+    traces.push(to_transaction_trace(frame, trace_address.clone()));
+
+    for (i, call) in frame.calls.iter().enumerate() {
+        let mut child_address = trace_address.clone();
+        child_address.push(i);
+        flatten_call_frame(call, child_address, traces);
+    }
+}
+
+/// Convert a single `callTracer` frame, ignoring its sub-calls.
+fn to_transaction_trace(frame: &CallFrame, trace_address: Vec<usize>) -> TransactionTrace {
+    let gas = frame.gas.saturating_to::<u64>();
+    let gas_used = frame.gas_used.saturating_to::<u64>();
+    let value = frame.value.unwrap_or_default();
+
+    let (action, result) = match frame.typ.to_uppercase().as_str() {
+        kind @ ("CREATE" | "CREATE2") => (
+            Action::Create(CreateAction {
+                from: frame.from,
+                gas,
+                init: frame.input.clone(),
+                value,
+                creation_method: if kind == "CREATE2" {
+                    CreationMethod::Create2
+                } else {
+                    CreationMethod::Create
+                },
+            }),
+            TraceOutput::Create(CreateOutput {
+                address: frame.to.unwrap_or_default(),
+                code: frame.output.clone().unwrap_or_default(),
+                gas_used,
+            }),
+        ),
+        "SELFDESTRUCT" => {
+            return TransactionTrace {
+                action: Action::Selfdestruct(SelfdestructAction {
+                    address: frame.from,
+                    balance: value,
+                    refund_address: frame.to.unwrap_or_default(),
+                }),
+                error: frame.error.clone(),
+                result: None,
+                subtraces: frame.calls.len(),
+                trace_address,
+            };
+        }
+        kind => (
+            Action::Call(CallAction {
+                from: frame.from,
+                call_type: match kind {
+                    "STATICCALL" => CallType::StaticCall,
+                    "DELEGATECALL" => CallType::DelegateCall,
+                    "CALLCODE" => CallType::CallCode,
+                    "AUTHCALL" => CallType::AuthCall,
+                    _ => CallType::Call,
+                },
+                gas,
+                input: frame.input.clone(),
+                to: frame.to.unwrap_or_default(),
+                value,
+            }),
+            TraceOutput::Call(CallOutput {
+                gas_used,
+                output: frame.output.clone().unwrap_or_default(),
+            }),
+        ),
+    };
+
+    TransactionTrace {
+        action,
+        // parity-style traces don't include a result for failed calls
+        result: if frame.error.is_some() { None } else { Some(result) },
+        error: frame.error.clone(),
+        subtraces: frame.calls.len(),
+        trace_address,
+    }
+}
+
+/// Build a skeleton [`VmTrace`] from a `callTracer` frame. Each log emitted by the frame becomes
+/// a `LOG{n}` operation, and each sub-call becomes an operation with a sub-trace.
+///
+/// Logs are ordered relative to sub-calls using their `position`, if the node provides it.
+/// Otherwise, they are placed after all sub-calls.
+pub fn to_vm_trace(frame: &CallFrame) -> VmTrace {
+    let mut ops = Vec::new();
+    let log_op = |log: &CallLogFrame| VmInstruction {
+        cost: 0,
+        ex: None,
+        pc: 0,
+        sub: None,
+        op: Some(format!("LOG{}", log.topics.as_ref().map(|t| t.len()).unwrap_or_default())),
+        idx: None,
+    };
+
+    for (i, call) in frame.calls.iter().enumerate() {
+        ops.extend(frame.logs.iter().filter(|log| log.position == Some(i as u64)).map(log_op));
+        ops.push(VmInstruction {
+            cost: 0,
+            ex: None,
+            pc: 0,
+            sub: Some(to_vm_trace(call)),
+            op: Some(call.typ.to_uppercase()),
+            idx: None,
+        });
+    }
+    ops.extend(
+        frame
+            .logs
+            .iter()
+            .filter(|log| log.position.is_none_or(|position| position >= frame.calls.len() as u64))
+            .map(log_op),
+    );
+
+    VmTrace { code: Bytes::new(), ops }
+}
+
+/// The logs emitted by a `callTracer` frame and its sub-calls, in execution order, as they would
+/// appear in the transaction's receipt. Logs of frames which reverted, and those within them, are
+/// skipped, as they were undone.
+///
+/// Logs are ordered relative to sub-calls using their `position`, as in [`to_vm_trace`].
+pub fn call_frame_logs(frame: &CallFrame) -> Vec<Log> {
+    let mut logs = Vec::new();
+    collect_call_frame_logs(frame, &mut logs);
+    for (i, log) in logs.iter_mut().enumerate() {
+        log.log_index = Some(i as u64);
+    }
+    logs
+}
+
+fn collect_call_frame_logs(frame: &CallFrame, logs: &mut Vec<Log>) {
+    if frame.error.is_some() {
+        return;
+    }
+
+    let to_log = |log: &CallLogFrame| Log {
+        inner: alloy::primitives::Log::new_unchecked(
+            log.address.or(frame.to).unwrap_or_default(),
+            log.topics.clone().unwrap_or_default(),
+            log.data.clone().unwrap_or_default(),
+        ),
+        ..Default::default()
+    };
+
+    for (i, call) in frame.calls.iter().enumerate() {
+        logs.extend(frame.logs.iter().filter(|log| log.position == Some(i as u64)).map(to_log));
+        collect_call_frame_logs(call, logs);
+    }
+    logs.extend(
+        frame
+            .logs
+            .iter()
+            .filter(|log| log.position.is_none_or(|position| position >= frame.calls.len() as u64))
+            .map(to_log),
+    );
+}
+
+/// The address whose code the call made by the struct log executes, if it's a call. The address
+/// is read from the log's stack, so it must have been traced with the stack enabled.
+fn callee(log: &StructLog) -> Option<Address> {
+    let stack = log.stack.as_ref()?;
+    match log.op.as_ref() {
+        "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL" => {
+            stack.len().checked_sub(2).map(|i| Address::from_word(stack[i].into()))
+        }
+        _ => None,
+    }
+}
+
+/// The addresses whose code is executed by the calls of the default struct logger's trace.
+pub fn struct_log_callees(logs: &[StructLog]) -> BTreeSet<Address> {
+    logs.iter().filter_map(callee).collect()
+}
+
+/// Build a [`VmTrace`] from the default struct logger's trace, nesting the operations executed
+/// by each call in a sub-trace. Each frame's code is the code of the root, or of its callee in
+/// `codes`. Contract creations execute their initcode, which is left empty.
+pub fn struct_logs_to_vm_trace(
+    logs: &[StructLog],
+    code: Bytes,
+    codes: &HashMap<Address, Bytes>,
+) -> VmTrace {
+    let mut position = 0;
+    let depth = logs.first().map(|log| log.depth).unwrap_or(1);
+    struct_log_frame(logs, &mut position, depth, code, codes)
+}
+
+/// Build the [`VmTrace`] of the frame at `depth`, starting at `position`.
+fn struct_log_frame(
+    logs: &[StructLog],
+    position: &mut usize,
+    depth: u64,
+    code: Bytes,
+    codes: &HashMap<Address, Bytes>,
+) -> VmTrace {
+    let mut ops = Vec::new();
+    while let Some(log) = logs.get(*position).filter(|log| log.depth == depth) {
+        *position += 1;
+        let sub = logs.get(*position).filter(|next| next.depth == depth + 1).map(|_| {
+            let code = callee(log).and_then(|address| codes.get(&address)).cloned();
+            struct_log_frame(logs, position, depth + 1, code.unwrap_or_default(), codes)
+        });
+        ops.push(VmInstruction {
+            cost: log.gas_cost,
+            ex: None,
+            pc: log.pc as usize,
+            sub,
+            op: Some(log.op.to_string()),
+            idx: None,
+        });
+    }
+
+    VmTrace { code, ops }
+}
+
+/// Convert a `prestateTracer` diff (with `diffMode: true`) into a Parity-style [`StateDiff`].
+///
+/// Geth omits unchanged fields from the post-state, and omits storage slots which were set to
+/// zero. Accounts which are only present in the pre-state were destroyed.
+pub fn to_state_diff(diff: DiffMode) -> StateDiff {
+    let addresses = diff.pre.keys().chain(diff.post.keys()).copied().collect::<BTreeSet<_>>();
+
+    StateDiff(
+        addresses
+            .into_iter()
+            .map(|address| {
+                let account_diff = match (diff.pre.get(&address), diff.post.get(&address)) {
+                    (Some(pre), Some(post)) => changed_account_diff(pre, post),
+                    (None, Some(post)) => AccountDiff {
+                        balance: Delta::Added(post.balance.unwrap_or_default()),
+                        code: Delta::Added(post.code.clone().unwrap_or_default()),
+                        nonce: Delta::Added(U64::from(post.nonce.unwrap_or_default())),
+                        storage: post.storage.iter().map(|(k, v)| (*k, Delta::Added(*v))).collect(),
+                    },
+                    (Some(pre), None) => AccountDiff {
+                        balance: Delta::Removed(pre.balance.unwrap_or_default()),
+                        code: Delta::Removed(pre.code.clone().unwrap_or_default()),
+                        nonce: Delta::Removed(U64::from(pre.nonce.unwrap_or_default())),
+                        storage: pre
+                            .storage
+                            .iter()
+                            .map(|(k, v)| (*k, Delta::Removed(*v)))
+                            .collect(),
+                    },
+                    (None, None) => unreachable!("address is present in either pre or post state"),
+                };
+
+                (address, account_diff)
+            })
+            .collect(),
+    )
+}
+
+fn changed_account_diff(pre: &AccountState, post: &AccountState) -> AccountDiff {
+    let slots = pre.storage.keys().chain(post.storage.keys()).copied().collect::<BTreeSet<_>>();
+
+    AccountDiff {
+        balance: changed_delta(pre.balance, post.balance, U256::ZERO),
+        code: changed_delta(pre.code.clone(), post.code.clone(), Bytes::new()),
+        nonce: changed_delta(pre.nonce.map(U64::from), post.nonce.map(U64::from), U64::ZERO),
+        storage: slots
+            .into_iter()
+            .map(|slot| {
+                let from = pre.storage.get(&slot).copied().unwrap_or_default();
+                let to = post.storage.get(&slot).copied().unwrap_or(B256::ZERO);
+                let delta = if from == to {
+                    Delta::Unchanged
+                } else {
+                    Delta::Changed(ChangedType { from, to })
+                };
+                (slot, delta)
+            })
+            .collect(),
+    }
+}
+
+/// Compare a field of the pre- and post-state. A field missing from the post-state is unchanged,
+/// while a field missing from the pre-state had its default value.
+fn changed_delta<T: PartialEq>(pre: Option<T>, post: Option<T>, default: T) -> Delta<T> {
+    match post {
+        Some(to) => {
+            let from = pre.unwrap_or(default);
+            if from == to {
+                Delta::Unchanged
+            } else {
+                Delta::Changed(ChangedType { from, to })
+            }
+        }
+        None => Delta::Unchanged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use alloy::primitives::{address, b256};
+
+    use super::*;
+
+    fn frame(typ: &str, calls: Vec<CallFrame>, logs: Vec<CallLogFrame>) -> CallFrame {
+        CallFrame {
+            from: address!("0000000000000000000000000000000000000001"),
+            to: Some(address!("0000000000000000000000000000000000000002")),
+            typ: typ.to_string(),
+            calls,
+            logs,
+            ..Default::default()
+        }
+    }
+
+    fn log(position: Option<u64>) -> CallLogFrame {
+        CallLogFrame { topics: Some(vec![B256::ZERO]), position, ..Default::default() }
+    }
+
+    #[test]
+    fn test_to_transaction_traces() {
+        let root = frame(
+            "CALL",
+            vec![
+                frame("STATICCALL", vec![], vec![]),
+                frame("DELEGATECALL", vec![frame("CREATE2", vec![], vec![])], vec![]),
+            ],
+            vec![],
+        );
+
+        let traces = to_transaction_traces(&root);
+        assert_eq!(
+            traces.iter().map(|t| t.trace_address.clone()).collect::<Vec<_>>(),
+            vec![vec![], vec![0], vec![1], vec![1, 0]]
+        );
+        assert_eq!(traces[0].subtraces, 2);
+        assert!(matches!(
+            &traces[1].action,
+            Action::Call(CallAction { call_type: CallType::StaticCall, .. })
+        ));
+        assert!(matches!(
+            &traces[3].action,
+            Action::Create(CreateAction { creation_method: CreationMethod::Create2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_failed_call_has_no_result() {
+        let mut root = frame("CALL", vec![], vec![]);
+        root.error = Some("execution reverted".to_string());
+
+        let traces = to_transaction_traces(&root);
+        assert!(traces[0].result.is_none());
+        assert_eq!(traces[0].error.as_deref(), Some("execution reverted"));
+    }
+
+    #[test]
+    fn test_to_vm_trace_orders_logs_by_position() {
+        let root = frame(
+            "CALL",
+            vec![frame("CALL", vec![], vec![log(None)])],
+            vec![log(Some(1)), log(Some(0))],
+        );
+
+        let vm_trace = to_vm_trace(&root);
+        let ops = vm_trace.ops.iter().map(|op| op.op.clone().unwrap()).collect::<Vec<_>>();
+        assert_eq!(ops, vec!["LOG1", "CALL", "LOG1"]);
+        assert_eq!(vm_trace.ops[1].sub.as_ref().unwrap().ops.len(), 1);
+    }
+
+    #[test]
+    fn test_call_frame_logs() {
+        let topic = |n: u8| B256::with_last_byte(n);
+        let log = |n: u8, position: Option<u64>| CallLogFrame {
+            topics: Some(vec![topic(n)]),
+            position,
+            ..Default::default()
+        };
+        let mut reverted = frame("CALL", vec![], vec![log(4, None)]);
+        reverted.error = Some("execution reverted".to_string());
+        let root = frame(
+            "CALL",
+            vec![frame("CALL", vec![], vec![log(2, None)]), reverted],
+            vec![log(3, Some(2)), log(1, Some(0))],
+        );
+
+        let logs = call_frame_logs(&root);
+        assert_eq!(
+            logs.iter().map(|log| log.topics()[0]).collect::<Vec<_>>(),
+            vec![topic(1), topic(2), topic(3)]
+        );
+        assert_eq!(logs[2].log_index, Some(2));
+        assert_eq!(logs[0].address(), address!("0000000000000000000000000000000000000002"));
+    }
+
+    #[test]
+    fn test_struct_logs_to_vm_trace() {
+        let callee = address!("0000000000000000000000000000000000000002");
+        let log = |pc: u64, op: &'static str, depth: u64, stack: Vec<U256>| StructLog {
+            pc,
+            op: op.into(),
+            gas: 100,
+            gas_cost: 3,
+            depth,
+            stack: Some(stack),
+            ..Default::default()
+        };
+        let logs = vec![
+            log(0, "PUSH1", 1, vec![]),
+            log(2, "CALL", 1, vec![U256::ZERO, U256::from_be_slice(callee.as_slice()), U256::ZERO]),
+            log(0, "STOP", 2, vec![]),
+            log(3, "STOP", 1, vec![]),
+        ];
+        assert_eq!(struct_log_callees(&logs), BTreeSet::from([callee]));
+
+        let codes = HashMap::from([(callee, Bytes::from_static(&[0x00]))]);
+        let trace = struct_logs_to_vm_trace(&logs, Bytes::from_static(&[0x60]), &codes);
+        assert_eq!(trace.ops.iter().map(|op| op.pc).collect::<Vec<_>>(), vec![0, 2, 3]);
+        let sub = trace.ops[1].sub.as_ref().expect("no sub-trace");
+        assert_eq!(sub.code, Bytes::from_static(&[0x00]));
+        assert_eq!(sub.ops[0].op.as_deref(), Some("STOP"));
+    }
+
+    #[test]
+    fn test_to_state_diff() {
+        let contract = address!("0000000000000000000000000000000000000002");
+        let created = address!("0000000000000000000000000000000000000003");
+        let cleared = b256!("0000000000000000000000000000000000000000000000000000000000000001");
+        let written = b256!("0000000000000000000000000000000000000000000000000000000000000002");
+        let value = b256!("00000000000000000000000000000000000000000000000000000000000000ff");
+
+        let diff = DiffMode {
+            pre: BTreeMap::from([(
+                contract,
+                AccountState {
+                    balance: Some(U256::from(1)),
+                    nonce: Some(1),
+                    storage: BTreeMap::from([(cleared, value)]),
+                    ..Default::default()
+                },
+            )]),
+            post: BTreeMap::from([
+                (
+                    contract,
+                    AccountState {
+                        balance: Some(U256::from(2)),
+                        storage: BTreeMap::from([(written, value)]),
+                        ..Default::default()
+                    },
+                ),
+                (created, AccountState { nonce: Some(1), ..Default::default() }),
+            ]),
+        };
+
+        let state_diff = to_state_diff(diff);
+        let account = &state_diff.0[&contract];
+        assert_eq!(
+            account.balance,
+            Delta::Changed(ChangedType { from: U256::from(1), to: U256::from(2) })
+        );
+        assert_eq!(account.nonce, Delta::Unchanged);
+        assert_eq!(
+            account.storage[&cleared],
+            Delta::Changed(ChangedType { from: value, to: B256::ZERO })
+        );
+        assert_eq!(
+            account.storage[&written],
+            Delta::Changed(ChangedType { from: B256::ZERO, to: value })
+        );
+        assert_eq!(state_diff.0[&created].nonce, Delta::Added(U64::from(1)));
+    }
+}
@@ -0,0 +1,310 @@
+//! Human-friendly renderings of decoded values, such as token amounts scaled by the token's
+//! decimals, UNIX timestamps, durations, basis points and `type(uintN).max` sentinels.
+
+use std::sync::Mutex;
+
+use alloy::primitives::{Address, U256};
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use chrono::DateTime;
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use tracing::debug;
+
+use crate::{
+    ether::rpc,
+    utils::{integers::ToLocaleString, offline::is_offline},
+};
+
+/// The selector of `decimals()`
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// The selector of `symbol()`
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+
+/// The functions whose input at the given index is an amount of the token being called, keyed
+/// by the function's name and number of inputs.
+const TOKEN_AMOUNT_INPUTS: [(&str, usize, usize); 10] = [
+    ("transfer", 2, 1),
+    ("transferFrom", 3, 2),
+    ("approve", 2, 1),
+    ("increaseAllowance", 2, 1),
+    ("decreaseAllowance", 2, 1),
+    ("permit", 7, 2),
+    ("mint", 2, 1),
+    ("burn", 1, 0),
+    ("burn", 2, 1),
+    ("burnFrom", 2, 1),
+];
+
+/// The functions whose first output is an amount of the token being called, keyed by the
+/// function's name and number of inputs.
+const TOKEN_AMOUNT_OUTPUTS: [(&str, usize); 3] =
+    [("balanceOf", 1), ("totalSupply", 0), ("allowance", 2)];
+
+/// The widths of the uints whose maximum value is commonly used as a sentinel, such as for
+/// infinite approvals.
+const SENTINEL_BITS: [usize; 7] = [256, 128, 112, 96, 64, 48, 32];
+
+/// Values within this range, from 2000 to 2100, are displayed as UNIX timestamps.
+const TIMESTAMP_RANGE: std::ops::Range<u64> = 946_684_800..4_102_444_800;
+
+lazy_static! {
+    /// The tokens already fetched from each rpc, so that each is only fetched once.
+    static ref TOKENS: Mutex<HashMap<(String, Address), Option<Token>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// The metadata of an ERC-20 token needed to display its amounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// The token's symbol, e.g. `USDC`
+    pub symbol: String,
+    /// The number of decimals the token's amounts are scaled by
+    pub decimals: u8,
+}
+
+impl Token {
+    /// Displays the amount scaled by the token's decimals, along with its raw value.
+    ///
+    /// ```
+    /// use alloy::primitives::U256;
+    /// use heimdall_common::ether::humanize::Token;
+    ///
+    /// let usdc = Token { symbol: "USDC".to_string(), decimals: 6 };
+    /// assert_eq!(usdc.format_amount(U256::from(1000500000)), "1,000.5 USDC (1000500000)");
+    /// ```
+    pub fn format_amount(&self, amount: U256) -> String {
+        format!("{} {} ({amount})", format_units(amount, self.decimals), self.symbol)
+    }
+}
+
+/// Fetches the symbol and decimals of the token at `address`, or `None` if it doesn't look like
+/// an ERC-20 token, or it couldn't be fetched. Tokens are cached for the rest of the run.
+pub async fn get_token(address: Address, rpc_url: &str) -> Option<Token> {
+    if rpc_url.is_empty() || is_offline() {
+        return None;
+    }
+
+    let key = (rpc_url.to_string(), address);
+    if let Some(token) = TOKENS.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return token.clone();
+    }
+
+    let calls = [(address, DECIMALS_SELECTOR.to_vec()), (address, SYMBOL_SELECTOR.to_vec())];
+    let token = match rpc::call_batch(&calls, None, rpc_url).await {
+        Ok(results) => match (results.first(), results.get(1)) {
+            (Some(Ok(decimals)), Some(Ok(symbol))) => decode_decimals(decimals)
+                .zip(decode_symbol(symbol))
+                .map(|(decimals, symbol)| Token { symbol, decimals }),
+            _ => None,
+        },
+        Err(e) => {
+            debug!("failed to fetch token {}: {}", address, e);
+            None
+        }
+    };
+
+    TOKENS.lock().unwrap_or_else(|e| e.into_inner()).insert(key, token.clone());
+    token
+}
+
+/// Renders each of the inputs of a call to the function `name` on `contract` in a human-friendly
+/// way, or `None` for inputs which don't have a friendlier rendering. Amounts of the token being
+/// called are scaled by its decimals, which are fetched from `rpc_url`.
+pub async fn humanize_inputs(
+    name: &str,
+    inputs: &[DynSolValue],
+    contract: Option<Address>,
+    rpc_url: &str,
+) -> Vec<Option<String>> {
+    let amount_index = TOKEN_AMOUNT_INPUTS
+        .iter()
+        .find(|(function, arity, _)| *function == name && *arity == inputs.len())
+        .map(|(_, _, index)| *index);
+    let token = match (amount_index, contract) {
+        (Some(_), Some(contract)) => get_token(contract, rpc_url).await,
+        _ => None,
+    };
+
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| match (input, &token) {
+            (DynSolValue::Uint(amount, _), Some(token)) if amount_index == Some(i) => {
+                Some(humanize_sentinel(*amount).unwrap_or_else(|| token.format_amount(*amount)))
+            }
+            (input, _) => humanize_value(name, input),
+        })
+        .collect()
+}
+
+/// Renders each of the outputs of a call to the function `name`, which took `input_count`
+/// arguments, on `contract` in a human-friendly way, or `None` for outputs which don't have a
+/// friendlier rendering.
+pub async fn humanize_outputs(
+    name: &str,
+    input_count: usize,
+    outputs: &[DynSolValue],
+    contract: Option<Address>,
+    rpc_url: &str,
+) -> Vec<Option<String>> {
+    let is_token_amount = TOKEN_AMOUNT_OUTPUTS
+        .iter()
+        .any(|(function, arity)| *function == name && *arity == input_count);
+    let token = match (is_token_amount, contract) {
+        (true, Some(contract)) => get_token(contract, rpc_url).await,
+        _ => None,
+    };
+
+    outputs
+        .iter()
+        .enumerate()
+        .map(|(i, output)| match (output, &token) {
+            (DynSolValue::Uint(amount, _), Some(token)) if i == 0 => {
+                Some(humanize_sentinel(*amount).unwrap_or_else(|| token.format_amount(*amount)))
+            }
+            (output, _) => humanize_value(name, output),
+        })
+        .collect()
+}
+
+/// Renders the value in a human-friendly way without knowing which token it may be an amount
+/// of, or `None` if it doesn't have a friendlier rendering. Basis points are only recognized in
+/// calls to functions whose `name` mentions them.
+///
+/// ```
+/// use alloy::primitives::U256;
+/// use alloy_dyn_abi::DynSolValue;
+/// use heimdall_common::ether::humanize::humanize_value;
+///
+/// let duration = DynSolValue::Uint(U256::from(604800), 256);
+/// assert_eq!(humanize_value("setDelay", &duration), Some("7 days (604800)".to_string()));
+/// ```
+pub fn humanize_value(name: &str, value: &DynSolValue) -> Option<String> {
+    let DynSolValue::Uint(value, _) = value else {
+        return None;
+    };
+    if let Some(sentinel) = humanize_sentinel(*value) {
+        return Some(sentinel);
+    }
+
+    let seconds = u64::try_from(*value).ok()?;
+    if TIMESTAMP_RANGE.contains(&seconds) {
+        return DateTime::from_timestamp(seconds as i64, 0)
+            .map(|time| format!("{} ({seconds})", time.format("%Y-%m-%d %H:%M:%S UTC")));
+    }
+
+    let name = name.to_lowercase();
+    if ["bps", "bips", "basispoint"].iter().any(|unit| name.contains(unit)) && seconds <= 10_000 {
+        return Some(format!("{}% ({seconds} bps)", format_units(*value, 2)));
+    }
+
+    (seconds >= 60 && seconds % 60 == 0)
+        .then(|| format!("{} ({seconds})", format_duration(seconds)))
+}
+
+/// Renders the value as `type(uintN).max` if it's the maximum value of a commonly used uint.
+fn humanize_sentinel(value: U256) -> Option<String> {
+    SENTINEL_BITS
+        .iter()
+        .find(|bits| value == U256::MAX >> (256 - **bits))
+        .map(|bits| format!("type(uint{bits}).max"))
+}
+
+/// Scales the amount down by `decimals`, grouping the digits of its integer part and trimming
+/// the trailing zeros of its fractional part.
+pub fn format_units(amount: U256, decimals: u8) -> String {
+    let scale = U256::from(10).pow(U256::from(decimals));
+    let integer = (amount / scale).to_locale_string();
+    let fraction = format!("{:0>width$}", (amount % scale).to_string(), width = decimals as usize);
+    match fraction.trim_end_matches('0') {
+        "" => integer,
+        fraction => format!("{integer}.{fraction}"),
+    }
+}
+
+/// Displays a number of seconds, which is a multiple of a minute, in days, hours and minutes.
+fn format_duration(seconds: u64) -> String {
+    [
+        (seconds / 86_400, "day"),
+        (seconds % 86_400 / 3_600, "hour"),
+        (seconds % 3_600 / 60, "minute"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, unit)| match count {
+        1 => format!("1 {unit}"),
+        count => format!("{count} {unit}s"),
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// Decodes the return data of `decimals()`, which is a `uint8` padded to a word.
+fn decode_decimals(returndata: &[u8]) -> Option<u8> {
+    match DynSolType::Uint(256).abi_decode(returndata).ok()? {
+        DynSolValue::Uint(decimals, _) => u8::try_from(decimals).ok(),
+        _ => None,
+    }
+}
+
+/// Decodes the return data of `symbol()`, which is a `string` for most tokens, but a `bytes32`
+/// for some older tokens, such as MKR.
+fn decode_symbol(returndata: &[u8]) -> Option<String> {
+    if let Ok(DynSolValue::String(symbol)) = DynSolType::String.abi_decode(returndata) {
+        return (!symbol.is_empty()).then_some(symbol);
+    }
+
+    let word = returndata.get(..32)?;
+    let symbol =
+        String::from_utf8(word.iter().copied().take_while(|byte| *byte != 0).collect()).ok()?;
+    (!symbol.is_empty() && symbol.chars().all(|c| c.is_ascii_graphic())).then_some(symbol)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::sol_types::SolValue;
+
+    use super::*;
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(U256::from(1000500000), 6), "1,000.5");
+        assert_eq!(format_units(U256::from(1), 18), "0.000000000000000001");
+        assert_eq!(format_units(U256::from(25) * U256::from(10).pow(U256::from(17)), 18), "2.5");
+        assert_eq!(format_units(U256::from(42), 0), "42");
+    }
+
+    #[test]
+    fn test_humanize_sentinel() {
+        assert_eq!(humanize_sentinel(U256::MAX), Some("type(uint256).max".to_string()));
+        assert_eq!(humanize_sentinel(U256::from(u128::MAX)), Some("type(uint128).max".to_string()));
+        assert_eq!(humanize_sentinel(U256::from(255)), None);
+    }
+
+    #[test]
+    fn test_humanize_value() {
+        let uint = |value: u64| DynSolValue::Uint(U256::from(value), 256);
+        assert_eq!(
+            humanize_value("deposit", &uint(1704067200)),
+            Some("2024-01-01 00:00:00 UTC (1704067200)".to_string())
+        );
+        assert_eq!(
+            humanize_value("setDelay", &uint(90000)),
+            Some("1 day 1 hour (90000)".to_string())
+        );
+        assert_eq!(humanize_value("setFeeBps", &uint(250)), Some("2.5% (250 bps)".to_string()));
+        assert_eq!(humanize_value("setFee", &uint(250)), None);
+        assert_eq!(humanize_value("deposit", &DynSolValue::Bool(true)), None);
+    }
+
+    #[test]
+    fn test_decode_symbol() {
+        assert_eq!(decode_symbol(&"USDC".to_string().abi_encode()), Some("USDC".to_string()));
+
+        let mut mkr = [0u8; 32];
+        mkr[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_symbol(&mkr), Some("MKR".to_string()));
+        assert_eq!(decode_symbol(&[0u8; 32]), None);
+    }
+}
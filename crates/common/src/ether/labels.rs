@@ -0,0 +1,461 @@
+//! A local database of address labels, such as `Uniswap V3 Router`, shown in place of or
+//! alongside bare addresses in decode, inspect and dump output.
+//!
+//! Labels for well-known mainnet contracts are built in. User labels are recorded in
+//! `~/.bifrost/labels.csv`, and take precedence over the built-in ones. They may be added one at
+//! a time with `heimdall labels add`, or imported from a CSV or TOML file, such as an export of
+//! Etherscan's label cloud, with `heimdall labels import`.
+
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use alloy::primitives::{address, Address};
+use clap::{Parser, ValueEnum};
+use eyre::{bail, eyre, Result};
+use tracing::{debug, warn};
+
+use crate::utils::io::file::read_file;
+
+/// The name of the label database file in `~/.bifrost`.
+pub const LABEL_DB_FILE: &str = "labels.csv";
+
+/// The label database used by this process, opened on first use.
+static LABEL_DB: Mutex<Option<LabelDb>> = Mutex::new(None);
+
+/// Labels of well-known mainnet contracts.
+pub const BUILTIN_LABELS: [(Address, &str); 22] = [
+    (address!("C02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"), "WETH"),
+    (address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"), "USDC"),
+    (address!("dAC17F958D2ee523a2206206994597C13D831ec7"), "USDT"),
+    (address!("6B175474E89094C44Da98b954EedeAC495271d0F"), "DAI"),
+    (address!("2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"), "WBTC"),
+    (address!("ae7ab96520DE3A18E5e111B5EaAb095312D7fE84"), "Lido stETH"),
+    (address!("5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"), "Uniswap V2 Factory"),
+    (address!("7a250d5630B4cF539739dF2C5dAcb4c659F2488D"), "Uniswap V2 Router"),
+    (address!("1F98431c8aD98523631AE4a59f267346ea31F984"), "Uniswap V3 Factory"),
+    (address!("E592427A0AEce92De3Edee1F18E0157C05861564"), "Uniswap V3 Router"),
+    (address!("68b3465833fb72A70ecDF485E0e4C7bD8665Fc45"), "Uniswap V3 Router 2"),
+    (address!("3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"), "Uniswap Universal Router"),
+    (address!("000000000022D473030F116dDEE9F6B43aC78BA3"), "Permit2"),
+    (address!("cA11bde05977b3631167028862bE2a173976CA11"), "Multicall3"),
+    (address!("1111111254EEB25477B68fb85Ed929f73A960582"), "1inch V5 Router"),
+    (address!("Def1C0ded9bec7F1a1670819833240f027b25EfF"), "0x Exchange Proxy"),
+    (address!("00000000000000ADc04C56Bf30aC9d3c0aAF14dC"), "Seaport 1.5"),
+    (address!("87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2"), "Aave V3 Pool"),
+    (address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e"), "ENS Registry"),
+    (address!("00000000219ab540356cBB839Cbe05303d7705Fa"), "Beacon Deposit Contract"),
+    (address!("d9Db270c1B5E3Bd161E8c8503c55cEABeE709552"), "Safe Singleton 1.3.0"),
+    (address!("a6B71E26C5e0845f74c812102Ca7114b6a896AB2"), "Safe Proxy Factory 1.3.0"),
+];
+
+/// Clap argument parser for the labels subcommand
+#[derive(Debug, Clone, Parser)]
+#[clap(
+    about = "Manage heimdall-rs' local address labels",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall labels <SUBCOMMAND>"
+)]
+pub struct LabelsArgs {
+    /// Labels subcommand
+    #[clap(subcommand)]
+    pub sub: Subcommands,
+}
+
+/// Arguments for the add subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct AddArgs {
+    /// The address to label.
+    pub address: Address,
+
+    /// The label to show for the address.
+    pub label: String,
+}
+
+/// Arguments for the import subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct ImportArgs {
+    /// The file to import labels from.
+    pub file: String,
+
+    /// The format of the file. Detected from its contents if not given.
+    #[clap(long, short)]
+    pub format: Option<LabelFormat>,
+}
+
+/// Arguments for the list subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct ListArgs {
+    /// Whether to also list the built-in labels of well-known contracts.
+    #[clap(long)]
+    pub builtin: bool,
+}
+
+/// Clap subcommand parser for labels subcommands
+#[derive(Debug, Clone, Parser)]
+pub enum Subcommands {
+    /// Label a single address
+    #[clap(name = "add", about = "Adds a label to ~/.bifrost/labels.csv")]
+    Add(AddArgs),
+
+    /// Import labels from a CSV or TOML file
+    #[clap(name = "import", about = "Imports labels into ~/.bifrost/labels.csv")]
+    Import(ImportArgs),
+
+    /// List the label database
+    #[clap(name = "list", about = "Lists all labels in ~/.bifrost/labels.csv")]
+    List(ListArgs),
+}
+
+/// The formats labels may be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LabelFormat {
+    /// One `address,label` row per line. If the first row is a header, the address is read from
+    /// its `Address` column, and the label from its `Name Tag`, `Label` or `Name` column, as in
+    /// Etherscan's label cloud exports.
+    Csv,
+    /// A table of `"0x..." = "label"` pairs, optionally under a `[labels]` table.
+    Toml,
+}
+
+impl LabelFormat {
+    /// Detect the format of the given file contents.
+    pub fn detect(contents: &str) -> Self {
+        match contents.parse::<toml::Table>() {
+            Ok(table) if !table.is_empty() => LabelFormat::Toml,
+            _ => LabelFormat::Csv,
+        }
+    }
+}
+
+/// [`LabelDb`] is a map of addresses to their labels, optionally backed by a CSV file with one
+/// `address,label` row per line. Later rows take precedence, so relabeling an address only
+/// appends a row.
+#[derive(Debug, Default, Clone)]
+pub struct LabelDb {
+    path: Option<PathBuf>,
+    labels: BTreeMap<Address, String>,
+}
+
+impl LabelDb {
+    /// Create an empty database which isn't backed by a file.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Open the database backed by the file at `path`, which is created on the first insertion
+    /// if it doesn't exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut db = Self { path: Some(path.to_path_buf()), labels: BTreeMap::new() };
+        if path.exists() {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| eyre!("failed to read '{}': {}", path.display(), e))?;
+            db.labels.extend(parse_labels(&contents, LabelFormat::Csv)?);
+        }
+        Ok(db)
+    }
+
+    /// Get the label recorded for the given address, if any.
+    pub fn lookup(&self, address: &Address) -> Option<&str> {
+        self.labels.get(address).map(String::as_str)
+    }
+
+    /// Insert the given `(address, label)` pairs, returning the number which weren't already
+    /// recorded. New and changed labels are appended to the backing file, if any.
+    pub fn insert(
+        &mut self,
+        entries: impl IntoIterator<Item = (Address, String)>,
+    ) -> Result<usize> {
+        let mut new_entries = String::new();
+        let mut inserted = 0;
+        for (address, label) in entries {
+            let label = label.trim();
+            if label.is_empty() || label.contains('\n') || self.lookup(&address) == Some(label) {
+                continue;
+            }
+
+            self.labels.insert(address, label.to_string());
+            new_entries.push_str(&format!("{address},{}\n", escape_field(label)));
+            inserted += 1;
+        }
+
+        if let (Some(path), false) = (&self.path, new_entries.is_empty()) {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(new_entries.as_bytes()))
+                .map_err(|e| eyre!("failed to write '{}': {}", path.display(), e))?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// The number of labeled addresses.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Whether no labels are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Iterate over all `(address, label)` pairs, ordered by address.
+    pub fn entries(&self) -> impl Iterator<Item = (&Address, &str)> {
+        self.labels.iter().map(|(address, label)| (address, label.as_str()))
+    }
+}
+
+/// Parse `(address, label)` pairs from the given file contents. Rows whose address is invalid
+/// are skipped.
+///
+/// ```
+/// use heimdall_common::ether::labels::{parse_labels, LabelFormat};
+///
+/// let entries = parse_labels(
+///     "\"Address\",\"Name Tag\"\n\"0xE592427A0AEce92De3Edee1F18E0157C05861564\",\"Uniswap V3: Router\"",
+///     LabelFormat::Csv,
+/// )
+/// .unwrap();
+/// assert_eq!(entries[0].1, "Uniswap V3: Router");
+/// ```
+pub fn parse_labels(contents: &str, format: LabelFormat) -> Result<Vec<(Address, String)>> {
+    let entries = match format {
+        LabelFormat::Csv => {
+            let mut rows = contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(split_fields)
+                .peekable();
+
+            // the first row is a header if it doesn't start with an address
+            let (address_column, label_column) = match rows.peek() {
+                Some(header) if header.first().is_some_and(|field| !field.starts_with("0x")) => {
+                    let column = |names: &[&str]| {
+                        header
+                            .iter()
+                            .position(|field| names.contains(&field.to_lowercase().as_str()))
+                    };
+                    let columns = column(&["address"]).zip(column(&["name tag", "label", "name"]));
+                    match columns {
+                        Some(columns) => {
+                            rows.next();
+                            columns
+                        }
+                        None => bail!("expected a header with 'Address' and 'Name Tag' columns"),
+                    }
+                }
+                _ => (0, 1),
+            };
+
+            rows.filter_map(|row| {
+                Some((row.get(address_column)?.clone(), row.get(label_column)?.clone()))
+            })
+            .collect::<Vec<_>>()
+        }
+        LabelFormat::Toml => {
+            let mut table = contents.parse::<toml::Table>()?;
+            if let Some(toml::Value::Table(labels)) = table.remove("labels") {
+                table = labels;
+            }
+
+            table
+                .into_iter()
+                .filter_map(|(address, label)| Some((address, label.as_str()?.to_string())))
+                .collect::<Vec<_>>()
+        }
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter_map(|(address, label)| match address.trim().parse::<Address>() {
+            Ok(address) => Some((address, label)),
+            Err(_) => {
+                debug!("skipping label '{}' of invalid address '{}'", label, address);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Split a CSV row into its fields, unquoting quoted fields.
+fn split_fields(row: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = row.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Quote a CSV field if it contains a comma or quote.
+fn escape_field(field: &str) -> String {
+    match field.contains([',', '"']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// The path of the label database, `~/.bifrost/labels.csv`.
+#[allow(deprecated)]
+fn default_label_db_path() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".bifrost").join(LABEL_DB_FILE))
+}
+
+/// Run `f` against the label database used by this process, opening it on first use. If it
+/// can't be opened, an in-memory database is used instead.
+fn with_label_db<T>(f: impl FnOnce(&mut LabelDb) -> T) -> T {
+    let mut db = LABEL_DB.lock().unwrap_or_else(|e| e.into_inner());
+    let db = db.get_or_insert_with(|| {
+        let opened = default_label_db_path()
+            .ok_or_else(|| eyre!("failed to get home directory"))
+            .and_then(|path| LabelDb::open(&path));
+        opened.unwrap_or_else(|e| {
+            warn!("failed to open label database: {}", e);
+            LabelDb::in_memory()
+        })
+    });
+    f(db)
+}
+
+/// Get the built-in label of the given well-known contract, if any.
+///
+/// ```
+/// use alloy::primitives::address;
+/// use heimdall_common::ether::labels::builtin_label;
+///
+/// let label = builtin_label(&address!("E592427A0AEce92De3Edee1F18E0157C05861564"));
+/// assert_eq!(label, Some("Uniswap V3 Router"));
+/// ```
+pub fn builtin_label(address: &Address) -> Option<&'static str> {
+    BUILTIN_LABELS.iter().find(|(labeled, _)| labeled == address).map(|(_, label)| *label)
+}
+
+/// Get the label of the given address, preferring the user's label over the built-in one.
+pub fn lookup_label(address: &Address) -> Option<String> {
+    with_label_db(|db| db.lookup(address).map(str::to_string))
+        .or_else(|| builtin_label(address).map(str::to_string))
+}
+
+/// Display the address followed by its label, if it has one.
+///
+/// ```
+/// use alloy::primitives::address;
+/// use heimdall_common::ether::labels::display_address;
+///
+/// let permit2 = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+/// assert_eq!(display_address(&permit2), format!("{permit2} (Permit2)"));
+/// ```
+pub fn display_address(address: &Address) -> String {
+    match lookup_label(address) {
+        Some(label) => format!("{address} ({label})"),
+        None => address.to_string(),
+    }
+}
+
+/// Labels subcommand handler
+pub fn labels(args: LabelsArgs) -> Result<()> {
+    match args.sub {
+        Subcommands::Add(args) => {
+            with_label_db(|db| db.insert([(args.address, args.label.clone())]))?;
+
+            println!("Labeled {} as '{}'.", args.address, args.label.trim());
+        }
+        Subcommands::Import(args) => {
+            let contents = read_file(&args.file)?;
+            let format = args.format.unwrap_or_else(|| LabelFormat::detect(&contents));
+            let entries = parse_labels(&contents, format)?;
+            let parsed = entries.len();
+            let imported = with_label_db(|db| db.insert(entries))?;
+
+            println!("Imported {imported} new labels ({parsed} parsed) from '{}'.", args.file);
+        }
+        Subcommands::List(args) => {
+            let mut labels = with_label_db(|db| {
+                db.entries()
+                    .map(|(address, label)| (*address, label.to_string()))
+                    .collect::<BTreeMap<_, _>>()
+            });
+            if args.builtin {
+                for (address, label) in BUILTIN_LABELS {
+                    labels.entry(address).or_insert_with(|| format!("{label} (built-in)"));
+                }
+            }
+
+            for (address, label) in &labels {
+                println!("{address}  {label}");
+            }
+            println!("{} labels.", labels.len());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_labels() {
+        let contents = "# my labels\n0xE592427A0AEce92De3Edee1F18E0157C05861564,\"Router, V3\"\nnot an address,Foo\n";
+        assert_eq!(LabelFormat::detect(contents), LabelFormat::Csv);
+
+        let entries = parse_labels(contents, LabelFormat::Csv).unwrap();
+        assert_eq!(
+            entries,
+            vec![(address!("E592427A0AEce92De3Edee1F18E0157C05861564"), "Router, V3".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_toml_labels() {
+        let contents = "[labels]\n\"0x000000000022D473030F116dDEE9F6B43aC78BA3\" = \"Permit2\"\n";
+        assert_eq!(LabelFormat::detect(contents), LabelFormat::Toml);
+
+        let entries = parse_labels(contents, LabelFormat::Toml).unwrap();
+        assert_eq!(
+            entries,
+            vec![(address!("000000000022D473030F116dDEE9F6B43aC78BA3"), "Permit2".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_label_db_persists() {
+        let path = std::env::temp_dir().join(format!("heimdall-labels-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let router = address!("E592427A0AEce92De3Edee1F18E0157C05861564");
+
+        let mut db = LabelDb::open(&path).unwrap();
+        assert!(db.is_empty());
+        assert_eq!(db.insert([(router, "My \"Router\", V3".to_string())]).unwrap(), 1);
+        assert_eq!(db.insert([(router, "My \"Router\", V3".to_string())]).unwrap(), 0);
+        assert_eq!(db.insert([(router, "Router".to_string())]).unwrap(), 1);
+
+        let reopened = LabelDb::open(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(reopened.lookup(&router), Some("Router"));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,249 @@
+//! Module for decoding the metadata compilers append to bytecode.
+
+use crate::utils::strings::encode_hex;
+
+/// The metadata solc and vyper append to the end of the bytecode, which is CBOR-encoded and
+/// followed by its length as two big-endian bytes.
+///
+/// Research:
+/// https://docs.soliditylang.org/en/latest/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilerMetadata {
+    /// The hash of the contract's metadata file, as a URI, e.g. `ipfs://Qm..`. The metadata file
+    /// includes the compiler's settings, so this fingerprints them.
+    pub metadata_hash: Option<String>,
+    /// The version of solc which compiled the contract, e.g. `0.8.28`
+    pub solc: Option<String>,
+    /// The version of vyper which compiled the contract, e.g. `0.3.10`
+    pub vyper: Option<String>,
+    /// Whether the contract was compiled with experimental features enabled
+    pub experimental: bool,
+}
+
+/// The subset of CBOR which compilers encode their metadata with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cbor {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Bool(bool),
+    Null,
+}
+
+/// Decodes the compiler metadata at the end of the bytecode, if it has any.
+///
+/// ```
+/// use heimdall_common::ether::metadata::decode_metadata;
+///
+/// // {"solc": 0x00081c}, followed by its length
+/// let bytecode = [0x00, 0xa1, 0x64, 0x73, 0x6f, 0x6c, 0x63, 0x43, 0x00, 0x08, 0x1c, 0x00, 0x0a];
+/// let metadata = decode_metadata(&bytecode).expect("failed to decode metadata");
+/// assert_eq!(metadata.solc.as_deref(), Some("0.8.28"));
+/// ```
+pub fn decode_metadata(bytecode: &[u8]) -> Option<CompilerMetadata> {
+    let length_at = bytecode.len().checked_sub(2)?;
+    let length = u16::from_be_bytes([bytecode[length_at], bytecode[length_at + 1]]) as usize;
+    let encoded = &bytecode[length_at.checked_sub(length)?..length_at];
+
+    let mut position = 0;
+    let value = decode_cbor(encoded, &mut position, 0)?;
+    if position != encoded.len() {
+        return None;
+    }
+
+    // vyper 0.3.10+ wraps its metadata map in an array of the contract's section sizes
+    let entries = match value {
+        Cbor::Map(entries) => entries,
+        Cbor::Array(items) => items.into_iter().find_map(|item| match item {
+            Cbor::Map(entries) => Some(entries),
+            _ => None,
+        })?,
+        _ => return None,
+    };
+
+    let mut metadata = CompilerMetadata::default();
+    for (key, value) in entries {
+        let Cbor::Text(key) = key else {
+            continue;
+        };
+        match (key.as_str(), value) {
+            ("ipfs", Cbor::Bytes(hash)) => {
+                metadata.metadata_hash = Some(format!("ipfs://{}", encode_base58(&hash)))
+            }
+            ("bzzr0" | "bzzr1", Cbor::Bytes(hash)) => {
+                metadata.metadata_hash = Some(format!("{key}://{}", encode_hex(&hash)))
+            }
+            ("solc", Cbor::Bytes(version)) => metadata.solc = Some(join_version(&version)),
+            ("solc", Cbor::Text(version)) => metadata.solc = Some(version),
+            ("vyper", Cbor::Array(version)) => {
+                let version = version
+                    .iter()
+                    .map(|part| match part {
+                        Cbor::Uint(part) => Some(part.to_string()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                metadata.vyper = Some(version.join("."));
+            }
+            ("experimental", Cbor::Bool(experimental)) => metadata.experimental = experimental,
+            _ => {}
+        }
+    }
+
+    match metadata == CompilerMetadata::default() {
+        true => None,
+        false => Some(metadata),
+    }
+}
+
+/// Decodes the CBOR value at the position, advancing past it. Nesting is limited, since the
+/// metadata is only ever a map or an array of maps.
+fn decode_cbor(data: &[u8], position: &mut usize, depth: usize) -> Option<Cbor> {
+    if depth > 4 {
+        return None;
+    }
+
+    let initial = *data.get(*position)?;
+    *position += 1;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+
+    // simple values don't have an argument
+    if major == 7 {
+        return match info {
+            20 => Some(Cbor::Bool(false)),
+            21 => Some(Cbor::Bool(true)),
+            22 => Some(Cbor::Null),
+            _ => None,
+        };
+    }
+
+    let argument = match info {
+        0..=23 => info as u64,
+        24..=27 => {
+            let size = 1 << (info - 24);
+            let bytes = data.get(*position..*position + size)?;
+            *position += size;
+            bytes.iter().fold(0, |value, byte| (value << 8) | *byte as u64)
+        }
+        _ => return None,
+    };
+
+    match major {
+        0 => Some(Cbor::Uint(argument)),
+        2 | 3 => {
+            let end = position.checked_add(usize::try_from(argument).ok()?)?;
+            let bytes = data.get(*position..end)?.to_vec();
+            *position = end;
+            match major {
+                2 => Some(Cbor::Bytes(bytes)),
+                _ => String::from_utf8(bytes).ok().map(Cbor::Text),
+            }
+        }
+        4 => (0..argument)
+            .map(|_| decode_cbor(data, position, depth + 1))
+            .collect::<Option<Vec<_>>>()
+            .map(Cbor::Array),
+        5 => (0..argument)
+            .map(|_| {
+                Some((
+                    decode_cbor(data, position, depth + 1)?,
+                    decode_cbor(data, position, depth + 1)?,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Cbor::Map),
+        _ => None,
+    }
+}
+
+/// Joins the bytes of a release version, e.g. `[0, 8, 28]` becomes `0.8.28`.
+fn join_version(version: &[u8]) -> String {
+    version.iter().map(|part| part.to_string()).collect::<Vec<_>>().join(".")
+}
+
+/// Encodes the bytes with the bitcoin base58 alphabet, which IPFS hashes are displayed in.
+fn encode_base58(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    // repeatedly divide the big-endian number by 58, keeping its digits little-endian
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in bytes {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // leading zero bytes are encoded as leading ones
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    std::iter::repeat_n(ALPHABET[0], zeros)
+        .chain(digits.iter().rev().map(|digit| ALPHABET[*digit as usize]))
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::strings::decode_hex;
+
+    #[test]
+    fn test_decode_solc_metadata() {
+        // {"ipfs": 0x1220.., "solc": 0x00081c}
+        let bytecode = decode_hex("6080a264697066735822122077f1d1c04d32a84a569b4aed05ef453ec4f464fcac98de97bb2f5033df2c4a0464736f6c634300081c0033").unwrap();
+        let metadata = decode_metadata(&bytecode).expect("failed to decode metadata");
+        assert_eq!(metadata.solc.as_deref(), Some("0.8.28"));
+        assert_eq!(
+            metadata.metadata_hash.as_deref(),
+            Some("ipfs://QmWQs6H9EXrD9VdS8GELWzAHfRSzEEKudjYorW1CivXyDD")
+        );
+        assert!(!metadata.experimental);
+    }
+
+    #[test]
+    fn test_decode_swarm_metadata() {
+        // {"bzzr0": 0x.., "experimental": true}
+        let mut bytecode = decode_hex("a265627a7a72305820").unwrap();
+        bytecode.extend([0xab; 32]);
+        bytecode.extend(decode_hex("6c6578706572696d656e74616cf5").unwrap());
+        bytecode.extend((bytecode.len() as u16).to_be_bytes());
+
+        let metadata = decode_metadata(&bytecode).expect("failed to decode metadata");
+        assert_eq!(metadata.metadata_hash, Some(format!("bzzr0://{}", "ab".repeat(32))));
+        assert!(metadata.experimental);
+        assert_eq!(metadata.solc, None);
+    }
+
+    #[test]
+    fn test_decode_vyper_metadata() {
+        // {"vyper": [0, 3, 9]}
+        let bytecode = decode_hex("a165767970657283000309000b").unwrap();
+        assert_eq!(decode_metadata(&bytecode).unwrap().vyper.as_deref(), Some("0.3.9"));
+
+        // [runtime size, [data sizes], immutables size, {"vyper": [0, 3, 10]}]
+        let bytecode = decode_hex("841901238019012ca16576797065728300030a0013").unwrap();
+        assert_eq!(decode_metadata(&bytecode).unwrap().vyper.as_deref(), Some("0.3.10"));
+    }
+
+    #[test]
+    fn test_decode_metadata_invalid() {
+        assert_eq!(decode_metadata(&[]), None);
+        assert_eq!(decode_metadata(&[0x60, 0x80, 0x60, 0x40, 0x52]), None);
+        assert_eq!(decode_metadata(&[0xa1, 0x64, 0x73, 0x6f, 0x00, 0x05]), None);
+    }
+
+    #[test]
+    fn test_encode_base58() {
+        assert_eq!(encode_base58(&[]), "");
+        assert_eq!(encode_base58(&[0, 0, 1]), "112");
+        assert_eq!(encode_base58(b"hello world"), "StV1DL6CwTryKyV");
+    }
+}
@@ -1,9 +1,35 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod beacon;
 pub mod bytecode;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod calldata;
+pub mod chains;
 pub mod compiler;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod etherscan;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fixture;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fork;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod geth;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod humanize;
+pub mod labels;
+pub mod metadata;
+pub mod multicall;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod provider;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod response_cache;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod rpc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sigdb;
 pub mod signatures;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sourcify;
 pub mod tokenize;
 pub mod types;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ws;
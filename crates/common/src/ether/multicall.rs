@@ -0,0 +1,118 @@
+//! Aggregation of many read-only calls into a single `eth_call` to the [Multicall3] contract.
+//!
+//! [Multicall3] can only aggregate calls, since a contract can't read another contract's storage.
+//! Batches of storage reads are instead sent as JSON-RPC batches, see
+//! [`crate::ether::provider::HeimdallProvider::get_storage_batch`].
+//!
+//! [Multicall3]: https://www.multicall3.com
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use alloy::{
+    primitives::{address, Address, Bytes},
+    sol,
+    sol_types::SolCall,
+};
+use eyre::{eyre, Result};
+
+use crate::utils::strings::encode_hex;
+
+/// The address [Multicall3](https://www.multicall3.com) is deployed at, on every chain it is
+/// deployed on.
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+sol! {
+    struct Call3 {
+        address target;
+        bool allowFailure;
+        bytes callData;
+    }
+
+    struct Result3 {
+        bool success;
+        bytes returnData;
+    }
+
+    function aggregate3(Call3[] calldata calls) external payable returns (Result3[] memory returnData);
+}
+
+/// Whether batches of calls are aggregated with Multicall3 by default.
+static DEFAULT_MULTICALL: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable aggregating batches of calls with Multicall3 for all providers created
+/// after this call.
+pub fn set_default_multicall(enabled: bool) {
+    DEFAULT_MULTICALL.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether batches of calls are aggregated with Multicall3 by default.
+pub fn default_multicall() -> bool {
+    DEFAULT_MULTICALL.load(Ordering::Relaxed)
+}
+
+/// Encode the calldata of an `aggregate3` call which makes each of the given `(target, calldata)`
+/// calls, allowing any of them to fail.
+pub fn encode_aggregate3(calls: &[(Address, Vec<u8>)]) -> Vec<u8> {
+    aggregate3Call {
+        calls: calls
+            .iter()
+            .map(|(target, calldata)| Call3 {
+                target: *target,
+                allowFailure: true,
+                callData: Bytes::copy_from_slice(calldata),
+            })
+            .collect(),
+    }
+    .abi_encode()
+}
+
+/// Decode the return data of an `aggregate3` call into the result of each call, in the order
+/// they were made.
+///
+/// ```
+/// use heimdall_common::ether::multicall::decode_aggregate3;
+///
+/// assert!(decode_aggregate3(&[]).is_err());
+/// ```
+pub fn decode_aggregate3(returndata: &[u8]) -> Result<Vec<Result<Vec<u8>>>> {
+    let results = aggregate3Call::abi_decode_returns(returndata)
+        .map_err(|e| eyre!("failed to decode multicall return data: {}", e))?;
+
+    Ok(results
+        .into_iter()
+        .map(|result| match result.success {
+            true => Ok(result.returnData.to_vec()),
+            false => Err(eyre!("call reverted: {}", encode_hex(&result.returnData))),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::sol_types::SolValue;
+
+    #[test]
+    fn test_aggregate3_round_trip() {
+        let calls = vec![
+            (Address::repeat_byte(1), vec![0x31, 0x3c, 0xe5, 0x67]),
+            (Address::repeat_byte(2), vec![0x95, 0xd8, 0x9b, 0x41]),
+        ];
+        let calldata = encode_aggregate3(&calls);
+        assert_eq!(calldata[..4], aggregate3Call::SELECTOR);
+
+        let decoded = aggregate3Call::abi_decode(&calldata).unwrap();
+        assert_eq!(decoded.calls.len(), 2);
+        assert_eq!(decoded.calls[1].target, Address::repeat_byte(2));
+        assert!(decoded.calls.iter().all(|call| call.allowFailure));
+
+        let returndata = vec![
+            Result3 { success: true, returnData: Bytes::from(vec![0x12]) },
+            Result3 { success: false, returnData: Bytes::new() },
+        ]
+        .abi_encode();
+        let results = decode_aggregate3(&returndata).unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &vec![0x12]);
+        assert!(results[1].is_err());
+    }
+}
@@ -1,21 +1,618 @@
 //! Create a custom data transport to use with a Provider.
+use std::{
+    fmt::Debug,
+    future::Future,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
 use alloy::{
     eips::BlockId,
     network::Ethereum,
-    primitives::{Address, TxHash},
-    providers::{ext::TraceApi, Provider, ProviderBuilder, RootProvider},
-    rpc::types::{
-        trace::parity::{TraceResults, TraceResultsWithTransactionHash, TraceType},
-        Filter, Log, Transaction,
+    primitives::{Address, Bytes, TxHash, B256, U256},
+    providers::{
+        ext::{DebugApi, TraceApi},
+        Provider, ProviderBuilder, RootProvider, WsConnect,
+    },
+    rpc::{
+        client::BatchRequest,
+        json_rpc::{RpcRecv, RpcSend},
+        types::{
+            trace::{
+                geth::{
+                    GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TraceResult,
+                },
+                parity::{TraceResults, TraceResultsWithTransactionHash, TraceType},
+            },
+            Filter, Log, Transaction, TransactionRequest,
+        },
+    },
+    transports::{Authorization, RpcError, TransportError, TransportErrorKind},
+};
+use async_trait::async_trait;
+use eyre::{bail, eyre, OptionExt, Result};
+use futures::{stream::BoxStream, StreamExt};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    ether::{
+        multicall::{decode_aggregate3, default_multicall, encode_aggregate3, MULTICALL3_ADDRESS},
+        response_cache::{default_response_cache, Freshness, ResponseCache, Ttl},
+        ws::ReconnectingWsConnect,
+    },
+    utils::{
+        metrics::{record_cache_lookup, record_rpc_request},
+        offline::{ensure_online, is_offline},
+        ratelimit::RateLimiter,
     },
 };
-use eyre::Result;
+use tokio::sync::OnceCell;
+use tokio_retry::{strategy::jitter, RetryIf};
+use tracing::{debug, warn};
+
+/// The default amount of time to wait for a single endpoint to respond before failing over to
+/// the next one.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default maximum number of calls sent in a single JSON-RPC batch.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// The retry policy used by newly connected providers, see [`RetryPolicy::set_default`].
+static DEFAULT_RETRY_POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::new());
+
+/// The policy used to reconnect dropped websocket connections, see
+/// [`RetryPolicy::set_default_reconnect`].
+static DEFAULT_RECONNECT_POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::reconnect());
+
+/// The rate limiter shared by newly connected providers, see [`set_default_rate_limit`].
+static DEFAULT_RATE_LIMITER: RwLock<Option<Arc<RateLimiter>>> = RwLock::new(None);
+
+/// Limit all newly connected providers to a combined `requests_per_second`, or remove the limit
+/// if `None` is given. Providers share a single token bucket, so the limit holds across
+/// concurrent tasks.
+pub fn set_default_rate_limit(requests_per_second: Option<u32>) {
+    *DEFAULT_RATE_LIMITER.write().unwrap_or_else(|e| e.into_inner()) =
+        requests_per_second.map(|rps| Arc::new(RateLimiter::new(rps)));
+}
+
+/// Get the rate limiter shared by newly connected providers, if any.
+pub fn default_rate_limiter() -> Option<Arc<RateLimiter>> {
+    DEFAULT_RATE_LIMITER.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// The extra HTTP headers sent by newly connected providers, see [`set_default_headers`].
+static DEFAULT_HEADERS: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+/// Send the given HTTP headers with every request of all newly connected providers, e.g. an
+/// `Authorization` or API key header required by a private rpc gateway.
+///
+/// All headers are sent to HTTP endpoints. WebSocket endpoints only receive the `Authorization`
+/// header, during the handshake.
+pub fn set_default_headers(headers: Vec<(String, String)>) {
+    *DEFAULT_HEADERS.write().unwrap_or_else(|e| e.into_inner()) = headers;
+}
+
+/// Get the extra HTTP headers sent by newly connected providers.
+pub fn default_headers() -> Vec<(String, String)> {
+    DEFAULT_HEADERS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Parse an HTTP header given as `KEY:VALUE`.
+///
+/// ```
+/// use heimdall_common::ether::provider::parse_rpc_header;
+///
+/// let (key, value) = parse_rpc_header("Authorization: Bearer token").unwrap();
+/// assert_eq!((key.as_str(), value.as_str()), ("Authorization", "Bearer token"));
+///
+/// assert!(parse_rpc_header("no-separator").is_err());
+/// assert!(parse_rpc_header("bad key: value").is_err());
+/// ```
+pub fn parse_rpc_header(header: &str) -> Result<(String, String)> {
+    let Some((key, value)) = header.split_once(':') else {
+        bail!("invalid header '{}', expected KEY:VALUE", header);
+    };
+    let (key, value) = (key.trim(), value.trim());
+
+    HeaderName::from_bytes(key.as_bytes())
+        .map_err(|e| eyre!("invalid header name '{}': {}", key, e))?;
+    HeaderValue::from_str(value).map_err(|e| eyre!("invalid value for header '{}': {}", key, e))?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Connect to a single endpoint, sending the given extra HTTP headers. Websocket endpoints
+/// reconnect according to [`RetryPolicy::default_reconnect`] when their connection drops.
+async fn connect_endpoint(
+    url: &str,
+    headers: &[(String, String)],
+) -> Result<RootProvider<Ethereum>> {
+    if url.starts_with("ws") {
+        let auth = headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(AUTHORIZATION.as_str()))
+            .map(|(_, value)| Authorization::raw(value));
+        if headers.len() > usize::from(auth.is_some()) {
+            warn!("only the 'Authorization' header is sent to websocket endpoint '{}'", url);
+        }
+        let connect = ReconnectingWsConnect::new(
+            WsConnect::new(url).with_auth_opt(auth),
+            RetryPolicy::default_reconnect(),
+        );
+        return Ok(ProviderBuilder::new().connect_pubsub_with(connect).await?.root().clone());
+    }
+
+    if headers.is_empty() {
+        return Ok(ProviderBuilder::new().connect(url).await?.root().clone());
+    }
+
+    if url.starts_with("http") {
+        let mut header_map = HeaderMap::with_capacity(headers.len());
+        for (key, value) in headers {
+            header_map
+                .insert(HeaderName::from_bytes(key.as_bytes())?, HeaderValue::from_str(value)?);
+        }
+        let client = reqwest::Client::builder().default_headers(header_map).build()?;
+        return Ok(ProviderBuilder::new().connect_reqwest(client, url.parse()?).root().clone());
+    }
+
+    warn!("custom headers are not sent to ipc endpoint '{}'", url);
+    Ok(ProviderBuilder::new().connect(url).await?.root().clone())
+}
+
+/// [`RetryPolicy`] controls how a [`MultiTransportProvider`] retries requests which failed on
+/// every endpoint with a transient error, e.g. a 429 or 503 response.
+///
+/// The delay between retries starts at `initial_backoff` and doubles after every retry, up to
+/// `max_backoff`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a failed request is retried.
+    pub max_retries: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The upper bound for the delay between retries.
+    pub max_backoff: Duration,
+    /// Whether to randomize each delay, to avoid many clients retrying in lockstep.
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    /// Create the default retry policy: 2 retries, starting at 50ms, with jitter.
+    pub const fn new() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(50),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+
+    /// Create the default policy for reconnecting dropped websocket connections: 10 attempts,
+    /// starting at 500ms, up to 30s, with jitter.
+    pub const fn reconnect() -> Self {
+        Self {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+
+    /// Set the maximum number of times a failed request is retried.
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn with_initial_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Set the upper bound for the delay between retries.
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set whether to randomize each delay.
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Get the retry policy used by newly connected providers.
+    pub fn default_policy() -> Self {
+        *DEFAULT_RETRY_POLICY.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the retry policy used by newly connected providers.
+    pub fn set_default(policy: Self) {
+        *DEFAULT_RETRY_POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    /// Get the policy used to reconnect dropped websocket connections of newly connected
+    /// providers.
+    pub fn default_reconnect() -> Self {
+        *DEFAULT_RECONNECT_POLICY.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Set the policy used to reconnect dropped websocket connections of newly connected
+    /// providers.
+    pub fn set_default_reconnect(policy: Self) {
+        *DEFAULT_RECONNECT_POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+    }
+
+    /// The delay to wait before the given retry, starting at 0.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay =
+            self.initial_backoff.saturating_mul(2u32.saturating_pow(retry)).min(self.max_backoff);
+        if self.jitter {
+            jitter(delay)
+        } else {
+            delay
+        }
+    }
+
+    /// The delays to wait before each retry.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> {
+        let policy = *self;
+        (0..policy.max_retries).map(move |retry| policy.delay(retry))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The tracing namespaces which a node may expose.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TraceNamespace {
+    /// Parity-style `trace_*` methods, e.g. `trace_replayTransaction`.
+    Parity,
+    /// Geth-style `debug_trace*` methods, e.g. `debug_traceTransaction`.
+    Geth,
+}
+
+impl TraceNamespace {
+    /// The method used to probe whether a node supports this namespace.
+    fn probe_method(&self) -> &'static str {
+        match self {
+            TraceNamespace::Parity => "trace_replayTransaction",
+            TraceNamespace::Geth => "debug_traceTransaction",
+        }
+    }
+}
+
+/// The capabilities of a node, as probed by [`HeimdallProvider::capabilities`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Whether the node supports Parity-style `trace_*` methods.
+    pub parity_traces: bool,
+    /// Whether the node supports Geth-style `debug_trace*` methods.
+    pub geth_traces: bool,
+    /// Whether the node serves historical state, i.e. is an archive node.
+    pub archive: bool,
+}
+
+impl Capabilities {
+    /// The best tracing namespace the node supports, preferring Parity-style traces over
+    /// Geth-style traces, or `None` if it supports neither.
+    ///
+    /// ```
+    /// use heimdall_common::ether::provider::{Capabilities, TraceNamespace};
+    ///
+    /// let capabilities = Capabilities { parity_traces: true, geth_traces: true, archive: false };
+    /// assert_eq!(capabilities.trace_namespace(), Some(TraceNamespace::Parity));
+    /// assert_eq!(Capabilities::default().trace_namespace(), None);
+    /// ```
+    pub fn trace_namespace(&self) -> Option<TraceNamespace> {
+        if self.parity_traces {
+            Some(TraceNamespace::Parity)
+        } else if self.geth_traces {
+            Some(TraceNamespace::Geth)
+        } else {
+            None
+        }
+    }
+}
+
+/// Probe each of the given provider's [`Capabilities`].
+async fn probe_capabilities<P: HeimdallProvider + ?Sized>(provider: &P) -> Result<Capabilities> {
+    let capabilities = Capabilities {
+        parity_traces: provider.supports_trace_namespace(TraceNamespace::Parity).await?,
+        geth_traces: provider.supports_trace_namespace(TraceNamespace::Geth).await?,
+        archive: provider.supports_archive_state().await?,
+    };
+    debug!("probed rpc capabilities: {:?}", capabilities);
+    Ok(capabilities)
+}
+
+/// Whether the given error indicates that the node doesn't support the requested method.
+fn is_unsupported_method(error: &TransportError) -> bool {
+    match error {
+        RpcError::ErrorResp(payload) => {
+            let message = payload.message.to_lowercase();
+            // -32601: method not found, -32004: method not supported (EIP-1474)
+            payload.code == -32601 ||
+                payload.code == -32004 ||
+                message.contains("does not exist") ||
+                message.contains("not available") ||
+                message.contains("not supported") ||
+                message.contains("unsupported") ||
+                message.contains("method not found")
+        }
+        _ => false,
+    }
+}
+
+/// Whether the given error may not recur if the request is retried, or sent to another endpoint:
+/// a transport failure, a timeout, an HTTP 429 or 5xx, or a rate limit. Errors the node returns
+/// for the request itself, e.g. a reverted call or invalid params, would only recur.
+fn is_transient(error: &eyre::Report) -> bool {
+    match error.downcast_ref::<TransportError>() {
+        Some(RpcError::ErrorResp(payload)) => payload.is_retry_err(),
+        Some(RpcError::Transport(TransportErrorKind::HttpError(e))) => {
+            e.status == 429 || e.status >= 500
+        }
+        Some(RpcError::Transport(_)) => true,
+        Some(_) => false,
+        None => error.downcast_ref::<tokio::time::error::Elapsed>().is_some(),
+    }
+}
+
+/// [`HeimdallProvider`] is the interface through which heimdall reads chain data.
+///
+/// [`MultiTransportProvider`] is the default implementation. Other implementations, e.g. mock or
+/// recording providers, may be used for all rpc requests by installing a [`ProviderFactory`] with
+/// [`set_provider_factory`].
+///
+/// Heimdall doesn't read a node's database directly, since reth's database crates are only
+/// published as git dependencies. A crate depending on them can implement this trait over reth's
+/// `ProviderFactory`, reading bytecode, storage and receipts from its MDBX database, and install
+/// it with [`set_provider_factory`].
+#[async_trait]
+pub trait HeimdallProvider: Debug + Send + Sync {
+    /// Get the chain id.
+    async fn get_chainid(&self) -> Result<u64>;
+
+    /// Get the latest block number.
+    async fn get_block_number(&self) -> Result<u64>;
+
+    /// Get the timestamp of the block with the given number, in seconds since the unix epoch.
+    async fn get_block_timestamp(&self, block_number: u64) -> Result<u64>;
+
+    /// Get the bytecode at the given address. If `block` is given, the bytecode is read at that
+    /// block instead of the latest block.
+    async fn get_code_at(&self, address: Address, block: Option<BlockId>) -> Result<Vec<u8>>;
+
+    /// Get the value of the storage slot at the given address. If `block` is given, the value is
+    /// read at that block instead of the latest block.
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        block: Option<BlockId>,
+    ) -> Result<U256>;
+
+    /// Execute a read-only call of `calldata` against the given address with `eth_call`,
+    /// returning the call's return data. If `block` is given, the call is executed at that block
+    /// instead of the latest block.
+    async fn call(
+        &self,
+        address: Address,
+        calldata: &[u8],
+        block: Option<BlockId>,
+    ) -> Result<Vec<u8>>;
+
+    /// Execute each of the given `(address, calldata)` read-only calls, returning the result of
+    /// each call in the same order as `calls`. A call which reverts fails on its own, without
+    /// failing the others.
+    ///
+    /// By default the calls are made one at a time. Implementations which support aggregating
+    /// calls should override this.
+    async fn call_batch(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        block: Option<BlockId>,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        let mut results = Vec::with_capacity(calls.len());
+        for (address, calldata) in calls {
+            results.push(self.call(*address, calldata, block).await);
+        }
+        Ok(results)
+    }
+
+    /// Get the transaction by hash.
+    async fn get_transaction_by_hash(&self, tx_hash: TxHash) -> Result<Option<Transaction>>;
+
+    /// Get the transactions with the given hashes, in the same order as `tx_hashes`.
+    ///
+    /// By default the transactions are fetched one at a time. Implementations which support
+    /// batching should override this.
+    async fn get_transactions_batch(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<Option<Transaction>>> {
+        let mut transactions = Vec::with_capacity(tx_hashes.len());
+        for tx_hash in tx_hashes {
+            transactions.push(self.get_transaction_by_hash(*tx_hash).await?);
+        }
+        Ok(transactions)
+    }
+
+    /// Get the value of each of the given `(address, slot)` storage slots, in the same order as
+    /// `slots`.
+    ///
+    /// By default the slots are fetched one at a time. Implementations which support batching
+    /// should override this.
+    async fn get_storage_batch(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>> {
+        let mut values = Vec::with_capacity(slots.len());
+        for (address, slot) in slots {
+            values.push(self.get_storage_at(*address, *slot, None).await?);
+        }
+        Ok(values)
+    }
+
+    /// Replays the transaction at the given hash.
+    /// The `trace_type` parameter is a list of the types of traces to return.
+    async fn trace_replay_transaction(
+        &self,
+        tx_hash: &str,
+        trace_type: &[TraceType],
+    ) -> Result<TraceResults>;
+
+    /// Replays the block at the given number.
+    /// The `trace_type` parameter is a list of the types of traces to return.
+    async fn trace_replay_block_transactions(
+        &self,
+        block_number: u64,
+        trace_type: &[TraceType],
+    ) -> Result<Vec<TraceResultsWithTransactionHash>>;
+
+    /// Reruns the transaction at the given hash using Geth's `debug_traceTransaction`.
+    /// The `options` parameter selects the tracer to use, e.g. `callTracer` or `prestateTracer`.
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: &str,
+        options: &GethDebugTracingOptions,
+    ) -> Result<GethTrace>;
+
+    /// Traces the given transaction request on top of the state at `block` (the latest block
+    /// by default) without sending it, using Parity's `trace_call`.
+    /// The `trace_type` parameter is a list of the types of traces to return.
+    async fn trace_call(
+        &self,
+        request: &TransactionRequest,
+        trace_type: &[TraceType],
+        block: Option<BlockId>,
+    ) -> Result<TraceResults>;
+
+    /// Traces the given transaction request on top of the state at `block` (the latest block
+    /// by default) without sending it, using Geth's `debug_traceCall`.
+    /// The `options` parameter selects the tracer to use, e.g. `callTracer` or `prestateTracer`.
+    async fn debug_trace_call(
+        &self,
+        request: &TransactionRequest,
+        options: &GethDebugTracingOptions,
+        block: Option<BlockId>,
+    ) -> Result<GethTrace>;
+
+    /// Reruns all transactions in the block at the given number using Geth's
+    /// `debug_traceBlockByNumber`.
+    /// The `options` parameter selects the tracer to use, e.g. `callTracer` or `prestateTracer`.
+    async fn debug_trace_block(
+        &self,
+        block_number: u64,
+        options: &GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>>;
+
+    /// Probe whether the node supports the given tracing namespace.
+    async fn supports_trace_namespace(&self, namespace: TraceNamespace) -> Result<bool>;
+
+    /// Whether the node serves historical state, i.e. is an archive node.
+    ///
+    /// By default this reads the code of the zero address at the genesis block, which fails on
+    /// nodes that have pruned it. Implementations which can tell a missing state error apart
+    /// from other errors should override this.
+    async fn supports_archive_state(&self) -> Result<bool> {
+        Ok(self.get_code_at(Address::ZERO, Some(BlockId::number(0))).await.is_ok())
+    }
+
+    /// Probe which tracing namespaces and historical state the node supports, so that callers
+    /// can pick the best available strategy up front rather than failing mid-run.
+    async fn capabilities(&self) -> Result<Capabilities> {
+        probe_capabilities(self).await
+    }
+
+    /// Get the logs that match the given filter. If `block` is given, it overrides the filter's
+    /// block range so that only logs emitted in that block are returned.
+    async fn get_logs(&self, filter: &Filter, block: Option<BlockId>) -> Result<Vec<Log>>;
+
+    /// Subscribe to the numbers of new blocks as they're added to the chain, or `None` if the
+    /// node doesn't support subscriptions.
+    ///
+    /// By default subscriptions aren't supported. Implementations connected over a transport
+    /// which supports them, e.g. a websocket, should override this.
+    async fn subscribe_block_numbers(&self) -> Result<Option<BoxStream<'static, u64>>> {
+        Ok(None)
+    }
+}
+
+/// [`ProviderFactory`] creates the [`HeimdallProvider`] used for an rpc url.
+#[async_trait]
+pub trait ProviderFactory: Send + Sync {
+    /// Connect to a provider using the given rpc_url.
+    async fn connect(&self, rpc_url: &str) -> Result<Arc<dyn HeimdallProvider>>;
+}
+
+/// The factory used by [`connect_provider`], if one was installed with [`set_provider_factory`].
+static PROVIDER_FACTORY: RwLock<Option<Arc<dyn ProviderFactory>>> = RwLock::new(None);
+
+/// Use the given [`ProviderFactory`] for all subsequent rpc requests, or restore the default
+/// [`MultiTransportProvider`] if `None` is given.
+pub fn set_provider_factory(factory: Option<Arc<dyn ProviderFactory>>) {
+    *PROVIDER_FACTORY.write().unwrap_or_else(|e| e.into_inner()) = factory;
+}
+
+/// Connect to a provider using the given rpc_url, through the installed [`ProviderFactory`] or,
+/// by default, a [`MultiTransportProvider`].
+pub async fn connect_provider(rpc_url: &str) -> Result<Arc<dyn HeimdallProvider>> {
+    let factory = PROVIDER_FACTORY.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match factory {
+        Some(factory) => factory.connect(rpc_url).await,
+        None => Ok(Arc::new(MultiTransportProvider::connect(rpc_url).await?)),
+    }
+}
+
+/// A single connected RPC endpoint.
+#[derive(Clone, Debug)]
+struct Endpoint {
+    url: String,
+    provider: RootProvider<Ethereum>,
+}
 
 /// [`MultiTransportProvider`] is a convenience wrapper around the different transport types
 /// supported by the [`Provider`].
+///
+/// The provider may be backed by several endpoints. Requests are sent to the endpoints in the
+/// order they were given, and transparently fail over to the next endpoint when a request
+/// errors or times out. If every endpoint fails, the request is retried according to the
+/// provider's [`RetryPolicy`]. Every request, including each attempt on a fallback endpoint,
+/// first takes a token from the provider's [`RateLimiter`], if one is set.
+///
+/// Responses are served from the provider's [`ResponseCache`] where possible, see
+/// [`crate::ether::response_cache`].
+///
+/// Batches of calls are aggregated into a single `eth_call` to Multicall3 where it is deployed,
+/// see [`crate::ether::multicall`].
 #[derive(Clone, Debug)]
 pub struct MultiTransportProvider {
-    provider: RootProvider<Ethereum>,
+    rpc_urls: Vec<String>,
+    endpoints: Vec<Endpoint>,
+    timeout: Duration,
+    retry_policy: RetryPolicy,
+    batch_size: usize,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cache: Option<Arc<ResponseCache>>,
+    multicall: bool,
+    capabilities: Arc<OnceCell<Capabilities>>,
+}
+
+/// Split a comma-separated list of RPC URLs into its individual, trimmed, non-empty URLs.
+///
+/// ```
+/// use heimdall_common::ether::provider::parse_rpc_urls;
+///
+/// let urls = parse_rpc_urls("https://a.example, https://b.example,,");
+/// assert_eq!(urls, vec!["https://a.example", "https://b.example"]);
+/// ```
+pub fn parse_rpc_urls(rpc_url: &str) -> Vec<&str> {
+    rpc_url.split(',').map(str::trim).filter(|url| !url.is_empty()).collect()
 }
 
 // We implement a convenience "constructor" method, to easily initialize the transport.
@@ -23,69 +620,1132 @@ pub struct MultiTransportProvider {
 // otherwise it'll default to [`Ipc`].
 impl MultiTransportProvider {
     /// Connect to a provider using the given rpc_url.
+    ///
+    /// The rpc_url may be a comma-separated list of URLs, in which case requests will fail over
+    /// to the next endpoint in the list when one errors or times out.
     pub async fn connect(rpc_url: &str) -> Result<Self> {
-        if rpc_url.is_empty() {
-            return Err(eyre::eyre!("No RPC URL provided"));
+        Self::connect_all(&parse_rpc_urls(rpc_url)).await
+    }
+
+    /// Connect to a provider backed by each of the given rpc_urls, in order of preference.
+    ///
+    /// Endpoints which cannot be connected to are skipped. An error is returned only if no
+    /// endpoint could be connected to.
+    ///
+    /// In offline mode, no endpoint is connected to, and only cached responses are served.
+    pub async fn connect_all(rpc_urls: &[&str]) -> Result<Self> {
+        if rpc_urls.is_empty() {
+            return Err(eyre!("No RPC URL provided"));
+        }
+        if is_offline() {
+            debug!("offline mode is enabled, serving cached rpc responses only");
+            return Ok(Self::new(rpc_urls, Vec::new()));
         }
 
-        let provider = ProviderBuilder::new().connect(rpc_url).await.unwrap().root().clone();
-        Ok(Self { provider })
+        let headers = default_headers();
+        let mut endpoints = Vec::with_capacity(rpc_urls.len());
+        let mut last_error = None;
+        for url in rpc_urls {
+            match connect_endpoint(url, &headers).await {
+                Ok(provider) => endpoints.push(Endpoint { url: url.to_string(), provider }),
+                Err(e) => {
+                    warn!("failed to connect to rpc endpoint '{}': {}", url, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if endpoints.is_empty() {
+            return Err(eyre!(
+                "failed to connect to any rpc endpoint: {}",
+                last_error.map(|e| e.to_string()).unwrap_or_default()
+            ));
+        }
+
+        Ok(Self::new(rpc_urls, endpoints))
     }
 
-    /// Get the chain id.
-    pub async fn get_chainid(&self) -> Result<u64> {
-        Ok(self.provider.get_chain_id().await?)
+    fn new(rpc_urls: &[&str], endpoints: Vec<Endpoint>) -> Self {
+        Self {
+            rpc_urls: rpc_urls.iter().map(|url| url.to_string()).collect(),
+            endpoints,
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::default_policy(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            rate_limiter: default_rate_limiter(),
+            cache: default_response_cache(),
+            multicall: default_multicall(),
+            capabilities: Arc::new(OnceCell::new()),
+        }
     }
 
-    /// Get the latest block number.
-    pub async fn get_block_number(&self) -> Result<u64> {
-        Ok(self.provider.get_block_number().await?)
+    /// Set the amount of time to wait for a single endpoint to respond before failing over to
+    /// the next one.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
-    /// Get the bytecode at the given address.
-    pub async fn get_code_at(&self, address: Address) -> Result<Vec<u8>> {
-        Ok(self.provider.get_code_at(address).await?.to_vec())
+    /// Set the policy used to retry requests which failed on every endpoint.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
 
-    /// Get the bytecode at the given address at a specific block.
-    pub async fn get_code_at_block(&self, address: Address, block: BlockId) -> Result<Vec<u8>> {
-        Ok(self.provider.get_code_at(address).block_id(block).await?.to_vec())
+    /// Set the maximum number of calls sent in a single JSON-RPC batch. Larger requests are
+    /// split into several batches.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
     }
 
-    /// Get the transaction by hash.
-    pub async fn get_transaction_by_hash(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
-        Ok(self.provider.get_transaction_by_hash(tx_hash).await?)
+    /// Set the rate limiter used to throttle requests, or remove it if `None` is given. The
+    /// same [`RateLimiter`] may be shared between several providers.
+    pub fn with_rate_limiter(mut self, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
     }
 
-    /// Replays the transaction at the given hash.
-    /// The `trace_type` parameter is a list of the types of traces to return.
-    pub async fn trace_replay_transaction(
+    /// Set the cache used to serve responses, or disable response caching if `None` is given.
+    /// The same [`ResponseCache`] may be shared between several providers.
+    pub fn with_response_cache(mut self, cache: Option<Arc<ResponseCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Enable or disable aggregating batches of calls with Multicall3. When disabled, or where
+    /// Multicall3 isn't deployed, the calls are made one at a time.
+    pub fn with_multicall(mut self, multicall: bool) -> Self {
+        self.multicall = multicall;
+        self
+    }
+
+    /// Get the URLs of the connected endpoints, in order of preference.
+    pub fn urls(&self) -> Vec<&str> {
+        self.endpoints.iter().map(|endpoint| endpoint.url.as_str()).collect()
+    }
+
+    /// Run the given request against each endpoint in order, returning the first successful
+    /// response. If every endpoint fails with a transient error, the request is retried according
+    /// to the [`RetryPolicy`], after which the last error is returned. Other errors are returned
+    /// straight away.
+    async fn with_fallback<T, F, Fut>(&self, method: &str, request: F) -> Result<T>
+    where
+        F: Fn(RootProvider<Ethereum>) -> Fut,
+        Fut: Future<Output = Result<T>>, {
+        RetryIf::spawn(
+            self.retry_policy.delays(),
+            || self.try_endpoints(method, &request),
+            is_transient,
+        )
+        .await
+    }
+
+    /// Run the given request against each endpoint in order, returning the first successful
+    /// response, the first error which isn't transient, or the last error.
+    async fn try_endpoints<T, F, Fut>(&self, method: &str, request: &F) -> Result<T>
+    where
+        F: Fn(RootProvider<Ethereum>) -> Fut,
+        Fut: Future<Output = Result<T>>, {
+        ensure_online(&format!("send '{method}' to rpc endpoint"))?;
+
+        let mut last_error = None;
+        for endpoint in &self.endpoints {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            let start_time = Instant::now();
+            let response =
+                tokio::time::timeout(self.timeout, request(endpoint.provider.clone())).await;
+            record_rpc_request(method, start_time.elapsed(), matches!(response, Ok(Ok(_))));
+
+            match response {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(e)) if !is_transient(&e) => return Err(e),
+                Ok(Err(e)) => {
+                    debug!("'{}' failed on rpc endpoint '{}': {}", method, endpoint.url, e);
+                    last_error = Some(e);
+                }
+                Err(elapsed) => {
+                    debug!(
+                        "'{}' timed out after {:?} on rpc endpoint '{}'",
+                        method, self.timeout, endpoint.url
+                    );
+                    last_error = Some(
+                        eyre::Report::new(elapsed)
+                            .wrap_err(format!("'{}' timed out after {:?}", method, self.timeout)),
+                    );
+                }
+            }
+
+            if self.endpoints.len() > 1 {
+                warn!("rpc endpoint '{}' failed, falling back to the next endpoint", endpoint.url);
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| eyre!("No RPC URL provided")))
+    }
+
+    /// Send one `method` call per entry in `params` as JSON-RPC batches of at most
+    /// `batch_size` calls, returning the responses in the same order as `params`.
+    ///
+    /// Each batch fails over and is retried as a whole, like any other request.
+    async fn batch<Params, Resp>(
+        &self,
+        method: &'static str,
+        params: &[Params],
+    ) -> Result<Vec<Resp>>
+    where
+        Params: RpcSend,
+        Resp: RpcRecv, {
+        let mut responses = Vec::with_capacity(params.len());
+        for chunk in params.chunks(self.batch_size) {
+            let chunk_responses = self
+                .with_fallback(method, |provider| async move {
+                    let mut batch = BatchRequest::new(provider.client());
+                    let waiters = chunk
+                        .iter()
+                        .map(|params| batch.add_call::<_, Resp>(method, params))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    batch.send().await?;
+
+                    let mut responses = Vec::with_capacity(waiters.len());
+                    for waiter in waiters {
+                        responses.push(waiter.await?);
+                    }
+                    Ok(responses)
+                })
+                .await?;
+            responses.extend(chunk_responses);
+        }
+
+        debug!(
+            "fetched {} '{}' responses in batches of {}",
+            responses.len(),
+            method,
+            self.batch_size
+        );
+        Ok(responses)
+    }
+
+    /// Make the given calls through Multicall3's `aggregate3`, in chunks of at most `batch_size`
+    /// calls, returning `None` if Multicall3 isn't deployed at `block`.
+    async fn multicall(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        block: Option<BlockId>,
+    ) -> Result<Option<Vec<Result<Vec<u8>>>>> {
+        if self.get_code_at(MULTICALL3_ADDRESS, block).await?.is_empty() {
+            debug!("multicall3 is not deployed, making calls one at a time");
+            return Ok(None);
+        }
+
+        let mut results = Vec::with_capacity(calls.len());
+        for chunk in calls.chunks(self.batch_size) {
+            let returndata =
+                self.call(MULTICALL3_ADDRESS, &encode_aggregate3(chunk), block).await?;
+            results.extend(decode_aggregate3(&returndata)?);
+        }
+
+        debug!("aggregated {} calls with multicall3 in chunks of {}", calls.len(), self.batch_size);
+        Ok(Some(results))
+    }
+
+    /// The [`Ttl`] of a `method` response with the given [`Freshness`].
+    async fn ttl(&self, method: &str, freshness: Freshness) -> Ttl {
+        let latest_block = match freshness.block_number() {
+            Some(_) => self.get_block_number().await.ok(),
+            None => None,
+        };
+        freshness.ttl(method, latest_block)
+    }
+
+    /// Serve the `method` response for `params` from the cache if possible. Otherwise, `fetch`
+    /// the response and cache it according to its [`Freshness`], if `freshness` returns one.
+    async fn cached<P, T, F, Fut>(
+        &self,
+        method: &'static str,
+        params: &P,
+        freshness: F,
+        fetch: Fut,
+    ) -> Result<T>
+    where
+        P: Serialize + Sync,
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(&T) -> Option<Freshness>,
+        Fut: Future<Output = Result<T>>, {
+        if self.cache.is_none() {
+            return fetch.await;
+        }
+        let Ok(chain_id) = self.get_chainid().await else {
+            return fetch.await;
+        };
+
+        self.cached_with_key(
+            method,
+            ResponseCache::key(method, Some(chain_id), params),
+            freshness,
+            fetch,
+        )
+        .await
+    }
+
+    /// Like [`Self::cached`], using the given cache `key`.
+    async fn cached_with_key<T, F, Fut>(
+        &self,
+        method: &'static str,
+        key: String,
+        freshness: F,
+        fetch: Fut,
+    ) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(&T) -> Option<Freshness>,
+        Fut: Future<Output = Result<T>>, {
+        let Some(cache) = &self.cache else {
+            return fetch.await;
+        };
+        if let Some(value) = cache.get(&key) {
+            record_cache_lookup(method, true);
+            return Ok(value);
+        }
+        record_cache_lookup(method, false);
+
+        let value = fetch.await?;
+        if let Some(freshness) = freshness(&value) {
+            cache.insert(&key, &value, self.ttl(method, freshness).await);
+        }
+        Ok(value)
+    }
+
+    /// Like [`Self::batch`], serving each response from the cache if possible. Only the calls
+    /// whose responses aren't cached are sent.
+    async fn cached_batch<Params, Resp, F>(
+        &self,
+        method: &'static str,
+        rpc_method: &'static str,
+        params: &[Params],
+        freshness: F,
+    ) -> Result<Vec<Resp>>
+    where
+        Params: RpcSend + Serialize,
+        Resp: RpcRecv + Serialize + DeserializeOwned,
+        F: Fn(&Resp) -> Option<Freshness>, {
+        let Some(cache) = self.cache.as_ref().filter(|_| !params.is_empty()) else {
+            return self.batch(rpc_method, params).await;
+        };
+        let Ok(chain_id) = self.get_chainid().await else {
+            return self.batch(rpc_method, params).await;
+        };
+
+        let keys = params
+            .iter()
+            .map(|params| ResponseCache::key(method, Some(chain_id), params))
+            .collect::<Vec<_>>();
+        let mut responses = keys.iter().map(|key| cache.get::<Resp>(key)).collect::<Vec<_>>();
+        for response in &responses {
+            record_cache_lookup(method, response.is_some());
+        }
+        let missing = (0..responses.len()).filter(|&i| responses[i].is_none()).collect::<Vec<_>>();
+        let missing_params = missing.iter().map(|&i| params[i].clone()).collect::<Vec<_>>();
+
+        for (i, response) in missing.into_iter().zip(self.batch(rpc_method, &missing_params).await?)
+        {
+            if let Some(freshness) = freshness(&response) {
+                cache.insert(&keys[i], &response, self.ttl(method, freshness).await);
+            }
+            responses[i] = Some(response);
+        }
+
+        responses.into_iter().collect::<Option<Vec<_>>>().ok_or_eyre("missing batch response")
+    }
+}
+
+/// The freshness of a transaction, which may only be cached once it has been mined.
+fn transaction_freshness(transaction: &Option<Transaction>) -> Option<Freshness> {
+    let block_number = transaction.as_ref()?.block_number?;
+    Some(Freshness::AtBlock(Some(BlockId::number(block_number))))
+}
+
+#[async_trait]
+impl HeimdallProvider for MultiTransportProvider {
+    async fn get_chainid(&self) -> Result<u64> {
+        // the chain id is needed to key every other response, so it's keyed by the rpc urls
+        self.cached_with_key(
+            "chain_id",
+            ResponseCache::key("chain_id", None, &self.rpc_urls),
+            |_| Some(Freshness::Immutable),
+            self.with_fallback("eth_chainId", |provider| async move {
+                Ok(provider.get_chain_id().await?)
+            }),
+        )
+        .await
+    }
+
+    async fn get_block_number(&self) -> Result<u64> {
+        self.cached(
+            "block_number",
+            &(),
+            |_| Some(Freshness::AtBlock(None)),
+            self.with_fallback("eth_blockNumber", |provider| async move {
+                Ok(provider.get_block_number().await?)
+            }),
+        )
+        .await
+    }
+
+    async fn get_block_timestamp(&self, block_number: u64) -> Result<u64> {
+        self.cached(
+            "block_timestamp",
+            &(block_number,),
+            |_| Some(Freshness::AtBlock(Some(BlockId::number(block_number)))),
+            self.with_fallback("eth_getBlockByNumber", |provider| async move {
+                let block = provider
+                    .get_block_by_number(block_number.into())
+                    .await?
+                    .ok_or_eyre(format!("block {block_number} not found"))?;
+                Ok(block.header.timestamp)
+            }),
+        )
+        .await
+    }
+
+    async fn get_code_at(&self, address: Address, block: Option<BlockId>) -> Result<Vec<u8>> {
+        let freshness = Freshness::AtBlock(block);
+        let block = block.unwrap_or_else(BlockId::latest);
+        let code: Bytes = self
+            .cached(
+                "get_code",
+                &(address, block),
+                |_| Some(freshness),
+                self.with_fallback("eth_getCode", |provider| async move {
+                    Ok(provider.get_code_at(address).block_id(block).await?)
+                }),
+            )
+            .await?;
+        Ok(code.to_vec())
+    }
+
+    async fn get_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        let freshness = Freshness::AtBlock(block);
+        let block = block.unwrap_or_else(BlockId::latest);
+        self.cached(
+            "get_storage",
+            &(address, slot, block),
+            |_| Some(freshness),
+            self.with_fallback("eth_getStorageAt", |provider| async move {
+                Ok(provider.get_storage_at(address, slot).block_id(block).await?)
+            }),
+        )
+        .await
+    }
+
+    async fn call(
+        &self,
+        address: Address,
+        calldata: &[u8],
+        block: Option<BlockId>,
+    ) -> Result<Vec<u8>> {
+        let freshness = Freshness::AtBlock(block);
+        let block = block.unwrap_or_else(BlockId::latest);
+        let calldata = Bytes::copy_from_slice(calldata);
+        let request = &TransactionRequest::default().to(address).input(calldata.clone().into());
+        let output: Bytes = self
+            .cached(
+                "call",
+                &(address, calldata, block),
+                |_| Some(freshness),
+                self.with_fallback("eth_call", |provider| async move {
+                    Ok(provider.call(request.clone()).block(block).await?)
+                }),
+            )
+            .await?;
+        Ok(output.to_vec())
+    }
+
+    async fn call_batch(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        block: Option<BlockId>,
+    ) -> Result<Vec<Result<Vec<u8>>>> {
+        if self.multicall && calls.len() > 1 {
+            match self.multicall(calls, block).await {
+                Ok(Some(results)) => return Ok(results),
+                Ok(None) => {}
+                Err(e) => warn!("failed to aggregate calls with multicall3: {}", e),
+            }
+        }
+
+        let mut results = Vec::with_capacity(calls.len());
+        for (address, calldata) in calls {
+            results.push(self.call(*address, calldata, block).await);
+        }
+        Ok(results)
+    }
+
+    async fn get_transaction_by_hash(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
+        self.cached(
+            "get_transaction",
+            &(tx_hash,),
+            transaction_freshness,
+            self.with_fallback("eth_getTransactionByHash", |provider| async move {
+                Ok(provider.get_transaction_by_hash(tx_hash).await?)
+            }),
+        )
+        .await
+    }
+
+    async fn get_transactions_batch(
+        &self,
+        tx_hashes: &[TxHash],
+    ) -> Result<Vec<Option<Transaction>>> {
+        let params = tx_hashes.iter().map(|tx_hash| (*tx_hash,)).collect::<Vec<_>>();
+        self.cached_batch(
+            "get_transaction",
+            "eth_getTransactionByHash",
+            &params,
+            transaction_freshness,
+        )
+        .await
+    }
+
+    async fn get_storage_batch(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>> {
+        let params = slots
+            .iter()
+            .map(|(address, slot)| (*address, *slot, BlockId::latest()))
+            .collect::<Vec<_>>();
+        self.cached_batch("get_storage", "eth_getStorageAt", &params, |_| {
+            Some(Freshness::AtBlock(None))
+        })
+        .await
+    }
+
+    async fn trace_replay_transaction(
         &self,
         tx_hash: &str,
         trace_type: &[TraceType],
     ) -> Result<TraceResults> {
         let tx_hash: TxHash = tx_hash.parse::<TxHash>()?;
-        let trace_builder = self.provider.trace_replay_transaction(tx_hash);
-        let trace_results = trace_builder.trace_types(trace_type.to_vec()).trace().await?;
-        Ok(trace_results)
+        self.cached(
+            "trace_replay_transaction",
+            &(tx_hash, trace_type),
+            |_| Some(Freshness::Immutable),
+            self.with_fallback("trace_replayTransaction", |provider| async move {
+                let trace_builder = provider.trace_replay_transaction(tx_hash);
+                Ok(trace_builder.trace_types(trace_type.to_vec()).trace().await?)
+            }),
+        )
+        .await
     }
 
-    /// Replays the block at the given number.
-    /// The `trace_type` parameter is a list of the types of traces to return.
-    pub async fn trace_replay_block_transactions(
+    async fn trace_replay_block_transactions(
         &self,
         block_number: u64,
         trace_type: &[TraceType],
     ) -> Result<Vec<TraceResultsWithTransactionHash>> {
-        let block_number = block_number.into();
+        self.cached(
+            "trace_replay_block_transactions",
+            &(block_number, trace_type),
+            |_| Some(Freshness::AtBlock(Some(BlockId::number(block_number)))),
+            self.with_fallback("trace_replayBlockTransactions", |provider| async move {
+                let trace_builder = provider.trace_replay_block_transactions(block_number.into());
+                Ok(trace_builder.trace_types(trace_type.to_vec()).trace().await?)
+            }),
+        )
+        .await
+    }
+
+    async fn debug_trace_transaction(
+        &self,
+        tx_hash: &str,
+        options: &GethDebugTracingOptions,
+    ) -> Result<GethTrace> {
+        let tx_hash: TxHash = tx_hash.parse::<TxHash>()?;
+        self.cached(
+            "debug_trace_transaction",
+            &(tx_hash, options),
+            |_| Some(Freshness::Immutable),
+            self.with_fallback("debug_traceTransaction", |provider| async move {
+                Ok(provider.debug_trace_transaction(tx_hash, options.clone()).await?)
+            }),
+        )
+        .await
+    }
+
+    async fn trace_call(
+        &self,
+        request: &TransactionRequest,
+        trace_type: &[TraceType],
+        block: Option<BlockId>,
+    ) -> Result<TraceResults> {
+        let freshness = Freshness::AtBlock(block);
+        let block = block.unwrap_or_else(BlockId::latest);
+        self.cached(
+            "trace_call",
+            &(request, trace_type, block),
+            |_| Some(freshness),
+            self.with_fallback("trace_call", |provider| async move {
+                let trace_builder = provider.trace_call(request).block_id(block);
+                Ok(trace_builder.trace_types(trace_type.to_vec()).trace().await?)
+            }),
+        )
+        .await
+    }
+
+    async fn debug_trace_call(
+        &self,
+        request: &TransactionRequest,
+        options: &GethDebugTracingOptions,
+        block: Option<BlockId>,
+    ) -> Result<GethTrace> {
+        let freshness = Freshness::AtBlock(block);
+        let block = block.unwrap_or_else(BlockId::latest);
+        self.cached(
+            "debug_trace_call",
+            &(request, options, block),
+            |_| Some(freshness),
+            self.with_fallback("debug_traceCall", |provider| async move {
+                Ok(provider
+                    .debug_trace_call(
+                        request.clone(),
+                        block,
+                        GethDebugTracingCallOptions::new(options.clone()),
+                    )
+                    .await?)
+            }),
+        )
+        .await
+    }
+
+    async fn debug_trace_block(
+        &self,
+        block_number: u64,
+        options: &GethDebugTracingOptions,
+    ) -> Result<Vec<TraceResult>> {
+        self.cached(
+            "debug_trace_block",
+            &(block_number, options),
+            |_| Some(Freshness::AtBlock(Some(BlockId::number(block_number)))),
+            self.with_fallback("debug_traceBlockByNumber", |provider| async move {
+                Ok(provider
+                    .debug_trace_block_by_number(block_number.into(), options.clone())
+                    .await?)
+            }),
+        )
+        .await
+    }
+
+    async fn supports_trace_namespace(&self, namespace: TraceNamespace) -> Result<bool> {
+        // call the namespace's transaction replay method on a nonexistent transaction, so any
+        // response other than a "method not found" error indicates support
+        self.with_fallback(namespace.probe_method(), |provider| async move {
+            let result = match namespace {
+                TraceNamespace::Parity => {
+                    provider
+                        .raw_request::<_, serde_json::Value>(
+                            namespace.probe_method().into(),
+                            (B256::ZERO, [TraceType::Trace]),
+                        )
+                        .await
+                }
+                TraceNamespace::Geth => {
+                    provider
+                        .raw_request::<_, serde_json::Value>(
+                            namespace.probe_method().into(),
+                            (B256::ZERO, GethDebugTracingOptions::default()),
+                        )
+                        .await
+                }
+            };
+
+            match result {
+                Ok(_) => Ok(true),
+                Err(e) if is_unsupported_method(&e) => Ok(false),
+                Err(RpcError::ErrorResp(_)) => Ok(true),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn supports_archive_state(&self) -> Result<bool> {
+        // read the balance of the zero address at the genesis block, which any response other
+        // than an error indicates is still available
+        self.with_fallback("eth_getBalance", |provider| async move {
+            match provider
+                .raw_request::<_, U256>(
+                    "eth_getBalance".into(),
+                    (Address::ZERO, BlockId::number(0)),
+                )
+                .await
+            {
+                Ok(_) => Ok(true),
+                Err(RpcError::ErrorResp(_)) => Ok(false),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn capabilities(&self) -> Result<Capabilities> {
+        self.capabilities.get_or_try_init(|| probe_capabilities(self)).await.copied()
+    }
+
+    async fn get_logs(&self, filter: &Filter, block: Option<BlockId>) -> Result<Vec<Log>> {
+        let filter = &match block {
+            Some(BlockId::Hash(hash)) => filter.clone().at_block_hash(hash.block_hash),
+            Some(BlockId::Number(number)) => filter.clone().from_block(number).to_block(number),
+            None => filter.clone(),
+        };
+        let freshness = match (filter.get_block_hash(), filter.get_to_block()) {
+            (Some(hash), _) => Freshness::AtBlock(Some(BlockId::hash(hash))),
+            (None, Some(number)) => Freshness::AtBlock(Some(BlockId::number(number))),
+            (None, None) => Freshness::AtBlock(None),
+        };
+        self.cached(
+            "get_logs",
+            filter,
+            |_| Some(freshness),
+            self.with_fallback("eth_getLogs", |provider| async move {
+                Ok(provider.get_logs(filter).await?)
+            }),
+        )
+        .await
+    }
+
+    async fn subscribe_block_numbers(&self) -> Result<Option<BoxStream<'static, u64>>> {
+        ensure_online("subscribe to new blocks")?;
+
+        // only websocket endpoints support subscriptions
+        let Some(endpoint) = self.endpoints.iter().find(|endpoint| endpoint.url.starts_with("ws"))
+        else {
+            return Ok(None);
+        };
+        let subscription = endpoint.provider.subscribe_blocks().await?;
+        Ok(Some(subscription.into_stream().map(|header| header.number).boxed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{rpc::json_rpc::ErrorPayload, sol_types::SolValue};
+
+    #[test]
+    fn test_parse_rpc_urls() {
+        assert_eq!(parse_rpc_urls("https://a.example"), vec!["https://a.example"]);
+        assert_eq!(
+            parse_rpc_urls(" https://a.example ,wss://b.example"),
+            vec!["https://a.example", "wss://b.example"]
+        );
+        assert!(parse_rpc_urls("").is_empty());
+        assert!(parse_rpc_urls(" , ,").is_empty());
+    }
+
+    #[test]
+    fn test_retry_policy_delays() {
+        let policy = RetryPolicy::new()
+            .with_max_retries(5)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500))
+            .with_jitter(false);
+        assert_eq!(
+            policy.delays().map(|d| d.as_millis()).collect::<Vec<_>>(),
+            vec![100, 200, 400, 500, 500]
+        );
+
+        let policy = policy.with_jitter(true);
+        assert!(policy.delays().all(|d| d <= Duration::from_millis(500)));
+        assert_eq!(policy.with_max_retries(0).delays().count(), 0);
+    }
+
+    #[test]
+    fn test_is_unsupported_method() {
+        let error = |code: i64, message: &str| {
+            TransportError::ErrorResp(ErrorPayload {
+                code,
+                message: message.to_string().into(),
+                data: None,
+            })
+        };
+
+        assert!(is_unsupported_method(&error(-32601, "method not found")));
+        assert!(is_unsupported_method(&error(
+            -32000,
+            "the method trace_replayTransaction does not exist/is not available"
+        )));
+        assert!(is_unsupported_method(&error(
+            -32600,
+            "Unsupported method: trace_replayTransaction"
+        )));
+        assert!(!is_unsupported_method(&error(-32000, "transaction not found")));
+    }
+
+    #[tokio::test]
+    async fn test_connect_without_urls() {
+        assert!(MultiTransportProvider::connect("").await.is_err());
+        assert!(MultiTransportProvider::connect(" , ").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connect_multiple_urls() {
+        let provider =
+            MultiTransportProvider::connect("http://localhost:1,http://localhost:2").await.unwrap();
+        assert_eq!(provider.urls(), vec!["http://localhost:1", "http://localhost:2"]);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_returns_last_error() {
+        let provider = MultiTransportProvider::connect("http://localhost:1,http://localhost:2")
+            .await
+            .unwrap()
+            .with_timeout(Duration::from_secs(1))
+            .with_retry_policy(RetryPolicy::new().with_max_retries(1));
+        assert!(provider.get_chainid().await.is_err());
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let report = |error: TransportError| eyre::Report::new(error);
+        let error = |code: i64, message: &str| {
+            report(TransportError::ErrorResp(ErrorPayload {
+                code,
+                message: message.to_string().into(),
+                data: None,
+            }))
+        };
+        let http = |status: u16| {
+            report(TransportError::Transport(TransportErrorKind::HttpError(
+                alloy::transports::HttpError { status, body: String::new() },
+            )))
+        };
+
+        assert!(is_transient(&error(429, "too many requests")));
+        assert!(is_transient(&error(-32005, "daily request count exceeded")));
+        assert!(is_transient(&http(429)));
+        assert!(is_transient(&http(502)));
+        assert!(is_transient(&report(TransportError::Transport(TransportErrorKind::BackendGone))));
+
+        assert!(!is_transient(&error(3, "execution reverted")));
+        assert!(!is_transient(&error(-32602, "invalid params")));
+        assert!(!is_transient(&http(401)));
+        assert!(!is_transient(&eyre!("block 1 not found")));
+    }
+
+    #[tokio::test]
+    async fn test_revert_is_not_retried() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        // a minimal http server which answers every request with a revert
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut request = vec![0u8; 4096];
+                    while stream.read(&mut request).await.is_ok_and(|len| len > 0) {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        let body = r#"{"jsonrpc":"2.0","id":0,"error":{"code":3,"message":"execution reverted","data":"0x"}}"#;
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        stream.write_all(response.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        // the revert is returned from the first endpoint, without retrying or failing over
+        let provider = MultiTransportProvider::connect(&format!("{url},{url}"))
+            .await
+            .unwrap()
+            .with_response_cache(None)
+            .with_retry_policy(RetryPolicy::new().with_max_retries(3));
+        let error = provider.call(Address::ZERO, &[], None).await.unwrap_err();
+        assert!(error.to_string().contains("execution reverted"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_headers() {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::TcpListener,
+        };
+
+        // a minimal http server which answers a single eth_chainId request
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let len = stream.read(&mut request).await.unwrap();
+            let body = r#"{"jsonrpc":"2.0","id":0,"result":"0x539"}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..len]).to_lowercase()
+        });
+
+        let headers = vec![("x-api-key".to_string(), "secret".to_string())];
+        let provider = connect_endpoint(&url, &headers).await.unwrap();
+        assert_eq!(provider.get_chain_id().await.unwrap(), 1337);
+        assert!(server.await.unwrap().contains("x-api-key: secret"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_without_requests() {
+        let provider = MultiTransportProvider::connect("http://localhost:1").await.unwrap();
+        assert!(provider.get_transactions_batch(&[]).await.unwrap().is_empty());
+        assert!(provider.get_storage_batch(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cached_responses() {
+        let cache = Arc::new(ResponseCache::new().with_persistence(false));
+        let urls = vec!["http://localhost:1".to_string()];
+        let slot = |slot: u64| (Address::ZERO, U256::from(slot), BlockId::latest());
+        cache.insert(&ResponseCache::key("chain_id", None, &urls), &1337u64, Ttl::Forever);
+        cache.insert(
+            &ResponseCache::key("get_code", Some(1337), &(Address::ZERO, BlockId::latest())),
+            &Bytes::from(vec![0x60, 0x80]),
+            Ttl::Forever,
+        );
+        cache.insert(
+            &ResponseCache::key("get_storage", Some(1337), &slot(1)),
+            &U256::from(7),
+            Ttl::Forever,
+        );
+
+        // every response is served from the cache, without reaching the unreachable endpoint
+        let provider = MultiTransportProvider::connect("http://localhost:1")
+            .await
+            .unwrap()
+            .with_response_cache(Some(cache))
+            .with_retry_policy(RetryPolicy::new().with_max_retries(0));
+        assert_eq!(provider.get_chainid().await.unwrap(), 1337);
+        assert_eq!(provider.get_code_at(Address::ZERO, None).await.unwrap(), vec![0x60, 0x80]);
+        assert_eq!(
+            provider.get_storage_batch(&[(Address::ZERO, U256::from(1))]).await.unwrap(),
+            vec![U256::from(7)]
+        );
+
+        // uncached responses are still fetched
+        assert!(provider.get_storage_batch(&[(Address::ZERO, U256::from(2))]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_call_batch_with_multicall() {
+        let cache = Arc::new(ResponseCache::new().with_persistence(false));
+        let urls = vec!["http://localhost:1".to_string()];
+        let calls = vec![
+            (Address::repeat_byte(1), vec![0x31, 0x3c, 0xe5, 0x67]),
+            (Address::repeat_byte(2), vec![0x31, 0x3c, 0xe5, 0x67]),
+        ];
+        let aggregate3 =
+            (MULTICALL3_ADDRESS, Bytes::from(encode_aggregate3(&calls)), BlockId::latest());
+        let returndata =
+            vec![(true, Bytes::from(U256::from(18).to_be_bytes_vec())), (false, Bytes::new())]
+                .abi_encode();
+        cache.insert(&ResponseCache::key("chain_id", None, &urls), &1337u64, Ttl::Forever);
+        cache.insert(
+            &ResponseCache::key("get_code", Some(1337), &(MULTICALL3_ADDRESS, BlockId::latest())),
+            &Bytes::from(vec![0x60, 0x80]),
+            Ttl::Forever,
+        );
+        cache.insert(
+            &ResponseCache::key("call", Some(1337), &aggregate3),
+            &Bytes::from(returndata),
+            Ttl::Forever,
+        );
+
+        // both calls are served by the single, cached, aggregate3 call
+        let provider = MultiTransportProvider::connect("http://localhost:1")
+            .await
+            .unwrap()
+            .with_response_cache(Some(cache))
+            .with_retry_policy(RetryPolicy::new().with_max_retries(0));
+        let results = provider.call_batch(&calls, None).await.unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &U256::from(18).to_be_bytes_vec());
+        assert!(results[1].is_err());
+
+        // without multicall, the calls are made one at a time against the unreachable endpoint
+        let results = provider.with_multicall(false).call_batch(&calls, None).await.unwrap();
+        assert!(results.iter().all(|result| result.is_err()));
+    }
+
+    #[tokio::test]
+    async fn test_get_transactions_batch() {
+        let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| {
+            println!("RPC_URL not set, skipping test");
+            std::process::exit(0);
+        });
+
+        let tx_hash: TxHash = "0x9a5f4ef7678a94dd87048eeec931d30af21b1f4cecbf7e850a531d2bb64a54ac"
+            .parse()
+            .expect("invalid");
+        let provider = MultiTransportProvider::connect(&rpc_url).await.unwrap().with_batch_size(2);
+        let transactions = provider
+            .get_transactions_batch(&[tx_hash, TxHash::ZERO, tx_hash])
+            .await
+            .expect("get_transactions_batch() returned an error!");
+
+        assert_eq!(transactions.len(), 3);
+        assert!(transactions[0].is_some());
+        assert!(transactions[1].is_none());
+        assert!(transactions[2].is_some());
+    }
+
+    /// A provider which only knows its chain id and a single transaction.
+    #[derive(Debug)]
+    struct MockProvider;
+
+    #[async_trait]
+    impl HeimdallProvider for MockProvider {
+        async fn get_chainid(&self) -> Result<u64> {
+            Ok(1337)
+        }
+
+        async fn get_block_number(&self) -> Result<u64> {
+            Ok(1)
+        }
+
+        async fn get_block_timestamp(&self, _: u64) -> Result<u64> {
+            Ok(1_700_000_000)
+        }
+
+        async fn get_code_at(&self, _: Address, _: Option<BlockId>) -> Result<Vec<u8>> {
+            Ok(vec![0x60, 0x80])
+        }
+
+        async fn get_storage_at(&self, _: Address, slot: U256, _: Option<BlockId>) -> Result<U256> {
+            Ok(slot)
+        }
+
+        async fn call(&self, _: Address, calldata: &[u8], _: Option<BlockId>) -> Result<Vec<u8>> {
+            Ok(calldata.to_vec())
+        }
+
+        async fn get_transaction_by_hash(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
+            if tx_hash == TxHash::ZERO {
+                return Ok(None);
+            }
+            Err(eyre!("unknown transaction"))
+        }
+
+        async fn trace_replay_transaction(&self, _: &str, _: &[TraceType]) -> Result<TraceResults> {
+            Err(eyre!("not mocked"))
+        }
+
+        async fn trace_replay_block_transactions(
+            &self,
+            _: u64,
+            _: &[TraceType],
+        ) -> Result<Vec<TraceResultsWithTransactionHash>> {
+            Err(eyre!("not mocked"))
+        }
+
+        async fn debug_trace_transaction(
+            &self,
+            _: &str,
+            _: &GethDebugTracingOptions,
+        ) -> Result<GethTrace> {
+            Err(eyre!("not mocked"))
+        }
+
+        async fn trace_call(
+            &self,
+            _: &TransactionRequest,
+            _: &[TraceType],
+            _: Option<BlockId>,
+        ) -> Result<TraceResults> {
+            Err(eyre!("not mocked"))
+        }
+
+        async fn debug_trace_call(
+            &self,
+            _: &TransactionRequest,
+            _: &GethDebugTracingOptions,
+            _: Option<BlockId>,
+        ) -> Result<GethTrace> {
+            Err(eyre!("not mocked"))
+        }
+
+        async fn debug_trace_block(
+            &self,
+            _: u64,
+            _: &GethDebugTracingOptions,
+        ) -> Result<Vec<TraceResult>> {
+            Err(eyre!("not mocked"))
+        }
+
+        async fn supports_trace_namespace(&self, _: TraceNamespace) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn get_logs(&self, _: &Filter, _: Option<BlockId>) -> Result<Vec<Log>> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Serves [`MockProvider`] for `mock://` urls, and connects normally otherwise.
+    struct MockFactory;
+
+    #[async_trait]
+    impl ProviderFactory for MockFactory {
+        async fn connect(&self, rpc_url: &str) -> Result<Arc<dyn HeimdallProvider>> {
+            if rpc_url.starts_with("mock://") {
+                return Ok(Arc::new(MockProvider));
+            }
+            Ok(Arc::new(MultiTransportProvider::connect(rpc_url).await?))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_factory() {
+        set_provider_factory(Some(Arc::new(MockFactory)));
+        let provider = connect_provider("mock://chain").await.unwrap();
+        set_provider_factory(None);
 
-        let trace_builder = self.provider.trace_replay_block_transactions(block_number);
-        let trace_results = trace_builder.trace_types(trace_type.to_vec()).trace().await?;
-        Ok(trace_results)
+        assert_eq!(provider.get_chainid().await.unwrap(), 1337);
+        assert_eq!(
+            provider.get_storage_batch(&[(Address::ZERO, U256::from(7))]).await.unwrap(),
+            vec![U256::from(7)]
+        );
+        assert_eq!(provider.get_transactions_batch(&[TxHash::ZERO]).await.unwrap().len(), 1);
+        assert!(provider
+            .get_transactions_batch(&[TxHash::ZERO, B256::repeat_byte(1)])
+            .await
+            .is_err());
     }
 
-    /// Get the logs that match the given filter.
-    pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
-        Ok(self.provider.get_logs(filter).await?)
+    #[tokio::test]
+    async fn test_default_capabilities() {
+        // the mock supports neither tracing namespace, but serves code at any block
+        let capabilities = MockProvider.capabilities().await.unwrap();
+        assert_eq!(
+            capabilities,
+            Capabilities { parity_traces: false, geth_traces: false, archive: true }
+        );
+        assert_eq!(capabilities.trace_namespace(), None);
     }
 }
@@ -0,0 +1,426 @@
+//! An in-memory and on-disk cache for rpc responses, used by [`MultiTransportProvider`].
+//!
+//! Responses are keyed by the method, its parameters, and the chain id. How long a response is
+//! cached for depends on the block it was read at: responses read at old blocks never change,
+//! so they're cached indefinitely, while responses read at the latest block are only cached for
+//! a short, per-method time.
+//!
+//! [`MultiTransportProvider`]: crate::ether::provider::MultiTransportProvider
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::keccak256,
+};
+use hashbrown::HashMap;
+use heimdall_cache::{read_cache, store_cache, RPC_CACHE_PREFIX};
+use lazy_static::lazy_static;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::debug;
+
+/// The default maximum combined size of the responses held in memory.
+pub const DEFAULT_MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// The default maximum size of a single cached response. Larger responses, e.g. the traces of
+/// very large blocks, are not cached.
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Blocks at least this many blocks behind the latest block are considered final, so responses
+/// read at them are cached indefinitely.
+pub const FINALITY_DEPTH: u64 = 64;
+
+/// Responses which are cached for at least this long are also written to disk, so they're
+/// available to later runs and in offline mode.
+pub const PERSIST_THRESHOLD: Duration = Duration::from_secs(60 * 60);
+
+lazy_static! {
+    /// The response cache shared by newly connected providers, see [`set_default_response_cache`].
+    static ref DEFAULT_RESPONSE_CACHE: RwLock<Option<Arc<ResponseCache>>> =
+        RwLock::new(Some(Arc::new(ResponseCache::new())));
+}
+
+/// Use the given [`ResponseCache`] for all newly connected providers, or disable response
+/// caching if `None` is given.
+pub fn set_default_response_cache(cache: Option<Arc<ResponseCache>>) {
+    *DEFAULT_RESPONSE_CACHE.write().unwrap_or_else(|e| e.into_inner()) = cache;
+}
+
+/// Get the response cache shared by newly connected providers, if any.
+pub fn default_response_cache() -> Option<Arc<ResponseCache>> {
+    DEFAULT_RESPONSE_CACHE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// How long a response may be cached for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ttl {
+    /// The response never changes.
+    Forever,
+    /// The response may change after the given duration.
+    For(Duration),
+}
+
+/// What a response depends on, which determines its [`Ttl`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Freshness {
+    /// The response never changes, e.g. the chain id or the trace of a transaction.
+    Immutable,
+    /// The response was read at the given block, or at the latest block if `None`.
+    AtBlock(Option<BlockId>),
+}
+
+impl Freshness {
+    /// The block number this response was read at, if it was read at a specific block number.
+    /// The latest block number is needed to tell whether that block is final.
+    pub fn block_number(&self) -> Option<u64> {
+        match self {
+            Freshness::AtBlock(Some(BlockId::Number(BlockNumberOrTag::Number(number)))) => {
+                Some(*number)
+            }
+            _ => None,
+        }
+    }
+
+    /// The [`Ttl`] of a `method` response, given the latest block number if known.
+    ///
+    /// ```
+    /// use alloy::eips::BlockId;
+    /// use heimdall_common::ether::response_cache::{latest_ttl, Freshness, Ttl};
+    ///
+    /// let old = Freshness::AtBlock(Some(BlockId::number(100)));
+    /// assert_eq!(old.ttl("get_storage", Some(1_000)), Ttl::Forever);
+    ///
+    /// let recent = Freshness::AtBlock(Some(BlockId::number(990)));
+    /// assert_eq!(recent.ttl("get_storage", Some(1_000)), Ttl::For(latest_ttl("get_storage")));
+    /// ```
+    pub fn ttl(&self, method: &str, latest_block: Option<u64>) -> Ttl {
+        match self {
+            Freshness::Immutable |
+            Freshness::AtBlock(Some(BlockId::Hash(_))) |
+            Freshness::AtBlock(Some(BlockId::Number(BlockNumberOrTag::Earliest))) => Ttl::Forever,
+            _ => match (self.block_number(), latest_block) {
+                (Some(number), Some(latest)) if number.saturating_add(FINALITY_DEPTH) <= latest => {
+                    Ttl::Forever
+                }
+                _ => Ttl::For(latest_ttl(method)),
+            },
+        }
+    }
+}
+
+/// The time to live of a `method` response read at the latest block.
+pub fn latest_ttl(method: &str) -> Duration {
+    match method {
+        // contract code only changes when a contract is self-destructed or redeployed
+        "get_code" => Duration::from_secs(60 * 60 * 24),
+        "block_number" => Duration::from_secs(2),
+        // roughly one block
+        _ => Duration::from_secs(12),
+    }
+}
+
+/// A cached response, serialized as JSON.
+#[derive(Debug)]
+struct Entry {
+    value: String,
+    expires_at: Option<Instant>,
+    /// The generation the entry was inserted at, which its key in [`Memory::order`] is stored
+    /// with.
+    generation: u64,
+}
+
+/// The responses held in memory, along with their insertion order for eviction.
+///
+/// Removing a response leaves its key in `order`, rather than searching for it. Each key in
+/// `order` is stored with the generation it was inserted at, so that keys which were removed or
+/// reinserted since are skipped when evicting.
+#[derive(Debug, Default)]
+struct Memory {
+    entries: HashMap<String, Entry>,
+    order: VecDeque<(String, u64)>,
+    next_generation: u64,
+    bytes: usize,
+}
+
+impl Memory {
+    fn insert(&mut self, key: &str, value: String, expires_at: Option<Instant>) {
+        self.remove(key);
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        self.bytes += value.len();
+        self.entries.insert(key.to_string(), Entry { value, expires_at, generation });
+        self.order.push_back((key.to_string(), generation));
+
+        // drop the stale keys once they outnumber the live ones, so that `order` stays
+        // proportional to the number of responses held
+        if self.order.len() > 2 * self.entries.len() {
+            let entries = &self.entries;
+            self.order.retain(|(key, generation)| {
+                entries.get(key).is_some_and(|entry| entry.generation == *generation)
+            });
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.bytes -= entry.value.len();
+        }
+    }
+
+    /// Evict the oldest response, returning whether there was one to evict.
+    fn evict_oldest(&mut self) -> bool {
+        while let Some((key, generation)) = self.order.pop_front() {
+            if self.entries.get(&key).is_some_and(|entry| entry.generation == generation) {
+                self.remove(&key);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// [`ResponseCache`] caches rpc responses in memory, and writes responses which are cached for
+/// at least [`PERSIST_THRESHOLD`] to the on-disk cache in `~/.bifrost/cache`.
+///
+/// When the responses held in memory exceed the cache's size limit, the oldest responses are
+/// evicted first.
+#[derive(Debug)]
+pub struct ResponseCache {
+    max_memory_bytes: usize,
+    max_response_bytes: usize,
+    persist: bool,
+    memory: Mutex<Memory>,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseCache {
+    /// Create a new response cache with the default size limits, which persists long-lived
+    /// responses to disk.
+    pub fn new() -> Self {
+        Self {
+            max_memory_bytes: DEFAULT_MAX_MEMORY_BYTES,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            persist: true,
+            memory: Mutex::new(Memory::default()),
+        }
+    }
+
+    /// Set the maximum combined size of the responses held in memory.
+    pub fn with_max_memory_bytes(mut self, max_memory_bytes: usize) -> Self {
+        self.max_memory_bytes = max_memory_bytes;
+        self
+    }
+
+    /// Set the maximum size of a single cached response.
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    /// Set whether long-lived responses are written to, and read from, the on-disk cache.
+    pub fn with_persistence(mut self, persist: bool) -> Self {
+        self.persist = persist;
+        self
+    }
+
+    /// The cache key of a `method` response with the given `params`, on the given chain.
+    ///
+    /// Keys have the form `rpc.{method}.{chain_id}.{params_hash}`, so that all cached responses
+    /// of a method can be cleared with `heimdall cache clear --method {method}`.
+    ///
+    /// ```
+    /// use heimdall_common::ether::response_cache::ResponseCache;
+    ///
+    /// let key = ResponseCache::key("get_code", Some(1), &("0x00", "latest"));
+    /// assert!(key.starts_with("rpc.get_code.1."));
+    /// assert_ne!(key, ResponseCache::key("get_code", Some(10), &("0x00", "latest")));
+    /// ```
+    pub fn key<P: Serialize>(method: &str, chain_id: Option<u64>, params: &P) -> String {
+        let params_hash = keccak256(serde_json::to_vec(params).unwrap_or_default());
+        match chain_id {
+            Some(chain_id) => format!("{RPC_CACHE_PREFIX}.{method}.{chain_id}.{params_hash:x}"),
+            None => format!("{RPC_CACHE_PREFIX}.{method}.{params_hash:x}"),
+        }
+    }
+
+    /// Get the cached response for `key`, if it exists and hasn't expired.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self
+            .get_memory(key)
+            .or_else(|| self.persist.then(|| read_cache::<String>(key).ok().flatten()).flatten())?;
+
+        match serde_json::from_str(&value) {
+            Ok(value) => {
+                debug!("rpc cache hit for key: '{}'", key);
+                Some(value)
+            }
+            Err(e) => {
+                debug!("failed to deserialize cached rpc response '{}': {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Cache the response `value` for `key`, for as long as `ttl` allows.
+    pub fn insert<T: Serialize>(&self, key: &str, value: &T, ttl: Ttl) {
+        let Ok(value) = serde_json::to_string(value) else {
+            return;
+        };
+        if value.len() > self.max_response_bytes {
+            debug!("not caching rpc response '{}' of {} bytes", key, value.len());
+            return;
+        }
+
+        let persist = self.persist &&
+            match ttl {
+                Ttl::Forever => true,
+                Ttl::For(ttl) => ttl >= PERSIST_THRESHOLD,
+            };
+        if persist {
+            let expiry = match ttl {
+                Ttl::Forever => None,
+                Ttl::For(ttl) => SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .map(|now| (now + ttl).as_secs()),
+            };
+            if let Err(e) = store_cache(key, &value, expiry) {
+                debug!("failed to persist rpc response '{}': {}", key, e);
+            }
+        }
+
+        let expires_at = match ttl {
+            Ttl::Forever => None,
+            Ttl::For(ttl) => Some(Instant::now() + ttl),
+        };
+        self.insert_memory(key, value, expires_at);
+    }
+
+    /// Remove all responses held in memory. Persisted responses are kept.
+    pub fn clear_memory(&self) {
+        *self.memory.lock().unwrap_or_else(|e| e.into_inner()) = Memory::default();
+    }
+
+    /// The number of responses, and their combined size, held in memory.
+    pub fn memory_usage(&self) -> (usize, usize) {
+        let memory = self.memory.lock().unwrap_or_else(|e| e.into_inner());
+        (memory.entries.len(), memory.bytes)
+    }
+
+    fn get_memory(&self, key: &str) -> Option<String> {
+        let mut memory = self.memory.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = memory.entries.get(key)?;
+        if entry.expires_at.is_some_and(|expires_at| expires_at <= Instant::now()) {
+            memory.remove(key);
+            return None;
+        }
+        Some(entry.value.clone())
+    }
+
+    fn insert_memory(&self, key: &str, value: String, expires_at: Option<Instant>) {
+        let mut memory = self.memory.lock().unwrap_or_else(|e| e.into_inner());
+        memory.insert(key, value, expires_at);
+
+        // evict the oldest responses until the cache fits within its size limit
+        while memory.bytes > self.max_memory_bytes && memory.evict_oldest() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::B256;
+
+    #[test]
+    fn test_freshness_ttl() {
+        let short = Ttl::For(latest_ttl("get_storage"));
+        let at = |block: BlockId| Freshness::AtBlock(Some(block));
+
+        assert_eq!(Freshness::Immutable.ttl("get_storage", None), Ttl::Forever);
+        assert_eq!(Freshness::AtBlock(None).ttl("get_storage", Some(1_000)), short);
+        assert_eq!(at(BlockId::latest()).ttl("get_storage", Some(1_000)), short);
+        assert_eq!(at(BlockId::hash(B256::ZERO)).ttl("get_storage", None), Ttl::Forever);
+        assert_eq!(at(BlockId::earliest()).ttl("get_storage", None), Ttl::Forever);
+        assert_eq!(at(BlockId::number(936)).ttl("get_storage", Some(1_000)), Ttl::Forever);
+        assert_eq!(at(BlockId::number(937)).ttl("get_storage", Some(1_000)), short);
+        assert_eq!(at(BlockId::number(1)).ttl("get_storage", None), short);
+        assert_eq!(
+            Freshness::AtBlock(None).ttl("get_code", None),
+            Ttl::For(Duration::from_secs(60 * 60 * 24))
+        );
+    }
+
+    #[test]
+    fn test_memory_cache() {
+        let cache = ResponseCache::new().with_persistence(false);
+        let key = ResponseCache::key("get_storage", Some(1), &(1u64, 2u64));
+
+        assert_eq!(cache.get::<u64>(&key), None);
+        cache.insert(&key, &42u64, Ttl::Forever);
+        assert_eq!(cache.get::<u64>(&key), Some(42));
+
+        cache.insert(&key, &43u64, Ttl::For(Duration::ZERO));
+        assert_eq!(cache.get::<u64>(&key), None);
+        assert_eq!(cache.memory_usage(), (0, 0));
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let cache = ResponseCache::new()
+            .with_persistence(false)
+            .with_max_memory_bytes(10)
+            .with_max_response_bytes(8);
+
+        // responses above the per-response limit are never cached
+        cache.insert("too_large", &"0123456789", Ttl::Forever);
+        assert_eq!(cache.get::<String>("too_large"), None);
+
+        // the oldest responses are evicted once the memory limit is exceeded
+        cache.insert("a", &"aaaa", Ttl::Forever);
+        cache.insert("b", &"bbbb", Ttl::Forever);
+        assert_eq!(cache.get::<String>("a"), None);
+        assert_eq!(cache.get::<String>("b"), Some("bbbb".to_string()));
+        assert_eq!(cache.memory_usage(), (1, 6));
+    }
+
+    #[test]
+    fn test_eviction_skips_reinserted_responses() {
+        let cache = ResponseCache::new().with_persistence(false).with_max_memory_bytes(10);
+
+        // reinserting "a" makes it the newest response, so "b" is the oldest
+        cache.insert("a", &"aa", Ttl::Forever);
+        cache.insert("b", &"bb", Ttl::Forever);
+        cache.insert("a", &"aa", Ttl::Forever);
+        cache.insert("c", &"cc", Ttl::Forever);
+        assert_eq!(cache.get::<String>("b"), None);
+        assert_eq!(cache.get::<String>("a"), Some("aa".to_string()));
+        assert_eq!(cache.get::<String>("c"), Some("cc".to_string()));
+
+        // the stale keys left behind by reinserting are dropped rather than accumulating
+        for _ in 0..100 {
+            cache.insert("a", &"aa", Ttl::Forever);
+        }
+        let memory = cache.memory.lock().unwrap();
+        assert!(memory.order.len() <= 2 * memory.entries.len());
+    }
+
+    #[test]
+    fn test_persisted_response() {
+        let cache = ResponseCache::new();
+        let key = ResponseCache::key("test_persisted_response", Some(1), &());
+        cache.insert(&key, &vec![1u8, 2, 3], Ttl::Forever);
+        cache.clear_memory();
+
+        assert_eq!(cache.get::<Vec<u8>>(&key), Some(vec![1, 2, 3]));
+        heimdall_cache::delete_cache(&key).expect("failed to delete cache");
+    }
+}
@@ -1,17 +1,70 @@
 //! RPC utilities for interacting with Ethereum nodes
 
-use crate::ether::provider::MultiTransportProvider;
+use std::{sync::Mutex, time::Duration};
+
+pub use crate::ether::provider::{Capabilities, RetryPolicy, TraceNamespace};
+use crate::ether::{geth, provider::connect_provider};
 use alloy::{
+    consensus::Transaction as _,
     eips::{BlockId, BlockNumberOrTag},
-    primitives::{Address, TxHash},
+    primitives::{Address, TxHash, U256},
     rpc::types::{
-        trace::parity::{TraceResults, TraceResultsWithTransactionHash, TraceType},
-        Filter, FilterBlockOption, FilterSet, Log, Transaction,
+        trace::{
+            common::TraceResult,
+            geth::{
+                CallConfig, GethDebugBuiltInTracerType, GethDebugTracingOptions,
+                GethDefaultTracingOptions, PreStateConfig,
+            },
+            parity::{TraceResults, TraceResultsWithTransactionHash, TraceType, VmTrace},
+        },
+        Filter, FilterBlockOption, FilterSet, Log, Transaction, TransactionRequest,
     },
 };
-use eyre::{bail, OptionExt, Result};
+use eyre::{bail, eyre, OptionExt, Result};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use hashbrown::HashMap;
 use heimdall_cache::with_cache;
-use tokio_retry::{strategy::ExponentialBackoff, Retry};
+use lazy_static::lazy_static;
+use tracing::debug;
+
+lazy_static! {
+    /// The capabilities probed for each rpc url, so each node is only probed once.
+    static ref CAPABILITIES: Mutex<HashMap<String, Capabilities>> = Mutex::new(HashMap::new());
+}
+
+/// Set the [`RetryPolicy`] used by all subsequent RPC requests.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::{set_retry_policy, RetryPolicy};
+/// use std::time::Duration;
+///
+/// set_retry_policy(
+///     RetryPolicy::default().with_max_retries(5).with_initial_backoff(Duration::from_millis(250)),
+/// );
+/// ```
+pub fn set_retry_policy(policy: RetryPolicy) {
+    RetryPolicy::set_default(policy);
+}
+
+/// Get the [`RetryPolicy`] used by RPC requests.
+pub fn retry_policy() -> RetryPolicy {
+    RetryPolicy::default_policy()
+}
+
+/// Limit all subsequent RPC requests to a combined `requests_per_second`, shared between
+/// concurrent tasks. Passing `None` removes the limit.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::set_rate_limit;
+///
+/// set_rate_limit(Some(10));
+/// ```
+pub fn set_rate_limit(requests_per_second: Option<u32>) {
+    crate::ether::provider::set_default_rate_limit(requests_per_second);
+}
 
 /// Get the chainId of the provided RPC URL
 ///
@@ -22,17 +75,8 @@ use tokio_retry::{strategy::ExponentialBackoff, Retry};
 /// // assert_eq!(chain_id, 1);
 /// ```
 pub async fn chain_id(rpc_url: &str) -> Result<u64> {
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        with_cache(
-            &format!("chain_id.{}", &rpc_url.replace('/', "").replace(['.', ':'], "-")),
-            || async {
-                let provider = MultiTransportProvider::connect(rpc_url).await?;
-                provider.get_chainid().await
-            },
-        )
-        .await
-    })
-    .await
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_chainid().await
 }
 
 /// Get the latest block number of the provided RPC URL
@@ -43,11 +87,49 @@ pub async fn chain_id(rpc_url: &str) -> Result<u64> {
 /// // assert!(block_number > 0);
 /// ```
 pub async fn latest_block_number(rpc_url: &str) -> Result<u128> {
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        let provider = MultiTransportProvider::connect(rpc_url).await?;
-        provider.get_block_number().await.map(|n| n as u128)
-    })
-    .await
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_block_number().await.map(|n| n as u128)
+}
+
+/// The interval at which the latest block number is polled by [`subscribe_block_numbers`], for
+/// nodes which don't support subscriptions.
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(4);
+
+/// Subscribe to the numbers of new blocks of the provided RPC URL, as they're added to the chain.
+///
+/// New blocks are subscribed to with `eth_subscribe` where the node supports subscriptions,
+/// e.g. over a websocket. Otherwise, the latest block number is polled, and yielded each time it
+/// increases. A block number may be skipped if several blocks are added between polls.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::subscribe_block_numbers;
+///
+/// // let mut blocks = subscribe_block_numbers("wss://eth.llamarpc.com").await?;
+/// // let block_number = blocks.next().await;
+/// ```
+pub async fn subscribe_block_numbers(rpc_url: &str) -> Result<BoxStream<'static, Result<u64>>> {
+    let provider = connect_provider(rpc_url).await?;
+    if let Some(blocks) = provider.subscribe_block_numbers().await? {
+        return Ok(blocks.map(Ok).boxed());
+    }
+
+    debug!("rpc doesn't support subscriptions, polling for new blocks");
+    let blocks = stream::unfold((provider, None), |(provider, last)| async move {
+        loop {
+            match provider.get_block_number().await {
+                Ok(number) if Some(number) > last => {
+                    return Some((Ok(number), (provider, Some(number))))
+                }
+                Ok(_) => tokio::time::sleep(BLOCK_POLL_INTERVAL).await,
+                Err(e) => {
+                    // wait before the next poll, so failing polls don't spin
+                    tokio::time::sleep(BLOCK_POLL_INTERVAL).await;
+                    return Some((Err(e), (provider, last)));
+                }
+            }
+        }
+    });
+    Ok(blocks.boxed())
 }
 
 /// Get the bytecode of the provided contract address
@@ -59,20 +141,106 @@ pub async fn latest_block_number(rpc_url: &str) -> Result<u128> {
 /// // assert!(bytecode.is_ok());
 /// ```
 pub async fn get_code(contract_address: Address, rpc_url: &str) -> Result<Vec<u8>> {
+    get_code_at(contract_address, None, rpc_url).await
+}
+
+/// Get the bytecode of the provided contract address at the given block. If `block` is `None`,
+/// the bytecode is read at the latest block.
+///
+/// This is useful for reading the code of a contract as it existed before a redeploy or
+/// self-destruct.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::get_code_at;
+///
+/// // let bytecode = get_code_at("0x0", Some(BlockId::number(1)), "https://eth.llamarpc.com").await;
+/// // assert!(bytecode.is_ok());
+/// ```
+pub async fn get_code_at(
+    contract_address: Address,
+    block: Option<BlockId>,
+    rpc_url: &str,
+) -> Result<Vec<u8>> {
     // if rpc_url is empty, return an error
     if rpc_url.is_empty() {
         bail!("cannot get_code, rpc_url is empty");
     }
 
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        let chain_id = chain_id(rpc_url).await.unwrap_or(1);
-        with_cache(&format!("contract.{}.{}", &chain_id, &contract_address), || async {
-            let provider = MultiTransportProvider::connect(rpc_url).await?;
-            provider.get_code_at(contract_address).await
-        })
-        .await
-    })
-    .await
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_code_at(contract_address, block).await
+}
+
+/// Get the value of the given storage slot of the provided contract address. If `block` is
+/// given, the value is read at that block instead of the latest block.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::get_storage_at;
+///
+/// // let value = get_storage_at("0x0", U256::ZERO, None, "https://eth.llamarpc.com").await;
+/// // assert!(value.is_ok());
+/// ```
+pub async fn get_storage_at(
+    contract_address: Address,
+    slot: U256,
+    block: Option<BlockId>,
+    rpc_url: &str,
+) -> Result<U256> {
+    // if rpc_url is empty, return an error
+    if rpc_url.is_empty() {
+        bail!("cannot get_storage_at, rpc_url is empty");
+    }
+
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_storage_at(contract_address, slot, block).await
+}
+
+/// Execute a read-only call of `calldata` against the provided contract address, returning the
+/// call's return data. If `block` is given, the call is executed at that block instead of the
+/// latest block.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::call;
+///
+/// // let decimals = call("0x0", &[0x31, 0x3c, 0xe5, 0x67], None, "https://eth.llamarpc.com").await;
+/// // assert!(decimals.is_ok());
+/// ```
+pub async fn call(
+    contract_address: Address,
+    calldata: &[u8],
+    block: Option<BlockId>,
+    rpc_url: &str,
+) -> Result<Vec<u8>> {
+    // if rpc_url is empty, return an error
+    if rpc_url.is_empty() {
+        bail!("cannot call, rpc_url is empty");
+    }
+
+    let provider = connect_provider(rpc_url).await?;
+    provider.call(contract_address, calldata, block).await
+}
+
+/// Execute each of the given `(address, calldata)` read-only calls, returning the result of
+/// each call in the same order as `calls`. Where Multicall3 is deployed, the calls are
+/// aggregated into as few `eth_call`s as possible.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::call_batch;
+///
+/// // let results = call_batch(&[("0x0", vec![0x31, 0x3c, 0xe5, 0x67])], None, "https://eth.llamarpc.com").await;
+/// // assert!(results.is_ok());
+/// ```
+pub async fn call_batch(
+    calls: &[(Address, Vec<u8>)],
+    block: Option<BlockId>,
+    rpc_url: &str,
+) -> Result<Vec<Result<Vec<u8>>>> {
+    // if rpc_url is empty, return an error
+    if rpc_url.is_empty() {
+        bail!("cannot call_batch, rpc_url is empty");
+    }
+
+    let provider = connect_provider(rpc_url).await?;
+    provider.call_batch(calls, block).await
 }
 
 /// Get the raw transaction data of the provided transaction hash \
@@ -83,40 +251,206 @@ pub async fn get_code(contract_address: Address, rpc_url: &str) -> Result<Vec<u8
 /// // let bytecode = get_code("0x0", "https://eth.llamarpc.com").await;
 /// // assert!(bytecode.is_ok());
 /// ```
-///
-/// Note: [`Transaction`] is un-cacheable
 pub async fn get_transaction(transaction_hash: TxHash, rpc_url: &str) -> Result<Transaction> {
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        let provider = MultiTransportProvider::connect(rpc_url).await?;
-        provider
-            .get_transaction_by_hash(transaction_hash)
-            .await?
-            .ok_or_eyre("transaction not found")
-    })
-    .await
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_transaction_by_hash(transaction_hash).await?.ok_or_eyre("transaction not found")
+}
+
+/// Get the timestamp of the block with the given number, in seconds since the unix epoch
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::get_block_timestamp;
+///
+/// // let timestamp = get_block_timestamp(19_000_000, "https://eth.llamarpc.com").await;
+/// // assert!(timestamp.is_ok());
+/// ```
+pub async fn get_block_timestamp(block_number: u64, rpc_url: &str) -> Result<u64> {
+    // if rpc_url is empty, return an error
+    if rpc_url.is_empty() {
+        bail!("cannot get_block_timestamp, rpc_url is empty");
+    }
+
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_block_timestamp(block_number).await
+}
+
+/// Probe which tracing namespaces and historical state the provided RPC URL supports. Each RPC
+/// URL is only probed once per process.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::capabilities;
+///
+/// // let capabilities = capabilities("https://eth.llamarpc.com").await?;
+/// ```
+pub async fn capabilities(rpc_url: &str) -> Result<Capabilities> {
+    if let Some(capabilities) = CAPABILITIES.lock().unwrap_or_else(|e| e.into_inner()).get(rpc_url)
+    {
+        return Ok(*capabilities);
+    }
+
+    let provider = connect_provider(rpc_url).await?;
+    let capabilities = provider.capabilities().await?;
+
+    CAPABILITIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(rpc_url.to_string(), capabilities);
+    Ok(capabilities)
+}
+
+/// Detect which tracing namespace the provided RPC URL supports. Parity-style `trace_*`
+/// methods are preferred, falling back to Geth-style `debug_trace*` methods.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::trace_namespace;
+///
+/// // let namespace = trace_namespace("https://eth.llamarpc.com").await?;
+/// ```
+pub async fn trace_namespace(rpc_url: &str) -> Result<TraceNamespace> {
+    let namespace = capabilities(rpc_url).await?.trace_namespace().ok_or_eyre(
+        "rpc supports neither `trace_*` nor `debug_trace*` methods. please use an rpc with one of \
+        these namespaces enabled",
+    )?;
+    debug!("using {:?} tracing namespace for rpc", namespace);
+    Ok(namespace)
 }
 
 /// Get the raw trace data of the provided transaction hash
 ///
+/// If the RPC doesn't support Parity-style traces, the trace is built from Geth's `callTracer`
+/// and `prestateTracer` instead. In that case, the returned [`TraceResults::vm_trace`] only
+/// contains the `LOG*` operations and sub-calls of each call.
+///
 /// ```no_run
 /// use heimdall_common::ether::rpc::get_trace;
 ///
 /// // let trace = get_trace("0x0", "https://eth.llamarpc.com").await;
 /// // assert!(trace.is_ok());
 /// ```
-///
-/// Note: [`TraceResults`] is un-cacheable
 pub async fn get_trace(transaction_hash: &str, rpc_url: &str) -> Result<TraceResults> {
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        let provider = MultiTransportProvider::connect(rpc_url).await?;
-        provider
-            .trace_replay_transaction(
-                transaction_hash,
-                &[TraceType::Trace, TraceType::VmTrace, TraceType::StateDiff],
-            )
-            .await
-    })
-    .await
+    let namespace = trace_namespace(rpc_url).await?;
+    let provider = connect_provider(rpc_url).await?;
+
+    match namespace {
+        TraceNamespace::Parity => {
+            provider
+                .trace_replay_transaction(
+                    transaction_hash,
+                    &[TraceType::Trace, TraceType::VmTrace, TraceType::StateDiff],
+                )
+                .await
+        }
+        TraceNamespace::Geth => {
+            let call_frame = provider
+                .debug_trace_transaction(transaction_hash, &call_tracer_options())
+                .await?
+                .try_into_call_frame()?;
+            let diff = provider
+                .debug_trace_transaction(transaction_hash, &prestate_diff_tracer_options())
+                .await?
+                .try_into_pre_state_frame()?
+                .as_diff()
+                .cloned();
+
+            Ok(geth::to_trace_results(&call_frame, diff))
+        }
+    }
+}
+
+/// Get the opcode-level trace of the provided transaction hash, listing the pc and gas cost of
+/// each operation executed, with the operations of each call nested in their own sub-trace.
+///
+/// If the RPC doesn't support Parity-style traces, the trace is built from Geth's default struct
+/// logger instead, fetching the code of each contract called to fill in each sub-trace's code.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::get_vm_trace;
+///
+/// // let trace = get_vm_trace("0x0", "https://eth.llamarpc.com").await;
+/// // assert!(trace.is_ok());
+/// ```
+pub async fn get_vm_trace(transaction_hash: &str, rpc_url: &str) -> Result<VmTrace> {
+    let namespace = trace_namespace(rpc_url).await?;
+    let provider = connect_provider(rpc_url).await?;
+
+    match namespace {
+        TraceNamespace::Parity => provider
+            .trace_replay_transaction(transaction_hash, &[TraceType::VmTrace])
+            .await?
+            .vm_trace
+            .ok_or_eyre("rpc returned no vm trace"),
+        TraceNamespace::Geth => {
+            let logs = provider
+                .debug_trace_transaction(transaction_hash, &struct_logger_options())
+                .await?
+                .try_into_default_frame()?
+                .struct_logs;
+
+            // the code executed is read at the block the transaction was included in
+            let transaction = get_transaction(transaction_hash.parse()?, rpc_url).await?;
+            let block = transaction.block_number.map(BlockId::number);
+            let mut codes = HashMap::new();
+            for address in geth::struct_log_callees(&logs).into_iter().chain(transaction.to()) {
+                codes.insert(address, get_code_at(address, block, rpc_url).await?.into());
+            }
+
+            let code = transaction.to().and_then(|to| codes.get(&to).cloned()).unwrap_or_default();
+            Ok(geth::struct_logs_to_vm_trace(&logs, code, &codes.into_iter().collect()))
+        }
+    }
+}
+
+/// Simulate the provided transaction request on top of the state at `block` (the latest block
+/// by default) without sending it, returning its raw trace data along with the logs it emitted.
+///
+/// Unlike [`get_trace`], Geth-style traces are preferred, as the logs emitted by the call are
+/// read from the frames of Geth's `debug_traceCall` `callTracer`. If the RPC only supports
+/// Parity-style traces, the call is traced with `trace_call` instead, which doesn't return logs,
+/// so the returned logs are empty.
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::trace_call;
+///
+/// // let (trace, logs) = trace_call(&request, None, "https://eth.llamarpc.com").await?;
+/// ```
+pub async fn trace_call(
+    request: &TransactionRequest,
+    block: Option<BlockId>,
+    rpc_url: &str,
+) -> Result<(TraceResults, Vec<Log>)> {
+    let namespace = match capabilities(rpc_url).await? {
+        capabilities if capabilities.geth_traces => TraceNamespace::Geth,
+        _ => trace_namespace(rpc_url).await?,
+    };
+    let provider = connect_provider(rpc_url).await?;
+
+    match namespace {
+        TraceNamespace::Parity => {
+            let trace = provider
+                .trace_call(
+                    request,
+                    &[TraceType::Trace, TraceType::VmTrace, TraceType::StateDiff],
+                    block,
+                )
+                .await?;
+            Ok((trace, Vec::new()))
+        }
+        TraceNamespace::Geth => {
+            let call_frame = provider
+                .debug_trace_call(request, &call_tracer_options(), block)
+                .await?
+                .try_into_call_frame()?;
+            let diff = provider
+                .debug_trace_call(request, &prestate_diff_tracer_options(), block)
+                .await?
+                .try_into_pre_state_frame()?
+                .as_diff()
+                .cloned();
+
+            let logs = geth::call_frame_logs(&call_frame);
+            Ok((geth::to_trace_results(&call_frame, diff), logs))
+        }
+    }
 }
 
 /// Get all logs for the given block number
@@ -127,13 +461,11 @@ pub async fn get_trace(transaction_hash: &str, rpc_url: &str) -> Result<TraceRes
 /// // let logs = get_block_logs(1, "https://eth.llamarpc.com").await;
 /// // assert!(logs.is_ok());
 /// ```
-///
-/// Note: [`Log`] is un-cacheable
 pub async fn get_block_logs(block_number: u64, rpc_url: &str) -> Result<Vec<Log>> {
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        let provider = MultiTransportProvider::connect(rpc_url).await?;
-        provider
-            .get_logs(&Filter {
+    let provider = connect_provider(rpc_url).await?;
+    provider
+        .get_logs(
+            &Filter {
                 block_option: FilterBlockOption::Range {
                     from_block: Some(BlockNumberOrTag::from(block_number)),
                     to_block: Some(BlockNumberOrTag::from(block_number)),
@@ -145,31 +477,126 @@ pub async fn get_block_logs(block_number: u64, rpc_url: &str) -> Result<Vec<Log>
                     FilterSet::default(),
                     FilterSet::default(),
                 ],
-            })
-            .await
-    })
-    .await
+            },
+            None,
+        )
+        .await
+}
+
+/// Get all logs matching the given filter, such as the logs of one event emitted by a contract
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::get_logs;
+///
+/// // let logs = get_logs(&Filter::new().address("0x0").from_block(0), "https://eth.llamarpc.com").await;
+/// // assert!(logs.is_ok());
+/// ```
+pub async fn get_logs(filter: &Filter, rpc_url: &str) -> Result<Vec<Log>> {
+    // if rpc_url is empty, return an error
+    if rpc_url.is_empty() {
+        bail!("cannot get_logs, rpc_url is empty");
+    }
+
+    let provider = connect_provider(rpc_url).await?;
+    provider.get_logs(filter, None).await
+}
+
+/// Get the logs emitted by the given transaction, in the order they were emitted
+///
+/// ```no_run
+/// use heimdall_common::ether::rpc::get_transaction_logs;
+///
+/// // let logs = get_transaction_logs("0x0".parse()?, "https://eth.llamarpc.com").await;
+/// // assert!(logs.is_ok());
+/// ```
+pub async fn get_transaction_logs(transaction_hash: TxHash, rpc_url: &str) -> Result<Vec<Log>> {
+    let block_hash = get_transaction(transaction_hash, rpc_url)
+        .await?
+        .block_hash
+        .ok_or_eyre("transaction is pending, and hasn't emitted any logs yet")?;
+
+    let mut logs = get_logs(&Filter::new().at_block_hash(block_hash), rpc_url)
+        .await?
+        .into_iter()
+        .filter(|log| log.transaction_hash == Some(transaction_hash))
+        .collect::<Vec<_>>();
+    logs.sort_by_key(|log| log.log_index);
+    Ok(logs)
 }
 
 /// Get all traces for the given block number
 ///
+/// If the RPC doesn't support Parity-style traces, the state diffs are built from Geth's
+/// `prestateTracer` instead.
+///
 /// ```no_run
 /// use heimdall_common::ether::rpc::get_block_state_diff;
 ///
 /// // let traces = get_block_state_diff(1, "https://eth.llamarpc.com").await;
 /// // assert!(traces.is_ok());
 /// ```
-///
-/// Note: [`TraceResultsWithTransactionHash`] is un-cacheable
 pub async fn get_block_state_diff(
     block_number: u64,
     rpc_url: &str,
 ) -> Result<Vec<TraceResultsWithTransactionHash>> {
-    Retry::spawn(ExponentialBackoff::from_millis(50).take(2), || async {
-        let provider = MultiTransportProvider::connect(rpc_url).await?;
-        provider.trace_replay_block_transactions(block_number, &[TraceType::StateDiff]).await
-    })
-    .await
+    let namespace = trace_namespace(rpc_url).await?;
+    let provider = connect_provider(rpc_url).await?;
+
+    match namespace {
+        TraceNamespace::Parity => {
+            provider.trace_replay_block_transactions(block_number, &[TraceType::StateDiff]).await
+        }
+        TraceNamespace::Geth => provider
+            .debug_trace_block(block_number, &prestate_diff_tracer_options())
+            .await?
+            .into_iter()
+            .map(|result| match result {
+                TraceResult::Success { result, tx_hash } => {
+                    let diff = result.try_into_pre_state_frame()?.as_diff().cloned();
+                    Ok(TraceResultsWithTransactionHash {
+                        full_trace: TraceResults {
+                            output: Default::default(),
+                            state_diff: diff.map(geth::to_state_diff),
+                            trace: Vec::new(),
+                            vm_trace: None,
+                        },
+                        transaction_hash: tx_hash.unwrap_or_default(),
+                    })
+                }
+                TraceResult::Error { error, tx_hash } => {
+                    Err(eyre!("failed to trace transaction {:?}: {}", tx_hash, error))
+                }
+            })
+            .collect(),
+    }
+}
+
+/// Tracing options for Geth's `callTracer`, including logs.
+fn call_tracer_options() -> GethDebugTracingOptions {
+    GethDebugTracingOptions::call_tracer(CallConfig::default().with_log())
+}
+
+/// Tracing options for Geth's default struct logger, which only keeps the stack, as it's needed
+/// to find the callee of each call.
+fn struct_logger_options() -> GethDebugTracingOptions {
+    GethDebugTracingOptions {
+        config: GethDefaultTracingOptions::default()
+            .with_disable_storage(true)
+            .with_disable_memory(true)
+            .with_disable_return_data(true),
+        ..Default::default()
+    }
+}
+
+/// Tracing options for Geth's `prestateTracer`, in diff mode.
+pub(crate) fn prestate_diff_tracer_options() -> GethDebugTracingOptions {
+    GethDebugTracingOptions::default()
+        .with_tracer(GethDebugBuiltInTracerType::PreStateTracer.into())
+        .with_prestate_config(PreStateConfig {
+            diff_mode: Some(true),
+            disable_code: Some(true),
+            disable_storage: None,
+        })
 }
 
 /// Get the block number at which a contract was created using binary search.
@@ -191,10 +618,10 @@ pub async fn get_contract_creation_block(contract_address: Address, rpc_url: &st
     let chain_id = chain_id(rpc_url).await.unwrap_or(1);
 
     with_cache(&format!("contract_creation_block.{}.{}", chain_id, contract_address), || async {
-        let provider = MultiTransportProvider::connect(rpc_url).await?;
+        let provider = connect_provider(rpc_url).await?;
 
         // Verify the contract exists at the latest block
-        let latest_code = provider.get_code_at(contract_address).await?;
+        let latest_code = provider.get_code_at(contract_address, None).await?;
         if latest_code.is_empty() {
             bail!("contract does not exist at address {}", contract_address);
         }
@@ -210,8 +637,7 @@ pub async fn get_contract_creation_block(contract_address: Address, rpc_url: &st
             let mid = low + (high - low) / 2;
 
             // Check if contract exists at block `mid`
-            let code =
-                provider.get_code_at_block(contract_address, BlockId::Number(mid.into())).await?;
+            let code = provider.get_code_at(contract_address, Some(BlockId::number(mid))).await?;
 
             if code.is_empty() {
                 low = mid + 1;
@@ -266,6 +692,27 @@ pub mod tests {
         assert!(!bytecode.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_get_storage_at() {
+        let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| {
+            println!("RPC_URL not set, skipping test");
+            std::process::exit(0);
+        });
+
+        // slot 0 of WETH9 holds the short string "Wrapped Ether"
+        let contract_address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+        let value = get_storage_at(
+            contract_address,
+            U256::ZERO,
+            Some(BlockId::number(18_000_000)),
+            &rpc_url,
+        )
+        .await
+        .expect("get_storage_at() returned an error!");
+
+        assert!(value.to_be_bytes::<32>().starts_with(b"Wrapped Ether"));
+    }
+
     #[tokio::test]
     async fn test_get_transaction() {
         let rpc_url = std::env::var("RPC_URL").unwrap_or_else(|_| {
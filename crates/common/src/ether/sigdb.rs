@@ -0,0 +1,536 @@
+//! A local database of text signatures, keyed by their selectors.
+//!
+//! Every signature resolved from openchain or read from a provided ABI is recorded in the sqlite
+//! database `~/.bifrost/signatures.db`, so that repeated runs can resolve selectors without any
+//! network requests. Signature dumps from 4byte or openchain may be imported with
+//! `heimdall sigs import`, and the database exported with `heimdall sigs export`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use alloy::primitives::keccak256;
+use clap::{Parser, ValueEnum};
+use eyre::{bail, eyre, Result};
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+use crate::utils::{
+    io::file::{read_file, write_file},
+    strings::encode_hex,
+};
+
+/// The name of the signature database file in `~/.bifrost`.
+pub const SIGNATURE_DB_FILE: &str = "signatures.db";
+
+/// The name of the tab-separated file in `~/.bifrost` which signatures were recorded in before
+/// the sqlite database. It is imported into the database, then removed, the first time the
+/// database is opened.
+const LEGACY_SIGNATURE_DB_FILE: &str = "signatures.tsv";
+
+/// The signature database used by this process, opened on first use.
+static SIGNATURE_DB: Mutex<Option<SignatureDb>> = Mutex::new(None);
+
+/// Clap argument parser for the sigs subcommand
+#[derive(Debug, Clone, Parser)]
+#[clap(
+    about = "Manage heimdall-rs' local signature database",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall sigs <SUBCOMMAND>"
+)]
+pub struct SigsArgs {
+    /// Sigs subcommand
+    #[clap(subcommand)]
+    pub sub: Subcommands,
+}
+
+/// Arguments for the import subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct ImportArgs {
+    /// The file to import signatures from.
+    pub file: String,
+
+    /// The format of the file. Detected from its contents if not given.
+    #[clap(long, short)]
+    pub format: Option<SignatureFormat>,
+}
+
+/// Arguments for the export subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct ExportArgs {
+    /// The file to export signatures to.
+    pub file: String,
+
+    /// The format to export signatures in.
+    #[clap(long, short, default_value = "openchain")]
+    pub format: SignatureFormat,
+}
+
+/// Clap subcommand parser for sigs subcommands
+#[derive(Debug, Clone, Parser)]
+pub enum Subcommands {
+    /// Import signatures from a 4byte or openchain dump, or a list of text signatures
+    #[clap(name = "import", about = "Imports signatures into ~/.bifrost/signatures.db")]
+    Import(ImportArgs),
+
+    /// Export the signature database
+    #[clap(name = "export", about = "Exports all signatures in ~/.bifrost/signatures.db")]
+    Export(ExportArgs),
+}
+
+/// The formats signatures may be imported from and exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SignatureFormat {
+    /// A 4byte.directory API response, `{"results": [{"hex_signature", "text_signature"}]}`, or
+    /// a bare array of its results.
+    #[clap(name = "4byte")]
+    FourByte,
+    /// An openchain lookup response, `{"result": {"function": {..}, "event": {..}}}`.
+    Openchain,
+    /// One text signature per line, e.g. `transfer(address,uint256)`.
+    Text,
+    /// One tab-separated selector and text signature per line, e.g.
+    /// `0xa9059cbb\ttransfer(address,uint256)`.
+    Tsv,
+}
+
+impl SignatureFormat {
+    /// Detect the format of the given file contents.
+    pub fn detect(contents: &str) -> Self {
+        match serde_json::from_str::<Value>(contents) {
+            Ok(value) if value.get("result").is_some() => SignatureFormat::Openchain,
+            Ok(_) => SignatureFormat::FourByte,
+            Err(_) if contents.lines().any(|line| line.contains('\t')) => SignatureFormat::Tsv,
+            Err(_) => SignatureFormat::Text,
+        }
+    }
+}
+
+/// [`SignatureDb`] is an append-only set of `(selector, signature)` pairs, stored in a sqlite
+/// database with one row per pair.
+///
+/// Selectors are stored as lowercase hex without a `0x` prefix, and may be 4 bytes long for
+/// functions and errors or 32 bytes long for events.
+#[derive(Debug)]
+pub struct SignatureDb {
+    connection: Connection,
+}
+
+impl SignatureDb {
+    /// Create an empty database which isn't backed by a file.
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    /// Open the database backed by the file at `path`, which is created if it doesn't exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path)
+            .map_err(|e| eyre!("failed to open '{}': {}", path.display(), e))?;
+        Self::init(connection)
+    }
+
+    /// Create the signatures table if it doesn't exist. Its primary key indexes the rows by
+    /// selector, since every lookup is by selector.
+    fn init(connection: Connection) -> Result<Self> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS signatures (
+                selector TEXT NOT NULL,
+                signature TEXT NOT NULL,
+                PRIMARY KEY (selector, signature)
+            ) WITHOUT ROWID;",
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// Get the signatures recorded for the given selector, if any.
+    pub fn lookup(&self, selector: &str) -> Result<Option<Vec<String>>> {
+        let mut query = self.connection.prepare_cached(
+            "SELECT signature FROM signatures WHERE selector = ?1 ORDER BY signature",
+        )?;
+        let signatures = query
+            .query_map([normalize_selector(selector)], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok((!signatures.is_empty()).then_some(signatures))
+    }
+
+    /// Insert the given `(selector, signature)` pairs, returning the number which weren't
+    /// already recorded.
+    pub fn insert(&mut self, entries: impl IntoIterator<Item = (String, String)>) -> Result<usize> {
+        let transaction = self.connection.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT OR IGNORE INTO signatures (selector, signature) VALUES (?1, ?2)",
+            )?;
+            for (selector, signature) in entries {
+                let selector = normalize_selector(&selector);
+                let signature = signature.trim();
+                if selector.is_empty() || signature.is_empty() || signature.contains(['\t', '\n']) {
+                    continue;
+                }
+
+                inserted += insert.execute([selector.as_str(), signature])?;
+            }
+        }
+        transaction.commit()?;
+
+        Ok(inserted)
+    }
+
+    /// The number of `(selector, signature)` pairs recorded.
+    pub fn len(&self) -> Result<usize> {
+        let len: i64 =
+            self.connection.query_row("SELECT COUNT(*) FROM signatures", [], |row| row.get(0))?;
+        Ok(len as usize)
+    }
+
+    /// Whether no signatures are recorded.
+    pub fn is_empty(&self) -> Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// All `(selector, signature)` pairs, ordered by selector.
+    pub fn entries(&self) -> Result<Vec<(String, String)>> {
+        let mut query = self
+            .connection
+            .prepare("SELECT selector, signature FROM signatures ORDER BY selector, signature")?;
+        let entries = query
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+}
+
+/// Lowercase a hex selector and strip its `0x` prefix.
+fn normalize_selector(selector: &str) -> String {
+    selector.trim().trim_start_matches("0x").to_lowercase()
+}
+
+/// Whether `selector` is a prefix of the keccak256 hash of the text `signature`.
+fn matches_signature(selector: &str, signature: &str) -> bool {
+    let selector = normalize_selector(selector);
+    selector.len() >= 8 &&
+        encode_hex(keccak256(signature.as_bytes()).as_slice()).starts_with(&selector)
+}
+
+/// Parse `(selector, signature)` pairs from the given file contents. Pairs whose selector doesn't
+/// match their signature are skipped.
+///
+/// Text signatures are paired with both their 4-byte and 32-byte selectors, since a text
+/// signature doesn't say whether it is a function, error or event.
+///
+/// ```
+/// use heimdall_common::ether::sigdb::{parse_signatures, SignatureFormat};
+///
+/// let entries = parse_signatures("transfer(address,uint256)", SignatureFormat::Text).unwrap();
+/// assert_eq!(entries[0], ("a9059cbb".to_string(), "transfer(address,uint256)".to_string()));
+/// ```
+pub fn parse_signatures(contents: &str, format: SignatureFormat) -> Result<Vec<(String, String)>> {
+    let entries = match format {
+        SignatureFormat::Text => {
+            return Ok(contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .flat_map(|signature| {
+                    let hash = encode_hex(keccak256(signature.as_bytes()).as_slice());
+                    [(hash[..8].to_string(), signature.to_string()), (hash, signature.to_string())]
+                })
+                .collect());
+        }
+        SignatureFormat::Tsv => contents
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(selector, signature)| (selector.to_string(), signature.to_string()))
+            .collect::<Vec<_>>(),
+        SignatureFormat::FourByte => {
+            let value: Value = serde_json::from_str(contents)?;
+            let results = match value.get("results").unwrap_or(&value).as_array() {
+                Some(results) => results,
+                None => bail!("expected a 4byte response or an array of its results"),
+            };
+
+            results
+                .iter()
+                .filter_map(|result| {
+                    Some((
+                        result.get("hex_signature")?.as_str()?.to_string(),
+                        result.get("text_signature")?.as_str()?.to_string(),
+                    ))
+                })
+                .collect::<Vec<_>>()
+        }
+        SignatureFormat::Openchain => {
+            let value: Value = serde_json::from_str(contents)?;
+            let result = match value.get("result") {
+                Some(result) => result,
+                None => bail!("expected an openchain lookup response"),
+            };
+
+            ["function", "event"]
+                .iter()
+                .filter_map(|kind| result.get(kind)?.as_object())
+                .flatten()
+                .flat_map(|(selector, signatures)| {
+                    signatures
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|signature| signature.get("name")?.as_str())
+                        .map(|signature| (selector.to_string(), signature.to_string()))
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    Ok(entries
+        .into_iter()
+        .filter(|(selector, signature)| {
+            let matches = matches_signature(selector, signature);
+            if !matches {
+                debug!("skipping '{}', which does not match selector '{}'", signature, selector);
+            }
+            matches
+        })
+        .collect())
+}
+
+/// Render `(selector, signature)` pairs in the given format. Selectors are given `0x` prefixes.
+///
+/// In the openchain format, 4-byte selectors are rendered as functions and 32-byte selectors as
+/// events.
+pub fn render_signatures<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+    format: SignatureFormat,
+) -> Result<String> {
+    let entries = entries.into_iter().collect::<Vec<_>>();
+    match format {
+        SignatureFormat::Text => {
+            let signatures =
+                entries.iter().map(|(_, signature)| *signature).collect::<BTreeSet<_>>();
+            Ok(signatures.into_iter().map(|signature| format!("{signature}\n")).collect())
+        }
+        SignatureFormat::Tsv => Ok(entries
+            .iter()
+            .map(|(selector, signature)| {
+                format!("0x{}\t{signature}\n", normalize_selector(selector))
+            })
+            .collect()),
+        SignatureFormat::FourByte => {
+            let results = entries
+                .iter()
+                .enumerate()
+                .map(|(i, (selector, signature))| {
+                    json!({
+                        "id": i + 1,
+                        "text_signature": signature,
+                        "hex_signature": format!("0x{}", normalize_selector(selector)),
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(serde_json::to_string_pretty(
+                &json!({ "count": results.len(), "results": results }),
+            )?)
+        }
+        SignatureFormat::Openchain => {
+            let mut functions: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+            let mut events: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+            for (selector, signature) in entries {
+                let selector = normalize_selector(selector);
+                let kind = match selector.len() {
+                    8 => &mut functions,
+                    _ => &mut events,
+                };
+                kind.entry(format!("0x{selector}"))
+                    .or_default()
+                    .push(json!({ "name": signature, "filtered": false }));
+            }
+            Ok(serde_json::to_string_pretty(&json!({
+                "ok": true,
+                "result": { "function": functions, "event": events },
+            }))?)
+        }
+    }
+}
+
+/// The directory the signature database is stored in, `~/.bifrost`.
+#[allow(deprecated)]
+fn signature_db_dir() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".bifrost"))
+}
+
+/// Open the signature database, `~/.bifrost/signatures.db`, importing the signatures recorded in
+/// the legacy `~/.bifrost/signatures.tsv` if it exists.
+fn open_default_signature_db() -> Result<SignatureDb> {
+    let dir = signature_db_dir().ok_or_else(|| eyre!("failed to get home directory"))?;
+    let mut db = SignatureDb::open(&dir.join(SIGNATURE_DB_FILE))?;
+
+    let legacy = dir.join(LEGACY_SIGNATURE_DB_FILE);
+    if legacy.exists() {
+        let contents = std::fs::read_to_string(&legacy)
+            .map_err(|e| eyre!("failed to read '{}': {}", legacy.display(), e))?;
+        let imported = db.insert(parse_signatures(&contents, SignatureFormat::Tsv)?)?;
+        std::fs::remove_file(&legacy)?;
+        debug!("imported {} signatures from '{}'", imported, legacy.display());
+    }
+
+    Ok(db)
+}
+
+/// Run `f` against the signature database used by this process, opening it on first use. If it
+/// can't be opened, an in-memory database is used instead.
+fn with_signature_db<T>(f: impl FnOnce(&mut SignatureDb) -> Result<T>) -> Result<T> {
+    let mut db = SIGNATURE_DB.lock().unwrap_or_else(|e| e.into_inner());
+    if db.is_none() {
+        *db = Some(match open_default_signature_db() {
+            Ok(opened) => opened,
+            Err(e) => {
+                warn!("failed to open signature database: {}", e);
+                SignatureDb::in_memory()?
+            }
+        });
+    }
+    f(db.as_mut().ok_or_else(|| eyre!("signature database isn't open"))?)
+}
+
+/// Get the signatures recorded in the local signature database for the given selector, if any.
+pub fn lookup_signatures(selector: &str) -> Option<Vec<String>> {
+    with_signature_db(|db| db.lookup(selector)).unwrap_or_else(|e| {
+        debug!("failed to look up signatures for '{}': {}", selector, e);
+        None
+    })
+}
+
+/// Record the given signatures for `selector` in the local signature database.
+pub fn record_signatures(selector: &str, signatures: impl IntoIterator<Item = String>) {
+    let entries = signatures.into_iter().map(|signature| (selector.to_string(), signature));
+    if let Err(e) = with_signature_db(|db| db.insert(entries)) {
+        debug!("failed to record signatures for '{}': {}", selector, e);
+    }
+}
+
+/// Sigs subcommand handler
+pub fn sigs(args: SigsArgs) -> Result<()> {
+    match args.sub {
+        Subcommands::Import(args) => {
+            let contents = read_file(&args.file)?;
+            let format = args.format.unwrap_or_else(|| SignatureFormat::detect(&contents));
+            let entries = parse_signatures(&contents, format)?;
+            let parsed = entries.len();
+            let imported = with_signature_db(|db| db.insert(entries))?;
+
+            println!("Imported {imported} new signatures ({parsed} parsed) from '{}'.", args.file);
+        }
+        Subcommands::Export(args) => {
+            let entries = with_signature_db(|db| db.entries())?;
+            let rendered = render_signatures(
+                entries.iter().map(|(selector, signature)| (selector.as_str(), signature.as_str())),
+                args.format,
+            )?;
+            let count = entries.len();
+            write_file(&args.file, &rendered)?;
+
+            println!("Exported {count} signatures to '{}'.", args.file);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_4byte_signatures() {
+        let contents = r#"{
+            "count": 2,
+            "results": [
+                {"id": 1, "text_signature": "transfer(address,uint256)", "hex_signature": "0xa9059cbb"},
+                {"id": 2, "text_signature": "notTransfer()", "hex_signature": "0xa9059cbb"}
+            ]
+        }"#;
+        assert_eq!(SignatureFormat::detect(contents), SignatureFormat::FourByte);
+
+        let entries = parse_signatures(contents, SignatureFormat::FourByte).unwrap();
+        assert_eq!(
+            entries,
+            vec![("0xa9059cbb".to_string(), "transfer(address,uint256)".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_openchain_signatures() {
+        let contents = r#"{
+            "ok": true,
+            "result": {
+                "function": {
+                    "0x70a08231": [{"name": "balanceOf(address)", "filtered": false}],
+                    "0x12345678": null
+                },
+                "event": {
+                    "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef": [
+                        {"name": "Transfer(address,address,uint256)", "filtered": false}
+                    ]
+                }
+            }
+        }"#;
+        assert_eq!(SignatureFormat::detect(contents), SignatureFormat::Openchain);
+
+        let entries = parse_signatures(contents, SignatureFormat::Openchain).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].1, "balanceOf(address)");
+        assert_eq!(entries[1].1, "Transfer(address,address,uint256)");
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut db = SignatureDb::in_memory().unwrap();
+        let entries = parse_signatures(
+            "# erc20\ntransfer(address,uint256)\nTransfer(address,address,uint256)\n",
+            SignatureFormat::Text,
+        )
+        .unwrap();
+        assert_eq!(db.insert(entries.clone()).unwrap(), 4);
+        assert_eq!(db.insert(entries).unwrap(), 0);
+        assert_eq!(db.lookup("0xA9059CBB").unwrap().unwrap(), vec!["transfer(address,uint256)"]);
+
+        let entries = db.entries().unwrap();
+        for format in [SignatureFormat::FourByte, SignatureFormat::Openchain, SignatureFormat::Tsv]
+        {
+            let rendered = render_signatures(
+                entries.iter().map(|(selector, signature)| (selector.as_str(), signature.as_str())),
+                format,
+            )
+            .unwrap();
+            assert_eq!(SignatureFormat::detect(&rendered), format);
+
+            let mut imported = SignatureDb::in_memory().unwrap();
+            imported.insert(parse_signatures(&rendered, format).unwrap()).unwrap();
+            assert_eq!(imported.entries().unwrap(), entries);
+        }
+    }
+
+    #[test]
+    fn test_signature_db_persists() {
+        let path = std::env::temp_dir().join(format!("heimdall-sigdb-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut db = SignatureDb::open(&path).unwrap();
+        assert!(db.is_empty().unwrap());
+        db.insert([("0x70a08231".to_string(), "balanceOf(address)".to_string())]).unwrap();
+
+        let reopened = SignatureDb::open(&path).unwrap();
+        assert_eq!(reopened.lookup("70a08231").unwrap().unwrap(), vec!["balanceOf(address)"]);
+        assert!(reopened.lookup("a9059cbb").unwrap().is_none());
+        drop(reopened);
+        drop(db);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
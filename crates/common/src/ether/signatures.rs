@@ -1,7 +1,13 @@
 //! This module contains the logic for resolving signatures from
 //! 4-byte function selector or a 32-byte event selector.
 
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        RwLock,
+    },
+};
 
 use alloy_dyn_abi::{DynSolType, DynSolValue};
 use alloy_json_abi::JsonAbi;
@@ -10,13 +16,14 @@ use async_trait::async_trait;
 use crate::{
     ether::types::{dyn_sol_types_to_strings, inputs_to_abi_format, parse_function_parameters},
     utils::{
-        http::get_json_from_url,
         io::{logging::TraceFactory, types::display},
+        offline::is_offline,
     },
 };
-use eyre::{OptionExt, Result};
-use heimdall_cache::{store_cache, with_cache};
+use eyre::{eyre, OptionExt, Result};
+use heimdall_cache::{read_cache, store_cache, with_cache};
 use serde::{
+    de::DeserializeOwned,
     ser::{SerializeMap, Serializer},
     Deserialize, Serialize,
 };
@@ -142,6 +149,126 @@ impl Serialize for ResolvedLog {
         map.end()
     }
 }
+/// The openchain signature database's lookup API, which selectors are resolved with by default.
+pub const OPENCHAIN_LOOKUP_URL: &str = "https://api.openchain.xyz/signature-database/v1/lookup";
+
+/// The lookup API which selectors are resolved with, if it was set with
+/// [`set_signature_resolver_url`].
+static SIGNATURE_RESOLVER_URL: RwLock<Option<String>> = RwLock::new(None);
+
+/// Resolve all subsequent selectors with the lookup API at `url`, or with openchain's if `None`
+/// is given. The API must have the same shape as [openchain's](https://openchain.xyz/signatures),
+/// i.e. `GET {url}?function=0x{selector}` and `GET {url}?event=0x{selector}` must return
+/// `{"result": {"function" | "event": {"0x{selector}": [{"name": "{signature}"}]}}}`.
+pub fn set_signature_resolver_url(url: Option<String>) {
+    *SIGNATURE_RESOLVER_URL.write().unwrap_or_else(|e| e.into_inner()) = url;
+}
+
+/// The lookup API which selectors are resolved with, see [`set_signature_resolver_url`].
+pub fn signature_resolver_url() -> String {
+    SIGNATURE_RESOLVER_URL
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| OPENCHAIN_LOOKUP_URL.to_string())
+}
+
+/// The url to look up the `kind` (either `function` or `event`) signatures of the given selector
+/// at, on the lookup API at `url`.
+fn lookup_url(url: &str, kind: &str, selector: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{url}{separator}filter=false&{kind}=0x{selector}")
+}
+
+/// The number of selectors which are resolved concurrently by default.
+pub const DEFAULT_SIGNATURE_CONCURRENCY: usize = 16;
+
+/// The number of selectors which are resolved concurrently, see [`set_signature_concurrency`].
+static SIGNATURE_CONCURRENCY: AtomicUsize = AtomicUsize::new(DEFAULT_SIGNATURE_CONCURRENCY);
+
+/// Resolve at most `limit` selectors concurrently from now on. A limit of 0 is treated as 1.
+pub fn set_signature_concurrency(limit: usize) {
+    SIGNATURE_CONCURRENCY.store(limit.max(1), Ordering::Relaxed);
+}
+
+/// The number of selectors which are resolved concurrently, see [`set_signature_concurrency`].
+pub fn signature_concurrency() -> usize {
+    SIGNATURE_CONCURRENCY.load(Ordering::Relaxed)
+}
+
+/// Signature lookups over http.
+#[cfg(not(target_arch = "wasm32"))]
+mod lookup {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use eyre::{eyre, Result};
+    use futures::future::{BoxFuture, FutureExt, Shared};
+    use lazy_static::lazy_static;
+    use serde_json::Value;
+
+    use crate::utils::http::get_json_from_url;
+
+    /// A signature lookup which may be awaited by several resolutions at once.
+    type SharedLookup = Shared<BoxFuture<'static, Result<Option<Value>, String>>>;
+
+    lazy_static! {
+        /// The signature lookups currently in flight, keyed by url.
+        static ref IN_FLIGHT_LOOKUPS: Mutex<HashMap<String, SharedLookup>> = Mutex::new(HashMap::new());
+    }
+
+    /// GET the given signature lookup url. Identical concurrent lookups share a single request.
+    pub(super) async fn fetch_lookup(url: String) -> Result<Option<Value>> {
+        let lookup = IN_FLIGHT_LOOKUPS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(url.clone())
+            .or_insert_with(|| {
+                async move {
+                    let response = get_json_from_url(&url, 10).await.map_err(|e| e.to_string());
+                    IN_FLIGHT_LOOKUPS.lock().unwrap_or_else(|e| e.into_inner()).remove(&url);
+                    response
+                }
+                .boxed()
+                .shared()
+            })
+            .clone();
+
+        lookup.await.map_err(|e| eyre!("signature lookup failed: {}", e))
+    }
+}
+
+/// Signatures can't be looked up over http on wasm, where the caller resolves selectors instead.
+#[cfg(target_arch = "wasm32")]
+mod lookup {
+    use eyre::{eyre, Result};
+    use serde_json::Value;
+
+    pub(super) async fn fetch_lookup(_url: String) -> Result<Option<Value>> {
+        Err(eyre!("signature lookups aren't available on wasm"))
+    }
+}
+
+use lookup::fetch_lookup;
+
+/// The local signature database isn't available on wasm, where no signatures are recorded.
+#[cfg(target_arch = "wasm32")]
+mod local_signature_db {
+    pub(super) fn lookup_signatures(_selector: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    pub(super) fn record_signatures(
+        _selector: &str,
+        _signatures: impl IntoIterator<Item = String>,
+    ) {
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ether::sigdb::{lookup_signatures, record_signatures};
+#[cfg(target_arch = "wasm32")]
+use local_signature_db::{lookup_signatures, record_signatures};
+
 /// A trait for resolving a selector into a vector of [`ResolvedFunction`]s, [`ResolvedError`]s, or
 #[async_trait]
 pub trait ResolveSelector {
@@ -152,9 +279,106 @@ pub trait ResolveSelector {
         Self: Sized;
 }
 
+/// Read the previously resolved signatures for the given selector from the cache, without making
+/// any network requests.
+fn read_cached_signatures<T: 'static + DeserializeOwned>(selector: &str) -> Option<Vec<T>> {
+    read_cache::<Option<Vec<T>>>(&format!("selector.{selector}")).ok().flatten().flatten()
+}
+
+/// A resolved signature which can be parsed from its text signature.
+pub trait FromTextSignature: Sized {
+    /// Build the resolved signature from its name, text signature and inputs.
+    fn from_parts(name: String, signature: String, inputs: Vec<String>) -> Self;
+
+    /// Parse the resolved signature from its text signature, e.g. `transfer(address,uint256)`.
+    fn from_text_signature(text_signature: &str) -> Option<Self> {
+        let (name, _) = text_signature.split_once('(')?;
+        let inputs = parse_function_parameters(text_signature).ok()?;
+        Some(Self::from_parts(
+            name.to_string(),
+            text_signature.to_string(),
+            dyn_sol_types_to_strings(&inputs),
+        ))
+    }
+}
+
+impl FromTextSignature for ResolvedFunction {
+    fn from_parts(name: String, signature: String, inputs: Vec<String>) -> Self {
+        ResolvedFunction { name, signature, inputs, decoded_inputs: None }
+    }
+}
+
+impl FromTextSignature for ResolvedError {
+    fn from_parts(name: String, signature: String, inputs: Vec<String>) -> Self {
+        ResolvedError { name, signature, inputs }
+    }
+}
+
+impl FromTextSignature for ResolvedLog {
+    fn from_parts(name: String, signature: String, inputs: Vec<String>) -> Self {
+        ResolvedLog { name, signature, inputs }
+    }
+}
+
+/// The errors built into solidity, keyed by their selector, which are never looked up.
+pub const BUILTIN_ERRORS: [(&str, &str); 2] =
+    [("08c379a0", "Error(string)"), ("4e487b71", "Panic(uint256)")];
+
+/// Get the builtin error with the given selector, i.e. `Error(string)` or `Panic(uint256)`.
+///
+/// ```
+/// use heimdall_common::ether::signatures::builtin_error;
+///
+/// assert_eq!(builtin_error("0x4e487b71").unwrap().signature, "Panic(uint256)");
+/// assert!(builtin_error("a9059cbb").is_none());
+/// ```
+pub fn builtin_error(selector: &str) -> Option<ResolvedError> {
+    let selector = selector.strip_prefix("0x").unwrap_or(selector);
+    BUILTIN_ERRORS
+        .iter()
+        .find(|(builtin, _)| *builtin == selector)
+        .and_then(|(_, signature)| ResolvedError::from_text_signature(signature))
+}
+
+/// Describe the given `Panic(uint256)` code, see
+/// <https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require>.
+pub fn panic_reason(code: u64) -> Option<&'static str> {
+    Some(match code {
+        0x00 => "generic compiler inserted panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic underflow or overflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on an empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to an uninitialized internal function",
+        _ => return None,
+    })
+}
+
+/// Read the signatures for the given selector from the local signature database, see
+/// [`crate::ether::sigdb`].
+fn local_signatures<T: FromTextSignature>(selector: &str) -> Option<Vec<T>> {
+    let signatures = lookup_signatures(selector)?
+        .iter()
+        .filter_map(|signature| T::from_text_signature(signature))
+        .collect::<Vec<_>>();
+    (!signatures.is_empty()).then_some(signatures)
+}
+
 #[async_trait]
 impl ResolveSelector for ResolvedError {
     async fn resolve(selector: &str) -> Result<Option<Vec<Self>>> {
+        if let Some(builtin) = builtin_error(selector) {
+            return Ok(Some(vec![builtin]));
+        }
+
+        if is_offline() {
+            return Ok(read_cached_signatures(selector).or_else(|| local_signatures(selector)));
+        }
+
         with_cache(&format!("selector.{selector}"), || async {
             // normalize selector
             let selector = match selector.strip_prefix("0x") {
@@ -162,21 +386,21 @@ impl ResolveSelector for ResolvedError {
                 None => selector,
             };
 
+            // prefer signatures which were previously resolved or imported
+            if let Some(signatures) = local_signatures(selector) {
+                return Ok(Some(signatures));
+            }
+
             trace!("resolving error selector {}", &selector);
 
-            // get function possibilities from openchain
-            let signatures = match get_json_from_url(
-                &format!(
-                    "https://api.openchain.xyz/signature-database/v1/lookup?filter=false&function=0x{}",
-                    &selector
-                ),
-                10,
-            )
-            .await?
-            {
-                Some(signatures) => signatures,
-                None => return Ok(None),
-            };
+            // get function possibilities from the signature resolver
+            let signatures =
+                match fetch_lookup(lookup_url(&signature_resolver_url(), "function", selector))
+                    .await?
+                {
+                    Some(signatures) => signatures,
+                    None => return Ok(None),
+                };
 
             // convert the serde value into a vec of possible functions
             let results = signatures
@@ -185,37 +409,16 @@ impl ResolveSelector for ResolvedError {
                 .and_then(|function| function.get(format!("0x{selector}")))
                 .and_then(|item| item.as_array())
                 .map(|array| array.to_vec())
-                .ok_or_eyre("error parsing signatures from openchain")?;
+                .ok_or_eyre("error parsing signatures from the signature resolver")?;
 
             trace!("found {} possible functions for selector: {}", &results.len(), &selector);
 
-            let mut signature_list: Vec<ResolvedError> = Vec::new();
-
-            for signature in results {
-                // get the function text signature and unwrap it into a string
-                let text_signature = match signature.get("name") {
-                    Some(text_signature) => text_signature.to_string().replace('"', ""),
-                    None => continue,
-                };
-
-                // safely split the text signature into name and inputs
-                let function_parts = match text_signature.split_once('(') {
-                    Some(function_parts) => function_parts,
-                    None => continue,
-                };
-
-                // Parse the inputs using parse_function_parameters
-                let parsed_inputs = match parse_function_parameters(&text_signature) {
-                    Ok(inputs) => inputs,
-                    Err(_) => continue,
-                };
-
-                signature_list.push(ResolvedError {
-                    name: function_parts.0.to_string(),
-                    signature: text_signature.to_string(),
-                    inputs: dyn_sol_types_to_strings(&parsed_inputs),
-                });
-            }
+            let signature_list = results
+                .iter()
+                .filter_map(|signature| signature.get("name")?.as_str())
+                .filter_map(ResolvedError::from_text_signature)
+                .collect::<Vec<_>>();
+            record_signatures(selector, signature_list.iter().map(|s| s.signature.clone()));
 
             Ok(match signature_list.len() {
                 0 => None,
@@ -229,6 +432,10 @@ impl ResolveSelector for ResolvedError {
 #[async_trait]
 impl ResolveSelector for ResolvedLog {
     async fn resolve(selector: &str) -> Result<Option<Vec<Self>>> {
+        if is_offline() {
+            return Ok(read_cached_signatures(selector).or_else(|| local_signatures(selector)));
+        }
+
         with_cache(&format!("selector.{selector}"), || async {
             // normalize selector
             let selector = match selector.strip_prefix("0x") {
@@ -236,21 +443,20 @@ impl ResolveSelector for ResolvedLog {
                 None => selector,
             };
 
+            // prefer signatures which were previously resolved or imported
+            if let Some(signatures) = local_signatures(selector) {
+                return Ok(Some(signatures));
+            }
+
             trace!("resolving event selector {}", &selector);
 
-            // get function possibilities from openchain
-            let signatures = match get_json_from_url(
-                &format!(
-                "https://api.openchain.xyz/signature-database/v1/lookup?filter=false&event=0x{}",
-                &selector
-            ),
-                10,
-            )
-            .await?
-            {
-                Some(signatures) => signatures,
-                None => return Ok(None),
-            };
+            // get function possibilities from the signature resolver
+            let signatures =
+                match fetch_lookup(lookup_url(&signature_resolver_url(), "event", selector)).await?
+                {
+                    Some(signatures) => signatures,
+                    None => return Ok(None),
+                };
 
             // convert the serde value into a vec of possible functions
             let results = signatures
@@ -259,37 +465,16 @@ impl ResolveSelector for ResolvedLog {
                 .and_then(|function| function.get(format!("0x{selector}")))
                 .and_then(|item| item.as_array())
                 .map(|array| array.to_vec())
-                .ok_or_eyre("error parsing signatures from openchain")?;
+                .ok_or_eyre("error parsing signatures from the signature resolver")?;
 
             trace!("found {} possible functions for selector: {}", &results.len(), &selector);
 
-            let mut signature_list: Vec<ResolvedLog> = Vec::new();
-
-            for signature in results {
-                // get the function text signature and unwrap it into a string
-                let text_signature = match signature.get("name") {
-                    Some(text_signature) => text_signature.to_string().replace('"', ""),
-                    None => continue,
-                };
-
-                // safely split the text signature into name and inputs
-                let function_parts = match text_signature.split_once('(') {
-                    Some(function_parts) => function_parts,
-                    None => continue,
-                };
-
-                // Parse the inputs using parse_function_parameters
-                let parsed_inputs = match parse_function_parameters(&text_signature) {
-                    Ok(inputs) => inputs,
-                    Err(_) => continue,
-                };
-
-                signature_list.push(ResolvedLog {
-                    name: function_parts.0.to_string(),
-                    signature: text_signature.to_string(),
-                    inputs: dyn_sol_types_to_strings(&parsed_inputs),
-                });
-            }
+            let signature_list = results
+                .iter()
+                .filter_map(|signature| signature.get("name")?.as_str())
+                .filter_map(ResolvedLog::from_text_signature)
+                .collect::<Vec<_>>();
+            record_signatures(selector, signature_list.iter().map(|s| s.signature.clone()));
 
             Ok(match signature_list.len() {
                 0 => None,
@@ -303,6 +488,10 @@ impl ResolveSelector for ResolvedLog {
 #[async_trait]
 impl ResolveSelector for ResolvedFunction {
     async fn resolve(selector: &str) -> Result<Option<Vec<Self>>> {
+        if is_offline() {
+            return Ok(read_cached_signatures(selector).or_else(|| local_signatures(selector)));
+        }
+
         with_cache(&format!("selector.{selector}"), || async {
             // normalize selector
             let selector = match selector.strip_prefix("0x") {
@@ -310,21 +499,21 @@ impl ResolveSelector for ResolvedFunction {
                 None => selector,
             };
 
+            // prefer signatures which were previously resolved or imported
+            if let Some(signatures) = local_signatures(selector) {
+                return Ok(Some(signatures));
+            }
+
             trace!("resolving function selector {}", &selector);
 
-            // get function possibilities from openchain
-            let signatures = match get_json_from_url(
-                &format!(
-                "https://api.openchain.xyz/signature-database/v1/lookup?filter=false&function=0x{}",
-                &selector
-            ),
-                10,
-            )
-            .await?
-            {
-                Some(signatures) => signatures,
-                None => return Ok(None),
-            };
+            // get function possibilities from the signature resolver
+            let signatures =
+                match fetch_lookup(lookup_url(&signature_resolver_url(), "function", selector))
+                    .await?
+                {
+                    Some(signatures) => signatures,
+                    None => return Ok(None),
+                };
 
             // convert the serde value into a vec of possible functions
             let results = signatures
@@ -333,38 +522,16 @@ impl ResolveSelector for ResolvedFunction {
                 .and_then(|function| function.get(format!("0x{selector}")))
                 .and_then(|item| item.as_array())
                 .map(|array| array.to_vec())
-                .ok_or_eyre("error parsing signatures from openchain")?;
+                .ok_or_eyre("error parsing signatures from the signature resolver")?;
 
             trace!("found {} possible functions for selector: {}", &results.len(), &selector);
 
-            let mut signature_list: Vec<ResolvedFunction> = Vec::new();
-
-            for signature in results {
-                // get the function text signature and unwrap it into a string
-                let text_signature = match signature.get("name") {
-                    Some(text_signature) => text_signature.to_string().replace('"', ""),
-                    None => continue,
-                };
-
-                // safely split the text signature into name and inputs
-                let function_parts = match text_signature.split_once('(') {
-                    Some(function_parts) => function_parts,
-                    None => continue,
-                };
-
-                // Parse the inputs using parse_function_parameters
-                let parsed_inputs = match parse_function_parameters(&text_signature) {
-                    Ok(inputs) => inputs,
-                    Err(_) => continue,
-                };
-
-                signature_list.push(ResolvedFunction {
-                    name: function_parts.0.to_string(),
-                    signature: text_signature.to_string(),
-                    inputs: dyn_sol_types_to_strings(&parsed_inputs),
-                    decoded_inputs: None,
-                });
-            }
+            let signature_list = results
+                .iter()
+                .filter_map(|signature| signature.get("name")?.as_str())
+                .filter_map(ResolvedFunction::from_text_signature)
+                .collect::<Vec<_>>();
+            record_signatures(selector, signature_list.iter().map(|s| s.signature.clone()));
 
             Ok(match signature_list.len() {
                 0 => None,
@@ -383,6 +550,22 @@ pub fn cache_signatures_from_abi(path: PathBuf) -> Result<()> {
 
     debug!("caching signatures from abi: {}", path.display());
 
+    cache_signatures_from_json_abi(&json_abi);
+    Ok(())
+}
+
+/// Caches the signatures of each of the ABI files at the given paths, in order, so that the
+/// signatures of later ABIs take precedence over those of earlier ones.
+pub fn cache_signatures_from_abis(paths: &[String]) -> Result<()> {
+    for path in paths {
+        cache_signatures_from_abi(path.into()).map_err(|e| eyre!("{}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+/// Parses all [`ResolvedFunction`]s, [`ResolvedError`]s, and [`ResolvedLog`]s from the given ABI
+/// and saves them to the cache, so that they take precedence over any other resolved signatures.
+pub fn cache_signatures_from_json_abi(json_abi: &JsonAbi) {
     json_abi.functions().for_each(|function| {
         let selector = function.selector().to_string().trim_start_matches("0x").to_string();
         let signature = function.signature();
@@ -403,6 +586,7 @@ pub fn cache_signatures_from_abi(path: PathBuf) -> Result<()> {
             decoded_inputs: None,
         };
 
+        record_signatures(&selector, [resolved_function.signature.clone()]);
         store_cache(&format!("selector.{selector}"), Some(vec![resolved_function]), None).ok();
     });
     json_abi.events().for_each(|event| {
@@ -420,6 +604,7 @@ pub fn cache_signatures_from_abi(path: PathBuf) -> Result<()> {
 
         let resolved_log = ResolvedLog { name: event.name.clone(), signature, inputs };
 
+        record_signatures(&selector, [resolved_log.signature.clone()]);
         store_cache(&format!("selector.{selector}"), Some(vec![resolved_log]), None).ok();
     });
     json_abi.errors().for_each(|error| {
@@ -437,6 +622,7 @@ pub fn cache_signatures_from_abi(path: PathBuf) -> Result<()> {
 
         let resolved_error = ResolvedError { name: error.name.clone(), signature, inputs };
 
+        record_signatures(&selector, [resolved_error.signature.clone()]);
         store_cache(&format!("selector.{selector}"), Some(vec![resolved_error]), None).ok();
     });
 
@@ -446,8 +632,6 @@ pub fn cache_signatures_from_abi(path: PathBuf) -> Result<()> {
         json_abi.events().count(),
         json_abi.errors().count(),
     );
-
-    Ok(())
 }
 
 /// Heuristic to score a function signature based on its spamminess.
@@ -549,12 +733,43 @@ mod tests {
 
     use crate::ether::{
         signatures::{
-            dyn_sol_types_to_strings, score_signature, ResolveSelector, ResolvedError,
-            ResolvedFunction, ResolvedLog,
+            dyn_sol_types_to_strings, lookup_url, panic_reason, score_signature,
+            set_signature_concurrency, signature_concurrency, ResolveSelector, ResolvedError,
+            ResolvedFunction, ResolvedLog, DEFAULT_SIGNATURE_CONCURRENCY, OPENCHAIN_LOOKUP_URL,
         },
         types::parse_function_parameters,
     };
 
+    #[test]
+    fn test_lookup_url() {
+        assert_eq!(
+            lookup_url(OPENCHAIN_LOOKUP_URL, "function", "a9059cbb"),
+            "https://api.openchain.xyz/signature-database/v1/lookup?filter=false&function=0xa9059cbb"
+        );
+        assert_eq!(
+            lookup_url("https://sigs.internal/lookup?token=abc", "event", "ddf252ad"),
+            "https://sigs.internal/lookup?token=abc&filter=false&event=0xddf252ad"
+        );
+    }
+
+    #[test]
+    fn test_signature_concurrency_is_at_least_one() {
+        set_signature_concurrency(0);
+        assert_eq!(signature_concurrency(), 1);
+        set_signature_concurrency(DEFAULT_SIGNATURE_CONCURRENCY);
+        assert_eq!(signature_concurrency(), DEFAULT_SIGNATURE_CONCURRENCY);
+    }
+
+    #[tokio::test]
+    async fn resolve_builtin_error_signatures() {
+        let result = ResolvedError::resolve("08c379a0").await.unwrap().unwrap();
+        assert_eq!(result[0].signature, "Error(string)");
+        let result = ResolvedError::resolve("0x4e487b71").await.unwrap().unwrap();
+        assert_eq!(result[0].signature, "Panic(uint256)");
+        assert_eq!(panic_reason(0x11), Some("arithmetic underflow or overflow"));
+        assert_eq!(panic_reason(0x99), None);
+    }
+
     #[tokio::test]
     async fn resolve_function_signature_nominal() {
         let signature = String::from("095ea7b3");
@@ -710,7 +925,7 @@ mod tests {
                                           // num_params = 1, num_dyn_params = 2 + 1 = 3
                                           // Without saturating_sub: 1 - 3 would underflow
                                           // With saturating_sub: 1.saturating_sub(3) = 0
-        let score = score_signature(&signature, Some(0));
+        let score = score_signature(signature, Some(0));
         // Should not panic, should return a valid score (greater than 0)
         assert!(score > 0);
     }
@@ -722,7 +937,7 @@ mod tests {
                                   // num_params = 1, num_dyn_params = 0, num_static_params = 1
                                   // If num_words = 10, then num_words - num_static_params = 9
                                   // This would reduce score by 90
-        let score = score_signature(&signature, Some(10));
+        let score = score_signature(signature, Some(10));
         // Should not panic and should be reduced appropriately
         // Initial score calculation:
         // - Start: 1000
@@ -742,7 +957,7 @@ mod tests {
         // num_dyn_params = bytes(3) + string(1) + [(1) = 5
         // Without saturating_sub: 3 - 5 would underflow
         // With saturating_sub: 3.saturating_sub(5) = 0
-        let score = score_signature(&signature, Some(2));
+        let score = score_signature(signature, Some(2));
         // Should not panic and should return a valid score
         // The score should be positive since we add 10 per param
         assert!(score > 0);
@@ -0,0 +1,144 @@
+//! Sourcify utilities for fetching the verified source and ABI of contracts.
+
+use std::collections::BTreeMap;
+
+use alloy::primitives::Address;
+use alloy_json_abi::JsonAbi;
+use eyre::{eyre, Result};
+use heimdall_cache::{read_cache, store_cache};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{ether::rpc::chain_id, utils::http::get_json_from_url};
+
+/// The Sourcify server queried for verified contracts.
+pub const SOURCIFY_API_URL: &str = "https://sourcify.dev/server";
+
+/// How closely a contract's verified source matches its deployed bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchType {
+    /// The source and its metadata match the deployed bytecode exactly.
+    Full,
+    /// The source compiles to the deployed bytecode, but its metadata (e.g. comments or variable
+    /// names) differs.
+    Partial,
+}
+
+impl std::fmt::Display for MatchType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchType::Full => write!(f, "full"),
+            MatchType::Partial => write!(f, "partial"),
+        }
+    }
+}
+
+/// A contract verified on Sourcify.
+#[derive(Debug, Clone)]
+pub struct VerifiedContract {
+    /// How closely the verified source matches the deployed bytecode.
+    pub match_type: MatchType,
+    /// The ABI of the verified contract.
+    pub abi: JsonAbi,
+    /// The verified source files, keyed by their path.
+    pub sources: BTreeMap<String, String>,
+}
+
+/// Parse a Sourcify `/v2/contract` response, returning `None` if the contract isn't verified.
+fn parse_verified_contract(response: &Value) -> Result<Option<VerifiedContract>> {
+    let match_type = match response.get("match").and_then(Value::as_str) {
+        Some("exact_match") => MatchType::Full,
+        Some("match") => MatchType::Partial,
+        _ => return Ok(None),
+    };
+
+    let abi = response.get("abi").ok_or_else(|| eyre!("sourcify response has no abi"))?;
+    let abi: JsonAbi = serde_json::from_value(abi.clone())
+        .map_err(|e| eyre!("failed to parse abi from sourcify: {}", e))?;
+
+    let sources = response
+        .get("sources")
+        .and_then(Value::as_object)
+        .map(|sources| {
+            sources
+                .iter()
+                .filter_map(|(path, source)| {
+                    Some((path.to_string(), source.get("content")?.as_str()?.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(VerifiedContract { match_type, abi, sources }))
+}
+
+/// Fetch the verified source and ABI of the contract at `address` on the given chain from
+/// Sourcify, returning `None` if it isn't verified. Both full and partial matches are returned.
+///
+/// Verified contracts are cached, while unverified ones are looked up again on the next call,
+/// since they may have been verified in the meantime.
+pub async fn get_verified_contract(
+    address: Address,
+    chain_id: u64,
+) -> Result<Option<VerifiedContract>> {
+    let cache_key = format!("sourcify.{chain_id}.{address}");
+    if let Some(response) = read_cache::<String>(&cache_key).ok().flatten() {
+        debug!("found cached sourcify response for {}", address);
+        return parse_verified_contract(&serde_json::from_str(&response)?);
+    }
+
+    let url = format!("{SOURCIFY_API_URL}/v2/contract/{chain_id}/{address}?fields=abi,sources");
+    let Some(response) = get_json_from_url(&url, 10).await? else {
+        return Ok(None);
+    };
+
+    let verified = parse_verified_contract(&response)?;
+    if verified.is_some() {
+        store_cache(&cache_key, response.to_string(), None).ok();
+    }
+    Ok(verified)
+}
+
+/// Fetch the verified source and ABI of the contract at `address` on the chain served by
+/// `rpc_url`, see [`get_verified_contract`].
+pub async fn get_verified_contract_from_rpc(
+    address: Address,
+    rpc_url: &str,
+) -> Result<Option<VerifiedContract>> {
+    let chain_id = chain_id(rpc_url).await?;
+    get_verified_contract(address, chain_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_verified_contract() {
+        let response = json!({
+            "match": "match",
+            "chainId": "1",
+            "address": "0x0000000000000000000000000000000000000001",
+            "abi": [{
+                "type": "function",
+                "name": "owner",
+                "inputs": [],
+                "outputs": [{"name": "", "type": "address", "internalType": "address"}],
+                "stateMutability": "view"
+            }],
+            "sources": {"contracts/Owned.sol": {"content": "contract Owned {}"}}
+        });
+
+        let verified = parse_verified_contract(&response).unwrap().unwrap();
+        assert_eq!(verified.match_type, MatchType::Partial);
+        assert_eq!(verified.abi.functions().next().unwrap().signature(), "owner()");
+        assert_eq!(verified.sources["contracts/Owned.sol"], "contract Owned {}");
+    }
+
+    #[test]
+    fn test_parse_unverified_contract() {
+        let response = json!({ "customCode": "not_found", "message": "Contract not found" });
+        assert!(parse_verified_contract(&response).unwrap().is_none());
+    }
+}
@@ -0,0 +1,112 @@
+//! A websocket connector which reconnects, with backoff, when its connection drops.
+
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use alloy::{
+    providers::WsConnect,
+    pubsub::{ConnectionHandle, PubSubConnect},
+    transports::TransportResult,
+};
+use tracing::{info, warn};
+
+use crate::ether::provider::RetryPolicy;
+
+/// [`ReconnectingWsConnect`] wraps a [`WsConnect`], reconnecting according to a [`RetryPolicy`]
+/// when the websocket connection drops.
+///
+/// Once reconnected, the pubsub service transparently reissues in-flight requests and resumes
+/// active subscriptions. If the connection can't be reestablished within
+/// [`RetryPolicy::max_retries`] attempts, the connection is closed and requests fail over to the
+/// provider's next endpoint, if any.
+#[derive(Clone, Debug)]
+pub struct ReconnectingWsConnect {
+    inner: WsConnect,
+    policy: RetryPolicy,
+    attempts: Arc<AtomicU32>,
+}
+
+impl ReconnectingWsConnect {
+    /// Create a new connector, which reconnects according to the given [`RetryPolicy`].
+    pub fn new(connect: WsConnect, policy: RetryPolicy) -> Self {
+        // the pubsub service retries at a fixed interval, so the backoff is applied in
+        // `try_reconnect` instead
+        let inner =
+            connect.with_max_retries(policy.max_retries.max(1)).with_retry_interval(Duration::ZERO);
+        Self { inner, policy, attempts: Arc::new(AtomicU32::new(0)) }
+    }
+
+    /// Get the url of the websocket endpoint.
+    pub fn url(&self) -> &str {
+        self.inner.url()
+    }
+
+    /// The number of consecutive failed reconnection attempts.
+    pub fn failed_attempts(&self) -> u32 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+}
+
+impl PubSubConnect for ReconnectingWsConnect {
+    fn is_local(&self) -> bool {
+        self.inner.is_local()
+    }
+
+    async fn connect(&self) -> TransportResult<ConnectionHandle> {
+        self.inner.connect().await
+    }
+
+    async fn try_reconnect(&self) -> TransportResult<ConnectionHandle> {
+        let attempt = self.attempts.load(Ordering::Relaxed);
+        if attempt == 0 {
+            warn!("websocket connection to '{}' dropped, reconnecting", self.url());
+        }
+        tokio::time::sleep(self.policy.delay(attempt)).await;
+
+        match self.inner.connect().await {
+            Ok(handle) => {
+                self.attempts.store(0, Ordering::Relaxed);
+                info!(
+                    "reconnected to websocket endpoint '{}' after {} attempt(s)",
+                    self.url(),
+                    attempt + 1
+                );
+                Ok(handle)
+            }
+            Err(e) => {
+                self.attempts.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "reconnection attempt {}/{} to websocket endpoint '{}' failed: {}",
+                    attempt + 1,
+                    self.policy.max_retries.max(1),
+                    self.url(),
+                    e
+                );
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_reconnects_are_counted() {
+        let policy = RetryPolicy::reconnect()
+            .with_initial_backoff(Duration::from_millis(1))
+            .with_max_backoff(Duration::from_millis(1))
+            .with_jitter(false);
+        let connect = ReconnectingWsConnect::new(WsConnect::new("ws://127.0.0.1:1"), policy);
+
+        assert!(connect.try_reconnect().await.is_err());
+        assert!(connect.try_reconnect().await.is_err());
+        assert_eq!(connect.failed_attempts(), 2);
+    }
+}
@@ -11,6 +11,7 @@ pub mod constants;
 pub mod ether;
 
 /// External resources and API integrations, such as OpenAI and Transpose.
+#[cfg(not(target_arch = "wasm32"))]
 pub mod resources;
 
 /// General utility functions and types for common tasks.
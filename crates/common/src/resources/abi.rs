@@ -0,0 +1,128 @@
+use alloy::primitives::Address;
+use alloy_json_abi::JsonAbi;
+use eyre::{eyre, Result};
+use heimdall_cache::{read_cache, store_cache};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    ether::{chains::chain_by_id, rpc::chain_id},
+    utils::http::get_json_from_url,
+};
+
+/// Parse the response of an Etherscan-compatible `getabi` request, returning `None` if the
+/// contract isn't verified.
+fn parse_getabi_response(response: &Value) -> Result<Option<JsonAbi>> {
+    let result = response.get("result").and_then(Value::as_str).unwrap_or_default();
+    if response.get("status").and_then(Value::as_str) != Some("1") {
+        if result.to_lowercase().contains("not verified") {
+            return Ok(None);
+        }
+        return Err(eyre!("explorer API returned an error: {}", result));
+    }
+
+    JsonAbi::from_json_str(result)
+        .map(Some)
+        .map_err(|e| eyre!("failed to parse abi from explorer API: {}", e))
+}
+
+/// Fetch the verified ABI of the contract at `address` on the given chain from an
+/// Etherscan-compatible block explorer API, returning `None` if it isn't verified.
+///
+/// If `api_url` is empty, the chain's default explorer API is used, which requires an Etherscan
+/// API key. Otherwise, `api_url` may be any Etherscan-compatible API, such as a Blockscout
+/// instance's `/api`, which may not require one.
+///
+/// ```
+/// use alloy::primitives::Address;
+/// use heimdall_common::resources::abi::get_verified_abi;
+///
+/// let address: Address = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap();
+///
+/// // let abi = get_verified_abi(address, 1, "", "YOUR_API_KEY").await;
+/// ```
+pub async fn get_verified_abi(
+    address: Address,
+    chain_id: u64,
+    api_url: &str,
+    api_key: &str,
+) -> Result<Option<JsonAbi>> {
+    let api_url = match api_url.is_empty() {
+        true if api_key.is_empty() => {
+            debug!("no etherscan api key is configured, skipping explorer abi lookup");
+            return Ok(None);
+        }
+        true => match chain_by_id(chain_id).and_then(|chain| chain.explorer_api_url) {
+            Some(api_url) => api_url,
+            None => return Ok(None),
+        },
+        false => api_url,
+    };
+
+    let cache_key = format!("explorer_abi.{chain_id}.{address}");
+    if let Some(abi) = read_cache::<String>(&cache_key).ok().flatten() {
+        debug!("found cached explorer abi for {}", address);
+        return Ok(Some(JsonAbi::from_json_str(&abi)?));
+    }
+
+    let url = format!(
+        "{api_url}?chainid={chain_id}&module=contract&action=getabi&address={address}&apikey={api_key}"
+    );
+    let Some(response) = get_json_from_url(&url, 10).await? else {
+        return Ok(None);
+    };
+
+    let abi = parse_getabi_response(&response)?;
+    if let Some(abi) = &abi {
+        store_cache(&cache_key, serde_json::to_string(abi)?, None).ok();
+    }
+    Ok(abi)
+}
+
+/// Fetch the verified ABI of the contract at `address` on the chain served by `rpc_url`, see
+/// [`get_verified_abi`].
+pub async fn get_verified_abi_from_rpc(
+    address: Address,
+    rpc_url: &str,
+    api_url: &str,
+    api_key: &str,
+) -> Result<Option<JsonAbi>> {
+    let chain_id = chain_id(rpc_url).await?;
+    get_verified_abi(address, chain_id, api_url, api_key).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_getabi_response() {
+        let response = json!({
+            "status": "1",
+            "message": "OK",
+            "result": r#"[{"type":"event","name":"Deposit","anonymous":false,"inputs":[{"name":"dst","type":"address","indexed":true},{"name":"wad","type":"uint256","indexed":false}]}]"#
+        });
+        let abi = parse_getabi_response(&response).unwrap().unwrap();
+        assert_eq!(abi.events().next().unwrap().signature(), "Deposit(address,uint256)");
+    }
+
+    #[test]
+    fn test_parse_getabi_error_response() {
+        let unverified = json!({
+            "status": "0",
+            "message": "NOTOK",
+            "result": "Contract source code not verified"
+        });
+        assert!(parse_getabi_response(&unverified).unwrap().is_none());
+
+        let invalid_key = json!({ "status": "0", "message": "NOTOK", "result": "Invalid API Key" });
+        assert!(parse_getabi_response(&invalid_key).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_verified_abi_without_api_key() {
+        let address = Address::repeat_byte(1);
+        assert!(get_verified_abi(address, 1, "", "").await.unwrap().is_none());
+    }
+}
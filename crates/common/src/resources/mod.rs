@@ -1,3 +1,6 @@
+/// Verified contract ABIs from Etherscan-compatible block explorer APIs.
+pub mod abi;
+
 /// OpenAI API integration for AI-powered analysis.
 pub mod openai;
 
@@ -7,7 +7,9 @@ use async_openai::{
     },
     Client,
 };
-use tracing::error;
+use tracing::{error, warn};
+
+use crate::utils::offline::is_offline;
 
 /// Complete the given prompt using the OpenAI API.
 ///
@@ -18,6 +20,11 @@ use tracing::error;
 /// let api_key = "your-api-key";
 /// // complete(prompt, api_key).await;
 pub async fn complete(prompt: &str, api_key: &str) -> Option<String> {
+    if is_offline() {
+        warn!("offline mode is enabled, skipping OpenAI request");
+        return None;
+    }
+
     let config = OpenAIConfig::new().with_api_key(api_key);
     let client = Client::with_config(config);
 
@@ -61,6 +68,11 @@ pub async fn complete(prompt: &str, api_key: &str) -> Option<String> {
 /// let api_key = "your-api-key";
 /// // complete_chat(prompt, api_key).await;
 pub async fn complete_chat(prompt: &str, api_key: &str) -> Option<String> {
+    if is_offline() {
+        warn!("offline mode is enabled, skipping OpenAI request");
+        return None;
+    }
+
     let http_client =
         reqwest::Client::builder().timeout(std::time::Duration::from_secs(90)).build().unwrap();
     let config = OpenAIConfig::new().with_api_key(api_key);
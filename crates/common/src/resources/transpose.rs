@@ -6,6 +6,8 @@ use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, trace};
 
+use crate::utils::offline::is_offline;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TransposeStats {
     count: u128,
@@ -22,6 +24,11 @@ struct TransposeResponse {
 
 /// executes a transpose SQL query and returns the response
 async fn call_transpose(query: &str, api_key: &str) -> Option<TransposeResponse> {
+    if is_offline() {
+        debug!("offline mode is enabled, skipping transpose query");
+        return None;
+    }
+
     backoff::future::retry(
         ExponentialBackoff {
             max_elapsed_time: Some(Duration::from_secs(10)),
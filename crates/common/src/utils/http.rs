@@ -3,7 +3,9 @@ use reqwest::Client;
 use serde_json::Value;
 use std::time::Duration;
 use tokio::time::sleep as async_sleep;
-use tracing::trace;
+use tracing::{debug, trace};
+
+use super::offline::is_offline;
 
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
@@ -17,6 +19,11 @@ static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_P
 /// // get_json_from_url(url, timeout).await;
 /// ```
 pub async fn get_json_from_url(url: &str, timeout: u64) -> Result<Option<Value>, reqwest::Error> {
+    if is_offline() {
+        debug!("offline mode is enabled, skipping GET {}", &url);
+        return Ok(None);
+    }
+
     _get_json_from_url(url, 0, 2, timeout).await
 }
 
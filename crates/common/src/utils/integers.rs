@@ -1,3 +1,5 @@
+use alloy::primitives::U256;
+
 /// Trait for formatting numbers with locale-specific formatting.
 ///
 /// This trait adds methods to format numbers in a more human-readable way,
@@ -22,18 +24,36 @@ impl ToLocaleString for usize {
     /// assert_eq!(1000000.to_locale_string(), "1,000,000");
     /// ```
     fn to_locale_string(&self) -> String {
-        let num_str = self.to_string();
-        let mut result = String::new();
+        group_digits(&self.to_string())
+    }
+}
 
-        for (count, c) in num_str.chars().rev().enumerate() {
-            if count != 0 && count % 3 == 0 {
-                result.push(',');
-            }
-            result.push(c);
-        }
+impl ToLocaleString for U256 {
+    /// Add commas every 3 digits, e.g. 1000000 -> 1,000,000.
+    ///
+    /// ```
+    /// use alloy::primitives::U256;
+    /// use heimdall_common::utils::integers::ToLocaleString;
+    ///
+    /// assert_eq!(U256::from(1000000).to_locale_string(), "1,000,000");
+    /// ```
+    fn to_locale_string(&self) -> String {
+        group_digits(&self.to_string())
+    }
+}
+
+/// Add commas every 3 digits of the decimal number.
+fn group_digits(num_str: &str) -> String {
+    let mut result = String::new();
 
-        result.chars().rev().collect()
+    for (count, c) in num_str.chars().rev().enumerate() {
+        if count != 0 && count % 3 == 0 {
+            result.push(',');
+        }
+        result.push(c);
     }
+
+    result.chars().rev().collect()
 }
 
 #[cfg(test)]
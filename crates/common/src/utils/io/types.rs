@@ -1,9 +1,13 @@
 use alloy_dyn_abi::DynSolValue;
 use colored::Colorize;
 
-use crate::utils::{hex::ToLowerHex, strings::encode_hex};
+use crate::{
+    ether::labels::display_address,
+    utils::{hex::ToLowerHex, strings::encode_hex},
+};
 
-/// A helper function used by the decode module to pretty format decoded tokens.
+/// A helper function used by the decode module to pretty format decoded tokens. Labeled
+/// addresses are followed by their label.
 pub fn display(inputs: Vec<DynSolValue>, prefix: &str) -> Vec<String> {
     let mut output = Vec::new();
     let prefix = prefix.to_string();
@@ -11,7 +15,7 @@ pub fn display(inputs: Vec<DynSolValue>, prefix: &str) -> Vec<String> {
     for input in inputs {
         match input {
             DynSolValue::Address(val) => {
-                output.push(format!("{prefix}{} {}", "address".blue(), val))
+                output.push(format!("{prefix}{} {}", "address".blue(), display_address(&val)))
             }
             DynSolValue::Int(val, _) => {
                 output.push(format!("{prefix}{} {}", "int    ".blue(), val))
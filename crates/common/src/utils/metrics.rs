@@ -0,0 +1,234 @@
+use std::{collections::BTreeMap, fmt::Write, sync::Mutex, time::Duration};
+
+use eyre::Result;
+
+use crate::utils::io::file::write_file;
+
+/// The upper bounds, in seconds, of the rpc request latency histogram's buckets.
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 10.0];
+
+/// The metrics recorded by this process, see [`Metrics`].
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics::new());
+
+/// Counters and timings describing where a run spent its time and rpc requests, which may be
+/// rendered in the Prometheus text exposition format.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metrics {
+    rpc_requests: BTreeMap<String, RequestStats>,
+    cache_lookups: BTreeMap<String, CacheStats>,
+    phases: BTreeMap<String, PhaseStats>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct RequestStats {
+    succeeded: u64,
+    failed: u64,
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    total_seconds: f64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct CacheStats {
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct PhaseStats {
+    count: u64,
+    total_seconds: f64,
+}
+
+impl Metrics {
+    /// Create an empty set of metrics.
+    pub const fn new() -> Self {
+        Self {
+            rpc_requests: BTreeMap::new(),
+            cache_lookups: BTreeMap::new(),
+            phases: BTreeMap::new(),
+        }
+    }
+
+    /// Record a single rpc request of `method`, which took `duration` to succeed or fail.
+    pub fn record_rpc_request(&mut self, method: &str, duration: Duration, success: bool) {
+        let stats = self.rpc_requests.entry(method.to_string()).or_default();
+        match success {
+            true => stats.succeeded += 1,
+            false => stats.failed += 1,
+        }
+
+        let seconds = duration.as_secs_f64();
+        stats.total_seconds += seconds;
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            stats.buckets[bucket] += 1;
+        }
+    }
+
+    /// Record a response cache lookup for a `method` response.
+    pub fn record_cache_lookup(&mut self, method: &str, hit: bool) {
+        let stats = self.cache_lookups.entry(method.to_string()).or_default();
+        match hit {
+            true => stats.hits += 1,
+            false => stats.misses += 1,
+        }
+    }
+
+    /// Record a single run of the given module phase, e.g. `decompile.symbolic_execution`.
+    pub fn record_phase(&mut self, phase: &str, duration: Duration) {
+        let stats = self.phases.entry(phase.to_string()).or_default();
+        stats.count += 1;
+        stats.total_seconds += duration.as_secs_f64();
+    }
+
+    /// Render the metrics in the Prometheus text exposition format.
+    ///
+    /// ```
+    /// use heimdall_common::utils::metrics::Metrics;
+    ///
+    /// let mut metrics = Metrics::new();
+    /// metrics.record_cache_lookup("get_code", true);
+    ///
+    /// assert!(metrics.render().contains(r#"heimdall_cache_lookups_total{method="get_code",result="hit"} 1"#));
+    /// ```
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP heimdall_rpc_requests_total The number of rpc requests sent.\n");
+        out.push_str("# TYPE heimdall_rpc_requests_total counter\n");
+        for (method, stats) in &self.rpc_requests {
+            for (status, count) in [("ok", stats.succeeded), ("error", stats.failed)] {
+                let _ = writeln!(
+                    out,
+                    "heimdall_rpc_requests_total{{method=\"{method}\",status=\"{status}\"}} {count}"
+                );
+            }
+        }
+
+        out.push_str("# HELP heimdall_rpc_request_duration_seconds The latency of rpc requests.\n");
+        out.push_str("# TYPE heimdall_rpc_request_duration_seconds histogram\n");
+        for (method, stats) in &self.rpc_requests {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "heimdall_rpc_request_duration_seconds_bucket{{method=\"{method}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let count = stats.succeeded + stats.failed;
+            let _ = writeln!(
+                out,
+                "heimdall_rpc_request_duration_seconds_bucket{{method=\"{method}\",le=\"+Inf\"}} {count}"
+            );
+            let _ = writeln!(
+                out,
+                "heimdall_rpc_request_duration_seconds_sum{{method=\"{method}\"}} {}",
+                stats.total_seconds
+            );
+            let _ = writeln!(
+                out,
+                "heimdall_rpc_request_duration_seconds_count{{method=\"{method}\"}} {count}"
+            );
+        }
+
+        out.push_str(
+            "# HELP heimdall_cache_lookups_total The number of rpc response cache lookups.\n",
+        );
+        out.push_str("# TYPE heimdall_cache_lookups_total counter\n");
+        for (method, stats) in &self.cache_lookups {
+            for (result, count) in [("hit", stats.hits), ("miss", stats.misses)] {
+                let _ = writeln!(
+                    out,
+                    "heimdall_cache_lookups_total{{method=\"{method}\",result=\"{result}\"}} {count}"
+                );
+            }
+        }
+
+        out.push_str(
+            "# HELP heimdall_phase_duration_seconds The time spent in each module phase.\n",
+        );
+        out.push_str("# TYPE heimdall_phase_duration_seconds summary\n");
+        for (phase, stats) in &self.phases {
+            let _ = writeln!(
+                out,
+                "heimdall_phase_duration_seconds_sum{{phase=\"{phase}\"}} {}",
+                stats.total_seconds
+            );
+            let _ = writeln!(
+                out,
+                "heimdall_phase_duration_seconds_count{{phase=\"{phase}\"}} {}",
+                stats.count
+            );
+        }
+
+        out
+    }
+}
+
+/// Run `f` against the metrics recorded by this process.
+fn with_metrics<T>(f: impl FnOnce(&mut Metrics) -> T) -> T {
+    f(&mut METRICS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Record a single rpc request of `method`, see [`Metrics::record_rpc_request`].
+pub fn record_rpc_request(method: &str, duration: Duration, success: bool) {
+    with_metrics(|metrics| metrics.record_rpc_request(method, duration, success))
+}
+
+/// Record a response cache lookup, see [`Metrics::record_cache_lookup`].
+pub fn record_cache_lookup(method: &str, hit: bool) {
+    with_metrics(|metrics| metrics.record_cache_lookup(method, hit))
+}
+
+/// Record a single run of a module phase, see [`Metrics::record_phase`].
+pub fn record_phase(phase: &str, duration: Duration) {
+    with_metrics(|metrics| metrics.record_phase(phase, duration))
+}
+
+/// Get a snapshot of the metrics recorded by this process.
+pub fn metrics() -> Metrics {
+    with_metrics(|metrics| metrics.clone())
+}
+
+/// Write the metrics recorded by this process to the given file, in the Prometheus text
+/// exposition format.
+pub fn write_metrics_file(path: &str) -> Result<()> {
+    write_file(path, &metrics().render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_rpc_requests() {
+        let mut metrics = Metrics::new();
+        metrics.record_rpc_request("eth_getCode", Duration::from_millis(20), true);
+        metrics.record_rpc_request("eth_getCode", Duration::from_millis(200), false);
+        metrics.record_rpc_request("eth_getCode", Duration::from_secs(60), true);
+
+        let rendered = metrics.render();
+        for line in [
+            r#"heimdall_rpc_requests_total{method="eth_getCode",status="ok"} 2"#,
+            r#"heimdall_rpc_requests_total{method="eth_getCode",status="error"} 1"#,
+            r#"heimdall_rpc_request_duration_seconds_bucket{method="eth_getCode",le="0.025"} 1"#,
+            r#"heimdall_rpc_request_duration_seconds_bucket{method="eth_getCode",le="0.25"} 2"#,
+            r#"heimdall_rpc_request_duration_seconds_bucket{method="eth_getCode",le="10"} 2"#,
+            r#"heimdall_rpc_request_duration_seconds_bucket{method="eth_getCode",le="+Inf"} 3"#,
+            r#"heimdall_rpc_request_duration_seconds_count{method="eth_getCode"} 3"#,
+        ] {
+            assert!(rendered.contains(line), "missing '{line}' in:\n{rendered}");
+        }
+    }
+
+    #[test]
+    fn test_render_phases() {
+        let mut metrics = Metrics::new();
+        metrics.record_phase("decompile", Duration::from_millis(1500));
+        metrics.record_phase("decompile", Duration::from_millis(500));
+
+        let rendered = metrics.render();
+        assert!(rendered.contains(r#"heimdall_phase_duration_seconds_sum{phase="decompile"} 2"#));
+        assert!(rendered.contains(r#"heimdall_phase_duration_seconds_count{phase="decompile"} 2"#));
+    }
+}
@@ -5,6 +5,7 @@ pub mod env;
 pub mod hex;
 
 /// HTTP request and response handling utilities.
+#[cfg(not(target_arch = "wasm32"))]
 pub mod http;
 
 /// Integer manipulation and formatting utilities.
@@ -16,10 +17,24 @@ pub mod io;
 /// Iterator and collection utilities.
 pub mod iter;
 
+/// Metrics describing rpc usage and module performance.
+pub mod metrics;
+
+/// Offline mode utilities.
+pub mod offline;
+
+/// Progress updates from long-running operations.
+pub mod progress;
+
+/// Rate limiting utilities.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ratelimit;
+
 /// String manipulation and formatting utilities.
 pub mod strings;
 
 /// Synchronization primitives and utilities.
+#[cfg(not(target_arch = "wasm32"))]
 pub mod sync;
 
 /// Threading and multi-threading utilities.
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use eyre::{bail, Result};
+
+/// Whether network access is disabled for this process.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable offline mode. While offline, heimdall refuses to make any network
+/// requests, and only uses local files and previously cached data.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether offline mode is enabled.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Return an error describing the refused `action` if offline mode is enabled.
+///
+/// ```
+/// use heimdall_common::utils::offline::{ensure_online, set_offline};
+///
+/// set_offline(true);
+/// assert!(ensure_online("connect to rpc").is_err());
+///
+/// set_offline(false);
+/// assert!(ensure_online("connect to rpc").is_ok());
+/// ```
+pub fn ensure_online(action: &str) -> Result<()> {
+    if is_offline() {
+        bail!("cannot {action}: network access is disabled in offline mode");
+    }
+    Ok(())
+}
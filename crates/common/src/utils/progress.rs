@@ -0,0 +1,25 @@
+use tokio::sync::mpsc::UnboundedSender;
+
+/// A progress update from a long-running operation, such as a storage dump or decompilation,
+/// which is sent to its caller as each step of the operation completes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of steps completed so far
+    pub completed: u64,
+    /// The total number of steps
+    pub total: u64,
+    /// The step which was completed, e.g. the block which was traced or the function which was
+    /// symbolically executed
+    pub step: String,
+}
+
+/// Sends the progress updates of an operation to its caller.
+pub type ProgressSender = UnboundedSender<Progress>;
+
+/// Sends the progress update to the caller, if they asked for progress updates. Updates are
+/// dropped if the caller stopped listening for them.
+pub fn report_progress(sender: Option<&ProgressSender>, completed: u64, total: u64, step: String) {
+    if let Some(sender) = sender {
+        let _ = sender.send(Progress { completed, total, step });
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A token bucket rate limiter which may be shared between concurrent tasks.
+///
+/// The bucket holds up to one second's worth of tokens, so short bursts of up to
+/// `requests_per_second` requests are allowed after a period of inactivity.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: u32,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a new rate limiter which allows `requests_per_second` requests per second.
+    pub fn new(requests_per_second: u32) -> Self {
+        let requests_per_second = requests_per_second.max(1);
+        Self {
+            requests_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: requests_per_second as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// The number of requests allowed per second.
+    pub fn requests_per_second(&self) -> u32 {
+        self.requests_per_second
+    }
+
+    /// Take a token from the bucket if one is available, otherwise return how long to wait
+    /// until the next token becomes available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let rate = self.requests_per_second as f64;
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = elapsed.mul_add(rate, bucket.tokens).min(rate);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    /// Wait until a token is available, and take it from the bucket.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_acquire_allows_burst() {
+        let limiter = RateLimiter::new(5);
+        for _ in 0..5 {
+            assert!(limiter.try_acquire().is_ok());
+        }
+
+        let wait = limiter.try_acquire().expect_err("bucket should be empty");
+        assert!(wait <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_zero_rate_is_clamped() {
+        let limiter = RateLimiter::new(0);
+        assert_eq!(limiter.requests_per_second(), 1);
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter = RateLimiter::new(10);
+        let start = Instant::now();
+        for _ in 0..12 {
+            limiter.acquire().await;
+        }
+
+        // the first 10 tokens are available immediately, the next 2 take ~100ms each
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+}
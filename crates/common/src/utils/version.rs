@@ -1,8 +1,8 @@
 use std::fmt::Display;
 
-use eyre::Result;
-
-use super::http::get_json_from_url;
+// checking for new releases isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {super::http::get_json_from_url, eyre::Result};
 
 #[derive(Debug)]
 /// Represents a semantic version number.
@@ -40,6 +40,7 @@ pub fn current_version() -> Version {
 }
 
 /// get the latest version from github
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn remote_version() -> Result<Version> {
     // get the latest release from github
     let remote_repository_url =
@@ -66,6 +67,7 @@ pub async fn remote_version() -> Result<Version> {
 }
 
 /// get the latest nightly version from github
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn remote_nightly_version() -> Result<Version> {
     // get the latest commit to main from github
     let remote_repository_url = "https://api.github.com/repos/Jon-Becker/heimdall-rs/commits/main";
@@ -49,6 +49,11 @@ pub struct Configuration {
 
     /// The API key for OpenAI services
     pub openai_api_key: String,
+
+    /// The URL of an Etherscan-compatible block explorer API, such as a Blockscout instance's
+    /// `/api`, to fetch verified ABIs from in place of the chain's default explorer
+    #[serde(default)]
+    pub explorer_api_url: String,
 }
 
 impl Default for Configuration {
@@ -59,6 +64,7 @@ impl Default for Configuration {
             etherscan_api_key: "".to_string(),
             transpose_api_key: "".to_string(),
             openai_api_key: "".to_string(),
+            explorer_api_url: "".to_string(),
         }
     }
 }
@@ -186,6 +192,9 @@ impl Configuration {
             "openai_api_key" => {
                 self.openai_api_key = value.to_string();
             }
+            "explorer_api_url" => {
+                self.explorer_api_url = value.to_string();
+            }
             _ => {
                 return Err(Error::Generic(format!(
                     "invalid key: \'{key}\' is not a valid configuration key."
@@ -222,13 +231,25 @@ pub fn config(args: ConfigArgs) -> Result<(), Error> {
 }
 
 /// Parse user input --rpc-url into a full url
+///
+/// The input may be a comma-separated list of urls, in which case each entry is parsed
+/// individually and the list is returned in the same order.
 pub fn parse_url_arg(url: &str) -> Result<String, String> {
+    if !url.contains(',') {
+        return Ok(parse_single_url_arg(url));
+    }
+
+    Ok(url.split(',').map(|url| parse_single_url_arg(url.trim())).collect::<Vec<_>>().join(","))
+}
+
+/// Parse a single url, resolving it as a MESC endpoint if possible
+fn parse_single_url_arg(url: &str) -> String {
     if mesc::is_mesc_enabled() {
         if let Ok(Some(endpoint)) = mesc::get_endpoint_by_query(url, Some("heimdall")) {
-            return Ok(endpoint.url);
+            return endpoint.url;
         }
     }
-    Ok(url.to_string())
+    url.to_string()
 }
 
 #[allow(deprecated)]
@@ -247,6 +268,7 @@ mod tests {
         assert_eq!(config.etherscan_api_key, "");
         assert_eq!(config.transpose_api_key, "");
         assert_eq!(config.openai_api_key, "");
+        assert_eq!(config.explorer_api_url, "");
     }
 
     // Test loading configuration from a file
@@ -313,4 +335,21 @@ mod tests {
         assert_eq!(config.transpose_api_key, "");
         assert_eq!(config.openai_api_key, "");
     }
+
+    // Test that configuration files written before a key was added still load
+    #[test]
+    #[serial]
+    fn test_load_configuration_missing_keys() {
+        let config: Configuration = toml::from_str(
+            r#"
+            rpc_url = ""
+            local_rpc_url = "http://localhost:8545"
+            etherscan_api_key = ""
+            transpose_api_key = ""
+            openai_api_key = ""
+            "#,
+        )
+        .expect("failed to parse config");
+        assert_eq!(config.explorer_api_url, "");
+    }
 }
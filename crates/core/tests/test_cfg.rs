@@ -5,7 +5,7 @@ mod integration_tests {
     use memory_stats::memory_stats;
     use std::path::PathBuf;
 
-    use heimdall_cfg::{cfg, CfgArgs, CfgArgsBuilder, HardFork};
+    use heimdall_cfg::{cfg, CfgArgs, CfgArgsBuilder, CfgFormat, HardFork};
     use petgraph::dot::Dot;
     use serde_json::Value;
 
@@ -26,6 +26,14 @@ mod integration_tests {
             timeout: 10000,
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            function: None,
+            per_function: false,
+            loops_only: false,
+            diff: Vec::new(),
+            gas: false,
+            tx: None,
+            call_graph: false,
+            format: CfgFormat::Dot,
         })
         .await
         .expect("failed to generate cfg");
@@ -57,6 +65,14 @@ mod integration_tests {
             timeout: 10000,
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            function: None,
+            per_function: false,
+            loops_only: false,
+            diff: Vec::new(),
+            gas: false,
+            tx: None,
+            call_graph: false,
+            format: CfgFormat::Dot,
         })
         .await
         .expect("failed to generate cfg");
@@ -86,6 +102,14 @@ mod integration_tests {
             timeout: 10000,
             hardfork: HardFork::Auto,
             etherscan_api_key: String::from(""),
+            function: None,
+            per_function: false,
+            loops_only: false,
+            diff: Vec::new(),
+            gas: false,
+            tx: None,
+            call_graph: false,
+            format: CfgFormat::Dot,
         })
         .await
         .expect("failed to generate cfg with auto hardfork");
@@ -112,6 +136,14 @@ mod integration_tests {
             timeout: 10000,
             hardfork: HardFork::Auto,
             etherscan_api_key: String::from(""),
+            function: None,
+            per_function: false,
+            loops_only: false,
+            diff: Vec::new(),
+            gas: false,
+            tx: None,
+            call_graph: false,
+            format: CfgFormat::Dot,
         })
         .await
         .expect("failed to generate cfg with auto hardfork fallback");
@@ -122,6 +154,47 @@ mod integration_tests {
         assert!(!output.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_cfg_function() {
+        let bytecode = "0x6080604052348015600e575f80fd5b50600436106030575f3560e01c80632125b65b146034578063b69ef8a8146044575b5f80fd5b6044603f3660046046565b505050565b005b5f805f606084860312156057575f80fd5b833563ffffffff811681146069575f80fd5b925060208401356001600160a01b03811681146083575f80fd5b915060408401356001600160e01b0381168114609d575f80fd5b80915050925092509256";
+
+        let whole = cfg(CfgArgsBuilder::new().target(bytecode.to_string()).build().unwrap())
+            .await
+            .expect("failed to generate cfg");
+        let function = cfg(CfgArgsBuilder::new()
+            .target(bytecode.to_string())
+            .function(Some(String::from("0xb69ef8a8")))
+            .build()
+            .unwrap())
+        .await
+        .expect("failed to generate cfg of function");
+        assert!(function.graph.node_count() > 0);
+        assert!(function.graph.node_count() < whole.graph.node_count());
+
+        let missing = cfg(CfgArgsBuilder::new()
+            .target(bytecode.to_string())
+            .function(Some(String::from("transfer(address,uint256)")))
+            .build()
+            .unwrap())
+        .await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cfg_per_function() {
+        let bytecode = "0x6080604052348015600e575f80fd5b50600436106030575f3560e01c80632125b65b146034578063b69ef8a8146044575b5f80fd5b6044603f3660046046565b505050565b005b5f805f606084860312156057575f80fd5b833563ffffffff811681146069575f80fd5b925060208401356001600160a01b03811681146083575f80fd5b915060408401356001600160e01b0381168114609d575f80fd5b80915050925092509256";
+
+        let result = cfg(CfgArgsBuilder::new()
+            .target(bytecode.to_string())
+            .per_function(true)
+            .build()
+            .unwrap())
+        .await
+        .expect("failed to generate cfgs");
+        assert_eq!(result.functions.keys().collect::<Vec<_>>(), vec!["0x2125b65b", "0xb69ef8a8"]);
+        assert!(result.to_json()["functions"]["0x2125b65b"]["nodes"].is_array());
+    }
+
     #[tokio::test]
     #[ignore]
     async fn heavy_integration_test() {
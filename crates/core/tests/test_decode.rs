@@ -8,34 +8,307 @@ mod integration_tests {
     #[tokio::test]
     async fn test_decode_transfer() {
         let args = DecodeArgs {
-            abi: None,
+            abi: Vec::new(),
             target: String::from("0xc47f00270000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000b6a6265636b65722e657468000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: false,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("print"),
         };
         let _ = heimdall_decoder::decode(args).await;
     }
 
+    #[tokio::test]
+    async fn test_decode_revert_data() {
+        // Error("Ownable: caller is not the owner")
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000204f776e61626c653a2063616c6c6572206973206e6f7420746865206f776e6572"))
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode revert data");
+        assert!(result.is_revert);
+        assert_eq!(result.decoded.signature, "Error(string)");
+
+        // Panic(0x11), an arithmetic overflow
+        let args = DecodeArgsBuilder::new()
+            .target(String::from(
+                "0x4e487b710000000000000000000000000000000000000000000000000000000000000011",
+            ))
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode revert data");
+        assert!(result.is_revert);
+        let json: Value = serde_json::from_str(&result.to_json().unwrap()).unwrap();
+        assert_eq!(json["signature"], "Panic(uint256)");
+        assert_eq!(json["revert"], true);
+        assert_eq!(
+            json["revert_reason"],
+            "panicked with code 0x11: arithmetic underflow or overflow"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_nested_calls() {
+        // multicall(bytes[]) with a call which is itself a multicall(bytes[])
+        let multicall = |selector: &str, call: &str| {
+            let padded = format!("{call}{}", "0".repeat((64 - call.len() % 64) % 64));
+            format!("{selector}{:064x}{:064x}{:064x}{:064x}{padded}", 0x20, 1, 0x20, call.len() / 2)
+        };
+        let innermost = format!("11111111{:064x}", 1);
+        let outer = multicall("33333333", &multicall("22222222", &innermost));
+
+        let decode = |max_depth: usize| {
+            heimdall_decoder::decode(
+                DecodeArgsBuilder::new()
+                    .target(format!("0x{outer}"))
+                    .skip_resolving(true)
+                    .max_depth(max_depth)
+                    .build()
+                    .expect("failed to build args"),
+            )
+        };
+
+        // each nested call is decoded, along with the calls nested within it
+        let result = decode(4).await.expect("failed to decode calldata");
+        let calls = result.multicall_results.as_ref().expect("no nested calls");
+        assert_eq!(calls.len(), 1);
+        let inner = calls[0].decoded.as_ref().expect("nested call wasn't decoded");
+        assert_eq!(inner.decoded.name, "Unresolved_22222222");
+        let innermost = &inner.multicall_results.as_ref().expect("no nested calls")[0];
+        assert_eq!(
+            innermost.decoded.as_ref().expect("nested call wasn't decoded").decoded.name,
+            "Unresolved_11111111"
+        );
+        let json: Value = serde_json::from_str(&result.to_json().unwrap()).unwrap();
+        assert_eq!(
+            json["multicall_results"][0]["decoded"]["multicall_results"][0]["decoded"]["name"],
+            "Unresolved_11111111"
+        );
+
+        // calls are only decoded up to `--max-depth` calls deep
+        let result = decode(1).await.expect("failed to decode calldata");
+        let inner = result.multicall_results.as_ref().expect("no nested calls")[0].decoded.clone();
+        assert!(inner.expect("nested call wasn't decoded").multicall_results.is_none());
+        assert!(decode(0).await.expect("failed to decode calldata").multicall_results.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_decode_revert_explains_custom_errors() {
+        // Error("Ownable: caller is not the owner")
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000204f776e61626c653a2063616c6c6572206973206e6f7420746865206f776e6572"))
+            .revert(true)
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode revert data");
+        assert_eq!(
+            result.revert_reason.as_deref(),
+            Some("reverted with reason \"Ownable: caller is not the owner\"")
+        );
+
+        // an unresolved custom error, InsufficientBalance(uint256,uint256)
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0xcf47918100000000000000000000000000000000000000000000000000000000000000640000000000000000000000000000000000000000000000000000000000000032"))
+            .revert(true)
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode revert data");
+        assert!(result.is_revert);
+        assert_eq!(
+            result.revert_reason.as_deref(),
+            Some("reverted with custom error Unresolved_cf479181(uint: 100, uint: 50)")
+        );
+
+        // a call which reverted without revert data can't be decoded
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0x"))
+            .revert(true)
+            .build()
+            .expect("failed to build args");
+        assert!(heimdall_decoder::decode(args).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decode_with_multiple_abis() {
+        let abis = [("heimdallFirstAbiTest", "uint256"), ("heimdallSecondAbiTest", "address")];
+        let paths = abis
+            .iter()
+            .map(|(name, input)| {
+                let path = std::env::temp_dir().join(format!("{name}.json"));
+                let abi = format!(
+                    r#"[{{"type":"function","name":"{name}","inputs":[{{"name":"a","type":"{input}"}}],"outputs":[],"stateMutability":"nonpayable"}}]"#
+                );
+                std::fs::write(&path, abi).expect("failed to write abi");
+                path.to_string_lossy().to_string()
+            })
+            .collect::<Vec<_>>();
+
+        // each function is decoded with the exact signature from its abi, rather than guessed
+        for (name, input) in abis {
+            let function = alloy_json_abi::Function::parse(&format!("{name}({input})"))
+                .expect("failed to parse function");
+            let args = DecodeArgsBuilder::new()
+                .target(format!("{}{}", function.selector(), "00".repeat(31) + "01"))
+                .abi(paths.clone())
+                .build()
+                .expect("failed to build args");
+            let result = heimdall_decoder::decode(args).await.expect("failed to decode calldata");
+            assert_eq!(result.decoded.signature, format!("{name}({input})"));
+        }
+
+        // a missing abi is an error, rather than being ignored
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0x"))
+            .abi(vec![String::from("/nonexistent/abi.json")])
+            .build()
+            .expect("failed to build args");
+        assert!(heimdall_decoder::decode(args).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decode_creation_code() {
+        // `constructor(address owner, uint256 value)`, deployed with `(0xaa, 42)`
+        let creation_code = String::from("608060405234801561001057600080fd5b506100a43803806100a4608039604090106100485760805160005560a05160015561005661004e60003960a05161002e526100566000f35b600080fdfe60003560e01c80638da5cb5b146100205780633fa4f2451461002c57600080fd5b60005460005260206000f35b7f000000000000000000000000000000000000000000000000000000000000000060005260206000f300000000000000000000000000000000000000000000000000000000000000aa000000000000000000000000000000000000000000000000000000000000002a");
+
+        // the argument types are guessed without an abi
+        let args = DecodeArgsBuilder::new()
+            .creation_code(Some(creation_code.clone()))
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode constructor");
+        assert_eq!(result.decoded.name, "constructor");
+        assert_eq!(result.decoded.decoded_inputs.expect("no decoded inputs").len(), 2);
+
+        // and taken from the constructor of the abi if one is given
+        let path = std::env::temp_dir().join("heimdallCreationCodeTest.json");
+        std::fs::write(
+            &path,
+            r#"[{"type":"constructor","inputs":[{"name":"owner","type":"address"},{"name":"value","type":"uint256"}],"stateMutability":"nonpayable"}]"#,
+        )
+        .expect("failed to write abi");
+        let args = DecodeArgsBuilder::new()
+            .creation_code(Some(creation_code))
+            .abi(vec![path.to_string_lossy().to_string()])
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode constructor");
+        assert_eq!(result.decoded.signature, "constructor(address,uint256)");
+    }
+
+    #[tokio::test]
+    async fn test_decode_guesses_confidence() {
+        // transfer(address,uint256), with its selector unresolved
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0xa9059cbb000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa960450000000000000000000000000000000000000000000000000de0b6b3a7640000"))
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode calldata");
+        let guesses = result.guesses.as_ref().expect("types weren't guessed");
+        assert_eq!(guesses[0].ty, "address");
+        assert!(guesses[0].confidence > 0.5);
+        assert!(guesses[1].alternatives.iter().any(|alternative| alternative.ty == "bytes32"));
+
+        let json: Value = serde_json::from_str(&result.to_json().unwrap()).unwrap();
+        assert_eq!(json["guesses"][0]["alternatives"][0]["type"], "uint256");
+    }
+
+    #[tokio::test]
+    async fn test_decode_humanize() {
+        // an unresolved call with a duration of 7 days and a timestamp
+        let args = DecodeArgsBuilder::new()
+            .target(String::from("0x12345678000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa960450000000000000000000000000000000000000000000000000000000000093a800000000000000000000000000000000000000000000000000000000065920080"))
+            .skip_resolving(true)
+            .humanize(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode calldata");
+        let humanized = result.humanized.as_ref().expect("inputs weren't humanized");
+        assert_eq!(humanized[0], None);
+        assert_eq!(humanized[1].as_deref(), Some("7 days (604800)"));
+        assert_eq!(humanized[2].as_deref(), Some("2024-01-01 00:00:00 UTC (1704067200)"));
+
+        let json: Value = serde_json::from_str(&result.to_json().unwrap()).unwrap();
+        assert_eq!(json["humanized"][1], "7 days (604800)");
+    }
+
+    #[tokio::test]
+    async fn test_decode_typed_data() {
+        // the example payload of EIP-712, signed by the "Cow" key of its reference implementation
+        let typed_data = String::from(
+            r#"{"types":{"EIP712Domain":[{"name":"name","type":"string"},{"name":"version","type":"string"},{"name":"chainId","type":"uint256"},{"name":"verifyingContract","type":"address"}],"Person":[{"name":"name","type":"string"},{"name":"wallet","type":"address"}],"Mail":[{"name":"from","type":"Person"},{"name":"to","type":"Person"},{"name":"contents","type":"string"}]},"primaryType":"Mail","domain":{"name":"Ether Mail","version":"1","chainId":1,"verifyingContract":"0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"},"message":{"from":{"name":"Cow","wallet":"0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},"to":{"name":"Bob","wallet":"0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},"contents":"Hello, Bob!"}}"#,
+        );
+
+        let args = DecodeArgsBuilder::new()
+            .target(typed_data)
+            .signature(Some(String::from("0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c")))
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build args");
+        let result = heimdall_decoder::decode(args).await.expect("failed to decode typed data");
+        assert_eq!(result.decoded.signature, "Mail((string,address),(string,address),string)");
+
+        let typed_data = result.typed_data.expect("no typed data");
+        assert_eq!(
+            typed_data.digest.map(|digest| digest.to_string()).as_deref(),
+            Some("0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+        );
+        assert_eq!(
+            typed_data.signer.map(|signer| signer.to_string()).as_deref(),
+            Some("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826")
+        );
+    }
+
     #[tokio::test]
     async fn test_decode_seaport_simple() {
         let args = DecodeArgs {
             target: String::from("0xfb0f3ee100000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000ec9c58de0a8000000000000000000000000000d2f8a98bde7c701ae961d10d0d1fc3a751be737f000000000000000000000000004c00500000ad104d7dbd00e3ae0a5c00560c000000000000000000000000005008c2a3af41024e9f0bd0432df4f75828602598000000000000000000000000000000000000000000000000000000000000110600000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000006358934b00000000000000000000000000000000000000000000000000000000637e22710000000000000000000000000000000000000000000000000000000000000000360c6ebe000000000000000000000000000000000000000038844ef19f04aecf0000007b02230091a7ed01230072f7006a004d60a8d4e71d599b8104250f000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000024000000000000000000000000000000000000000000000000000000000000002e0000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000066517289880000000000000000000000000000000a26b00c1f0df003000390027140000faa719000000000000000000000000000000000000000000000000000cca2e51310000000000000000000000000000cecf12f47d2896c90f6e19b7376fa3b169fabd920000000000000000000000000000000000000000000000000000000000000041447858c6d8251fb8ffba546bedb410457ff77148fdf59ac8e046993936a134b028f535c5b1f760508b6e0c3c18d44927d82da0502c66688c0dc961a434a9b0071c00000000000000000000000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
-            abi: None,
+            abi: Vec::new(),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: false,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("print"),
 
         };
@@ -50,14 +323,28 @@ mod integration_tests {
         let args = DecodeArgs {
             target: String::from("0x1749e1e30000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
-            abi: None,
+            abi: Vec::new(),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: true,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("json"),
         };
 
@@ -94,14 +381,28 @@ mod integration_tests {
             // - bytes: 0x70a08231 (balanceOf selector)
             target: String::from("0x252dba4200000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000002000000000000000000000000069c8ebef7752407cc5818a099b1fcad65d5eee990000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000000470a0823100000000000000000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
-            abi: None,
+            abi: Vec::new(),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: true,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("json"),
         };
 
@@ -130,14 +431,28 @@ mod integration_tests {
         let args = DecodeArgs {
             target: String::from("0x1749e1e30000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
-            abi: None,
+            abi: Vec::new(),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: false,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("json"),
         };
 
@@ -194,14 +509,28 @@ mod integration_tests {
             // Using aggregate function which has tuple[] input
             target: String::from("0x252dba4200000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
-            abi: None,
+            abi: Vec::new(),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: false,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("json"),
         };
 
@@ -229,14 +558,28 @@ mod integration_tests {
             let args = DecodeArgs {
                 target: String::from(calldata),
                 rpc_url: String::from(""),
-                abi: None,
+                abi: Vec::new(),
                 openai_api_key: String::from(""),
+                etherscan_api_key: String::from(""),
+                explorer_api_url: String::from(""),
                 explain: false,
+                humanize: false,
                 default: true,
                 constructor: false,
+                creation_code: None,
+                creation_tx: None,
+                signature: None,
                 truncate_calldata: false,
                 skip_resolving: false,
                 raw: false,
+                revert: false,
+                max_depth: 4,
+                no_sourcify: false,
+                log: Vec::new(),
+                all_logs: false,
+                beacon_url: String::new(),
+                batch: None,
+                threads: 4,
                 output: String::from("json"),
             };
 
@@ -274,14 +617,28 @@ mod integration_tests {
         let args = DecodeArgs {
             target: String::from("0x1749e1e30000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000100000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000"),
             rpc_url: String::from(""),
-            abi: None,
+            abi: Vec::new(),
             openai_api_key: String::from(""),
+            etherscan_api_key: String::from(""),
+            explorer_api_url: String::from(""),
             explain: false,
+            humanize: false,
             default: true,
             constructor: false,
+            creation_code: None,
+            creation_tx: None,
+            signature: None,
             truncate_calldata: false,
             skip_resolving: false,
             raw: false,
+            revert: false,
+            max_depth: 4,
+            no_sourcify: false,
+            log: Vec::new(),
+            all_logs: false,
+            beacon_url: String::new(),
+            batch: None,
+            threads: 4,
             output: String::from("json"),
         };
 
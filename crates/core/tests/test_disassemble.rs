@@ -4,7 +4,9 @@
 mod integration_tests {
     use std::{io::Write, path::PathBuf};
 
-    use heimdall_disassembler::{disassemble, DisassemblerArgs, DisassemblerArgsBuilder, HardFork};
+    use heimdall_disassembler::{
+        disassemble, DisassemblerArgs, DisassemblerArgsBuilder, DisassemblerFormat, HardFork,
+    };
     use serde_json::Value;
 
     #[tokio::test]
@@ -15,11 +17,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: bytecode.to_owned(),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: false,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -35,11 +42,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: bytecode.to_owned(),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -55,11 +67,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: bytecode.to_owned(),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: false,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -75,11 +92,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: bytecode.to_owned(),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -95,11 +117,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: bytecode.to_owned(),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -119,11 +146,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: String::from("test_disassemble_from_file"),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Latest,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -148,11 +180,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: String::from("0xafc2f2d803479a2af3a72022d54cc0901a0ec0d6"),
             rpc_url,
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Pectra,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
@@ -172,11 +209,16 @@ mod integration_tests {
         let result = disassemble(DisassemblerArgs {
             target: String::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
             rpc_url,
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Auto,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble with auto hardfork");
@@ -195,11 +237,16 @@ mod integration_tests {
         let assembly = disassemble(DisassemblerArgs {
             target: bytecode.to_owned(),
             rpc_url: String::from(""),
+            block: None,
             decimal_counter: true,
             name: String::from(""),
             output: String::from(""),
             hardfork: HardFork::Auto,
             etherscan_api_key: String::from(""),
+            format: DisassemblerFormat::Asm,
+            annotate: false,
+            skip_resolving: false,
+            strings: false,
         })
         .await
         .expect("failed to disassemble");
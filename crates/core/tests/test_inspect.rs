@@ -14,7 +14,16 @@ mod integration_tests {
         });
 
         let args = InspectArgs {
-            abi: None,
+            abi: Vec::new(),
+            humanize: false,
+            state_diff: false,
+            gas_profile: false,
+            flamegraph: None,
+            from: None,
+            to: None,
+            data: None,
+            value: None,
+            block: None,
             target: String::from(
                 "0xa5f676d0ee4c23cc1ccb0b802be5aaead5827a3337c06e9da8b0a85dfa3e7dd5",
             ),
@@ -37,7 +46,16 @@ mod integration_tests {
         });
 
         let args = InspectArgs {
-            abi: None,
+            abi: Vec::new(),
+            humanize: false,
+            state_diff: false,
+            gas_profile: false,
+            flamegraph: None,
+            from: None,
+            to: None,
+            data: None,
+            value: None,
+            block: None,
             target: String::from(
                 "0x37321f192623002fc4b398b90ea825c37f81e29526fd355cff93ef6962fc0fba",
             ),
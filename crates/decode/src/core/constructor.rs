@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use alloy::{consensus::Transaction, primitives::TxHash};
+use alloy_json_abi::JsonAbi;
+use eyre::eyre;
+use heimdall_common::{
+    ether::{rpc::get_transaction, signatures::ResolvedFunction},
+    utils::strings::decode_hex,
+};
+use heimdall_vm::{core::hardfork::HardFork, ext::creation::split_creation_code};
+use tracing::{debug, info, warn};
+
+use crate::{
+    core::{build_result, is_guessed, select_match},
+    error::Error,
+    interfaces::{DecodeArgs, DecodeResult},
+    utils::parse_deployment_bytecode,
+};
+
+/// How long the creation bytecode may execute for while it's split from its arguments, in
+/// milliseconds.
+const CREATION_TIMEOUT: u64 = 10_000;
+
+/// Decodes the constructor arguments appended to the creation bytecode given with
+/// `--creation-code`, or deployed by the `--creation-tx`. Their types are taken from the
+/// constructor of a provided ABI, or inferred from the arguments otherwise.
+pub(crate) async fn decode_constructor(args: &DecodeArgs) -> Result<DecodeResult, Error> {
+    let bytecode = get_creation_code(args).await?;
+
+    // the arguments follow the runtime bytecode the constructor deploys, which is found by
+    // executing it, or otherwise by matching the standard solidity deployment pattern
+    let (runtime_length, arguments) = match split_creation_code(
+        &bytecode,
+        HardFork::Latest,
+        CREATION_TIMEOUT,
+    ) {
+        Some(creation) => (Some(creation.runtime.len()), creation.arguments.unwrap_or_default()),
+        None => {
+            warn!("failed to execute the creation bytecode, matching the standard deployment pattern instead");
+            (None, parse_deployment_bytecode(bytecode)?.arguments)
+        }
+    };
+    if arguments.is_empty() {
+        return Err(Error::Eyre(eyre!("the creation bytecode has no constructor arguments")));
+    }
+    info!("extracted {} bytes of constructor arguments", arguments.len());
+
+    // the constructor arguments are decoded like calldata with an empty selector
+    let calldata = [&[0u8; 4], arguments.as_slice()].concat();
+    let mut selected_match = select_match(&calldata, abi_constructors(&args.abi)?)?;
+    let guessed = is_guessed(&selected_match);
+    selected_match.name = "constructor".to_string();
+    selected_match.signature = format!("constructor({})", selected_match.inputs.join(","));
+
+    let mut result = build_result(selected_match, false, guessed)?;
+    if let Some(runtime_length) = runtime_length {
+        result._trace.add_message(
+            1,
+            line!(),
+            vec![format!("deploys:   {runtime_length} bytes of runtime bytecode")],
+        );
+        result._trace.br(1);
+    }
+    Ok(result)
+}
+
+/// The creation bytecode given with `--creation-code`, either as hex or a file containing it, or
+/// the input of the `--creation-tx`.
+async fn get_creation_code(args: &DecodeArgs) -> Result<Vec<u8>, Error> {
+    if let Some(creation_code) = &args.creation_code {
+        let creation_code = match Path::new(creation_code).is_file() {
+            true => std::fs::read_to_string(creation_code).map_err(|e| {
+                Error::Eyre(eyre!(
+                    "failed to read creation bytecode from '{}': {}",
+                    creation_code,
+                    e
+                ))
+            })?,
+            false => creation_code.clone(),
+        };
+        return Ok(decode_hex(creation_code.trim())?);
+    }
+
+    let tx_hash = args
+        .creation_tx
+        .as_deref()
+        .unwrap_or_default()
+        .parse::<TxHash>()
+        .map_err(|e| Error::Eyre(eyre!("invalid creation transaction hash: {}", e)))?;
+    let transaction = get_transaction(tx_hash, &args.rpc_url)
+        .await
+        .map_err(|e| Error::FetchError(format!("fetching the creation transaction failed: {e}")))?;
+    if let Some(to) = transaction.to() {
+        return Err(Error::Eyre(eyre!(
+            "the transaction calls {} rather than creating a contract. contracts deployed by factories aren't supported",
+            to
+        )));
+    }
+    Ok(transaction.input().to_vec())
+}
+
+/// The constructors of the ABI files at the given paths, which are tried before the arguments'
+/// types are inferred.
+fn abi_constructors(paths: &[String]) -> Result<Vec<ResolvedFunction>, Error> {
+    let mut constructors = Vec::new();
+    for path in paths {
+        let abi = std::fs::read_to_string(path)
+            .map_err(|e| eyre!("failed to read abi '{}': {}", path, e))
+            .and_then(|abi| {
+                JsonAbi::from_json_str(&abi).map_err(|e| eyre!("invalid abi '{}': {}", path, e))
+            })?;
+
+        if let Some(constructor) = abi.constructor {
+            let inputs = constructor
+                .inputs
+                .iter()
+                .map(|input| input.selector_type().to_string())
+                .collect::<Vec<_>>();
+            debug!("using the constructor of abi '{}'", path);
+            constructors.push(ResolvedFunction {
+                name: "constructor".to_string(),
+                signature: format!("constructor({})", inputs.join(",")),
+                inputs,
+                decoded_inputs: None,
+            });
+        }
+    }
+    Ok(constructors)
+}
@@ -0,0 +1,374 @@
+use alloy::primitives::B256;
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use eyre::eyre;
+use heimdall_common::ether::{
+    signatures::{score_signature, ResolvedLog},
+    types::parse_function_parameters,
+};
+use tracing::{debug, info, trace, warn};
+
+use crate::{
+    error::Error,
+    interfaces::DecodedEvent,
+    utils::{infer_parameter_types, infer_parameter_types_by_word, infer_word_type, try_decode},
+};
+
+// fetching the target transaction's logs and resolving their selectors isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::{core::get_verified_callee_abi, interfaces::DecodeArgs, interfaces::DecodeLogsResult},
+    alloy::primitives::TxHash,
+    heimdall_common::{
+        ether::{
+            rpc::get_transaction_logs,
+            signatures::{
+                cache_signatures_from_abis, cache_signatures_from_json_abi, ResolveSelector,
+            },
+        },
+        utils::{hex::ToLowerHex, metrics::record_phase, strings::decode_hex},
+    },
+    std::time::Instant,
+};
+
+/// Decodes the event logs given by `--log` or `--all-logs` into human-readable event signatures
+/// and parameters.
+///
+/// With `--log`, the target is the data of a single log with the given topics. With `--all-logs`,
+/// the target is a transaction hash, and each of the logs it emitted is decoded.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn decode_logs(args: DecodeArgs) -> Result<DecodeLogsResult, Error> {
+    let start_time = Instant::now();
+
+    // prefer the verified ABI of the called contract, and then the provided ABI, for resolving
+    // the events' selectors, just as `decode` does
+    if !args.skip_resolving {
+        if let Some(verified_abi) = get_verified_callee_abi(&args).await {
+            cache_signatures_from_json_abi(&verified_abi);
+        }
+    }
+    cache_signatures_from_abis(&args.abi)
+        .map_err(|e| Error::Eyre(eyre!("caching signatures from ABI failed: {}", e)))?;
+
+    let mut events = Vec::new();
+    if args.all_logs {
+        let tx_hash = args.target.parse::<TxHash>().map_err(|_| {
+            Error::Eyre(eyre!("--all-logs requires the target to be a transaction hash"))
+        })?;
+        let logs = get_transaction_logs(tx_hash, &args.rpc_url)
+            .await
+            .map_err(|e| Error::FetchError(format!("fetching transaction logs failed: {e}")))?;
+        info!("decoding {} logs emitted by {}", logs.len(), tx_hash);
+
+        for log in logs {
+            let mut event =
+                resolve_and_decode_log(log.topics(), &log.data().data, args.skip_resolving).await?;
+            event.address = Some(log.address());
+            event.log_index = log.log_index;
+            events.push(event);
+        }
+    } else {
+        let topics = args
+            .log
+            .iter()
+            .map(|topic| topic.parse::<B256>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::Eyre(eyre!("parsing log topics failed: {}", e)))?;
+        let data = decode_hex(&args.target)
+            .map_err(|e| Error::Eyre(eyre!("parsing log data failed: {}", e)))?;
+
+        events.push(resolve_and_decode_log(&topics, &data, args.skip_resolving).await?);
+    }
+
+    debug!("decoding logs took {:?}", start_time.elapsed());
+    record_phase("decode", start_time.elapsed());
+
+    Ok(DecodeLogsResult::new(events))
+}
+
+/// Resolves the log's selector, i.e. its first topic, and decodes the log with the best of the
+/// resolved event signatures.
+#[cfg(not(target_arch = "wasm32"))]
+async fn resolve_and_decode_log(
+    topics: &[B256],
+    data: &[u8],
+    skip_resolving: bool,
+) -> Result<DecodedEvent, Error> {
+    let potential_matches = match topics.first() {
+        Some(selector) if !skip_resolving => {
+            match ResolvedLog::resolve(&selector.to_lower_hex()).await {
+                Ok(Some(signatures)) => signatures,
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    };
+    if !potential_matches.is_empty() {
+        info!("resolved {} potential event signatures", potential_matches.len());
+    }
+
+    decode_log(topics, data, potential_matches)
+}
+
+/// Decodes the event log with the best of the potential matches for its selector, i.e. the
+/// resolved event signatures it could have been emitted with. If none of them decode the log, its
+/// parameters are inferred from its topics and data instead.
+///
+/// Logs don't record which of the event's inputs are indexed, so for each potential match, the
+/// inputs indexed into the topics after the selector are chosen as early in the signature as
+/// possible, such that the indexed inputs decode from the topics, and the remaining inputs from
+/// the data.
+///
+/// Unlike [`decode_logs`], this doesn't fetch the logs or resolve their selectors, so it doesn't
+/// require network access.
+pub fn decode_log(
+    topics: &[B256],
+    data: &[u8],
+    potential_matches: Vec<ResolvedLog>,
+) -> Result<DecodedEvent, Error> {
+    let selector = topics
+        .first()
+        .ok_or_else(|| Error::Eyre(eyre!("the log has no topics, so it can't be resolved")))?;
+
+    // iterate over potential matches and attempt to decode the log with them
+    let mut matches = potential_matches
+        .iter()
+        .filter_map(|potential_match| {
+            let inputs = parse_function_parameters(&potential_match.signature).ok()?;
+            let Some((indexed, decoded_inputs)) = decode_indexed(&inputs, &topics[1..], data)
+            else {
+                debug!(
+                    "potential match '{}' ignored. decoding types failed",
+                    &potential_match.signature
+                );
+                return None;
+            };
+
+            Some(DecodedEvent {
+                address: None,
+                log_index: None,
+                topics: topics.to_vec(),
+                data: data.to_vec(),
+                name: potential_match.name.clone(),
+                signature: potential_match.signature.clone(),
+                inputs: inputs.iter().map(|input| input.to_string()).collect(),
+                indexed,
+                decoded_inputs,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if matches.len() > 1 {
+        debug!(
+            "multiple possible matches found. heimdall uses a heuristic to select the best match."
+        );
+        let num_words = data.chunks(32).len();
+        matches.sort_by_key(|event| {
+            std::cmp::Reverse(score_signature(&event.signature, Some(num_words)))
+        });
+    } else if matches.is_empty() {
+        warn!("couldn't find any resolved matches for '{}'", selector);
+        info!("falling back to inferring the event's types from its topics and data");
+        matches.push(infer_log(topics, data)?);
+    }
+
+    let selected_match = matches.remove(0);
+    info!("decoded event '{}' successfully", selected_match.indexed_signature());
+    Ok(selected_match)
+}
+
+/// Decodes the event's inputs, indexing the earliest combination of them into the topics which
+/// decodes. Returns whether each input is indexed, along with the decoded value of each input.
+fn decode_indexed(
+    inputs: &[DynSolType],
+    topics: &[B256],
+    data: &[u8],
+) -> Option<(Vec<bool>, Vec<DynSolValue>)> {
+    if topics.len() > inputs.len() {
+        return None;
+    }
+
+    combinations(inputs.len(), topics.len()).into_iter().find_map(|positions| {
+        let indexed = (0..inputs.len()).map(|i| positions.contains(&i)).collect::<Vec<_>>();
+        let mut topic_values = positions
+            .iter()
+            .zip(topics)
+            .map(|(i, topic)| decode_topic(&inputs[*i], topic))
+            .collect::<Option<Vec<_>>>()?
+            .into_iter();
+
+        // the data must be exactly the encoding of the remaining inputs
+        let data_types = inputs
+            .iter()
+            .zip(&indexed)
+            .filter(|(_, indexed)| !**indexed)
+            .map(|(input, _)| input.clone())
+            .collect::<Vec<_>>();
+        let data_values = DynSolType::Tuple(data_types).abi_decode_params(data).ok()?;
+        if data_values.abi_encode_params() != data {
+            return None;
+        }
+        let mut data_values = match data_values {
+            DynSolValue::Tuple(values) => values.into_iter(),
+            _ => return None,
+        };
+
+        let decoded_inputs = indexed
+            .iter()
+            .map(|indexed| match indexed {
+                true => topic_values.next(),
+                false => data_values.next(),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        trace!("decoded log with indexed inputs {:?}", positions);
+        Some((indexed, decoded_inputs))
+    })
+}
+
+/// Decodes the indexed input of type `ty` from its topic. Indexed dynamic types, arrays and
+/// tuples are only logged as the keccak256 hash of their value, so their hash is returned as
+/// `bytes32`. Otherwise, the topic must be exactly the encoding of the value.
+fn decode_topic(ty: &DynSolType, topic: &B256) -> Option<DynSolValue> {
+    match ty {
+        DynSolType::String |
+        DynSolType::Bytes |
+        DynSolType::Array(_) |
+        DynSolType::FixedArray(..) |
+        DynSolType::Tuple(_) => Some(DynSolValue::FixedBytes(*topic, 32)),
+        _ => {
+            let value = ty.abi_decode(topic.as_slice()).ok()?;
+            (value.abi_encode() == topic.as_slice()).then_some(value)
+        }
+    }
+}
+
+/// Infers the types of the event's inputs, indexing one input into each of the topics after the
+/// selector, and inferring the rest from the data, just as raw calldata is decoded.
+fn infer_log(topics: &[B256], data: &[u8]) -> Result<DecodedEvent, Error> {
+    let selector = &topics[0];
+    let mut inputs = topics[1..]
+        .iter()
+        .map(|topic| infer_word_type(topic.as_slice()).unwrap_or(DynSolType::Uint(256)))
+        .collect::<Vec<_>>();
+    let mut decoded_inputs = inputs
+        .iter()
+        .zip(&topics[1..])
+        .map(|(input, topic)| decode_topic(input, topic))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| Error::Eyre(eyre!("decoding the log's topics failed")))?;
+
+    if !data.is_empty() {
+        let data_types = match infer_parameter_types(data) {
+            Some(data_types) => data_types,
+            None => infer_parameter_types_by_word(data)?,
+        };
+        let (data_values, _) = try_decode(&data_types, data)
+            .map_err(|e| Error::Eyre(eyre!("dynamically decoding log data failed: {}", e)))?;
+        inputs.extend(data_types);
+        decoded_inputs.extend(data_values);
+    }
+
+    let name = format!("Event_{}", &selector.to_string()[2..10]);
+    let input_types = inputs.iter().map(|input| input.to_string()).collect::<Vec<_>>();
+    Ok(DecodedEvent {
+        address: None,
+        log_index: None,
+        topics: topics.to_vec(),
+        data: data.to_vec(),
+        signature: format!("{}({})", name, input_types.join(",")),
+        name,
+        indexed: (0..input_types.len()).map(|i| i < topics.len() - 1).collect(),
+        inputs: input_types,
+        decoded_inputs,
+    })
+}
+
+/// Each combination of `k` of the positions `0..n`, in lexicographic order.
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+
+    (0..n.saturating_sub(k - 1))
+        .flat_map(|first| {
+            combinations(n - first - 1, k - 1).into_iter().map(move |rest| {
+                std::iter::once(first).chain(rest.into_iter().map(|i| i + first + 1)).collect()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, b256, U256};
+    use alloy_dyn_abi::DynSolValue;
+
+    use super::*;
+
+    fn transfer() -> ResolvedLog {
+        ResolvedLog {
+            name: "Transfer".to_string(),
+            signature: "Transfer(address,address,uint256)".to_string(),
+            inputs: vec!["address".to_string(), "address".to_string(), "uint256".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(3, 0), vec![Vec::<usize>::new()]);
+        assert_eq!(combinations(3, 2), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+        assert!(combinations(1, 2).is_empty());
+    }
+
+    #[test]
+    fn test_decode_log_infers_indexed_inputs() {
+        let topics = [
+            b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+            b256!("000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045"),
+            b256!("000000000000000000000000000000000000000000000000000000000000dead"),
+        ];
+        let data = U256::from(1000).to_be_bytes_vec();
+
+        // an erc20 transfer indexes the sender and recipient, but not the amount
+        let event = decode_log(&topics, &data, vec![transfer()]).expect("failed to decode log");
+        assert_eq!(event.indexed_signature(), "Transfer(address indexed,address indexed,uint256)");
+        assert_eq!(
+            event.decoded_inputs[0],
+            DynSolValue::Address(address!("d8da6bf26964af9d7eed9e03e53415d37aa96045"))
+        );
+        assert_eq!(event.decoded_inputs[2], DynSolValue::Uint(U256::from(1000), 256));
+
+        // an erc721 transfer indexes the token id too, so it has no data
+        let topics = [topics[0], topics[1], topics[2], U256::from(7).into()];
+        let event = decode_log(&topics, &[], vec![transfer()]).expect("failed to decode log");
+        assert_eq!(event.indexed, vec![true, true, true]);
+        assert_eq!(event.decoded_inputs[2], DynSolValue::Uint(U256::from(7), 256));
+    }
+
+    #[test]
+    fn test_decode_log_skips_undecodable_indexes() {
+        // the first input can't be indexed, since its topic isn't a valid address
+        let log = ResolvedLog {
+            name: "Deposit".to_string(),
+            signature: "Deposit(address,uint256)".to_string(),
+            inputs: vec!["address".to_string(), "uint256".to_string()],
+        };
+        let topics = [B256::repeat_byte(1), B256::repeat_byte(0xff)];
+        let data = address!("d8da6bf26964af9d7eed9e03e53415d37aa96045").into_word().to_vec();
+        let event = decode_log(&topics, &data, vec![log]).expect("failed to decode log");
+        assert_eq!(event.indexed, vec![false, true]);
+    }
+
+    #[test]
+    fn test_decode_log_unresolved() {
+        let topics = [
+            b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+            b256!("000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045"),
+        ];
+        let data = U256::from(1000).to_be_bytes_vec();
+        let event = decode_log(&topics, &data, Vec::new()).expect("failed to decode log");
+        assert_eq!(event.name, "Event_ddf252ad");
+        assert_eq!(event.indexed, vec![true, false]);
+        assert_eq!(event.decoded_inputs.len(), 2);
+        assert!(decode_log(&[], &data, Vec::new()).is_err());
+    }
+}
@@ -1,30 +1,76 @@
-use hashbrown::HashSet;
-use std::time::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+mod constructor;
+pub(crate) mod event;
+#[cfg(not(target_arch = "wasm32"))]
+mod typed_data;
+
+pub use event::decode_log;
+#[cfg(not(target_arch = "wasm32"))]
+pub use event::decode_logs;
 
 use alloy::primitives::Selector;
-use alloy_dyn_abi::{DynSolCall, DynSolReturns, DynSolType};
+use alloy_dyn_abi::{DynSolCall, DynSolReturns, DynSolValue};
 use eyre::eyre;
 use heimdall_common::{
     ether::{
         signatures::{
-            cache_signatures_from_abi, score_signature, ResolveSelector, ResolvedFunction,
+            builtin_error, panic_reason, score_signature, ResolvedError, ResolvedFunction,
         },
         types::parse_function_parameters,
     },
-    utils::{io::logging::TraceFactory, strings::encode_hex},
+    utils::{
+        io::{logging::TraceFactory, types::Parameterize},
+        strings::encode_hex,
+    },
 };
-use heimdall_vm::core::types::{get_padding, get_potential_types_for_word, to_type, Padding};
 use tracing::{debug, info, trace, warn};
 
 use crate::{
     error::Error,
-    interfaces::{DecodeArgs, DecodeResult},
-    utils::{
-        decode_multicall, format_multicall_trace, is_multicall_pattern, parse_deployment_bytecode,
-        try_decode, try_decode_dynamic_parameter,
+    interfaces::DecodeResult,
+    utils::{guess_parameters, infer_parameter_types, infer_parameter_types_by_word, try_decode},
+};
+
+// fetching the target's calldata and resolving its selector isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::{
+        core::{
+            constructor::decode_constructor,
+            typed_data::{decode_typed_data, parse_typed_data, recover_permit},
+        },
+        interfaces::{DecodeArgs, DecodedBlob, DecodedTransaction},
+        utils::{
+            decode_nested_calls, decode_permit, decode_signed_transaction, explain,
+            find_nested_calls, format_multicall_trace, is_multicall_pattern,
+            parse_deployment_bytecode,
+        },
+    },
+    alloy::{
+        consensus::Transaction, primitives::TxHash, rpc::types::Transaction as RpcTransaction,
     },
+    alloy_json_abi::JsonAbi,
+    eyre::OptionExt,
+    heimdall_common::{
+        ether::{
+            beacon::get_blobs,
+            humanize::humanize_inputs,
+            rpc::{get_block_timestamp, get_transaction},
+            signatures::{
+                cache_signatures_from_abis, cache_signatures_from_json_abi, ResolveSelector,
+            },
+            sourcify::get_verified_contract_from_rpc,
+        },
+        resources::abi::get_verified_abi_from_rpc,
+        utils::{metrics::record_phase, offline::is_offline},
+    },
+    std::time::Instant,
 };
 
+// on wasm, the target is always raw calldata
+#[cfg(target_arch = "wasm32")]
+use {crate::interfaces::DecodeArgs, heimdall_common::utils::strings::decode_hex};
+
 /// Decodes EVM calldata into human-readable function signatures and parameters
 ///
 /// This function attempts to identify the function being called based on the function
@@ -39,6 +85,7 @@ use crate::{
 /// # Returns
 ///
 /// A DecodeResult containing the resolved function and its decoded parameters
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn decode(mut args: DecodeArgs) -> Result<DecodeResult, Error> {
     let start_time = Instant::now();
 
@@ -49,20 +96,85 @@ pub async fn decode(mut args: DecodeArgs) -> Result<DecodeResult, Error> {
         ));
     }
 
+    // use the verified ABI of the called contract from Sourcify or the chain's block explorer, if
+    // it is verified. signatures from a provided ABI are cached afterwards, so that they take
+    // precedence
+    if !args.skip_resolving {
+        if let Some(verified_abi) = get_verified_callee_abi(&args).await {
+            cache_signatures_from_json_abi(&verified_abi);
+        }
+    }
+
     // parse and cache signatures from the ABI, if provided
-    if let Some(abi_path) = args.abi.as_ref() {
-        cache_signatures_from_abi(abi_path.into())
-            .map_err(|e| Error::Eyre(eyre!("caching signatures from ABI failed: {}", e)))?;
+    cache_signatures_from_abis(&args.abi)
+        .map_err(|e| Error::Eyre(eyre!("caching signatures from ABI failed: {}", e)))?;
+
+    // decode the constructor arguments of the given creation bytecode, rather than the target
+    if args.creation_code.is_some() || args.creation_tx.is_some() {
+        let result = decode_constructor(&args).await?;
+        record_phase("decode", start_time.elapsed());
+        return Ok(result);
+    }
+
+    // decode the target as EIP-712 typed data, rather than calldata, if it's a JSON payload
+    if let Some(typed_data) = parse_typed_data(&args.target) {
+        let result = decode_typed_data(typed_data?, args.signature.as_deref())?;
+        record_phase("decode", start_time.elapsed());
+        return Ok(result);
     }
 
-    // get the bytecode from the target
+    // get the calldata from the target, keeping the transaction's envelope if it's a transaction
     let start_fetch_time = Instant::now();
-    let mut calldata = args
-        .get_calldata()
-        .await
-        .map_err(|e| Error::FetchError(format!("fetching target calldata failed: {e}")))?;
+    let transaction = match args.target.parse::<TxHash>() {
+        Ok(tx_hash) if !args.raw && !args.revert => {
+            Some(get_transaction(tx_hash, &args.rpc_url).await.map_err(|e| {
+                Error::FetchError(format!("fetching target transaction failed: {e}"))
+            })?)
+        }
+        _ => None,
+    };
+    let signed_transaction = match &transaction {
+        None if !args.raw && !args.revert && !args.constructor => {
+            decode_signed_transaction(&args.target)
+        }
+        _ => None,
+    };
+    if let Some(signed_transaction) = &signed_transaction {
+        info!("target is a signed transaction, recovered sender {}", signed_transaction.signer);
+    }
+    let mut calldata = match (&transaction, &signed_transaction) {
+        (Some(transaction), _) => transaction.input().to_vec(),
+        (None, Some(signed_transaction)) => signed_transaction.envelope.input().to_vec(),
+        (None, None) => args
+            .get_calldata()
+            .await
+            .map_err(|e| Error::FetchError(format!("fetching target calldata failed: {e}")))?,
+    };
     debug!("fetching target calldata took {:?}", start_fetch_time.elapsed());
 
+    // explain why the call reverted, if the target is revert data
+    if args.revert {
+        let error_selector = encode_hex(&calldata[0..calldata.len().min(4)]);
+        let potential_matches = match args.skip_resolving {
+            true => Vec::new(),
+            false => {
+                ResolvedError::resolve(&error_selector).await.ok().flatten().unwrap_or_default()
+            }
+        };
+        let mut result = decode_revert(&calldata, potential_matches)?;
+        info!("{}", result.revert_reason.as_deref().unwrap_or_default());
+
+        if args.explain {
+            result.explanation = explain(&result.decoded, true, &args.openai_api_key).await;
+            if let Some(explanation) = &result.explanation {
+                result._trace.add_message(1, line!(), vec![format!("explanation: {explanation}")]);
+                result._trace.br(1);
+            }
+        }
+        record_phase("decode", start_time.elapsed());
+        return Ok(result);
+    }
+
     if calldata.is_empty() {
         return Err(Error::Eyre(eyre!("calldata is empty. is this a value transfer?")));
     }
@@ -100,27 +212,245 @@ pub async fn decode(mut args: DecodeArgs) -> Result<DecodeResult, Error> {
         calldata = [selector, args].concat();
     }
 
-    // parse the two parts of calldata, inputs and selector
-    let function_selector = encode_hex(&calldata[0..4]);
-    let byte_args = &calldata[4..];
-
     // get the function signature possibilities
+    let function_selector = encode_hex(&calldata[0..4]);
     let start_resolve_time = Instant::now();
+    let builtin_error = builtin_error(&function_selector).map(|error| ResolvedFunction {
+        name: error.name,
+        signature: error.signature,
+        inputs: error.inputs,
+        decoded_inputs: None,
+    });
     let potential_matches = if !args.skip_resolving {
-        match ResolvedFunction::resolve(&function_selector).await {
-            Ok(Some(signatures)) => signatures,
-            _ => Vec::new(),
+        match builtin_error.clone() {
+            // the target is revert data, which can't be anything but the builtin error
+            Some(error) => vec![error],
+            None => match ResolvedFunction::resolve(&function_selector).await {
+                Ok(Some(signatures)) => signatures,
+                _ => Vec::new(),
+            },
         }
     } else {
         Vec::new()
     };
     debug!("resolving potential matches took {:?}", start_resolve_time.elapsed());
+    record_phase("decode.resolve", start_resolve_time.elapsed());
     if !potential_matches.is_empty() {
         info!("resolved {} potential function signatures", potential_matches.len());
     }
 
     // iterate over potential matches and attempt to decode the calldata with them
     let decode_start_time = Instant::now();
+    let mut result = decode_calldata(&calldata, potential_matches)?;
+    debug!("decoding calldata took {:?}", decode_start_time.elapsed());
+
+    // decode the arguments which are themselves calldata, such as the calls of a multicall, up to
+    // `--max-depth` calls deep
+    if let Some(decoded_inputs) =
+        result.decoded.decoded_inputs.as_ref().filter(|_| args.max_depth > 0)
+    {
+        if decoded_inputs.iter().any(is_multicall_pattern) {
+            debug!("Detected multicall pattern");
+        }
+
+        let nested_calls = find_nested_calls(decoded_inputs);
+        if !nested_calls.is_empty() {
+            let results = decode_nested_calls(nested_calls, &args).await;
+            info!("decoded {} nested calls", results.len());
+            result.multicall_results = Some(results);
+        }
+    }
+
+    debug!("decoding took {:?}", start_time.elapsed());
+    record_phase("decode", start_time.elapsed());
+
+    // reconstruct the typed data signed by the owner of a permit, and recover its signer
+    let (chain_id, callee) = match (&transaction, &signed_transaction) {
+        (Some(transaction), _) => (transaction.chain_id(), transaction.to()),
+        (None, Some(signed_transaction)) => {
+            (signed_transaction.envelope.chain_id(), signed_transaction.envelope.to())
+        }
+        (None, None) => (None, None),
+    };
+    let typed_data = match decode_permit(&calldata, chain_id, callee) {
+        Some(permit) => Some(
+            recover_permit(
+                &args,
+                permit,
+                transaction.as_ref().and_then(|transaction| transaction.block_number),
+            )
+            .await,
+        ),
+        None => None,
+    };
+
+    // annotate the inputs in a human-friendly way, scaling amounts of the called token
+    if args.humanize {
+        result.humanized = Some(
+            humanize_inputs(
+                &result.decoded.name,
+                result.decoded.decoded_inputs.as_deref().unwrap_or_default(),
+                callee,
+                &args.rpc_url,
+            )
+            .await,
+        );
+    }
+
+    let decoded_transaction = match (transaction, signed_transaction) {
+        (Some(transaction), _) => {
+            let mut decoded_transaction =
+                DecodedTransaction::new(&transaction, Some(transaction.inner.signer()));
+            if !args.beacon_url.is_empty() && !decoded_transaction.blob_versioned_hashes.is_empty()
+            {
+                match get_transaction_blobs(&args, &transaction).await {
+                    Ok(blobs) => {
+                        info!("fetched {} blobs from beacon node", blobs.len());
+                        decoded_transaction.blobs = blobs;
+                    }
+                    Err(e) => warn!("failed to fetch blobs from beacon node: {}", e),
+                }
+            }
+            Some(decoded_transaction)
+        }
+        (None, Some(signed_transaction)) => Some(signed_transaction.to_decoded_transaction()),
+        (None, None) => None,
+    };
+    if let Some(decoded_transaction) = decoded_transaction {
+        result._trace.add_message(1, line!(), decoded_transaction.trace_lines());
+        result._trace.br(1);
+        result.transaction = Some(decoded_transaction);
+    }
+    if let Some(typed_data) = typed_data {
+        result._trace.add_message(1, line!(), typed_data.trace_lines());
+        result._trace.br(1);
+        result.typed_data = Some(typed_data);
+    }
+    if let Some(humanized) = result.humanized.as_ref().filter(|h| h.iter().any(Option::is_some)) {
+        let lines = humanized
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| {
+                value.as_ref().map(|value| {
+                    format!("human {i}:{}{value}", " ".repeat(4 - i.to_string().len()))
+                })
+            })
+            .collect();
+        result._trace.add_message(1, line!(), lines);
+        result._trace.br(1);
+    }
+
+    if let Some(ref multicall_results) = result.multicall_results {
+        // Add the nested calls to trace
+        let decode_call = 1; // The main decode call is always index 1
+        format_multicall_trace(multicall_results, decode_call, &mut result._trace);
+    }
+
+    // explain the decoded calldata or revert data, if requested
+    if args.explain {
+        result.explanation = explain(&result.decoded, result.is_revert, &args.openai_api_key).await;
+        if let Some(explanation) = &result.explanation {
+            result._trace.add_message(1, line!(), vec![format!("explanation: {explanation}")]);
+            result._trace.br(1);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Decodes the calldata with the best of the potential matches for its selector, i.e. the
+/// resolved function signatures it could be calldata for. If none of them decode the calldata,
+/// its parameters are inferred from the raw calldata instead.
+///
+/// Unlike [`decode`], this doesn't fetch the target's calldata or resolve its selector, so it
+/// doesn't require network access.
+pub fn decode_calldata(
+    calldata: &[u8],
+    potential_matches: Vec<ResolvedFunction>,
+) -> Result<DecodeResult, Error> {
+    let selected_match = select_match(calldata, potential_matches)?;
+    let is_revert = builtin_error(&encode_hex(&calldata[0..4]))
+        .is_some_and(|error| error.signature == selected_match.signature);
+    if is_revert {
+        info!("target is revert data, decoded as builtin error '{}'", selected_match.signature);
+    }
+
+    let guessed = is_guessed(&selected_match);
+    build_result(selected_match, is_revert, guessed)
+}
+
+/// Decodes the revert data of a failed call with the best of the potential matches for its
+/// selector, i.e. the resolved error signatures it could be the revert data of, explaining why
+/// the call reverted in one line. `Error(string)` and `Panic(uint256)` are always recognized,
+/// and as with [`decode_calldata`], the parameters of unresolved custom errors are inferred.
+pub fn decode_revert(
+    revert_data: &[u8],
+    potential_matches: Vec<ResolvedError>,
+) -> Result<DecodeResult, Error> {
+    if revert_data.is_empty() {
+        return Err(Error::Eyre(eyre!(
+            "revert data is empty, so the call reverted without a reason"
+        )));
+    }
+
+    // the builtin errors are always recognized, so other errors with their selector are ignored
+    let potential_matches =
+        match builtin_error(&encode_hex(&revert_data[0..revert_data.len().min(4)])) {
+            Some(error) => vec![error],
+            None => potential_matches,
+        };
+    let potential_matches = potential_matches
+        .into_iter()
+        .map(|error| ResolvedFunction {
+            name: error.name,
+            signature: error.signature,
+            inputs: error.inputs,
+            decoded_inputs: None,
+        })
+        .collect();
+
+    let selected_match = select_match(revert_data, potential_matches)?;
+    let guessed = is_guessed(&selected_match);
+    build_result(selected_match, true, guessed)
+}
+
+/// Explains why a call reverted with the decoded error in one line, humanizing the code of a
+/// `Panic(uint256)`.
+pub fn revert_reason(error: &ResolvedFunction) -> String {
+    let inputs = error.decoded_inputs.as_deref().unwrap_or_default();
+    match (error.signature.as_str(), inputs) {
+        ("Error(string)", [DynSolValue::String(reason)]) => {
+            format!("reverted with reason \"{reason}\"")
+        }
+        ("Panic(uint256)", [DynSolValue::Uint(code, _)]) => {
+            match panic_reason(u64::try_from(*code).unwrap_or(u64::MAX)) {
+                Some(reason) => format!("panicked with code {code:#x}: {reason}"),
+                None => format!("panicked with unknown code {code:#x}"),
+            }
+        }
+        _ => format!(
+            "reverted with custom error {}({})",
+            error.name,
+            inputs.iter().map(|input| input.parameterize()).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+/// Selects the best of the potential matches which decode the calldata, inferring its
+/// parameters from the raw calldata if none of them do.
+fn select_match(
+    calldata: &[u8],
+    potential_matches: Vec<ResolvedFunction>,
+) -> Result<ResolvedFunction, Error> {
+    if calldata.len() < 4 {
+        return Err(Error::Eyre(eyre!("calldata is too short to contain a selector")));
+    }
+
+    // parse the two parts of calldata, inputs and selector
+    let function_selector = encode_hex(&calldata[0..4]);
+    let byte_args = &calldata[4..];
+
+    // iterate over potential matches and attempt to decode the calldata with them
     let mut matches = potential_matches
         .iter()
         .map(|potential_match| {
@@ -178,46 +508,12 @@ pub async fn decode(mut args: DecodeArgs) -> Result<DecodeResult, Error> {
         if matches.is_empty() {
             info!("falling back to raw calldata decoding: https://jbecker.dev/research/decoding-raw-calldata");
 
-            // we're going to build a Vec<DynSolType> of all possible types for each
-            let mut potential_inputs: Vec<DynSolType> = Vec::new();
-
-            // chunk in blocks of 32 bytes
-            let calldata_words = calldata[4..].chunks(32).map(|x| x.to_owned()).collect::<Vec<_>>();
-
-            // while calldata_words is not empty, iterate over itcar
-            let mut i = 0;
-            let mut covered_words = HashSet::new();
-            while covered_words.len() != calldata_words.len() {
-                let word = calldata_words[i].to_owned();
-
-                // check if the first word is abiencoded
-                if let Some(abi_encoded) = try_decode_dynamic_parameter(i, &calldata_words)? {
-                    let potential_type = to_type(&abi_encoded.ty);
-                    potential_inputs.push(potential_type);
-                    covered_words.extend(abi_encoded.coverages);
-                } else {
-                    let (_, mut potential_types) = get_potential_types_for_word(&word);
-
-                    // perform heuristics
-                    // - if we use right-padding, this is probably bytesN
-                    // - if we use left-padding, this is probably uintN or intN
-                    // - if we use no padding, this is probably bytes32
-                    match get_padding(&word) {
-                        Padding::Left => potential_types
-                            .retain(|t| t.starts_with("uint") || t.starts_with("address")),
-                        _ => potential_types
-                            .retain(|t| t.starts_with("bytes") || t.starts_with("string")),
-                    }
-
-                    let potential_type =
-                        to_type(potential_types.first().expect("potential types is empty"));
-
-                    potential_inputs.push(potential_type);
-                    covered_words.insert(i);
-                }
-
-                i += 1;
-            }
+            // infer nested tuples and arrays from the offsets in the calldata, falling back to
+            // inferring each word's type if the calldata can't be explained that way
+            let potential_inputs = match infer_parameter_types(&calldata[4..]) {
+                Some(potential_inputs) => potential_inputs,
+                None => infer_parameter_types_by_word(&calldata[4..])?,
+            };
 
             trace!(
                 "potential parameter inputs, ({:?})",
@@ -243,43 +539,126 @@ pub async fn decode(mut args: DecodeArgs) -> Result<DecodeResult, Error> {
     }
 
     let selected_match = matches.first().expect("matches is empty").clone();
-    debug!("decoding calldata took {:?}", decode_start_time.elapsed());
     info!("decoded {} bytes successfully", calldata.len());
+    Ok(selected_match)
+}
 
-    // Check for multicall pattern
-    let multicall_results = if let Some(decoded_inputs) = &selected_match.decoded_inputs {
-        let mut multicall_decoded = None;
-
-        for input in decoded_inputs {
-            if is_multicall_pattern(input) {
-                debug!("Detected multicall pattern");
-                match decode_multicall(input, &args).await {
-                    Ok(results) => {
-                        info!("Successfully decoded {} multicall items", results.len());
-                        multicall_decoded = Some(results);
-                        break;
-                    }
-                    Err(e) => {
-                        warn!("Failed to decode multicall: {:?}", e);
-                    }
-                }
-            }
-        }
+/// Whether the types of the selected match were guessed from the raw calldata, as none of the
+/// resolved signatures decoded it.
+fn is_guessed(selected_match: &ResolvedFunction) -> bool {
+    selected_match.name.starts_with("Unresolved_")
+}
 
-        multicall_decoded
-    } else {
-        None
-    };
+/// Builds the result for the selected match, explaining why the call reverted if it's revert
+/// data, and how confident each type is if they were `guessed`.
+fn build_result(
+    selected_match: ResolvedFunction,
+    is_revert: bool,
+    guessed: bool,
+) -> Result<DecodeResult, Error> {
+    let mut trace = TraceFactory::try_from(&selected_match)?;
 
-    debug!("decoding took {:?}", start_time.elapsed());
+    let guesses = guessed
+        .then(|| guess_parameters(selected_match.decoded_inputs.as_deref().unwrap_or_default()));
+    if let Some(guesses) = guesses.as_ref().filter(|guesses| !guesses.is_empty()) {
+        trace.add_message(
+            1,
+            line!(),
+            guesses.iter().enumerate().map(|(i, guess)| guess.trace_line(i)).collect(),
+        );
+        trace.br(1);
+    }
 
-    // Create trace factory with multicall support
-    let mut trace = TraceFactory::try_from(&selected_match)?;
-    if let Some(ref multicall_results) = multicall_results {
-        // Add multicall results to trace
-        let decode_call = 1; // The main decode call is always index 1
-        format_multicall_trace(multicall_results, decode_call, &mut trace);
+    let revert_reason = is_revert.then(|| revert_reason(&selected_match));
+    if let Some(reason) = &revert_reason {
+        trace.add_message(1, line!(), vec![format!("reason:    {reason}")]);
+        trace.br(1);
     }
 
-    Ok(DecodeResult { decoded: selected_match, multicall_results, _trace: trace })
+    Ok(DecodeResult {
+        decoded: selected_match,
+        guesses,
+        multicall_results: None,
+        is_revert,
+        revert_reason,
+        transaction: None,
+        typed_data: None,
+        explanation: None,
+        humanized: None,
+        _trace: trace,
+    })
+}
+
+/// Fetch the blobs carried by the blob transaction from the beacon node at `--beacon-url`.
+#[cfg(not(target_arch = "wasm32"))]
+async fn get_transaction_blobs(
+    args: &DecodeArgs,
+    transaction: &RpcTransaction,
+) -> eyre::Result<Vec<DecodedBlob>> {
+    let block_number =
+        transaction.block_number.ok_or_eyre("transaction hasn't been included in a block")?;
+    let block_timestamp = get_block_timestamp(block_number, &args.rpc_url).await?;
+    let versioned_hashes = transaction.blob_versioned_hashes().unwrap_or_default();
+
+    Ok(get_blobs(&args.beacon_url, block_timestamp, versioned_hashes)
+        .await?
+        .into_iter()
+        .map(DecodedBlob::from)
+        .collect())
+}
+
+/// Decodes the target, which is raw calldata on wasm, where fetching the target's calldata and
+/// resolving its selector isn't available. Its arguments are inferred from the raw calldata, as if
+/// resolving were skipped.
+#[cfg(target_arch = "wasm32")]
+pub async fn decode(args: DecodeArgs) -> Result<DecodeResult, Error> {
+    let calldata = decode_hex(&args.target)
+        .map_err(|e| Error::FetchError(format!("parsing target calldata failed: {e}")))?;
+    if calldata.is_empty() {
+        return Err(Error::Eyre(eyre!("calldata is empty. is this a value transfer?")));
+    }
+
+    decode_calldata(&calldata, Vec::new())
+}
+
+/// Look up the verified ABI of the contract called by the target transaction on Sourcify, or
+/// otherwise on the chain's block explorer, if the target is a transaction hash or a signed
+/// transaction.
+#[cfg(not(target_arch = "wasm32"))]
+async fn get_verified_callee_abi(args: &DecodeArgs) -> Option<JsonAbi> {
+    if args.raw || args.revert || args.rpc_url.is_empty() || is_offline() {
+        return None;
+    }
+
+    let callee = match args.target.parse::<TxHash>() {
+        Ok(tx_hash) => get_transaction(tx_hash, &args.rpc_url).await.ok()?.inner.to()?,
+        Err(_) => decode_signed_transaction(&args.target)?.envelope.to()?,
+    };
+    if !args.no_sourcify {
+        let verified_contract =
+            get_verified_contract_from_rpc(callee, &args.rpc_url).await.unwrap_or_else(|e| {
+                warn!("failed to look up called contract on sourcify: {}", e);
+                None
+            });
+        if let Some(verified_contract) = verified_contract {
+            info!(
+                "found a {} sourcify match for the called contract, using its verified abi",
+                verified_contract.match_type
+            );
+            return Some(verified_contract.abi);
+        }
+    }
+
+    get_verified_abi_from_rpc(
+        callee,
+        &args.rpc_url,
+        &args.explorer_api_url,
+        &args.etherscan_api_key,
+    )
+    .await
+    .unwrap_or_else(|e| {
+        warn!("failed to fetch the verified abi of the called contract: {}", e);
+        None
+    })
+    .inspect(|_| info!("found a verified abi for the called contract, using it for naming"))
 }
@@ -0,0 +1,190 @@
+use std::path::Path;
+
+use alloy::{
+    eips::BlockId,
+    primitives::{keccak256, Signature, B256, U256},
+};
+use alloy_dyn_abi::{DynSolValue, TypedData};
+use eyre::eyre;
+use heimdall_common::{
+    ether::{
+        rpc::{call, chain_id},
+        signatures::ResolvedFunction,
+    },
+    utils::{offline::is_offline, strings::decode_hex},
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    core::build_result,
+    error::Error,
+    interfaces::{DecodeArgs, DecodeResult, DecodedTypedData},
+    utils::Permit,
+};
+
+/// Parses the target as an EIP-712 typed data payload, as given to `eth_signTypedData_v4`, or a
+/// `.json` file containing one. Returns `None` if the target isn't typed data, in which case it's
+/// decoded as calldata.
+pub(crate) fn parse_typed_data(target: &str) -> Option<Result<TypedData, Error>> {
+    let payload = match target.trim_start().starts_with('{') {
+        true => target.to_string(),
+        false if target.ends_with(".json") && Path::new(target).is_file() => {
+            match std::fs::read_to_string(target) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    return Some(Err(Error::Eyre(eyre!(
+                        "failed to read typed data from '{}': {}",
+                        target,
+                        e
+                    ))))
+                }
+            }
+        }
+        false => return None,
+    };
+
+    Some(
+        serde_json::from_str(&payload)
+            .map_err(|e| Error::Eyre(eyre!("invalid EIP-712 typed data: {}", e))),
+    )
+}
+
+/// Decodes EIP-712 typed data, computing its digest and recovering the signer of the
+/// `--signature` over it, if given. The fields of the message are displayed like the inputs of
+/// calldata, so that the result can be read the same way.
+pub(crate) fn decode_typed_data(
+    typed_data: TypedData,
+    signature: Option<&str>,
+) -> Result<DecodeResult, Error> {
+    // coercing the message to its primary type validates it against the types of the payload
+    let message = typed_data
+        .coerce()
+        .map_err(|e| Error::Eyre(eyre!("the message doesn't match its types: {}", e)))?;
+    let fields = match into_tuple(message) {
+        DynSolValue::Tuple(fields) => fields,
+        _ => return Err(Error::Eyre(eyre!("the primary type of typed data must be a struct"))),
+    };
+    let inputs = fields
+        .iter()
+        .map(|field| field.sol_type_name().unwrap_or_default().to_string())
+        .collect::<Vec<_>>();
+
+    let signature = signature.map(parse_signature).transpose()?;
+    let domain_separator = typed_data.domain.separator();
+    let primary_type = typed_data.primary_type.clone();
+    let decoded_typed_data =
+        DecodedTypedData::new(None, typed_data, Some(domain_separator), signature.as_ref(), None);
+    if let Some(signer) = decoded_typed_data.signer {
+        info!("recovered signer {} of the typed data", signer);
+    }
+
+    let mut result = build_result(
+        ResolvedFunction {
+            name: primary_type.clone(),
+            signature: format!("{}({})", primary_type, inputs.join(",")),
+            inputs,
+            decoded_inputs: Some(fields),
+        },
+        false,
+        false,
+    )?;
+    result._trace.add_message(1, line!(), decoded_typed_data.trace_lines());
+    result._trace.br(1);
+    result.typed_data = Some(decoded_typed_data);
+    Ok(result)
+}
+
+/// Recovers the signer of a permit recognized in the calldata, completing its domain and message
+/// from the chain where they aren't part of the calldata. A token's permit is signed over the
+/// token's `DOMAIN_SEPARATOR()` and the owner's nonce before the permit was used, i.e. as of the
+/// block before `block_number` if the permit was mined.
+pub(crate) async fn recover_permit(
+    args: &DecodeArgs,
+    mut permit: Permit,
+    block_number: Option<u64>,
+) -> DecodedTypedData {
+    let mut domain_separator = None;
+    if !args.rpc_url.is_empty() && !is_offline() {
+        if permit.typed_data.domain.chain_id.is_none() {
+            match chain_id(&args.rpc_url).await {
+                Ok(chain_id) => permit.typed_data.domain.chain_id = Some(U256::from(chain_id)),
+                Err(e) => warn!("failed to fetch the chain id of the permit: {}", e),
+            }
+        }
+
+        if let Some(token) =
+            permit.typed_data.domain.verifying_contract.filter(|_| permit.is_token_permit())
+        {
+            let block =
+                block_number.map(|block_number| BlockId::number(block_number.saturating_sub(1)));
+            domain_separator = call(token, &selector("DOMAIN_SEPARATOR()"), block, &args.rpc_url)
+                .await
+                .inspect_err(|e| warn!("failed to fetch the domain separator of {}: {}", token, e))
+                .ok()
+                .filter(|result| result.len() >= 32)
+                .map(|result| B256::from_slice(&result[..32]));
+
+            if permit.is_missing_nonce() {
+                let calldata =
+                    [&selector("nonces(address)")[..], &[0; 12], permit.owner.as_slice()].concat();
+                match call(token, &calldata, block, &args.rpc_url).await {
+                    Ok(result) if result.len() >= 32 => {
+                        permit.set_nonce(U256::from_be_slice(&result[..32]))
+                    }
+                    Ok(_) => warn!("{} returned an invalid nonce for {}", token, permit.owner),
+                    Err(e) => warn!("failed to fetch the nonce of {}: {}", permit.owner, e),
+                }
+            }
+        }
+    }
+
+    let domain_separator = domain_separator.or_else(|| permit.permit2_domain_separator());
+    let decoded_typed_data = DecodedTypedData::new(
+        Some(permit.standard),
+        permit.typed_data,
+        domain_separator,
+        Some(&permit.signature),
+        Some(permit.owner),
+    );
+    match decoded_typed_data.is_valid() {
+        Some(true) => info!("the {} permit is signed by its owner", permit.standard),
+        Some(false) => warn!("the {} permit isn't signed by its owner", permit.standard),
+        None => debug!(
+            "couldn't compute the digest of the {} permit. decode its transaction, or pass --rpc-url",
+            permit.standard
+        ),
+    }
+    decoded_typed_data
+}
+
+/// Parses the `--signature` over typed data, which is either 65 bytes, or 64 bytes in its
+/// EIP-2098 compact form.
+fn parse_signature(signature: &str) -> Result<Signature, Error> {
+    let signature = decode_hex(signature)?;
+    match signature.len() {
+        64 => Ok(Signature::from_erc2098(&signature)),
+        _ => Signature::from_raw(&signature)
+            .map_err(|e| Error::Eyre(eyre!("invalid signature: {}", e))),
+    }
+}
+
+/// The selector of the function with the given signature.
+fn selector(signature: &str) -> [u8; 4] {
+    keccak256(signature.as_bytes())[0..4].try_into().expect("selector is 4 bytes")
+}
+
+/// Converts the structs within the value to tuples, which can be displayed like calldata.
+fn into_tuple(value: DynSolValue) -> DynSolValue {
+    match value {
+        DynSolValue::CustomStruct { tuple, .. } | DynSolValue::Tuple(tuple) => {
+            DynSolValue::Tuple(tuple.into_iter().map(into_tuple).collect())
+        }
+        DynSolValue::Array(elements) => {
+            DynSolValue::Array(elements.into_iter().map(into_tuple).collect())
+        }
+        DynSolValue::FixedArray(elements) => {
+            DynSolValue::FixedArray(elements.into_iter().map(into_tuple).collect())
+        }
+        value => value,
+    }
+}
@@ -1,9 +1,11 @@
 use clap::Parser;
 use derive_builder::Builder;
-use eyre::Result;
-use heimdall_common::ether::calldata::get_calldata_from_target;
 use heimdall_config::parse_url_arg;
 
+// fetching the target's calldata isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {eyre::Result, heimdall_common::ether::calldata::get_calldata_from_target};
+
 #[derive(Debug, Clone, Parser, Builder)]
 #[clap(
     about = "Decodes raw/arbitrary calldata into readable types",
@@ -15,12 +17,28 @@ use heimdall_config::parse_url_arg;
 /// This struct contains all the configuration parameters needed to decode
 /// calldata into human-readable function signatures and parameters.
 pub struct DecodeArgs {
-    /// The target to decode, either a transaction hash or string of bytes.
-    #[clap(required = true)]
+    /// The target to decode, either a transaction hash, a raw signed transaction, EIP-712 typed
+    /// data as JSON or a `.json` file containing it, or string of bytes.
+    #[clap(
+        required_unless_present_any = ["batch", "creation_code", "creation_tx"],
+        default_value = "",
+        hide_default_value = true
+    )]
     pub target: String,
 
+    /// A file listing the targets to decode, one per line, or '-' to read them from stdin. Each
+    /// target's result is printed to stdout as a line of JSON as soon as it's decoded, so that
+    /// the output can be piped into other tools.
+    #[clap(long, conflicts_with = "target")]
+    pub batch: Option<String>,
+
+    /// The number of targets to decode concurrently with '--batch'.
+    #[clap(long, default_value = "4", hide_default_value = true)]
+    pub threads: usize,
+
     /// The RPC provider to use for fetching target calldata.
-    /// This can be an explicit URL or a reference to a MESC endpoint.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
     #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
     pub rpc_url: String,
 
@@ -28,10 +46,24 @@ pub struct DecodeArgs {
     #[clap(long, default_value = "", hide_default_value = true)]
     pub openai_api_key: String,
 
-    /// Whether to explain the decoded calldata using OpenAI.
+    /// Your Etherscan API key, used for fetching the verified ABI of the called contract.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub etherscan_api_key: String,
+
+    /// An Etherscan-compatible block explorer API, such as a Blockscout instance's `/api`, to
+    /// fetch the verified ABI of the called contract from. Defaults to the chain's explorer.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub explorer_api_url: String,
+
+    /// Whether to explain the decoded calldata or revert data using OpenAI.
     #[clap(long)]
     pub explain: bool,
 
+    /// Whether to annotate decoded values in a human-friendly way, such as token amounts scaled
+    /// by the called token's decimals, UNIX timestamps, durations and `type(uintN).max`.
+    #[clap(long)]
+    pub humanize: bool,
+
     /// When prompted, always select the default value.
     #[clap(long, short)]
     pub default: bool,
@@ -48,20 +80,83 @@ pub struct DecodeArgs {
     #[clap(long = "skip-resolving")]
     pub skip_resolving: bool,
 
+    /// Creation bytecode, or a file containing it, whose appended constructor arguments are
+    /// extracted and decoded instead of the target. Their types are taken from the constructor
+    /// of a provided '--abi', or guessed otherwise.
+    #[clap(
+        long,
+        value_name = "HEX|FILE",
+        conflicts_with_all = ["constructor", "revert", "log", "all_logs", "batch"]
+    )]
+    pub creation_code: Option<String>,
+
+    /// The hash of a transaction which deployed a contract, whose constructor arguments are
+    /// extracted from its creation bytecode and decoded instead of the target.
+    #[clap(
+        long,
+        value_name = "HASH",
+        conflicts_with_all = ["creation_code", "constructor", "revert", "log", "all_logs", "batch"]
+    )]
+    pub creation_tx: Option<String>,
+
+    /// The signature over EIP-712 typed data given as the target, whose signer is recovered from
+    /// the typed data's digest.
+    #[clap(long, value_name = "HEX")]
+    pub signature: Option<String>,
+
     /// Whether to treat the target as a raw calldata string. Useful if the target is exactly 32
-    /// bytes.
+    /// bytes, or is a valid signed transaction.
     #[clap(long)]
     pub raw: bool,
 
-    /// Path to an optional ABI file to use for resolving errors, functions, and events.
-    #[clap(long, short, default_value = None, hide_default_value = true)]
-    pub abi: Option<String>,
+    /// Whether the target is the revert data of a failed call, explaining why it reverted with
+    /// `Error(string)`, `Panic(uint256)` or a custom error.
+    #[clap(long, conflicts_with_all = ["constructor", "log", "all_logs"])]
+    pub revert: bool,
+
+    /// How many calls deep to decode arguments which are themselves calldata, such as the calls
+    /// of a multicall, a Gnosis Safe transaction or a Timelock proposal. 0 disables decoding
+    /// nested calls.
+    #[clap(long = "max-depth", default_value = "4")]
+    pub max_depth: usize,
+
+    /// Path to an ABI file to use for resolving errors, functions, and events, which may be given
+    /// more than once. Signatures in the ABIs take precedence over resolved ones, so only
+    /// selectors which aren't in any of them are resolved or guessed.
+    #[clap(long, short, value_name = "ABI")]
+    pub abi: Vec<String>,
+
+    /// Whether to skip looking up the called contract on Sourcify. By default, if the target is a
+    /// transaction to a verified contract, its verified ABI is used to resolve the function.
+    #[clap(long = "no-sourcify")]
+    pub no_sourcify: bool,
+
+    /// Decode the target as the data of an event log with these comma-separated topics, the
+    /// first of which is the event's selector.
+    #[clap(
+        long = "log",
+        value_name = "TOPICS",
+        value_delimiter = ',',
+        conflicts_with = "all_logs"
+    )]
+    pub log: Vec<String>,
+
+    /// Decode every event log emitted by the target transaction.
+    #[clap(long = "all-logs")]
+    pub all_logs: bool,
+
+    /// A beacon node API to fetch the blobs of the target blob transaction from, decoding their
+    /// contents. Blobs are only kept by beacon nodes for around 18 days.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub beacon_url: String,
 
-    /// The output directory to write the output to or 'print' to print to the console
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
     #[clap(long = "output", short = 'o', default_value = "print", hide_default_value = true)]
     pub output: String,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl DecodeArgs {
     /// Retrieves the calldata from the specified target
     ///
@@ -71,7 +166,7 @@ impl DecodeArgs {
     /// # Returns
     /// The raw calldata as a vector of bytes
     pub async fn get_calldata(&self) -> Result<Vec<u8>> {
-        get_calldata_from_target(&self.target, self.raw, &self.rpc_url).await
+        get_calldata_from_target(&self.target, self.raw || self.revert, &self.rpc_url).await
     }
 }
 
@@ -80,15 +175,29 @@ impl DecodeArgsBuilder {
     pub fn new() -> Self {
         Self {
             target: Some(String::new()),
+            batch: Some(None),
+            threads: Some(4),
             rpc_url: Some(String::new()),
             openai_api_key: Some(String::new()),
+            etherscan_api_key: Some(String::new()),
+            explorer_api_url: Some(String::new()),
             explain: Some(false),
+            humanize: Some(false),
             default: Some(true),
             constructor: Some(false),
+            creation_code: Some(None),
+            creation_tx: Some(None),
+            signature: Some(None),
             truncate_calldata: Some(false),
             skip_resolving: Some(false),
             raw: Some(false),
-            abi: Some(None),
+            revert: Some(false),
+            max_depth: Some(4),
+            abi: Some(Vec::new()),
+            no_sourcify: Some(false),
+            log: Some(Vec::new()),
+            all_logs: Some(false),
+            beacon_url: Some(String::new()),
             output: Some(String::from("print")),
         }
     }
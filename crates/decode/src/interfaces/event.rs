@@ -0,0 +1,151 @@
+use alloy::primitives::{Address, B256};
+use alloy_dyn_abi::DynSolValue;
+use heimdall_common::{
+    ether::types::DynSolValueExt,
+    utils::{hex::ToLowerHex, io::logging::TraceFactory, strings::encode_hex},
+};
+use serde_json::{json, Value};
+
+#[cfg(not(target_arch = "wasm32"))]
+use heimdall_common::utils::io::types::display;
+
+/// An event log, decoded with the best of the resolved signatures for its selector, or with
+/// types inferred from its topics and data if none of them decode it.
+#[derive(Debug, Clone)]
+pub struct DecodedEvent {
+    /// The contract which emitted the log, if it was fetched from a transaction
+    pub address: Option<Address>,
+    /// The index of the log in its block, if it was fetched from a transaction
+    pub log_index: Option<u64>,
+    /// The log's topics, the first of which is the event's selector
+    pub topics: Vec<B256>,
+    /// The log's data
+    pub data: Vec<u8>,
+    /// The name of the event, or `Event_<selector>` if it wasn't resolved
+    pub name: String,
+    /// The signature of the event. For example, `Transfer(address,address,uint256)`
+    pub signature: String,
+    /// The type of each of the event's inputs
+    pub inputs: Vec<String>,
+    /// Whether each of the event's inputs is indexed, i.e. decoded from the log's topics rather
+    /// than its data. Indexed dynamic types are only logged as the keccak256 hash of their value,
+    /// so they're decoded as `bytes32`
+    pub indexed: Vec<bool>,
+    /// The decoded value of each of the event's inputs
+    pub decoded_inputs: Vec<DynSolValue>,
+}
+
+impl DecodedEvent {
+    /// The signature of the event, marking which of its inputs are indexed. For example,
+    /// `Transfer(address indexed,address indexed,uint256)`
+    pub fn indexed_signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.inputs
+                .iter()
+                .zip(&self.indexed)
+                .map(|(input, indexed)| match indexed {
+                    true => format!("{input} indexed"),
+                    false => input.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    /// Converts the decoded event to JSON
+    pub fn to_json(&self) -> Value {
+        json!({
+            "address": self.address.map(|address| address.to_string()),
+            "log_index": self.log_index,
+            "topics": self.topics.iter().map(|topic| topic.to_lower_hex()).collect::<Vec<_>>(),
+            "data": format!("0x{}", encode_hex(&self.data)),
+            "name": self.name,
+            "signature": self.signature,
+            "inputs": self
+                .inputs
+                .iter()
+                .zip(&self.indexed)
+                .enumerate()
+                .map(|(i, (input, indexed))| json!({
+                    "name": format!("arg{i}"),
+                    "type": input,
+                    "indexed": indexed,
+                }))
+                .collect::<Vec<_>>(),
+            "decoded_inputs": self
+                .decoded_inputs
+                .iter()
+                .map(|input| input.serialize())
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Result of a successful `--log` or `--all-logs` decode operation
+#[derive(Debug, Clone)]
+pub struct DecodeLogsResult {
+    /// Each of the decoded event logs, in the order they were emitted
+    pub events: Vec<DecodedEvent>,
+    pub(crate) _trace: TraceFactory,
+}
+
+impl DecodeLogsResult {
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Builds the result for the decoded events, along with the trace displaying them
+    pub(crate) fn new(events: Vec<DecodedEvent>) -> Self {
+        let mut trace = TraceFactory::default();
+        let decode_call = trace.add_call(
+            0,
+            line!(),
+            "heimdall".to_string(),
+            "decode".to_string(),
+            vec![],
+            "()".to_string(),
+        );
+        trace.br(decode_call);
+
+        for event in &events {
+            trace.add_message(
+                decode_call,
+                line!(),
+                vec![format!("event:     {}", event.indexed_signature())],
+            );
+            if let Some(address) = event.address {
+                trace.add_message(decode_call, line!(), vec![format!("address:   {address}")]);
+            }
+
+            for (i, input) in event.decoded_inputs.iter().enumerate() {
+                let mut decoded_input_as_message = display(vec![input.to_owned()], "           ");
+                if decoded_input_as_message.is_empty() {
+                    break;
+                }
+
+                let label = match i {
+                    0 => format!("input {i}:"),
+                    _ => format!("      {i}:"),
+                };
+                decoded_input_as_message[0] = format!(
+                    "{label}{}{}",
+                    " ".repeat(4 - i.to_string().len()),
+                    decoded_input_as_message[0].replacen("           ", "", 1)
+                );
+                trace.add_message(decode_call, line!(), decoded_input_as_message);
+            }
+            trace.br(decode_call);
+        }
+
+        Self { events, _trace: trace }
+    }
+
+    /// Displays the decoded events in a formatted way
+    pub fn display(&self) {
+        self._trace.display();
+    }
+
+    /// Converts the decoded events to JSON
+    pub fn to_json(&self) -> Value {
+        json!({ "events": self.events.iter().map(DecodedEvent::to_json).collect::<Vec<_>>() })
+    }
+}
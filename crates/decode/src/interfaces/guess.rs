@@ -0,0 +1,72 @@
+use serde_json::{json, Value};
+
+/// The type guessed for a parameter of calldata whose selector wasn't resolved, along with how
+/// confident the guess is and the other types the parameter could plausibly be.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterGuess {
+    /// The type the parameter was decoded as
+    pub ty: String,
+    /// How likely the guessed type is, from 0 to 1
+    pub confidence: f64,
+    /// The other plausible interpretations of the parameter, most likely first
+    pub alternatives: Vec<Interpretation>,
+}
+
+/// A plausible interpretation of a parameter as another type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interpretation {
+    /// The type the parameter could be
+    pub ty: String,
+    /// The parameter's value as that type
+    pub value: String,
+    /// How likely the interpretation is, from 0 to 1
+    pub confidence: f64,
+}
+
+impl ParameterGuess {
+    /// The line of the trace which displays the guess for the `i`th parameter
+    pub(crate) fn trace_line(&self, i: usize) -> String {
+        let mut line = format!(
+            "guess {i}:{}{} ({})",
+            " ".repeat(4 - i.to_string().len()),
+            self.ty,
+            percentage(self.confidence)
+        );
+        for alternative in &self.alternatives {
+            line.push_str(&format!(
+                ", or {} {} ({})",
+                alternative.ty,
+                alternative.value,
+                percentage(alternative.confidence)
+            ));
+        }
+        line
+    }
+
+    /// Converts the guess to JSON
+    pub fn to_json(&self) -> Value {
+        json!({
+            "type": self.ty,
+            "confidence": round(self.confidence),
+            "alternatives": self
+                .alternatives
+                .iter()
+                .map(|alternative| json!({
+                    "type": alternative.ty,
+                    "value": alternative.value,
+                    "confidence": round(alternative.confidence),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// The confidence as a whole percentage
+fn percentage(confidence: f64) -> String {
+    format!("{:.0}%", confidence * 100.0)
+}
+
+/// The confidence rounded to two decimal places, so that JSON output stays readable
+fn round(confidence: f64) -> f64 {
+    (confidence * 100.0).round() / 100.0
+}
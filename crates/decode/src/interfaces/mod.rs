@@ -1,6 +1,14 @@
 mod args;
+mod event;
+mod guess;
 mod result;
+mod transaction;
+mod typed_data;
 
 // re-export the public interface
 pub use args::{DecodeArgs, DecodeArgsBuilder};
+pub use event::{DecodeLogsResult, DecodedEvent};
+pub use guess::{Interpretation, ParameterGuess};
 pub use result::DecodeResult;
+pub use transaction::{DecodedBlob, DecodedTransaction};
+pub use typed_data::DecodedTypedData;
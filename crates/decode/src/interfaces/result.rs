@@ -1,13 +1,21 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::Address;
+use alloy_dyn_abi::DynSolValue;
 use heimdall_common::{
     ether::{
+        labels::lookup_label,
         signatures::ResolvedFunction,
         types::{parse_function_parameters, to_abi_string, to_components, DynSolValueExt},
     },
     utils::{io::logging::TraceFactory, strings::encode_hex},
 };
-use serde_json::json;
+use serde_json::{json, Value};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    interfaces::{DecodedTransaction, DecodedTypedData, ParameterGuess},
+};
 
 #[derive(Debug, Clone)]
 /// Result of a successful decode operation
@@ -17,8 +25,27 @@ use crate::error::Error;
 pub struct DecodeResult {
     /// The resolved function with its decoded inputs
     pub decoded: ResolvedFunction,
+    /// How confident the guessed type of each parameter is, along with the other types it could
+    /// be, if the selector wasn't resolved and the types were inferred from the raw calldata
+    pub guesses: Option<Vec<ParameterGuess>>,
     /// Multicall results if detected
     pub multicall_results: Option<Vec<crate::utils::MulticallDecoded>>,
+    /// Whether the target was revert data, i.e. a builtin `Error(string)` or `Panic(uint256)`,
+    /// rather than calldata
+    pub is_revert: bool,
+    /// A one-line explanation of why the call reverted, if the target was revert data
+    pub revert_reason: Option<String>,
+    /// The envelope of the transaction, such as its fees, access list and blobs, if the target
+    /// was a transaction
+    pub transaction: Option<DecodedTransaction>,
+    /// The EIP-712 typed data, if the target was typed data or the calldata of a permit, along
+    /// with the signer recovered from its signature
+    pub typed_data: Option<DecodedTypedData>,
+    /// An explanation of the decoded calldata or revert data, if requested with `--explain`
+    pub explanation: Option<String>,
+    /// A human-friendly rendering of each input, or `None` for inputs without one, if requested
+    /// with `--humanize`
+    pub humanized: Option<Vec<Option<String>>>,
     pub(crate) _trace: TraceFactory,
 }
 
@@ -28,79 +55,56 @@ impl DecodeResult {
         self._trace.display();
     }
 
-    /// Converts the decode result to JSON, including multicall results if present
+    /// Converts the decode result to JSON, including its nested calls if present
     pub fn to_json(&self) -> Result<String, Error> {
-        // Helper to convert inputs to ABI format with components
-        let inputs_to_abi_format = |signature: &str| -> Vec<serde_json::Value> {
-            match parse_function_parameters(signature) {
-                Ok(types) => {
-                    types
-                        .iter()
-                        .enumerate()
-                        .map(|(i, sol_type)| {
-                            let mut param = json!({
-                                "name": format!("arg{}", i),
-                                "type": to_abi_string(sol_type)
-                            });
-
-                            // Add components if it's a tuple type
-                            let components = to_components(sol_type);
-                            if !components.is_empty() {
-                                param["components"] = json!(components
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(j, comp)| {
-                                        let mut comp_json = json!({
-                                            "name": format!("arg{}", j),
-                                            "type": comp.ty.clone()
-                                        });
-
-                                        // Recursively add nested components
-                                        if !comp.components.is_empty() {
-                                            comp_json["components"] = json!(comp
-                                                .components
-                                                .iter()
-                                                .enumerate()
-                                                .map(|(k, nested)| {
-                                                    json!({
-                                                        "name": format!("arg{}", k),
-                                                        "type": nested.ty.clone()
-                                                    })
-                                                })
-                                                .collect::<Vec<_>>());
-                                        }
-
-                                        comp_json
-                                    })
-                                    .collect::<Vec<_>>());
-                            }
-
-                            param
-                        })
-                        .collect()
-                }
-                Err(_) => {
-                    // Fallback to simple format if parsing fails
-                    vec![]
-                }
-            }
-        };
+        serde_json::to_string_pretty(&self.to_value())
+            .map_err(|e| Error::Eyre(eyre::eyre!("Failed to serialize to JSON: {}", e)))
+    }
 
+    /// The decode result as a JSON value, including each of its nested calls, and the calls
+    /// nested within them
+    fn to_value(&self) -> Value {
         let mut result = json!({
             "name": self.decoded.name,
             "signature": self.decoded.signature,
             "inputs": inputs_to_abi_format(&self.decoded.signature),
-            "decoded_inputs": if let Some(decoded_inputs) = &self.decoded.decoded_inputs {
-                decoded_inputs
-                    .iter()
-                    .map(|input| input.serialize())
-                    .collect::<Vec<_>>()
-            } else {
-                vec![]
-            }
+            "decoded_inputs": self
+                .decoded
+                .decoded_inputs
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|input| input.serialize())
+                .collect::<Vec<_>>(),
         });
 
-        // Add multicall results if present
+        if let Some(guesses) = &self.guesses {
+            result["guesses"] = guesses.iter().map(ParameterGuess::to_json).collect();
+        }
+        if self.is_revert {
+            result["revert"] = json!(true);
+        }
+        if let Some(revert_reason) = &self.revert_reason {
+            result["revert_reason"] = json!(revert_reason);
+        }
+        if let Some(transaction) = &self.transaction {
+            result["transaction"] = transaction.to_json();
+        }
+        if let Some(typed_data) = &self.typed_data {
+            result["typed_data"] = typed_data.to_json();
+        }
+        if let Some(explanation) = &self.explanation {
+            result["explanation"] = json!(explanation);
+        }
+        if let Some(humanized) = &self.humanized {
+            result["humanized"] = json!(humanized);
+        }
+        let labels = self.labels();
+        if !labels.is_empty() {
+            result["labels"] = json!(labels);
+        }
+
+        // Add nested calls if present
         if let Some(multicall_results) = &self.multicall_results {
             let mut multicalls = vec![];
 
@@ -114,19 +118,7 @@ impl DecodeResult {
 
                 // Add decoded result if available
                 if let Some(decoded) = &mc_result.decoded {
-                    mc_json["decoded"] = json!({
-                        "name": decoded.decoded.name,
-                        "signature": decoded.decoded.signature,
-                        "inputs": inputs_to_abi_format(&decoded.decoded.signature),
-                        "decoded_inputs": if let Some(decoded_inputs) = &decoded.decoded.decoded_inputs {
-                            decoded_inputs
-                                .iter()
-                                .map(|input| input.serialize())
-                                .collect::<Vec<_>>()
-                        } else {
-                            vec![]
-                        }
-                    });
+                    mc_json["decoded"] = decoded.to_value();
                 }
 
                 multicalls.push(mc_json);
@@ -135,7 +127,95 @@ impl DecodeResult {
             result["multicall_results"] = json!(multicalls);
         }
 
-        serde_json::to_string_pretty(&result)
-            .map_err(|e| Error::Eyre(eyre::eyre!("Failed to serialize to JSON: {}", e)))
+        result
+    }
+
+    /// The labels of the labeled addresses among the decoded inputs and the transaction's
+    /// sender and recipient
+    fn labels(&self) -> BTreeMap<String, String> {
+        let mut addresses = Vec::new();
+        for input in self.decoded.decoded_inputs.as_deref().unwrap_or_default() {
+            collect_addresses(input, &mut addresses);
+        }
+        if let Some(transaction) = &self.transaction {
+            addresses.extend(transaction.from.into_iter().chain(transaction.to));
+        }
+
+        addresses
+            .into_iter()
+            .filter_map(|address| Some((address.to_string(), lookup_label(&address)?)))
+            .collect()
+    }
+}
+
+/// Collects the addresses within the decoded value, including those nested in arrays and tuples
+fn collect_addresses(value: &DynSolValue, addresses: &mut Vec<Address>) {
+    match value {
+        DynSolValue::Address(address) => addresses.push(*address),
+        DynSolValue::Array(values) |
+        DynSolValue::FixedArray(values) |
+        DynSolValue::Tuple(values) => {
+            values.iter().for_each(|value| collect_addresses(value, addresses))
+        }
+        DynSolValue::CustomStruct { tuple, .. } => {
+            tuple.iter().for_each(|value| collect_addresses(value, addresses))
+        }
+        _ => {}
+    }
+}
+
+/// Converts the inputs of the signature to ABI format, with the components of any tuples
+fn inputs_to_abi_format(signature: &str) -> Vec<Value> {
+    match parse_function_parameters(signature) {
+        Ok(types) => {
+            types
+                .iter()
+                .enumerate()
+                .map(|(i, sol_type)| {
+                    let mut param = json!({
+                        "name": format!("arg{}", i),
+                        "type": to_abi_string(sol_type)
+                    });
+
+                    // Add components if it's a tuple type
+                    let components = to_components(sol_type);
+                    if !components.is_empty() {
+                        param["components"] = json!(components
+                            .iter()
+                            .enumerate()
+                            .map(|(j, comp)| {
+                                let mut comp_json = json!({
+                                    "name": format!("arg{}", j),
+                                    "type": comp.ty.clone()
+                                });
+
+                                // Recursively add nested components
+                                if !comp.components.is_empty() {
+                                    comp_json["components"] = json!(comp
+                                        .components
+                                        .iter()
+                                        .enumerate()
+                                        .map(|(k, nested)| {
+                                            json!({
+                                                "name": format!("arg{}", k),
+                                                "type": nested.ty.clone()
+                                            })
+                                        })
+                                        .collect::<Vec<_>>());
+                                }
+
+                                comp_json
+                            })
+                            .collect::<Vec<_>>());
+                    }
+
+                    param
+                })
+                .collect()
+        }
+        Err(_) => {
+            // Fallback to simple format if parsing fails
+            vec![]
+        }
     }
 }
@@ -0,0 +1,284 @@
+use alloy::primitives::{Address, B256, U256};
+use heimdall_common::utils::{hex::ToLowerHex, strings::encode_hex};
+use serde_json::{json, Value};
+
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    alloy::consensus::Transaction,
+    heimdall_common::ether::{beacon::Blob, labels::display_address},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+/// The number of bytes of a blob's contents which are displayed in the trace.
+const BLOB_PREVIEW_LENGTH: usize = 64;
+
+/// A blob carried by a blob transaction, fetched from a beacon node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBlob {
+    /// The index of the blob in its block, or in its transaction if it was read from the
+    /// transaction's sidecar
+    pub index: u64,
+    /// The versioned hash of the blob's KZG commitment, as referenced by the transaction
+    pub versioned_hash: B256,
+    /// The contents of the blob, with the padding of its field elements removed
+    pub contents: Vec<u8>,
+}
+
+impl DecodedBlob {
+    /// The contents of the blob as text, if they're printable UTF-8
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.contents)
+            .ok()
+            .filter(|text| text.chars().all(|c| !c.is_control() || c.is_whitespace()))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// A short preview of the blob's contents, as text if they're printable or as hex otherwise
+    fn preview(&self) -> String {
+        let ellipsis = match self.contents.len() > BLOB_PREVIEW_LENGTH {
+            true => "...",
+            false => "",
+        };
+        match self.text() {
+            Some(text) => {
+                format!(
+                    "\"{}{ellipsis}\"",
+                    text.chars().take(BLOB_PREVIEW_LENGTH).collect::<String>()
+                )
+            }
+            None => format!(
+                "0x{}{ellipsis}",
+                encode_hex(&self.contents[..self.contents.len().min(BLOB_PREVIEW_LENGTH)])
+            ),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<Blob> for DecodedBlob {
+    fn from(blob: Blob) -> Self {
+        Self { index: blob.index, versioned_hash: blob.versioned_hash, contents: blob.contents() }
+    }
+}
+
+/// The fields of an EIP-2718 typed transaction's envelope which aren't part of its calldata, such
+/// as its fees, access list, and the versioned hashes of its blobs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DecodedTransaction {
+    /// The type of the transaction, i.e. 0 for legacy, 1 for EIP-2930, 2 for EIP-1559, 3 for
+    /// EIP-4844 and 4 for EIP-7702 transactions
+    pub tx_type: u8,
+    /// The chain the transaction was signed for, unless it's a pre-EIP-155 legacy transaction
+    pub chain_id: Option<u64>,
+    /// The sender of the transaction, if known
+    pub from: Option<Address>,
+    /// The recipient of the transaction, or `None` if it creates a contract
+    pub to: Option<Address>,
+    /// The value sent with the transaction, in wei
+    pub value: U256,
+    /// The nonce of the sender
+    pub nonce: u64,
+    /// The gas limit of the transaction
+    pub gas_limit: u64,
+    /// The gas price of legacy and EIP-2930 transactions, in wei
+    pub gas_price: Option<u128>,
+    /// The maximum fee per gas of dynamic fee transactions, in wei
+    pub max_fee_per_gas: Option<u128>,
+    /// The maximum priority fee per gas of dynamic fee transactions, in wei
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// The maximum fee per blob gas of blob transactions, in wei
+    pub max_fee_per_blob_gas: Option<u128>,
+    /// The addresses and storage slots the transaction declares it will access
+    pub access_list: Vec<(Address, Vec<B256>)>,
+    /// The versioned hashes of the blobs carried by blob transactions
+    pub blob_versioned_hashes: Vec<B256>,
+    /// The blobs carried by the transaction, if they were fetched with `--beacon-url`
+    pub blobs: Vec<DecodedBlob>,
+}
+
+impl DecodedTransaction {
+    /// Summarizes the envelope of the transaction, signed by `from` if it's known.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(transaction: &impl Transaction, from: Option<Address>) -> Self {
+        let dynamic_fee = transaction.is_dynamic_fee();
+        Self {
+            tx_type: transaction.ty(),
+            chain_id: transaction.chain_id(),
+            from,
+            to: transaction.to(),
+            value: transaction.value(),
+            nonce: transaction.nonce(),
+            gas_limit: transaction.gas_limit(),
+            gas_price: transaction.gas_price(),
+            max_fee_per_gas: dynamic_fee.then(|| transaction.max_fee_per_gas()),
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas(),
+            max_fee_per_blob_gas: transaction.max_fee_per_blob_gas(),
+            access_list: transaction
+                .access_list()
+                .map(|access_list| {
+                    access_list
+                        .iter()
+                        .map(|item| (item.address, item.storage_keys.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            blob_versioned_hashes: transaction
+                .blob_versioned_hashes()
+                .map(<[B256]>::to_vec)
+                .unwrap_or_default(),
+            blobs: Vec::new(),
+        }
+    }
+
+    /// The name of the transaction's type
+    pub fn type_name(&self) -> &'static str {
+        match self.tx_type {
+            0 => "legacy",
+            1 => "EIP-2930",
+            2 => "EIP-1559",
+            3 => "EIP-4844",
+            4 => "EIP-7702",
+            _ => "unknown",
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// The lines of the trace which display the transaction's envelope
+    pub(crate) fn trace_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("tx type:   {} ({})", self.tx_type, self.type_name())];
+        if let Some(chain_id) = self.chain_id {
+            lines.push(format!("chain id:  {chain_id}"));
+        }
+        if let Some(from) = self.from {
+            lines.push(format!("from:      {}", display_address(&from)));
+        }
+        match self.to {
+            Some(to) => lines.push(format!("to:        {}", display_address(&to))),
+            None => lines.push("to:        contract creation".to_string()),
+        }
+        lines.push(format!("value:     {} wei", self.value));
+        lines.push(format!("nonce:     {}", self.nonce));
+        lines.push(format!("gas limit: {}", self.gas_limit));
+
+        let fees = [
+            ("gas price: ", self.gas_price),
+            ("max fee:   ", self.max_fee_per_gas),
+            ("max tip:   ", self.max_priority_fee_per_gas),
+            ("blob fee:  ", self.max_fee_per_blob_gas),
+        ];
+        for (label, fee) in fees {
+            if let Some(fee) = fee {
+                lines.push(format!("{label}{fee} wei"));
+            }
+        }
+
+        for (i, (address, storage_keys)) in self.access_list.iter().enumerate() {
+            let label = match i {
+                0 => "access:    ",
+                _ => "           ",
+            };
+            lines.push(format!("{label}{address}"));
+            lines.extend(storage_keys.iter().map(|key| format!("             {key}")));
+        }
+
+        for (i, versioned_hash) in self.blob_versioned_hashes.iter().enumerate() {
+            lines.push(format!("blob {i}:{}{versioned_hash}", " ".repeat(5 - i.to_string().len())));
+            if let Some(blob) =
+                self.blobs.iter().find(|blob| blob.versioned_hash == *versioned_hash)
+            {
+                lines.push(format!("           {} bytes: {}", blob.contents.len(), blob.preview()));
+            }
+        }
+
+        lines
+    }
+
+    /// Converts the transaction's envelope to JSON
+    pub fn to_json(&self) -> Value {
+        json!({
+            "type": self.tx_type,
+            "chain_id": self.chain_id,
+            "from": self.from.map(|from| from.to_string()),
+            "to": self.to.map(|to| to.to_string()),
+            "value": self.value.to_string(),
+            "nonce": self.nonce,
+            "gas_limit": self.gas_limit,
+            "gas_price": self.gas_price.map(|fee| fee.to_string()),
+            "max_fee_per_gas": self.max_fee_per_gas.map(|fee| fee.to_string()),
+            "max_priority_fee_per_gas": self.max_priority_fee_per_gas.map(|fee| fee.to_string()),
+            "max_fee_per_blob_gas": self.max_fee_per_blob_gas.map(|fee| fee.to_string()),
+            "access_list": self
+                .access_list
+                .iter()
+                .map(|(address, storage_keys)| json!({
+                    "address": address.to_string(),
+                    "storage_keys": storage_keys
+                        .iter()
+                        .map(|key| key.to_lower_hex())
+                        .collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+            "blob_versioned_hashes": self
+                .blob_versioned_hashes
+                .iter()
+                .map(|versioned_hash| versioned_hash.to_lower_hex())
+                .collect::<Vec<_>>(),
+            "blobs": self
+                .blobs
+                .iter()
+                .map(|blob| json!({
+                    "index": blob.index,
+                    "versioned_hash": blob.versioned_hash.to_lower_hex(),
+                    "contents": format!("0x{}", encode_hex(&blob.contents)),
+                    "text": blob.text(),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::TxEip4844,
+        eips::eip2930::{AccessList, AccessListItem},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_blob_transaction() {
+        let versioned_hash = B256::with_last_byte(1);
+        let transaction = TxEip4844 {
+            chain_id: 1,
+            nonce: 7,
+            gas_limit: 21_000,
+            max_fee_per_gas: 30,
+            max_priority_fee_per_gas: 2,
+            max_fee_per_blob_gas: 5,
+            access_list: AccessList(vec![AccessListItem {
+                address: Address::with_last_byte(2),
+                storage_keys: vec![B256::with_last_byte(3)],
+            }]),
+            blob_versioned_hashes: vec![versioned_hash],
+            ..Default::default()
+        };
+
+        let mut decoded = DecodedTransaction::new(&transaction, None);
+        assert_eq!(decoded.type_name(), "EIP-4844");
+        assert_eq!(decoded.gas_price, None);
+        assert_eq!(decoded.max_fee_per_gas, Some(30));
+        assert_eq!(decoded.max_fee_per_blob_gas, Some(5));
+        assert_eq!(
+            decoded.access_list,
+            vec![(Address::with_last_byte(2), vec![B256::with_last_byte(3)])]
+        );
+        assert_eq!(decoded.blob_versioned_hashes, vec![versioned_hash]);
+
+        decoded.blobs.push(DecodedBlob { index: 0, versioned_hash, contents: b"hello".to_vec() });
+        let lines = decoded.trace_lines();
+        assert!(lines.contains(&format!("blob 0:    {versioned_hash}")));
+        assert!(lines.contains(&"           5 bytes: \"hello\"".to_string()));
+        assert_eq!(decoded.to_json()["blobs"][0]["text"], "hello");
+    }
+}
@@ -0,0 +1,186 @@
+use alloy::primitives::{Address, B256};
+use alloy_dyn_abi::TypedData;
+use heimdall_common::utils::hex::ToLowerHex;
+use serde_json::{json, Value};
+
+#[cfg(not(target_arch = "wasm32"))]
+use {alloy::primitives::Signature, alloy_dyn_abi::DynSolType};
+
+/// An EIP-712 typed data payload, either given as the target or reconstructed from the calldata
+/// of a permit, along with its hashes and the signer recovered from its signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedTypedData {
+    /// The standard of the permit the typed data was reconstructed from, i.e. `EIP-2612`, `DAI`
+    /// or `Permit2`, or `None` if it was given as the target
+    pub standard: Option<String>,
+    /// The domain, types and message of the typed data
+    pub typed_data: TypedData,
+    /// The separator of the typed data's domain, or `None` if it couldn't be determined, such as
+    /// when a token's domain couldn't be fetched
+    pub domain_separator: Option<B256>,
+    /// The `hashStruct` of the message, or `None` if the message is incomplete, such as when a
+    /// token's permit nonce couldn't be fetched
+    pub struct_hash: Option<B256>,
+    /// The EIP-712 digest which was signed, if both of its hashes are known
+    pub digest: Option<B256>,
+    /// The address recovered from the signature over the digest, if one was given
+    pub signer: Option<Address>,
+    /// The address the permit claims to be signed by, if it was reconstructed from a permit
+    pub owner: Option<Address>,
+}
+
+impl DecodedTypedData {
+    /// Computes the hashes of the typed data, recovering the signer of the digest if a signature
+    /// is given. The domain separator is computed from the typed data's domain unless it's given,
+    /// as token permits are signed over the token's own `DOMAIN_SEPARATOR()`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(
+        standard: Option<&str>,
+        typed_data: TypedData,
+        domain_separator: Option<B256>,
+        signature: Option<&Signature>,
+        owner: Option<Address>,
+    ) -> Self {
+        let struct_hash = typed_data.hash_struct().ok();
+        let digest = domain_separator.zip(struct_hash).map(|(domain_separator, struct_hash)| {
+            alloy::primitives::keccak256(
+                [&[0x19, 0x01], domain_separator.as_slice(), struct_hash.as_slice()].concat(),
+            )
+        });
+        let signer = digest
+            .zip(signature)
+            .and_then(|(digest, signature)| signature.recover_address_from_prehash(&digest).ok());
+
+        Self {
+            standard: standard.map(str::to_string),
+            typed_data,
+            domain_separator,
+            struct_hash,
+            digest,
+            signer,
+            owner,
+        }
+    }
+
+    /// Whether the recovered signer is the owner of the permit, or `None` if either is unknown
+    pub fn is_valid(&self) -> Option<bool> {
+        self.signer.zip(self.owner).map(|(signer, owner)| signer == owner)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    /// The lines of the trace which display the typed data
+    pub(crate) fn trace_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(standard) = &self.standard {
+            lines.push(format!("permit:    {standard}"));
+        }
+
+        let domain = &self.typed_data.domain;
+        let mut domain_fields = Vec::new();
+        if let Some(name) = &domain.name {
+            domain_fields.push(format!("name \"{name}\""));
+        }
+        if let Some(version) = &domain.version {
+            domain_fields.push(format!("version \"{version}\""));
+        }
+        if let Some(chain_id) = domain.chain_id {
+            domain_fields.push(format!("chain {chain_id}"));
+        }
+        if let Some(verifying_contract) = domain.verifying_contract {
+            domain_fields.push(format!("contract {verifying_contract}"));
+        }
+        if let Some(salt) = domain.salt {
+            domain_fields.push(format!("salt {salt}"));
+        }
+        lines.push(format!("domain:    {}", domain_fields.join(", ")));
+
+        lines.push(format!("message:   {}", self.typed_data.primary_type));
+        let ty = self.typed_data.resolver.resolve(&self.typed_data.primary_type).ok();
+        message_lines(ty.as_ref(), &self.typed_data.message, 13, &mut lines);
+
+        let hashes = [
+            ("separator: ", self.domain_separator),
+            ("struct:    ", self.struct_hash),
+            ("digest:    ", self.digest),
+        ];
+        for (label, hash) in hashes {
+            match hash {
+                Some(hash) => lines.push(format!("{label}{hash}")),
+                None => lines.push(format!("{label}unknown")),
+            }
+        }
+
+        match (self.signer, self.owner) {
+            (Some(signer), Some(owner)) if signer == owner => {
+                lines.push(format!("signer:    {signer} (the owner)"))
+            }
+            (Some(signer), Some(owner)) => {
+                lines.push(format!("signer:    {signer} (invalid, the owner is {owner})"))
+            }
+            (Some(signer), None) => lines.push(format!("signer:    {signer}")),
+            (None, _) => {}
+        }
+
+        lines
+    }
+
+    /// Converts the typed data to JSON
+    pub fn to_json(&self) -> Value {
+        json!({
+            "standard": self.standard,
+            "payload": self.typed_data,
+            "domain_separator": self.domain_separator.map(|hash| hash.to_lower_hex()),
+            "struct_hash": self.struct_hash.map(|hash| hash.to_lower_hex()),
+            "digest": self.digest.map(|hash| hash.to_lower_hex()),
+            "signer": self.signer.map(|signer| signer.to_string()),
+            "owner": self.owner.map(|owner| owner.to_string()),
+            "valid": self.is_valid(),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+/// Displays each field of the message on its own line in the order of its type's fields,
+/// indenting the fields of nested structs and the elements of arrays.
+fn message_lines(ty: Option<&DynSolType>, value: &Value, indent: usize, lines: &mut Vec<String>) {
+    let entries = match (ty, value) {
+        (Some(DynSolType::CustomStruct { prop_names, tuple, .. }), Value::Object(fields)) => {
+            prop_names
+                .iter()
+                .zip(tuple)
+                .filter_map(|(name, ty)| {
+                    fields.get(name).map(|value| (name.clone(), Some(ty), value))
+                })
+                .collect::<Vec<_>>()
+        }
+        (_, Value::Object(fields)) => {
+            fields.iter().map(|(name, value)| (name.clone(), None, value)).collect()
+        }
+        (ty, Value::Array(elements)) => {
+            let element_ty = match ty {
+                Some(DynSolType::Array(ty) | DynSolType::FixedArray(ty, _)) => Some(ty.as_ref()),
+                _ => None,
+            };
+            elements
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (i.to_string(), element_ty, value))
+                .collect()
+        }
+        _ => return,
+    };
+
+    let width = entries.iter().map(|(name, ..)| name.len()).max().unwrap_or_default();
+    for (name, ty, value) in entries {
+        let label = format!("{}{name}:", " ".repeat(indent));
+        let padding = " ".repeat(width - name.len() + 1);
+        match value {
+            Value::Object(_) | Value::Array(_) => {
+                lines.push(label);
+                message_lines(ty, value, indent + 2, lines);
+            }
+            Value::String(value) => lines.push(format!("{label}{padding}{value}")),
+            value => lines.push(format!("{label}{padding}{value}")),
+        }
+    }
+}
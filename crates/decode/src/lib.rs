@@ -12,6 +12,11 @@ mod interfaces;
 mod utils;
 
 // re-export the public interface
-pub use core::decode;
+#[cfg(not(target_arch = "wasm32"))]
+pub use core::decode_logs;
+pub use core::{decode, decode_calldata, decode_log, decode_revert, revert_reason};
 pub use error::Error;
-pub use interfaces::{DecodeArgs, DecodeArgsBuilder, DecodeResult};
+pub use interfaces::{
+    DecodeArgs, DecodeArgsBuilder, DecodeLogsResult, DecodeResult, DecodedBlob, DecodedEvent,
+    DecodedTransaction, DecodedTypedData, Interpretation, ParameterGuess,
+};
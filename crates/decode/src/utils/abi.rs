@@ -6,12 +6,15 @@ use alloy_json_abi::Param;
 use eyre::eyre;
 use heimdall_common::utils::strings::encode_hex;
 use heimdall_vm::core::types::{
-    get_padding, get_padding_size, get_potential_types_for_word, Padding,
+    get_padding, get_padding_size, get_potential_types_for_word, to_type, Padding,
 };
 use tracing::trace;
 
 use crate::error::Error;
 
+/// The deepest nesting of dynamic types which raw calldata decoding will infer.
+const MAX_INFERENCE_DEPTH: usize = 8;
+
 #[derive(Debug, Clone)]
 pub(crate) struct AbiEncoded {
     pub ty: String,
@@ -59,6 +62,284 @@ pub(crate) fn try_decode(
     Ok((result, params))
 }
 
+/// Infers the types of the ABI-encoded parameters in `byte_args`, recursively inferring the
+/// structure of nested tuples and arrays by following the offsets in each head to its tail.
+///
+/// Each tail spans from its offset to the next offset in the same head, or the end of the
+/// enclosing encoding, so the length of each dynamic parameter is validated against the data it
+/// spans. Returns `None` if the inferred types don't re-encode to exactly `byte_args`.
+pub(crate) fn infer_parameter_types(byte_args: &[u8]) -> Option<Vec<DynSolType>> {
+    if byte_args.is_empty() || !byte_args.len().is_multiple_of(32) {
+        return None;
+    }
+
+    let types = infer_tuple(byte_args, 0)?;
+    let values = DynSolType::Tuple(types.clone()).abi_decode_params(byte_args).ok()?;
+    if values.abi_encode_params() != byte_args {
+        trace!("inferred types {:?} don't re-encode to the calldata", types);
+        return None;
+    }
+
+    trace!("inferred parameter types: {:?}", types);
+    Some(types)
+}
+
+/// Infers the types of each member of the tuple encoded in `data`, following each offset in its
+/// head to the tail it points to.
+fn infer_tuple(data: &[u8], depth: usize) -> Option<Vec<DynSolType>> {
+    let words = data.chunks(32).collect::<Vec<_>>();
+
+    // words which look like offsets may be static values, so if the tail one points to can't be
+    // inferred, try again treating the word as a static value
+    let mut static_words = HashSet::new();
+    'head: loop {
+        // offsets point past the head, and tails follow the head in order, so the first offset
+        // is also the length of the head
+        let mut offsets = Vec::new();
+        let mut head_length = words.len();
+        let mut i = 0;
+        while i < head_length {
+            let offset = as_offset(words[i], data.len()).filter(|offset| {
+                !static_words.contains(&i) &&
+                    *offset >= (i + 1) * 32 &&
+                    offsets.last().is_none_or(|(_, last)| offset > last)
+            });
+            if let Some(offset) = offset {
+                if offsets.is_empty() {
+                    head_length = offset / 32;
+                }
+                offsets.push((i, offset));
+            }
+            i += 1;
+        }
+
+        let mut types =
+            words[..head_length].iter().map(|word| infer_word_type(word)).collect::<Vec<_>>();
+        for (index, &(i, start)) in offsets.iter().enumerate() {
+            let end = offsets.get(index + 1).map_or(data.len(), |(_, end)| *end);
+            match infer_dynamic(&data[start..end], depth) {
+                Some(ty) => types[i] = Some(ty),
+                None => {
+                    static_words.insert(i);
+                    continue 'head;
+                }
+            }
+        }
+
+        return Some(types.into_iter().map(|ty| ty.unwrap_or(DynSolType::Uint(256))).collect());
+    }
+}
+
+/// Infers the dynamic type encoded in `data`, which is the tail an offset points to. This is
+/// either a `bytes` or `string`, an array, or a tuple with dynamic members.
+fn infer_dynamic(data: &[u8], depth: usize) -> Option<DynSolType> {
+    if depth >= MAX_INFERENCE_DEPTH || data.len() < 32 {
+        return None;
+    }
+
+    // the first word is the length of bytes and arrays, or the first member of a tuple
+    let (length, body) = (as_usize(&data[..32]), &data[32..]);
+    let bytes = length.and_then(|length| infer_bytes(length, body));
+    let array = length
+        .and_then(|length| infer_array(length, body, depth + 1))
+        .map(|ty| DynSolType::Array(Box::new(ty)));
+    match (bytes, array) {
+        // arrays of numbers are left-padded, while bytes are right-padded
+        (Some(bytes), Some(array)) => {
+            if body.chunks(32).all(|word| get_padding(word) == Padding::Left) {
+                Some(array)
+            } else {
+                Some(bytes)
+            }
+        }
+        (bytes, array) => bytes.or(array).or_else(|| {
+            let members = infer_tuple(data, depth + 1)?;
+            members.iter().any(|member| member.is_dynamic()).then_some(DynSolType::Tuple(members))
+        }),
+    }
+}
+
+/// Infers whether `body` is the encoding of `length` bytes, i.e. the bytes right-padded to a
+/// multiple of 32. The bytes are a `string` if they're printable UTF-8.
+fn infer_bytes(length: usize, body: &[u8]) -> Option<DynSolType> {
+    if body.len() != length.div_ceil(32) * 32 || body[length..].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+
+    match std::str::from_utf8(&body[..length]) {
+        Ok(string)
+            if length > 0 && string.chars().all(|c| !c.is_control() || c.is_ascii_whitespace()) =>
+        {
+            Some(DynSolType::String)
+        }
+        _ => Some(DynSolType::Bytes),
+    }
+}
+
+/// Infers the type of the elements of the array, if `body` is the encoding of `length` of them.
+/// Dynamic elements are found by following their offsets, and static elements must all span
+/// the same number of words, in which case elements spanning more than one word are tuples.
+fn infer_array(length: usize, body: &[u8], depth: usize) -> Option<DynSolType> {
+    if length == 0 || length > body.len() / 32 {
+        return None;
+    }
+    let words = body.chunks(32).collect::<Vec<_>>();
+
+    // the heads of dynamic elements are increasing offsets, the first pointing just past them
+    let heads = words[..length]
+        .iter()
+        .map(|word| as_offset(word, body.len()))
+        .collect::<Option<Vec<_>>>()
+        .filter(|heads| heads[0] == length * 32 && heads.windows(2).all(|w| w[0] < w[1]));
+    if let Some(heads) = heads {
+        let elements = heads
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = heads.get(i + 1).copied().unwrap_or(body.len());
+                infer_dynamic(&body[start..end], depth)
+            })
+            .collect::<Option<Vec<_>>>();
+        if let Some(ty) = elements.and_then(unify_types) {
+            return Some(ty);
+        }
+    }
+
+    if !words.len().is_multiple_of(length) {
+        return None;
+    }
+    let width = words.len() / length;
+    let columns = (0..width)
+        .map(|column| {
+            words
+                .iter()
+                .skip(column)
+                .step_by(width)
+                .filter_map(|word| infer_word_type(word))
+                .reduce(unify_type)
+                .unwrap_or(DynSolType::Uint(256))
+        })
+        .collect::<Vec<_>>();
+
+    match columns.as_slice() {
+        [ty] => Some(ty.clone()),
+        _ => Some(DynSolType::Tuple(columns)),
+    }
+}
+
+/// Infers the most likely static type of the word, or `None` if it's zero, which fits any type.
+pub(crate) fn infer_word_type(word: &[u8]) -> Option<DynSolType> {
+    if word.iter().all(|byte| *byte == 0) {
+        return None;
+    }
+
+    // - if we use left-padding, this is probably uintN or address
+    // - otherwise, this is probably bytesN
+    let (_, mut potential_types) = get_potential_types_for_word(word);
+    match get_padding(word) {
+        Padding::Left => {
+            potential_types.retain(|t| t.starts_with("uint") || t.starts_with("address"))
+        }
+        _ => potential_types.retain(|t| t.starts_with("bytes")),
+    }
+    potential_types.first().map(|ty| to_type(ty))
+}
+
+/// The single type which each of the types can be decoded as, if there is one.
+fn unify_types(types: Vec<DynSolType>) -> Option<DynSolType> {
+    let mut types = types.into_iter();
+    let first = types.next()?;
+    types.try_fold(first, |a, b| match (&a, &b) {
+        _ if a == b => Some(a),
+        (DynSolType::Array(a), DynSolType::Array(b)) => {
+            unify_types(vec![*a.clone(), *b.clone()]).map(|ty| DynSolType::Array(Box::new(ty)))
+        }
+        (DynSolType::Tuple(a), DynSolType::Tuple(b)) if a.len() == b.len() => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| unify_types(vec![a.clone(), b.clone()]))
+            .collect::<Option<Vec<_>>>()
+            .map(DynSolType::Tuple),
+        (DynSolType::String, DynSolType::Bytes) | (DynSolType::Bytes, DynSolType::String) => {
+            Some(DynSolType::Bytes)
+        }
+        _ if !a.is_dynamic() && !b.is_dynamic() => Some(unify_type(a, b)),
+        _ => None,
+    })
+}
+
+/// The static type which both static types can be decoded as, widening them if necessary.
+fn unify_type(a: DynSolType, b: DynSolType) -> DynSolType {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (DynSolType::Uint(a), DynSolType::Uint(b)) => DynSolType::Uint(a.max(b)),
+        (DynSolType::FixedBytes(a), DynSolType::FixedBytes(b)) => DynSolType::FixedBytes(a.max(b)),
+        (DynSolType::FixedBytes(_), _) | (_, DynSolType::FixedBytes(_)) => {
+            DynSolType::FixedBytes(32)
+        }
+        _ => DynSolType::Uint(256),
+    }
+}
+
+/// The word as a byte offset into data of `length` bytes, if it could be one.
+fn as_offset(word: &[u8], length: usize) -> Option<usize> {
+    as_usize(word).filter(|offset| *offset > 0 && offset.is_multiple_of(32) && *offset < length)
+}
+
+/// The word as a `usize`, if it fits in one.
+fn as_usize(word: &[u8]) -> Option<usize> {
+    U256::from_be_slice(word).try_into().ok()
+}
+
+/// Infers the types of the ABI-encoded parameters in `byte_args` one word at a time, checking
+/// whether each word is the offset of a dynamic parameter, and otherwise inferring its static
+/// type from its padding. This doesn't infer tuples, so it's used if [`infer_parameter_types`]
+/// can't explain the calldata, e.g. if it's non-standard.
+pub(crate) fn infer_parameter_types_by_word(byte_args: &[u8]) -> Result<Vec<DynSolType>, Error> {
+    // we're going to build a Vec<DynSolType> of all possible types for each
+    let mut potential_inputs: Vec<DynSolType> = Vec::new();
+
+    // chunk in blocks of 32 bytes
+    let calldata_words = byte_args.chunks(32).map(|x| x.to_owned()).collect::<Vec<_>>();
+
+    // while calldata_words is not empty, iterate over it
+    let mut i = 0;
+    let mut covered_words = HashSet::new();
+    while covered_words.len() != calldata_words.len() {
+        let word = calldata_words[i].to_owned();
+
+        // check if the first word is abiencoded
+        if let Some(abi_encoded) = try_decode_dynamic_parameter(i, &calldata_words)? {
+            let potential_type = to_type(&abi_encoded.ty);
+            potential_inputs.push(potential_type);
+            covered_words.extend(abi_encoded.coverages);
+        } else {
+            let (_, mut potential_types) = get_potential_types_for_word(&word);
+
+            // perform heuristics
+            // - if we use right-padding, this is probably bytesN
+            // - if we use left-padding, this is probably uintN or intN
+            // - if we use no padding, this is probably bytes32
+            match get_padding(&word) {
+                Padding::Left => {
+                    potential_types.retain(|t| t.starts_with("uint") || t.starts_with("address"))
+                }
+                _ => potential_types.retain(|t| t.starts_with("bytes") || t.starts_with("string")),
+            }
+
+            let potential_type =
+                to_type(potential_types.first().expect("potential types is empty"));
+
+            potential_inputs.push(potential_type);
+            covered_words.insert(i);
+        }
+
+        i += 1;
+    }
+
+    Ok(potential_inputs)
+}
+
 /// Finds the offsets of all ABI-encoded items in the given calldata.
 pub(crate) fn try_decode_dynamic_parameter(
     parameter_index: usize,
@@ -422,6 +703,7 @@ fn get_potential_type(
 
 #[cfg(test)]
 mod tests {
+    use alloy::primitives::Address;
     use heimdall_common::utils::strings::decode_hex;
 
     use super::*;
@@ -656,4 +938,71 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_infer_nested_arrays() {
+        // `g(uint256[][],string[])` with `([[1, 2], [3]], ["one", "two", "three"])`
+        let values = DynSolValue::Tuple(vec![
+            DynSolValue::Array(vec![
+                DynSolValue::Array(vec![U256::from(1).into(), U256::from(2).into()]),
+                DynSolValue::Array(vec![U256::from(3).into()]),
+            ]),
+            DynSolValue::Array(
+                ["one", "two", "three"].map(|s| DynSolValue::String(s.to_string())).to_vec(),
+            ),
+        ]);
+
+        let types = infer_parameter_types(&values.abi_encode_params())
+            .expect("failed to infer parameter types");
+        assert_eq!(
+            types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>(),
+            vec!["uint8[][]", "string[]"]
+        );
+    }
+
+    #[test]
+    fn test_infer_nested_tuples() {
+        // a seaport-style order, i.e. `((address,(uint8,address,uint256,uint256,uint256)[],
+        // uint256),bytes)`
+        let item = |i: u64| {
+            DynSolValue::Tuple(vec![
+                U256::from(2).into(),
+                DynSolValue::Address(Address::repeat_byte(0x11)),
+                U256::from(1000 + i).into(),
+                U256::from(u64::MAX).into(),
+                U256::from(1).into(),
+            ])
+        };
+        let values = DynSolValue::Tuple(vec![
+            DynSolValue::Tuple(vec![
+                DynSolValue::Address(Address::repeat_byte(0x22)),
+                DynSolValue::Array(vec![item(1), item(2)]),
+                U256::from(1700000000).into(),
+            ]),
+            DynSolValue::Bytes(vec![0xab; 65]),
+        ]);
+
+        let types = infer_parameter_types(&values.abi_encode_params())
+            .expect("failed to infer parameter types");
+        assert_eq!(
+            types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>(),
+            vec!["(address,(uint8,address,uint16,uint64,uint8)[],uint32)", "bytes"]
+        );
+    }
+
+    #[test]
+    fn test_infer_bytes_array() {
+        // a uniswap v3 style `multicall(bytes[])`
+        let values = DynSolValue::Tuple(vec![DynSolValue::Array(vec![
+            DynSolValue::Bytes(vec![0xac, 0x96, 0x50, 0xd8, 0x01]),
+            DynSolValue::Bytes(vec![0x12; 100]),
+        ])]);
+
+        let types = infer_parameter_types(&values.abi_encode_params())
+            .expect("failed to infer parameter types");
+        assert_eq!(types, vec![DynSolType::Array(Box::new(DynSolType::Bytes))]);
+
+        // calldata which isn't a standard length can't be explained
+        assert_eq!(infer_parameter_types(&[0u8; 33]), None);
+    }
 }
@@ -0,0 +1,39 @@
+use heimdall_common::{
+    ether::{signatures::ResolvedFunction, types::DynSolValueExt},
+    resources::openai::complete_chat,
+};
+
+/// The prompt used to explain decoded calldata or revert data.
+const EXPLAIN_PROMPT: &str =
+"The following is the {kind}, decoded by a decompiler into its signature and arguments.
+
+Explain in a few plain sentences what it most likely means, e.g. what the caller was trying to do, or why the call reverted. Do not repeat the raw arguments back, and say so if the signature is unresolved and the meaning cannot be inferred.
+
+Signature: {signature}
+Arguments: {arguments}";
+
+/// Explain the decoded calldata or revert data using OpenAI, returning `None` if the request
+/// fails.
+pub(crate) async fn explain(
+    decoded: &ResolvedFunction,
+    is_revert: bool,
+    openai_api_key: &str,
+) -> Option<String> {
+    let kind = match is_revert {
+        true => "revert data returned by a failed call to an ethereum smart contract",
+        false => "calldata of a call to an ethereum smart contract",
+    };
+    let arguments = decoded
+        .decoded_inputs
+        .as_ref()
+        .map(|inputs| inputs.iter().map(|input| input.serialize().to_string()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .join(", ");
+
+    let prompt = EXPLAIN_PROMPT
+        .replace("{kind}", kind)
+        .replace("{signature}", &decoded.signature)
+        .replace("{arguments}", &arguments);
+
+    complete_chat(&prompt, openai_api_key).await.map(|explanation| explanation.trim().to_string())
+}
@@ -0,0 +1,243 @@
+use alloy::primitives::{Address, I256, U256};
+use alloy_dyn_abi::DynSolValue;
+use heimdall_common::utils::strings::encode_hex;
+
+use crate::interfaces::{Interpretation, ParameterGuess};
+
+/// Interpretations less likely than this aren't listed as alternatives.
+const MIN_CONFIDENCE: f64 = 0.05;
+
+/// The confidence that a dynamic parameter is a `string`, rather than `bytes` which happen to be
+/// printable.
+const STRING_CONFIDENCE: f64 = 0.9;
+
+/// The confidence in the inferred structure of an array or tuple.
+const STRUCTURE_CONFIDENCE: f64 = 0.8;
+
+/// A kind of static type a 32-byte word can be interpreted as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Uint,
+    Int,
+    Address,
+    Bool,
+    FixedBytes,
+    /// An address packed into the low bytes of the word, with a `uint96` in its high bytes
+    PackedAddress,
+}
+
+impl Kind {
+    /// The kind of the decoded static value, if it's static
+    fn of(value: &DynSolValue) -> Option<Self> {
+        match value {
+            DynSolValue::Uint(..) => Some(Self::Uint),
+            DynSolValue::Int(..) => Some(Self::Int),
+            DynSolValue::Address(_) => Some(Self::Address),
+            DynSolValue::Bool(_) => Some(Self::Bool),
+            DynSolValue::FixedBytes(..) => Some(Self::FixedBytes),
+            _ => None,
+        }
+    }
+
+    /// The name of the widest type of the kind
+    fn type_name(self) -> &'static str {
+        match self {
+            Self::Uint => "uint256",
+            Self::Int => "int256",
+            Self::Address => "address",
+            Self::Bool => "bool",
+            Self::FixedBytes => "bytes32",
+            Self::PackedAddress => "(uint96,address)",
+        }
+    }
+
+    /// The word as a value of the kind
+    fn render(self, word: &[u8; 32]) -> String {
+        match self {
+            Self::Uint => U256::from_be_bytes(*word).to_string(),
+            Self::Int => I256::from_be_bytes(*word).to_string(),
+            Self::Address => Address::from_slice(&word[12..]).to_string(),
+            Self::Bool => (word[31] == 1).to_string(),
+            Self::FixedBytes => format!("0x{}", encode_hex(word)),
+            Self::PackedAddress => format!(
+                "({}, {})",
+                U256::from_be_slice(&word[..12]),
+                Address::from_slice(&word[12..])
+            ),
+        }
+    }
+}
+
+/// Guesses how likely the inferred type of each of the decoded parameters is, listing the other
+/// types each could plausibly be. Static parameters are scored by the shape of their word, e.g.
+/// uints are usually small, addresses are 20 random bytes, and fixed bytes are left-aligned.
+pub(crate) fn guess_parameters(inputs: &[DynSolValue]) -> Vec<ParameterGuess> {
+    inputs.iter().map(guess_parameter).collect()
+}
+
+/// Guesses how likely the inferred type of the decoded parameter is.
+fn guess_parameter(value: &DynSolValue) -> ParameterGuess {
+    let ty = value.sol_type_name().unwrap_or_default().to_string();
+    let (Some(kind), Some(word)) = (Kind::of(value), value.as_word()) else {
+        return match value {
+            DynSolValue::String(string) => ParameterGuess {
+                ty,
+                confidence: STRING_CONFIDENCE,
+                alternatives: vec![Interpretation {
+                    ty: "bytes".to_string(),
+                    value: format!("0x{}", encode_hex(string.as_bytes())),
+                    confidence: 1.0 - STRING_CONFIDENCE,
+                }],
+            },
+            // bytes are only inferred if they aren't printable, so they can't be a string
+            DynSolValue::Bytes(_) => {
+                ParameterGuess { ty, confidence: 1.0, alternatives: Vec::new() }
+            }
+            _ => ParameterGuess { ty, confidence: STRUCTURE_CONFIDENCE, alternatives: Vec::new() },
+        };
+    };
+
+    // the guessed kind is always an interpretation, even if its word doesn't look like it
+    let mut weights = weigh_interpretations(&word.0);
+    if !weights.iter().any(|(candidate, _)| *candidate == kind) {
+        weights.push((kind, 0.1));
+    }
+    let total = weights.iter().map(|(_, weight)| weight).sum::<f64>();
+
+    let mut alternatives = weights
+        .iter()
+        .filter(|(candidate, weight)| *candidate != kind && weight / total >= MIN_CONFIDENCE)
+        .map(|(candidate, weight)| Interpretation {
+            ty: candidate.type_name().to_string(),
+            value: candidate.render(&word.0),
+            confidence: weight / total,
+        })
+        .collect::<Vec<_>>();
+    alternatives.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    ParameterGuess {
+        ty,
+        confidence: weights
+            .iter()
+            .find(|(candidate, _)| *candidate == kind)
+            .map(|(_, weight)| weight / total)
+            .unwrap_or_default(),
+        alternatives,
+    }
+}
+
+/// The relative likelihood of each kind of type the word could be, by its shape.
+fn weigh_interpretations(word: &[u8; 32]) -> Vec<(Kind, f64)> {
+    let leading_zeros = word.iter().take_while(|byte| **byte == 0).count();
+    let trailing_zeros = word.iter().rev().take_while(|byte| **byte == 0).count();
+    let leading_ones = word.iter().take_while(|byte| **byte == 0xff).count();
+
+    // zero is a valid value of every type
+    if leading_zeros == 32 {
+        return vec![
+            (Kind::Uint, 1.0),
+            (Kind::Bool, 1.0),
+            (Kind::Address, 0.5),
+            (Kind::FixedBytes, 0.5),
+        ];
+    }
+
+    // uints are usually small, so most of their high bytes are zero
+    let mut weights = vec![(
+        Kind::Uint,
+        match leading_zeros {
+            16.. => 1.0,
+            8.. => 0.5,
+            1.. => 0.2,
+            _ => 0.05,
+        },
+    )];
+    if leading_zeros == 31 && word[31] == 1 {
+        weights.push((Kind::Bool, 1.0));
+    }
+
+    // addresses are 20 random bytes, whose first bytes are rarely zero
+    if leading_zeros >= 12 {
+        weights.push((
+            Kind::Address,
+            match leading_zeros {
+                12 | 13 => 2.0,
+                14 | 15 => 0.2,
+                _ => 0.02,
+            },
+        ));
+    } else if leading_zeros > 0 && word[12] != 0 {
+        weights.push((Kind::PackedAddress, 0.5));
+    }
+
+    // negative ints are sign-extended, so their high bytes are set
+    if leading_ones >= 2 {
+        weights.push((Kind::Int, 2.0));
+    }
+
+    // fixed bytes, such as hashes and selectors, are left-aligned
+    weights.push((
+        Kind::FixedBytes,
+        match (leading_zeros, trailing_zeros) {
+            (0, 1..) => 1.5,
+            (0, 0) => 1.0,
+            _ => 0.1,
+        },
+    ));
+
+    weights
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, B256};
+
+    use super::*;
+
+    #[test]
+    fn test_guess_address() {
+        let guess = guess_parameter(&DynSolValue::Address(address!(
+            "d8dA6BF26964aF9D7eEd9e03E53415D37aA96045"
+        )));
+        assert_eq!(guess.ty, "address");
+        assert!(guess.confidence > 0.7);
+        assert_eq!(guess.alternatives[0].ty, "uint256");
+    }
+
+    #[test]
+    fn test_guess_small_uint() {
+        let guess = guess_parameter(&DynSolValue::Uint(U256::from(1), 8));
+        assert_eq!(guess.ty, "uint8");
+        assert_eq!(guess.alternatives[0].ty, "bool");
+        assert_eq!(guess.alternatives[0].value, "true");
+        assert!((guess.confidence - guess.alternatives[0].confidence).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_guess_hash() {
+        let guess = guess_parameter(&DynSolValue::FixedBytes(B256::repeat_byte(0xab), 32));
+        assert!(guess.confidence > 0.9);
+        assert!(guess.alternatives.iter().all(|alternative| alternative.ty != "address"));
+    }
+
+    #[test]
+    fn test_guess_packed_address() {
+        let mut word = [0u8; 32];
+        word[11] = 0x01;
+        word[12..].copy_from_slice(&[0xaa; 20]);
+        let guess = guess_parameter(&DynSolValue::Uint(U256::from_be_bytes(word), 256));
+        let packed = guess
+            .alternatives
+            .iter()
+            .find(|alternative| alternative.ty == "(uint96,address)")
+            .expect("no packed address interpretation");
+        assert_eq!(packed.value, format!("(1, {})", Address::repeat_byte(0xaa)));
+    }
+
+    #[test]
+    fn test_guess_string() {
+        let guess = guess_parameter(&DynSolValue::String("hi".to_string()));
+        assert_eq!(guess.alternatives[0].ty, "bytes");
+        assert_eq!(guess.alternatives[0].value, "0x6869");
+    }
+}
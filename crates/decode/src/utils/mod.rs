@@ -1,8 +1,26 @@
 mod abi;
+#[cfg(not(target_arch = "wasm32"))]
 mod constructor;
+#[cfg(not(target_arch = "wasm32"))]
+mod explain;
+mod guess;
 mod multicall;
+#[cfg(not(target_arch = "wasm32"))]
+mod permit;
+#[cfg(not(target_arch = "wasm32"))]
+mod transaction;
 
 // re-export
-pub(crate) use abi::{try_decode, try_decode_dynamic_parameter};
+pub(crate) use abi::{
+    infer_parameter_types, infer_parameter_types_by_word, infer_word_type, try_decode,
+};
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) use constructor::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use explain::explain;
+pub(crate) use guess::guess_parameters;
 pub(crate) use multicall::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use permit::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use transaction::*;
@@ -1,15 +1,15 @@
-use alloy_dyn_abi::DynSolValue;
-use eyre::eyre;
-use heimdall_common::utils::{
-    io::{logging::TraceFactory, types::display},
-    strings::encode_hex,
-};
-use tracing::{debug, trace};
-
-use crate::{
-    core::decode,
-    error::Error,
-    interfaces::{DecodeArgs, DecodeResult},
+use crate::interfaces::DecodeResult;
+
+// decoding nested calls resolves their selectors, which isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::{core::decode, interfaces::DecodeArgs},
+    alloy_dyn_abi::DynSolValue,
+    heimdall_common::utils::{
+        io::{logging::TraceFactory, types::display},
+        strings::encode_hex,
+    },
+    tracing::{debug, trace},
 };
 
 /// Detects if a decoded value represents a multicall pattern.
@@ -19,6 +19,7 @@ use crate::{
 ///
 /// Additional parameters (like bool flags or uint values) are allowed
 /// but not required. The order of parameters doesn't matter.
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn is_multicall_pattern(value: &DynSolValue) -> bool {
     match value {
         DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
@@ -44,153 +45,228 @@ pub(crate) fn is_multicall_pattern(value: &DynSolValue) -> bool {
     }
 }
 
-/// Decodes multicall data recursively
-pub(crate) async fn decode_multicall(
-    value: &DynSolValue,
-    args: &DecodeArgs,
-) -> Result<Vec<MulticallDecoded>, Error> {
-    match value {
-        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
-            let mut results = Vec::new();
-
-            for (index, item) in items.iter().enumerate() {
-                if let DynSolValue::Tuple(tuple_items) = item {
-                    let decoded = decode_multicall_item(tuple_items, args, index).await?;
-                    results.push(decoded);
-                }
-            }
+/// A `bytes` argument which looks like ABI-encoded calldata, along with the contract it calls
+/// and the value it sends, if they're known.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct NestedCall {
+    pub target: Option<String>,
+    pub value: Option<String>,
+    pub calldata: Vec<u8>,
+}
 
-            Ok(results)
-        }
-        _ => Err(Error::Eyre(eyre!("Expected array for multicall decoding"))),
-    }
+/// Whether the bytes look like calldata, i.e. a 4 byte selector followed by padded arguments.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_calldata(bytes: &[u8]) -> bool {
+    bytes.len() >= 4 && (bytes.len() - 4).is_multiple_of(32)
 }
 
-/// Represents a decoded multicall item
-#[derive(Debug, Clone)]
-pub struct MulticallDecoded {
-    pub index: usize,
-    pub target: String,
-    pub value: Option<String>,
-    pub calldata: Vec<u8>,
-    pub decoded: Option<DecodeResult>,
+/// Finds each of the decoded arguments which is itself calldata, such as the calls of a
+/// multicall, a Gnosis Safe's `execTransaction`, or a Governor or Timelock proposal.
+///
+/// The target and value of a call are taken from the arguments alongside it, i.e. from an
+/// `address` and `uint` in the same tuple, or from `address[]` and `uint[]` arrays the same
+/// length as a `bytes[]` array of calls.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn find_nested_calls(inputs: &[DynSolValue]) -> Vec<NestedCall> {
+    let mut calls = Vec::new();
+    find_calls_in_tuple(inputs, &mut calls);
+    calls
 }
 
-async fn decode_multicall_item(
-    tuple_items: &[DynSolValue],
-    args: &DecodeArgs,
-    index: usize,
-) -> Result<MulticallDecoded, Error> {
-    // Find each component regardless of order
-    let mut target = None;
-    let mut value = None;
-    let mut calldata = None;
-
-    for item in tuple_items {
-        match item {
-            DynSolValue::Address(addr) => {
-                if target.is_none() {
-                    target = Some(format!("{addr:?}"));
-                }
-            }
-            DynSolValue::Uint(val, _) => {
-                if value.is_none() {
-                    value = Some(val.to_string());
-                }
-            }
-            DynSolValue::Bytes(data) => {
-                if calldata.is_none() {
-                    calldata = Some(data.clone());
+/// Finds the calls among the members of the tuple, and then the calls nested within them.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_calls_in_tuple(members: &[DynSolValue], calls: &mut Vec<NestedCall>) {
+    let target = members.iter().find_map(|member| match member {
+        DynSolValue::Address(address) => Some(format!("{address:?}")),
+        _ => None,
+    });
+    let value = members.iter().find_map(|member| match member {
+        DynSolValue::Uint(value, _) => Some(value.to_string()),
+        _ => None,
+    });
+
+    // `address[]`, `uint[]` and `bytes[]` arrays of the same length are parallel arrays of calls
+    let arrays = members
+        .iter()
+        .filter_map(|member| match member {
+            DynSolValue::Array(items) | DynSolValue::FixedArray(items) => Some(items),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let parallel_array = |len: usize, f: fn(&DynSolValue) -> Option<String>| {
+        arrays.iter().find_map(|items| {
+            (items.len() == len).then(|| items.iter().map(f).collect::<Option<Vec<_>>>()).flatten()
+        })
+    };
+
+    for member in members {
+        match member {
+            DynSolValue::Bytes(calldata) if is_calldata(calldata) => calls.push(NestedCall {
+                target: target.clone(),
+                value: value.clone(),
+                calldata: calldata.clone(),
+            }),
+            DynSolValue::Array(items) | DynSolValue::FixedArray(items)
+                if !items.is_empty() && items.iter().all(|item| item.as_bytes().is_some()) =>
+            {
+                let targets = parallel_array(items.len(), |item| {
+                    item.as_address().map(|address| format!("{address:?}"))
+                });
+                let values = parallel_array(items.len(), |item| {
+                    item.as_uint().map(|(value, _)| value.to_string())
+                });
+
+                for (i, item) in items.iter().enumerate() {
+                    let Some(calldata) = item.as_bytes().filter(|bytes| is_calldata(bytes)) else {
+                        continue;
+                    };
+                    calls.push(NestedCall {
+                        target: targets.as_ref().map(|targets| targets[i].clone()),
+                        value: values.as_ref().map(|values| values[i].clone()),
+                        calldata: calldata.to_vec(),
+                    });
                 }
             }
-            _ => {}
+            _ => find_calls_in_value(member, calls),
         }
     }
+}
 
-    // Validate we have required fields
-    let target = target.ok_or_else(|| Error::Eyre(eyre!("No address found in multicall tuple")))?;
-    let calldata =
-        calldata.ok_or_else(|| Error::Eyre(eyre!("No bytes found in multicall tuple")))?;
+/// Finds the calls nested within the tuples and arrays of the value.
+#[cfg(not(target_arch = "wasm32"))]
+fn find_calls_in_value(value: &DynSolValue, calls: &mut Vec<NestedCall>) {
+    match value {
+        DynSolValue::Tuple(members) => find_calls_in_tuple(members, calls),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => {
+            for item in items {
+                find_calls_in_value(item, calls);
+            }
+        }
+        _ => {}
+    }
+}
 
-    // Check if calldata looks like a function call (4 byte selector + padded args)
-    let decoded = if calldata.len() >= 4 && (calldata.len() - 4) % 32 == 0 {
+/// Decodes each of the nested calls, which decodes the calls nested within them in turn, up to
+/// `--max-depth` calls deep.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn decode_nested_calls(
+    nested_calls: Vec<NestedCall>,
+    args: &DecodeArgs,
+) -> Vec<MulticallDecoded> {
+    let mut results = Vec::new();
+    for (index, nested_call) in nested_calls.into_iter().enumerate() {
         trace!(
-            "Attempting to decode multicall item {} with calldata: {}",
+            "attempting to decode nested call {} with calldata: {}",
             index,
-            encode_hex(&calldata)
+            encode_hex(&nested_call.calldata)
         );
 
-        // Create a new DecodeArgs for the nested call
+        // the nested call is raw calldata, and each call nested within it is one level deeper
         let mut nested_args = args.clone();
-        nested_args.target = encode_hex(&calldata);
+        nested_args.target = encode_hex(&nested_call.calldata);
         nested_args.raw = true;
+        nested_args.explain = false;
+        nested_args.max_depth = args.max_depth.saturating_sub(1);
 
-        match Box::pin(decode(nested_args)).await {
+        let decoded = match Box::pin(decode(nested_args)).await {
             Ok(result) => {
-                debug!("Successfully decoded multicall item {}", index);
+                debug!("successfully decoded nested call {}", index);
                 Some(result)
             }
             Err(e) => {
-                debug!("Failed to decode multicall item {}: {:?}", index, e);
+                debug!("failed to decode nested call {}: {:?}", index, e);
                 None
             }
-        }
-    } else {
-        None
-    };
+        };
+
+        results.push(MulticallDecoded {
+            index,
+            target: nested_call.target,
+            value: nested_call.value,
+            calldata: nested_call.calldata,
+            decoded,
+        });
+    }
 
-    Ok(MulticallDecoded { index, target, value, calldata, decoded })
+    results
+}
+
+/// Represents a decoded nested call, such as one of the calls of a multicall
+#[derive(Debug, Clone)]
+pub struct MulticallDecoded {
+    pub index: usize,
+    /// The contract the call is made to, if it's known
+    pub target: Option<String>,
+    pub value: Option<String>,
+    pub calldata: Vec<u8>,
+    /// The decoded call, which includes the calls nested within it
+    pub decoded: Option<DecodeResult>,
 }
 
-/// Formats multicall results for display
+/// Formats the nested calls as a tree for display
+#[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn format_multicall_trace(
     multicall_results: &[MulticallDecoded],
     parent_trace: u32,
     trace_factory: &mut TraceFactory,
 ) {
-    // Build all multicall messages as a single batch
+    // Build all nested call messages as a single batch
+    let mut messages = Vec::new();
+    messages.push("calls:".to_string());
+    messages.extend(format_nested_calls(multicall_results, "   "));
+
+    // Add all nested call lines as a single message
+    trace_factory.add_message(parent_trace, line!(), messages);
+}
+
+/// Formats each of the nested calls, and the calls nested within them, indented by `indent`.
+#[cfg(not(target_arch = "wasm32"))]
+fn format_nested_calls(multicall_results: &[MulticallDecoded], indent: &str) -> Vec<String> {
     let mut messages = Vec::new();
-    messages.push("multicall:".to_string());
 
     for (idx, result) in multicall_results.iter().enumerate() {
         let is_last = idx == multicall_results.len() - 1;
         let prefix = if is_last { "└─" } else { "├─" };
         let continuation = if is_last { "   " } else { "│  " };
+        let continuation = format!("{indent}{continuation}");
 
-        messages.push(format!("   {} [{}] target: {}", prefix, result.index, result.target));
+        match &result.target {
+            Some(target) => {
+                messages.push(format!("{indent}{prefix} [{}] target: {target}", result.index))
+            }
+            None => messages.push(format!("{indent}{prefix} [{}]", result.index)),
+        }
 
         if let Some(decoded) = &result.decoded {
             // Add the decoded function signature
-            messages.push(format!("   {}    └─ {}", continuation, decoded.decoded.signature));
+            messages.push(format!("{continuation}    └─ {}", decoded.decoded.signature));
 
             // Add decoded inputs
             if let Some(inputs) = &decoded.decoded.decoded_inputs {
                 if inputs.is_empty() {
                     // Show that there are no parameters
-                    messages.push(format!("   {continuation}         (no parameters)"));
+                    messages.push(format!("{continuation}         (no parameters)"));
                 } else {
                     for (i, input) in inputs.iter().enumerate() {
-                        let formatted_inputs = display(
-                            vec![input.clone()],
-                            &format!("   {continuation}              "),
-                        );
+                        let formatted_inputs =
+                            display(vec![input.clone()], &format!("{continuation}              "));
                         if !formatted_inputs.is_empty() {
                             // Format the first line with input index
                             let first_line = format!(
-                                "   {}         input {}: {}",
+                                "{}         input {}: {}",
                                 continuation,
                                 i,
-                                formatted_inputs[0].trim_start_matches(&format!(
-                                    "   {continuation}              "
-                                ))
+                                formatted_inputs[0]
+                                    .trim_start_matches(&format!("{continuation}              "))
                             );
                             messages.push(first_line);
 
                             // Add subsequent lines with proper indentation
                             for line in formatted_inputs.iter().skip(1) {
                                 let line = line.replace(
-                                    &format!("   {continuation}              "),
-                                    &format!("   {continuation}                "),
+                                    &format!("{continuation}              "),
+                                    &format!("{continuation}                "),
                                 );
                                 messages.push(line);
                             }
@@ -199,42 +275,49 @@ pub(crate) fn format_multicall_trace(
                             match input {
                                 DynSolValue::Bytes(b) if b.is_empty() => {
                                     messages.push(format!(
-                                        "   {continuation}         input {i}: bytes: 0x (empty)"
+                                        "{continuation}         input {i}: bytes: 0x (empty)"
                                     ));
                                 }
                                 DynSolValue::String(s) if s.is_empty() => {
                                     messages.push(format!(
-                                        "   {continuation}         input {i}: string: \"\" (empty)"
+                                        "{continuation}         input {i}: string: \"\" (empty)"
                                     ));
                                 }
                                 _ => {
                                     // Fallback for other empty types
-                                    messages.push(format!(
-                                        "   {continuation}         input {i}: (empty)"
-                                    ));
+                                    messages
+                                        .push(format!("{continuation}         input {i}: (empty)"));
                                 }
                             }
                         }
                     }
                 }
             }
+
+            // Add the calls nested within this call
+            if let Some(nested_results) = &decoded.multicall_results {
+                messages.push(format!("{continuation}         calls:"));
+                messages.extend(format_nested_calls(
+                    nested_results,
+                    &format!("{continuation}            "),
+                ));
+            }
         } else {
             // Show raw calldata if decoding failed
             messages.push(format!(
-                "   {}    └─ Raw calldata: 0x{}",
+                "{}    └─ Raw calldata: 0x{}",
                 continuation,
                 encode_hex(&result.calldata)
             ));
         }
 
-        // Add space between multicalls if not the last one
+        // Add space between nested calls if not the last one
         if !is_last {
-            messages.push(format!("   {continuation} "));
+            messages.push(format!("{continuation} "));
         }
     }
 
-    // Add all multicall lines as a single message
-    trace_factory.add_message(parent_trace, line!(), messages);
+    messages
 }
 
 #[cfg(test)]
@@ -412,6 +495,62 @@ mod tests {
         assert!(is_multicall_pattern(&permutation3));
     }
 
+    #[test]
+    fn test_find_nested_calls_in_safe_transaction() {
+        // execTransaction(to, value, data, operation, ..., signatures), whose signatures aren't
+        // calldata
+        let data = [vec![0xa9, 0x05, 0x9c, 0xbb], vec![0u8; 64]].concat();
+        let inputs = vec![
+            DynSolValue::Address(Address::repeat_byte(1)),
+            DynSolValue::Uint(U256::from(5), 256),
+            DynSolValue::Bytes(data.clone()),
+            DynSolValue::Uint(U256::ZERO, 8),
+            DynSolValue::Bytes(vec![0xff; 65]),
+        ];
+
+        let calls = find_nested_calls(&inputs);
+        assert_eq!(
+            calls,
+            vec![NestedCall {
+                target: Some(format!("{:?}", Address::repeat_byte(1))),
+                value: Some("5".to_string()),
+                calldata: data,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_nested_calls_in_parallel_arrays() {
+        // scheduleBatch(targets, values, payloads, ...), with a value transfer without calldata
+        let payload = [vec![0x12, 0x34, 0x56, 0x78], vec![0u8; 32]].concat();
+        let inputs = vec![
+            DynSolValue::Array(vec![
+                DynSolValue::Address(Address::repeat_byte(1)),
+                DynSolValue::Address(Address::repeat_byte(2)),
+            ]),
+            DynSolValue::Array(vec![
+                DynSolValue::Uint(U256::from(1), 256),
+                DynSolValue::Uint(U256::from(2), 256),
+            ]),
+            DynSolValue::Array(vec![
+                DynSolValue::Bytes(Vec::new()),
+                DynSolValue::Bytes(payload.clone()),
+            ]),
+            DynSolValue::FixedBytes(Default::default(), 32),
+        ];
+
+        let calls = find_nested_calls(&inputs);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, Some(format!("{:?}", Address::repeat_byte(2))));
+        assert_eq!(calls[0].value, Some("2".to_string()));
+        assert_eq!(calls[0].calldata, payload);
+
+        // multicall(bytes[]) calls itself, so its calls have no target
+        let inputs = vec![DynSolValue::Array(vec![DynSolValue::Bytes(payload.clone())])];
+        let calls = find_nested_calls(&inputs);
+        assert_eq!(calls, vec![NestedCall { target: None, value: None, calldata: payload }]);
+    }
+
     #[test]
     fn test_real_multicall_data() {
         use alloy_dyn_abi::DynSolType;
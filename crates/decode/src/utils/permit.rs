@@ -0,0 +1,389 @@
+use alloy::primitives::{address, keccak256, normalize_v, Address, Signature, B256, U256};
+use alloy_dyn_abi::{DynSolType, DynSolValue, TypedData};
+use heimdall_common::ether::types::parse_function_parameters;
+use serde_json::{json, Value};
+use tracing::debug;
+
+/// The canonical Permit2 deployment, which is the verifying contract of every Permit2 signature.
+pub(crate) const PERMIT2: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
+
+/// EIP-2612 `permit(owner, spender, value, deadline, v, r, s)`.
+const EIP2612_PERMIT: &str = "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)";
+
+/// DAI's `permit(holder, spender, nonce, expiry, allowed, v, r, s)`.
+const DAI_PERMIT: &str = "permit(address,address,uint256,uint256,bool,uint8,bytes32,bytes32)";
+
+/// Permit2's `permit(owner, PermitSingle, signature)`.
+const PERMIT2_PERMIT_SINGLE: &str =
+    "permit(address,((address,uint160,uint48,uint48),address,uint256),bytes)";
+
+/// Permit2's `permit(owner, PermitBatch, signature)`.
+const PERMIT2_PERMIT_BATCH: &str =
+    "permit(address,((address,uint160,uint48,uint48)[],address,uint256),bytes)";
+
+/// A permit recognized in calldata, i.e. an approval signed off-chain by its owner as EIP-712
+/// typed data.
+#[derive(Debug, Clone)]
+pub(crate) struct Permit {
+    /// The standard of the permit, i.e. `EIP-2612`, `DAI` or `Permit2`
+    pub standard: &'static str,
+    /// The address the permit claims to be signed by
+    pub owner: Address,
+    /// The typed data which was signed. The domain of a token's permit only holds its chain id
+    /// and verifying contract, and the message of an EIP-2612 permit lacks the owner's nonce, as
+    /// neither are part of the calldata
+    pub typed_data: TypedData,
+    /// The owner's signature over the typed data
+    pub signature: Signature,
+}
+
+impl Permit {
+    /// Whether the permit is signed over the token's own domain, whose separator must be fetched
+    /// from the token, rather than Permit2's
+    pub(crate) fn is_token_permit(&self) -> bool {
+        self.standard != "Permit2"
+    }
+
+    /// Whether the message lacks the owner's nonce, which must be fetched from the token
+    pub(crate) fn is_missing_nonce(&self) -> bool {
+        self.typed_data.message.get("nonce").is_none()
+    }
+
+    /// Completes the message with the owner's nonce at the time the permit was signed
+    pub(crate) fn set_nonce(&mut self, nonce: U256) {
+        self.typed_data.message["nonce"] = json!(nonce.to_string());
+    }
+
+    /// The separator of Permit2's domain, if the permit is a Permit2 permit and its chain is known
+    pub(crate) fn permit2_domain_separator(&self) -> Option<B256> {
+        let domain = &self.typed_data.domain;
+        domain.chain_id.filter(|_| !self.is_token_permit()).map(|_| domain.separator())
+    }
+}
+
+/// Recognizes a call to an EIP-2612, DAI or Permit2 `permit` in the calldata, reconstructing the
+/// typed data its owner signed. `chain_id` and `contract`, the called contract, complete the
+/// permit's domain if they're known.
+pub(crate) fn decode_permit(
+    calldata: &[u8],
+    chain_id: Option<u64>,
+    contract: Option<Address>,
+) -> Option<Permit> {
+    let selector = calldata.get(0..4)?;
+    let signature = [EIP2612_PERMIT, DAI_PERMIT, PERMIT2_PERMIT_SINGLE, PERMIT2_PERMIT_BATCH]
+        .into_iter()
+        .find(|signature| keccak256(signature.as_bytes())[0..4] == *selector)?;
+
+    let types = parse_function_parameters(signature).ok()?;
+    let inputs = match DynSolType::Tuple(types).abi_decode_params(&calldata[4..]) {
+        Ok(DynSolValue::Tuple(inputs)) => inputs,
+        _ => {
+            debug!("calldata has the selector of '{}', but isn't a permit", signature);
+            return None;
+        }
+    };
+
+    let permit = match signature {
+        EIP2612_PERMIT => {
+            let [owner, spender, value, deadline, v, r, s] = inputs.as_slice() else {
+                return None;
+            };
+            let owner = owner.as_address()?;
+            Permit {
+                standard: "EIP-2612",
+                owner,
+                typed_data: typed_data(
+                    json!({
+                        "Permit": [
+                            { "name": "owner", "type": "address" },
+                            { "name": "spender", "type": "address" },
+                            { "name": "value", "type": "uint256" },
+                            { "name": "nonce", "type": "uint256" },
+                            { "name": "deadline", "type": "uint256" },
+                        ]
+                    }),
+                    "Permit",
+                    json!({ "chainId": chain_id, "verifyingContract": contract }),
+                    json!({
+                        "owner": owner.to_string(),
+                        "spender": spender.as_address()?.to_string(),
+                        "value": uint(value)?,
+                        "deadline": uint(deadline)?,
+                    }),
+                )?,
+                signature: vrs_signature(v, r, s)?,
+            }
+        }
+        DAI_PERMIT => {
+            let [holder, spender, nonce, expiry, allowed, v, r, s] = inputs.as_slice() else {
+                return None;
+            };
+            let holder = holder.as_address()?;
+            Permit {
+                standard: "DAI",
+                owner: holder,
+                typed_data: typed_data(
+                    json!({
+                        "Permit": [
+                            { "name": "holder", "type": "address" },
+                            { "name": "spender", "type": "address" },
+                            { "name": "nonce", "type": "uint256" },
+                            { "name": "expiry", "type": "uint256" },
+                            { "name": "allowed", "type": "bool" },
+                        ]
+                    }),
+                    "Permit",
+                    json!({ "chainId": chain_id, "verifyingContract": contract }),
+                    json!({
+                        "holder": holder.to_string(),
+                        "spender": spender.as_address()?.to_string(),
+                        "nonce": uint(nonce)?,
+                        "expiry": uint(expiry)?,
+                        "allowed": allowed.as_bool()?,
+                    }),
+                )?,
+                signature: vrs_signature(v, r, s)?,
+            }
+        }
+        _ => {
+            let [owner, permit, signature] = inputs.as_slice() else {
+                return None;
+            };
+            let [details, spender, sig_deadline] = permit.as_tuple()? else {
+                return None;
+            };
+            let (primary_type, details_type, details) = match details.as_array() {
+                Some(details) => (
+                    "PermitBatch",
+                    "PermitDetails[]",
+                    Value::Array(details.iter().map(permit2_details).collect::<Option<_>>()?),
+                ),
+                None => ("PermitSingle", "PermitDetails", permit2_details(details)?),
+            };
+
+            let signature = signature.as_bytes()?;
+            Permit {
+                standard: "Permit2",
+                owner: owner.as_address()?,
+                typed_data: typed_data(
+                    json!({
+                        primary_type: [
+                            { "name": "details", "type": details_type },
+                            { "name": "spender", "type": "address" },
+                            { "name": "sigDeadline", "type": "uint256" },
+                        ],
+                        "PermitDetails": [
+                            { "name": "token", "type": "address" },
+                            { "name": "amount", "type": "uint160" },
+                            { "name": "expiration", "type": "uint48" },
+                            { "name": "nonce", "type": "uint48" },
+                        ]
+                    }),
+                    primary_type,
+                    json!({ "name": "Permit2", "chainId": chain_id, "verifyingContract": PERMIT2 }),
+                    json!({
+                        "details": details,
+                        "spender": spender.as_address()?.to_string(),
+                        "sigDeadline": uint(sig_deadline)?,
+                    }),
+                )?,
+                signature: match signature.len() {
+                    64 => Signature::from_erc2098(signature),
+                    _ => Signature::from_raw(signature).ok()?,
+                },
+            }
+        }
+    };
+
+    debug!("calldata is a {} permit signed by {}", permit.standard, permit.owner);
+    Some(permit)
+}
+
+/// Builds typed data from its types, primary type, domain and message, omitting the domain's
+/// unknown fields.
+fn typed_data(
+    types: Value,
+    primary_type: &str,
+    domain: Value,
+    message: Value,
+) -> Option<TypedData> {
+    let domain = match domain {
+        Value::Object(fields) => {
+            Value::Object(fields.into_iter().filter(|(_, value)| !value.is_null()).collect())
+        }
+        domain => domain,
+    };
+    serde_json::from_value(json!({
+        "types": types,
+        "primaryType": primary_type,
+        "domain": domain,
+        "message": message,
+    }))
+    .ok()
+}
+
+/// The `PermitDetails` of a Permit2 permit as part of its message.
+fn permit2_details(details: &DynSolValue) -> Option<Value> {
+    let [token, amount, expiration, nonce] = details.as_tuple()? else {
+        return None;
+    };
+    Some(json!({
+        "token": token.as_address()?.to_string(),
+        "amount": uint(amount)?,
+        "expiration": uint(expiration)?,
+        "nonce": uint(nonce)?,
+    }))
+}
+
+/// A uint as part of a message, which is encoded as a decimal string so that it isn't truncated.
+fn uint(value: &DynSolValue) -> Option<Value> {
+    value.as_uint().map(|(value, _)| json!(value.to_string()))
+}
+
+/// A signature given as separate `v`, `r` and `s` parameters.
+fn vrs_signature(v: &DynSolValue, r: &DynSolValue, s: &DynSolValue) -> Option<Signature> {
+    let (v, _) = v.as_uint()?;
+    let parity = normalize_v(u64::try_from(v).ok()?)?;
+    let (r, _) = r.as_fixed_bytes()?;
+    let (s, _) = s.as_fixed_bytes()?;
+    Some(Signature::from_scalars_and_parity(B256::from_slice(r), B256::from_slice(s), parity))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_dyn_abi::DynSolValue;
+
+    use super::*;
+    use crate::interfaces::DecodedTypedData;
+
+    /// Encodes the calldata of a call to the function with the given signature.
+    fn calldata(signature: &str, inputs: Vec<DynSolValue>) -> Vec<u8> {
+        [
+            &keccak256(signature.as_bytes())[0..4],
+            DynSolValue::Tuple(inputs).abi_encode_params().as_slice(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_decode_eip2612_permit() {
+        let signer = PrivateKeySigner::random();
+        let token = Address::with_last_byte(0xaa);
+        let domain_separator = B256::with_last_byte(1);
+
+        // sign the permit as the token would verify it, with the owner's nonce of 3
+        let mut permit = decode_permit(
+            &calldata(
+                EIP2612_PERMIT,
+                vec![
+                    DynSolValue::Address(signer.address()),
+                    DynSolValue::Address(Address::with_last_byte(0xbb)),
+                    DynSolValue::Uint(U256::from(1000), 256),
+                    DynSolValue::Uint(U256::from(1_700_000_000), 256),
+                    DynSolValue::Uint(U256::from(27), 8),
+                    DynSolValue::FixedBytes(B256::ZERO, 32),
+                    DynSolValue::FixedBytes(B256::ZERO, 32),
+                ],
+            ),
+            Some(1),
+            Some(token),
+        )
+        .expect("failed to decode permit");
+        assert!(permit.is_token_permit());
+        assert!(permit.is_missing_nonce());
+        assert_eq!(permit.owner, signer.address());
+        assert_eq!(permit.typed_data.domain.verifying_contract, Some(token));
+
+        permit.set_nonce(U256::from(3));
+        let signature = signer
+            .sign_hash_sync(
+                &DecodedTypedData::new(
+                    None,
+                    permit.typed_data.clone(),
+                    Some(domain_separator),
+                    None,
+                    None,
+                )
+                .digest
+                .expect("failed to compute digest"),
+            )
+            .expect("failed to sign");
+
+        let decoded = DecodedTypedData::new(
+            Some(permit.standard),
+            permit.typed_data,
+            Some(domain_separator),
+            Some(&signature),
+            Some(permit.owner),
+        );
+        assert_eq!(decoded.signer, Some(signer.address()));
+        assert_eq!(decoded.is_valid(), Some(true));
+        assert_eq!(decoded.typed_data.message["nonce"], "3");
+    }
+
+    #[test]
+    fn test_decode_permit2_permit() {
+        let signer = PrivateKeySigner::random();
+        let details = DynSolValue::Tuple(vec![
+            DynSolValue::Address(Address::with_last_byte(0xaa)),
+            DynSolValue::Uint(U256::from(1000), 160),
+            DynSolValue::Uint(U256::from(1_700_000_000), 48),
+            DynSolValue::Uint(U256::from(0), 48),
+        ]);
+        let permit_single = DynSolValue::Tuple(vec![
+            details,
+            DynSolValue::Address(Address::with_last_byte(0xbb)),
+            DynSolValue::Uint(U256::from(1_700_000_000), 256),
+        ]);
+
+        // the permit is signed over its typed data, which doesn't depend on the signature
+        let unsigned = decode_permit(
+            &calldata(
+                PERMIT2_PERMIT_SINGLE,
+                vec![
+                    DynSolValue::Address(signer.address()),
+                    permit_single.clone(),
+                    DynSolValue::Bytes(vec![0; 65]),
+                ],
+            ),
+            Some(1),
+            None,
+        )
+        .expect("failed to decode permit");
+        let digest = unsigned.typed_data.eip712_signing_hash().expect("failed to compute digest");
+        let signature = signer.sign_hash_sync(&digest).expect("failed to sign");
+
+        let permit = decode_permit(
+            &calldata(
+                PERMIT2_PERMIT_SINGLE,
+                vec![
+                    DynSolValue::Address(signer.address()),
+                    permit_single,
+                    DynSolValue::Bytes(signature.as_bytes().to_vec()),
+                ],
+            ),
+            Some(1),
+            None,
+        )
+        .expect("failed to decode permit");
+        assert_eq!(permit.typed_data.primary_type, "PermitSingle");
+        assert_eq!(permit.typed_data.domain.verifying_contract, Some(PERMIT2));
+
+        let decoded = DecodedTypedData::new(
+            Some(permit.standard),
+            permit.typed_data.clone(),
+            permit.permit2_domain_separator(),
+            Some(&permit.signature),
+            Some(permit.owner),
+        );
+        assert_eq!(decoded.digest, Some(digest));
+        assert_eq!(decoded.is_valid(), Some(true));
+    }
+
+    #[test]
+    fn test_decode_non_permit() {
+        assert!(decode_permit(&[0xa9, 0x05, 0x9c, 0xbb], Some(1), None).is_none());
+        assert!(decode_permit(&keccak256(DAI_PERMIT.as_bytes())[0..4], Some(1), None).is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use alloy::{
+    consensus::{transaction::SignerRecoverable, TxEip4844Variant, TxEnvelope},
+    eips::eip2718::Decodable2718,
+    primitives::Address,
+};
+use heimdall_common::{ether::beacon::blob_contents, utils::strings::decode_hex};
+use tracing::debug;
+
+use crate::interfaces::{DecodedBlob, DecodedTransaction};
+
+/// A raw signed transaction, along with the sender recovered from its signature.
+#[derive(Debug, Clone)]
+pub(crate) struct SignedTransaction {
+    pub envelope: TxEnvelope,
+    pub signer: Address,
+}
+
+impl SignedTransaction {
+    /// Summarizes the envelope of the signed transaction, including the blobs of its sidecar if
+    /// it's a blob transaction in its network form.
+    pub(crate) fn to_decoded_transaction(&self) -> DecodedTransaction {
+        let mut decoded_transaction = DecodedTransaction::new(&self.envelope, Some(self.signer));
+        if let TxEnvelope::Eip4844(signed) = &self.envelope {
+            if let TxEip4844Variant::TxEip4844WithSidecar(transaction) = signed.tx() {
+                decoded_transaction.blobs = transaction
+                    .sidecar
+                    .blobs
+                    .iter()
+                    .zip(transaction.sidecar.versioned_hashes())
+                    .enumerate()
+                    .map(|(i, (blob, versioned_hash))| DecodedBlob {
+                        index: i as u64,
+                        versioned_hash,
+                        contents: blob_contents(blob.as_slice()),
+                    })
+                    .collect();
+            }
+        }
+        decoded_transaction
+    }
+}
+
+/// Parses the target as a raw signed transaction, i.e. an RLP-encoded legacy transaction or an
+/// EIP-2718 typed transaction envelope, recovering its sender from its signature. Returns `None`
+/// if the target isn't a signed transaction, in which case it's decoded as calldata.
+pub(crate) fn decode_signed_transaction(target: &str) -> Option<SignedTransaction> {
+    let raw = decode_hex(target).ok()?;
+    let mut buf = raw.as_slice();
+    let envelope = TxEnvelope::decode_2718(&mut buf).ok().filter(|_| buf.is_empty())?;
+
+    // calldata which happens to be valid rlp won't have a valid signature
+    let signer = envelope
+        .recover_signer()
+        .inspect_err(|e| debug!("target is rlp, but its signature is invalid: {}", e))
+        .ok()?;
+    Some(SignedTransaction { envelope, signer })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{SignableTransaction, TxEip1559},
+        eips::eip2718::Encodable2718,
+        primitives::{address, Bytes, TxKind, U256},
+        signers::{local::PrivateKeySigner, SignerSync},
+    };
+    use heimdall_common::utils::strings::encode_hex;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_legacy_transaction() {
+        // the example transaction from EIP-155
+        let signed = decode_signed_transaction("0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83")
+            .expect("failed to decode signed transaction");
+        assert_eq!(signed.signer, address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"));
+
+        let decoded_transaction = signed.to_decoded_transaction();
+        assert_eq!(decoded_transaction.tx_type, 0);
+        assert_eq!(decoded_transaction.chain_id, Some(1));
+        assert_eq!(decoded_transaction.nonce, 9);
+        assert_eq!(decoded_transaction.gas_price, Some(20_000_000_000));
+        assert_eq!(decoded_transaction.from, Some(signed.signer));
+    }
+
+    #[test]
+    fn test_decode_typed_transaction() {
+        let signer = PrivateKeySigner::random();
+        let transaction = TxEip1559 {
+            chain_id: 1,
+            nonce: 1,
+            gas_limit: 100_000,
+            max_fee_per_gas: 30,
+            max_priority_fee_per_gas: 2,
+            to: TxKind::Call(Address::with_last_byte(1)),
+            value: U256::ZERO,
+            input: Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]),
+            ..Default::default()
+        };
+        let signature =
+            signer.sign_hash_sync(&transaction.signature_hash()).expect("failed to sign");
+        let raw = TxEnvelope::from(transaction.into_signed(signature)).encoded_2718();
+
+        let signed = decode_signed_transaction(&format!("0x{}", encode_hex(&raw)))
+            .expect("failed to decode signed transaction");
+        assert_eq!(signed.signer, signer.address());
+        assert_eq!(signed.to_decoded_transaction().type_name(), "EIP-1559");
+    }
+
+    #[test]
+    fn test_calldata_is_not_a_signed_transaction() {
+        assert!(decode_signed_transaction("0xa9059cbb000000000000000000000000000000000000000000000000000000000000dead0000000000000000000000000000000000000000000000000000000000000001").is_none());
+        assert!(decode_signed_transaction("not hex").is_none());
+    }
+}
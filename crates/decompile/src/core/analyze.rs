@@ -1,21 +1,36 @@
-use std::{fmt::Display, time::Instant};
+use std::fmt::Display;
+use web_time::Instant;
 
+use alloy::primitives::U256;
 use futures::future::BoxFuture;
-use heimdall_vm::ext::exec::VMTrace;
+use hashbrown::HashMap;
+use heimdall_vm::{
+    core::{
+        opcodes::{WrappedOpcode, MSTORE, MSTORE8},
+        vm::State,
+    },
+    ext::exec::VMTrace,
+};
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use crate::{
-    interfaces::AnalyzedFunction,
+    core::{
+        internal::{call_at, InternalCall},
+        loops::{find_loop, is_back_edge, leaves_loop, LoopHeader},
+        origins::{extend_origins, Origin},
+    },
+    interfaces::{AnalyzedFunction, StorageFrame, StorageSlot},
     utils::heuristics::{
-        argument_heuristic, event_heuristic, extcall_heuristic, modifier_heuristic,
-        solidity_heuristic, yul_heuristic, Heuristic,
+        argument_heuristic, error_heuristic, event_heuristic, extcall_heuristic,
+        modifier_heuristic, solidity_heuristic, storage_heuristic, yul_heuristic, Heuristic,
     },
     Error,
 };
 
 /// The type of analyzer to use. This will determine which heuristics are used when analyzing a
 /// [`VMTrace`] generated by symbolic execution.
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub(crate) enum AnalyzerType {
     /// Analyze the trace using Solidity heuristics, which will generate high-level Solidity code
     Solidity,
@@ -59,6 +74,19 @@ pub(crate) struct AnalyzerState {
     pub analyzer_type: AnalyzerType,
     /// Whether to skip resolving internal calls
     pub skip_resolving: bool,
+    /// Maps keccak256 hashes computed by the function to the storage location they represent
+    pub hashed_slots: HashMap<U256, StorageSlot>,
+    /// Maps SLOAD operations to the storage location and member they loaded, for tracking packed
+    /// reads
+    pub loaded_slots: HashMap<WrappedOpcode, (StorageSlot, usize)>,
+    /// The headers of the loops the current branch is in, from the outermost loop inwards
+    pub loops: Vec<LoopHeader>,
+    /// The call to an internal function the current branch is in, whose body is analyzed
+    /// separately
+    pub internal_call: Option<InternalCall>,
+    /// Whether the compiler checks arithmetic for overflows itself, rather than the contract
+    /// checking it with SafeMath
+    pub checked_arithmetic: bool,
 }
 
 /// The analyzer, which will analyze a [`VMTrace`] generated by symbolic execution and build an
@@ -74,12 +102,59 @@ pub(crate) struct Analyzer {
     function: AnalyzedFunction,
     /// A list of registered heuristics with the Heuristic Trait
     heuristics: Vec<Heuristic>,
+    /// The heuristics which also run over the bodies of the internal functions the function
+    /// calls, tracking its arguments, mutability and events without adding to its logic
+    inlined_heuristics: Vec<Heuristic>,
+    /// The name and number of arguments of the internal functions which are called rather than
+    /// inlined, keyed by their entries
+    internal_functions: HashMap<u128, (String, usize)>,
+    /// The call the function returns from, if it's the body of an internal function
+    returning: Option<InternalCall>,
+    /// Whether the compiler checks arithmetic for overflows itself
+    checked_arithmetic: bool,
+    /// The instructions analyzed since a line of logic was last added or changed, which the next
+    /// line is lifted from
+    pending_origins: Vec<Origin>,
 }
 
 impl Analyzer {
     /// Build a new analyzer with the given type, function, and trace
     pub(crate) fn new(typ: AnalyzerType, skip_resolving: bool, function: AnalyzedFunction) -> Self {
-        Self { typ, function, skip_resolving, heuristics: Vec::new() }
+        Self {
+            typ,
+            function,
+            skip_resolving,
+            heuristics: Vec::new(),
+            inlined_heuristics: Vec::new(),
+            internal_functions: HashMap::new(),
+            returning: None,
+            checked_arithmetic: true,
+            pending_origins: Vec::new(),
+        }
+    }
+
+    /// Calls the given internal functions, keyed by their entries, rather than inlining their
+    /// bodies into the function's logic
+    pub(crate) fn with_internal_functions(
+        mut self,
+        internal_functions: HashMap<u128, (String, usize)>,
+    ) -> Self {
+        self.internal_functions = internal_functions;
+        self
+    }
+
+    /// Sets whether the compiler checks arithmetic for overflows itself, which it has since solc
+    /// 0.8.0. Otherwise the SafeMath checks the contract makes are noted in its logic, like the
+    /// compiler's checks
+    pub(crate) fn with_checked_arithmetic(mut self, checked_arithmetic: bool) -> Self {
+        self.checked_arithmetic = checked_arithmetic;
+        self
+    }
+
+    /// Analyzes the body of an internal function, which returns from the given call
+    pub(crate) fn returning(mut self, call: InternalCall) -> Self {
+        self.returning = Some(call);
+        self
     }
 
     /// Register heuristics for the given function and trace
@@ -91,15 +166,23 @@ impl Analyzer {
                 self.heuristics.push(Heuristic::new(argument_heuristic));
                 self.heuristics.push(Heuristic::new(modifier_heuristic));
                 self.heuristics.push(Heuristic::new(extcall_heuristic));
+                self.heuristics.push(Heuristic::new(storage_heuristic));
+
+                self.inlined_heuristics.push(Heuristic::new(event_heuristic));
+                self.inlined_heuristics.push(Heuristic::new(argument_heuristic));
+                self.inlined_heuristics.push(Heuristic::new(modifier_heuristic));
+                self.inlined_heuristics.push(Heuristic::new(storage_heuristic));
             }
             AnalyzerType::Yul => {
                 self.heuristics.push(Heuristic::new(event_heuristic));
                 self.heuristics.push(Heuristic::new(yul_heuristic));
                 self.heuristics.push(Heuristic::new(argument_heuristic));
                 self.heuristics.push(Heuristic::new(modifier_heuristic));
+                self.heuristics.push(Heuristic::new(storage_heuristic));
             }
             AnalyzerType::Abi => {
                 self.heuristics.push(Heuristic::new(event_heuristic));
+                self.heuristics.push(Heuristic::new(error_heuristic));
                 self.heuristics.push(Heuristic::new(argument_heuristic));
                 self.heuristics.push(Heuristic::new(modifier_heuristic));
             }
@@ -126,11 +209,20 @@ impl Analyzer {
             conditional_stack: Vec::new(),
             analyzer_type: self.typ,
             skip_resolving: self.skip_resolving,
+            hashed_slots: HashMap::new(),
+            loaded_slots: HashMap::new(),
+            loops: Vec::new(),
+            internal_call: None,
+            checked_arithmetic: self.checked_arithmetic,
         };
 
         // Perform analysis
         self.analyze_inner(&trace_root, &mut analyzer_state).await?;
 
+        // conditionals which were later folded into requires leave their closing brackets behind
+        drop_unmatched_brackets(&mut self.function.logic);
+        self.function.sync_origins(&mut Vec::new());
+
         debug!(
             "analysis for '{}' completed in {:?}",
             self.function.selector,
@@ -149,20 +241,130 @@ impl Analyzer {
         Box::pin(async move {
             // reset jumped conditional, we dont propagate conditionals across branches
             analyzer_state.jumped_conditional = None;
+            let internal_call = analyzer_state.internal_call.clone();
+            self.pending_origins.clear();
+
+            // a branch which returns to the header of a loop it's in starts the loop's next
+            // iteration, which has already been analyzed
+            let is_back_edge = is_back_edge(branch, &analyzer_state.loops);
+            let operations = match is_back_edge {
+                true => &branch.operations[..branch.operations.len() - 1],
+                false => &branch.operations[..],
+            };
 
             // for each operation in the current trace branch, peform analysis with registerred
             // heuristics
-            for operation in &branch.operations {
+            let mut conditional_at = self.function.logic.len();
+            for (i, operation) in operations.iter().enumerate() {
+                conditional_at = self.function.logic.len();
+
+                // the bodies of internal functions are analyzed separately, but the arguments,
+                // mutability and events of their callers still depend on them
+                if let Some(call) = &analyzer_state.internal_call {
+                    if call.returns_from(operation) {
+                        analyzer_state.internal_call = None;
+                    }
+                    self.run_inlined_heuristics(operation, analyzer_state).await?;
+                    continue;
+                }
+                extend_origins(&mut self.pending_origins, &operation.last_instruction);
+                if let Some((name, call)) = self.internal_call_at(branch, i) {
+                    self.function.logic.push(call.to_line(&name, operation));
+                    self.function.sync_origins(&mut self.pending_origins);
+                    self.function.internal_calls.insert(call.entry);
+                    analyzer_state.internal_call = Some(call);
+                    continue;
+                }
+
                 for heuristic in &self.heuristics {
                     heuristic.run(&mut self.function, operation, analyzer_state).await?;
                 }
+                self.function.sync_origins(&mut self.pending_origins);
             }
 
-            // recurse into the children of the current trace branch
-            for child in &branch.children {
-                self.analyze_inner(child, analyzer_state).await?;
+            // only the first path which returns from the internal function continues its caller,
+            // but the others may still read its arguments or emit its events
+            if let Some(call) = analyzer_state.internal_call.clone() {
+                let continues = branch.children.iter().find(|child| call.returns_within(child));
+                for child in &branch.children {
+                    match continues.is_some_and(|continues| std::ptr::eq(child, continues)) {
+                        true => self.analyze_inner(child, analyzer_state).await?,
+                        false => self.inline_branch(child, analyzer_state).await?,
+                    }
+                    analyzer_state.internal_call = Some(call.clone());
+                }
+                analyzer_state.internal_call = internal_call;
+                return Ok(());
             }
 
+            // the bodies of internal functions return their values to the caller
+            if let Some(returned) = branch
+                .operations
+                .last()
+                .filter(|state| {
+                    branch.children.is_empty() &&
+                        self.returning.as_ref().is_some_and(|call| call.returns_from(state))
+                })
+                .and_then(|state| {
+                    let values = self.returning.as_ref()?.returned_values(state);
+                    match values.as_slice() {
+                        [] => None,
+                        [value] => Some(format!("return {};", value.solidify())),
+                        values => Some(format!(
+                            "return ({});",
+                            values.iter().map(|v| v.solidify()).collect::<Vec<_>>().join(", ")
+                        )),
+                    }
+                })
+            {
+                self.function.logic.push(returned);
+                self.function.sync_origins(&mut self.pending_origins);
+            }
+
+            if is_back_edge {
+                analyzer_state.internal_call = internal_call;
+                return Ok(());
+            }
+
+            // children are in the loops this branch doesn't leave
+            let loops = analyzer_state.loops.clone();
+            analyzer_state.loops.retain(|header| !leaves_loop(branch, header));
+
+            // if this branch ends at a loop's header, its conditional opens the loop instead
+            let found_loop = find_loop(branch).filter(|_| {
+                self.function.logic.len() == conditional_at + 1 &&
+                    self.function.logic[conditional_at].starts_with("if")
+            });
+            match found_loop {
+                Some(found_loop) => {
+                    self.function.logic[conditional_at] =
+                        found_loop.to_header(self.typ, analyzer_state.loops.len());
+                    analyzer_state.conditional_stack.pop();
+                    analyzer_state.jumped_conditional = None;
+
+                    // the loop is closed after its body, before the code which runs once it exits
+                    analyzer_state.loops.push(found_loop.header());
+                    self.analyze_inner(found_loop.body, analyzer_state).await?;
+                    analyzer_state.loops.pop();
+                    let depth = block_depth(&self.function.logic[..conditional_at]);
+                    for _ in depth..block_depth(&self.function.logic) {
+                        self.function.logic.push("}".to_string());
+                    }
+                    self.function.sync_origins(&mut Vec::new());
+                    if let Some(exit) = found_loop.exit {
+                        self.analyze_inner(exit, analyzer_state).await?;
+                    }
+                }
+                None => {
+                    // recurse into the children of the current trace branch
+                    for child in &branch.children {
+                        self.analyze_inner(child, analyzer_state).await?;
+                    }
+                }
+            }
+            analyzer_state.loops = loops;
+            analyzer_state.internal_call = internal_call;
+
             // check if the ending brackets are needed
             if analyzer_state.jumped_conditional.is_some() &&
                 analyzer_state.conditional_stack.contains(
@@ -184,9 +386,97 @@ impl Analyzer {
                 }
 
                 self.function.logic.push("}".to_string());
+                self.function.sync_origins(&mut Vec::new());
             }
 
             Ok(())
         })
     }
 }
+
+impl Analyzer {
+    /// Runs the inlined heuristics over the branch and its children, without adding to the
+    /// function's logic.
+    fn inline_branch<'a>(
+        &'a mut self,
+        branch: &'a VMTrace,
+        analyzer_state: &'a mut AnalyzerState,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            for operation in &branch.operations {
+                self.run_inlined_heuristics(operation, analyzer_state).await?;
+            }
+            for child in &branch.children {
+                self.inline_branch(child, analyzer_state).await?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Runs the inlined heuristics over an operation in the body of an internal function.
+    async fn run_inlined_heuristics(
+        &mut self,
+        operation: &State,
+        analyzer_state: &mut AnalyzerState,
+    ) -> Result<(), Error> {
+        let logic_len = self.function.logic.len();
+        for heuristic in &self.inlined_heuristics {
+            heuristic.run(&mut self.function, operation, analyzer_state).await?;
+        }
+        self.function.logic.truncate(logic_len);
+
+        // memory written by an internal function may be returned by its caller
+        let instruction = &operation.last_instruction;
+        if matches!(instruction.opcode, MSTORE | MSTORE8) {
+            self.function.memory.insert(
+                instruction.inputs[0],
+                StorageFrame {
+                    operation: instruction.input_operations[1].clone(),
+                    value: instruction.inputs[1],
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// The call to one of the internal functions which are called rather than inlined, made by
+    /// the operation at `index` in the branch.
+    fn internal_call_at(&self, branch: &VMTrace, index: usize) -> Option<(String, InternalCall)> {
+        let entry = branch.operations[index].last_instruction.inputs.first()?;
+        let (name, arguments) = self.internal_functions.get(&u128::try_from(*entry).ok()?)?;
+        call_at(branch, index, *arguments).map(|call| (name.clone(), call))
+    }
+}
+
+/// The number of blocks left open by the lines.
+fn block_depth(logic: &[String]) -> usize {
+    logic.iter().fold(0, |depth, line| {
+        let line = line.trim();
+        let depth = match line.starts_with('}') {
+            true => depth.saturating_sub(1),
+            false => depth,
+        };
+        match line.ends_with('{') {
+            true => depth + 1,
+            false => depth,
+        }
+    })
+}
+
+/// Removes the closing brackets which don't close a block.
+fn drop_unmatched_brackets(logic: &mut Vec<String>) {
+    let mut depth = 0usize;
+    logic.retain(|line| {
+        let line = line.trim();
+        if line == "}" && depth == 0 {
+            return false;
+        }
+        if line.starts_with('}') {
+            depth = depth.saturating_sub(1);
+        }
+        if line.ends_with('{') {
+            depth += 1;
+        }
+        true
+    });
+}
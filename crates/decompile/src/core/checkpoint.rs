@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::keccak256;
+use eyre::eyre;
+use heimdall_common::utils::{io::file::read_file, strings::encode_hex};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{core::analyze::AnalyzerType, error::Error, interfaces::AnalyzedFunction};
+
+/// The functions analyzed so far while decompiling a target, which are saved to the `--resume`
+/// file as each one is analyzed, so that an interrupted run doesn't have to execute them again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    /// The hash of the bytecode being decompiled, so that a checkpoint isn't resumed for a
+    /// different target
+    bytecode_hash: String,
+    /// The analyzer the functions were analyzed with
+    analyzer_type: AnalyzerType,
+    /// The analyzed functions, keyed by selector
+    functions: BTreeMap<String, CheckpointedFunction>,
+}
+
+/// A function restored from a [`Checkpoint`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CheckpointedFunction {
+    /// The function, as analyzed from its symbolic execution trace
+    pub function: AnalyzedFunction,
+    /// The number of unique branches symbolic execution found in the function
+    pub branches: u32,
+}
+
+impl Checkpoint {
+    /// Creates an empty checkpoint for decompiling the bytecode with the given analyzer.
+    pub(crate) fn new(bytecode: &[u8], analyzer_type: AnalyzerType) -> Self {
+        Self {
+            bytecode_hash: encode_hex(keccak256(bytecode).as_slice()),
+            analyzer_type,
+            functions: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the checkpoint saved at `path`. If there's none, or it was saved while decompiling
+    /// other bytecode or with another analyzer, an empty checkpoint is returned instead.
+    pub(crate) fn load(path: &str, bytecode: &[u8], analyzer_type: AnalyzerType) -> Self {
+        let checkpoint = Self::new(bytecode, analyzer_type);
+        let Ok(contents) = read_file(path) else {
+            return checkpoint;
+        };
+
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(saved)
+                if saved.bytecode_hash == checkpoint.bytecode_hash &&
+                    saved.analyzer_type == analyzer_type =>
+            {
+                info!("resuming {} analyzed functions from '{}'", saved.functions.len(), path);
+                saved
+            }
+            Ok(_) => {
+                warn!("the checkpoint at '{}' is for another target, starting over", path);
+                checkpoint
+            }
+            Err(e) => {
+                warn!("failed to parse the checkpoint at '{}', starting over: {}", path, e);
+                checkpoint
+            }
+        }
+    }
+
+    /// Gets the function with the given selector, if it was already analyzed.
+    pub(crate) fn get(&self, selector: &str) -> Option<&CheckpointedFunction> {
+        self.functions.get(selector)
+    }
+
+    /// Records the analyzed function, and saves the checkpoint to `path`. The checkpoint is
+    /// written to a temporary file which replaces it, so that it's never left partially written.
+    pub(crate) fn save(
+        &mut self,
+        path: &str,
+        selector: &str,
+        function: CheckpointedFunction,
+    ) -> Result<(), Error> {
+        self.functions.insert(selector.to_string(), function);
+
+        let contents = serde_json::to_string(self)
+            .map_err(|e| Error::Eyre(eyre!("failed to serialize checkpoint: {}", e)))?;
+        let partial = format!("{path}.partial");
+        std::fs::write(&partial, contents)
+            .and_then(|_| std::fs::rename(&partial, path))
+            .map_err(|e| Error::Eyre(eyre!("failed to write checkpoint to '{}': {}", path, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let path = std::env::temp_dir().join("heimdall_test_checkpoint.json");
+        let path = path.to_str().expect("invalid path");
+        let _ = std::fs::remove_file(path);
+
+        let mut function = AnalyzedFunction::new("a9059cbb", false);
+        function.logic.push("store_a = arg0;".to_string());
+        function.payable = false;
+        let mut checkpoint = Checkpoint::new(&[0x60, 0x00], AnalyzerType::Solidity);
+        checkpoint
+            .save(path, "a9059cbb", CheckpointedFunction { function, branches: 3 })
+            .expect("failed to save checkpoint");
+
+        let restored = Checkpoint::load(path, &[0x60, 0x00], AnalyzerType::Solidity);
+        let restored = restored.get("a9059cbb").expect("function wasn't checkpointed");
+        assert_eq!(restored.branches, 3);
+        assert_eq!(restored.function.logic, vec!["store_a = arg0;"]);
+        assert!(!restored.function.payable);
+
+        // checkpoints of other targets, or analyzed with another analyzer, aren't resumed
+        assert!(Checkpoint::load(path, &[0x60, 0x01], AnalyzerType::Solidity)
+            .get("a9059cbb")
+            .is_none());
+        assert!(Checkpoint::load(path, &[0x60, 0x00], AnalyzerType::Yul).get("a9059cbb").is_none());
+
+        std::fs::remove_file(path).expect("failed to remove checkpoint");
+    }
+}
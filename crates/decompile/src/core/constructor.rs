@@ -0,0 +1,350 @@
+use alloy::primitives::{Address, B256, U256};
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::Constructor;
+use eyre::eyre;
+use heimdall_common::utils::strings::{base26_encode, encode_hex, encode_hex_reduced};
+use heimdall_decoder::{decode, DecodeArgsBuilder};
+use heimdall_vm::{
+    core::{hardfork::HardFork, vm::VM},
+    ext::creation::{CreationCode, PLACEHOLDER_ARGUMENTS},
+};
+use tracing::warn;
+use web_time::{Duration, Instant};
+
+use crate::{
+    core::{
+        analyze::{Analyzer, AnalyzerType},
+        validate::format_value,
+    },
+    error::Error,
+    interfaces::{AnalyzedFunction, ConstructorArgument, ConstructorInfo, Immutable},
+};
+
+/// Fetches the creation bytecode of a deployed contract from its creation transaction, which is
+/// looked up on the chain's block explorer.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn fetch_creation_code(
+    address: Address,
+    rpc_url: &str,
+    etherscan_api_key: &str,
+) -> Result<Vec<u8>, Error> {
+    if etherscan_api_key.is_empty() {
+        return Err(Error::Eyre(eyre!(
+            "fetching the creation transaction requires an etherscan API key. please provide one using the '--etherscan-api-key' flag."
+        )));
+    }
+
+    let chain_id = heimdall_common::ether::rpc::chain_id(rpc_url)
+        .await
+        .map_err(|e| Error::FetchError(format!("fetching the chain id failed: {e}")))?;
+    heimdall_common::ether::etherscan::get_creation_bytecode(
+        address,
+        rpc_url,
+        chain_id,
+        etherscan_api_key,
+    )
+    .await
+    .map_err(|e| Error::FetchError(format!("fetching the creation transaction failed: {e}")))
+}
+
+/// The creation transaction can't be fetched on wasm, where network access isn't available.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn fetch_creation_code(
+    _address: Address,
+    _rpc_url: &str,
+    _etherscan_api_key: &str,
+) -> Result<Vec<u8>, Error> {
+    Err(Error::FetchError("fetching the creation transaction isn't available on wasm".to_string()))
+}
+
+/// Recovers the constructor of the creation bytecode: its arguments, the immutables it assigns,
+/// and its logic. The values of the immutables are read from `deployed`, the target's deployed
+/// runtime bytecode, if it's known. `verified` is the constructor of the target's verified ABI,
+/// whose argument types take precedence over the guessed ones.
+pub(crate) async fn recover_constructor(
+    creation: &CreationCode,
+    deployed: Option<&[u8]>,
+    verified: Option<&Constructor>,
+    skip_resolving: bool,
+    hardfork: HardFork,
+    timeout: u64,
+) -> Result<(ConstructorInfo, AnalyzedFunction), Error> {
+    let mut function = analyze_constructor(creation, skip_resolving, hardfork, timeout).await?;
+
+    // find the arguments of the constructor, guessing their types unless the target is verified
+    let arguments = match verified {
+        Some(verified) => Some(verified_arguments(verified, creation.arguments.as_deref())),
+        None => match &creation.arguments {
+            Some(arguments) => decode_arguments(arguments).await,
+            None => None,
+        },
+    }
+    .unwrap_or_else(|| {
+        (0..referenced_argument_count(&function, creation))
+            .map(|_| ConstructorArgument { typ: "bytes32".to_string(), value: None })
+            .collect()
+    });
+
+    // the runtime bytecode only holds the immutables' real values if it was deployed with the
+    // real arguments
+    let deployed =
+        deployed.or_else(|| creation.arguments.is_some().then_some(creation.runtime.as_slice()));
+    let immutables = creation
+        .immutables
+        .iter()
+        .enumerate()
+        .map(|(i, offsets)| Immutable {
+            name: format!("immutable_{}", base26_encode(i + 1)),
+            typ: "bytes32".to_string(),
+            offsets: offsets.clone(),
+            value: deployed
+                .and_then(|deployed| deployed.get(offsets[0]..offsets[0] + 32))
+                .map(B256::from_slice),
+        })
+        .collect::<Vec<_>>();
+
+    function.logic = rewrite_logic(&function.logic, creation, &immutables, arguments.len());
+
+    // immutables assigned an argument share its type
+    let immutables = immutables
+        .into_iter()
+        .map(|immutable| {
+            let assigned = arguments.iter().enumerate().find(|(i, _)| {
+                function
+                    .logic
+                    .iter()
+                    .any(|line| line.trim() == format!("{} = arg{i};", immutable.name))
+            });
+            match assigned {
+                Some((_, argument)) => Immutable { typ: argument.typ.clone(), ..immutable },
+                None => immutable,
+            }
+        })
+        .collect::<Vec<_>>();
+    let constructor = ConstructorInfo { arguments, immutables, payable: function.payable };
+    Ok((constructor, function))
+}
+
+/// Symbolically executes the constructor, and analyzes its logic like any other function.
+async fn analyze_constructor(
+    creation: &CreationCode,
+    skip_resolving: bool,
+    hardfork: HardFork,
+    timeout: u64,
+) -> Result<AnalyzedFunction, Error> {
+    let bytecode = match &creation.arguments {
+        Some(_) => creation.bytecode.clone(),
+        None => [creation.bytecode.as_slice(), &PLACEHOLDER_ARGUMENTS].concat(),
+    };
+    let mut evm = VM::new(
+        &bytecode,
+        &[],
+        Address::default(),
+        Address::default(),
+        Address::default(),
+        0,
+        u128::MAX,
+    )
+    .with_hardfork(hardfork);
+    let (trace, _) = evm
+        .symbolic_exec(
+            Instant::now().checked_add(Duration::from_millis(timeout)).expect("invalid timeout"),
+        )
+        .map_err(|e| Error::Eyre(eyre!("symbolic execution of the constructor failed: {}", e)))?;
+
+    Analyzer::new(
+        AnalyzerType::Solidity,
+        skip_resolving,
+        AnalyzedFunction::new("constructor", false),
+    )
+    .analyze(trace)
+    .await
+}
+
+/// Decodes the constructor arguments with the verified constructor's types.
+fn verified_arguments(
+    verified: &Constructor,
+    arguments: Option<&[u8]>,
+) -> Vec<ConstructorArgument> {
+    let values = arguments.and_then(|arguments| {
+        verified
+            .abi_decode_input(arguments)
+            .inspect_err(|e| warn!("failed to decode the verified constructor arguments: {}", e))
+            .ok()
+    });
+
+    verified
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| ConstructorArgument {
+            typ: input.selector_type().to_string(),
+            value: values.as_ref().and_then(|values| values.get(i)).cloned().map(format_value),
+        })
+        .collect()
+}
+
+/// Decodes the constructor arguments, guessing their types.
+async fn decode_arguments(arguments: &[u8]) -> Option<Vec<ConstructorArgument>> {
+    if arguments.is_empty() {
+        return Some(Vec::new());
+    }
+
+    // the decoder expects calldata, so the arguments are prefixed with an empty selector
+    let decoded = decode(
+        DecodeArgsBuilder::new()
+            .target(encode_hex(&[&[0u8; 4], arguments].concat()))
+            .raw(true)
+            .skip_resolving(true)
+            .build()
+            .expect("failed to build DecodeArgs"),
+    )
+    .await
+    .inspect_err(|e| warn!("failed to decode the constructor arguments: {}", e))
+    .ok()?;
+
+    let values = decoded.decoded.decoded_inputs.unwrap_or_default();
+    Some(
+        decoded
+            .decoded
+            .inputs
+            .into_iter()
+            .zip(values)
+            .map(|(typ, value)| ConstructorArgument { typ, value: Some(format_value(value)) })
+            .collect(),
+    )
+}
+
+/// The number of argument words the constructor reads, for creation bytecode which doesn't
+/// include its arguments.
+fn referenced_argument_count(function: &AnalyzedFunction, creation: &CreationCode) -> usize {
+    let Some(arguments_memory) = creation.arguments_memory else {
+        return 0;
+    };
+
+    (0..PLACEHOLDER_ARGUMENTS.len() / 32)
+        .rev()
+        .find(|i| {
+            let word = memory_access(arguments_memory + i * 32);
+            function.logic.iter().any(|line| line.contains(&word))
+        })
+        .map(|i| i + 1)
+        .unwrap_or(0)
+}
+
+/// Rewrites the constructor's logic in terms of its arguments and immutables, removing the
+/// copying and returning of the deployed code.
+fn rewrite_logic(
+    logic: &[String],
+    creation: &CreationCode,
+    immutables: &[Immutable],
+    argument_count: usize,
+) -> Vec<String> {
+    let copies = [Some(creation.runtime_memory), creation.arguments_memory]
+        .into_iter()
+        .flatten()
+        .map(|dest| format!("{} = this.code[", memory_access(dest)))
+        .collect::<Vec<_>>();
+
+    let mut assigned = Vec::new();
+    logic
+        .iter()
+        .filter(|line| {
+            let line = line.trim();
+            !line.starts_with("return") && !copies.iter().any(|copy| line.starts_with(copy))
+        })
+        .filter_map(|line| {
+            // assign immutables once, rather than to each of their references
+            let immutable = immutables.iter().find(|immutable| {
+                immutable.offsets.iter().any(|offset| {
+                    line.trim_start().starts_with(&format!(
+                        "{} = ",
+                        memory_access(creation.runtime_memory + offset)
+                    ))
+                })
+            });
+            let mut line = match immutable {
+                Some(immutable) if assigned.contains(&immutable.name) => return None,
+                Some(immutable) => {
+                    assigned.push(immutable.name.clone());
+                    let (_, value) =
+                        line.split_once(" = ").expect("impossible case: no assignment");
+                    format!("{} = {value}", immutable.name)
+                }
+                None => line.to_string(),
+            };
+
+            for i in 0..argument_count {
+                let argument =
+                    creation.arguments_memory.map(|memory| memory_access(memory + i * 32));
+                if let Some(argument) = argument {
+                    line = line.replace(&argument, &format!("arg{i}"));
+                }
+            }
+            Some(line)
+        })
+        .collect()
+}
+
+/// The memory access of the given offset, as it's written by the solidity heuristics.
+fn memory_access(offset: usize) -> String {
+    format!("memory[{}]", encode_hex_reduced(U256::from(offset)))
+}
+
+#[cfg(test)]
+mod tests {
+    use heimdall_common::utils::strings::decode_hex;
+    use heimdall_vm::ext::creation::split_creation_code;
+
+    use super::*;
+
+    /// `constructor(address owner, uint256 value)`, which stores both arguments and assigns
+    /// `value` to an immutable read by `value()`
+    const CREATION_BYTECODE: &str = "608060405234801561001057600080fd5b506100a43803806100a4608039604090106100485760805160005560a05160015561005661004e60003960a05161002e526100566000f35b600080fdfe60003560e01c80638da5cb5b146100205780633fa4f2451461002c57600080fd5b60005460005260206000f35b7f000000000000000000000000000000000000000000000000000000000000000060005260206000f3";
+
+    /// The abi-encoded arguments `(0x00000000000000000000000000000000000000aa, 42)`
+    const ARGUMENTS: &str = "00000000000000000000000000000000000000000000000000000000000000aa000000000000000000000000000000000000000000000000000000000000002a";
+
+    #[tokio::test]
+    async fn test_recover_constructor() {
+        let bytecode = decode_hex(&format!("{CREATION_BYTECODE}{ARGUMENTS}")).expect("invalid hex");
+        let creation =
+            split_creation_code(&bytecode, HardFork::Latest, 1000).expect("not creation bytecode");
+        let verified: Constructor = serde_json::from_str(
+            r#"{"type":"constructor","inputs":[{"name":"owner","type":"address"},{"name":"value","type":"uint256"}],"stateMutability":"nonpayable"}"#,
+        )
+        .expect("invalid constructor");
+
+        let (constructor, function) =
+            recover_constructor(&creation, None, Some(&verified), true, HardFork::Latest, 1000)
+                .await
+                .expect("failed to recover constructor");
+
+        assert_eq!(
+            constructor.arguments,
+            vec![
+                ConstructorArgument {
+                    typ: "address".to_string(),
+                    value: Some("0x00000000000000000000000000000000000000AA".to_string()),
+                },
+                ConstructorArgument { typ: "uint256".to_string(), value: Some("42".to_string()) },
+            ]
+        );
+        assert_eq!(
+            constructor.immutables,
+            vec![Immutable {
+                name: "immutable_a".to_string(),
+                typ: "uint256".to_string(),
+                offsets: vec![46],
+                value: Some(B256::from(U256::from(42))),
+            }]
+        );
+        assert!(!constructor.payable);
+        assert!(function.logic.contains(&"storage[0] = arg0;".to_string()));
+        assert!(function.logic.contains(&"immutable_a = arg1;".to_string()));
+        assert!(!function
+            .logic
+            .iter()
+            .any(|line| line.contains("this.code[") || line.starts_with("return")));
+    }
+}
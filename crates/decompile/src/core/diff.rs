@@ -0,0 +1,568 @@
+use alloy::primitives::U256;
+use alloy_json_abi::JsonAbi;
+use eyre::eyre;
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use tracing::{debug, info};
+use web_time::Instant;
+
+use crate::{
+    core::{decompile, DecompileResult},
+    error::Error,
+    interfaces::{DecompilerArgsBuilder, DiffArgs, OutputFormat, StorageLayout},
+};
+
+lazy_static! {
+    /// Internal functions are named by their entry's pc, which moves whenever the code before
+    /// it changes
+    static ref INTERNAL_FUNCTION_REGEX: Regex =
+        Regex::new(r"\binternal_[0-9a-f]+\b").expect("failed to compile regex");
+}
+
+/// How a part of the newer contract differs from the older one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// The part is only in the newer contract
+    Added,
+    /// The part is only in the older contract
+    Removed,
+    /// The part is in both contracts, but differs
+    Changed,
+}
+
+impl Change {
+    /// The name of the change, e.g. `added`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Changed => "changed",
+        }
+    }
+
+    /// The symbol the change is listed with in the summary.
+    fn symbol(&self) -> char {
+        match self {
+            Change::Added => '+',
+            Change::Removed => '-',
+            Change::Changed => '~',
+        }
+    }
+
+    /// How the part with the given old and new values changed, if it did.
+    fn between<T: PartialEq>(old: Option<T>, new: Option<T>) -> Option<Change> {
+        match (old, new) {
+            (None, Some(_)) => Some(Change::Added),
+            (Some(_), None) => Some(Change::Removed),
+            (Some(old), Some(new)) if old != new => Some(Change::Changed),
+            _ => None,
+        }
+    }
+}
+
+/// A function which differs between the contracts, matched by its selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionDiff {
+    /// The function's selector, without a `0x` prefix
+    pub selector: String,
+    /// The function's signature in the newer contract, or the older one if it was removed
+    pub signature: String,
+    /// How the function changed
+    pub change: Change,
+    /// The unified diff of the function's decompiled body
+    pub diff: String,
+}
+
+/// An event or state variable which differs between the contracts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeclarationDiff {
+    /// What the declaration is matched by: an event's name, or a variable's slot and offset
+    pub key: String,
+    /// How the declaration changed
+    pub change: Change,
+    /// The declaration in the older contract, unless it was added
+    pub old: Option<String>,
+    /// The declaration in the newer contract, unless it was removed
+    pub new: Option<String>,
+}
+
+/// The difference between two decompiled contracts, such as two implementations of a proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractDiff {
+    /// The functions which differ, in order of their selectors
+    pub functions: Vec<FunctionDiff>,
+    /// The number of functions in both contracts which decompile identically
+    pub unchanged_functions: usize,
+    /// The events which differ, matched by their name
+    pub events: Vec<DeclarationDiff>,
+    /// The state variables which differ, matched by their slot and offset
+    pub storage: Vec<DeclarationDiff>,
+}
+
+/// The decompiled source of a function, split into lines.
+#[derive(Debug, Clone, Default)]
+struct Function {
+    signature: String,
+    body: Vec<String>,
+}
+
+impl Function {
+    /// The function's signature and its body's normalized lines, which are compared to tell
+    /// whether it changed.
+    fn normalized(&self) -> (&str, Vec<String>) {
+        (&self.signature, self.body.iter().map(|line| normalize(line)).collect())
+    }
+}
+
+/// An edit turning the older lines into the newer ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    /// The line is in both, at the given indices of the older and newer lines
+    Keep(usize, usize),
+    /// The line at the index of the older lines was removed
+    Remove(usize),
+    /// The line at the index of the newer lines was inserted
+    Insert(usize),
+}
+
+/// Normalizes a line of decompiled source for comparison, so that lines which only differ by
+/// where an internal function was laid out compare equal.
+fn normalize(line: &str) -> String {
+    INTERNAL_FUNCTION_REGEX.replace_all(line, "internal_").to_string()
+}
+
+/// The shortest edit script between the lines, from their longest common subsequence.
+fn edits(old: &[String], new: &[String]) -> Vec<Edit> {
+    let old_keys = old.iter().map(|line| normalize(line)).collect::<Vec<_>>();
+    let new_keys = new.iter().map(|line| normalize(line)).collect::<Vec<_>>();
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lcs = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i * width + j] = match old_keys[i] == new_keys[j] {
+                true => lcs[(i + 1) * width + j + 1] + 1,
+                false => lcs[(i + 1) * width + j].max(lcs[i * width + j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old_keys[i] == new_keys[j] {
+            edits.push(Edit::Keep(i, j));
+            i += 1;
+            j += 1;
+        } else if j == new.len() ||
+            (i < old.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            edits.push(Edit::Remove(i));
+            i += 1;
+        } else {
+            edits.push(Edit::Insert(j));
+            j += 1;
+        }
+    }
+    edits
+}
+
+/// Returns the unified diff of the lines, with `context` unchanged lines around each hunk, or an
+/// empty string if they don't differ.
+fn unified_diff(
+    old: &[String],
+    new: &[String],
+    old_name: &str,
+    new_name: &str,
+    context: usize,
+) -> String {
+    let edits = edits(old, new);
+    let changes = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Keep(..)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // group the changes into hunks, merging those whose context overlaps
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for change in changes {
+        let start = change.saturating_sub(context);
+        let end = (change + context + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n");
+    for (start, end) in hunks {
+        // the lines of each side before the hunk, and in it
+        let consumed = |edits: &[Edit]| {
+            edits.iter().fold((0, 0), |(old, new), edit| match edit {
+                Edit::Keep(..) => (old + 1, new + 1),
+                Edit::Remove(_) => (old + 1, new),
+                Edit::Insert(_) => (old, new + 1),
+            })
+        };
+        let (old_start, new_start) = consumed(&edits[..start]);
+        let (old_count, new_count) = consumed(&edits[start..end]);
+
+        // an empty side starts at the line before the hunk, as in `diff -u`
+        let line = |start: usize, count: usize| match count {
+            0 => format!("{start},0"),
+            _ => format!("{},{count}", start + 1),
+        };
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            line(old_start, old_count),
+            line(new_start, new_count)
+        ));
+        for edit in &edits[start..end] {
+            let line = match edit {
+                Edit::Keep(_, j) => format!(" {}", new[*j]),
+                Edit::Remove(i) => format!("-{}", old[*i]),
+                Edit::Insert(j) => format!("+{}", new[*j]),
+            };
+            diff.push_str(&line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// Splits the decompiled Solidity or Vyper source into the functions it dispatches to, by
+/// selector, from the selector each function is tagged with. Functions with no body, such as
+/// public constants, are listed with just their signature.
+fn functions(result: &DecompileResult) -> BTreeMap<String, Function> {
+    let mut functions = result
+        .abi_with_details
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let selector = item.get("selector")?.as_str()?.trim_start_matches("0x").to_string();
+            let signature = item.get("signature")?.as_str()?.to_string();
+            Some((selector, Function { signature, body: Vec::new() }))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let source = result.source.as_deref().unwrap_or_default();
+    let mut lines = source.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        let Some(selector) = trimmed
+            .strip_prefix("/// @custom:selector")
+            .or_else(|| trimmed.strip_prefix("# @custom:selector"))
+        else {
+            continue;
+        };
+        let Some(function) = functions.get_mut(selector.trim().trim_start_matches("0x")) else {
+            continue;
+        };
+
+        // skip the rest of the function's doc comment
+        while lines
+            .next_if(|line| matches!(line.trim_start().chars().next(), Some('/' | '#')))
+            .is_some()
+        {}
+        let Some(line) = lines.next() else { break };
+        let trimmed = line.trim_start();
+        function.body.push(line.to_string());
+
+        // a solidity function's body ends at the closing brace indented like its declaration
+        if trimmed.starts_with("function ") && trimmed.ends_with('{') {
+            let closing = format!("{}}}", &line[..line.len() - trimmed.len()]);
+            for line in lines.by_ref() {
+                function.body.push(line.to_string());
+                if line == closing {
+                    break;
+                }
+            }
+        }
+
+        // a vyper function's decorators are followed by its declaration, and its body ends at
+        // the first line which isn't indented
+        if trimmed.starts_with('@') || trimmed.starts_with("def ") {
+            if !trimmed.starts_with("def ") {
+                while let Some(line) = lines.next_if(|line| line.starts_with('@')) {
+                    function.body.push(line.to_string());
+                }
+                function
+                    .body
+                    .extend(lines.next_if(|line| line.starts_with("def ")).map(str::to_string));
+            }
+            while let Some(line) =
+                lines.next_if(|line| line.is_empty() || line.starts_with(char::is_whitespace))
+            {
+                function.body.push(line.to_string());
+            }
+            while function.body.last().is_some_and(|line| line.trim().is_empty()) {
+                function.body.pop();
+            }
+        }
+    }
+    functions
+}
+
+/// The events of the ABI, as their declaration by their name.
+fn events(abi: &JsonAbi) -> BTreeMap<String, String> {
+    abi.events().map(|event| (event.name.clone(), event.full_signature())).collect()
+}
+
+/// The state variables of the layout, as their type and name by their slot and offset.
+fn variables(layout: &StorageLayout) -> BTreeMap<(U256, usize), (String, String)> {
+    layout
+        .entries
+        .iter()
+        .map(|entry| ((entry.slot, entry.offset), (entry.typ.label(), entry.label.clone())))
+        .collect()
+}
+
+/// Compares two decompiled contracts, matching their functions by selector.
+pub(crate) fn diff_results(
+    old: &DecompileResult,
+    new: &DecompileResult,
+    context: usize,
+) -> ContractDiff {
+    let old_functions = functions(old);
+    let new_functions = functions(new);
+    let mut selectors = old_functions.keys().chain(new_functions.keys()).collect::<Vec<_>>();
+    selectors.sort();
+    selectors.dedup();
+
+    let mut functions = Vec::new();
+    let mut unchanged_functions = 0;
+    for selector in selectors {
+        let old_function = old_functions.get(selector);
+        let new_function = new_functions.get(selector);
+        let Some(change) = Change::between(
+            old_function.map(Function::normalized),
+            new_function.map(Function::normalized),
+        ) else {
+            unchanged_functions += 1;
+            continue;
+        };
+
+        let signature =
+            new_function.or(old_function).map(|f| f.signature.clone()).unwrap_or_default();
+        let name = |side: &str, function: Option<&Function>| match function {
+            Some(function) => format!("{side}/0x{selector} {}", function.signature),
+            None => "/dev/null".to_string(),
+        };
+        let mut diff = unified_diff(
+            old_function.map(|function| function.body.as_slice()).unwrap_or_default(),
+            new_function.map(|function| function.body.as_slice()).unwrap_or_default(),
+            &name("old", old_function),
+            &name("new", new_function),
+            context,
+        );
+
+        // a function without a body, such as a constant, only changes its signature
+        if diff.is_empty() {
+            diff =
+                format!("--- {}\n+++ {}\n", name("old", old_function), name("new", new_function));
+        }
+        functions.push(FunctionDiff { selector: selector.clone(), signature, change, diff });
+    }
+
+    let declarations = |old: BTreeMap<String, String>, new: BTreeMap<String, String>| {
+        let mut keys = old.keys().chain(new.keys()).cloned().collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .filter_map(|key| {
+                let (old, new) = (old.get(&key).cloned(), new.get(&key).cloned());
+                Change::between(old.as_ref(), new.as_ref()).map(|change| DeclarationDiff {
+                    key,
+                    change,
+                    old,
+                    new,
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let events = declarations(events(&old.abi), events(&new.abi));
+
+    // variables are matched by their slot and offset, and only change with their type, since
+    // their names are recovered rather than read from the contract
+    let (old_variables, new_variables) =
+        (variables(&old.storage_layout), variables(&new.storage_layout));
+    let mut slots = old_variables.keys().chain(new_variables.keys()).collect::<Vec<_>>();
+    slots.sort();
+    slots.dedup();
+    let storage = slots
+        .into_iter()
+        .filter_map(|slot| {
+            let (old, new) = (old_variables.get(slot), new_variables.get(slot));
+            let change = Change::between(old.map(|(typ, _)| typ), new.map(|(typ, _)| typ))?;
+            let declaration = |variable: Option<&(String, String)>| {
+                variable.map(|(typ, name)| format!("{typ} {name}"))
+            };
+            Some(DeclarationDiff {
+                key: format!("slot {:#x}, offset {}", slot.0, slot.1),
+                change,
+                old: declaration(old),
+                new: declaration(new),
+            })
+        })
+        .collect();
+
+    ContractDiff { functions, unchanged_functions, events, storage }
+}
+
+impl ContractDiff {
+    /// Whether the contracts' functions, events and storage layouts don't differ.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.events.is_empty() && self.storage.is_empty()
+    }
+
+    /// The number of functions with the given change.
+    pub fn count(&self, change: Change) -> usize {
+        self.functions.iter().filter(|function| function.change == change).count()
+    }
+
+    /// Returns a report of the diff: a summary of the functions, events and state variables
+    /// which differ, followed by the unified diff of each function which does.
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "functions: {} added, {} removed, {} changed, {} unchanged\n",
+            self.count(Change::Added),
+            self.count(Change::Removed),
+            self.count(Change::Changed),
+            self.unchanged_functions
+        );
+        for function in &self.functions {
+            report.push_str(&format!(
+                "  {} 0x{} {}\n",
+                function.change.symbol(),
+                function.selector,
+                function.signature
+            ));
+        }
+
+        for (title, declarations) in [("events", &self.events), ("storage", &self.storage)] {
+            report.push_str(&format!("{title}: {} changes\n", declarations.len()));
+            for declaration in declarations {
+                let description = match (&declaration.old, &declaration.new) {
+                    (Some(old), Some(new)) => format!("{old} -> {new}"),
+                    (Some(declaration), None) | (None, Some(declaration)) => declaration.clone(),
+                    (None, None) => String::new(),
+                };
+                report.push_str(&format!(
+                    "  {} {}: {description}\n",
+                    declaration.change.symbol(),
+                    declaration.key
+                ));
+            }
+        }
+
+        for function in &self.functions {
+            report.push('\n');
+            report.push_str(&function.diff);
+        }
+        report
+    }
+
+    /// Serializes the diff as JSON.
+    pub fn to_json(&self) -> Value {
+        let declarations = |declarations: &[DeclarationDiff]| {
+            declarations
+                .iter()
+                .map(|declaration| {
+                    json!({
+                        "key": declaration.key,
+                        "change": declaration.change.name(),
+                        "old": declaration.old,
+                        "new": declaration.new,
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        json!({
+            "functions": self.functions.iter().map(|function| json!({
+                "selector": format!("0x{}", function.selector),
+                "signature": function.signature,
+                "change": function.change.name(),
+                "diff": function.diff,
+            })).collect::<Vec<_>>(),
+            "unchanged_functions": self.unchanged_functions,
+            "events": declarations(&self.events),
+            "storage": declarations(&self.storage),
+        })
+    }
+}
+
+/// Decompiles both contracts and compares them: the functions matched by selector which were
+/// added, removed or changed, with the unified diff of each one's decompiled body, and the events
+/// and state variables which differ. Both contracts are decompiled deterministically, so that
+/// functions which didn't change decompile identically.
+pub async fn diff(args: DiffArgs) -> Result<ContractDiff, Error> {
+    let start_time = Instant::now();
+
+    let mut results = Vec::with_capacity(2);
+    for target in [&args.old_target, &args.new_target] {
+        let decompile_args = DecompilerArgsBuilder::new()
+            .target(target.clone())
+            .rpc_url(args.rpc_url.clone())
+            .skip_resolving(args.skip_resolving)
+            .output_format(Some(OutputFormat::Solidity))
+            .deterministic(true)
+            .max_branches(args.max_branches)
+            .follow_proxy(false)
+            .build()
+            .map_err(|e| eyre!("failed to build decompiler arguments: {}", e))?;
+        let result = decompile(decompile_args)
+            .await
+            .map_err(|e| eyre!("failed to decompile '{}': {}", target, e))?;
+        results.push(result);
+    }
+
+    let diff = diff_results(&results[0], &results[1], args.context);
+    debug!("diffing contracts took {:?}", start_time.elapsed());
+    info!(
+        "{} functions, {} events and {} state variables differ",
+        diff.functions.len(),
+        diff.events.len(),
+        diff.storage.len()
+    );
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = lines("function a() {\n    b();\n    c();\n    d();\n    e();\n    f();\n}");
+        let new = lines("function a() {\n    b();\n    x();\n    d();\n    e();\n    f();\n}");
+        assert_eq!(
+            unified_diff(&old, &new, "old", "new", 1),
+            "--- old\n+++ new\n@@ -2,3 +2,3 @@\n     b();\n-    c();\n+    x();\n     d();\n"
+        );
+
+        assert_eq!(unified_diff(&old, &old, "old", "new", 3), "");
+        assert_eq!(
+            unified_diff(&[], &lines("a\nb"), "/dev/null", "new", 3),
+            "--- /dev/null\n+++ new\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_ignores_moved_internal_functions() {
+        let old = lines("function a() {\n    internal_01a2(arg0);\n}");
+        let new = lines("function a() {\n    internal_02b4(arg0);\n}");
+        assert_eq!(unified_diff(&old, &new, "old", "new", 3), "");
+    }
+}
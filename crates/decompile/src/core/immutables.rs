@@ -0,0 +1,152 @@
+use alloy::primitives::{keccak256, U256};
+use heimdall_common::utils::strings::encode_hex_reduced;
+use heimdall_vm::core::opcodes::PUSH32;
+
+use crate::interfaces::{AnalyzedFunction, Immutable};
+
+/// A marker unique to the immutable at `index`, which is pushed in place of its value so that
+/// reads of the immutable can be told apart from other constants in the analyzed logic. Markers
+/// are 20 bytes wide, so that they survive being masked to an address.
+fn marker(index: usize) -> U256 {
+    U256::from_be_slice(&keccak256(format!("heimdall immutable {index}"))[..20])
+}
+
+/// Replaces the value of each immutable in the runtime bytecode with its marker.
+pub(crate) fn mark_immutables(runtime: &[u8], immutables: &[Immutable]) -> Vec<u8> {
+    let mut marked = runtime.to_vec();
+    for (i, immutable) in immutables.iter().enumerate() {
+        let marker = marker(i).to_be_bytes::<32>();
+        for &offset in &immutable.offsets {
+            // solidity reads immutables by pushing their value
+            if offset > 0 && marked.get(offset - 1) == Some(&PUSH32) && offset + 32 <= marked.len()
+            {
+                marked[offset..offset + 32].copy_from_slice(&marker);
+            }
+        }
+    }
+    marked
+}
+
+/// Replaces the markers in the logic of the functions with the names of their immutables.
+/// Functions which read an immutable are view rather than constant.
+pub(crate) fn name_immutables(functions: &mut [AnalyzedFunction], immutables: &[Immutable]) {
+    let markers = immutables
+        .iter()
+        .enumerate()
+        .map(|(i, immutable)| (encode_hex_reduced(marker(i)), immutable.name.as_str()))
+        .collect::<Vec<_>>();
+
+    for function in functions.iter_mut() {
+        let mut reads_immutable = false;
+        for line in function.logic.iter_mut() {
+            for (marker, name) in &markers {
+                if line.contains(marker.as_str()) {
+                    *line = line.replace(marker.as_str(), name);
+                    reads_immutable = true;
+                }
+            }
+        }
+
+        if reads_immutable && function.pure {
+            function.pure = false;
+            function.view = true;
+            function.constant_value = None;
+        }
+    }
+}
+
+/// Finds the argument-less view functions which return an immutable, and marks them as its getter.
+/// The immutable takes the type its getter returns.
+pub(crate) fn find_immutable_getters(
+    functions: &mut [AnalyzedFunction],
+    immutables: &mut [Immutable],
+) {
+    for immutable in immutables.iter_mut() {
+        let Some(getter) = functions.iter_mut().find(|f| {
+            f.view &&
+                f.arguments.is_empty() &&
+                f.maybe_getter_for.is_none() &&
+                f.logic.iter().any(|line| returns_immutable(line, &immutable.name))
+        }) else {
+            continue;
+        };
+
+        getter.maybe_getter_for = Some(immutable.name.clone());
+        if let Some(returns) = &getter.returns {
+            immutable.typ = returns.replacen("memory", "", 1).trim().to_string();
+        }
+    }
+}
+
+/// Whether the line returns the immutable, or a cast of it, e.g. `return address(immutable_a);`.
+fn returns_immutable(line: &str, name: &str) -> bool {
+    let Some(value) = line.trim().strip_prefix("return ").and_then(|v| v.strip_suffix(';')) else {
+        return false;
+    };
+
+    value == name ||
+        value.strip_suffix(&format!("({name})")).is_some_and(|cast| {
+            !cast.is_empty() && cast.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn immutable(name: &str, offsets: Vec<usize>) -> Immutable {
+        Immutable { name: name.to_string(), typ: "bytes32".to_string(), offsets, value: None }
+    }
+
+    #[test]
+    fn test_mark_immutables() {
+        // PUSH32 0, PUSH1 0, PUSH32 0
+        let mut runtime = vec![PUSH32];
+        runtime.extend([0; 32]);
+        runtime.extend([0x60, 0x00, PUSH32]);
+        runtime.extend([0; 32]);
+
+        let marked = mark_immutables(&runtime, &[immutable("immutable_a", vec![1, 36])]);
+        let marker = marker(0).to_be_bytes::<32>();
+        assert_eq!(marked[1..33], marker);
+        assert_eq!(marked[33..36], [0x60, 0x00, PUSH32]);
+        assert_eq!(marked[36..68], marker);
+
+        // offsets which aren't pushed are left untouched
+        assert_eq!(mark_immutables(&runtime, &[immutable("immutable_a", vec![34])]), runtime);
+    }
+
+    #[test]
+    fn test_name_immutables() {
+        let mut function = AnalyzedFunction::new("3fa4f245", false);
+        function.logic = vec![
+            format!("require(msg.sender == address({}));", encode_hex_reduced(marker(1))),
+            format!("return {};", encode_hex_reduced(marker(0))),
+        ];
+        let mut functions = vec![function];
+        let mut immutables =
+            vec![immutable("immutable_a", vec![1]), immutable("immutable_b", vec![40])];
+
+        name_immutables(&mut functions, &immutables);
+        assert_eq!(
+            functions[0].logic,
+            vec!["require(msg.sender == address(immutable_b));", "return immutable_a;"]
+        );
+        assert!(functions[0].view && !functions[0].pure);
+
+        functions[0].returns = Some("uint256".to_string());
+        find_immutable_getters(&mut functions, &mut immutables);
+        assert_eq!(functions[0].maybe_getter_for.as_deref(), Some("immutable_a"));
+        assert_eq!(immutables[0].typ, "uint256");
+        assert_eq!(immutables[1].typ, "bytes32");
+    }
+
+    #[test]
+    fn test_returns_immutable() {
+        assert!(returns_immutable("return immutable_a;", "immutable_a"));
+        assert!(returns_immutable("    return address(immutable_a);", "immutable_a"));
+        assert!(!returns_immutable("return immutable_a + 0x01;", "immutable_a"));
+        assert!(!returns_immutable("return (0x01 + immutable_a);", "immutable_a"));
+        assert!(!returns_immutable("return immutable_ab;", "immutable_a"));
+    }
+}
@@ -0,0 +1,415 @@
+use std::sync::Arc;
+use web_time::{Duration, Instant};
+
+use alloy::primitives::U256;
+use hashbrown::{HashMap, HashSet};
+use heimdall_common::utils::strings::decode_hex;
+use heimdall_vm::{
+    core::{
+        opcodes::{WrappedInput, WrappedOpcode, CALLDATALOAD, JUMP, JUMPDEST, PUSH32},
+        stack::StackFrame,
+        vm::{State, VM},
+    },
+    ext::exec::VMTrace,
+};
+
+use crate::{
+    core::{
+        analyze::{Analyzer, AnalyzerType},
+        loops::is_constant,
+    },
+    interfaces::{AnalyzedFunction, CalldataFrame},
+    Error,
+};
+
+/// The most arguments an internal function is assumed to take, which bounds how deep its return
+/// address is searched for on the stack.
+const MAX_ARGUMENTS: usize = 8;
+
+/// The most values an internal function is assumed to return.
+const MAX_RETURNS: usize = 4;
+
+/// A call to an internal function: a JUMP to the function's entry, which leaves the address the
+/// function returns to on the stack beneath its arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InternalCall {
+    /// The entry of the called function
+    pub entry: u128,
+    /// The number of arguments the function is called with
+    pub arguments: usize,
+    /// The number of values the function returns
+    pub returns: usize,
+    /// The address the function returns to
+    return_address: U256,
+    /// The size of the stack beneath the return address
+    base: usize,
+}
+
+/// An internal function whose body is shared by several call sites. It's emitted once, and
+/// called from each of its callers rather than inlined into them.
+#[derive(Clone, Debug)]
+pub(crate) struct InternalFunction {
+    /// The entry of the function in the bytecode
+    pub entry: u128,
+    /// The name of the function in the decompiled source
+    pub name: String,
+    /// The number of arguments the function takes
+    pub arguments: usize,
+    /// The number of values the function returns
+    pub returns: usize,
+    /// The analyzed body of the function
+    pub function: AnalyzedFunction,
+}
+
+/// The first call to an internal function, whose state the function's body is traced from.
+struct CallSite<'a> {
+    call: InternalCall,
+    selector: &'a str,
+    state: &'a State,
+}
+
+impl InternalCall {
+    /// Whether the state returns from the call, jumping back to the caller.
+    pub(crate) fn returns_from(&self, state: &State) -> bool {
+        state.last_instruction.opcode == JUMP &&
+            state.last_instruction.inputs.first() == Some(&self.return_address) &&
+            state.stack.size() >= self.base
+    }
+
+    /// Whether the branch, or one of its children, returns from the call.
+    pub(crate) fn returns_within(&self, branch: &VMTrace) -> bool {
+        find_return(branch, 0, self).is_some()
+    }
+
+    /// The values the call returns, in order, once the state has returned from it.
+    pub(crate) fn returned_values(&self, state: &State) -> Vec<WrappedOpcode> {
+        (0..state.stack.size() - self.base).rev().map(|i| state.stack.peek(i).operation).collect()
+    }
+
+    /// The line which calls the function from the state which jumps to it.
+    pub(crate) fn to_line(&self, name: &str, state: &State) -> String {
+        let arguments = (0..self.arguments)
+            .rev()
+            .map(|i| state.stack.peek(i).operation.solidify())
+            .collect::<Vec<_>>();
+        format!("{name}({});", arguments.join(", "))
+    }
+}
+
+/// The call to a function taking `arguments` arguments, made by the operation at `index` in the
+/// branch, if the function returns to the address beneath them.
+pub(crate) fn call_at(branch: &VMTrace, index: usize, arguments: usize) -> Option<InternalCall> {
+    let state = branch.operations.get(index)?;
+    if state.last_instruction.opcode != JUMP || state.stack.size() <= arguments {
+        return None;
+    }
+
+    let return_address = state.stack.peek(arguments);
+    if !is_constant(&return_address.operation) {
+        return None;
+    }
+    let mut call = InternalCall {
+        entry: state.last_instruction.inputs[0].try_into().unwrap_or(u128::MAX),
+        arguments,
+        returns: 0,
+        return_address: return_address.value,
+        base: state.stack.size() - arguments - 1,
+    };
+    call.returns = find_return(branch, index + 1, &call)?;
+    Some(call)
+}
+
+/// Finds the number of values the call returns, on the first path from the operation at `start`
+/// in the branch which returns from it.
+fn find_return(branch: &VMTrace, start: usize, call: &InternalCall) -> Option<usize> {
+    for state in branch.operations.iter().skip(start) {
+        if call.returns_from(state) {
+            return Some(state.stack.size() - call.base).filter(|returns| *returns <= MAX_RETURNS);
+        }
+
+        // the return address was popped without returning to it
+        if state.stack.size() <= call.base {
+            return None;
+        }
+    }
+
+    branch.children.iter().find_map(|child| find_return(child, 0, call))
+}
+
+/// Finds the first call to each internal function which is called from more than one call site
+/// across the traces, in the order of their entries.
+fn find_shared_calls<'a>(
+    traces: &'a HashMap<String, VMTrace>,
+    bytecode: &[u8],
+) -> Vec<CallSite<'a>> {
+    let mut calls: HashMap<u128, (CallSite<'a>, HashSet<u128>)> = HashMap::new();
+    let mut selectors = traces.keys().collect::<Vec<_>>();
+    selectors.sort();
+    for selector in selectors {
+        find_calls(&traces[selector], selector, bytecode, Vec::new(), &mut calls);
+    }
+
+    let mut shared = calls
+        .into_values()
+        .filter(|(_, call_sites)| call_sites.len() > 1)
+        .map(|(call_site, _)| call_site)
+        .collect::<Vec<_>>();
+    shared.sort_by_key(|call_site| call_site.call.entry);
+    shared
+}
+
+/// Records the calls made by the branch and its children, along with the instructions they're
+/// made from. `active` holds the calls which haven't returned yet, from the outermost inwards.
+fn find_calls<'a>(
+    branch: &'a VMTrace,
+    selector: &'a str,
+    bytecode: &[u8],
+    mut active: Vec<InternalCall>,
+    calls: &mut HashMap<u128, (CallSite<'a>, HashSet<u128>)>,
+) {
+    let is_jumpdest =
+        |value: U256| usize::try_from(value).is_ok_and(|pc| bytecode.get(pc) == Some(&JUMPDEST));
+    for (i, state) in branch.operations.iter().enumerate() {
+        while active
+            .last()
+            .is_some_and(|call| call.returns_from(state) || state.stack.size() <= call.base)
+        {
+            active.pop();
+        }
+        if state.last_instruction.opcode != JUMP {
+            continue;
+        }
+
+        // the return address of a call is pushed above those of the calls it's made within
+        let max_arguments = match active.last() {
+            Some(call) => state.stack.size().saturating_sub(call.base + 1),
+            None => state.stack.size(),
+        }
+        .min(MAX_ARGUMENTS);
+        let call = (0..max_arguments)
+            .filter(|arguments| is_jumpdest(state.stack.peek(*arguments).value))
+            .find_map(|arguments| call_at(branch, i, arguments));
+        if let Some(call) = call {
+            calls
+                .entry(call.entry)
+                .or_insert_with(|| {
+                    (CallSite { call: call.clone(), selector, state }, HashSet::new())
+                })
+                .1
+                .insert(state.last_instruction.instruction);
+            active.push(call);
+        }
+    }
+
+    for child in &branch.children {
+        find_calls(child, selector, bytecode, active.clone(), calls);
+    }
+}
+
+/// The operation an internal function's argument is read with while its body is traced. Each
+/// argument reads its own calldata slot, so that it's rendered as `arg{i}`.
+fn argument(index: usize) -> WrappedOpcode {
+    WrappedOpcode::new(
+        CALLDATALOAD,
+        vec![WrappedInput::Opcode(Arc::new(WrappedOpcode::new(
+            PUSH32,
+            vec![WrappedInput::Raw(U256::from(4 + 32 * index))],
+        )))],
+    )
+}
+
+/// Symbolically executes the function's body from its first call site, with its arguments
+/// replaced by their own calldata slots. The function returns to an address past the end of the
+/// bytecode, which ends the trace.
+fn trace_body(evm: &VM, call_site: &CallSite<'_>, timeout: Instant) -> Result<VMTrace, Error> {
+    let mut vm = evm.clone();
+    vm.reset();
+    vm.calldata = decode_hex(call_site.selector).unwrap_or_default();
+    vm.stack = call_site.state.stack.clone();
+    vm.memory = call_site.state.memory.clone();
+    vm.storage = call_site.state.storage.clone();
+    vm.instruction = call_site.call.entry + 1;
+
+    let arguments = call_site.call.arguments;
+    for i in 0..arguments {
+        let frame = &mut vm.stack.stack[i];
+        *frame = StackFrame { value: frame.value, operation: argument(arguments - i - 1) };
+    }
+    vm.stack.stack[arguments].value = U256::from(vm.bytecode.len() + 1);
+
+    let (trace, _) = vm.symbolic_exec(timeout)?;
+    Ok(trace)
+}
+
+/// Finds the internal functions called from several call sites, and analyzes each of their bodies
+/// once. Functions whose bodies only compute the values they return, or write memory, are left
+/// inlined, since they're already rendered as part of the expressions which use those values. So
+/// are guards without arguments, which are lifted into modifiers instead.
+pub(crate) async fn find_internal_functions(
+    evm: &VM,
+    traces: &HashMap<String, VMTrace>,
+    bytecode: &[u8],
+    skip_resolving: bool,
+    checked_arithmetic: bool,
+    timeout: u64,
+) -> Result<Vec<InternalFunction>, Error> {
+    let mut bodies = Vec::new();
+    for call_site in find_shared_calls(traces, bytecode) {
+        let timeout_at =
+            Instant::now().checked_add(Duration::from_millis(timeout)).expect("invalid timeout");
+        let trace = trace_body(evm, &call_site, timeout_at)?;
+        let call = InternalCall {
+            return_address: U256::from(bytecode.len() + 1),
+            ..call_site.call.clone()
+        };
+        let mut function =
+            analyze_body(&call, &trace, &HashMap::new(), skip_resolving, checked_arithmetic)
+                .await?;
+        let mut statements = function.logic.iter().filter(|line| !line.starts_with("memory["));
+        let is_guard =
+            call.arguments == 0 && statements.clone().all(|line| line.starts_with("require("));
+        if is_guard || statements.all(|line| line.starts_with("return")) {
+            continue;
+        }
+
+        function.selector = format!("internal_{:04x}", call.entry);
+        bodies.push((call, trace, function));
+    }
+
+    // the bodies are analyzed again, now calling the other internal functions
+    let callees = bodies
+        .iter()
+        .map(|(call, _, function)| (call.entry, (function.selector.clone(), call.arguments)))
+        .collect::<HashMap<_, _>>();
+    let mut internal_functions = Vec::new();
+    for (call, trace, function) in bodies {
+        let mut callees = callees.clone();
+        callees.remove(&call.entry);
+        let mut body =
+            analyze_body(&call, &trace, &callees, skip_resolving, checked_arithmetic).await?;
+        body.selector = function.selector;
+
+        internal_functions.push(InternalFunction {
+            entry: call.entry,
+            name: body.selector.clone(),
+            arguments: call.arguments,
+            returns: call.returns,
+            function: body,
+        });
+    }
+
+    Ok(internal_functions)
+}
+
+/// Analyzes the body of an internal function, which was traced from the call it returns from.
+async fn analyze_body(
+    call: &InternalCall,
+    trace: &VMTrace,
+    callees: &HashMap<u128, (String, usize)>,
+    skip_resolving: bool,
+    checked_arithmetic: bool,
+) -> Result<AnalyzedFunction, Error> {
+    let mut function = AnalyzedFunction::new(&format!("{:04x}", call.entry), false);
+    for i in 0..call.arguments {
+        function.arguments.insert(
+            i,
+            CalldataFrame {
+                arg_op: argument(i).inputs[0].to_string(),
+                mask_size: 32,
+                heuristics: HashSet::new(),
+            },
+        );
+    }
+
+    Analyzer::new(AnalyzerType::Solidity, skip_resolving, function)
+        .with_internal_functions(callees.clone())
+        .returning(call.clone())
+        .with_checked_arithmetic(checked_arithmetic)
+        .analyze(trace.clone())
+        .await
+}
+
+/// Adds the custom errors raised by the internal functions each function calls to its own, since
+/// they're raised on its behalf.
+pub(crate) fn inherit_errors(
+    functions: &mut [AnalyzedFunction],
+    internal_functions: &mut [InternalFunction],
+) {
+    // internal functions inherit the errors of those they call first, until none are added
+    loop {
+        let errors = internal_functions
+            .iter()
+            .map(|f| (f.entry, f.function.clone()))
+            .collect::<HashMap<_, _>>();
+        let mut inherited = false;
+        for f in internal_functions.iter_mut() {
+            inherited |= inherit_from(&mut f.function, &errors);
+        }
+        if !inherited {
+            break;
+        }
+    }
+
+    let errors =
+        internal_functions.iter().map(|f| (f.entry, f.function.clone())).collect::<HashMap<_, _>>();
+    for f in functions.iter_mut() {
+        inherit_from(f, &errors);
+    }
+}
+
+/// Adds the custom errors of the internal functions the function calls to its own, returning
+/// whether any were added.
+fn inherit_from(
+    function: &mut AnalyzedFunction,
+    callees: &HashMap<u128, AnalyzedFunction>,
+) -> bool {
+    let mut inherited = false;
+    let mut calls = function.internal_calls.iter().copied().collect::<Vec<_>>();
+    calls.sort();
+    for callee in calls.iter().filter_map(|entry| callees.get(entry)) {
+        for error in &callee.errors {
+            inherited |= function.errors.insert(*error);
+        }
+        for (selector, arguments) in &callee.error_arguments {
+            function.error_arguments.entry(*selector).or_insert_with(|| arguments.clone());
+        }
+    }
+    inherited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn internal_function(entry: u128, calls: &[u128], errors: &[u64]) -> InternalFunction {
+        let mut function = AnalyzedFunction::new(&format!("internal_{entry:04x}"), false);
+        function.internal_calls = calls.iter().copied().collect();
+        function.errors = errors.iter().map(|e| U256::from(*e)).collect();
+        InternalFunction {
+            entry,
+            name: format!("internal_{entry:04x}"),
+            arguments: 0,
+            returns: 0,
+            function,
+        }
+    }
+
+    #[test]
+    fn test_inherit_errors() {
+        let mut internal_functions = vec![
+            internal_function(0x10, &[0x20], &[1]),
+            internal_function(0x20, &[0x30], &[]),
+            internal_function(0x30, &[], &[3]),
+        ];
+        let mut function = AnalyzedFunction::new("01", false);
+        function.internal_calls = [0x10].into_iter().collect();
+        let mut functions = vec![function];
+
+        inherit_errors(&mut functions, &mut internal_functions);
+        assert_eq!(internal_functions[1].function.errors, [U256::from(3)].into_iter().collect());
+        assert_eq!(
+            functions[0].errors,
+            [U256::from(1), U256::from(3)].into_iter().collect::<HashSet<_>>()
+        );
+    }
+}
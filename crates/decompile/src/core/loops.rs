@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use alloy::primitives::{keccak256, U256};
+use heimdall_common::utils::strings::encode_hex_reduced;
+use heimdall_vm::{
+    core::{
+        opcodes::{WrappedInput, WrappedOpcode, ADD, ISZERO, JUMPI, PUSH0, PUSH32, SUB},
+        stack::StackFrame,
+        vm::State,
+    },
+    ext::exec::VMTrace,
+};
+
+use crate::core::analyze::AnalyzerType;
+
+/// The names given to the induction variables of nested loops, from the outermost loop inwards.
+const INDUCTION_VARIABLES: [&str; 3] = ["i", "j", "k"];
+
+/// A loop entered by the JUMPI which checks its condition, the loop's header. Symbolic execution
+/// maps a single iteration of the loop's body, which ends where it returns to the header.
+pub(crate) struct Loop<'a> {
+    /// The state after the header's JUMPI, in the first iteration
+    header: &'a State,
+    /// The state after the header's JUMPI, once the body has run
+    back_edge: &'a State,
+    /// The branch which runs the loop's body
+    pub body: &'a VMTrace,
+    /// The branch which runs once the loop exits, if symbolic execution reached it
+    pub exit: Option<&'a VMTrace>,
+}
+
+/// The header of a loop, which is being analyzed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct LoopHeader {
+    /// The instruction of the header's JUMPI
+    pub instruction: u128,
+    /// The size of the stack after the header's JUMPI
+    pub stack_size: usize,
+}
+
+/// How an induction variable changes between iterations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Step {
+    Increment(U256),
+    Decrement(U256),
+}
+
+/// A variable which is stepped by the loop's body, and checked by the loop's condition.
+struct Induction<'a> {
+    init: &'a WrappedOpcode,
+    next: &'a WrappedOpcode,
+    step: Step,
+}
+
+/// Finds the loop headed by the JUMPI which ends `branch`, if exactly one of its children returns
+/// to it.
+pub(crate) fn find_loop(branch: &VMTrace) -> Option<Loop<'_>> {
+    // loops whose condition is constant were fully evaluated by symbolic execution
+    let header = branch.operations.last().filter(|s| {
+        s.last_instruction.opcode == JUMPI && !is_constant(&s.last_instruction.input_operations[1])
+    })?;
+    let loop_header = LoopHeader {
+        instruction: header.last_instruction.instruction,
+        stack_size: header.stack.size(),
+    };
+
+    let mut back_edges = branch.children.iter().map(|child| find_back_edge(child, &loop_header));
+    let (body, back_edge) = match (back_edges.next(), back_edges.next()) {
+        (Some(Some(back_edge)), None | Some(None)) => (&branch.children[0], back_edge),
+        (Some(None), Some(Some(back_edge))) => (&branch.children[1], back_edge),
+        _ => return None,
+    };
+    let exit = branch.children.iter().find(|child| !std::ptr::eq(*child, body));
+
+    Some(Loop { header, back_edge, body, exit })
+}
+
+/// Whether the branch returns to the header of one of the loops it's in, starting the loop's next
+/// iteration.
+pub(crate) fn is_back_edge(branch: &VMTrace, loops: &[LoopHeader]) -> bool {
+    branch.operations.last().is_some_and(|state| {
+        state.last_instruction.opcode == JUMPI &&
+            loops.iter().any(|header| {
+                header.instruction == state.last_instruction.instruction &&
+                    !leaves_loop(branch, header)
+            })
+    })
+}
+
+/// Whether the branch pops the stack below its size at the loop's header. Shared internal
+/// functions are re-entered from several call sites, but return in between, so this tells their
+/// JUMPIs apart from a loop's header.
+pub(crate) fn leaves_loop(branch: &VMTrace, header: &LoopHeader) -> bool {
+    branch.operations.iter().any(|state| state.stack.size() < header.stack_size)
+}
+
+/// Finds the state in which the branch, or one of its children, returns to the loop's header.
+fn find_back_edge<'a>(branch: &'a VMTrace, header: &LoopHeader) -> Option<&'a State> {
+    if leaves_loop(branch, header) {
+        return None;
+    }
+
+    match branch.operations.last() {
+        Some(state) if is_back_edge(branch, std::slice::from_ref(header)) => Some(state),
+        _ => branch.children.iter().find_map(|child| find_back_edge(child, header)),
+    }
+}
+
+impl Loop<'_> {
+    /// The header of the loop, for tracking the loops which are being analyzed.
+    pub(crate) fn header(&self) -> LoopHeader {
+        LoopHeader {
+            instruction: self.header.last_instruction.instruction,
+            stack_size: self.header.stack.size(),
+        }
+    }
+
+    /// Builds the line which opens the loop, nested in `depth` other loops. The loop is a `for`
+    /// loop if its condition checks an induction variable, and a `while` loop otherwise.
+    pub(crate) fn to_header(&self, typ: AnalyzerType, depth: usize) -> String {
+        let name = INDUCTION_VARIABLES
+            .get(depth)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("i{depth}"));
+        let marker = U256::from_be_slice(&keccak256("heimdall loop variable")[..20]);
+        let placeholder = encode_hex_reduced(marker);
+        let marker = WrappedOpcode::new(PUSH32, vec![WrappedInput::Raw(marker)]);
+
+        // the loop runs while the header jumps to its body
+        let header_condition = self.condition(self.header);
+        let back_edge_condition = self.condition(self.back_edge);
+
+        // the condition is rewritten in terms of the first induction variable it checks
+        let induction = self.inductions().into_iter().find_map(|induction| {
+            let condition =
+                substitute(&header_condition, &back_edge_condition, &induction, &marker);
+            (condition != header_condition).then_some((induction, condition))
+        });
+        let render = |op: &WrappedOpcode| {
+            let rendered = match typ {
+                AnalyzerType::Yul => op.yulify(),
+                _ => op.solidify(),
+            };
+            rendered.replace(&placeholder, &name)
+        };
+
+        match (typ, induction) {
+            (AnalyzerType::Yul, Some((induction, condition))) => format!(
+                "for {{ let {name} := {} }} {} {{ {name} := {}({name}, {}) }} {{",
+                render(induction.init),
+                render(&condition),
+                match induction.step {
+                    Step::Increment(_) => "add",
+                    Step::Decrement(_) => "sub",
+                },
+                encode_hex_reduced(induction.step.amount())
+            ),
+            (AnalyzerType::Yul, None) => {
+                format!("for {{ }} {} {{ }} {{", render(&header_condition))
+            }
+            (_, Some((induction, condition))) => format!(
+                "for (uint256 {name} = {}; {}; {}) {{",
+                render(induction.init),
+                render(&condition),
+                match induction.step {
+                    Step::Increment(amount) if amount == U256::from(1) => format!("{name}++"),
+                    Step::Decrement(amount) if amount == U256::from(1) => format!("{name}--"),
+                    Step::Increment(amount) => format!("{name} += {}", encode_hex_reduced(amount)),
+                    Step::Decrement(amount) => format!("{name} -= {}", encode_hex_reduced(amount)),
+                }
+            ),
+            (_, None) => format!("while ({}) {{", render(&header_condition)),
+        }
+    }
+
+    /// The condition under which the loop's JUMPI, in the given state, continues into the body.
+    fn condition(&self, state: &State) -> WrappedOpcode {
+        let condition = state.last_instruction.input_operations[1].clone();
+        let jumps_to_body = self.body.instruction != self.header.last_instruction.instruction + 1;
+        if jumps_to_body {
+            return condition;
+        }
+
+        match (condition.opcode, condition.inputs.first()) {
+            (ISZERO, Some(WrappedInput::Opcode(negated))) => (**negated).clone(),
+            _ => WrappedOpcode::new(ISZERO, vec![WrappedInput::Opcode(Arc::new(condition))]),
+        }
+    }
+
+    /// The variables on the stack which the loop's body steps by a constant amount.
+    fn inductions(&self) -> Vec<Induction<'_>> {
+        if self.header.stack.size() != self.back_edge.stack.size() {
+            return Vec::new();
+        }
+
+        self.header
+            .stack
+            .stack
+            .iter()
+            .zip(self.back_edge.stack.stack.iter())
+            .filter(|(init, next)| init.operation != next.operation)
+            .filter_map(|(init, next)| {
+                Some(Induction {
+                    init: &init.operation,
+                    next: &next.operation,
+                    step: step(init, next)?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Step {
+    fn amount(&self) -> U256 {
+        match self {
+            Step::Increment(amount) | Step::Decrement(amount) => *amount,
+        }
+    }
+}
+
+/// How the value of a stack frame changes between iterations, if it's stepped by a constant.
+fn step(init: &StackFrame, next: &StackFrame) -> Option<Step> {
+    if is_constant(&init.operation) && is_constant(&next.operation) {
+        return match next.value > init.value {
+            true => Some(Step::Increment(next.value - init.value)),
+            false => Some(Step::Decrement(init.value - next.value)),
+        };
+    }
+
+    // otherwise, the next value is the initial value plus or minus a constant
+    let [a, b] = next.operation.inputs.as_slice() else {
+        return None;
+    };
+    let constant = |input: &WrappedInput| match input {
+        WrappedInput::Opcode(op) if is_constant(op) => match op.inputs.first() {
+            Some(WrappedInput::Raw(value)) => Some(*value),
+            _ => Some(U256::ZERO),
+        },
+        _ => None,
+    };
+    let is_init =
+        |input: &WrappedInput| matches!(input, WrappedInput::Opcode(op) if **op == init.operation);
+    match next.operation.opcode {
+        ADD if is_init(a) => constant(b).map(Step::Increment),
+        ADD if is_init(b) => constant(a).map(Step::Increment),
+        SUB if is_init(a) => constant(b).map(Step::Decrement),
+        _ => None,
+    }
+}
+
+/// Whether the operation pushes a constant.
+pub(crate) fn is_constant(op: &WrappedOpcode) -> bool {
+    (PUSH0..=PUSH32).contains(&op.opcode)
+}
+
+/// Replaces the induction variable in the header's condition with `marker`, wherever the
+/// condition differs from the back-edge's condition by the variable's step.
+fn substitute(
+    header: &WrappedOpcode,
+    back_edge: &WrappedOpcode,
+    induction: &Induction<'_>,
+    marker: &WrappedOpcode,
+) -> WrappedOpcode {
+    if header == induction.init && back_edge == induction.next {
+        return marker.clone();
+    }
+    if header == back_edge ||
+        header.opcode != back_edge.opcode ||
+        header.inputs.len() != back_edge.inputs.len()
+    {
+        return header.clone();
+    }
+
+    WrappedOpcode::new(
+        header.opcode,
+        header
+            .inputs
+            .iter()
+            .zip(back_edge.inputs.iter())
+            .map(|inputs| match inputs {
+                (WrappedInput::Opcode(h), WrappedInput::Opcode(b)) => {
+                    WrappedInput::Opcode(Arc::new(substitute(h, b, induction, marker)))
+                }
+                (input, _) => input.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use heimdall_vm::core::opcodes::{CALLDATALOAD, LT};
+
+    use super::*;
+
+    fn push(value: u64) -> WrappedOpcode {
+        WrappedOpcode::new(PUSH32, vec![WrappedInput::Raw(U256::from(value))])
+    }
+
+    fn op(opcode: u8, inputs: Vec<WrappedOpcode>) -> WrappedOpcode {
+        WrappedOpcode::new(
+            opcode,
+            inputs.into_iter().map(|input| WrappedInput::Opcode(Arc::new(input))).collect(),
+        )
+    }
+
+    fn frame(value: u64, operation: WrappedOpcode) -> StackFrame {
+        StackFrame { value: U256::from(value), operation }
+    }
+
+    #[test]
+    fn test_step() {
+        assert_eq!(
+            step(&frame(0, push(0)), &frame(1, push(1))),
+            Some(Step::Increment(U256::from(1)))
+        );
+        assert_eq!(
+            step(&frame(0x40, push(0x40)), &frame(0x20, push(0x20))),
+            Some(Step::Decrement(U256::from(0x20)))
+        );
+
+        let arg = op(CALLDATALOAD, vec![push(4)]);
+        assert_eq!(
+            step(&frame(5, arg.clone()), &frame(6, op(ADD, vec![push(1), arg.clone()]))),
+            Some(Step::Increment(U256::from(1)))
+        );
+        assert_eq!(
+            step(&frame(5, arg.clone()), &frame(3, op(SUB, vec![arg.clone(), push(2)]))),
+            Some(Step::Decrement(U256::from(2)))
+        );
+        assert_eq!(step(&frame(5, arg.clone()), &frame(4, op(SUB, vec![push(1), arg]))), None);
+    }
+
+    #[test]
+    fn test_substitute() {
+        let arg = op(CALLDATALOAD, vec![push(4)]);
+        let induction =
+            Induction { init: &push(0), next: &push(1), step: Step::Increment(U256::from(1)) };
+        let marker = push(0xff);
+
+        let header = op(LT, vec![push(0), arg.clone()]);
+        let back_edge = op(LT, vec![push(1), arg.clone()]);
+        assert_eq!(
+            substitute(&header, &back_edge, &induction, &marker),
+            op(LT, vec![marker.clone(), arg.clone()])
+        );
+
+        // conditions which don't check the variable are left untouched
+        assert_eq!(substitute(&arg, &arg, &induction, &marker), arg);
+    }
+}
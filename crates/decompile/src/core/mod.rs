@@ -1,57 +1,245 @@
 pub(crate) mod analyze;
+pub(crate) mod checkpoint;
+pub(crate) mod constructor;
+pub(crate) mod diff;
+pub(crate) mod immutables;
+pub(crate) mod internal;
+pub(crate) mod loops;
+pub(crate) mod modifiers;
+pub(crate) mod origins;
 pub(crate) mod out;
 pub(crate) mod postprocess;
+pub(crate) mod proxy;
 pub(crate) mod resolve;
+pub(crate) mod validate;
+pub(crate) mod verify;
 
-use alloy::primitives::Address;
-use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy::{
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::Address,
+};
+use alloy_dyn_abi::DynSolType;
 use alloy_json_abi::JsonAbi;
 use eyre::eyre;
-use hashbrown::HashMap;
+use futures::stream::{FuturesUnordered, TryStreamExt};
+use hashbrown::{HashMap, HashSet};
 use heimdall_common::{
     ether::{
-        compiler::detect_compiler,
+        compiler::{detect_compiler, Compiler},
+        metadata::{decode_metadata, CompilerMetadata},
         signatures::{
-            cache_signatures_from_abi, score_signature, ResolvedError, ResolvedFunction,
-            ResolvedLog,
+            cache_signatures_from_abi, cache_signatures_from_json_abi, score_signature,
+            ResolvedError, ResolvedFunction, ResolvedLog,
         },
         types::to_type,
     },
-    utils::strings::{decode_hex, encode_hex, encode_hex_reduced, StringExt},
+    utils::{
+        metrics::record_phase,
+        offline::is_offline,
+        progress::report_progress,
+        strings::{decode_hex, encode_hex, StringExt},
+    },
 };
-use heimdall_disassembler::{disassemble, DisassemblerArgsBuilder};
+use heimdall_disassembler::disassemble_bytecode;
 use heimdall_vm::{
-    core::vm::VM,
-    ext::selectors::{find_function_selectors, resolve_selectors},
+    core::{
+        eof::{is_eof, EofContainer},
+        vm::VM,
+    },
+    ext::{
+        creation::split_creation_code,
+        eof::lower_to_legacy,
+        exec::{Truncation, VMTrace},
+        selectors::{find_function_selectors, resolve_selectors},
+    },
 };
-use std::time::{Duration, Instant};
+use std::{collections::BTreeMap, future::Future};
+use web_time::{Duration, Instant};
 
 use crate::{
     core::{
         analyze::{Analyzer, AnalyzerType},
-        out::{build_abi, build_abi_with_details, source::build_source},
+        checkpoint::{Checkpoint, CheckpointedFunction},
+        constructor::{fetch_creation_code, recover_constructor},
+        immutables::{find_immutable_getters, mark_immutables, name_immutables},
+        internal::{find_internal_functions, inherit_errors},
+        modifiers::lift_modifiers,
+        out::{
+            build_abi, build_abi_with_details, build_interface,
+            foundry::{build_foundry_project, TestTarget},
+            layout::{build_storage_layout, qualify_struct_accesses},
+            source::{annotate_compiler, build_source},
+            vyper::build_vyper_source,
+        },
         postprocess::PostprocessOrchestrator,
-        resolve::match_parameters,
+        proxy::{annotate_facet, annotate_source, detect_proxy, resolve_proxy},
+        resolve::{is_selected, resolve_signature},
+        validate::{format_value, validate_onchain},
+        verify::verify,
     },
     error::Error,
-    interfaces::{AnalyzedFunction, DecompilerArgs},
+    interfaces::{
+        AnalyzedFunction, ConstructorInfo, DecompilerArgs, FunctionResult, FunctionResultSender,
+        OutputFormat, ProxyInfo, ProxyType, SourceMap, StorageLayout, VerificationReport,
+    },
+    utils::{
+        heuristics::rejects_callvalue_before,
+        selectors::{error_selector, event_selector},
+    },
 };
 use tracing::{debug, info, warn};
 
+// looking up the target's verified source isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use heimdall_common::{
+    ether::sourcify::get_verified_contract_from_rpc, resources::abi::get_verified_abi_from_rpc,
+};
+
 #[derive(Debug, Clone)]
 /// Result of a successful decompile operation
 ///
 /// Contains the decompiled source code (if requested) and the reconstructed ABI
 /// of the contract.
 pub struct DecompileResult {
-    /// The decompiled source code in Solidity or Yul format (if requested)
+    /// The decompiled source code in Solidity, Yul or Vyper format (if requested)
     pub source: Option<String>,
+    /// The bytecode ranges each line of the decompiled Solidity or Yul source was lifted from
+    pub source_map: Option<SourceMap>,
+    /// The format of the decompiled source code, which is Vyper if Solidity was requested for a
+    /// contract compiled with vyper
+    pub source_format: OutputFormat,
     /// The reconstructed JSON ABI of the contract
     pub abi: JsonAbi,
     /// The extended ABI with selector and signature information
     pub abi_with_details: serde_json::Value,
+    /// The storage layout recovered from the contract's storage accesses
+    pub storage_layout: StorageLayout,
+    /// The target's verified source files from Sourcify, keyed by path (if found and requested)
+    pub verified_sources: Option<BTreeMap<String, String>>,
+    /// The proxy standard the target implements, and the addresses it delegates to (if the
+    /// target is a proxy)
+    pub proxy: Option<ProxyInfo>,
+    /// The decompiled implementation of the target (if the target is a proxy, and
+    /// `--follow-proxy` is enabled)
+    pub implementation: Option<Box<DecompileResult>>,
+    /// The decompiled facets of the target, keyed by address (if the target is a diamond, and
+    /// `--follow-proxy` is enabled)
+    pub facets: BTreeMap<Address, DecompileResult>,
+    /// The files of a foundry project wrapping the decompiled source, keyed by path relative to
+    /// the project root (if the foundry output format was requested)
+    pub project: Option<BTreeMap<String, String>>,
+    /// How closely the decompiled source matches the target when recompiled with solc (if
+    /// `--verify` is enabled)
+    pub verification: Option<VerificationReport>,
+    /// The constructor of the target, recovered from its creation bytecode (if the target is
+    /// creation bytecode, or `--creation` is enabled)
+    pub constructor: Option<ConstructorInfo>,
+    /// The compiler metadata appended to the target's bytecode, including the exact compiler
+    /// version and the hash of its metadata file (if the target has any)
+    pub metadata: Option<CompilerMetadata>,
+}
+
+/// Analyzes the symbolic execution trace of a function. The function is marked non-payable if
+/// `rejects_callvalue`, and the value of constant functions is read by calling them.
+async fn analyze_trace(
+    mut analyzer: Analyzer,
+    trace: VMTrace,
+    evm: &VM,
+    rejects_callvalue: bool,
+) -> Result<AnalyzedFunction, Error> {
+    let mut analyzed_function = analyzer.analyze(trace).await?;
+    if rejects_callvalue {
+        analyzed_function.payable = false;
+    }
+
+    // if the function is constant, we can get the exact val
+    if analyzed_function.is_constant() && !analyzed_function.fallback {
+        let mut evm = evm.clone();
+        evm.reset();
+        let x = evm.call(&decode_hex(&analyzed_function.selector).expect("invalid selector"), 0)?;
+
+        let returns_param_type = analyzed_function
+            .returns
+            .as_ref()
+            .map(|ret_type| to_type(ret_type.replace("memory", "").trim()))
+            .unwrap_or(DynSolType::Bytes);
+
+        let decoded = returns_param_type
+            .abi_decode(&x.returndata)
+            .map(format_value)
+            .unwrap_or_else(|_| encode_hex(&x.returndata));
+
+        analyzed_function.constant_value = Some(decoded);
+    }
+
+    Ok(analyzed_function)
+}
+
+/// Notes that the function is incomplete, if a budget cut its symbolic execution short. Functions
+/// cut short before any of their logic was reached are emitted as stubs carrying the notice. The
+/// paths which weren't explored may write or read state, so the function is neither marked pure
+/// nor view.
+fn note_truncation(function: &mut AnalyzedFunction, truncations: &HashMap<String, Truncation>) {
+    if let Some(truncation) = truncations.get(&function.selector) {
+        function.notices.push(format!(
+            "symbolic execution was cut short by {truncation}, so this function is incomplete."
+        ));
+        function.pure = false;
+        function.view = false;
+        function.constant_value = None;
+    }
+}
+
+/// Awaits the functions' analyses concurrently. Each function's signature is matched, and its
+/// result reported, as soon as it's analyzed.
+async fn analyze_functions(
+    analyses: impl IntoIterator<Item = impl Future<Output = Result<AnalyzedFunction, Error>>>,
+    truncations: &HashMap<String, Truncation>,
+    resolved_selectors: &HashMap<String, Vec<ResolvedFunction>>,
+    function_results: Option<&FunctionResultSender>,
+) -> Result<Vec<AnalyzedFunction>, Error> {
+    let mut analyses = analyses.into_iter().collect::<FuturesUnordered<_>>();
+    let mut analyzed_functions = Vec::new();
+    while let Some(mut function) = analyses.try_next().await? {
+        note_truncation(&mut function, truncations);
+        resolve_signature(&mut function, resolved_selectors);
+        report_function_result(function_results, &function)?;
+        analyzed_functions.push(function);
+    }
+
+    Ok(analyzed_functions)
+}
+
+/// Sends the function's result to the caller, if they asked for each function's result. The
+/// fallback function has no ABI entry, so it isn't reported.
+fn report_function_result(
+    sender: Option<&FunctionResultSender>,
+    function: &AnalyzedFunction,
+) -> Result<(), Error> {
+    let Some(sender) = sender.filter(|_| !function.fallback) else {
+        return Ok(());
+    };
+
+    let abi = build_abi(std::slice::from_ref(function), None, &HashMap::new(), &HashMap::new())?;
+    if let Some(entry) = abi.functions.into_values().flatten().next() {
+        let _ = sender.send(FunctionResult {
+            selector: function.selector.clone(),
+            signature: function.resolved_function.as_ref().map(|f| f.signature.clone()),
+            abi: entry,
+        });
+    }
+
+    Ok(())
 }
 
+/// The number of branches symbolic execution may create for each function in deterministic mode,
+/// which bounds it in place of the timeout.
+const DETERMINISTIC_BRANCH_LIMIT: u32 = 10_000;
+
+/// The timeout used in deterministic mode, in milliseconds. It's far longer than the branch limit
+/// takes to reach, so that it never truncates execution.
+const DETERMINISTIC_TIMEOUT: u64 = 365 * 24 * 60 * 60 * 1000;
+
 /// Decompiles EVM bytecode into higher-level Solidity-like code
 ///
 /// This function analyzes the bytecode of a contract through symbolic execution
@@ -68,6 +256,10 @@ pub struct DecompileResult {
 pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
     // init
     let start_time = Instant::now();
+    let timeout = match args.deterministic {
+        true => DETERMINISTIC_TIMEOUT,
+        false => args.timeout,
+    };
     let mut all_resolved_events: HashMap<String, ResolvedLog> = HashMap::new();
     let mut all_resolved_errors: HashMap<String, ResolvedError> = HashMap::new();
 
@@ -82,13 +274,54 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
                 "llm postprocessing requires an openai API key. please provide one using the '--openai-api-key' flag."
             )));
     }
-    if !args.include_solidity && args.llm_postprocess {
+    let validation_target = match args.validate_onchain {
+        true => {
+            let address = args.target.parse::<Address>().map_err(|_| {
+                Error::Eyre(eyre!(
+                    "on-chain validation requires the target to be a contract address."
+                ))
+            })?;
+            if args.rpc_url.is_empty() {
+                return Err(Error::Eyre(eyre!(
+                    "on-chain validation requires an rpc url. please provide one using the '--rpc-url' flag."
+                )));
+            }
+            Some(address)
+        }
+        false => None,
+    };
+    let mut source_format = args.source_format();
+    if args.verify && !matches!(source_format, OutputFormat::Solidity | OutputFormat::Foundry) {
+        return Err(Error::Eyre(eyre!(
+            "verification requires including solidity source code. please enable the '--include-sol' flag."
+        )));
+    }
+    if !matches!(source_format, OutputFormat::Solidity | OutputFormat::Foundry) &&
+        args.llm_postprocess
+    {
         return Err(Error::Eyre(eyre!(
             "llm postprocessing requires including solidity source code. please enable the '--include-sol' flag."
         )));
     }
 
-    let analyzer_type = AnalyzerType::from_args(args.include_solidity, args.include_yul);
+    // vyper output is translated from the solidity heuristics' output, while interfaces only
+    // need the abi, so they skip lifting the functions' bodies
+    let analyzer_type = AnalyzerType::from_args(
+        matches!(
+            source_format,
+            OutputFormat::Solidity | OutputFormat::Vyper | OutputFormat::Foundry
+        ),
+        source_format == OutputFormat::Yul,
+    );
+
+    // use the verified ABI of the target from Sourcify or the chain's block explorer, if it is
+    // verified. signatures from a provided ABI are cached afterwards, so that they take precedence
+    let target_address =
+        args.target.parse::<Address>().ok().filter(|_| !args.rpc_url.is_empty() && !is_offline());
+    let (verified_abi, verified_sources) = get_verified_target(&args, target_address).await;
+    if let Some(verified_abi) = &verified_abi {
+        cache_signatures_from_json_abi(verified_abi);
+    }
 
     // parse and cache signatures from the ABI, if provided
     if let Some(abi_path) = args.abi.as_ref() {
@@ -108,6 +341,7 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         .await
         .map_err(|e| Error::FetchError(format!("fetching target bytecode failed: {e}")))?;
     debug!("fetching target bytecode took {:?}", start_fetch_time.elapsed());
+    record_phase("decompile.fetch_bytecode", start_fetch_time.elapsed());
 
     if contract_bytecode.is_empty() {
         return Err(Error::Eyre(eyre!(
@@ -118,13 +352,139 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         )));
     }
 
+    // EOF containers are lowered to legacy bytecode, which the VM can symbolically execute.
+    // they're never creation bytecode, since EOF initcode deploys its runtime with RETURNCONTRACT
+    let is_eof_container = is_eof(&contract_bytecode);
+    let contract_bytecode = if is_eof_container {
+        let container = EofContainer::parse(&contract_bytecode)
+            .map_err(|e| Error::Eyre(eyre!("parsing the EOF container failed: {e}")))?;
+        let lowered = lower_to_legacy(&container)
+            .map_err(|e| Error::Eyre(eyre!("lowering the EOF container failed: {e}")))?;
+        info!(
+            "target is an EOF container, decompiling its {} code section(s) as legacy bytecode",
+            container.code_sections.len()
+        );
+        lowered
+    } else {
+        contract_bytecode
+    };
+
+    // split creation bytecode into its constructor and the runtime bytecode it deploys. the
+    // creation bytecode of deployed targets is fetched from their creation transaction (if enabled)
+    if args.creation && target_address.is_none() && args.target.parse::<Address>().is_ok() {
+        return Err(Error::Eyre(eyre!(
+            "fetching the creation transaction requires an rpc url. please provide one using the '--rpc-url' flag."
+        )));
+    }
+    let (creation, deployed) = match target_address.filter(|_| args.creation) {
+        Some(address) => {
+            let creation_bytecode =
+                fetch_creation_code(address, &args.rpc_url, &args.etherscan_api_key).await?;
+            let creation = split_creation_code(&creation_bytecode, hardfork, timeout);
+            if creation.is_none() {
+                warn!("the target's creation transaction doesn't deploy it directly, it may have been deployed by a factory");
+            }
+            (creation, Some(contract_bytecode.clone()))
+        }
+        None if is_eof_container => (None, None),
+        None => (split_creation_code(&contract_bytecode, hardfork, timeout), None),
+    };
+    let contract_bytecode = match (&creation, &deployed) {
+        (Some(creation), None) => {
+            info!("target is creation bytecode, decompiling the runtime bytecode it deploys");
+            creation.runtime.clone()
+        }
+        _ => contract_bytecode,
+    };
+
+    // detect proxies, and read the addresses they delegate to from the chain
+    let proxy = match (detect_proxy(&contract_bytecode), target_address) {
+        (Some(proxy), Some(address)) => {
+            resolve_proxy(proxy, address, args.block, &args.rpc_url).await
+        }
+        // facets share the diamond's storage and loupe, so diamonds can only be told apart from
+        // their facets on-chain
+        (proxy, _) => proxy.filter(|proxy| proxy.proxy_type != ProxyType::Diamond),
+    };
+    if let Some(proxy) = &proxy {
+        info!("target is an {} proxy", proxy.proxy_type);
+    }
+
+    // decompile the implementation the proxy delegates to, or each facet of a diamond (if enabled)
+    let follow_proxy = args.follow_proxy && !args.rpc_url.is_empty() && !is_offline();
+    let mut implementation = None;
+    let mut facets = BTreeMap::new();
+    if let Some(proxy) = proxy.as_ref().filter(|_| follow_proxy) {
+        if let Some(address) = proxy.implementation {
+            info!("decompiling the proxy's implementation at {}", address);
+            implementation = decompile_delegate(&args, address).await.map(Box::new);
+        }
+        for facet in &proxy.facets {
+            info!("decompiling the diamond's facet at {}", facet.address);
+            if let Some(mut result) = decompile_delegate(&args, facet.address).await {
+                let diamond = target_address.expect("diamonds are only resolved on-chain");
+                result.source = result
+                    .source
+                    .map(|source| annotate_facet(&source, diamond, facet, &result.abi));
+                result.source_map = result
+                    .source_map
+                    .zip(result.source.as_ref())
+                    .map(|(source_map, source)| source_map.annotated(source));
+                facets.insert(facet.address, result);
+            }
+        }
+    }
+
     // perform versioning and compiler heuristics
-    let (_compiler, _version) = detect_compiler(&contract_bytecode);
+    let (compiler, compiler_version) = detect_compiler(&contract_bytecode);
+    let metadata = decode_metadata(&contract_bytecode);
+    let checked_arithmetic = compiler.checks_arithmetic(&compiler_version);
+    if compiler == Compiler::Vyper && source_format == OutputFormat::Solidity {
+        match args.llm_postprocess {
+            true => warn!(
+                "target was compiled with vyper, but llm postprocessing requires solidity output"
+            ),
+            false => {
+                info!("target was compiled with vyper, emitting vyper source");
+                source_format = OutputFormat::Vyper;
+            }
+        }
+    }
+
+    // recover the constructor, if the creation bytecode is known. interfaces don't declare one
+    let mut constructor = match &creation {
+        Some(creation) if source_format != OutputFormat::Interface => {
+            let start_constructor_time = Instant::now();
+            let constructor = recover_constructor(
+                creation,
+                deployed.as_deref(),
+                verified_abi.as_ref().and_then(|abi| abi.constructor.as_ref()),
+                args.skip_resolving,
+                hardfork,
+                timeout,
+            )
+            .await
+            .inspect_err(|e| warn!("failed to recover the constructor: {}", e))
+            .ok();
+            debug!("recovering the constructor took {:?}", start_constructor_time.elapsed());
+            constructor
+        }
+        _ => None,
+    };
+
+    // the immutables read by the runtime bytecode are marked, so that they can be named in the
+    // solidity source rather than inlined as constants
+    let immutables = constructor
+        .as_ref()
+        .filter(|_| matches!(source_format, OutputFormat::Solidity | OutputFormat::Foundry))
+        .map(|(info, _)| info.immutables.clone())
+        .unwrap_or_default();
+    let analyzed_bytecode = mark_immutables(&contract_bytecode, &immutables);
 
     // create a new EVM instance. we will use this for finding function selectors,
     // performing symbolic execution, and more.
     let mut evm = VM::new(
-        &contract_bytecode,
+        &analyzed_bytecode,
         &[],
         Address::default(),
         Address::default(),
@@ -133,21 +493,23 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         u128::MAX,
     )
     .with_hardfork(hardfork);
+    let branch_limit = match args.deterministic {
+        true => Some(args.max_branches.unwrap_or(DETERMINISTIC_BRANCH_LIMIT)),
+        false => args.max_branches,
+    };
+    if let Some(limit) = branch_limit {
+        evm = evm.with_branch_limit(limit);
+    }
+    if let Some(limit) = args.max_memory {
+        evm = evm.with_memory_limit(limit);
+    }
 
     // disassemble the contract's bytecode
-    let assembly = disassemble(
-        DisassemblerArgsBuilder::new()
-            .target(encode_hex(&contract_bytecode))
-            .hardfork(hardfork)
-            .build()
-            .expect("impossible case: failed to build disassembly arguments"),
-    )
-    .await
-    .map_err(|e| Error::Eyre(eyre!("disassembling contract bytecode failed: {}", e)))?;
+    let assembly = disassemble_bytecode(&contract_bytecode, hardfork, false);
 
     // find all the function selectors in the bytecode
     let start_selectors_time = Instant::now();
-    let selectors = find_function_selectors(&evm, &assembly);
+    let mut selectors = find_function_selectors(&evm, &assembly);
     debug!("finding function selectors took {:?}", start_selectors_time.elapsed());
 
     // resolve selectors (if enabled)
@@ -156,35 +518,84 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         false => resolve_selectors::<ResolvedFunction>(selectors.keys().cloned().collect()).await,
     };
 
+    // only the requested functions are executed, if the selectors or functions were filtered
+    if !args.selectors.is_empty() || !args.functions.is_empty() {
+        selectors.retain(|selector, _| {
+            is_selected(selector, &args.selectors, &args.functions, &resolved_selectors)
+        });
+        if selectors.is_empty() {
+            return Err(Error::Eyre(eyre!(
+                "no functions matched the '--selector' or '--function' filters"
+            )));
+        }
+        info!("decompiling {} selected functions", selectors.len());
+    }
+
     info!("performing symbolic execution on '{}'", args.target.truncate(64));
 
     let mut symbolic_execution_maps = HashMap::new();
+    let mut branch_counts = HashMap::new();
+    let mut truncations = HashMap::new();
+    let mut rejects_callvalue = HashSet::new();
     if selectors.is_empty() {
         warn!("discovered no function selectors in the bytecode.");
         let start_sym_exec_time = Instant::now();
         let (map, jumpdest_count) = evm
             .symbolic_exec(
                 Instant::now()
-                    .checked_add(Duration::from_millis(args.timeout))
+                    .checked_add(Duration::from_millis(timeout))
                     .expect("invalid timeout"),
             )
             .map_err(|e| Error::Eyre(eyre!("symbolic execution failed: {}", e)))?;
 
+        if let Some(truncation) = evm.truncation {
+            warn!("symbolic execution of 'fallback' was cut short by {}", truncation);
+            truncations.insert("fallback".to_string(), truncation);
+        }
         symbolic_execution_maps.insert("fallback".to_string(), map);
         debug!("symbolic execution (fallback) took {:?}", start_sym_exec_time.elapsed());
         debug!("'fallback' has {} unique branches", jumpdest_count);
     }
 
+    // with '--resume', each function is analyzed as soon as it's executed, and checkpointed
+    let mut checkpoint =
+        args.resume.as_ref().map(|path| Checkpoint::load(path, &analyzed_bytecode, analyzer_type));
+    let mut checkpointed_functions = Vec::new();
+
+    // the vm's storage and gas carry over from one selector to the next, so each function's
+    // trace depends on the ones executed before it. they're executed in selector order, rather
+    // than the hash map's, in deterministic mode
+    let mut selectors = selectors.into_iter().collect::<Vec<_>>();
+    if args.deterministic {
+        selectors.sort();
+    }
+
     let overall_sym_exec_time = Instant::now();
-    for (selector, entry_point) in selectors {
+    let total = selectors.len();
+    for (index, (selector, entry_point)) in selectors.into_iter().enumerate() {
+        if let Some(restored) = checkpoint.as_ref().and_then(|c| c.get(&selector)) {
+            info!(
+                selector = selector.as_str(),
+                function = index + 1,
+                functions = total,
+                "restored '{}' from the checkpoint ({}/{})",
+                selector,
+                index + 1,
+                total
+            );
+            branch_counts.insert(selector.clone(), restored.branches);
+            checkpointed_functions.push(restored.function.clone());
+            report_progress(args.progress.as_ref(), index as u64 + 1, total as u64, selector);
+            continue;
+        }
+
         let start_sym_exec_time = Instant::now();
+        let rejects_value = rejects_callvalue_before(&evm, &selector, entry_point);
         evm.reset();
         let (map, jumpdest_count) = match evm.symbolic_exec_selector(
             &selector,
             entry_point,
-            Instant::now()
-                .checked_add(Duration::from_millis(args.timeout))
-                .expect("invalid timeout"),
+            Instant::now().checked_add(Duration::from_millis(timeout)).expect("invalid timeout"),
         ) {
             Ok(map) => map,
             Err(e) => {
@@ -192,56 +603,121 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
                 continue;
             }
         };
-        symbolic_execution_maps.insert(selector.clone(), map);
-        debug!("symbolically executed '{}' in {:?}", selector, start_sym_exec_time.elapsed());
-        debug!("'{}' has {} unique branches", selector, jumpdest_count);
+        branch_counts.insert(selector.clone(), jumpdest_count);
+        if let Some(truncation) = evm.truncation {
+            warn!("symbolic execution of '{}' was cut short by {}", selector, truncation);
+            truncations.insert(selector.clone(), truncation);
+        }
+        info!(
+            selector = selector.as_str(),
+            function = index + 1,
+            functions = total,
+            branches = jumpdest_count,
+            elapsed_ms = start_sym_exec_time.elapsed().as_millis() as u64,
+            "symbolically executed '{}' ({}/{})",
+            selector,
+            index + 1,
+            total
+        );
+        report_progress(args.progress.as_ref(), index as u64 + 1, total as u64, selector.clone());
+
+        match (checkpoint.as_mut(), args.resume.as_ref()) {
+            (Some(checkpoint), Some(path)) => {
+                let analyzer = Analyzer::new(
+                    analyzer_type,
+                    args.skip_resolving,
+                    AnalyzedFunction::new(&selector, false),
+                )
+                .with_checked_arithmetic(checked_arithmetic);
+                let mut function = analyze_trace(analyzer, map, &evm, rejects_value).await?;
+                note_truncation(&mut function, &truncations);
+                checkpoint
+                    .save(
+                        path,
+                        &selector,
+                        CheckpointedFunction {
+                            function: function.clone(),
+                            branches: jumpdest_count,
+                        },
+                    )
+                    .unwrap_or_else(|e| warn!("failed to checkpoint '{}': {}", selector, e));
+                checkpointed_functions.push(function);
+            }
+            _ => {
+                if rejects_value {
+                    rejects_callvalue.insert(selector.clone());
+                }
+                symbolic_execution_maps.insert(selector, map);
+            }
+        }
     }
     debug!("symbolic execution took {:?}", overall_sym_exec_time.elapsed());
-    info!("symbolically executed {} selectors", symbolic_execution_maps.len());
+    record_phase("decompile.symbolic_execution", overall_sym_exec_time.elapsed());
+    info!(
+        "symbolically executed {} selectors",
+        symbolic_execution_maps.len() + checkpointed_functions.len()
+    );
 
     let start_analysis_time = Instant::now();
-    let handles = symbolic_execution_maps.into_iter().map(|(selector, trace_root)| {
-        let mut evm_clone = evm.clone();
-        async move {
-            let mut analyzer = Analyzer::new(
-                analyzer_type,
-                args.skip_resolving,
-                AnalyzedFunction::new(&selector, selector == "fallback"),
-            );
 
-            // analyze the symbolic execution trace
-            let mut analyzed_function = analyzer.analyze(trace_root).await?;
-
-            // if the function is constant, we can get the exact val
-            if analyzed_function.is_constant() && !analyzed_function.fallback {
-                evm_clone.reset();
-                let x = evm_clone.call(&decode_hex(&selector).expect("invalid selector"), 0)?;
-
-                let returns_param_type = analyzed_function
-                    .returns
-                    .as_ref()
-                    .map(|ret_type| to_type(ret_type.replace("memory", "").trim()))
-                    .unwrap_or(DynSolType::Bytes);
-
-                let decoded = returns_param_type
-                    .abi_decode(&x.returndata)
-                    .map(|decoded| match decoded {
-                        DynSolValue::String(s) => format!("\"{s}\""),
-                        DynSolValue::Uint(x, _) => x.to_string(),
-                        DynSolValue::Int(x, _) => x.to_string(),
-                        token => format!("0x{token:?}"),
-                    })
-                    .unwrap_or_else(|_| encode_hex(&x.returndata));
-
-                analyzed_function.constant_value = Some(decoded);
-            }
-
-            Ok::<_, Error>(analyzed_function)
+    // the bodies of internal functions shared by several call sites are analyzed once, and called
+    // from each of their callers rather than inlined into them
+    let mut internal_functions = match source_format {
+        OutputFormat::Solidity | OutputFormat::Foundry => {
+            find_internal_functions(
+                &evm,
+                &symbolic_execution_maps,
+                &analyzed_bytecode,
+                args.skip_resolving,
+                checked_arithmetic,
+                timeout,
+            )
+            .await?
         }
+        _ => Vec::new(),
+    };
+    let callees = internal_functions
+        .iter()
+        .map(|f| (f.entry, (f.name.clone(), f.arguments)))
+        .collect::<HashMap<_, _>>();
+    debug!("found {} shared internal functions", internal_functions.len());
+
+    let handles = symbolic_execution_maps.into_iter().map(|(selector, trace_root)| {
+        let analyzer = Analyzer::new(
+            analyzer_type,
+            args.skip_resolving,
+            AnalyzedFunction::new(&selector, selector == "fallback"),
+        )
+        .with_internal_functions(callees.clone())
+        .with_checked_arithmetic(checked_arithmetic);
+        analyze_trace(analyzer, trace_root, &evm, rejects_callvalue.contains(&selector))
     });
-    let mut analyzed_functions = futures::future::try_join_all(handles).await?;
+    let mut analyzed_functions = Box::pin(analyze_functions(
+        handles,
+        &truncations,
+        &resolved_selectors,
+        args.function_results.as_ref(),
+    ))
+    .await?;
+    for function in checkpointed_functions.iter_mut() {
+        resolve_signature(function, &resolved_selectors);
+        report_function_result(args.function_results.as_ref(), function)?;
+    }
+    analyzed_functions.extend(checkpointed_functions);
+
+    // storage variables and modifiers are named in the order they're first seen, which follows
+    // the hash map's iteration order unless the functions are sorted by their selectors
+    if args.deterministic {
+        analyzed_functions.sort_by(|a, b| a.selector.cmp(&b.selector));
+    }
+    name_immutables(&mut analyzed_functions, &immutables);
+    for f in internal_functions.iter_mut() {
+        name_immutables(std::slice::from_mut(&mut f.function), &immutables);
+    }
+    inherit_errors(&mut analyzed_functions, &mut internal_functions);
 
     debug!("analyzing symbolic execution results took {:?}", start_analysis_time.elapsed());
+    record_phase("decompile.analysis", start_analysis_time.elapsed());
     info!("analyzed {} symbolic execution traces", analyzed_functions.len());
 
     // resolve event and error selectors
@@ -250,8 +726,10 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         let start_error_resolving_time = Instant::now();
         let mut error_selectors: Vec<String> = analyzed_functions
             .iter()
-            .flat_map(|f| f.errors.iter().map(|e| encode_hex_reduced(*e).replacen("0x", "", 1)))
+            .chain(constructor.as_ref().map(|(_, f)| f))
+            .flat_map(|f| f.errors.iter().map(error_selector))
             .collect();
+        error_selectors.sort();
         error_selectors.dedup();
         debug!("resolving {} error signatures", error_selectors.len());
         let resolved_errors: HashMap<String, ResolvedError> =
@@ -281,8 +759,10 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         let start_event_resolving_time = Instant::now();
         let mut event_selectors: Vec<String> = analyzed_functions
             .iter()
-            .flat_map(|f| f.events.iter().map(|e| encode_hex_reduced(*e).replacen("0x", "", 1)))
+            .chain(constructor.as_ref().map(|(_, f)| f))
+            .flat_map(|f| f.events.iter().filter(|e| !e.is_zero()).map(event_selector))
             .collect();
+        event_selectors.sort();
         event_selectors.dedup();
         debug!("resolving {} event signatures", event_selectors.len());
         let resolved_events: HashMap<String, ResolvedLog> =
@@ -309,62 +789,263 @@ pub async fn decompile(args: DecompilerArgs) -> Result<DecompileResult, Error> {
         all_resolved_events.extend(resolved_events);
     }
 
-    // match analyzed parameters with resolved signatures for each function
-    analyzed_functions.iter_mut().for_each(|f| {
-        let resolve_function_signatures =
-            resolved_selectors.get(&f.selector).unwrap_or(&Vec::new()).to_owned();
-        let mut matched_resolved_functions = match_parameters(resolve_function_signatures, f);
-        debug!(
-            "matched {} resolved functions for '{}'",
-            matched_resolved_functions.len(),
-            f.selector
-        );
-
-        matched_resolved_functions.sort_by(|a, b| {
-            let a_score = score_signature(&a.signature, None);
-            let b_score = score_signature(&b.signature, None);
-            b_score.cmp(&a_score)
-        });
-
-        f.resolved_function = matched_resolved_functions.first().cloned();
-        debug!(
-            "using signature '{}' for '{}'",
-            f.resolved_function.as_ref().map(|r| &r.signature).unwrap_or(&String::new()),
-            f.selector
-        );
-    });
+    // probe view functions with eth_call to validate the decompiled signatures (if enabled)
+    if let Some(address) = validation_target {
+        let start_validation_time = Instant::now();
+        validate_onchain(&mut analyzed_functions, address, args.block, &args.rpc_url).await;
+        debug!("on-chain validation took {:?}", start_validation_time.elapsed());
+        record_phase("decompile.validation", start_validation_time.elapsed());
+    }
 
     // get a new PostprocessorOrchestrator
-    // note: this will do nothing if the include_solidity and include_yul flags are false
+    // note: this will do nothing if the output format is abi
     let mut postprocessor = PostprocessOrchestrator::new(analyzer_type)?;
-    let states = analyzed_functions
+    let states = internal_functions
         .iter_mut()
+        .map(|f| &mut f.function)
+        .chain(analyzed_functions.iter_mut())
+        .chain(constructor.as_mut().map(|(_, f)| f))
         .filter_map(|f| {
             postprocessor.postprocess(f).map_err(|e| f.notices.push(e.to_string())).ok()
         })
         .collect::<Vec<_>>();
 
-    let storage_variables = states
+    if let Some((info, _)) = constructor.as_mut().filter(|_| !immutables.is_empty()) {
+        find_immutable_getters(&mut analyzed_functions, &mut info.immutables);
+    }
+
+    let mut storage_variables = states
         .iter()
         .flat_map(|s| s.storage_type_map.iter())
         .chain(states.iter().flat_map(|s| s.transient_type_map.iter()))
         .map(|(k, v)| (k.to_string(), v.to_string()))
         .collect::<HashMap<String, String>>();
+    let storage_names = states
+        .iter()
+        .flat_map(|s| s.storage_map.iter())
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect::<HashMap<String, String>>();
+    let layout_functions = analyzed_functions
+        .iter()
+        .chain(constructor.as_ref().map(|(_, f)| f))
+        .chain(internal_functions.iter().map(|f| &f.function))
+        .cloned()
+        .collect::<Vec<_>>();
+    let (storage_layout, storage_slots) =
+        build_storage_layout(&layout_functions, &storage_names, &mut storage_variables);
+
+    // the first member of a struct is stored at the struct's own slot, so accesses to it aren't
+    // qualified by the storage postprocessor
+    let struct_mappings = storage_variables
+        .iter()
+        .filter(|(_, typ)| typ.contains("=> Struct_"))
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    analyzed_functions
+        .iter_mut()
+        .chain(constructor.as_mut().map(|(_, f)| f))
+        .chain(internal_functions.iter_mut().map(|f| &mut f.function))
+        .flat_map(|f| f.logic.iter_mut())
+        .for_each(|line| {
+            for name in &struct_mappings {
+                *line = qualify_struct_accesses(line, name);
+            }
+        });
+
+    // lift the guards shared by functions into modifiers, which vyper doesn't support
+    let modifiers = match source_format {
+        OutputFormat::Solidity | OutputFormat::Foundry => {
+            lift_modifiers(&mut analyzed_functions, &storage_variables)
+        }
+        _ => Vec::new(),
+    };
+
+    // carry the origins of each line over the edits made since postprocessing
+    analyzed_functions
+        .iter_mut()
+        .chain(constructor.as_mut().map(|(_, f)| f))
+        .chain(internal_functions.iter_mut().map(|f| &mut f.function))
+        .for_each(|f| f.sync_origins(&mut Vec::new()));
 
     // construct the abi for the given analyzed functions
-    let abi = build_abi(&analyzed_functions, &all_resolved_errors, &all_resolved_events)?;
-    let abi_with_details = build_abi_with_details(&abi, &analyzed_functions)?;
-    let source = build_source(
+    let constructor_source = constructor.as_ref().map(|(info, f)| (info, f));
+    let abi = build_abi(
         &analyzed_functions,
+        constructor_source,
         &all_resolved_errors,
         &all_resolved_events,
-        &storage_variables,
-        args.llm_postprocess,
-        args.openai_api_key,
-    )
-    .await?;
+    )?;
+    let abi_with_details = build_abi_with_details(&abi, &analyzed_functions)?;
+
+    let (source, source_map) = match source_format {
+        OutputFormat::Vyper => (
+            build_vyper_source(
+                &analyzed_functions,
+                &all_resolved_errors,
+                &all_resolved_events,
+                &storage_variables,
+                &storage_layout,
+                &compiler_version,
+            )?,
+            None,
+        ),
+        OutputFormat::Interface => (Some(build_interface(&abi, "IDecompiledContract")), None),
+        _ => build_source(
+            &analyzed_functions,
+            constructor_source,
+            &modifiers,
+            &internal_functions,
+            &all_resolved_errors,
+            &all_resolved_events,
+            &storage_variables,
+            &storage_slots,
+            &storage_layout,
+            args.llm_postprocess,
+            args.openai_api_key,
+        )
+        .await?
+        .unzip(),
+    };
+
+    let source = match &proxy {
+        Some(proxy) => source.map(|source| annotate_source(&source, proxy)),
+        None => source,
+    };
+    let source = source
+        .map(|source| annotate_compiler(&source, &compiler, &compiler_version, metadata.as_ref()));
+    let source_map =
+        source_map.zip(source.as_ref()).map(|(source_map, source)| source_map.annotated(source));
+
+    // annotate each line with the bytecode offsets it was lifted from (if enabled)
+    let source = match (source, &source_map) {
+        (Some(source), Some(source_map)) if args.include_pc => {
+            Some(source_map.annotate_offsets(&source))
+        }
+        (source, _) if args.include_pc => {
+            warn!("skipping pc annotations, which require solidity or yul output");
+            source
+        }
+        (source, _) => source,
+    };
+
+    // recompile the source, and compare its shape against the target (if enabled)
+    let verification = match (&source, source_format) {
+        (Some(source), OutputFormat::Solidity | OutputFormat::Foundry) if args.verify => {
+            let start_verification_time = Instant::now();
+            let report =
+                verify(source, &abi, &branch_counts, args.solc.as_deref(), hardfork, timeout).await;
+            debug!("verification took {:?}", start_verification_time.elapsed());
+            record_phase("decompile.verification", start_verification_time.elapsed());
+            Some(report)
+        }
+        (..) if args.verify => {
+            warn!("skipping verification, which requires solidity output");
+            None
+        }
+        _ => None,
+    };
+
+    // wrap the source in a foundry project, whose test forks the chain if the target is deployed
+    let project = match (&source, source_format) {
+        (Some(source), OutputFormat::Foundry) => {
+            let target = match args.target.parse::<Address>() {
+                Ok(address) => TestTarget::Fork(
+                    address,
+                    match args.block {
+                        Some(BlockId::Number(BlockNumberOrTag::Number(block))) => Some(block),
+                        _ => None,
+                    },
+                ),
+                Err(_) => TestTarget::Etch(contract_bytecode.clone()),
+            };
+            Some(build_foundry_project(source, &abi, &analyzed_functions, target))
+        }
+        _ => None,
+    };
 
     debug!("decompilation took {:?}", start_time.elapsed());
+    record_phase("decompile", start_time.elapsed());
+
+    let verified_sources = verified_sources.filter(|_| args.include_verified_source);
 
-    Ok(DecompileResult { source, abi, abi_with_details })
+    Ok(DecompileResult {
+        source,
+        source_map,
+        source_format,
+        abi,
+        abi_with_details,
+        storage_layout,
+        verified_sources,
+        proxy,
+        implementation,
+        facets,
+        project,
+        verification,
+        constructor: constructor.map(|(info, _)| info),
+        metadata,
+    })
+}
+
+/// Looks up the verified ABI of the target on Sourcify, or otherwise on the chain's block explorer,
+/// if it's a deployed contract. The verified sources are returned too, if it's verified on
+/// Sourcify.
+#[cfg(not(target_arch = "wasm32"))]
+async fn get_verified_target(
+    args: &DecompilerArgs,
+    target_address: Option<Address>,
+) -> (Option<JsonAbi>, Option<BTreeMap<String, String>>) {
+    let verified_contract = match target_address {
+        Some(address) if !args.no_sourcify => {
+            get_verified_contract_from_rpc(address, &args.rpc_url).await.unwrap_or_else(|e| {
+                warn!("failed to look up target on sourcify: {}", e);
+                None
+            })
+        }
+        _ => None,
+    };
+    match (verified_contract, target_address) {
+        (Some(verified_contract), _) => {
+            info!(
+                "found a {} sourcify match for the target, using its verified abi",
+                verified_contract.match_type
+            );
+            (Some(verified_contract.abi), Some(verified_contract.sources))
+        }
+        (None, Some(address)) => (
+            get_verified_abi_from_rpc(
+                address,
+                &args.rpc_url,
+                &args.explorer_api_url,
+                &args.etherscan_api_key,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                warn!("failed to fetch the verified abi of the target: {}", e);
+                None
+            })
+            .inspect(|_| info!("found a verified abi for the target, using it for naming")),
+            None,
+        ),
+        (None, None) => (None, None),
+    }
+}
+
+/// Verified contracts can't be looked up on wasm, where network access isn't available.
+#[cfg(target_arch = "wasm32")]
+async fn get_verified_target(
+    _args: &DecompilerArgs,
+    _target_address: Option<Address>,
+) -> (Option<JsonAbi>, Option<BTreeMap<String, String>>) {
+    (None, None)
+}
+
+/// Decompiles a contract the target delegates to, such as a proxy's implementation or a diamond's
+/// facet, with the same arguments as the target.
+async fn decompile_delegate(args: &DecompilerArgs, address: Address) -> Option<DecompileResult> {
+    let delegate_args =
+        DecompilerArgs { target: address.to_string(), follow_proxy: false, ..args.clone() };
+    Box::pin(decompile(delegate_args))
+        .await
+        .inspect_err(|e| warn!("failed to decompile {}: {}", address, e))
+        .ok()
 }
@@ -0,0 +1,332 @@
+use hashbrown::HashMap;
+
+use crate::interfaces::AnalyzedFunction;
+
+/// A guard shared by several functions, which is lifted out of their logic into a modifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Modifier {
+    /// The name of the modifier
+    pub name: String,
+    /// The lines the modifier runs before the function's body
+    pub before: Vec<String>,
+    /// The lines the modifier runs after the function's body
+    pub after: Vec<String>,
+}
+
+/// The kinds of guards which are lifted into modifiers, in the order they're declared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum GuardKind {
+    /// Requires the caller to be the address held by a storage variable
+    Owner,
+    /// Requires a storage flag to be set, which is toggled while the body runs
+    Reentrancy,
+    /// Requires a storage flag to be unset
+    Pause,
+}
+
+impl GuardKind {
+    fn name(&self) -> &'static str {
+        match self {
+            GuardKind::Owner => "onlyOwner",
+            GuardKind::Reentrancy => "nonReentrant",
+            GuardKind::Pause => "whenNotPaused",
+        }
+    }
+}
+
+/// A guard found in a function's logic.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Guard {
+    kind: GuardKind,
+    before: Vec<String>,
+    after: Vec<String>,
+}
+
+/// Lifts the guards which are repeated across functions into modifiers: owner checks, reentrancy
+/// locks, and pause-flag checks. The guards are removed from the logic of each function, which
+/// the modifiers are applied to instead.
+pub(crate) fn lift_modifiers(
+    functions: &mut [AnalyzedFunction],
+    storage_variables: &HashMap<String, String>,
+) -> Vec<Modifier> {
+    let guards = functions
+        .iter()
+        .map(|f| match f.fallback || f.maybe_getter_for.is_some() || f.is_constant() {
+            true => Vec::new(),
+            false => find_guards(&f.logic, storage_variables),
+        })
+        .collect::<Vec<_>>();
+
+    // only guards repeated across functions are lifted, ordered by kind and first appearance
+    let mut shared: Vec<(Guard, usize)> = Vec::new();
+    for guard in guards.iter().flatten() {
+        match shared.iter_mut().find(|(g, _)| g == guard) {
+            Some((_, count)) => *count += 1,
+            None => shared.push((guard.clone(), 1)),
+        }
+    }
+    let mut shared = shared
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(guard, _)| guard)
+        .collect::<Vec<_>>();
+    shared.sort_by_key(|guard| guard.kind);
+
+    let mut kind_counts: HashMap<GuardKind, usize> = HashMap::new();
+    let modifiers = shared
+        .iter()
+        .map(|guard| {
+            let count = kind_counts.entry(guard.kind).or_default();
+            *count += 1;
+            Modifier {
+                name: match count {
+                    1 => guard.kind.name().to_string(),
+                    n => format!("{}{n}", guard.kind.name()),
+                },
+                before: guard.before.clone(),
+                after: guard.after.clone(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for (f, guards) in functions.iter_mut().zip(guards) {
+        for guard in guards {
+            let Some(i) = shared.iter().position(|g| *g == guard) else {
+                continue;
+            };
+
+            if let Some(start) = f.logic.windows(guard.before.len()).position(|w| w == guard.before)
+            {
+                f.logic.drain(start..start + guard.before.len());
+            }
+            f.logic.retain(|line| !guard.after.contains(line));
+            f.modifiers.push(modifiers[i].name.clone());
+        }
+    }
+
+    modifiers
+}
+
+/// Finds the guards in the top-level scope of a function's logic.
+fn find_guards(logic: &[String], storage_variables: &HashMap<String, String>) -> Vec<Guard> {
+    let mut guards: Vec<Guard> = Vec::new();
+    let mut depth = 0;
+    for (i, line) in logic.iter().enumerate() {
+        let is_top_level = depth == 0;
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+        let Some(condition) = is_top_level.then(|| require_condition(line)).flatten() else {
+            continue;
+        };
+        if !is_guard_condition(condition) {
+            continue;
+        }
+
+        let variables =
+            storage_variables.keys().filter(|name| references(condition, name)).collect::<Vec<_>>();
+        let guard = match variables.as_slice() {
+            [] => None,
+            _ if references(condition, "msg.sender") => {
+                Some(Guard { kind: GuardKind::Owner, before: vec![line.clone()], after: vec![] })
+            }
+            [variable] => {
+                // locks are set after they're checked, and reset once the body has run
+                let assignment = format!("{variable} = ");
+                match logic.get(i + 1).filter(|next| next.starts_with(&assignment)) {
+                    Some(lock) => logic[i + 2..]
+                        .iter()
+                        .rev()
+                        .find(|line| line.starts_with(&assignment) && *line != lock)
+                        .map(|unlock| Guard {
+                            kind: GuardKind::Reentrancy,
+                            before: vec![line.clone(), lock.clone()],
+                            after: vec![unlock.clone()],
+                        }),
+                    None if is_unset_flag(condition, variable) => Some(Guard {
+                        kind: GuardKind::Pause,
+                        before: vec![line.clone()],
+                        after: vec![],
+                    }),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(guard) = guard.filter(|guard| !guards.contains(guard)) {
+            guards.push(guard);
+        }
+    }
+
+    guards
+}
+
+/// The condition of a `require` statement, without its error.
+fn require_condition(line: &str) -> Option<&str> {
+    let arguments = line.strip_prefix("require(")?;
+    let mut depth = 0;
+    for (i, c) in arguments.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(&arguments[..i]),
+            ')' => depth -= 1,
+            ',' if depth == 0 => return Some(&arguments[..i]),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether the condition only depends on the caller and storage, so it doesn't read the
+/// function's arguments or any state local to the call.
+fn is_guard_condition(condition: &str) -> bool {
+    !["msg.data", "msg.value", "memory[", "ret0", "var_"]
+        .iter()
+        .any(|local| condition.contains(local)) &&
+        !(0..16).any(|i| references(condition, &format!("arg{i}")))
+}
+
+/// Whether the condition requires the flag to be unset, e.g. `!(uint8(store_a))`.
+fn is_unset_flag(condition: &str, flag: &str) -> bool {
+    let rest = condition.replacen(flag, "", 1);
+    let rest = ["uint8", "bool", "bytes1"].iter().fold(rest, |rest, cast| rest.replace(cast, ""));
+    condition.starts_with('!') && rest.chars().all(|c| matches!(c, '!' | '(' | ')' | ' '))
+}
+
+/// Whether the line references the variable, rather than a variable it's a prefix of.
+fn references(line: &str, name: &str) -> bool {
+    let is_identifier = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    line.match_indices(name).any(|(i, _)| {
+        !line[..i].chars().next_back().is_some_and(is_identifier) &&
+            !line[i + name.len()..].chars().next().is_some_and(is_identifier)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(selector: &str, logic: &[&str]) -> AnalyzedFunction {
+        let mut f = AnalyzedFunction::new(selector, false);
+        f.pure = false;
+        f.logic = logic.iter().map(|line| line.to_string()).collect();
+        f
+    }
+
+    fn storage_variables() -> HashMap<String, String> {
+        ["store_a", "store_b", "store_c", "store_d"]
+            .iter()
+            .map(|name| (name.to_string(), "uint256".to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_lift_modifiers() {
+        let owner =
+            "require(msg.sender == (address(store_a)), \"Ownable: caller is not the owner\");";
+        let lock = "require(!(store_b == 0x02), CustomError_3ee5aeb5());";
+        let paused = "require(!(uint8(store_c)), \"Pausable: paused\");";
+        let mut functions = vec![
+            function(
+                "01",
+                &["require(arg0 - (address(arg0)));", owner, paused, "store_d = arg0;", "return ;"],
+            ),
+            function(
+                "02",
+                &[
+                    lock,
+                    "store_b = 0x02;",
+                    "if (arg0 > 0x01) {",
+                    "store_b = 0x01;",
+                    "return ;",
+                    "}",
+                    paused,
+                    "store_d = store_d + arg0;",
+                    "store_b = 0x01;",
+                    "return ;",
+                ],
+            ),
+            function("03", &[lock, "store_b = 0x02;", owner, "store_d = 0;", "store_b = 0x01;"]),
+            function("04", &[paused, "return store_d;"]),
+        ];
+
+        let modifiers = lift_modifiers(&mut functions, &storage_variables());
+        assert_eq!(
+            modifiers,
+            vec![
+                Modifier {
+                    name: "onlyOwner".to_string(),
+                    before: vec![owner.to_string()],
+                    after: vec![]
+                },
+                Modifier {
+                    name: "nonReentrant".to_string(),
+                    before: vec![lock.to_string(), "store_b = 0x02;".to_string()],
+                    after: vec!["store_b = 0x01;".to_string()]
+                },
+                Modifier {
+                    name: "whenNotPaused".to_string(),
+                    before: vec![paused.to_string()],
+                    after: vec![]
+                },
+            ]
+        );
+
+        assert_eq!(functions[0].modifiers, vec!["onlyOwner", "whenNotPaused"]);
+        assert_eq!(
+            functions[0].logic,
+            vec!["require(arg0 - (address(arg0)));", "store_d = arg0;", "return ;"]
+        );
+        assert_eq!(functions[1].modifiers, vec!["nonReentrant", "whenNotPaused"]);
+        assert_eq!(
+            functions[1].logic,
+            vec!["if (arg0 > 0x01) {", "return ;", "}", "store_d = store_d + arg0;", "return ;"]
+        );
+        assert_eq!(functions[2].modifiers, vec!["nonReentrant", "onlyOwner"]);
+        assert_eq!(functions[2].logic, vec!["store_d = 0;"]);
+        assert_eq!(functions[3].modifiers, vec!["whenNotPaused"]);
+    }
+
+    #[test]
+    fn test_lift_modifiers_ignores_unique_guards() {
+        let mut functions = vec![
+            function("01", &["require(msg.sender == (address(store_a)));", "store_d = 0x01;"]),
+            function("02", &["require(msg.sender == (address(store_b)));", "store_d = 0x02;"]),
+            function("03", &["if (store_d) {", "require(!store_c);", "}"]),
+            function("04", &["if (store_d) {", "require(!store_c);", "}"]),
+            function("05", &["require(!(store_c > 0x05));"]),
+            function("06", &["require(!(store_c > 0x05));"]),
+        ];
+
+        assert!(lift_modifiers(&mut functions, &storage_variables()).is_empty());
+        assert!(functions.iter().all(|f| f.modifiers.is_empty()));
+        assert_eq!(functions[2].logic.len(), 3);
+    }
+
+    #[test]
+    fn test_require_condition() {
+        assert_eq!(
+            require_condition("require(!(uint8(store_a)), \"Pausable: paused\");"),
+            Some("!(uint8(store_a))")
+        );
+        assert_eq!(
+            require_condition("require(msg.sender == store_a);"),
+            Some("msg.sender == store_a")
+        );
+        assert_eq!(require_condition("store_a = 0x01;"), None);
+    }
+
+    #[test]
+    fn test_is_unset_flag() {
+        assert!(is_unset_flag("!store_a", "store_a"));
+        assert!(is_unset_flag("!(bool(store_a))", "store_a"));
+        assert!(!is_unset_flag("bool(store_a)", "store_a"));
+        assert!(!is_unset_flag("!(store_a > 0x05)", "store_a"));
+    }
+
+    #[test]
+    fn test_references() {
+        assert!(references("require(!store_a);", "store_a"));
+        assert!(!references("require(!store_ab);", "store_a"));
+        assert!(!references("require(!var_store_a);", "store_a"));
+    }
+}
@@ -0,0 +1,143 @@
+use heimdall_vm::core::vm::Instruction;
+
+/// A range of bytecode offsets, from the first byte of an instruction up to (but excluding) the
+/// end of the last.
+pub(crate) type Origin = (u128, u128);
+
+/// Adds the instruction to the ranges, extending the last range if the instruction follows it.
+pub(crate) fn extend_origins(origins: &mut Vec<Origin>, instruction: &Instruction) {
+    // the vm's instruction pointer is one past the instruction's offset
+    let start = instruction.instruction.saturating_sub(1);
+    let size = match instruction.opcode {
+        0x60..=0x7f => 1 + (instruction.opcode - 0x5f) as u128,
+        _ => 1,
+    };
+
+    match origins.last_mut() {
+        Some((_, end)) if *end == start => *end = start + size,
+        _ => origins.push((start, start + size)),
+    }
+}
+
+/// Carries the origins of each line of `before` over to the lines of `after` they became. Lines
+/// present in both are matched by their longest common subsequence, and the lines changed between
+/// two matches are matched in order. Lines which don't match any line of `before` were added, and
+/// are lifted from the `pending` instructions.
+pub(crate) fn realign(
+    before: &[String],
+    origins: &[Vec<Origin>],
+    after: &[String],
+    pending: &[Origin],
+) -> Vec<Vec<Origin>> {
+    let changed = |origins: &[Origin]| {
+        let mut origins = origins.to_vec();
+        origins.extend_from_slice(pending);
+        origins
+    };
+
+    // lines changed in place, e.g. by a line-level postprocessor, keep their origins
+    if before.len() == after.len() {
+        return before
+            .iter()
+            .zip(after)
+            .zip(origins)
+            .map(|((b, a), origins)| match b == a {
+                true => origins.clone(),
+                false => changed(origins),
+            })
+            .collect();
+    }
+
+    // most changes only push or pop lines, so the common prefix and suffix are skipped
+    let prefix = before.iter().zip(after).take_while(|(b, a)| b == a).count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(b, a)| b == a)
+        .count();
+    let (old, new) = (&before[prefix..before.len() - suffix], &after[prefix..after.len() - suffix]);
+
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut realigned = origins[..prefix].to_vec();
+    let (mut i, mut j) = (0, 0);
+    let mut removed: Vec<usize> = Vec::new();
+    let mut added = 0;
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+            realigned.push(origins[prefix + i - 1].clone());
+            removed.clear();
+            added = 0;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            // a line replacing one removed since the last match takes its origins
+            realigned.push(match removed.get(added) {
+                Some(removed) => changed(&origins[prefix + removed]),
+                None => pending.to_vec(),
+            });
+            added += 1;
+            j += 1;
+        }
+    }
+    realigned.extend_from_slice(&origins[origins.len() - suffix..]);
+    realigned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_realign_pushed_and_changed_lines() {
+        let before = lines(&["a;", "if (b) {"]);
+        let origins = vec![vec![(0, 2)], vec![(2, 4)]];
+
+        // pushed lines are lifted from the pending instructions
+        let after = lines(&["a;", "if (b) {", "c;"]);
+        assert_eq!(
+            realign(&before, &origins, &after, &[(4, 6)]),
+            vec![vec![(0, 2)], vec![(2, 4)], vec![(4, 6)]]
+        );
+
+        // changed lines keep their origins, as well as the pending instructions
+        let after = lines(&["a;", "require(b);"]);
+        assert_eq!(
+            realign(&before, &origins, &after, &[(4, 6)]),
+            vec![vec![(0, 2)], vec![(2, 4), (4, 6)]]
+        );
+    }
+
+    #[test]
+    fn test_realign_removed_lines() {
+        let before = lines(&["a;", "b;", "c;", "d;"]);
+        let origins = vec![vec![(0, 1)], vec![(1, 2)], vec![(2, 3)], vec![(3, 4)]];
+
+        let after = lines(&["a;", "d;"]);
+        assert_eq!(realign(&before, &origins, &after, &[]), vec![vec![(0, 1)], vec![(3, 4)]]);
+
+        // a removed line which is replaced passes its origins on
+        let after = lines(&["a;", "x;", "d;"]);
+        assert_eq!(
+            realign(&before, &origins, &after, &[]),
+            vec![vec![(0, 1)], vec![(1, 2)], vec![(3, 4)]]
+        );
+    }
+}
@@ -1,25 +1,39 @@
 use alloy_dyn_abi::DynSolType;
 use hashbrown::HashMap;
-use std::time::Instant;
+use web_time::Instant;
 
-use alloy_json_abi::{Error, Event, EventParam, Function, JsonAbi, Param, StateMutability};
+use alloy_json_abi::{
+    Constructor, Error, Event, EventParam, Function, JsonAbi, Param, StateMutability,
+};
 
 use eyre::Result;
-use heimdall_common::{
-    ether::{
-        signatures::{ResolvedError, ResolvedLog},
-        types::{to_abi_string, to_components},
-    },
-    utils::{hex::ToLowerHex, strings::encode_hex_reduced},
+use heimdall_common::ether::{
+    signatures::{ResolvedError, ResolvedLog},
+    types::{to_abi_string, to_components},
 };
 use serde_json::{json, Value};
 
 use tracing::debug;
 
-use crate::interfaces::AnalyzedFunction;
+use crate::{
+    interfaces::{AnalyzedFunction, ConstructorInfo},
+    utils::selectors::{
+        error_selector as error_selector_hex, event_selector as event_selector_hex,
+    },
+};
+
+/// The header of every generated solidity file.
+pub(crate) const SOLIDITY_HEADER: &str =
+    "// SPDX-License-Identifier: MIT\npragma solidity >=0.8.0;\n";
+
+/// Builds a solidity interface declaring the functions, events, and errors of the ABI.
+pub(crate) fn build_interface(abi: &JsonAbi, name: &str) -> String {
+    format!("{SOLIDITY_HEADER}\n{}\n", abi.to_sol(name, None))
+}
 
 pub(crate) fn build_abi(
     functions: &[AnalyzedFunction],
+    constructor: Option<(&ConstructorInfo, &AnalyzedFunction)>,
     all_resolved_errors: &HashMap<String, ResolvedError>,
     all_resolved_logs: &HashMap<String, ResolvedLog>,
 ) -> Result<JsonAbi> {
@@ -75,85 +89,146 @@ pub(crate) fn build_abi(
                 })
                 .collect(),
             outputs: f
-                .returns
-                .as_ref()
-                .map(|r| {
-                    vec![Param {
-                        name: "".to_string(),
-                        internal_type: None,
-                        ty: r.replacen("memory", "", 1).trim().to_string(),
-                        components: vec![],
-                    }]
+                .return_types()
+                .iter()
+                .map(|typ| Param {
+                    name: "".to_string(),
+                    internal_type: None,
+                    ty: to_abi_string(typ),
+                    components: to_components(typ),
                 })
-                .unwrap_or_default(),
+                .collect(),
             state_mutability,
         };
 
-        // add functions errors
-        f.errors.iter().for_each(|error_selector| {
-            // determine the name of the error
-            let (name, inputs) = match all_resolved_errors
-                .get(&encode_hex_reduced(*error_selector).replacen("0x", "", 1))
-            {
-                Some(error) => (
-                    error.name.clone(),
-                    error
-                        .inputs()
-                        .iter()
-                        .enumerate()
-                        .map(|(i, input)| Param {
-                            name: format!("arg{i}"),
-                            internal_type: None,
-                            ty: to_abi_string(input),
-                            components: to_components(input),
-                        })
-                        .collect(),
-                ),
-                None => (format!("CustomError_{}", error_selector.to_lower_hex()), vec![]),
-            };
-
-            let error = Error { name, inputs };
-
-            abi.errors.insert(error.name.clone(), vec![error]);
-        });
-
-        // add functions events
-        f.events.iter().for_each(|event_selector| {
-            // determine the name of the event
-            let (name, inputs) = match all_resolved_logs
-                .get(&encode_hex_reduced(*event_selector).replacen("0x", "", 1))
-            {
-                Some(event) => (
-                    event.name.clone(),
-                    event
-                        .inputs()
-                        .iter()
-                        .enumerate()
-                        .map(|(i, input)| EventParam {
-                            name: format!("arg{i}"),
-                            internal_type: None,
-                            ty: to_abi_string(input),
-                            components: to_components(input),
-                            indexed: false,
-                        })
-                        .collect(),
-                ),
-                None => (format!("Event_{}", event_selector.to_lower_hex()), vec![]),
-            };
-
-            let event = Event { name, inputs, anonymous: event_selector.is_zero() };
-
-            abi.events.insert(event.name.clone(), vec![event]);
-        });
+        // add functions errors and events
+        add_errors(&mut abi, f, all_resolved_errors);
+        add_events(&mut abi, f, all_resolved_logs);
 
         abi.functions.insert(name, vec![function]);
     });
 
+    // add the constructor, and the errors and events it emits
+    if let Some((constructor, f)) = constructor {
+        abi.constructor = Some(build_constructor(constructor));
+        add_errors(&mut abi, f, all_resolved_errors);
+        add_events(&mut abi, f, all_resolved_logs);
+    }
+
     debug!("constructing abi took {:?}", start_time.elapsed());
 
     Ok(abi)
 }
 
+/// Adds the errors the function reverts with to the abi.
+fn add_errors(
+    abi: &mut JsonAbi,
+    f: &AnalyzedFunction,
+    all_resolved_errors: &HashMap<String, ResolvedError>,
+) {
+    f.errors.iter().for_each(|error_selector| {
+        // determine the name of the error
+        let (name, inputs) = match all_resolved_errors.get(&error_selector_hex(error_selector)) {
+            Some(error) => (
+                error.name.clone(),
+                error
+                    .inputs()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, input)| Param {
+                        name: format!("arg{i}"),
+                        internal_type: None,
+                        ty: to_abi_string(input),
+                        components: to_components(input),
+                    })
+                    .collect(),
+            ),
+            None => (
+                format!("CustomError_{}", error_selector_hex(error_selector)),
+                f.error_arguments
+                    .get(error_selector)
+                    .map(|arguments| {
+                        arguments
+                            .iter()
+                            .enumerate()
+                            .map(|(i, typ)| Param {
+                                name: format!("arg{i}"),
+                                internal_type: None,
+                                ty: typ.to_string(),
+                                components: vec![],
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+        };
+
+        let error = Error { name, inputs };
+
+        abi.errors.insert(error.name.clone(), vec![error]);
+    });
+}
+
+/// Adds the events the function emits to the abi.
+fn add_events(
+    abi: &mut JsonAbi,
+    f: &AnalyzedFunction,
+    all_resolved_logs: &HashMap<String, ResolvedLog>,
+) {
+    f.events.iter().for_each(|event_selector| {
+        // determine the name of the event
+        let resolved = all_resolved_logs.get(&event_selector_hex(event_selector));
+        let name = match resolved {
+            Some(event) => event.name.clone(),
+            None => format!("Event_{}", &event_selector_hex(event_selector)[0..8]),
+        };
+        let inputs = f
+            .event_parameters
+            .get(event_selector)
+            .cloned()
+            .unwrap_or_default()
+            .inputs(resolved)
+            .iter()
+            .enumerate()
+            .map(|(i, (input, indexed))| EventParam {
+                name: format!("arg{i}"),
+                internal_type: None,
+                ty: to_abi_string(input),
+                components: to_components(input),
+                indexed: *indexed,
+            })
+            .collect();
+
+        let event = Event { name, inputs, anonymous: event_selector.is_zero() };
+
+        abi.events.insert(event.name.clone(), vec![event]);
+    });
+}
+
+/// Builds the abi of the recovered constructor.
+fn build_constructor(constructor: &ConstructorInfo) -> Constructor {
+    Constructor {
+        inputs: constructor
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                let typ = DynSolType::parse(&arg.typ).unwrap_or(DynSolType::Bytes);
+                Param {
+                    name: format!("arg{i}"),
+                    internal_type: None,
+                    ty: to_abi_string(&typ),
+                    components: to_components(&typ),
+                }
+            })
+            .collect(),
+        state_mutability: match constructor.payable {
+            true => StateMutability::Payable,
+            false => StateMutability::NonPayable,
+        },
+    }
+}
+
 pub(crate) fn build_abi_with_details(
     abi: &JsonAbi,
     functions: &[AnalyzedFunction],
@@ -215,6 +290,16 @@ pub(crate) fn build_abi_with_details(
                                 }
                             };
                             obj.insert("signature".to_string(), json!(signature));
+
+                            // Add the ranked alternatives, if the selector was ambiguous
+                            if analyzed_func.candidate_signatures.len() > 1 {
+                                let candidates = analyzed_func
+                                    .candidate_signatures
+                                    .iter()
+                                    .map(|c| json!({ "signature": c.signature, "score": c.score }))
+                                    .collect::<Vec<_>>();
+                                obj.insert("candidates".to_string(), json!(candidates));
+                            }
                         }
                     }
                 }
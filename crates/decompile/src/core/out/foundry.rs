@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::Address;
+use alloy_json_abi::JsonAbi;
+use heimdall_common::utils::strings::encode_hex;
+
+use crate::{
+    core::out::abi::{build_interface, SOLIDITY_HEADER},
+    interfaces::AnalyzedFunction,
+};
+
+/// The foundry configuration of the project. Forked tests read the rpc url from `RPC_URL`.
+const FOUNDRY_TOML: &str = "[profile.default]
+src = \"src\"
+out = \"out\"
+libs = [\"lib\"]
+test = \"test\"
+
+# install forge-std with `forge install foundry-rs/forge-std`
+";
+
+/// The project's remappings.
+const REMAPPINGS: &str = "forge-std/=lib/forge-std/src/\n";
+
+/// The chain the generated test reads the target from.
+pub(crate) enum TestTarget {
+    /// The target is a deployed contract, which the test forks the chain to call, optionally at
+    /// the given block
+    Fork(Address, Option<u64>),
+    /// The target is raw bytecode, which the test deploys with `vm.etch`
+    Etch(Vec<u8>),
+}
+
+/// Builds a foundry project around the decompiled source, keyed by path relative to the project
+/// root. The project contains the source, an interface generated from the ABI, and a test which
+/// checks that the target dispatches each recovered selector.
+pub(crate) fn build_foundry_project(
+    source: &str,
+    abi: &JsonAbi,
+    functions: &[AnalyzedFunction],
+    target: TestTarget,
+) -> BTreeMap<String, String> {
+    let mut foundry_toml = FOUNDRY_TOML.to_string();
+    if matches!(target, TestTarget::Fork(..)) {
+        foundry_toml.push_str("\n[rpc_endpoints]\ntarget = \"${RPC_URL}\"\n");
+    }
+
+    BTreeMap::from([
+        ("foundry.toml".to_string(), foundry_toml),
+        ("remappings.txt".to_string(), REMAPPINGS.to_string()),
+        ("src/Contract.sol".to_string(), source.to_string()),
+        ("src/IDecompiledContract.sol".to_string(), build_interface(abi, "IDecompiledContract")),
+        ("test/Contract.t.sol".to_string(), build_test(functions, target)),
+    ])
+}
+
+/// Builds the project's test, which checks every recovered selector is dispatched by the target,
+/// and that the target's argument-less view functions can be called.
+fn build_test(functions: &[AnalyzedFunction], target: TestTarget) -> String {
+    let mut functions = functions.iter().filter(|f| !f.fallback).collect::<Vec<_>>();
+    functions.sort_by(|a, b| a.selector.cmp(&b.selector));
+    let views = functions
+        .iter()
+        .filter(|f| (f.pure || f.view) && f.arguments.is_empty())
+        .copied()
+        .collect::<Vec<_>>();
+
+    let (target, set_up) = match target {
+        TestTarget::Fork(address, block) => (
+            address.to_string(),
+            match block {
+                Some(block) => format!("vm.createSelectFork(vm.rpcUrl(\"target\"), {block});"),
+                None => "vm.createSelectFork(vm.rpcUrl(\"target\"));".to_string(),
+            },
+        ),
+        TestTarget::Etch(bytecode) => (
+            "address(uint160(uint256(keccak256(\"DecompiledContract\"))))".to_string(),
+            format!("vm.etch(TARGET, hex\"{}\");", encode_hex(&bytecode)),
+        ),
+    };
+
+    format!(
+        "{SOLIDITY_HEADER}
+import {{Test}} from \"forge-std/Test.sol\";
+
+/// @notice Checks that the target dispatches the selectors recovered by the decompiler.
+contract ContractTest is Test {{
+    address internal constant TARGET = {target};
+
+    function setUp() public {{
+        {set_up}
+    }}
+
+    /// @notice Each recovered selector is compared against by the target's dispatcher
+    function test_selectorDispatch() public view {{
+{}
+        for (uint256 i = 0; i < selectors.length; i++) {{
+            assertTrue(dispatches(selectors[i]), \"selector is not dispatched\");
+        }}
+    }}
+
+    /// @notice Each recovered argument-less view function can be called
+    function test_viewFunctions() public view {{
+{}
+        for (uint256 i = 0; i < selectors.length; i++) {{
+            (bool success,) = TARGET.staticcall(abi.encodePacked(selectors[i]));
+            assertTrue(success, \"view function reverted\");
+        }}
+    }}
+
+    /// @notice Whether the target's code pushes `selector` to compare it against the calldata
+    function dispatches(bytes4 selector) internal view returns (bool) {{
+        bytes memory code = TARGET.code;
+        for (uint256 i = 0; i + 4 < code.length; i++) {{
+            // PUSH4 <selector>
+            if (code[i] == 0x63 && bytes4(bytes.concat(code[i + 1], code[i + 2], code[i + 3], code[i + 4])) == selector) {{
+                return true;
+            }}
+        }}
+        return false;
+    }}
+}}
+",
+        selector_array(&functions),
+        selector_array(&views),
+    )
+}
+
+/// Declares a `selectors` array containing the selectors of the given functions.
+fn selector_array(functions: &[&AnalyzedFunction]) -> String {
+    let mut lines =
+        vec![format!("        bytes4[] memory selectors = new bytes4[]({});", functions.len())];
+    lines.extend(
+        functions
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("        selectors[{i}] = 0x{};", f.selector)),
+    );
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_foundry_project() {
+        let owner = AnalyzedFunction::new("8da5cb5b", false);
+        let mut transfer = AnalyzedFunction::new("a9059cbb", false);
+        transfer.pure = false;
+        transfer.view = false;
+        let abi: JsonAbi = serde_json::from_str(r#"[{"type":"function","name":"owner","inputs":[],"outputs":[{"name":"","type":"address"}],"stateMutability":"view"}]"#).expect("invalid abi");
+
+        let project = build_foundry_project(
+            "contract DecompiledContract {}",
+            &abi,
+            &[transfer, owner],
+            TestTarget::Fork(Address::ZERO, Some(1)),
+        );
+
+        assert_eq!(
+            project.keys().collect::<Vec<_>>(),
+            vec![
+                "foundry.toml",
+                "remappings.txt",
+                "src/Contract.sol",
+                "src/IDecompiledContract.sol",
+                "test/Contract.t.sol"
+            ]
+        );
+        assert!(project["foundry.toml"].contains("target = \"${RPC_URL}\""));
+        assert!(project["src/IDecompiledContract.sol"]
+            .contains("function owner() external view returns (address);"));
+
+        let test = &project["test/Contract.t.sol"];
+        assert!(test.contains("vm.createSelectFork(vm.rpcUrl(\"target\"), 1);"));
+        assert!(test.contains(
+            "        bytes4[] memory selectors = new bytes4[](2);
+        selectors[0] = 0x8da5cb5b;
+        selectors[1] = 0xa9059cbb;"
+        ));
+        assert!(test.contains(
+            "        bytes4[] memory selectors = new bytes4[](1);
+        selectors[0] = 0x8da5cb5b;"
+        ));
+    }
+
+    #[test]
+    fn test_build_foundry_project_etch() {
+        let project =
+            build_foundry_project("", &JsonAbi::new(), &[], TestTarget::Etch(vec![0x60, 0x00]));
+
+        assert!(!project["foundry.toml"].contains("rpc_endpoints"));
+        assert!(project["test/Contract.t.sol"].contains("vm.etch(TARGET, hex\"6000\");"));
+    }
+}
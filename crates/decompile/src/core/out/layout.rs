@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::U256;
+use fancy_regex::Regex;
+use hashbrown::{HashMap, HashSet};
+use heimdall_common::utils::strings::{base26_encode, find_balanced_encapsulator};
+use heimdall_vm::core::types::byte_size_to_type;
+use lazy_static::lazy_static;
+use tracing::debug;
+
+use crate::interfaces::{
+    AnalyzedFunction, StorageLayout, StorageLayoutEntry, StorageSlot, StorageType, StructMember,
+};
+
+lazy_static! {
+    static ref STORAGE_MAP_NAME_REGEX: Regex =
+        Regex::new(r"\bstorage_map_[a-z]+\b").expect("failed to compile regex");
+}
+
+/// The accesses observed for the state variable stored in a single slot.
+#[derive(Default)]
+struct SlotAccesses {
+    /// The location of the variable's values, if it is a mapping or dynamic array
+    hashed: Option<StorageSlot>,
+    /// The `(member, offset, size)` fields read or written within the variable's value slots
+    fields: HashSet<(usize, usize, usize)>,
+}
+
+/// Recovers the contract's storage layout from the storage accesses observed during analysis.
+/// Mapping values spanning multiple slots are recovered as structs, named `Struct_a`, `Struct_b`,
+/// and so on, with a `field_{n}` member for each slot.
+///
+/// Variables are named after the storage variables of the decompiled source, which are matched by
+/// `storage_names` (generated by the storage postprocessor, e.g. `storage[0x01]` => `store_a`).
+/// Where the layout recovers a more precise type than the postprocessor did, the type in
+/// `storage_variables` is updated to match.
+///
+/// Returns the layout, along with the slot each storage variable of the source is stored in.
+pub(crate) fn build_storage_layout(
+    functions: &[AnalyzedFunction],
+    storage_names: &HashMap<String, String>,
+    storage_variables: &mut HashMap<String, String>,
+) -> (StorageLayout, HashMap<String, U256>) {
+    // group the accesses by the slot of the variable they belong to
+    let mut slots: BTreeMap<U256, SlotAccesses> = BTreeMap::new();
+    for access in functions.iter().flat_map(|f| f.storage_accesses.iter()) {
+        let entry = slots.entry(access.slot.root()).or_default();
+        let field = (access.member, access.offset, access.size);
+        match (&access.slot, &entry.hashed) {
+            // a mapping's slot is never accessed directly, and an array's slot holds its length,
+            // so these accesses don't describe the variable's type
+            (StorageSlot::Value(_), Some(_)) => {}
+            (StorageSlot::Value(_), None) => {
+                entry.fields.insert(field);
+            }
+            (hashed, None) => {
+                entry.fields = HashSet::from([field]);
+                entry.hashed = Some(hashed.clone());
+            }
+            (hashed, Some(previous)) => {
+                if let Some(merged) = merge_slots(previous, hashed) {
+                    entry.fields.insert(field);
+                    entry.hashed = Some(merged);
+                }
+            }
+        }
+    }
+
+    // match the source's storage variables to slots. plain variables are named after their
+    // slot, while mappings are matched to the function that accesses them
+    let mut names: HashMap<U256, String> = storage_names
+        .iter()
+        .filter_map(|(loc, name)| {
+            let slot = loc.strip_prefix("storage[")?.strip_suffix(']')?;
+            let slot = match slot.strip_prefix("0x") {
+                Some(slot) => U256::from_str_radix(slot, 16),
+                None => U256::from_str_radix(slot, 10),
+            };
+            Some((slot.ok()?, name.to_string()))
+        })
+        .collect();
+    let accessed_maps = functions
+        .iter()
+        .map(|f| {
+            let hashed_slots = f
+                .storage_accesses
+                .iter()
+                .filter(|access| !matches!(access.slot, StorageSlot::Value(_)))
+                .map(|access| access.slot.root())
+                .collect::<HashSet<_>>();
+            let map_names = f
+                .logic
+                .iter()
+                .flat_map(|line| STORAGE_MAP_NAME_REGEX.find_iter(line).filter_map(|m| m.ok()))
+                .map(|m| m.as_str().to_string())
+                .collect::<HashSet<_>>();
+            (hashed_slots, map_names)
+        })
+        .collect::<Vec<_>>();
+
+    // the postprocessor can't tell mappings apart by their slot, so a name is only matched when a
+    // function accesses a single mapping by a single name
+    for (hashed_slots, map_names) in accessed_maps {
+        if let ([slot], [name]) = (
+            hashed_slots.into_iter().collect::<Vec<_>>().as_slice(),
+            map_names.into_iter().collect::<Vec<_>>().as_slice(),
+        ) {
+            if !names.contains_key(slot) && !names.values().any(|n| n == name) {
+                names.insert(*slot, name.to_string());
+            }
+        }
+    }
+
+    let mut layout = StorageLayout::default();
+    let mut storage_slots = HashMap::new();
+    let mut struct_count = 0;
+    for (slot, accesses) in slots {
+        let name = names.get(&slot);
+        let source_type = name.and_then(|name| storage_variables.get(name)).cloned();
+
+        // the postprocessor's type for full-slot values, e.g. `uint256` in
+        // `mapping(address => uint256)`
+        let source_value_type = source_type
+            .as_deref()
+            .map(|typ| typ.rsplit("=> ").next().unwrap_or(typ).trim_end_matches(')'))
+            .filter(|typ| !typ.starts_with("Struct_"))
+            .unwrap_or("bytes32")
+            .to_string();
+        let label = match name {
+            Some(name) => functions
+                .iter()
+                .find(|f| f.maybe_getter_for.as_ref() == Some(name))
+                .map(|f| {
+                    f.resolved_function
+                        .as_ref()
+                        .map(|x| x.name.clone())
+                        .unwrap_or_else(|| format!("unresolved_{}", f.selector))
+                })
+                .unwrap_or_else(|| name.to_string()),
+            None => format!("slot_{slot}"),
+        };
+
+        let mut members: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+        for (member, offset, size) in accesses.fields {
+            members.entry(member).or_default().push((offset, size));
+        }
+        for fields in members.values_mut() {
+            // a full-slot access of a packed slot doesn't describe any of its variables
+            if fields.iter().any(|(_, size)| *size < 32) {
+                fields.retain(|(_, size)| *size < 32);
+            }
+            fields.sort();
+            fields.dedup_by(|(offset, _), (previous, _)| offset == previous);
+        }
+        let field_type = |size: usize, source_type: &str| match size {
+            32 => StorageType::Elementary(source_type.to_string()),
+            size => StorageType::Elementary(byte_size_to_type(size).1[0].clone()),
+        };
+
+        match accesses.hashed {
+            Some(hashed) => {
+                let value = match members.iter().collect::<Vec<_>>().as_slice() {
+                    [(0, fields)] if fields.len() == 1 => {
+                        field_type(fields[0].1, &source_value_type)
+                    }
+                    [(0, _)] => field_type(32, &source_value_type),
+                    _ => {
+                        // values spanning multiple slots are structs. each slot is a member,
+                        // rather than each packed field, to match the decompiled source
+                        struct_count += 1;
+                        let last_member = members.keys().max().copied().unwrap_or(0);
+                        StorageType::Struct(
+                            format!("Struct_{}", base26_encode(struct_count)),
+                            (0..=last_member)
+                                .map(|member| StructMember {
+                                    label: format!("field_{member}"),
+                                    slot: member,
+                                    offset: 0,
+                                    typ: match members.get(&member).map(Vec::as_slice) {
+                                        Some([(0, size)]) => field_type(*size, "bytes32"),
+                                        _ => field_type(32, "bytes32"),
+                                    },
+                                })
+                                .collect(),
+                        )
+                    }
+                };
+                layout.entries.push(StorageLayoutEntry {
+                    label,
+                    slot,
+                    offset: 0,
+                    typ: to_storage_type(&hashed, value),
+                });
+            }
+            None => {
+                let fields = members.remove(&0).unwrap_or_default();
+                if let [(offset, size)] = fields.as_slice() {
+                    layout.entries.push(StorageLayoutEntry {
+                        label,
+                        slot,
+                        offset: *offset,
+                        typ: field_type(*size, &source_value_type),
+                    });
+                } else {
+                    layout.entries.extend(fields.into_iter().map(|(offset, size)| {
+                        StorageLayoutEntry {
+                            label: format!("{label}_{offset}"),
+                            slot,
+                            offset,
+                            typ: field_type(size, &source_value_type),
+                        }
+                    }));
+                    if let Some(name) = name {
+                        storage_slots.insert(name.to_string(), slot);
+                    }
+                    continue;
+                }
+            }
+        }
+
+        // prefer the recovered type over the postprocessor's when it is more precise
+        if let (Some(name), Some(entry)) = (name, layout.entries.last()) {
+            let typ = entry.typ.label();
+            if !entry.typ.structs().is_empty() ||
+                (source_type.as_deref().is_none_or(|source| source.contains("bytes32")) &&
+                    !typ.ends_with("bytes32"))
+            {
+                storage_variables.insert(name.to_string(), typ);
+            }
+            storage_slots.insert(name.to_string(), slot);
+        }
+    }
+
+    debug!("recovered {} storage variables", layout.entries.len());
+    (layout, storage_slots)
+}
+
+/// Accesses a struct-valued mapping's first member wherever it is accessed without a member, since
+/// the first member is stored at the value's own slot, e.g. `storage_map_a[arg0]` =>
+/// `storage_map_a[arg0].field_0`.
+pub(crate) fn qualify_struct_accesses(line: &str, name: &str) -> String {
+    let mut output = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find(&format!("{name}[")) {
+        let is_name = !rest[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+
+        // skip over the mapping's keys
+        let mut end = start + name.len();
+        while rest[end..].starts_with('[') {
+            match find_balanced_encapsulator(&rest[end..], ('[', ']')) {
+                Ok(range) => end += range.end + 1,
+                Err(_) => break,
+            }
+        }
+
+        output.push_str(&rest[..end]);
+        if is_name && !rest[end..].starts_with('.') {
+            output.push_str(".field_0");
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Merges two locations of the same variable, which may disagree on key types if only some
+/// accesses revealed them. Returns `None` if the locations don't share a shape.
+fn merge_slots(a: &StorageSlot, b: &StorageSlot) -> Option<StorageSlot> {
+    match (a, b) {
+        (StorageSlot::Value(a), StorageSlot::Value(b)) => {
+            (a == b).then_some(StorageSlot::Value(*a))
+        }
+        (
+            StorageSlot::Mapping { base: a, key_type: a_key },
+            StorageSlot::Mapping { base: b, key_type: b_key },
+        ) => Some(StorageSlot::Mapping {
+            base: Box::new(merge_slots(a, b)?),
+            key_type: if a_key == "bytes32" { b_key } else { a_key }.to_string(),
+        }),
+        (StorageSlot::Array { base: a }, StorageSlot::Array { base: b }) => {
+            Some(StorageSlot::Array { base: Box::new(merge_slots(a, b)?) })
+        }
+        _ => None,
+    }
+}
+
+/// Builds the type of a mapping or dynamic array whose values are of type `value`.
+fn to_storage_type(slot: &StorageSlot, value: StorageType) -> StorageType {
+    match slot {
+        StorageSlot::Value(_) => value,
+        StorageSlot::Mapping { base, key_type } => to_storage_type(
+            base,
+            StorageType::Mapping(
+                Box::new(StorageType::Elementary(key_type.to_string())),
+                Box::new(value),
+            ),
+        ),
+        StorageSlot::Array { base } => to_storage_type(base, StorageType::Array(Box::new(value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::StorageAccess;
+
+    fn access(slot: StorageSlot, offset: usize, size: usize) -> StorageAccess {
+        StorageAccess { slot, member: 0, offset, size }
+    }
+
+    #[test]
+    fn test_build_storage_layout() {
+        let balances = StorageSlot::Mapping {
+            base: Box::new(StorageSlot::Value(U256::from(1))),
+            key_type: "address".to_string(),
+        };
+
+        let mut transfer = AnalyzedFunction::new("a9059cbb", false);
+        transfer.logic.push("storage_map_b[var_a] = storage_map_b[var_a] - arg1;".to_string());
+        transfer.storage_accesses.insert(access(balances, 0, 32));
+        let mut owner = AnalyzedFunction::new("8da5cb5b", false);
+        owner.maybe_getter_for = Some("store_a".to_string());
+        owner.storage_accesses.insert(access(StorageSlot::Value(U256::ZERO), 0, 32));
+        owner.storage_accesses.insert(access(StorageSlot::Value(U256::ZERO), 0, 20));
+        owner.storage_accesses.insert(access(StorageSlot::Value(U256::ZERO), 20, 1));
+
+        let storage_names = HashMap::from([("storage[0x00]".to_string(), "store_a".to_string())]);
+        let mut storage_variables = HashMap::from([
+            ("store_a".to_string(), "bytes32".to_string()),
+            ("storage_map_b".to_string(), "mapping(bytes32 => uint256)".to_string()),
+        ]);
+        let (layout, storage_slots) =
+            build_storage_layout(&[transfer, owner], &storage_names, &mut storage_variables);
+
+        let entries = layout
+            .entries
+            .iter()
+            .map(|entry| {
+                (entry.label.as_str(), entry.slot.to::<u64>(), entry.offset, entry.typ.label())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                ("unresolved_8da5cb5b_0", 0, 0, "address".to_string()),
+                ("unresolved_8da5cb5b_20", 0, 20, "bool".to_string()),
+                ("storage_map_b", 1, 0, "mapping(address => uint256)".to_string()),
+            ]
+        );
+        assert_eq!(storage_variables["storage_map_b"], "mapping(address => uint256)");
+        assert_eq!(storage_slots["store_a"], U256::ZERO);
+        assert_eq!(storage_slots["storage_map_b"], U256::from(1));
+
+        let json = layout.to_solc_json();
+        assert_eq!(json["storage"][2]["slot"], "1");
+        assert_eq!(json["types"]["t_mapping(t_address,t_uint256)"]["encoding"], "mapping");
+        assert_eq!(json["types"]["t_bool"]["numberOfBytes"], "1");
+    }
+
+    #[test]
+    fn test_build_storage_layout_structs() {
+        let positions = StorageSlot::Mapping {
+            base: Box::new(StorageSlot::Value(U256::from(3))),
+            key_type: "uint256".to_string(),
+        };
+
+        let mut f = AnalyzedFunction::new("99fbab88", false);
+        f.logic.push("return storage_map_a[arg0].field_2;".to_string());
+        f.storage_accesses.insert(access(positions.clone(), 0, 20));
+        f.storage_accesses.insert(StorageAccess {
+            slot: positions,
+            member: 2,
+            offset: 0,
+            size: 32,
+        });
+
+        let mut storage_variables = HashMap::from([(
+            "storage_map_a".to_string(),
+            "mapping(bytes32 => uint256)".to_string(),
+        )]);
+        let (layout, _) = build_storage_layout(&[f], &HashMap::new(), &mut storage_variables);
+
+        assert_eq!(storage_variables["storage_map_a"], "mapping(uint256 => Struct_a)");
+        let structs = layout.structs();
+        let [StorageType::Struct(name, members)] = structs.as_slice() else {
+            panic!("expected a single struct, got {structs:?}");
+        };
+        assert_eq!(name, "Struct_a");
+        assert_eq!(
+            members.iter().map(|member| member.typ.label()).collect::<Vec<_>>(),
+            vec!["address", "bytes32", "bytes32"]
+        );
+
+        let json = layout.to_solc_json();
+        let definition = &json["types"]["t_struct(Struct_a)_storage"];
+        assert_eq!(definition["numberOfBytes"], "96");
+        assert_eq!(definition["members"][2]["slot"], "2");
+    }
+
+    #[test]
+    fn test_qualify_struct_accesses() {
+        assert_eq!(
+            qualify_struct_accesses(
+                "storage_map_a[var_a] = storage_map_a[var_a].field_1 + my_storage_map_a[var_b];",
+                "storage_map_a"
+            ),
+            "storage_map_a[var_a].field_0 = storage_map_a[var_a].field_1 + my_storage_map_a[var_b];"
+        );
+        assert_eq!(
+            qualify_struct_accesses("storage_map_a[keccak256(a[0])][b]", "storage_map_a"),
+            "storage_map_a[keccak256(a[0])][b].field_0"
+        );
+    }
+}
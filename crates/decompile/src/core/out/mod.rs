@@ -1,4 +1,7 @@
 pub(crate) mod abi;
+pub(crate) mod foundry;
+pub(crate) mod layout;
 pub(crate) mod source;
+pub(crate) mod vyper;
 
-pub(crate) use abi::{build_abi, build_abi_with_details};
+pub(crate) use abi::{build_abi, build_abi_with_details, build_interface};
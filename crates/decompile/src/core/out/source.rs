@@ -1,26 +1,41 @@
 use futures::future::join_all;
-use hashbrown::{HashMap, HashSet};
-use std::time::Instant;
+use hashbrown::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
+use web_time::Instant;
 
+use alloy::primitives::U256;
 use alloy_json_abi::StateMutability;
 
-use eyre::{OptionExt, Result};
-use heimdall_common::{
-    ether::signatures::{ResolvedError, ResolvedLog},
-    resources::openai::complete_chat,
-    utils::{hex::ToLowerHex, strings::encode_hex_reduced},
+use eyre::Result;
+use heimdall_common::ether::{
+    compiler::Compiler,
+    metadata::CompilerMetadata,
+    signatures::{ResolvedError, ResolvedLog},
+    types::to_type,
 };
 
 use tracing::debug;
 
 use crate::{
-    core::analyze::AnalyzerType,
-    interfaces::AnalyzedFunction,
-    utils::constants::{
-        DECOMPILED_SOURCE_HEADER_SOL, DECOMPILED_SOURCE_HEADER_YUL, LLM_POSTPROCESSING_PROMPT,
+    core::{
+        analyze::AnalyzerType, internal::InternalFunction, modifiers::Modifier, origins::Origin,
+        validate::format_value,
     },
+    interfaces::{AnalyzedFunction, ConstructorInfo, SourceMap, StorageLayout, StorageType},
+    utils::{
+        constants::{DECOMPILED_SOURCE_HEADER_SOL, DECOMPILED_SOURCE_HEADER_YUL},
+        selectors::{error_selector, event_selector},
+    },
+};
+
+// llm postprocessing isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::utils::constants::LLM_POSTPROCESSING_PROMPT, eyre::OptionExt,
+    heimdall_common::resources::openai::complete_chat,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
 async fn annotate_function(source: &str, openai_api_key: &str) -> Result<String> {
     let annotated =
         complete_chat(&LLM_POSTPROCESSING_PROMPT.replace("{source}", source), openai_api_key)
@@ -43,14 +58,26 @@ async fn annotate_function(source: &str, openai_api_key: &str) -> Result<String>
     Ok(annotated)
 }
 
+/// Functions can't be llm postprocessed on wasm, where network access isn't available.
+#[cfg(target_arch = "wasm32")]
+async fn annotate_function(_source: &str, _openai_api_key: &str) -> Result<String> {
+    Err(eyre::eyre!("llm postprocessing isn't available on wasm"))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn build_source(
     functions: &[AnalyzedFunction],
+    constructor: Option<(&ConstructorInfo, &AnalyzedFunction)>,
+    modifiers: &[Modifier],
+    internal_functions: &[InternalFunction],
     all_resolved_errors: &HashMap<String, ResolvedError>,
     all_resolved_logs: &HashMap<String, ResolvedLog>,
     storage_variables: &HashMap<String, String>,
+    storage_slots: &HashMap<String, U256>,
+    storage_layout: &StorageLayout,
     llm_postprocess: bool,
     openai_api_key: String,
-) -> Result<Option<String>> {
+) -> Result<Option<(String, SourceMap)>> {
     // we can get the AnalyzerType from the first function, since they are all the same
     let analyzer_type = functions.first().map(|f| f.analyzer_type).unwrap_or(AnalyzerType::Yul);
     if analyzer_type == AnalyzerType::Abi {
@@ -60,6 +87,7 @@ pub(crate) async fn build_source(
 
     debug!("constructing {} source representation", analyzer_type);
     let mut source = Vec::new();
+    let mut origins = Vec::new();
     let start_time = Instant::now();
 
     // write the header to the output file
@@ -70,23 +98,46 @@ pub(crate) async fn build_source(
         source.extend(get_constants(functions));
     }
 
-    // add storage variables
+    // add struct definitions and storage variables
     if analyzer_type == AnalyzerType::Solidity {
-        source.extend(get_storage_variables(storage_variables, functions));
+        source.extend(get_struct_definitions(storage_layout));
+        if let Some((constructor, _)) = constructor {
+            source.extend(get_immutables(constructor, functions));
+        }
+        source.extend(get_storage_variables(storage_variables, storage_slots, functions));
     }
 
-    // add event and error declarations
-    let resolved_event_error_map =
-        get_event_and_error_declarations(functions, all_resolved_errors, all_resolved_logs);
+    // add event and error declarations, including those of the constructor
+    let declaring_functions = functions
+        .iter()
+        .chain(constructor.map(|(_, f)| f))
+        .chain(internal_functions.iter().map(|f| &f.function))
+        .cloned()
+        .collect::<Vec<_>>();
+    let resolved_event_error_map = get_event_and_error_declarations(
+        &declaring_functions,
+        all_resolved_errors,
+        all_resolved_logs,
+    );
     if analyzer_type == AnalyzerType::Solidity {
         resolved_event_error_map.iter().for_each(|(_, (resolved_name, typ))| {
             source.push(format!("{typ} {resolved_name}"));
         });
 
+        // add the modifiers lifted from the functions' shared guards
+        source.extend(get_modifiers(modifiers));
+
+        // add the constructor, if it was recovered from the creation bytecode
+        if let Some((constructor, f)) = constructor {
+            source.extend(get_constructor(constructor, f));
+        }
+
         // add the fallback function, if it exists
         if let Some(fallback) = functions.iter().find(|f| f.fallback) {
             source.push(String::from("fallback() external payable {"));
+            origins.resize(source.len(), Vec::new());
             source.extend(fallback.logic.clone());
+            origins.extend(fallback.origins.clone());
             source.extend(vec![String::from("}"), String::from("")]);
         }
     }
@@ -103,17 +154,19 @@ pub(crate) async fn build_source(
             let f = f.clone(); // Ensure `Function` is cloneable, or adjust as needed.
             let openai_api_key = openai_api_key.clone();
 
-            // Spawn each function processing on a separate task.
-            tokio::task::spawn(async move {
+            // each function is postprocessed concurrently
+            async move {
                 let mut function_source = Vec::new();
 
                 // get the function header
                 function_source.extend(get_function_header(&f));
+                let mut function_origins = vec![Vec::new(); function_source.len()];
                 function_source.extend(f.logic.clone());
+                function_origins.extend(f.origins.clone());
                 function_source.push("}".to_string());
 
                 let imbalance = get_indentation_imbalance(&function_source);
-                function_source.extend(vec!["}".to_string(); imbalance as usize]);
+                function_source.extend(vec!["}".to_string(); imbalance.max(0) as usize]);
 
                 if llm_postprocess {
                     // postprocess the source code
@@ -143,28 +196,44 @@ pub(crate) async fn build_source(
                     if let Some(postprocessed_source) = postprocessed_source {
                         function_source =
                             postprocessed_source.split('\n').map(|x| x.to_string()).collect();
+                        function_origins.clear();
                     }
                 }
 
-                Ok::<Vec<String>, eyre::Report>(function_source)
-            })
+                function_origins.resize(function_source.len(), Vec::new());
+                Ok::<_, eyre::Report>((function_source, function_origins))
+            }
         })
         .collect();
 
-    // Await all tasks to complete
+    // Await all of the functions' sources
     let results = join_all(futures).await;
 
     // Combine all the results into one single vector
     for res in results {
-        let function_source = res??;
+        let (function_source, function_origins) = res?;
+        origins.resize(source.len(), Vec::new());
         source.extend(function_source);
+        origins.extend(function_origins);
+    }
+
+    // add the internal functions shared by the functions above
+    if analyzer_type == AnalyzerType::Solidity {
+        internal_functions.iter().for_each(|f| {
+            let (function_source, function_origins) = get_internal_function(f);
+            origins.resize(source.len(), Vec::new());
+            source.extend(function_source);
+            origins.extend(function_origins);
+        });
     }
 
     if analyzer_type == AnalyzerType::Yul {
         // add the fallback function, if it exists
         if let Some(fallback) = functions.iter().find(|f| f.fallback) {
             source.push("default {".to_string());
+            origins.resize(source.len(), Vec::new());
             source.extend(fallback.logic.clone());
+            origins.extend(fallback.origins.clone());
             source.push("}".to_string());
         } else {
             source.push("default { revert(0, 0) }".to_string());
@@ -173,10 +242,20 @@ pub(crate) async fn build_source(
 
     // add missing closing brackets
     let imbalance = get_indentation_imbalance(&source);
-    source.extend(vec!["}".to_string(); imbalance as usize]);
+    source.extend(vec!["}".to_string(); imbalance.max(0) as usize]);
 
-    // indent and combine source
+    // indent and combine source, accounting for lines which span several
     indent_source(&mut source);
+    origins.resize(source.len(), Vec::new());
+    let source_map = SourceMap {
+        lines: source
+            .iter()
+            .zip(origins)
+            .flat_map(|(line, origins)| {
+                std::iter::once(origins).chain(vec![Vec::new(); line.matches('\n').count()])
+            })
+            .collect(),
+    };
     let mut source = source.join("\n");
 
     // replace all custom event and error declarations with their resolved names
@@ -200,7 +279,7 @@ pub(crate) async fn build_source(
 
     debug!("constructing {} source took {:?}", analyzer_type, start_time.elapsed());
 
-    Ok(Some(source))
+    Ok(Some((source, source_map)))
 }
 
 /// Helper function which returns the header for the decompiled source code.
@@ -220,6 +299,45 @@ fn get_source_header(analyzer_type: &AnalyzerType) -> Vec<String> {
     }
 }
 
+/// Inserts the given lines below the decompiler version in the source's header, commented the same
+/// way as the header.
+pub(crate) fn annotate_header(source: &str, annotation: Vec<String>) -> String {
+    let mut lines = source.split('\n').map(|line| line.to_string()).collect::<Vec<_>>();
+    let Some((i, prefix)) = lines.iter().enumerate().find_map(|(i, line)| {
+        line.find("@custom:version").map(|position| (i, line[..position].to_string()))
+    }) else {
+        return source.to_string();
+    };
+
+    lines.splice(i + 1..i + 1, annotation.into_iter().map(|line| format!("{prefix}{line}")));
+    lines.join("\n")
+}
+
+/// Notes the compiler which compiled the target in the decompiled source's header, along with the
+/// hash of its metadata file, which fingerprints the compiler's settings.
+pub(crate) fn annotate_compiler(
+    source: &str,
+    compiler: &Compiler,
+    version: &str,
+    metadata: Option<&CompilerMetadata>,
+) -> String {
+    let mut annotation = Vec::new();
+    match (compiler, version) {
+        (Compiler::Unknown, _) => {}
+        (compiler, "unknown") => annotation.push(format!("@custom:compiler  {compiler}")),
+        (compiler, version) => annotation.push(format!("@custom:compiler  {compiler} {version}")),
+    }
+    if let Some(metadata) = metadata {
+        if let Some(hash) = &metadata.metadata_hash {
+            annotation.push(format!("@custom:metadata  {hash}"));
+        }
+        if metadata.experimental {
+            annotation.push("                    compiled with experimental features".to_string());
+        }
+    }
+    annotate_header(source, annotation)
+}
+
 /// Helper function which will get the function header/signature for a given [`AnalyzedFunction`].
 fn get_function_header(f: &AnalyzedFunction) -> Vec<String> {
     // determine the state mutability of the function
@@ -239,6 +357,7 @@ fn get_function_header(f: &AnalyzedFunction) -> Vec<String> {
     if let Some(state_mutability) = state_mutability.as_str() {
         function_modifiers.push(state_mutability.to_owned());
     }
+    function_modifiers.extend(f.modifiers.iter().cloned());
     if let Some(returns) = f.returns.as_ref() {
         function_modifiers.push(format!("returns ({returns})"));
     }
@@ -317,6 +436,131 @@ fn get_function_header(f: &AnalyzedFunction) -> Vec<String> {
     }
 }
 
+/// Helper function which will get the constructor for the given [`ConstructorInfo`], whose logic is
+/// held by the given [`AnalyzedFunction`].
+fn get_constructor(constructor: &ConstructorInfo, f: &AnalyzedFunction) -> Vec<String> {
+    let arguments = constructor
+        .arguments
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| format!("{} arg{i}", with_data_location(&arg.typ)))
+        .collect::<Vec<_>>();
+
+    let mut output = vec![String::new()];
+    output.extend(f.notices.iter().map(|notice| format!("/// @notice             {notice}")));
+    output.extend(constructor.arguments.iter().enumerate().filter_map(|(i, arg)| {
+        arg.value
+            .as_ref()
+            .map(|value| format!("/// @param              arg{i} deployed with {value}"))
+    }));
+    output.push(format!(
+        "constructor({}){} {{",
+        arguments.join(", "),
+        if constructor.payable { " payable" } else { "" }
+    ));
+    output.extend(f.logic.clone());
+    output.push("}".to_string());
+
+    let imbalance = get_indentation_imbalance(&output);
+    output.extend(vec!["}".to_string(); imbalance.max(0) as usize]);
+    output
+}
+
+/// Helper function which will write an internal function shared by several call sites, along with
+/// the origins of its lines.
+fn get_internal_function(f: &InternalFunction) -> (Vec<String>, Vec<Vec<Origin>>) {
+    let arguments = (0..f.arguments)
+        .map(|i| {
+            // masks which clear an argument entirely don't describe its type
+            let typ = f
+                .function
+                .arguments
+                .get(&i)
+                .filter(|arg| arg.mask_size > 0)
+                .and_then(|arg| arg.potential_types().first().cloned());
+            format!("{} arg{i}", typ.unwrap_or_else(|| "uint256".to_string()))
+        })
+        .collect::<Vec<_>>();
+    let state_mutability = match (f.function.pure, f.function.view) {
+        (true, _) => " pure",
+        (false, true) => " view",
+        (false, false) => "",
+    };
+    let returns = match f.returns {
+        0 => String::new(),
+        n => format!(" returns ({})", vec!["uint256"; n].join(", ")),
+    };
+
+    let mut output = vec![String::new()];
+    output.extend(
+        f.function.notices.iter().map(|notice| format!("/// @notice             {notice}")),
+    );
+    output.push(format!(
+        "function {}({}) internal{state_mutability}{returns} {{",
+        f.name,
+        arguments.join(", ")
+    ));
+    let mut origins = vec![Vec::new(); output.len()];
+    output.extend(f.function.logic.clone());
+    origins.extend(f.function.origins.clone());
+    output.push("}".to_string());
+
+    let imbalance = get_indentation_imbalance(&output);
+    output.extend(vec!["}".to_string(); imbalance.max(0) as usize]);
+    origins.resize(output.len(), Vec::new());
+    (output, origins)
+}
+
+/// Helper function which will write the modifiers lifted from the functions' shared guards.
+fn get_modifiers(modifiers: &[Modifier]) -> Vec<String> {
+    let mut output = Vec::new();
+    for modifier in modifiers {
+        output.push(String::new());
+        output.push(format!("modifier {}() {{", modifier.name));
+        output.extend(modifier.before.iter().cloned());
+        output.push("_;".to_string());
+        output.extend(modifier.after.iter().cloned());
+        output.push("}".to_string());
+    }
+    output
+}
+
+/// Helper function which will write the declarations of the immutables assigned by the
+/// constructor, along with their deployed values. Immutables returned by a getter are public.
+fn get_immutables(constructor: &ConstructorInfo, functions: &[AnalyzedFunction]) -> Vec<String> {
+    constructor
+        .immutables
+        .iter()
+        .map(|immutable| {
+            let value_comment = immutable
+                .value
+                .and_then(|value| to_type(&immutable.typ).abi_decode(value.as_slice()).ok())
+                .map(|value| format!(" // deployed with {}", format_value(value)))
+                .unwrap_or_default();
+
+            match functions.iter().find(|f| f.maybe_getter_for.as_ref() == Some(&immutable.name)) {
+                Some(f) => format!(
+                    "{} public immutable {};{value_comment}",
+                    immutable.typ,
+                    f.resolved_function
+                        .as_ref()
+                        .map(|x| x.name.clone())
+                        .unwrap_or_else(|| format!("unresolved_{}", f.selector)),
+                ),
+                None => format!("{} immutable {};{value_comment}", immutable.typ, immutable.name),
+            }
+        })
+        .collect()
+}
+
+/// Adds the `memory` data location to dynamic types, which constructor arguments require.
+fn with_data_location(typ: &str) -> String {
+    match typ == "string" || typ == "bytes" || typ.ends_with(']') || typ.starts_with('(') {
+        true => format!("{typ} memory"),
+        false => typ.to_string(),
+    }
+}
+
 /// Helper function which will write constant variables to the source code.
 fn get_constants(functions: &[AnalyzedFunction]) -> Vec<String> {
     let mut output: Vec<String> = functions
@@ -343,15 +587,38 @@ fn get_constants(functions: &[AnalyzedFunction]) -> Vec<String> {
     output
 }
 
+/// Helper function which will write the definitions of the structs stored in storage.
+fn get_struct_definitions(storage_layout: &StorageLayout) -> Vec<String> {
+    let mut output = Vec::new();
+    for typ in storage_layout.structs() {
+        if let StorageType::Struct(name, members) = typ {
+            output.push(format!("struct {name} {{"));
+            output.extend(
+                members.iter().map(|member| format!("{} {};", member.typ.label(), member.label)),
+            );
+            output.extend(vec!["}".to_string(), "".to_string()]);
+        }
+    }
+    output
+}
+
 /// Helper function which will write the storage variable declarations for the decompiled source
-/// code.
+/// code, ordered by the slot they're stored in.
 fn get_storage_variables(
     storage_variables: &HashMap<String, String>,
+    storage_slots: &HashMap<String, U256>,
     functions: &[AnalyzedFunction],
 ) -> Vec<String> {
+    let mut storage_variables = storage_variables.iter().collect::<Vec<_>>();
+    storage_variables
+        .sort_by_key(|(name, _)| (storage_slots.get(*name).unwrap_or(&U256::MAX), *name));
+
     let mut output: Vec<String> = storage_variables
-        .iter()
+        .into_iter()
         .map(|(name, typ)| {
+            let slot_comment =
+                storage_slots.get(name).map(|slot| format!(" // slot {slot}")).unwrap_or_default();
+
             if let Some(f) = functions.iter().find(|f| f.maybe_getter_for.as_ref() == Some(name)) {
                 let name = f
                     .resolved_function
@@ -361,13 +628,13 @@ fn get_storage_variables(
 
                 // TODO: for public getters, we can use `eth_getStorageAt` to get the value
                 return format!(
-                    "{} public {};",
+                    "{} public {};{slot_comment}",
                     f.returns.as_ref().unwrap_or(typ).replacen("memory", "", 1).trim(),
                     name,
                 );
             }
 
-            format!("{typ} {name};")
+            format!("{typ} {name};{slot_comment}")
         })
         .collect();
     if !output.is_empty() {
@@ -381,71 +648,58 @@ fn get_event_and_error_declarations(
     functions: &[AnalyzedFunction],
     all_resolved_errors: &HashMap<String, ResolvedError>,
     all_resolved_logs: &HashMap<String, ResolvedLog>,
-) -> HashMap<String, (String, String)> {
-    let mut output = HashMap::new();
+) -> BTreeMap<String, (String, String)> {
+    let mut output = BTreeMap::new();
 
-    // get all events and errors
-    let all_events = functions.iter().flat_map(|f| f.events.clone()).collect::<HashSet<_>>();
-    let all_errors = functions.iter().flat_map(|f| f.errors.clone()).collect::<HashSet<_>>();
+    // get all events and errors, in the order of their selectors, since selectors which share
+    // their first bytes are declared under the same name
+    let all_events = functions.iter().flat_map(|f| f.events.clone()).collect::<BTreeSet<_>>();
+    let all_errors = functions.iter().flat_map(|f| f.errors.clone()).collect::<BTreeSet<_>>();
 
     // add event declarations
-    all_events.iter().for_each(|event_selector| {
+    all_events.iter().for_each(|selector| {
         // determine the name of the event
-        let (name, inputs) = match all_resolved_logs
-            .get(&encode_hex_reduced(*event_selector).replacen("0x", "", 1))
-        {
-            Some(event) => {
-                (event.name.clone(), event.inputs().iter().map(|i| i.to_string()).collect())
-            }
-            None => (
-                format!(
-                    "Event_{}",
-                    event_selector
-                        .to_lower_hex()
-                        .replacen("0x", "", 1)
-                        .get(0..8)
-                        .unwrap_or("00000000")
-                ),
-                vec![],
-            ),
-        };
+        let unresolved_name = format!("Event_{}", &event_selector(selector)[0..8]);
+        let resolved = all_resolved_logs.get(&event_selector(selector));
+        let name =
+            resolved.map(|event| event.name.clone()).unwrap_or_else(|| unresolved_name.clone());
+        let inputs = functions
+            .iter()
+            .find_map(|f| f.event_parameters.get(selector))
+            .cloned()
+            .unwrap_or_default()
+            .inputs(resolved)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (typ, indexed))| {
+                format!("{typ}{} arg{i}", if indexed { " indexed" } else { "" })
+            })
+            .collect::<Vec<_>>();
 
-        let unresolved_name = format!(
-            "Event_{}",
-            event_selector.to_lower_hex().replacen("0x", "", 1).get(0..8).unwrap_or("00000000")
-        );
         output.insert(
             unresolved_name,
             (format!("{name}({});", inputs.join(", ")), "event".to_string()),
         );
     });
 
-    // add error declarations
-    all_errors.iter().for_each(|error_selector| {
+    // add error declarations. unresolved errors are declared with the arguments they're raised
+    // with
+    all_errors.iter().for_each(|selector| {
         // determine the name of the error
-        let (name, inputs) = match all_resolved_errors
-            .get(&encode_hex_reduced(*error_selector).replacen("0x", "", 1))
-        {
+        let unresolved_name = format!("CustomError_{}", error_selector(selector));
+        let (name, inputs) = match all_resolved_errors.get(&error_selector(selector)) {
             Some(error) => {
                 (error.name.clone(), error.inputs().iter().map(|i| i.to_string()).collect())
             }
             None => (
-                format!(
-                    "CustomError_{}",
-                    error_selector
-                        .to_lower_hex()
-                        .replacen("0x", "", 1)
-                        .get(0..8)
-                        .unwrap_or("00000000")
-                ),
-                vec![],
+                unresolved_name.clone(),
+                functions
+                    .iter()
+                    .find_map(|f| f.error_arguments.get(selector).cloned())
+                    .unwrap_or_default(),
             ),
         };
 
-        let unresolved_name = format!(
-            "CustomError_{}",
-            error_selector.to_lower_hex().replacen("0x", "", 1).get(0..8).unwrap_or("00000000")
-        );
         output.insert(
             unresolved_name,
             (format!("{name}({});", inputs.join(", ")), "error".to_string()),
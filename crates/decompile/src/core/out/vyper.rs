@@ -0,0 +1,547 @@
+use hashbrown::HashMap;
+use std::collections::BTreeSet;
+use web_time::Instant;
+
+use eyre::Result;
+use fancy_regex::Captures;
+use heimdall_common::ether::signatures::{ResolvedError, ResolvedLog};
+use tracing::debug;
+
+use crate::{
+    interfaces::{AnalyzedFunction, StorageLayout, StorageType},
+    utils::{
+        constants::{
+            BOOLEAN_LITERAL_REGEX, DECOMPILED_SOURCE_HEADER_VY, NEGATION_REGEX,
+            VARIABLE_DECLARATION_REGEX,
+        },
+        selectors::{error_selector, event_selector},
+    },
+};
+
+/// The maximum length assigned to dynamic vyper types, which must be bounded.
+const DYNAMIC_TYPE_BOUND: usize = 1024;
+
+/// Build a vyper representation of the given [`AnalyzedFunction`]s, which were analyzed with the
+/// solidity heuristics. Functions are emitted as `@external` functions with their mutability
+/// decorators, storage variables as vyper declarations, and the solidity logic of each function
+/// is translated into python-like statements.
+pub(crate) fn build_vyper_source(
+    functions: &[AnalyzedFunction],
+    all_resolved_errors: &HashMap<String, ResolvedError>,
+    all_resolved_logs: &HashMap<String, ResolvedLog>,
+    storage_variables: &HashMap<String, String>,
+    storage_layout: &StorageLayout,
+    compiler_version: &str,
+) -> Result<Option<String>> {
+    debug!("constructing vyper source representation");
+    let start_time = Instant::now();
+    let mut source = Vec::new();
+
+    // write the header and version pragma
+    if compiler_version.split('.').count() == 3 &&
+        compiler_version.split('.').all(|part| part.parse::<u8>().is_ok())
+    {
+        source.push(format!("# @version ^{compiler_version}"));
+        source.push(String::new());
+    }
+    source.extend(
+        DECOMPILED_SOURCE_HEADER_VY
+            .replace("{}", env!("CARGO_PKG_VERSION"))
+            .split('\n')
+            .map(|x| x.to_string()),
+    );
+
+    // storage variables are accessed through `self`, and renamed after their public getters
+    let storage_names = get_storage_names(storage_variables, functions);
+
+    source.extend(get_struct_declarations(storage_layout));
+    source.extend(get_event_declarations(functions, all_resolved_logs));
+    source.extend(get_constants(functions));
+    source.extend(get_storage_variables(storage_variables, functions, &storage_names));
+
+    // add functions
+    for f in functions.iter().filter(|f| !f.fallback && f.maybe_getter_for.is_none()) {
+        if f.is_constant() {
+            continue;
+        }
+
+        source.extend(get_function_header(f));
+        source.extend(translate_logic(&f.logic, &storage_names));
+    }
+
+    // add the fallback function, if it exists
+    if let Some(fallback) = functions.iter().find(|f| f.fallback) {
+        source.extend(vec![
+            String::new(),
+            "@external".to_string(),
+            "@payable".to_string(),
+            "def __default__():".to_string(),
+        ]);
+        source.extend(translate_logic(&fallback.logic, &storage_names));
+    }
+
+    let mut source = source.join("\n");
+
+    // replace all custom event and error placeholders with their resolved names
+    let all_events = functions.iter().flat_map(|f| f.events.iter()).collect::<BTreeSet<_>>();
+    for selector in all_events {
+        if let Some(event) = all_resolved_logs.get(&event_selector(selector)) {
+            source =
+                source.replace(&format!("Event_{}", &event_selector(selector)[0..8]), &event.name);
+        }
+    }
+    let all_errors = functions.iter().flat_map(|f| f.errors.iter()).collect::<BTreeSet<_>>();
+    for selector in all_errors {
+        if let Some(error) = all_resolved_errors.get(&error_selector(selector)) {
+            source =
+                source.replace(&format!("CustomError_{}", error_selector(selector)), &error.name);
+        }
+    }
+
+    debug!("constructing vyper source took {:?}", start_time.elapsed());
+
+    Ok(Some(format!("{}\n", source.trim_end())))
+}
+
+/// Convert a solidity type into its vyper equivalent, e.g. `mapping(address => uint256)` into
+/// `HashMap[address, uint256]`. Dynamic types are bounded by [`DYNAMIC_TYPE_BOUND`].
+fn to_vyper_type(typ: &str) -> String {
+    let typ = typ.replace(" memory", "").replace(" calldata", "").replace(" storage", "");
+    let typ = typ.trim();
+
+    if let Some(inner) = typ.strip_prefix("mapping(").and_then(|t| t.strip_suffix(')')) {
+        if let Some((key, value)) = inner.split_once(" => ") {
+            return format!("HashMap[{}, {}]", to_vyper_type(key), to_vyper_type(value));
+        }
+    }
+    if let Some(inner) = typ.strip_suffix("[]") {
+        return format!("DynArray[{}, {DYNAMIC_TYPE_BOUND}]", to_vyper_type(inner));
+    }
+
+    match typ {
+        "string" => format!("String[{DYNAMIC_TYPE_BOUND}]"),
+        "bytes" => format!("Bytes[{DYNAMIC_TYPE_BOUND}]"),
+        typ => typ.to_string(),
+    }
+}
+
+/// Get the name each storage variable is declared with, which is the name of its public getter,
+/// if it has one.
+fn get_storage_names(
+    storage_variables: &HashMap<String, String>,
+    functions: &[AnalyzedFunction],
+) -> Vec<(String, String)> {
+    let mut names = storage_variables
+        .keys()
+        .map(|name| {
+            let getter = functions.iter().find(|f| f.maybe_getter_for.as_ref() == Some(name));
+            let declared_name = match getter {
+                Some(f) => f
+                    .resolved_function
+                    .as_ref()
+                    .map(|x| x.name.clone())
+                    .unwrap_or_else(|| format!("unresolved_{}", f.selector)),
+                None => name.clone(),
+            };
+            (name.clone(), declared_name)
+        })
+        .collect::<Vec<_>>();
+
+    // replace longer names first, so that `store_ab` isn't replaced as `store_a`
+    names.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+    names
+}
+
+/// Get the vyper struct declarations for the structs stored in storage.
+fn get_struct_declarations(storage_layout: &StorageLayout) -> Vec<String> {
+    let mut output = Vec::new();
+    for typ in storage_layout.structs() {
+        if let StorageType::Struct(name, members) = typ {
+            output.push(format!("struct {name}:"));
+            output.extend(members.iter().map(|member| {
+                format!("    {}: {}", member.label, to_vyper_type(&member.typ.label()))
+            }));
+            output.push(String::new());
+        }
+    }
+    output
+}
+
+/// Get the vyper event declarations for all events emitted by the given functions.
+fn get_event_declarations(
+    functions: &[AnalyzedFunction],
+    all_resolved_logs: &HashMap<String, ResolvedLog>,
+) -> Vec<String> {
+    let mut all_events = functions.iter().flat_map(|f| f.events.iter()).collect::<Vec<_>>();
+    all_events.sort();
+    all_events.dedup();
+
+    let mut output = Vec::new();
+    for selector in all_events {
+        let resolved = all_resolved_logs.get(&event_selector(selector));
+        match resolved {
+            Some(event) => output.push(format!("event {}:", event.name)),
+            None => output.push(format!("event Event_{}:", &event_selector(selector)[0..8])),
+        }
+
+        let inputs = functions
+            .iter()
+            .find_map(|f| f.event_parameters.get(selector))
+            .cloned()
+            .unwrap_or_default()
+            .inputs(resolved);
+        output.extend(inputs.iter().enumerate().map(|(i, (input, indexed))| {
+            let typ = to_vyper_type(&input.to_string());
+            match indexed {
+                true => format!("    arg{i}: indexed({typ})"),
+                false => format!("    arg{i}: {typ}"),
+            }
+        }));
+        if inputs.is_empty() {
+            output.push("    pass".to_string());
+        }
+        output.push(String::new());
+    }
+    output
+}
+
+/// Get the vyper constant declarations for the given functions.
+fn get_constants(functions: &[AnalyzedFunction]) -> Vec<String> {
+    let mut output = functions
+        .iter()
+        .filter(|f| f.is_constant() && !f.fallback)
+        .map(|f| {
+            format!(
+                "{}: public(constant({})) = {}",
+                f.resolved_function
+                    .as_ref()
+                    .map(|x| x.name.clone())
+                    .unwrap_or_else(|| format!("unresolved_{}", f.selector)),
+                to_vyper_type(f.returns.as_deref().unwrap_or("bytes")),
+                f.constant_value.as_deref().unwrap_or("0x")
+            )
+        })
+        .collect::<Vec<_>>();
+    output.sort();
+    if !output.is_empty() {
+        output.push(String::new());
+    }
+    output
+}
+
+/// Get the vyper storage variable declarations. Variables with a public getter are declared
+/// `public`, and transient variables are declared `transient`.
+fn get_storage_variables(
+    storage_variables: &HashMap<String, String>,
+    functions: &[AnalyzedFunction],
+    storage_names: &[(String, String)],
+) -> Vec<String> {
+    let mut variables = storage_variables.iter().collect::<Vec<_>>();
+    variables.sort();
+
+    let mut output = variables
+        .into_iter()
+        .map(|(name, typ)| {
+            let declared_name = storage_names
+                .iter()
+                .find(|(storage_name, _)| storage_name == name)
+                .map(|(_, declared_name)| declared_name.as_str())
+                .unwrap_or(name);
+            let getter = functions.iter().find(|f| f.maybe_getter_for.as_ref() == Some(name));
+            let typ = to_vyper_type(getter.and_then(|f| f.returns.as_deref()).unwrap_or(typ));
+            let typ = match name.starts_with("tstore_") || name.starts_with("transient_map_") {
+                true => format!("transient({typ})"),
+                false => typ,
+            };
+
+            match getter {
+                Some(_) => format!("{declared_name}: public({typ})"),
+                None => format!("{declared_name}: {typ}"),
+            }
+        })
+        .collect::<Vec<_>>();
+    if !output.is_empty() {
+        output.push(String::new());
+    }
+    output
+}
+
+/// Get the decorators and definition of the given [`AnalyzedFunction`].
+fn get_function_header(f: &AnalyzedFunction) -> Vec<String> {
+    let function_name = match f.resolved_function {
+        Some(ref sig) => sig.name.clone(),
+        None => format!("Unresolved_{}", f.selector),
+    };
+    let arguments = match f.resolved_function {
+        Some(ref sig) => sig
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| format!("arg{i}: {}", to_vyper_type(&arg.to_string())))
+            .collect::<Vec<_>>(),
+        None => f
+            .sorted_arguments()
+            .iter()
+            .enumerate()
+            .map(|(i, (_, arg))| {
+                format!(
+                    "arg{i}: {}",
+                    to_vyper_type(
+                        &arg.potential_types()
+                            .first()
+                            .cloned()
+                            .unwrap_or_else(|| "bytes32".to_string())
+                    )
+                )
+            })
+            .collect::<Vec<_>>(),
+    };
+    let returns = f
+        .returns
+        .as_ref()
+        .map(|returns| match returns.split(", ").map(to_vyper_type).collect::<Vec<_>>() {
+            types if types.len() > 1 => format!(" -> ({})", types.join(", ")),
+            types => format!(" -> {}", types.join(", ")),
+        })
+        .unwrap_or_default();
+
+    let mut output = vec![String::new(), format!("# @custom:selector    0x{}", f.selector)];
+    output.extend(f.notices.iter().map(|notice| format!("# @notice             {notice}")));
+    output.push("@external".to_string());
+    if f.pure {
+        output.push("@pure".to_string());
+    } else if f.view {
+        output.push("@view".to_string());
+    } else if f.payable {
+        output.push("@payable".to_string());
+    }
+    output.push(format!("def {function_name}({}){returns}:", arguments.join(", ")));
+    output
+}
+
+/// Split the arguments of a call at top-level commas, ignoring commas within parentheses,
+/// brackets and strings.
+fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut output = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    for c in arguments.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' | '[' if !in_string => depth += 1,
+            ')' | ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                output.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        output.push(current.trim().to_string());
+    }
+    output
+}
+
+/// Translate a solidity expression into a vyper expression.
+fn translate_expression(expression: &str, storage_names: &[(String, String)]) -> String {
+    let mut expression = expression
+        .replace(" && ", " and ")
+        .replace(" || ", " or ")
+        .replace("msg.data.length", "len(msg.data)")
+        .replace("address(this)", "self");
+    expression = BOOLEAN_LITERAL_REGEX
+        .replace_all(&expression, |captures: &Captures<'_>| match &captures[1] {
+            "true" => "True",
+            _ => "False",
+        })
+        .to_string();
+    expression = NEGATION_REGEX.replace_all(&expression, "not ").to_string();
+
+    // storage variables are accessed through `self`
+    let mut replacements = Vec::new();
+    for (i, (name, declared_name)) in storage_names.iter().enumerate() {
+        let placeholder = format!("\u{0}{i}\u{0}");
+        if expression.contains(name.as_str()) {
+            expression = expression.replace(name.as_str(), &placeholder);
+            replacements.push((placeholder, format!("self.{declared_name}")));
+        }
+    }
+    for (placeholder, replacement) in replacements {
+        expression = expression.replace(&placeholder, &replacement);
+    }
+
+    expression
+}
+
+/// Translate a single solidity statement into a vyper statement.
+fn translate_statement(statement: &str, storage_names: &[(String, String)]) -> String {
+    let statement = statement.trim().trim_end_matches(';');
+    if let Some(comment) = statement.strip_prefix("//") {
+        return format!("#{comment}");
+    }
+
+    // require(condition[, reason]) => assert condition[, reason]
+    if let Some(arguments) = statement.strip_prefix("require(").and_then(|s| s.strip_suffix(')')) {
+        let arguments = split_arguments(arguments);
+        let condition = translate_expression(
+            arguments.first().map(String::as_str).unwrap_or(""),
+            storage_names,
+        );
+        return match arguments.get(1) {
+            Some(reason) if reason.starts_with('"') => format!("assert {condition}, {reason}"),
+            Some(reason) => format!("assert {condition}  # {reason}"),
+            None => format!("assert {condition}"),
+        };
+    }
+
+    // revert(reason) / revert CustomError() => raise reason
+    if let Some(reason) = statement.strip_prefix("revert") {
+        let reason = reason.trim();
+        let reason = reason.strip_prefix('(').and_then(|r| r.strip_suffix(')')).unwrap_or(reason);
+        return match reason {
+            "" => "raise".to_string(),
+            reason if reason.starts_with('"') => format!("raise {reason}"),
+            reason => format!("raise  # {reason}"),
+        };
+    }
+
+    // emit Event(..) => log Event(..)
+    if let Some(event) = statement.strip_prefix("emit ") {
+        return format!("log {}", translate_expression(event, storage_names));
+    }
+
+    // (bool success, bytes memory ret0) = call(..) => success, ret0 = call(..)
+    if let Some((lhs, rhs)) = statement.split_once(" = ") {
+        if let Some(names) = lhs.strip_prefix('(').and_then(|l| l.strip_suffix(')')) {
+            let names = names
+                .split(',')
+                .filter_map(|declaration| declaration.split_whitespace().last())
+                .collect::<Vec<_>>();
+            return format!("{} = {}", names.join(", "), translate_expression(rhs, storage_names));
+        }
+    }
+
+    // uint256 var_a = 1 => var_a: uint256 = 1
+    if let Ok(Some(captures)) = VARIABLE_DECLARATION_REGEX.captures(statement) {
+        if captures[1] != *"return" {
+            return format!(
+                "{}: {} = {}",
+                &captures[2],
+                to_vyper_type(&captures[1]),
+                translate_expression(&captures[3], storage_names)
+            );
+        }
+    }
+
+    translate_expression(statement, storage_names)
+}
+
+/// Translate the solidity logic of a function into indented vyper statements, where blocks are
+/// delimited by indentation rather than braces.
+fn translate_logic(logic: &[String], storage_names: &[(String, String)]) -> Vec<String> {
+    let mut output = Vec::new();
+    let mut depth = 1;
+    let mut block_is_empty = true;
+
+    let indent = |depth: usize, line: String| format!("{}{line}", "    ".repeat(depth));
+    for line in logic.iter().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        // close the current block, adding a `pass` if it has no statements
+        if line.starts_with('}') {
+            if block_is_empty {
+                output.push(indent(depth, "pass".to_string()));
+            }
+            depth = (depth - 1).max(1);
+            block_is_empty = false;
+        }
+
+        let line = line.trim_start_matches('}').trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // open a new block
+        if let Some(header) = line.strip_suffix('{') {
+            let header = header.trim();
+            let header = match header {
+                "else" => "else".to_string(),
+                header if header.starts_with("else if (") => format!(
+                    "elif {}",
+                    translate_expression(
+                        header["else if (".len()..].trim_end_matches(')'),
+                        storage_names
+                    )
+                ),
+                header if header.starts_with("if (") => format!(
+                    "if {}",
+                    translate_expression(
+                        header["if (".len()..].trim_end_matches(')'),
+                        storage_names
+                    )
+                ),
+                header => translate_expression(header, storage_names),
+            };
+            output.push(indent(depth, format!("{header}:")));
+            depth += 1;
+            block_is_empty = true;
+            continue;
+        }
+
+        output.push(indent(depth, translate_statement(line, storage_names)));
+        block_is_empty = false;
+    }
+
+    if block_is_empty {
+        output.push(indent(depth, "pass".to_string()));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_vyper_type() {
+        assert_eq!(to_vyper_type("uint256"), "uint256");
+        assert_eq!(to_vyper_type("string memory"), "String[1024]");
+        assert_eq!(to_vyper_type("address[]"), "DynArray[address, 1024]");
+        assert_eq!(
+            to_vyper_type("mapping(address => mapping(address => uint256))"),
+            "HashMap[address, HashMap[address, uint256]]"
+        );
+    }
+
+    #[test]
+    fn test_translate_logic() {
+        let storage_names = vec![("storage_map_a".to_string(), "balanceOf".to_string())];
+        let logic = [
+            "require(!msg.value, \"no value\");",
+            "uint256 var_a = storage_map_a[arg0];",
+            "if (var_a > 0x01 && true) {",
+            "emit Event_ddf252ad(arg0, var_a);",
+            "} else {",
+            "}",
+            "(bool success, bytes memory ret0) = address(arg0).call(0);",
+            "return var_a;",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            translate_logic(&logic, &storage_names),
+            vec![
+                "    assert not msg.value, \"no value\"",
+                "    var_a: uint256 = self.balanceOf[arg0]",
+                "    if var_a > 0x01 and True:",
+                "        log Event_ddf252ad(arg0, var_a)",
+                "    else:",
+                "        pass",
+                "    success, ret0 = address(arg0).call(0)",
+                "    return var_a",
+            ]
+        );
+    }
+}
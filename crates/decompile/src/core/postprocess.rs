@@ -1,5 +1,5 @@
-use hashbrown::HashMap;
-use std::time::Instant;
+use std::collections::BTreeMap;
+use web_time::Instant;
 
 use eyre::eyre;
 use heimdall_common::utils::strings::find_balanced_encapsulator;
@@ -20,23 +20,24 @@ use crate::{
 
 use super::analyze::AnalyzerType;
 
-/// State shared between postprocessors
+/// State shared between postprocessors. The maps are ordered, since the postprocessors infer types
+/// from the first variable which matches a line
 #[derive(Debug, Clone, Default)]
 pub(crate) struct PostprocessorState {
     /// A mapping from memory locations to their corresponding variable names
-    pub memory_map: HashMap<String, String>,
+    pub memory_map: BTreeMap<String, String>,
     /// A mapping which holds the last assigned value for a given variable
-    pub variable_map: HashMap<String, String>,
+    pub variable_map: BTreeMap<String, String>,
     /// A mapping which holds inferred types for memory variables
-    pub memory_type_map: HashMap<String, String>,
+    pub memory_type_map: BTreeMap<String, String>,
     /// A mapping from storage locations to their corresponding variable names
-    pub storage_map: HashMap<String, String>,
+    pub storage_map: BTreeMap<String, String>,
     /// A mapping which holds inferred types for storage variables
-    pub storage_type_map: HashMap<String, String>,
+    pub storage_type_map: BTreeMap<String, String>,
     /// A mapping from transient storage locations to their corresponding variable names
-    pub transient_map: HashMap<String, String>,
+    pub transient_map: BTreeMap<String, String>,
     /// A mapping which holds inferred types for transient storage variables
-    pub transient_type_map: HashMap<String, String>,
+    pub transient_type_map: BTreeMap<String, String>,
     /// An optional field which holds the storage location if the function is a public getter
     pub maybe_getter_for: Option<String>,
 }
@@ -101,6 +102,7 @@ impl PostprocessOrchestrator {
             function.selector, self.typ
         );
         let start_postprocess_time = Instant::now();
+        function.sync_origins(&mut Vec::new());
 
         // get postprocessor state
         let mut state = PostprocessorState {
@@ -179,6 +181,7 @@ impl PostprocessOrchestrator {
         // Run all registered passes
         for pass in &self.passes {
             pass.run(function, &mut state)?;
+            function.sync_origins(&mut Vec::new());
         }
 
         // wherever storage_map contains a value that doesnt exist in storage_type_map, add it with
@@ -0,0 +1,431 @@
+use alloy::{
+    eips::BlockId,
+    primitives::{b256, Address, B256},
+};
+use alloy_json_abi::JsonAbi;
+use heimdall_common::utils::iter::ByteSliceExt;
+use tracing::warn;
+
+use crate::{
+    core::out::source::annotate_header,
+    interfaces::{DiamondFacet, ProxyInfo, ProxyType},
+};
+
+// reading the proxy's slots from the chain isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    alloy::{
+        primitives::{FixedBytes, U256},
+        rpc::types::Filter,
+        sol,
+        sol_types::{SolCall, SolEvent},
+    },
+    hashbrown::HashMap,
+    heimdall_common::ether::rpc,
+    tracing::{debug, info},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+sol! {
+    struct Facet {
+        address facetAddress;
+        bytes4[] functionSelectors;
+    }
+
+    struct FacetCut {
+        address facetAddress;
+        uint8 action;
+        bytes4[] functionSelectors;
+    }
+
+    function facets() external view returns (Facet[] memory facets_);
+
+    event DiamondCut(FacetCut[] _diamondCut, address _init, bytes _calldata);
+}
+
+/// The EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`.
+const IMPLEMENTATION_SLOT: B256 =
+    b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// The EIP-1967 admin slot, `keccak256("eip1967.proxy.admin") - 1`.
+#[cfg(not(target_arch = "wasm32"))]
+const ADMIN_SLOT: B256 = b256!("b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+
+/// The EIP-1967 beacon slot, `keccak256("eip1967.proxy.beacon") - 1`.
+const BEACON_SLOT: B256 = b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// The EIP-1822 implementation slot, `keccak256("PROXIABLE")`.
+const PROXIABLE_SLOT: B256 =
+    b256!("c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7");
+
+/// The diamond storage position of the EIP-2535 reference implementation,
+/// `keccak256("diamond.standard.diamond.storage")`.
+const DIAMOND_STORAGE_SLOT: B256 =
+    b256!("c8fcad8db84d3cc18b4c41d551ea0ee66dd599cde068d998e57d5e09332c131c");
+
+/// `PUSH4 facets()`, for diamonds which implement the loupe themselves.
+const FACETS_SELECTOR: [u8; 5] = [0x63, 0x7a, 0x0e, 0xd6, 0x27];
+
+/// `PUSH4 proxiableUUID()`. UUPS implementations, rather than their proxies, expose this function,
+/// and reference the implementation slot when upgrading.
+const PROXIABLE_UUID_SELECTOR: [u8; 5] = [0x63, 0x52, 0xd1, 0x90, 0x2d];
+
+/// The `implementation()` selector, which beacons expose.
+#[cfg(not(target_arch = "wasm32"))]
+const IMPLEMENTATION_SELECTOR: [u8; 4] = [0x5c, 0x60, 0xda, 0x1b];
+
+/// The EIP-1167 minimal proxy's code before and after the implementation address.
+const MINIMAL_PROXY_PREFIX: [u8; 9] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d];
+const MINIMAL_PROXY_SUFFIX: [u8; 9] = [0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91];
+
+/// Detects whether the bytecode belongs to a known proxy type. Only minimal proxies have their
+/// implementation embedded in the bytecode, so other proxies need to be resolved on-chain with
+/// [`resolve_proxy`].
+pub(crate) fn detect_proxy(bytecode: &[u8]) -> Option<ProxyInfo> {
+    // minimal proxies are `PUSHn <implementation>` surrounded by a fixed sequence of opcodes
+    if let Some(code) = bytecode.strip_prefix(MINIMAL_PROXY_PREFIX.as_slice()) {
+        let push_size = code.first().filter(|op| (0x60..=0x73).contains(*op)).map(|op| op - 0x5f);
+        if let Some(push_size) = push_size.map(usize::from) {
+            let rest = &code[1..];
+            if rest.len() >= push_size && rest[push_size..].starts_with(&MINIMAL_PROXY_SUFFIX) {
+                return Some(ProxyInfo {
+                    proxy_type: ProxyType::Minimal,
+                    implementation: Some(Address::left_padding_from(&rest[..push_size])),
+                    admin: None,
+                    beacon: None,
+                    facets: Vec::new(),
+                });
+            }
+        }
+    }
+
+    if bytecode.contains_slice(&PROXIABLE_UUID_SELECTOR) {
+        return None;
+    }
+
+    let proxy_type = if bytecode.contains_slice(DIAMOND_STORAGE_SLOT.as_slice()) ||
+        bytecode.contains_slice(&FACETS_SELECTOR)
+    {
+        ProxyType::Diamond
+    } else if bytecode.contains_slice(IMPLEMENTATION_SLOT.as_slice()) {
+        ProxyType::Eip1967
+    } else if bytecode.contains_slice(BEACON_SLOT.as_slice()) {
+        ProxyType::Beacon
+    } else if bytecode.contains_slice(PROXIABLE_SLOT.as_slice()) {
+        ProxyType::Eip1822
+    } else {
+        return None;
+    };
+
+    Some(ProxyInfo {
+        proxy_type,
+        implementation: None,
+        admin: None,
+        beacon: None,
+        facets: Vec::new(),
+    })
+}
+
+/// Reads the implementation, admin and beacon of the proxy deployed at `address`. Returns `None`
+/// if the proxy's slots are empty, since the target is then just a contract sharing the proxy's
+/// code patterns, such as an implementation.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn resolve_proxy(
+    mut proxy: ProxyInfo,
+    address: Address,
+    block: Option<BlockId>,
+    rpc_url: &str,
+) -> Option<ProxyInfo> {
+    let read_address = |slot: B256| async move {
+        match rpc::get_storage_at(address, U256::from_be_bytes(slot.0), block, rpc_url).await {
+            Ok(value) => Some(Address::from_word(value.into())).filter(|a| !a.is_zero()),
+            Err(e) => {
+                warn!("failed to read proxy slot {}: {}", slot, e);
+                None
+            }
+        }
+    };
+
+    match proxy.proxy_type {
+        ProxyType::Minimal => return Some(proxy),
+        ProxyType::Diamond => {
+            proxy.facets = get_facets(address, block, rpc_url).await;
+            if proxy.facets.is_empty() {
+                debug!("target matches diamond patterns, but it has no facets");
+                return None;
+            }
+            return Some(proxy);
+        }
+        ProxyType::Eip1822 => proxy.implementation = read_address(PROXIABLE_SLOT).await,
+        ProxyType::Eip1967 | ProxyType::Beacon => {
+            proxy.implementation = read_address(IMPLEMENTATION_SLOT).await;
+            proxy.admin = read_address(ADMIN_SLOT).await;
+            proxy.beacon = read_address(BEACON_SLOT).await;
+        }
+    }
+
+    // beacon proxies ask their beacon for the implementation on every call
+    if proxy.implementation.is_none() {
+        if let Some(beacon) = proxy.beacon {
+            proxy.proxy_type = ProxyType::Beacon;
+            proxy.implementation =
+                match rpc::call(beacon, &IMPLEMENTATION_SELECTOR, block, rpc_url).await {
+                    Ok(returndata) if returndata.len() == 32 => {
+                        Some(Address::from_slice(&returndata[12..])).filter(|a| !a.is_zero())
+                    }
+                    Ok(returndata) => {
+                        warn!("beacon returned {} bytes for implementation()", returndata.len());
+                        None
+                    }
+                    Err(e) => {
+                        warn!("failed to call implementation() on beacon {}: {}", beacon, e);
+                        None
+                    }
+                };
+        }
+    }
+
+    if proxy.implementation.is_none() && proxy.beacon.is_none() {
+        debug!("target matches {} proxy patterns, but its proxy slots are empty", proxy.proxy_type);
+        return None;
+    }
+
+    Some(proxy)
+}
+
+/// Enumerates the facets of the diamond at `address` with the loupe's `facets()` function or, if
+/// the diamond doesn't implement the loupe, by replaying its `DiamondCut` events.
+#[cfg(not(target_arch = "wasm32"))]
+async fn get_facets(address: Address, block: Option<BlockId>, rpc_url: &str) -> Vec<DiamondFacet> {
+    match rpc::call(address, &facetsCall {}.abi_encode(), block, rpc_url).await {
+        Ok(returndata) => match facetsCall::abi_decode_returns(&returndata) {
+            Ok(facets) => {
+                return facets
+                    .into_iter()
+                    .filter(|facet| !facet.functionSelectors.is_empty())
+                    .map(|facet| DiamondFacet {
+                        address: facet.facetAddress,
+                        selectors: facet.functionSelectors,
+                    })
+                    .collect()
+            }
+            Err(e) => debug!("failed to decode facets() return data: {}", e),
+        },
+        Err(e) => debug!("failed to call facets() on diamond: {}", e),
+    }
+
+    info!("diamond doesn't implement the loupe, replaying its DiamondCut events");
+    let from_block = rpc::get_contract_creation_block(address, rpc_url).await.unwrap_or_default();
+    let filter = Filter::new()
+        .address(address)
+        .event_signature(DiamondCut::SIGNATURE_HASH)
+        .from_block(from_block);
+    match rpc::get_logs(&filter, rpc_url).await {
+        Ok(logs) => apply_diamond_cuts(
+            logs.iter()
+                .filter_map(|log| DiamondCut::decode_log_data(log.data()).ok())
+                .flat_map(|cut| cut._diamondCut),
+        ),
+        Err(e) => {
+            warn!("failed to fetch DiamondCut events: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Applies the given facet cuts in order, returning the facets which have selectors routed to them
+/// afterwards, in the order they were first added.
+#[cfg(not(target_arch = "wasm32"))]
+fn apply_diamond_cuts(cuts: impl IntoIterator<Item = FacetCut>) -> Vec<DiamondFacet> {
+    let mut routes: HashMap<FixedBytes<4>, Address> = HashMap::new();
+    let mut order: Vec<FixedBytes<4>> = Vec::new();
+    for cut in cuts {
+        for selector in cut.functionSelectors {
+            match cut.action {
+                // add and replace
+                0 | 1 => {
+                    if routes.insert(selector, cut.facetAddress).is_none() {
+                        order.push(selector);
+                    }
+                }
+                // remove
+                _ => {
+                    routes.remove(&selector);
+                    order.retain(|s| *s != selector);
+                }
+            }
+        }
+    }
+
+    let mut facets: Vec<DiamondFacet> = Vec::new();
+    for selector in order {
+        let address = routes[&selector];
+        match facets.iter_mut().find(|facet| facet.address == address) {
+            Some(facet) => facet.selectors.push(selector),
+            None => facets.push(DiamondFacet { address, selectors: vec![selector] }),
+        }
+    }
+    facets
+}
+
+/// Proxies can't be resolved on wasm, where network access isn't available, so they're reported
+/// as detected.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn resolve_proxy(
+    proxy: ProxyInfo,
+    _address: Address,
+    _block: Option<BlockId>,
+    _rpc_url: &str,
+) -> Option<ProxyInfo> {
+    warn!("reading the proxy's slots isn't available on wasm");
+    Some(proxy)
+}
+
+/// Notes the diamond and the selectors it routes to a facet in the facet's decompiled source
+/// header, along with the signatures of the routed selectors the facet's ABI resolved.
+pub(crate) fn annotate_facet(
+    source: &str,
+    diamond: Address,
+    facet: &DiamondFacet,
+    abi: &JsonAbi,
+) -> String {
+    let mut annotation =
+        vec![format!("@custom:facet     This contract is a facet of the diamond at {diamond}.")];
+    annotation.extend(facet.selectors.iter().map(|selector| {
+        let signature = abi
+            .functions()
+            .find(|f| f.selector() == *selector)
+            .map(|f| f.signature())
+            .unwrap_or_else(|| "unresolved".to_string());
+        format!("                    {selector}     {signature}")
+    }));
+    annotate_header(source, annotation)
+}
+
+/// Notes the proxy summary in the decompiled source's header, below the decompiler version.
+pub(crate) fn annotate_source(source: &str, proxy: &ProxyInfo) -> String {
+    let mut annotation = vec!["@custom:proxy     This contract is a proxy.".to_string()];
+    annotation.extend(proxy.summary().iter().map(|line| format!("                    {line}")));
+    annotate_header(source, annotation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{address, keccak256};
+    use heimdall_common::utils::strings::decode_hex;
+
+    #[test]
+    fn test_proxy_slots() {
+        let eip1967_slot =
+            |name: &str| B256::from(U256::from_be_bytes(keccak256(name).0) - U256::from(1));
+
+        assert_eq!(IMPLEMENTATION_SLOT, eip1967_slot("eip1967.proxy.implementation"));
+        assert_eq!(ADMIN_SLOT, eip1967_slot("eip1967.proxy.admin"));
+        assert_eq!(BEACON_SLOT, eip1967_slot("eip1967.proxy.beacon"));
+        assert_eq!(PROXIABLE_SLOT, keccak256("PROXIABLE"));
+        assert_eq!(DIAMOND_STORAGE_SLOT, keccak256("diamond.standard.diamond.storage"));
+        assert_eq!(FACETS_SELECTOR[1..], facetsCall::SELECTOR);
+    }
+
+    #[test]
+    fn test_detect_minimal_proxy() {
+        let bytecode = decode_hex(
+            "363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3",
+        )
+        .expect("invalid bytecode");
+
+        assert_eq!(
+            detect_proxy(&bytecode),
+            Some(ProxyInfo {
+                proxy_type: ProxyType::Minimal,
+                implementation: Some(address!("bebebebebebebebebebebebebebebebebebebebe")),
+                admin: None,
+                beacon: None,
+                facets: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_detect_eip1967_proxy() {
+        // PUSH32 <implementation slot> SLOAD
+        let bytecode = [[0x7f].as_slice(), IMPLEMENTATION_SLOT.as_slice(), &[0x54]].concat();
+        assert_eq!(detect_proxy(&bytecode).map(|proxy| proxy.proxy_type), Some(ProxyType::Eip1967));
+
+        // UUPS implementations reference the slot too, but expose `proxiableUUID()`
+        let bytecode = [&PROXIABLE_UUID_SELECTOR, bytecode.as_slice()].concat();
+        assert_eq!(detect_proxy(&bytecode), None);
+    }
+
+    #[test]
+    fn test_annotate_source() {
+        let proxy = ProxyInfo {
+            proxy_type: ProxyType::Eip1967,
+            implementation: Some(Address::ZERO),
+            admin: Some(Address::ZERO),
+            beacon: None,
+            facets: Vec::new(),
+        };
+
+        let annotated = annotate_source("/// @custom:version   heimdall-rs v0\n///\n", &proxy);
+        assert_eq!(
+            annotated.lines().take(4).collect::<Vec<_>>(),
+            vec![
+                "/// @custom:version   heimdall-rs v0",
+                "/// @custom:proxy     This contract is a proxy.",
+                "///                     proxy type:       EIP-1967",
+                "///                     implementation:   0x0000000000000000000000000000000000000000",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_diamond_cuts() {
+        let cut = |facet: u8, action: u8, selectors: &[u8]| FacetCut {
+            facetAddress: Address::repeat_byte(facet),
+            action,
+            functionSelectors: selectors.iter().map(|s| FixedBytes::repeat_byte(*s)).collect(),
+        };
+
+        // selectors 2 and 3 are added to facet 1, then 2 is replaced by facet 2 and 3 is removed
+        let facets =
+            apply_diamond_cuts(vec![cut(1, 0, &[1, 2, 3]), cut(2, 1, &[2]), cut(0, 2, &[3])]);
+        assert_eq!(
+            facets,
+            vec![
+                DiamondFacet {
+                    address: Address::repeat_byte(1),
+                    selectors: vec![FixedBytes::repeat_byte(1)],
+                },
+                DiamondFacet {
+                    address: Address::repeat_byte(2),
+                    selectors: vec![FixedBytes::repeat_byte(2)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotate_facet() {
+        let abi: JsonAbi =
+            serde_json::from_str(r#"[{"type":"function","name":"owner","inputs":[],"outputs":[{"name":"","type":"address"}],"stateMutability":"view"}]"#)
+                .expect("invalid abi");
+        let facet = DiamondFacet {
+            address: Address::repeat_byte(1),
+            selectors: vec![FixedBytes::new([0x8d, 0xa5, 0xcb, 0x5b]), FixedBytes::repeat_byte(2)],
+        };
+
+        let annotated =
+            annotate_facet("# @custom:version   heimdall-rs v0", Address::ZERO, &facet, &abi);
+        assert_eq!(
+            annotated.lines().collect::<Vec<_>>(),
+            vec![
+                "# @custom:version   heimdall-rs v0",
+                "# @custom:facet     This contract is a facet of the diamond at 0x0000000000000000000000000000000000000000.",
+                "#                     0x8da5cb5b     owner()",
+                "#                     0x02020202     unresolved",
+            ]
+        );
+    }
+}
@@ -1,6 +1,21 @@
-use crate::interfaces::AnalyzedFunction;
-use heimdall_common::ether::signatures::ResolvedFunction;
-use tracing::trace;
+use crate::interfaces::{AnalyzedFunction, CalldataFrame, TypeHeuristic};
+use alloy::primitives::keccak256;
+use hashbrown::HashMap;
+use heimdall_common::{
+    ether::signatures::{score_signature, ResolvedFunction},
+    utils::strings::encode_hex,
+};
+use tracing::{debug, trace};
+
+/// A candidate signature for a function, with the score it was ranked by in
+/// [`rank_signatures`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RankedSignature {
+    /// The text signature, e.g. `transfer(address,uint256)`.
+    pub signature: String,
+    /// The candidate's score. Higher scores are more likely to be correct.
+    pub score: u32,
+}
 
 /// Given a list of potential [`ResolvedFunction`]s and a [`Snapshot`], return a list of
 /// [`ResolvedFunction`]s (that is, resolved signatures that were found on a 4byte directory) that
@@ -85,3 +100,220 @@ pub(crate) fn match_parameters(
 
     matched_functions
 }
+
+/// Whether the given solidity type is ABI-encoded dynamically, i.e. by an offset into calldata.
+fn is_dynamic_type(input: &str) -> bool {
+    input == "bytes" || input == "string" || input.ends_with("[]")
+}
+
+/// Score how well the bytecode's evidence about an accessed argument supports the given input
+/// type, using the type heuristics found during analysis and the argument's mask size.
+fn argument_evidence(input: &str, frame: &CalldataFrame) -> i64 {
+    let mut evidence = 0;
+    let numeric = input.starts_with("uint") || input.starts_with("int");
+    let bytes = input.starts_with("bytes") || input == "string";
+
+    // a mask which narrows the argument to exactly this type, e.g. 20 bytes for an address
+    if !is_dynamic_type(input) && frame.potential_types().first().map(String::as_str) == Some(input)
+    {
+        evidence += 5;
+    }
+
+    for heuristic in &frame.heuristics {
+        evidence += match heuristic {
+            TypeHeuristic::Boolean if input == "bool" => 20,
+            TypeHeuristic::Boolean => -10,
+            TypeHeuristic::Numeric if numeric => 10,
+            TypeHeuristic::Bytes if bytes || is_dynamic_type(input) => 10,
+            _ => 0,
+        };
+    }
+
+    // arithmetic on an argument is expected for numbers and for the offsets of dynamic types,
+    // but contradicts fixed-size bytes which are only ever masked or shifted
+    if frame.heuristics.contains(&TypeHeuristic::Numeric) &&
+        !frame.heuristics.contains(&TypeHeuristic::Bytes) &&
+        bytes &&
+        !is_dynamic_type(input)
+    {
+        evidence -= 10;
+    }
+
+    evidence
+}
+
+/// Rank the given candidate signatures for `function`, most likely first, using evidence from the
+/// bytecode rather than the order in which a signature database returned them.
+///
+/// Each candidate's spamminess score (see [`score_signature`]) is adjusted by:
+///   - the number of its parameters which the function never reads from calldata, and
+///   - how well the type heuristics and mask size of each argument the function does read support
+///     the candidate's type for that parameter.
+///
+/// Ties are broken by the signature, so that ranking is deterministic.
+pub(crate) fn rank_signatures(
+    candidates: Vec<ResolvedFunction>,
+    function: &AnalyzedFunction,
+) -> Vec<(ResolvedFunction, RankedSignature)> {
+    let mut ranked = candidates
+        .into_iter()
+        .map(|candidate| {
+            let mut score = score_signature(&candidate.signature, None) as i64;
+
+            // penalize parameters which are never read from calldata
+            let unread = candidate.inputs.len().saturating_sub(function.arguments.len());
+            score -= unread as i64 * 15;
+
+            for (index, input) in candidate.inputs.iter().enumerate() {
+                if let Some(frame) = function.arguments.get(&index) {
+                    score += argument_evidence(input, frame);
+                }
+            }
+
+            trace!("ranked signature {} with score {}", candidate.signature, score);
+            let ranking = RankedSignature {
+                signature: candidate.signature.clone(),
+                score: score.max(0) as u32,
+            };
+            (candidate, ranking)
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|(_, a), (_, b)| {
+        b.score.cmp(&a.score).then_with(|| a.signature.cmp(&b.signature))
+    });
+    ranked
+}
+
+/// Matches the function's analyzed parameters against the signatures its selector resolved to,
+/// using the highest ranked match as its signature, and the rest as its candidate signatures.
+pub(crate) fn resolve_signature(
+    function: &mut AnalyzedFunction,
+    resolved_selectors: &HashMap<String, Vec<ResolvedFunction>>,
+) {
+    let resolved_functions =
+        resolved_selectors.get(&function.selector).unwrap_or(&Vec::new()).to_owned();
+    let matched_resolved_functions = match_parameters(resolved_functions, function);
+    debug!(
+        "matched {} resolved functions for '{}'",
+        matched_resolved_functions.len(),
+        function.selector
+    );
+
+    let ranked_resolved_functions = rank_signatures(matched_resolved_functions, function);
+    function.resolved_function = ranked_resolved_functions.first().map(|(r, _)| r.clone());
+    function.candidate_signatures = ranked_resolved_functions.into_iter().map(|(_, r)| r).collect();
+    debug!(
+        "using signature '{}' for '{}'",
+        function.resolved_function.as_ref().map(|r| &r.signature).unwrap_or(&String::new()),
+        function.selector
+    );
+}
+
+/// Returns whether the function with the given selector was requested by the `--selector` and
+/// `--function` filters. Selectors may be given with or without the `0x` prefix. Functions match
+/// if one of their resolved signatures starts with the filter, e.g. `transfer(`, or if the filter
+/// is a full signature which hashes to their selector, which doesn't require resolving them.
+pub(crate) fn is_selected(
+    selector: &str,
+    selectors: &[String],
+    functions: &[String],
+    resolved_selectors: &HashMap<String, Vec<ResolvedFunction>>,
+) -> bool {
+    if selectors.is_empty() && functions.is_empty() {
+        return true;
+    }
+
+    let matches_selector = selectors.iter().any(|wanted| {
+        wanted.trim_start_matches("0x").eq_ignore_ascii_case(selector.trim_start_matches("0x"))
+    });
+    let matches_function = functions.iter().any(|wanted| {
+        encode_hex(&keccak256(wanted.as_bytes())[..4]) == selector.trim_start_matches("0x") ||
+            resolved_selectors.get(selector).is_some_and(|resolved| {
+                resolved.iter().any(|function| function.signature.starts_with(wanted.as_str()))
+            })
+    });
+    matches_selector || matches_function
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hashbrown::HashSet;
+
+    fn resolved(signature: &str) -> ResolvedFunction {
+        let (name, inputs) = signature.trim_end_matches(')').split_once('(').unwrap();
+        ResolvedFunction {
+            name: name.to_string(),
+            signature: signature.to_string(),
+            inputs: inputs.split(',').filter(|i| !i.is_empty()).map(String::from).collect(),
+            decoded_inputs: None,
+        }
+    }
+
+    fn frame(mask_size: usize, heuristics: &[TypeHeuristic]) -> CalldataFrame {
+        CalldataFrame {
+            arg_op: String::new(),
+            mask_size,
+            heuristics: heuristics.iter().cloned().collect::<HashSet<_>>(),
+        }
+    }
+
+    #[test]
+    fn test_rank_signatures_by_type_evidence() {
+        let mut function = AnalyzedFunction::new("12345678", false);
+        function.arguments.insert(0, frame(32, &[TypeHeuristic::Numeric]));
+
+        let ranked =
+            rank_signatures(vec![resolved("foo(bytes32)"), resolved("foo(uint256)")], &function);
+        assert_eq!(ranked[0].1.signature, "foo(uint256)");
+        assert!(ranked[0].1.score > ranked[1].1.score);
+
+        function.arguments.insert(0, frame(1, &[TypeHeuristic::Boolean]));
+        let ranked =
+            rank_signatures(vec![resolved("foo(uint8)"), resolved("foo(bool)")], &function);
+        assert_eq!(ranked[0].1.signature, "foo(bool)");
+    }
+
+    #[test]
+    fn test_rank_signatures_by_argument_count() {
+        let mut function = AnalyzedFunction::new("12345678", false);
+        function.arguments.insert(0, frame(20, &[]));
+
+        let ranked = rank_signatures(
+            vec![resolved("bar(address,uint256,uint256)"), resolved("bar(address)")],
+            &function,
+        );
+        assert_eq!(ranked[0].1.signature, "bar(address)");
+    }
+
+    #[test]
+    fn test_is_selected() {
+        let resolved_selectors = HashMap::from([
+            ("a9059cbb".to_string(), vec![resolved("transfer(address,uint256)")]),
+            ("23b872dd".to_string(), vec![resolved("transferFrom(address,address,uint256)")]),
+        ]);
+        let selected = |selector: &str, selectors: &[&str], functions: &[&str]| {
+            is_selected(
+                selector,
+                &selectors.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                &functions.iter().map(|f| f.to_string()).collect::<Vec<_>>(),
+                &resolved_selectors,
+            )
+        };
+
+        // without filters, every function is selected
+        assert!(selected("a9059cbb", &[], &[]));
+
+        assert!(selected("a9059cbb", &["0xA9059CBB"], &[]));
+        assert!(!selected("23b872dd", &["0xa9059cbb"], &[]));
+
+        assert!(selected("a9059cbb", &[], &["transfer("]));
+        assert!(!selected("23b872dd", &[], &["transfer("]));
+        assert!(selected("23b872dd", &[], &["transfer"]));
+
+        // full signatures are matched by their selector, even if unresolved
+        assert!(selected("095ea7b3", &[], &["approve(address,uint256)"]));
+        assert!(!selected("095ea7b3", &[], &["approve("]));
+    }
+}
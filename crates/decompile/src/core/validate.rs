@@ -0,0 +1,253 @@
+use alloy::{eips::BlockId, primitives::Address};
+use alloy_dyn_abi::DynSolValue;
+use tracing::warn;
+
+use crate::interfaces::AnalyzedFunction;
+
+// probing functions on-chain isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::interfaces::to_return_type,
+    alloy::{
+        primitives::address,
+        rpc::types::trace::parity::{Delta, StateDiff},
+    },
+    heimdall_common::{
+        ether::{
+            fork::{default_fork, Fork},
+            rpc,
+        },
+        utils::strings::{decode_hex, encode_hex},
+    },
+    tracing::{debug, info},
+};
+
+/// The sender of transactions simulated on a fork.
+#[cfg(not(target_arch = "wasm32"))]
+const PROBE_SENDER: Address = address!("00000000000000000000000000000000000f0c4e");
+
+/// The outcome of probing a decompiled function with `eth_call`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Validation {
+    /// The call succeeded, and its return data decoded as the decompiled return type.
+    Matched(String),
+    /// The call succeeded, but its return data didn't decode as the decompiled return type.
+    Mismatched(String),
+    /// The call reverted or otherwise failed.
+    Failed(String),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Validation {
+    /// Check the return data of a successful call against the decompiled return type, if any.
+    pub(crate) fn from_returndata(returns: Option<&str>, returndata: &[u8]) -> Self {
+        let Some(returns) = returns else {
+            return match returndata.is_empty() {
+                true => Validation::Matched("no return data".to_string()),
+                false => Validation::Mismatched(format!(
+                    "returned {} bytes, but no return type was recovered",
+                    returndata.len()
+                )),
+            };
+        };
+
+        let return_type = to_return_type(returns);
+        match return_type.abi_decode(returndata) {
+            Ok(value) => Validation::Matched(format_value(value)),
+            Err(_) => Validation::Mismatched(format!(
+                "returned '{}', which does not decode as '{}'",
+                encode_hex(returndata),
+                returns.replace("memory", "").trim()
+            )),
+        }
+    }
+
+    /// The notice to attach to the probed function.
+    fn notice(&self) -> String {
+        match self {
+            Validation::Matched(value) => format!("validated on-chain, returned {value}"),
+            Validation::Mismatched(reason) => {
+                format!("on-chain validation failed: {reason}")
+            }
+            Validation::Failed(reason) => format!("on-chain call failed: {reason}"),
+        }
+    }
+}
+
+/// Format a decoded return value for display.
+pub(crate) fn format_value(value: DynSolValue) -> String {
+    match value {
+        DynSolValue::String(s) => format!("\"{s}\""),
+        DynSolValue::Uint(x, _) => x.to_string(),
+        DynSolValue::Int(x, _) => x.to_string(),
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Address(a) => a.to_string(),
+        DynSolValue::Tuple(values) => {
+            format!("({})", values.into_iter().map(format_value).collect::<Vec<_>>().join(", "))
+        }
+        token => format!("0x{token:?}"),
+    }
+}
+
+/// Whether the given function can be probed with `eth_call`. Only argument-less view and pure
+/// functions are probed, since calling anything else may revert or require state changes.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_probeable(function: &AnalyzedFunction) -> bool {
+    !function.fallback && (function.pure || function.view) && function.arguments.is_empty()
+}
+
+/// Probe each argument-less view and pure function of the contract at `address` with `eth_call`,
+/// recording whether its live behavior matches the decompiled return type as a notice on the
+/// function.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn validate_onchain(
+    functions: &mut [AnalyzedFunction],
+    address: Address,
+    block: Option<BlockId>,
+    rpc_url: &str,
+) {
+    let probed = functions.iter().filter(|f| is_probeable(f)).collect::<Vec<_>>();
+    let calls = probed
+        .iter()
+        .map(|f| (address, decode_hex(&f.selector).expect("invalid selector")))
+        .collect::<Vec<_>>();
+    let results = match rpc::call_batch(&calls, block, rpc_url).await {
+        Ok(results) => results,
+        Err(e) => {
+            warn!("failed to validate functions on-chain: {}", e);
+            return;
+        }
+    };
+
+    let validations = probed
+        .iter()
+        .zip(results)
+        .map(|(f, result)| {
+            let validation = match result {
+                Ok(returndata) => Validation::from_returndata(f.returns.as_deref(), &returndata),
+                Err(e) => Validation::Failed(e.to_string()),
+            };
+            (f.selector.clone(), validation)
+        })
+        .collect::<Vec<_>>();
+
+    let mut matched = 0;
+    for (selector, validation) in &validations {
+        debug!("validated '{}' on-chain: {:?}", selector, validation);
+        let Some(function) = functions.iter_mut().find(|f| &f.selector == selector) else {
+            continue;
+        };
+
+        match validation {
+            Validation::Matched(_) => matched += 1,
+            _ => warn!("function '{}': {}", selector, validation.notice()),
+        }
+        function.notices.push(validation.notice());
+    }
+
+    info!("validated {}/{} probed functions on-chain", matched, validations.len());
+
+    if let Some(fork) = default_fork() {
+        simulate_on_fork(functions, address, &fork).await;
+    }
+}
+
+/// Simulate each argument-less, state-mutating function of the contract at `address` on the
+/// given fork, recording whether it succeeded and how much storage it changed as a notice on
+/// the function. The fork's state is reverted after each simulation.
+#[cfg(not(target_arch = "wasm32"))]
+async fn simulate_on_fork(functions: &mut [AnalyzedFunction], address: Address, fork: &Fork) {
+    let mut simulated = 0;
+    for function in
+        functions.iter_mut().filter(|f| !(f.fallback || f.pure || f.view) && f.arguments.is_empty())
+    {
+        let calldata = decode_hex(&function.selector).expect("invalid selector");
+        let notice = match fork.simulate(PROBE_SENDER, address, &calldata).await {
+            Ok(simulation) if simulation.success => {
+                simulated += 1;
+                match simulation.state_diff {
+                    Some(diff) => format!(
+                        "simulated on fork, succeeded using {} gas and changed {} storage slot(s)",
+                        simulation.gas_used,
+                        changed_slots(&diff)
+                    ),
+                    None => {
+                        format!("simulated on fork, succeeded using {} gas", simulation.gas_used)
+                    }
+                }
+            }
+            Ok(_) => "simulated on fork, reverted".to_string(),
+            Err(e) => {
+                warn!("failed to simulate '{}' on fork: {}", function.selector, e);
+                continue;
+            }
+        };
+        debug!("function '{}': {}", function.selector, notice);
+        function.notices.push(notice);
+    }
+
+    info!("simulated {} state-mutating functions successfully on fork", simulated);
+}
+
+/// The number of storage slots changed in the given state diff.
+#[cfg(not(target_arch = "wasm32"))]
+fn changed_slots(diff: &StateDiff) -> usize {
+    diff.0
+        .values()
+        .flat_map(|account| account.storage.values())
+        .filter(|delta| !matches!(delta, Delta::Unchanged))
+        .count()
+}
+
+/// Functions can't be probed on-chain on wasm, where network access isn't available.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn validate_onchain(
+    _functions: &mut [AnalyzedFunction],
+    _address: Address,
+    _block: Option<BlockId>,
+    _rpc_url: &str,
+) {
+    warn!("on-chain validation isn't available on wasm");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+
+    #[test]
+    fn test_matched_return_data() {
+        let returndata = U256::from(18).to_be_bytes_vec();
+        assert_eq!(
+            Validation::from_returndata(Some("uint8"), &returndata),
+            Validation::Matched("18".to_string())
+        );
+
+        let returndata = DynSolValue::String("WETH".to_string()).abi_encode_params();
+        assert_eq!(
+            Validation::from_returndata(Some("string memory"), &returndata),
+            Validation::Matched("\"WETH\"".to_string())
+        );
+
+        let returndata =
+            [U256::from(18).to_be_bytes_vec(), U256::from(1).to_be_bytes_vec()].concat();
+        assert_eq!(
+            Validation::from_returndata(Some("uint256, bool"), &returndata),
+            Validation::Matched("(18, true)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_mismatched_return_data() {
+        assert!(matches!(
+            Validation::from_returndata(Some("uint256"), &[]),
+            Validation::Mismatched(_)
+        ));
+        assert!(matches!(Validation::from_returndata(None, &[0u8; 32]), Validation::Mismatched(_)));
+        assert_eq!(
+            Validation::from_returndata(None, &[]),
+            Validation::Matched("no return data".to_string())
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+use web_time::{Duration, Instant};
+
+use alloy_json_abi::JsonAbi;
+use eyre::eyre;
+use hashbrown::HashMap;
+use heimdall_common::utils::{io::file::write_file, strings::decode_hex};
+use heimdall_disassembler::disassemble_bytecode;
+use heimdall_vm::{
+    core::{hardfork::HardFork, vm::VM},
+    ext::selectors::find_function_selectors,
+};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::{
+    error::Error,
+    interfaces::{FunctionVerification, VerificationReport},
+};
+
+/// Recompiles the decompiled source with solc, and compares the jump structure of each function
+/// of the recompiled bytecode against `original_branches`, the number of unique branches of each
+/// function in the original bytecode.
+pub(crate) async fn verify(
+    source: &str,
+    abi: &JsonAbi,
+    original_branches: &HashMap<String, u32>,
+    solc: Option<&str>,
+    hardfork: HardFork,
+    timeout: u64,
+) -> VerificationReport {
+    let failed = |error: String| {
+        warn!("failed to verify the decompiled source: {}", error);
+        VerificationReport {
+            score: 0.0,
+            functions: compare(original_branches, &HashMap::new()).functions,
+            error: Some(error),
+        }
+    };
+
+    let Some(solc) = find_solc(solc) else {
+        return failed(
+            "solc was not found. please install it, or provide one using the '--solc' flag."
+                .to_string(),
+        );
+    };
+    debug!("verifying the decompiled source with {}", solc.display());
+
+    let bytecode = match compile(&solc, source) {
+        Ok(bytecode) => bytecode,
+        Err(e) => return failed(e),
+    };
+    let compiled_branches = match branch_counts(&bytecode, hardfork, timeout).await {
+        Ok(compiled_branches) => original_selectors(compiled_branches, abi),
+        Err(e) => return failed(format!("analyzing the recompiled bytecode failed: {e}")),
+    };
+
+    let report = compare(original_branches, &compiled_branches);
+    info!("recompiled source has a similarity score of {:.2}", report.score);
+    for f in report.diverging() {
+        warn!("function 0x{} diverges from the original ({:.2} similar)", f.selector, f.similarity);
+    }
+    report
+}
+
+/// Keys the recompiled functions by their original selector. Unresolved functions are named after
+/// their original selector, e.g. `Unresolved_8da5cb5b`, so their recompiled selector differs.
+fn original_selectors(compiled: HashMap<String, u32>, abi: &JsonAbi) -> HashMap<String, u32> {
+    let aliases = abi
+        .functions()
+        .filter_map(|f| {
+            f.name.strip_prefix("Unresolved_").map(|selector| (f.selector().to_string(), selector))
+        })
+        .collect::<HashMap<_, _>>();
+
+    compiled
+        .into_iter()
+        .map(|(selector, branches)| match aliases.get(&format!("0x{selector}")) {
+            Some(original) => (original.to_string(), branches),
+            None => (selector, branches),
+        })
+        .collect()
+}
+
+/// Finds the solc binary to compile with: the given path, `solc` on the `PATH`, or the global
+/// version selected with solc-select.
+fn find_solc(solc: Option<&str>) -> Option<PathBuf> {
+    if let Some(solc) = solc {
+        return Some(PathBuf::from(solc));
+    }
+
+    if let Some(path) = std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths).map(|dir| dir.join("solc")).find(|p| p.is_file())
+    }) {
+        return Some(path);
+    }
+
+    // solc-select installs each version to `~/.solc-select/artifacts/solc-<version>/`
+    let solc_select = PathBuf::from(std::env::var_os("HOME")?).join(".solc-select");
+    let version = std::fs::read_to_string(solc_select.join("global-version")).ok()?;
+    let version = version.trim();
+    Some(solc_select.join(format!("artifacts/solc-{version}/solc-{version}")))
+        .filter(|p| p.is_file())
+}
+
+/// Compiles the source with solc, returning the runtime bytecode of `DecompiledContract`.
+fn compile(solc: &Path, source: &str) -> Result<Vec<u8>, String> {
+    let path = std::env::temp_dir().join(format!("heimdall-verify-{}.sol", std::process::id()));
+    write_file(&path.to_string_lossy(), source)
+        .map_err(|e| format!("failed to write the decompiled source: {e}"))?;
+
+    let output = Command::new(solc)
+        .args(["--optimize", "--combined-json", "bin-runtime"])
+        .arg(&path)
+        .output();
+    let _ = std::fs::remove_file(&path);
+    let output = output.map_err(|e| format!("failed to run solc: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "solc failed to compile the decompiled source: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("failed to parse solc output: {e}"))?;
+    json["contracts"]
+        .as_object()
+        .and_then(|contracts| {
+            contracts.iter().find(|(name, _)| name.ends_with(":DecompiledContract"))
+        })
+        .and_then(|(_, contract)| contract["bin-runtime"].as_str())
+        .ok_or_else(|| "solc output doesn't contain DecompiledContract".to_string())
+        .and_then(|bytecode| decode_hex(bytecode).map_err(|e| e.to_string()))
+}
+
+/// Symbolically executes each function of the bytecode, returning the number of unique branches
+/// of each function, keyed by selector.
+async fn branch_counts(
+    bytecode: &[u8],
+    hardfork: HardFork,
+    timeout: u64,
+) -> Result<HashMap<String, u32>, Error> {
+    let mut evm = VM::new(
+        bytecode,
+        &[],
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        0,
+        u128::MAX,
+    )
+    .with_hardfork(hardfork);
+    let assembly = disassemble_bytecode(bytecode, hardfork, false);
+
+    let mut branch_counts = HashMap::new();
+    for (selector, entry_point) in find_function_selectors(&evm, &assembly) {
+        evm.reset();
+        let (_, branches) = evm
+            .symbolic_exec_selector(
+                &selector,
+                entry_point,
+                Instant::now()
+                    .checked_add(Duration::from_millis(timeout))
+                    .expect("invalid timeout"),
+            )
+            .map_err(|e| Error::Eyre(eyre!("symbolic execution failed: {}", e)))?;
+        branch_counts.insert(selector, branches);
+    }
+    Ok(branch_counts)
+}
+
+/// Compares the number of unique branches of each function in the original and recompiled
+/// bytecode. A function's similarity is the ratio of its smaller branch count to its larger one,
+/// or 0 if it's missing from either bytecode, and the score is the mean similarity.
+fn compare(original: &HashMap<String, u32>, compiled: &HashMap<String, u32>) -> VerificationReport {
+    let mut selectors = original.keys().chain(compiled.keys()).collect::<Vec<_>>();
+    selectors.sort();
+    selectors.dedup();
+
+    let functions = selectors
+        .into_iter()
+        .map(|selector| {
+            let original_branches = original.get(selector).copied();
+            let compiled_branches = compiled.get(selector).copied();
+            let similarity = match (original_branches, compiled_branches) {
+                (Some(a), Some(b)) if a == b => 1.0,
+                (Some(a), Some(b)) => a.min(b) as f64 / a.max(b) as f64,
+                _ => 0.0,
+            };
+            FunctionVerification {
+                selector: selector.to_string(),
+                original_branches,
+                compiled_branches,
+                similarity,
+            }
+        })
+        .collect::<Vec<FunctionVerification>>();
+
+    let score = match functions.is_empty() {
+        true => 1.0,
+        false => functions.iter().map(|f| f.similarity).sum::<f64>() / functions.len() as f64,
+    };
+
+    VerificationReport { score, functions, error: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let original =
+            HashMap::from([("01".to_string(), 4), ("02".to_string(), 2), ("03".to_string(), 1)]);
+        let compiled =
+            HashMap::from([("01".to_string(), 4), ("02".to_string(), 1), ("04".to_string(), 1)]);
+
+        let report = compare(&original, &compiled);
+        assert_eq!(
+            report.functions.iter().map(|f| f.similarity).collect::<Vec<_>>(),
+            vec![1.0, 0.5, 0.0, 0.0]
+        );
+        assert_eq!(report.score, 0.375);
+        assert_eq!(
+            report.diverging().map(|f| f.selector.as_str()).collect::<Vec<_>>(),
+            vec!["02", "03", "04"]
+        );
+    }
+
+    #[test]
+    fn test_original_selectors() {
+        let abi: JsonAbi = serde_json::from_str(
+            r#"[{"type":"function","name":"Unresolved_8da5cb5b","inputs":[],"outputs":[],"stateMutability":"view"}]"#,
+        )
+        .expect("invalid abi");
+        let alias = abi.functions().next().expect("abi is empty").selector().to_string();
+        let compiled = HashMap::from([(alias[2..].to_string(), 1), ("a9059cbb".to_string(), 2)]);
+
+        assert_eq!(
+            original_selectors(compiled, &abi),
+            HashMap::from([("8da5cb5b".to_string(), 1), ("a9059cbb".to_string(), 2)])
+        );
+    }
+
+    #[test]
+    fn test_compare_empty() {
+        assert_eq!(compare(&HashMap::new(), &HashMap::new()).score, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_verify_without_solc() {
+        let original = HashMap::from([("01".to_string(), 1)]);
+        let report = verify(
+            "",
+            &JsonAbi::new(),
+            &original,
+            Some("/nonexistent/solc"),
+            HardFork::Latest,
+            1000,
+        )
+        .await;
+
+        assert_eq!(report.score, 0.0);
+        assert!(report.error.is_some_and(|e| e.starts_with("failed to run solc")));
+        assert_eq!(report.functions.len(), 1);
+    }
+}
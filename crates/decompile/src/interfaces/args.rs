@@ -1,11 +1,19 @@
-use alloy::primitives::Address;
-use clap::Parser;
+use alloy::eips::BlockId;
+use clap::{Parser, ValueEnum};
 use derive_builder::Builder;
 use eyre::Result;
-use heimdall_common::ether::bytecode::get_bytecode_from_target;
+use heimdall_common::utils::progress::ProgressSender;
 use heimdall_config::parse_url_arg;
 use heimdall_vm::core::hardfork::HardFork;
 
+use crate::interfaces::FunctionResultSender;
+
+// fetching the target's bytecode isn't available on wasm
+#[cfg(target_arch = "wasm32")]
+use heimdall_common::utils::strings::decode_hex;
+#[cfg(not(target_arch = "wasm32"))]
+use {alloy::primitives::Address, heimdall_common::ether::bytecode::get_bytecode_from_target_at};
+
 #[derive(Debug, Clone, Parser, Builder)]
 #[clap(
     about = "Decompiles EVM bytecode to human-readable representations",
@@ -18,14 +26,31 @@ use heimdall_vm::core::hardfork::HardFork;
 /// bytecode into human-readable source code and ABI.
 pub struct DecompilerArgs {
     /// The target to decompile, either a file, bytecode, contract address, or ENS name.
-    #[clap(required = true)]
+    #[clap(required_unless_present = "batch", default_value = "", hide_default_value = true)]
     pub target: String,
 
+    /// A file listing the targets to decompile, one per line, or '-' to read them from stdin.
+    /// Each target's output is written to its own directory within the output directory,
+    /// alongside a summary of which targets succeeded or failed, and how long each took.
+    #[clap(long, conflicts_with = "target")]
+    pub batch: Option<String>,
+
+    /// The number of targets to decompile concurrently with '--batch'.
+    #[clap(long, default_value = "4", hide_default_value = true)]
+    pub threads: usize,
+
     /// The RPC provider to use for fetching target bytecode.
-    /// This can be an explicit URL or a reference to a MESC endpoint.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
     #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
     pub rpc_url: String,
 
+    /// The block to read the target's bytecode at, either a block number, block hash, or tag
+    /// (e.g. 'latest'). Useful for targets which were since redeployed or self-destructed.
+    /// Defaults to the latest block.
+    #[clap(long, short = 'b')]
+    pub block: Option<BlockId>,
+
     /// When prompted, always select the default value.
     #[clap(long, short)]
     pub default: bool,
@@ -34,6 +59,16 @@ pub struct DecompilerArgs {
     #[clap(long = "skip-resolving")]
     pub skip_resolving: bool,
 
+    /// Only decompile the function with this selector, e.g. '0xa9059cbb'. May be repeated.
+    #[clap(long = "selector")]
+    pub selectors: Vec<String>,
+
+    /// Only decompile the functions whose resolved signature starts with this, e.g. 'transfer('.
+    /// A full signature, e.g. 'transfer(address,uint256)', is matched by its selector, so it
+    /// doesn't need to be resolved. May be repeated.
+    #[clap(long = "function")]
+    pub functions: Vec<String>,
+
     /// Whether to include solidity source code in the output (in beta).
     #[clap(long = "include-sol")]
     pub include_solidity: bool,
@@ -42,7 +77,30 @@ pub struct DecompilerArgs {
     #[clap(long = "include-yul")]
     pub include_yul: bool,
 
-    /// The output directory to write the output to or 'print' to print to the console
+    /// The source representation to emit alongside the ABI. Yul preserves low-level semantics,
+    /// such as memory operations and exact masks, which the Solidity output has to approximate.
+    /// Solidity output is emitted as Vyper if the target was compiled with vyper. Foundry output
+    /// wraps the Solidity output in a project which can be built and tested with forge. Equivalent
+    /// to '--include-sol' or '--include-yul', and defaults to 'abi'.
+    #[clap(
+        long = "output-format",
+        value_enum,
+        conflicts_with_all = ["include_solidity", "include_yul"]
+    )]
+    pub output_format: Option<OutputFormat>,
+
+    /// Whether to write a source map alongside the decompiled Solidity or Yul source, which maps
+    /// each of its lines to the ranges of the runtime bytecode it was lifted from.
+    #[clap(long = "source-map")]
+    pub source_map: bool,
+
+    /// Whether to annotate each line of the decompiled Solidity or Yul source with the offsets of
+    /// the runtime bytecode it was lifted from, as a trailing comment.
+    #[clap(long = "include-pc")]
+    pub include_pc: bool,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
     #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
     pub output: String,
 
@@ -50,10 +108,41 @@ pub struct DecompilerArgs {
     #[clap(long, short, default_value = "", hide_default_value = true)]
     pub name: String,
 
-    /// The timeout for each function's symbolic execution in milliseconds.
-    #[clap(long, short, default_value = "10000", hide_default_value = true)]
+    /// The timeout for each function's symbolic execution in milliseconds. Functions which time
+    /// out are emitted with the paths explored so far, and a notice that they're incomplete.
+    #[clap(
+        long,
+        short,
+        visible_alias = "timeout-per-function",
+        default_value = "10000",
+        hide_default_value = true
+    )]
     pub timeout: u64,
 
+    /// Whether to make the output reproducible, so that decompiling the same bytecode twice
+    /// produces byte-for-byte identical output. Symbolic execution is bounded by a fixed number of
+    /// branches per function rather than the timeout, which is ignored.
+    #[clap(long)]
+    pub deterministic: bool,
+
+    /// The number of branches symbolic execution may explore in each function, after which its
+    /// remaining paths are skipped. Defaults to 10000 with '--deterministic', and unbounded
+    /// otherwise.
+    #[clap(long = "max-branches")]
+    pub max_branches: Option<u32>,
+
+    /// The number of bytes of memory each path of a function may expand to during symbolic
+    /// execution, after which the path is skipped.
+    #[clap(long = "max-memory")]
+    pub max_memory: Option<u128>,
+
+    /// A checkpoint file to resume decompiling from. Functions are checkpointed to the file as
+    /// they're analyzed, and those already in it aren't executed again, so an interrupted run can
+    /// be resumed by passing the same file. Shared internal functions are inlined into their
+    /// callers, since they're found by comparing the traces of every function.
+    #[clap(long, conflicts_with = "batch")]
+    pub resume: Option<String>,
+
     /// Path to an optional ABI file to use for resolving errors, functions, and events.
     #[clap(long, short, default_value = None, hide_default_value = true)]
     pub abi: Option<String>,
@@ -66,17 +155,112 @@ pub struct DecompilerArgs {
     #[clap(long, default_value = "", hide_default_value = true)]
     pub openai_api_key: String,
 
-    /// Your Etherscan API key, used for fetching creation bytecode of self-destructed contracts.
+    /// Your Etherscan API key, used for fetching creation bytecode of self-destructed contracts
+    /// and the verified ABI of the target.
     #[clap(long, default_value = "", hide_default_value = true)]
     pub etherscan_api_key: String,
 
+    /// An Etherscan-compatible block explorer API, such as a Blockscout instance's `/api`, to
+    /// fetch the verified ABI of the target from. Defaults to the chain's explorer.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub explorer_api_url: String,
+
     /// The hardfork to use for opcode recognition. Opcodes introduced after this hardfork
     /// will be treated as unknown. Defaults to 'latest'.
     #[clap(long, short = 'f', default_value = "latest")]
     pub hardfork: HardFork,
+
+    /// Whether to validate recovered view functions against the live contract, by probing each
+    /// argument-less view function with `eth_call`. Requires the target to be a contract address.
+    /// If a fork is configured with `--fork`, argument-less state-mutating functions are also
+    /// simulated against the fork.
+    #[clap(long = "validate-onchain")]
+    pub validate_onchain: bool,
+
+    /// Whether to skip looking up the target on Sourcify. By default, if the target is a
+    /// verified contract address, its verified ABI is used to name functions, events and errors.
+    #[clap(long = "no-sourcify")]
+    pub no_sourcify: bool,
+
+    /// Whether to include the target's verified source from Sourcify in the output, if found.
+    #[clap(long = "include-verified-source")]
+    pub include_verified_source: bool,
+
+    /// Whether to decompile the implementation of the target, if the target is a proxy. The
+    /// implementation is read from the proxy's storage, so this requires the target to be a
+    /// contract address. Enabled by default, pass '--follow-proxy=false' to disable.
+    #[clap(
+        long = "follow-proxy",
+        default_value_t = true,
+        num_args = 0..=1,
+        default_missing_value = "true",
+        action = clap::ArgAction::Set
+    )]
+    pub follow_proxy: bool,
+
+    /// Whether to verify the decompiled source by recompiling it with solc, and comparing the
+    /// recompiled bytecode's selectors and jump structure against the target's. Reports a
+    /// similarity score, and the functions which diverge from the target.
+    #[clap(long = "verify")]
+    pub verify: bool,
+
+    /// The solc binary to verify the decompiled source with. Defaults to `solc` on the PATH, or
+    /// the global version selected with solc-select.
+    #[clap(long = "solc")]
+    pub solc: Option<String>,
+
+    /// Whether to recover the target's constructor from its creation transaction, which is looked
+    /// up on the chain's block explorer. Requires the target to be a contract address, and an
+    /// Etherscan API key. Creation bytecode targets are detected, and their constructor recovered,
+    /// automatically.
+    #[clap(long = "creation")]
+    pub creation: bool,
+
+    /// Receives a progress update as each function is symbolically executed or restored from the
+    /// checkpoint. Only available when decompiling programmatically.
+    #[clap(skip)]
+    pub progress: Option<ProgressSender>,
+
+    /// Receives each function's result as soon as it's analyzed, before the rest of the contract
+    /// is decompiled. Only available when decompiling programmatically.
+    #[clap(skip)]
+    pub function_results: Option<FunctionResultSender>,
+}
+
+/// The source representations the decompiler can emit alongside the ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Only the ABI is emitted.
+    Abi,
+    /// Solidity source is emitted.
+    #[clap(name = "sol", alias = "solidity")]
+    Solidity,
+    /// Yul source is emitted.
+    Yul,
+    /// Vyper source is emitted. Solidity output switches to this automatically if the target
+    /// was compiled with vyper.
+    #[clap(name = "vy", alias = "vyper")]
+    Vyper,
+    /// Solidity source is emitted as part of a foundry project, alongside an interface generated
+    /// from the ABI and a test which checks the target dispatches each recovered selector.
+    Foundry,
+    /// A solidity interface declaring the ABI's functions, events, and errors is emitted. The
+    /// functions' bodies aren't lifted, so this is as fast as emitting only the ABI.
+    Interface,
 }
 
 impl DecompilerArgs {
+    /// The source representation to emit, from either '--output-format', '--include-sol' or
+    /// '--include-yul'.
+    pub fn source_format(&self) -> OutputFormat {
+        match self.output_format {
+            Some(output_format) => output_format,
+            None if self.include_solidity => OutputFormat::Solidity,
+            None if self.include_yul => OutputFormat::Yul,
+            None => OutputFormat::Abi,
+        }
+    }
+
     /// Retrieves the bytecode for the specified target
     ///
     /// This method fetches the bytecode from a file, address, or directly from a hex string,
@@ -87,8 +271,22 @@ impl DecompilerArgs {
     ///
     /// # Returns
     /// The raw bytecode as a vector of bytes
+    #[cfg(not(target_arch = "wasm32"))]
     pub async fn get_bytecode(&self) -> Result<Vec<u8>> {
-        get_bytecode_from_target(&self.target, &self.rpc_url, &self.etherscan_api_key).await
+        get_bytecode_from_target_at(
+            &self.target,
+            self.block,
+            &self.rpc_url,
+            &self.etherscan_api_key,
+        )
+        .await
+    }
+
+    /// Retrieves the bytecode of the target, which is raw bytecode on wasm, where fetching it from
+    /// a file or the chain isn't available.
+    #[cfg(target_arch = "wasm32")]
+    pub async fn get_bytecode(&self) -> Result<Vec<u8>> {
+        Ok(decode_hex(&self.target)?)
     }
 
     /// Gets the hardfork to use for decompilation.
@@ -107,6 +305,7 @@ impl DecompilerArgs {
     }
 
     /// Attempts to detect the hardfork based on the contract's creation block.
+    #[cfg(not(target_arch = "wasm32"))]
     async fn detect_hardfork_from_creation_block(&self) -> Option<HardFork> {
         if self.rpc_url.is_empty() {
             return None;
@@ -120,6 +319,7 @@ impl DecompilerArgs {
     }
 
     /// Gets the creation block for a contract address.
+    #[cfg(not(target_arch = "wasm32"))]
     async fn get_creation_block(&self, address: Address, chain_id: u64) -> Option<u64> {
         if !self.etherscan_api_key.is_empty() &&
             heimdall_common::ether::etherscan::is_supported_chain(chain_id)
@@ -138,6 +338,12 @@ impl DecompilerArgs {
 
         heimdall_common::ether::rpc::get_contract_creation_block(address, &self.rpc_url).await.ok()
     }
+
+    /// The creation block can't be looked up on wasm, where network access isn't available.
+    #[cfg(target_arch = "wasm32")]
+    async fn detect_hardfork_from_creation_block(&self) -> Option<HardFork> {
+        None
+    }
 }
 
 impl DecompilerArgsBuilder {
@@ -145,19 +351,41 @@ impl DecompilerArgsBuilder {
     pub fn new() -> Self {
         Self {
             target: Some(String::new()),
+            batch: Some(None),
+            threads: Some(4),
             rpc_url: Some(String::new()),
+            block: Some(None),
             default: Some(true),
             skip_resolving: Some(false),
+            selectors: Some(Vec::new()),
+            functions: Some(Vec::new()),
             include_solidity: Some(false),
             include_yul: Some(false),
+            output_format: Some(None),
+            source_map: Some(false),
+            include_pc: Some(false),
             output: Some(String::new()),
             name: Some(String::new()),
             timeout: Some(10000),
+            deterministic: Some(false),
+            max_branches: Some(None),
+            max_memory: Some(None),
+            resume: Some(None),
             abi: Some(None),
             llm_postprocess: Some(false),
             openai_api_key: Some(String::new()),
             etherscan_api_key: Some(String::new()),
+            explorer_api_url: Some(String::new()),
             hardfork: Some(HardFork::Latest),
+            validate_onchain: Some(false),
+            no_sourcify: Some(false),
+            include_verified_source: Some(false),
+            follow_proxy: Some(true),
+            verify: Some(false),
+            solc: Some(None),
+            creation: Some(false),
+            progress: Some(None),
+            function_results: Some(None),
         }
     }
 }
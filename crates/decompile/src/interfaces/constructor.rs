@@ -0,0 +1,56 @@
+use alloy::primitives::B256;
+use serde_json::{json, Value};
+
+/// The constructor of the target, recovered from its creation bytecode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConstructorInfo {
+    /// The arguments of the constructor, in order
+    pub arguments: Vec<ConstructorArgument>,
+    /// The immutables the constructor assigns, in the order they're first assigned
+    pub immutables: Vec<Immutable>,
+    /// Whether the constructor accepts ether
+    pub payable: bool,
+}
+
+/// An argument of the target's constructor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstructorArgument {
+    /// The type of the argument
+    pub typ: String,
+    /// The value the target was deployed with, if the creation bytecode included its arguments
+    pub value: Option<String>,
+}
+
+/// An immutable assigned by the target's constructor, which is embedded in the runtime bytecode
+/// wherever it's read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Immutable {
+    /// The name of the immutable in the decompiled source
+    pub name: String,
+    /// The type of the immutable, inferred from its getter or the constructor argument it's
+    /// assigned
+    pub typ: String,
+    /// The offsets of the immutable's references in the runtime bytecode
+    pub offsets: Vec<usize>,
+    /// The value of the immutable in the deployed runtime bytecode, if it's known
+    pub value: Option<B256>,
+}
+
+impl ConstructorInfo {
+    /// Serializes the constructor as JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "payable": self.payable,
+            "arguments": self.arguments.iter().map(|arg| json!({
+                "type": arg.typ,
+                "value": arg.value,
+            })).collect::<Vec<_>>(),
+            "immutables": self.immutables.iter().map(|immutable| json!({
+                "name": immutable.name,
+                "type": immutable.typ,
+                "offsets": immutable.offsets,
+                "value": immutable.value,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
@@ -0,0 +1,68 @@
+use clap::Parser;
+use derive_builder::Builder;
+use heimdall_config::parse_url_arg;
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Decompiles two contracts and reports how their functions, events and storage differ",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall diff <OLD> <NEW> [OPTIONS]"
+)]
+/// Arguments for the diff operation
+///
+/// This struct contains all the configuration parameters needed to decompile two contracts,
+/// such as two implementations of a proxy, and compare them function by function.
+pub struct DiffArgs {
+    /// The older contract, either a file, bytecode, contract address, or ENS name.
+    #[clap(required = true, value_name = "OLD")]
+    pub old_target: String,
+
+    /// The newer contract, either a file, bytecode, contract address, or ENS name.
+    #[clap(required = true, value_name = "NEW")]
+    pub new_target: String,
+
+    /// The RPC provider to use for fetching target bytecode.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
+    #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// Whether to skip resolving function selectors.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+
+    /// The number of unchanged lines to show around each change in the diffs of the functions.
+    #[clap(long, default_value = "3")]
+    pub context: usize,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
+    #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
+    pub output: String,
+
+    /// The name for the output file
+    #[clap(long, short, default_value = "", hide_default_value = true)]
+    pub name: String,
+
+    /// The number of branches symbolic execution may explore in each function, after which its
+    /// remaining paths are skipped. Both contracts are decompiled deterministically, so that
+    /// functions which didn't change decompile identically.
+    #[clap(long = "max-branches")]
+    pub max_branches: Option<u32>,
+}
+
+impl DiffArgsBuilder {
+    /// Creates a new DiffArgsBuilder with default values
+    pub fn new() -> Self {
+        Self {
+            old_target: Some(String::new()),
+            new_target: Some(String::new()),
+            rpc_url: Some(String::new()),
+            skip_resolving: Some(false),
+            context: Some(3),
+            output: Some(String::new()),
+            name: Some(String::new()),
+            max_branches: Some(None),
+        }
+    }
+}
@@ -1,13 +1,26 @@
 use hashbrown::{HashMap, HashSet};
 
 use alloy::primitives::U256;
-use heimdall_common::ether::signatures::ResolvedFunction;
+use alloy_dyn_abi::DynSolType;
+use heimdall_common::ether::{
+    signatures::{ResolvedFunction, ResolvedLog},
+    types::to_type,
+};
 use heimdall_vm::core::{opcodes::WrappedOpcode, types::byte_size_to_type};
+use serde::{Deserialize, Serialize};
 
-use crate::core::analyze::AnalyzerType;
+use crate::{
+    core::{
+        analyze::AnalyzerType,
+        origins::{realign, Origin},
+        resolve::RankedSignature,
+    },
+    interfaces::StorageAccess,
+};
 
 /// The [`AnalyzedFunction`] struct represents a function that has been analyzed by the decompiler.
-#[derive(Clone, Debug)]
+/// It's serialized to checkpoint analyzed functions, before their signatures are resolved.
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct AnalyzedFunction {
     /// the function's 4byte selector
     pub selector: String,
@@ -20,6 +33,7 @@ pub(crate) struct AnalyzedFunction {
     /// memory structure:
     ///   - key : slot of the argument. I.E: slot 0 is CALLDATALOAD(4).
     ///   - value : tuple of ({value: U256, operation: WrappedOpcode})
+    #[serde(skip)]
     pub memory: HashMap<U256, StorageFrame>,
 
     /// returns the return type for the function.
@@ -28,15 +42,33 @@ pub(crate) struct AnalyzedFunction {
     /// holds function logic to be written to the output solidity file.
     pub logic: Vec<String>,
 
+    /// the bytecode ranges each line of logic was lifted from, as of the last
+    /// [`AnalyzedFunction::sync_origins`]
+    pub origins: Vec<Vec<Origin>>,
+
+    /// the logic as of the last [`AnalyzedFunction::sync_origins`]
+    pub synced_logic: Vec<String>,
+
     /// holds all found event selectors found
     pub events: HashSet<U256>,
 
+    /// the parameters each event is emitted with, guessed from its topics and data
+    pub event_parameters: HashMap<U256, EventParameters>,
+
     /// holds all found custom error selectors found
     pub errors: HashSet<U256>,
 
+    /// the guessed types of the arguments each custom error is raised with
+    pub error_arguments: HashMap<U256, Vec<String>>,
+
     /// stores the matched resolved function for this Functon
+    #[serde(skip)]
     pub resolved_function: Option<ResolvedFunction>,
 
+    /// all candidate signatures matching this function, ranked most likely first
+    #[serde(skip)]
+    pub candidate_signatures: Vec<RankedSignature>,
+
     /// stores decompiler notices
     pub notices: Vec<String>,
 
@@ -56,6 +88,15 @@ pub(crate) struct AnalyzedFunction {
 
     /// optional constant value for this function
     pub constant_value: Option<String>,
+
+    /// the storage reads and writes performed by this function
+    pub storage_accesses: HashSet<StorageAccess>,
+
+    /// the names of the modifiers lifted from guards this function shares with others, in order
+    pub modifiers: Vec<String>,
+
+    /// the entries of the internal functions this function calls rather than inlines
+    pub internal_calls: HashSet<u128>,
 }
 
 #[derive(Clone, Debug)]
@@ -64,7 +105,7 @@ pub(crate) struct StorageFrame {
     pub value: U256,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct CalldataFrame {
     pub arg_op: String,
     pub mask_size: usize,
@@ -79,7 +120,41 @@ impl CalldataFrame {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+/// The parameters of an event, as they're laid out in the logs which emit it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct EventParameters {
+    /// the guessed types of the parameters which are emitted as topics
+    pub indexed: Vec<String>,
+    /// the guessed types of the parameters which are ABI-encoded in the log's data
+    pub data: Vec<String>,
+}
+
+impl EventParameters {
+    /// The types of the event's parameters, and whether each is indexed. The types of resolved
+    /// events take precedence over the guessed ones, and their leading parameters are assumed to
+    /// be the indexed ones.
+    pub(crate) fn inputs(&self, resolved: Option<&ResolvedLog>) -> Vec<(DynSolType, bool)> {
+        match resolved {
+            Some(resolved) => resolved
+                .inputs()
+                .into_iter()
+                .enumerate()
+                .map(|(i, typ)| (typ, i < self.indexed.len()))
+                .collect(),
+            None => self
+                .indexed
+                .iter()
+                .map(|typ| (typ, true))
+                .chain(self.data.iter().map(|typ| (typ, false)))
+                .map(|(typ, indexed)| {
+                    (DynSolType::parse(typ).unwrap_or(DynSolType::Bytes), indexed)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub(crate) enum TypeHeuristic {
     Numeric,
     Bytes,
@@ -94,9 +169,14 @@ impl AnalyzedFunction {
             memory: HashMap::new(),
             returns: None,
             logic: Vec::new(),
+            origins: Vec::new(),
+            synced_logic: Vec::new(),
             events: HashSet::new(),
+            event_parameters: HashMap::new(),
             errors: HashSet::new(),
+            error_arguments: HashMap::new(),
             resolved_function: None,
+            candidate_signatures: Vec::new(),
             notices: Vec::new(),
             pure: true,
             view: true,
@@ -105,12 +185,34 @@ impl AnalyzedFunction {
             fallback,
             maybe_getter_for: None,
             constant_value: None,
+            storage_accesses: HashSet::new(),
+            modifiers: Vec::new(),
+            internal_calls: HashSet::new(),
+        }
+    }
+
+    /// Carries the origins of each line over the changes made to the logic since it was last
+    /// synced. Lines added since are lifted from the `pending` instructions, which are consumed
+    /// if any line was added or changed.
+    pub(crate) fn sync_origins(&mut self, pending: &mut Vec<Origin>) {
+        if self.logic == self.synced_logic {
+            return;
         }
+
+        self.origins = realign(&self.synced_logic, &self.origins, &self.logic, pending);
+        self.synced_logic = self.logic.clone();
+        pending.clear();
     }
 
-    /// Whether this is a constant or not
+    /// Whether this is a constant or not. Functions which return several values can't be
+    /// declared as constants.
     pub(crate) fn is_constant(&self) -> bool {
-        self.pure && self.arguments.is_empty()
+        self.pure && self.arguments.is_empty() && self.return_types().len() <= 1
+    }
+
+    /// The types of the values the function returns, in order.
+    pub(crate) fn return_types(&self) -> Vec<DynSolType> {
+        self.returns.iter().flat_map(|returns| returns.split(", ")).map(to_type).collect()
     }
 
     /// Gets the inputs for a range of memory
@@ -140,3 +242,14 @@ impl AnalyzedFunction {
         arguments
     }
 }
+
+/// Parses the types a function returns, which are separated by commas if it returns several
+/// values, e.g. `uint256, address`. Several values are decoded as a tuple.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn to_return_type(returns: &str) -> DynSolType {
+    let mut types = returns.split(", ").map(to_type).collect::<Vec<_>>();
+    match types.len() {
+        1 => types.remove(0),
+        _ => DynSolType::Tuple(types),
+    }
+}
@@ -0,0 +1,18 @@
+use alloy_json_abi::Function;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// A function's result, which is sent to the caller as soon as the function is analyzed, before
+/// the rest of the contract is decompiled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionResult {
+    /// The selector of the function, as hex without a `0x` prefix
+    pub selector: String,
+    /// The function's resolved signature, if it was resolved
+    pub signature: Option<String>,
+    /// The function's ABI entry. Its name and argument types are taken from the resolved
+    /// signature, if it was resolved.
+    pub abi: Function,
+}
+
+/// Sends each function's result to the caller as it's analyzed.
+pub type FunctionResultSender = UnboundedSender<FunctionResult>;
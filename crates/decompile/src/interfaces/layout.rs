@@ -0,0 +1,240 @@
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// The location of a storage access, relative to the slot of the state variable it belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum StorageSlot {
+    /// A value stored directly at the given slot
+    Value(U256),
+    /// The value of a mapping stored at `base`, for a key of type `key_type`
+    Mapping { base: Box<StorageSlot>, key_type: String },
+    /// An element of a dynamic array stored at `base`
+    Array { base: Box<StorageSlot> },
+}
+
+impl StorageSlot {
+    /// The slot of the state variable this location belongs to
+    pub(crate) fn root(&self) -> U256 {
+        match self {
+            StorageSlot::Value(slot) => *slot,
+            StorageSlot::Mapping { base, .. } | StorageSlot::Array { base } => base.root(),
+        }
+    }
+}
+
+/// A single storage read or write observed during analysis, covering `size` bytes starting at
+/// byte `offset` of the `member`th word of the value stored at `slot`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) struct StorageAccess {
+    pub slot: StorageSlot,
+    pub member: usize,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// The type of a recovered state variable.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StorageType {
+    /// A value type, such as `uint256` or `address`
+    Elementary(String),
+    /// A `mapping(key => value)`
+    Mapping(Box<StorageType>, Box<StorageType>),
+    /// A dynamic `value[]` array
+    Array(Box<StorageType>),
+    /// A named `struct`, whose members are stored in consecutive slots
+    Struct(String, Vec<StructMember>),
+}
+
+/// A member of a recovered `struct`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StructMember {
+    /// The name of the member
+    pub label: String,
+    /// The slot of the member, relative to the struct's first slot
+    pub slot: usize,
+    /// The byte offset of the member within its slot
+    pub offset: usize,
+    /// The type of the member
+    pub typ: StorageType,
+}
+
+impl StorageType {
+    /// The solidity representation of this type, e.g. `mapping(address => uint256)`
+    pub fn label(&self) -> String {
+        match self {
+            StorageType::Elementary(typ) => typ.to_string(),
+            StorageType::Mapping(key, value) => {
+                format!("mapping({} => {})", key.label(), value.label())
+            }
+            StorageType::Array(base) => format!("{}[]", base.label()),
+            StorageType::Struct(name, _) => name.to_string(),
+        }
+    }
+
+    /// The solc type identifier of this type, e.g. `t_mapping(t_address,t_uint256)`
+    pub fn id(&self) -> String {
+        match self {
+            StorageType::Elementary(typ) => format!("t_{typ}"),
+            StorageType::Mapping(key, value) => format!("t_mapping({},{})", key.id(), value.id()),
+            StorageType::Array(base) => format!("t_array({})dyn_storage", base.id()),
+            StorageType::Struct(name, _) => format!("t_struct({name})_storage"),
+        }
+    }
+
+    /// The number of bytes this type occupies in its slot
+    pub fn size(&self) -> usize {
+        match self {
+            StorageType::Elementary(typ) => match typ.as_str() {
+                "bool" => 1,
+                "address" => 20,
+                typ => typ
+                    .trim_start_matches(char::is_alphabetic)
+                    .parse::<usize>()
+                    .map(|bits| if typ.starts_with("bytes") { bits } else { bits / 8 })
+                    .unwrap_or(32),
+            },
+            StorageType::Struct(_, members) => {
+                32 * (members.iter().map(|member| member.slot).max().unwrap_or(0) + 1)
+            }
+            _ => 32,
+        }
+    }
+
+    /// The structs used by this type, including itself
+    pub fn structs(&self) -> Vec<&StorageType> {
+        match self {
+            StorageType::Elementary(_) => Vec::new(),
+            StorageType::Mapping(key, value) => {
+                key.structs().into_iter().chain(value.structs()).collect()
+            }
+            StorageType::Array(base) => base.structs(),
+            StorageType::Struct(_, members) => members
+                .iter()
+                .flat_map(|member| member.typ.structs())
+                .chain(std::iter::once(self))
+                .collect(),
+        }
+    }
+
+    /// Adds the solc type definition of this type, and of the types it contains, to `types`
+    fn insert_definitions(&self, types: &mut Map<String, Value>) {
+        let mut definition = json!({
+            "encoding": "inplace",
+            "label": self.label(),
+            "numberOfBytes": self.size().to_string(),
+        });
+        match self {
+            StorageType::Elementary(_) => {}
+            StorageType::Mapping(key, value) => {
+                definition["encoding"] = json!("mapping");
+                definition["key"] = json!(key.id());
+                definition["value"] = json!(value.id());
+                key.insert_definitions(types);
+                value.insert_definitions(types);
+            }
+            StorageType::Array(base) => {
+                definition["encoding"] = json!("dynamic_array");
+                definition["base"] = json!(base.id());
+                base.insert_definitions(types);
+            }
+            StorageType::Struct(name, members) => {
+                definition["label"] = json!(format!("struct DecompiledContract.{name}"));
+                definition["members"] = members
+                    .iter()
+                    .enumerate()
+                    .map(|(i, member)| {
+                        member.typ.insert_definitions(types);
+                        json!({
+                            "astId": i,
+                            "contract": "DecompiledContract",
+                            "label": member.label,
+                            "offset": member.offset,
+                            "slot": member.slot.to_string(),
+                            "type": member.typ.id(),
+                        })
+                    })
+                    .collect();
+            }
+        }
+        types.insert(self.id(), definition);
+    }
+}
+
+/// A state variable recovered from the contract's storage accesses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageLayoutEntry {
+    /// The name of the variable, matching the decompiled source where possible
+    pub label: String,
+    /// The slot the variable is stored in
+    pub slot: U256,
+    /// The byte offset of the variable within its slot, for packed variables
+    pub offset: usize,
+    /// The type of the variable
+    pub typ: StorageType,
+}
+
+/// The storage layout of a decompiled contract, ordered by slot and offset.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageLayout {
+    /// The recovered state variables
+    pub entries: Vec<StorageLayoutEntry>,
+}
+
+impl StorageLayout {
+    /// Whether no state variables were recovered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The structs used by the layout's variables, in the order they're first used
+    pub fn structs(&self) -> Vec<&StorageType> {
+        let mut structs: Vec<&StorageType> = Vec::new();
+        for typ in self.entries.iter().flat_map(|entry| entry.typ.structs()) {
+            if !structs.contains(&typ) {
+                structs.push(typ);
+            }
+        }
+        structs
+    }
+
+    /// Serializes the layout in the format of solc's `storageLayout` output.
+    ///
+    /// ```
+    /// use alloy::primitives::U256;
+    /// use heimdall_decompiler::{StorageLayout, StorageLayoutEntry, StorageType};
+    ///
+    /// let layout = StorageLayout {
+    ///     entries: vec![StorageLayoutEntry {
+    ///         label: "owner".to_string(),
+    ///         slot: U256::ZERO,
+    ///         offset: 0,
+    ///         typ: StorageType::Elementary("address".to_string()),
+    ///     }],
+    /// };
+    /// let json = layout.to_solc_json();
+    /// assert_eq!(json["storage"][0]["type"], "t_address");
+    /// assert_eq!(json["types"]["t_address"]["numberOfBytes"], "20");
+    /// ```
+    pub fn to_solc_json(&self) -> Value {
+        let mut types = Map::new();
+        let storage = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                entry.typ.insert_definitions(&mut types);
+                json!({
+                    "astId": i,
+                    "contract": "DecompiledContract",
+                    "label": entry.label,
+                    "offset": entry.offset,
+                    "slot": entry.slot.to_string(),
+                    "type": entry.typ.id(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        json!({ "storage": storage, "types": types })
+    }
+}
@@ -1,6 +1,21 @@
 mod args;
+mod constructor;
+mod diff;
 mod function;
+mod function_result;
+mod layout;
+mod proxy;
+mod source_map;
+mod verification;
 
 // re-export the public interface
-pub use args::{DecompilerArgs, DecompilerArgsBuilder};
+pub use args::{DecompilerArgs, DecompilerArgsBuilder, OutputFormat};
+pub use constructor::{ConstructorArgument, ConstructorInfo, Immutable};
+pub use diff::{DiffArgs, DiffArgsBuilder};
 pub(crate) use function::*;
+pub use function_result::{FunctionResult, FunctionResultSender};
+pub(crate) use layout::{StorageAccess, StorageSlot};
+pub use layout::{StorageLayout, StorageLayoutEntry, StorageType, StructMember};
+pub use proxy::{DiamondFacet, ProxyInfo, ProxyType};
+pub use source_map::SourceMap;
+pub use verification::{FunctionVerification, VerificationReport};
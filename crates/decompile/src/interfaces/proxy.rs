@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+use alloy::primitives::{Address, FixedBytes};
+use serde_json::{json, Value};
+
+/// The proxy standards the decompiler recognizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyType {
+    /// An EIP-1967 proxy, which stores its implementation and admin in standardized slots
+    Eip1967,
+    /// An EIP-1822 (UUPS) proxy, which stores its implementation in the `PROXIABLE` slot
+    Eip1822,
+    /// An EIP-1967 beacon proxy, which asks a beacon contract for its implementation
+    Beacon,
+    /// An EIP-1167 minimal proxy, which has its implementation embedded in its bytecode
+    Minimal,
+    /// An EIP-2535 diamond, which routes each selector to one of many facets
+    Diamond,
+}
+
+impl Display for ProxyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyType::Eip1967 => write!(f, "EIP-1967"),
+            ProxyType::Eip1822 => write!(f, "EIP-1822"),
+            ProxyType::Beacon => write!(f, "EIP-1967 beacon"),
+            ProxyType::Minimal => write!(f, "EIP-1167 minimal"),
+            ProxyType::Diamond => write!(f, "EIP-2535 diamond"),
+        }
+    }
+}
+
+/// A proxy detected in the target's bytecode, and the addresses it delegates to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyInfo {
+    /// The proxy standard the target implements
+    pub proxy_type: ProxyType,
+    /// The implementation the proxy delegates to, if it could be resolved
+    pub implementation: Option<Address>,
+    /// The admin allowed to upgrade the proxy, if any
+    pub admin: Option<Address>,
+    /// The beacon the proxy reads its implementation from, for beacon proxies
+    pub beacon: Option<Address>,
+    /// The facets the diamond routes selectors to, for diamonds
+    pub facets: Vec<DiamondFacet>,
+}
+
+/// A facet of an EIP-2535 diamond.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiamondFacet {
+    /// The address of the facet
+    pub address: Address,
+    /// The selectors the diamond routes to the facet
+    pub selectors: Vec<FixedBytes<4>>,
+}
+
+impl ProxyInfo {
+    /// A human-readable summary of the proxy, one line per property.
+    ///
+    /// ```
+    /// use alloy::primitives::Address;
+    /// use heimdall_decompiler::{ProxyInfo, ProxyType};
+    ///
+    /// let proxy = ProxyInfo {
+    ///     proxy_type: ProxyType::Minimal,
+    ///     implementation: Some(Address::ZERO),
+    ///     admin: None,
+    ///     beacon: None,
+    ///     facets: Vec::new(),
+    /// };
+    /// assert_eq!(
+    ///     proxy.summary(),
+    ///     vec![
+    ///         "proxy type:       EIP-1167 minimal".to_string(),
+    ///         "implementation:   0x0000000000000000000000000000000000000000".to_string(),
+    ///     ]
+    /// );
+    /// ```
+    pub fn summary(&self) -> Vec<String> {
+        let mut summary = vec![format!("proxy type:       {}", self.proxy_type)];
+        if self.proxy_type != ProxyType::Diamond {
+            summary.push(format!(
+                "implementation:   {}",
+                self.implementation.map(|a| a.to_string()).unwrap_or_else(|| "unknown".to_string())
+            ));
+        }
+        if let Some(admin) = self.admin {
+            summary.push(format!("admin:            {admin}"));
+        }
+        if let Some(beacon) = self.beacon {
+            summary.push(format!("beacon:           {beacon}"));
+        }
+        for facet in &self.facets {
+            summary.push(format!(
+                "facet:            {} ({} selectors)",
+                facet.address,
+                facet.selectors.len()
+            ));
+        }
+        summary
+    }
+
+    /// Serializes the proxy summary as JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "proxyType": self.proxy_type.to_string(),
+            "implementation": self.implementation,
+            "admin": self.admin,
+            "beacon": self.beacon,
+            "facets": self.facets.iter().map(|facet| json!({
+                "address": facet.address,
+                "selectors": facet.selectors,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
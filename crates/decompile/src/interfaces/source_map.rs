@@ -0,0 +1,77 @@
+use serde_json::{json, Value};
+
+/// Maps each line of the decompiled source to the ranges of the runtime bytecode it was lifted
+/// from. Ranges are `[start, end)` offsets into the bytecode, covering whole instructions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// The ranges each line was lifted from, in order. Lines which weren't lifted from the
+    /// runtime bytecode, such as declarations and the constructor, have none.
+    pub lines: Vec<Vec<(u128, u128)>>,
+}
+
+impl SourceMap {
+    /// The ranges the given line was lifted from, numbered from 1.
+    pub fn ranges(&self, line: usize) -> &[(u128, u128)] {
+        line.checked_sub(1).and_then(|i| self.lines.get(i)).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Accounts for the lines annotated into the header of the source after it was built, which
+    /// aren't lifted from the bytecode.
+    pub(crate) fn annotated(mut self, source: &str) -> Self {
+        let annotations = source.split('\n').count().saturating_sub(self.lines.len());
+        self.lines.splice(0..0, std::iter::repeat_n(Vec::new(), annotations));
+        self
+    }
+
+    /// Annotates each line of the source which was lifted from the bytecode with the ranges it
+    /// was lifted from, as a trailing comment.
+    pub(crate) fn annotate_offsets(&self, source: &str) -> String {
+        source
+            .split('\n')
+            .enumerate()
+            .map(|(i, line)| match self.ranges(i + 1) {
+                [] => line.to_string(),
+                ranges => format!(
+                    "{line} // pc: {}",
+                    ranges
+                        .iter()
+                        .map(|(start, end)| format!("{start:#x}..{end:#x}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Serializes the source map as JSON, listing the lines which were lifted from the bytecode.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version": 1,
+            "lines": self
+                .lines
+                .iter()
+                .enumerate()
+                .filter(|(_, ranges)| !ranges.is_empty())
+                .map(|(i, ranges)| json!({
+                    "line": i + 1,
+                    "ranges": ranges.iter().map(|(start, end)| json!([start, end])).collect::<Vec<_>>(),
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate_offsets() {
+        let source_map = SourceMap { lines: vec![vec![], vec![(0x20, 0x2b), (0x42, 0x45)]] };
+        assert_eq!(
+            source_map.annotate_offsets("function a() {\n    b();\n}"),
+            "function a() {\n    b(); // pc: 0x20..0x2b, 0x42..0x45\n}"
+        );
+    }
+}
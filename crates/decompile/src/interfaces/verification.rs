@@ -0,0 +1,48 @@
+use serde_json::{json, Value};
+
+/// How closely a single function of the recompiled source matches the original.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionVerification {
+    /// The selector of the function
+    pub selector: String,
+    /// The number of unique branches of the function in the original bytecode, if it was found
+    pub original_branches: Option<u32>,
+    /// The number of unique branches of the function in the recompiled bytecode, if it was found
+    pub compiled_branches: Option<u32>,
+    /// The similarity of the function's jump structure, from 0 to 1
+    pub similarity: f64,
+}
+
+/// The result of recompiling the decompiled source with solc and comparing the recompiled
+/// bytecode's shape against the original.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerificationReport {
+    /// The overall similarity of the recompiled bytecode, from 0 to 1
+    pub score: f64,
+    /// The functions of the original and recompiled bytecode, ordered by selector
+    pub functions: Vec<FunctionVerification>,
+    /// Why the decompiled source couldn't be recompiled, if it couldn't be
+    pub error: Option<String>,
+}
+
+impl VerificationReport {
+    /// The functions whose recompiled jump structure differs from the original, or which are
+    /// missing from either bytecode.
+    pub fn diverging(&self) -> impl Iterator<Item = &FunctionVerification> {
+        self.functions.iter().filter(|f| f.similarity < 1.0)
+    }
+
+    /// Serializes the report as JSON.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "score": self.score,
+            "error": self.error,
+            "functions": self.functions.iter().map(|f| json!({
+                "selector": format!("0x{}", f.selector),
+                "originalBranches": f.original_branches,
+                "compiledBranches": f.compiled_branches,
+                "similarity": f.similarity,
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
@@ -13,7 +13,16 @@ mod interfaces;
 mod utils;
 
 // re-export the public interface
-pub use core::{decompile, DecompileResult};
+pub use core::{
+    decompile,
+    diff::{diff, Change, ContractDiff, DeclarationDiff, FunctionDiff},
+    DecompileResult,
+};
 pub use error::Error;
 pub use heimdall_vm::core::hardfork::HardFork;
-pub use interfaces::{DecompilerArgs, DecompilerArgsBuilder};
+pub use interfaces::{
+    ConstructorArgument, ConstructorInfo, DecompilerArgs, DecompilerArgsBuilder, DiamondFacet,
+    DiffArgs, DiffArgsBuilder, FunctionResult, FunctionResultSender, FunctionVerification,
+    Immutable, OutputFormat, ProxyInfo, ProxyType, SourceMap, StorageLayout, StorageLayoutEntry,
+    StorageType, StructMember, VerificationReport,
+};
@@ -74,6 +74,31 @@ pragma solidity >=0.8.0;
 
 contract DecompiledContract {".to_string();
 
+    /// the static header for decompiled vyper contracts
+    pub static ref DECOMPILED_SOURCE_HEADER_VY: String =
+"# @title            Decompiled Contract
+# @author           Jonathan Becker <jonathan@jbecker.dev>
+# @custom:version   heimdall-rs v{}
+#
+# @notice           This contract was decompiled using the heimdall-rs decompiler.
+#                     It was generated directly by tracing the EVM opcodes from this contract.
+#                     As a result, it may not compile or even be valid vyper code.
+#                     Despite this, it should be obvious what each function does. Overall
+#                     logic should have been preserved throughout decompiling.
+#
+# @custom:github    You can find the open-source decompiler here:
+#                       https://heimdall.rs
+".to_string();
+
+    /// used to detect typed variable declarations in solidity logic, e.g. `uint256 var_a = 1;`
+    pub static ref VARIABLE_DECLARATION_REGEX: Regex = Regex::new(r"^([a-zA-Z0-9_]+(?:\[\])?)(?: memory| calldata| storage)? ([a-zA-Z_][a-zA-Z0-9_]*) = (.*)$").expect("failed to build regex");
+
+    /// used to detect boolean literals
+    pub static ref BOOLEAN_LITERAL_REGEX: Regex = Regex::new(r"\b(true|false)\b").expect("failed to build regex");
+
+    /// used to detect negations, but not inequalities
+    pub static ref NEGATION_REGEX: Regex = Regex::new(r"!(?!=)").expect("failed to build regex");
+
     /// the static header for decompiled yul contracts
     pub static ref DECOMPILED_SOURCE_HEADER_YUL: String =
 "/// @title            Decompiled Contract
@@ -12,8 +12,11 @@ use heimdall_vm::core::{
 use tracing::{debug, trace};
 
 use crate::{
-    core::analyze::{AnalyzerState, AnalyzerType},
-    interfaces::{AnalyzedFunction, CalldataFrame, TypeHeuristic},
+    core::{
+        analyze::{AnalyzerState, AnalyzerType},
+        loops::is_constant,
+    },
+    interfaces::{AnalyzedFunction, CalldataFrame, StorageFrame, TypeHeuristic},
     utils::constants::{AND_BITMASK_REGEX, AND_BITMASK_REGEX_2, STORAGE_ACCESS_REGEX},
     Error,
 };
@@ -103,12 +106,23 @@ pub(crate) fn argument_heuristic<'a>(
                     .collect::<Vec<String>>()
                     .join(", ");
 
+                // words which aren't abi-encoded as dynamic data are returned as a tuple
+                let tuple_words = (size > 32 &&
+                    size.is_multiple_of(32) &&
+                    return_memory_operations.len() == size / 32 &&
+                    !is_dynamic_offset(&return_memory_operations[0]))
+                .then_some(&return_memory_operations);
+
                 // add the return statement to the function logic
                 if analyzer_state.analyzer_type == AnalyzerType::Solidity {
                     if return_memory_operations.len() <= 1 {
                         function
                             .logic
                             .push(format!("return {return_memory_operations_solidified};"));
+                    } else if tuple_words.is_some() {
+                        function
+                            .logic
+                            .push(format!("return ({return_memory_operations_solidified});"));
                     } else {
                         function.logic.push(format!(
                             "return abi.encodePacked({return_memory_operations_solidified});"
@@ -128,70 +142,29 @@ pub(crate) fn argument_heuristic<'a>(
                     return Ok(());
                 }
 
-                // if the any input op is ISZERO(x), this is a boolean return
-                if return_memory_operations.iter().any(|x| x.operation.opcode == ISZERO) {
-                    function.returns = Some(String::from("bool"));
-                }
-                // if the input op is any of the following, it is a uint256 return
-                // this is because these push numeric values onto the stack
-                else if return_memory_operations.iter().any(|x| {
-                    [0x31, 0x34, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x58, 0x5a]
-                        .contains(&x.operation.opcode)
-                }) {
-                    function.returns = Some(String::from("uint256"));
-                }
-                // if the input op is any of the following, it is an address return
-                // this is because these push address values onto the stack
-                else if return_memory_operations
-                    .iter()
-                    .any(|x| [0x30, 0x32, 0x33, 0x41].contains(&x.operation.opcode))
-                {
-                    function.returns = Some(String::from("address"));
-                }
-                // if the size of returndata is > 32, it must be a bytes or string return.
-                else if size > 32 {
-                    // some hardcoded function selectors where the return type is known to be a
-                    // string
-                    if ["06fdde03", "95d89b41", "6a98de4c", "9d2b0822", "1a0d4bca"]
-                        .contains(&function.selector.as_str())
-                    {
-                        function.returns = Some(String::from("string memory"));
-                    } else {
-                        function.returns = Some(String::from("bytes memory"));
-                    }
-                } else {
-                    // attempt to find a return type within the return memory operations
-                    let byte_size = match AND_BITMASK_REGEX
-                        .find(&return_memory_operations_solidified)
-                        .ok()
-                        .flatten()
-                    {
-                        Some(bitmask) => {
-                            let cast = bitmask.as_str();
-
-                            cast.matches("ff").count()
-                        }
-                        None => match AND_BITMASK_REGEX_2
-                            .find(&return_memory_operations_solidified)
-                            .ok()
-                            .flatten()
+                function.returns = Some(match tuple_words {
+                    Some(words) => words
+                        .iter()
+                        .map(|word| return_type(std::slice::from_ref(word)))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    // if the size of returndata is > 32, it must be a bytes or string return.
+                    None if size > 32 => {
+                        // some hardcoded function selectors where the return type is known to be
+                        // a string
+                        if ["06fdde03", "95d89b41", "6a98de4c", "9d2b0822", "1a0d4bca"]
+                            .contains(&function.selector.as_str())
                         {
-                            Some(bitmask) => {
-                                let cast = bitmask.as_str();
-
-                                cast.matches("ff").count()
-                            }
-                            None => 32,
-                        },
-                    };
-
-                    // convert the cast size to a string
-                    let (_, cast_types) = byte_size_to_type(byte_size);
-                    function.returns = Some(cast_types[0].to_string());
-                }
+                            String::from("string memory")
+                        } else {
+                            String::from("bytes memory")
+                        }
+                    }
+                    None => return_type(&return_memory_operations),
+                });
 
                 // check if this is a state getter
-                if function.arguments.is_empty() {
+                if function.arguments.is_empty() && tuple_words.is_none() {
                     if let Some(storage_access) = STORAGE_ACCESS_REGEX
                         .find(&return_memory_operations_solidified)
                         .unwrap_or(None)
@@ -296,3 +269,46 @@ pub(crate) fn argument_heuristic<'a>(
         Ok(())
     })
 }
+
+/// The type of a value returned from the given memory words.
+fn return_type(operations: &[StorageFrame]) -> String {
+    // if the any input op is ISZERO(x), this is a boolean return
+    if operations.iter().any(|x| x.operation.opcode == ISZERO) {
+        return String::from("bool");
+    }
+
+    // if the input op is any of the following, it is a uint256 return
+    // this is because these push numeric values onto the stack
+    if operations.iter().any(|x| {
+        [0x31, 0x34, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x58, 0x5a]
+            .contains(&x.operation.opcode)
+    }) {
+        return String::from("uint256");
+    }
+
+    // if the input op is any of the following, it is an address return
+    // this is because these push address values onto the stack
+    if operations.iter().any(|x| [0x30, 0x32, 0x33, 0x41].contains(&x.operation.opcode)) {
+        return String::from("address");
+    }
+
+    // attempt to find a return type within the return memory operations
+    let solidified =
+        operations.iter().map(|x| x.operation.solidify()).collect::<Vec<_>>().join(", ");
+    let byte_size = match AND_BITMASK_REGEX.find(&solidified).ok().flatten() {
+        Some(bitmask) => bitmask.as_str().matches("ff").count(),
+        None => match AND_BITMASK_REGEX_2.find(&solidified).ok().flatten() {
+            Some(bitmask) => bitmask.as_str().matches("ff").count(),
+            None => 32,
+        },
+    };
+
+    // convert the cast size to a string
+    let (_, cast_types) = byte_size_to_type(byte_size);
+    cast_types[0].to_string()
+}
+
+/// Whether the memory word is the offset which starts abi-encoded dynamic data, such as a string.
+fn is_dynamic_offset(word: &StorageFrame) -> bool {
+    word.value == U256::from(32) && is_constant(&word.operation)
+}
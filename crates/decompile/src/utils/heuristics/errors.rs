@@ -0,0 +1,73 @@
+use alloy::primitives::U256;
+use futures::future::BoxFuture;
+use heimdall_vm::core::{
+    opcodes::{MSTORE, MSTORE8, REVERT},
+    vm::State,
+};
+
+use crate::{
+    core::analyze::AnalyzerState,
+    interfaces::{AnalyzedFunction, StorageFrame},
+    utils::heuristics::{guess_type, merge_types},
+    Error,
+};
+
+/// The selectors of solidity's `Error(string)` and `Panic(uint256)`, which aren't custom errors.
+const BUILTIN_ERRORS: [[u8; 4]; 2] = [[0x08, 0xc3, 0x79, 0xa0], [0x4e, 0x48, 0x7b, 0x71]];
+
+/// Records the custom errors the function reverts with, and the types of their arguments, for
+/// analyzers which don't lift the function's body. The memory the function writes is tracked, so
+/// that the arguments' types can be guessed from the operations which produced them.
+pub(crate) fn error_heuristic<'a>(
+    function: &'a mut AnalyzedFunction,
+    state: &'a State,
+    _: &'a mut AnalyzerState,
+) -> BoxFuture<'a, Result<(), Error>> {
+    Box::pin(async move {
+        let instruction = &state.last_instruction;
+        match instruction.opcode {
+            MSTORE | MSTORE8 => {
+                function.memory.insert(
+                    instruction.inputs[0],
+                    StorageFrame {
+                        operation: instruction.input_operations[1].clone(),
+                        value: instruction.inputs[1],
+                    },
+                );
+            }
+            REVERT => {
+                let offset: usize = instruction.inputs[0].try_into().unwrap_or(0);
+                let size: usize = instruction.inputs[1].try_into().unwrap_or(0);
+                let revert_data = state.memory.read(offset, size);
+                if let Some(selector) = revert_data
+                    .get(0..4)
+                    .filter(|selector| !BUILTIN_ERRORS.iter().any(|builtin| builtin == *selector))
+                {
+                    record_custom_error(function, U256::from_be_slice(selector), offset, size);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    })
+}
+
+/// Records that the function reverts with the custom error with the given selector, guessing the
+/// types of its arguments, which are abi-encoded one word each after the selector in the `size`
+/// bytes of memory at `offset`.
+pub(crate) fn record_custom_error(
+    function: &mut AnalyzedFunction,
+    selector: U256,
+    offset: usize,
+    size: usize,
+) {
+    let types = (0..size.saturating_sub(4).min(2048) / 32)
+        .map(|i| {
+            let word = function.memory.get(&U256::from(offset.saturating_add(4 + i * 32)));
+            guess_type(word.map(|frame| &frame.operation))
+        })
+        .collect();
+    function.errors.insert(selector);
+    merge_types(function.error_arguments.entry(selector).or_default(), types);
+}
@@ -6,7 +6,8 @@ use heimdall_vm::core::vm::State;
 
 use crate::{
     core::analyze::{AnalyzerState, AnalyzerType},
-    interfaces::AnalyzedFunction,
+    interfaces::{AnalyzedFunction, EventParameters, StorageFrame},
+    utils::heuristics::{guess_type, merge_types},
     Error,
 };
 
@@ -25,46 +26,51 @@ pub(crate) fn event_heuristic<'a>(
             // insert this selector into events
             function.events.insert(selector);
 
-            // decode the data field
-            let data_mem_ops = function.get_memory_range(
-                state.last_instruction.inputs[0],
-                state.last_instruction.inputs[1],
-            );
-            let data_mem_ops_solidified = data_mem_ops
-                .iter()
-                .map(|x| x.operation.solidify())
-                .collect::<Vec<String>>()
-                .join(", ");
+            // the data is abi-encoded, so each word of its head is either a static parameter or
+            // the offset of a dynamic one
+            let offset: usize = state.last_instruction.inputs[0].try_into().unwrap_or(0);
+            let size: usize = state.last_instruction.inputs[1].try_into().unwrap_or(0);
+            let words = (0..size.min(2048) / 32)
+                .map(|i| function.memory.get(&U256::from(offset.saturating_add(i * 32))))
+                .collect::<Vec<_>>();
+            let head = data_head(&words, size);
+
+            let topics = (0..event.topics.len().saturating_sub(1))
+                .map(|i| &state.last_instruction.input_operations[i + 3])
+                .collect::<Vec<_>>();
+            let parameters = EventParameters {
+                indexed: topics.iter().map(|topic| guess_type(Some(topic))).collect(),
+                data: head
+                    .iter()
+                    .map(|word| match word {
+                        DataWord::Static(frame) => guess_type(frame.map(|frame| &frame.operation)),
+                        DataWord::Dynamic(_) => "bytes".to_string(),
+                    })
+                    .collect(),
+            };
+            let known = function
+                .event_parameters
+                .entry(selector)
+                .or_insert_with(|| EventParameters { indexed: Vec::new(), data: Vec::new() });
+            merge_types(&mut known.indexed, parameters.indexed);
+            merge_types(&mut known.data, parameters.data);
 
             // add the event emission to the function's logic
             if analyzer_state.analyzer_type == AnalyzerType::Solidity {
-                function.logic.push(format!(
-                    "emit Event_{}({}{});{}",
-                    &event
-                        .topics
-                        .first()
-                        .unwrap_or(&U256::ZERO)
-                        .to_lower_hex()
-                        .replacen("0x", "", 1)[0..8],
-                    event
-                        .topics
-                        .get(1..)
-                        .map(|topics| {
-                            let mut solidified_topics: Vec<String> = Vec::new();
-                            for (i, _) in topics.iter().enumerate() {
-                                solidified_topics.push(
-                                    state.last_instruction.input_operations[i + 3].solidify(),
-                                );
-                            }
+                let arguments = topics
+                    .iter()
+                    .map(|topic| topic.solidify())
+                    .chain(head.iter().map(|word| match word {
+                        DataWord::Static(Some(frame)) => frame.operation.solidify(),
+                        DataWord::Static(None) => "0".to_string(),
+                        DataWord::Dynamic(length) => dynamic_argument(*length),
+                    }))
+                    .collect::<Vec<String>>();
 
-                            if !event.data.is_empty() && !topics.is_empty() {
-                                format!("{}, ", solidified_topics.join(", "))
-                            } else {
-                                solidified_topics.join(", ")
-                            }
-                        })
-                        .unwrap_or_else(|| "".to_string()),
-                    data_mem_ops_solidified,
+                function.logic.push(format!(
+                    "emit Event_{}({});{}",
+                    &selector.to_lower_hex().replacen("0x", "", 1)[0..8],
+                    arguments.join(", "),
                     if anonymous { " // anonymous event" } else { "" }
                 ));
             }
@@ -73,3 +79,47 @@ pub(crate) fn event_heuristic<'a>(
         Ok(())
     })
 }
+
+/// A word in the head of an event's abi-encoded data.
+enum DataWord<'a> {
+    /// A static parameter, written to memory by the given operation (if it was written)
+    Static(Option<&'a StorageFrame>),
+    /// The offset of a dynamic parameter, whose length is written to memory by the given
+    /// operation (if it was written)
+    Dynamic(Option<&'a StorageFrame>),
+}
+
+/// Splits the head of the event's abi-encoded data into its parameters. Words which are in-bounds
+/// offsets past the head point to dynamic parameters, and the first of them marks the end of the
+/// head.
+fn data_head<'a>(words: &[Option<&'a StorageFrame>], size: usize) -> Vec<DataWord<'a>> {
+    let is_offset = |i: usize, word: &Option<&StorageFrame>| {
+        word.and_then(|word| usize::try_from(word.value).ok()).filter(|offset| {
+            offset % 32 == 0 && *offset > i * 32 && *offset < size && offset / 32 < words.len()
+        })
+    };
+    let head_len = words
+        .iter()
+        .enumerate()
+        .find_map(|(i, word)| is_offset(i, word))
+        .map(|offset| offset / 32)
+        .unwrap_or(words.len());
+
+    words[..head_len]
+        .iter()
+        .enumerate()
+        .map(|(i, word)| match is_offset(i, word).filter(|offset| *offset >= head_len * 32) {
+            Some(offset) => DataWord::Dynamic(words[offset / 32]),
+            None => DataWord::Static(*word),
+        })
+        .collect()
+}
+
+/// The argument emitted for a dynamic parameter, which is read from the length written before
+/// its contents, e.g. `var_a` for `var_a.length`.
+fn dynamic_argument(length: Option<&StorageFrame>) -> String {
+    length
+        .map(|length| length.operation.solidify())
+        .and_then(|length| length.strip_suffix(".length").map(|value| value.to_string()))
+        .unwrap_or_else(|| "\"\"".to_string())
+}
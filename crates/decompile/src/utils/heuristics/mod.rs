@@ -1,22 +1,32 @@
+use alloy::primitives::U256;
 use futures::future::BoxFuture;
-use heimdall_vm::core::vm::State;
+use heimdall_vm::core::{
+    opcodes::{
+        WrappedInput, WrappedOpcode, ADDRESS, AND, CALLER, EQ, GT, ISZERO, LT, ORIGIN, SGT, SLT,
+    },
+    vm::State,
+};
 
 use crate::{core::analyze::AnalyzerState, interfaces::AnalyzedFunction, Error};
 
 // import heuristics
 mod arguments;
+mod errors;
 mod events;
 mod extcall;
 mod modifiers;
 mod solidity;
+mod storage;
 mod yul;
 
 // re-export heuristics
 pub(crate) use arguments::argument_heuristic;
+pub(crate) use errors::{error_heuristic, record_custom_error};
 pub(crate) use events::event_heuristic;
 pub(crate) use extcall::extcall_heuristic;
-pub(crate) use modifiers::modifier_heuristic;
+pub(crate) use modifiers::{modifier_heuristic, rejects_callvalue_before};
 pub(crate) use solidity::solidity_heuristic;
+pub(crate) use storage::storage_heuristic;
 pub(crate) use yul::yul_heuristic;
 
 /// A heuristic is a function that takes a function and a state and modifies the function based on
@@ -45,3 +55,39 @@ impl Heuristic {
         (self.implementation)(function, state, analyzer_state).await
     }
 }
+
+/// Guesses the type of a value from the operation which produced it, such as an argument of a
+/// custom error or an event. Unknown values are `uint256`.
+pub(crate) fn guess_type(operation: Option<&WrappedOpcode>) -> String {
+    let Some(operation) = operation else {
+        return "uint256".to_string();
+    };
+
+    // addresses are masked to 20 bytes
+    let address_mask = (U256::from(1) << 160) - U256::from(1);
+    let is_address_mask = |input: &WrappedInput| match input {
+        WrappedInput::Raw(value) => *value == address_mask,
+        WrappedInput::Opcode(push) => push.inputs == [WrappedInput::Raw(address_mask)],
+    };
+    match operation.opcode {
+        AND if operation.inputs.iter().any(is_address_mask) => "address".to_string(),
+        CALLER | ORIGIN | ADDRESS => "address".to_string(),
+        ISZERO | EQ | LT | GT | SLT | SGT => "bool".to_string(),
+        _ => "uint256".to_string(),
+    }
+}
+
+/// Merges the types guessed for the arguments of another occurrence of the same error or event
+/// into those already known, preferring the more specific guess of each argument.
+pub(crate) fn merge_types(known: &mut Vec<String>, types: Vec<String>) {
+    if known.is_empty() {
+        *known = types;
+        return;
+    }
+
+    known.iter_mut().zip(types).filter(|(known, _)| *known == "uint256").for_each(
+        |(known, typ)| {
+            *known = typ;
+        },
+    );
+}
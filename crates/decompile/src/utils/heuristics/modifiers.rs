@@ -1,8 +1,9 @@
 use futures::future::BoxFuture;
+use heimdall_common::utils::strings::decode_hex;
 use heimdall_vm::{
     core::{
-        opcodes::{OpCodeInfo, JUMPI},
-        vm::State,
+        opcodes::{OpCodeInfo, WrappedOpcode, JUMPI},
+        vm::{State, VM},
     },
     w_callvalue, w_iszero,
 };
@@ -39,10 +40,10 @@ pub(crate) fn modifier_heuristic<'a>(
         }
 
         // if the instruction is a JUMPI with non-zero CALLVALUE requirement, the function is
-        // non-payable exactly: ISZERO(CALLVALUE())
+        // non-payable exactly
         if function.payable &&
             state.last_instruction.opcode == JUMPI &&
-            state.last_instruction.input_operations[1] == w_iszero!(w_callvalue!())
+            is_callvalue_check(&state.last_instruction.input_operations[1])
         {
             debug!(
                 "conditional at instruction {} indicates a non-payable function",
@@ -54,3 +55,34 @@ pub(crate) fn modifier_heuristic<'a>(
         Ok(())
     })
 }
+
+/// Whether the condition of a JUMPI rejects calls with value: ISZERO(CALLVALUE()) for solidity,
+/// which jumps over a revert, or CALLVALUE() for vyper, which jumps to one.
+fn is_callvalue_check(condition: &WrappedOpcode) -> bool {
+    *condition == w_iszero!(w_callvalue!()) || *condition == w_callvalue!()
+}
+
+/// Whether calls with value are rejected before the dispatcher reaches the function at the entry
+/// point. Solidity checks this once for every function when none of them are payable, so the
+/// check isn't part of the function's own trace.
+pub(crate) fn rejects_callvalue_before(evm: &VM, selector: &str, entry_point: u128) -> bool {
+    let mut evm = evm.clone();
+    evm.reset();
+    evm.calldata = decode_hex(selector).unwrap_or_default();
+
+    while evm.bytecode.len() >= evm.instruction as usize && evm.instruction <= entry_point {
+        let Ok(state) = evm.step() else {
+            return false;
+        };
+        if state.last_instruction.opcode == JUMPI &&
+            is_callvalue_check(&state.last_instruction.input_operations[1])
+        {
+            return true;
+        }
+        if evm.exitcode != 255 || !evm.returndata.is_empty() {
+            break;
+        }
+    }
+
+    false
+}
@@ -7,10 +7,16 @@ use heimdall_vm::core::vm::State;
 use crate::{
     core::analyze::AnalyzerState,
     interfaces::{AnalyzedFunction, StorageFrame},
-    utils::constants::VARIABLE_SIZE_CHECK_REGEX,
+    utils::{
+        constants::VARIABLE_SIZE_CHECK_REGEX, heuristics::record_custom_error,
+        selectors::error_selector,
+    },
     Error,
 };
 
+/// The note which replaces the overflow checks solc 0.8.0+ emits for checked arithmetic.
+const CHECKED_ARITHMETIC_NOTE: &str = "// checked arithmetic, which panics on overflow";
+
 pub(crate) fn solidity_heuristic<'a>(
     function: &'a mut AnalyzedFunction,
     state: &'a State,
@@ -122,6 +128,31 @@ pub(crate) fn solidity_heuristic<'a>(
                 ));
             }
 
+            // MCOPY
+            0x5e => {
+                let memory_offset = &instruction.input_operations[0];
+                let destination = instruction.inputs[0];
+                let source_offset = instruction.inputs[1];
+                let size_bytes = instruction.inputs[2];
+
+                // the copied words keep the operations which stored them
+                let source_end = source_offset.saturating_add(size_bytes);
+                let copied = function
+                    .memory
+                    .iter()
+                    .filter(|(offset, _)| (source_offset..source_end).contains(*offset))
+                    .map(|(offset, frame)| (destination + (*offset - source_offset), frame.clone()))
+                    .collect::<Vec<_>>();
+                function.memory.extend(copied);
+
+                function.logic.push(format!(
+                    "memory[{}] = memory[{}:{}];",
+                    memory_offset.solidify(),
+                    source_offset,
+                    source_end
+                ));
+            }
+
             // CREATE / CREATE2
             0xf0 | 0xf5 => {
                 function.logic.push(format!(
@@ -164,10 +195,20 @@ pub(crate) fn solidity_heuristic<'a>(
                         },
                         None => "decoding error".to_string(),
                     };
+
+                    // SafeMath checks arithmetic for overflows before solc 0.8.0, after which the
+                    // compiler checks it itself. the checks are noted, rather than rendered
+                    let check = (!analyzer_state.checked_arithmetic &&
+                        revert_string.starts_with("SafeMath: "))
+                    .then(|| {
+                        format!("// checked by SafeMath, which reverts with \"{revert_string}\"")
+                    });
                     revert_logic = match analyzer_state.jumped_conditional.clone() {
                         Some(condition) => {
                             analyzer_state.jumped_conditional = None;
-                            format!("require({condition}, \"{revert_string}\");")
+                            check.unwrap_or_else(|| {
+                                format!("require({condition}, \"{revert_string}\");")
+                            })
                         }
                         None => {
                             // loop backwards through logic to find the last IF statement
@@ -178,8 +219,9 @@ pub(crate) fn solidity_heuristic<'a>(
                                         None => break,
                                     };
 
-                                    function.logic[i] =
-                                        format!("require({conditional}, \"{revert_string}\");");
+                                    function.logic[i] = check.clone().unwrap_or_else(|| {
+                                        format!("require({conditional}, \"{revert_string}\");")
+                                    });
                                 }
                             }
                             return Ok(());
@@ -190,11 +232,29 @@ pub(crate) fn solidity_heuristic<'a>(
                 else if !revert_data.starts_with(&[0x4e, 0x48, 0x7b, 0x71]) {
                     let custom_error_placeholder = match revert_data.get(0..4) {
                         Some(selector) => {
-                            function.errors.insert(U256::from_be_slice(selector));
+                            let selector = U256::from_be_slice(selector);
+                            record_custom_error(function, selector, offset, size);
+
+                            // the arguments are abi-encoded after the selector, one word each
+                            let arguments = (0..size.saturating_sub(4).min(2048) / 32)
+                                .map(|i| {
+                                    function
+                                        .memory
+                                        .get(&U256::from(offset.saturating_add(4 + i * 32)))
+                                })
+                                .collect::<Vec<_>>();
+
                             format!(
-                                "CustomError_{}()",
-                                encode_hex_reduced(U256::from_be_slice(selector))
-                                    .replacen("0x", "", 1)
+                                "CustomError_{}({})",
+                                error_selector(&selector),
+                                arguments
+                                    .iter()
+                                    .map(|x| match x {
+                                        Some(x) => x.operation.solidify(),
+                                        None => "0".to_string(),
+                                    })
+                                    .collect::<Vec<String>>()
+                                    .join(", ")
                             )
                         }
                         None => "()".to_string(),
@@ -211,12 +271,14 @@ pub(crate) fn solidity_heuristic<'a>(
                         }
                         None => {
                             // loop backwards through logic to find the last IF statement
+                            let mut is_conditional = false;
                             for i in (0..function.logic.len()).rev() {
                                 if function.logic[i].starts_with("if") {
                                     let conditional = match analyzer_state.conditional_stack.pop() {
                                         Some(condition) => condition,
                                         None => break,
                                     };
+                                    is_conditional = true;
 
                                     if custom_error_placeholder == *"()" {
                                         function.logic[i] = format!("require({conditional});",);
@@ -227,11 +289,43 @@ pub(crate) fn solidity_heuristic<'a>(
                                     }
                                 }
                             }
+
+                            // custom errors which aren't raised by a condition are reverted with
+                            if !is_conditional && custom_error_placeholder != *"()" {
+                                function.logic.push(format!("revert {custom_error_placeholder};"));
+                            }
                             return Ok(());
                         }
                     }
-                } else {
-                    return Ok(());
+                }
+                // handle case with a compiler panic. failed assertions panic with code 0x01, and
+                // checked arithmetic with 0x11 when it overflows, which is noted rather than
+                // rendered. the other panics are raised by checks the compiler inserts, so they're
+                // left out
+                else {
+                    let code = revert_data.get(4..36).map(U256::from_be_slice).unwrap_or_default();
+                    let to_line = |condition: &str| match code == U256::from(0x11) {
+                        true => CHECKED_ARITHMETIC_NOTE.to_string(),
+                        false => format!("assert({condition});"),
+                    };
+                    if code != U256::from(0x01) && code != U256::from(0x11) {
+                        return Ok(());
+                    }
+
+                    revert_logic = match analyzer_state.jumped_conditional.take() {
+                        Some(condition) => to_line(&condition),
+                        None => {
+                            // the check is the last IF statement, whose branch this panic is in
+                            if let Some(i) =
+                                function.logic.iter().rposition(|line| line.starts_with("if"))
+                            {
+                                if let Some(conditional) = analyzer_state.conditional_stack.pop() {
+                                    function.logic[i] = to_line(&conditional);
+                                }
+                            }
+                            return Ok(());
+                        }
+                    };
                 }
 
                 function.logic.push(revert_logic);
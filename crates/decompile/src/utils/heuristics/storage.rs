@@ -0,0 +1,280 @@
+use alloy::primitives::U256;
+use futures::future::BoxFuture;
+use heimdall_vm::{
+    core::{
+        opcodes::{
+            WrappedInput, WrappedOpcode, ADDRESS, AND, CALLDATALOAD, CALLER, DIV, ORIGIN, PUSH0,
+            PUSH32, SHR, SLOAD,
+        },
+        types::byte_size_to_type,
+        vm::State,
+    },
+    w_sload,
+};
+
+use crate::{
+    core::analyze::AnalyzerState,
+    interfaces::{AnalyzedFunction, StorageAccess, StorageSlot},
+    Error,
+};
+
+/// The maximum distance between a hashed slot and an access for the access to be considered part
+/// of the value stored at the hashed slot (e.g. a struct member or an array element).
+const MAX_HASHED_SLOT_OFFSET: u64 = 1 << 16;
+
+/// Records the storage locations accessed by the function, so the contract's storage layout can
+/// be recovered after analysis.
+pub(crate) fn storage_heuristic<'a>(
+    function: &'a mut AnalyzedFunction,
+    state: &'a State,
+    analyzer_state: &'a mut AnalyzerState,
+) -> BoxFuture<'a, Result<(), Error>> {
+    Box::pin(async move {
+        let instruction = &state.last_instruction;
+
+        match instruction.opcode {
+            // SHA3
+            0x20 => {
+                let Some(hash) = instruction.outputs.first() else { return Ok(()) };
+                let offset: usize = instruction.inputs[0].try_into().unwrap_or(usize::MAX);
+                let size: usize = instruction.inputs[1].try_into().unwrap_or(usize::MAX);
+
+                // mappings hash `key . slot`, while dynamic arrays hash `slot`
+                let hashed_slot = match size {
+                    0x40 => {
+                        let base = U256::from_be_slice(&state.memory.read(offset + 0x20, 0x20));
+                        let key_type = function
+                            .memory
+                            .get(&instruction.inputs[0])
+                            .map(|frame| operation_type(&frame.operation, function))
+                            .unwrap_or_else(|| "bytes32".to_string());
+                        resolve_slot(base, analyzer_state).map(|(base, _)| StorageSlot::Mapping {
+                            base: Box::new(base),
+                            key_type,
+                        })
+                    }
+                    0x20 => {
+                        let base = U256::from_be_slice(&state.memory.read(offset, 0x20));
+                        resolve_slot(base, analyzer_state)
+                            .map(|(base, _)| StorageSlot::Array { base: Box::new(base) })
+                    }
+                    _ => None,
+                };
+
+                if let Some(hashed_slot) = hashed_slot {
+                    analyzer_state.hashed_slots.insert(*hash, hashed_slot);
+                }
+            }
+
+            // SLOAD
+            0x54 => {
+                if let Some((slot, member)) = resolve_slot(instruction.inputs[0], analyzer_state) {
+                    analyzer_state.loaded_slots.insert(
+                        w_sload!(instruction.input_operations[0].clone()),
+                        (slot.clone(), member),
+                    );
+                    function.storage_accesses.insert(StorageAccess {
+                        slot,
+                        member,
+                        offset: 0,
+                        size: 32,
+                    });
+                }
+            }
+
+            // SSTORE
+            0x55 => {
+                if let Some((slot, member)) = resolve_slot(instruction.inputs[0], analyzer_state) {
+                    // packed writes preserve the rest of the slot by masking out the bits being
+                    // written, e.g. `(storage[0x00] & ~0xff) | value`
+                    let (offset, size) = find_preserving_mask(&instruction.input_operations[1], 4)
+                        .and_then(|mask| mask_field(!mask))
+                        .unwrap_or((0, 32));
+                    function.storage_accesses.insert(StorageAccess { slot, member, offset, size });
+                }
+            }
+
+            // AND
+            0x16 => {
+                // packed reads shift the slot's value down to the variable, then mask it, e.g.
+                // `(storage[0x00] >> 0xa0) & 0xff`. masks which don't start at the shifted value's
+                // lowest bit are preserving the rest of the slot for a packed write instead
+                for (i, operation) in instruction.input_operations.iter().enumerate() {
+                    let Some((slot, member, shift)) = loaded_slot(operation, analyzer_state) else {
+                        continue;
+                    };
+                    if constant(&instruction.input_operations[1 - i]).is_none() ||
+                        !shift.is_multiple_of(8)
+                    {
+                        continue;
+                    }
+
+                    if let Some((0, size)) = mask_field(instruction.inputs[1 - i]) {
+                        let offset = shift / 8;
+                        if offset + size <= 32 {
+                            function.storage_accesses.insert(StorageAccess {
+                                slot,
+                                member,
+                                offset,
+                                size,
+                            });
+                        }
+                    }
+                    break;
+                }
+            }
+
+            _ => {}
+        };
+
+        Ok(())
+    })
+}
+
+/// Resolves a concrete storage slot to the location it represents, using the hashes the function
+/// has computed so far. Also returns the index of the word within the value stored at a hashed
+/// location, e.g. the struct member of a mapping's value.
+fn resolve_slot(slot: U256, analyzer_state: &AnalyzerState) -> Option<(StorageSlot, usize)> {
+    // accesses just after a hashed slot belong to the value stored there, which is the closest
+    // hashed slot before them
+    if let Some((hash, hashed_slot)) = analyzer_state
+        .hashed_slots
+        .iter()
+        .filter(|(hash, _)| {
+            slot.checked_sub(**hash)
+                .is_some_and(|offset| offset < U256::from(MAX_HASHED_SLOT_OFFSET))
+        })
+        .max_by_key(|(hash, _)| **hash)
+    {
+        // array elements can't be told apart from members of an element
+        let member = match hashed_slot {
+            StorageSlot::Mapping { .. } => (slot - hash).to::<usize>(),
+            _ => 0,
+        };
+        return Some((hashed_slot.clone(), member));
+    }
+
+    // state variables are assigned small, sequential slots
+    (slot < U256::from(u64::MAX)).then_some((StorageSlot::Value(slot), 0))
+}
+
+/// If the operation is a (shifted) SLOAD, returns the loaded location, its member and the shift in
+/// bits.
+fn loaded_slot(
+    operation: &WrappedOpcode,
+    analyzer_state: &AnalyzerState,
+) -> Option<(StorageSlot, usize, usize)> {
+    match operation.opcode {
+        SLOAD => analyzer_state
+            .loaded_slots
+            .get(operation)
+            .map(|(slot, member)| (slot.clone(), *member, 0)),
+        SHR => {
+            let shift: usize = constant(input_operation(operation, 0)?)?.try_into().ok()?;
+            let (slot, member, _) = loaded_slot(input_operation(operation, 1)?, analyzer_state)
+                .filter(|(_, _, shift)| *shift == 0)?;
+            Some((slot, member, shift))
+        }
+        // older compilers divide by `0x100 ** offset` instead of shifting
+        DIV => {
+            let divisor = constant(input_operation(operation, 1)?)?;
+            let (slot, member, _) = loaded_slot(input_operation(operation, 0)?, analyzer_state)
+                .filter(|(_, _, shift)| *shift == 0)?;
+            divisor.is_power_of_two().then(|| (slot, member, divisor.trailing_zeros()))
+        }
+        _ => None,
+    }
+}
+
+/// Finds the constant mask an SSTORE's value applies to the slot's previous value, if any.
+fn find_preserving_mask(operation: &WrappedOpcode, depth: usize) -> Option<U256> {
+    if operation.opcode == AND {
+        let sload = operation.inputs.iter().any(
+            |input| matches!(input, WrappedInput::Opcode(operation) if operation.opcode == SLOAD),
+        );
+        let mask = operation
+            .inputs
+            .iter()
+            .find_map(|input| match input {
+                WrappedInput::Opcode(operation) => constant(operation),
+                WrappedInput::Raw(_) => None,
+            })
+            .filter(|mask| *mask != U256::MAX);
+        if sload && mask.is_some() {
+            return mask;
+        }
+    }
+
+    if depth == 0 {
+        return None;
+    }
+    operation.inputs.iter().find_map(|input| match input {
+        WrappedInput::Opcode(operation) => find_preserving_mask(operation, depth - 1),
+        WrappedInput::Raw(_) => None,
+    })
+}
+
+/// Converts a mask of contiguous, byte-aligned set bits into the byte offset and size it covers,
+/// e.g. `0xffff00` covers 2 bytes at offset 1.
+fn mask_field(mask: U256) -> Option<(usize, usize)> {
+    if mask.is_zero() {
+        return None;
+    }
+
+    let shift = mask.trailing_zeros();
+    let bits = (mask >> shift).count_ones();
+    if (mask >> shift) != U256::MAX >> (256 - bits) ||
+        !shift.is_multiple_of(8) ||
+        !bits.is_multiple_of(8)
+    {
+        return None;
+    }
+
+    Some((shift / 8, bits / 8))
+}
+
+/// Infers the type of the value produced by the operation, e.g. a mapping key.
+fn operation_type(operation: &WrappedOpcode, function: &AnalyzedFunction) -> String {
+    match operation.opcode {
+        CALLER | ORIGIN | ADDRESS => "address".to_string(),
+        AND => operation
+            .inputs
+            .iter()
+            .find_map(|input| match input {
+                WrappedInput::Opcode(operation) => constant(operation).and_then(mask_field),
+                WrappedInput::Raw(_) => None,
+            })
+            .filter(|(offset, _)| *offset == 0)
+            .map(|(_, size)| byte_size_to_type(size).1[0].clone())
+            .unwrap_or_else(|| "bytes32".to_string()),
+        CALLDATALOAD => input_operation(operation, 0)
+            .and_then(constant)
+            .and_then(|offset| offset.checked_sub(U256::from(4)))
+            .and_then(|offset| usize::try_from(offset / U256::from(32)).ok())
+            .and_then(|index| function.arguments.get(&index))
+            .map(|argument| argument.potential_types()[0].clone())
+            .unwrap_or_else(|| "uint256".to_string()),
+        _ => "bytes32".to_string(),
+    }
+}
+
+/// Returns the value of the operation, if it is a constant.
+fn constant(operation: &WrappedOpcode) -> Option<U256> {
+    if !(PUSH0..=PUSH32).contains(&operation.opcode) {
+        return None;
+    }
+
+    match operation.inputs.first() {
+        Some(WrappedInput::Raw(value)) => Some(*value),
+        Some(WrappedInput::Opcode(_)) => None,
+        None => Some(U256::ZERO),
+    }
+}
+
+/// Returns the operation that produced the given input, if it isn't a raw value.
+fn input_operation(operation: &WrappedOpcode, index: usize) -> Option<&WrappedOpcode> {
+    match operation.inputs.get(index) {
+        Some(WrappedInput::Opcode(operation)) => Some(operation),
+        _ => None,
+    }
+}
@@ -77,11 +77,11 @@ pub(crate) fn yul_heuristic<'a>(
             }
 
             // STATICCALL, CALL, CALLCODE, DELEGATECALL, CREATE, CREATE2
-            // CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY, TSTORE,
+            // CALLDATACOPY, CODECOPY, EXTCODECOPY, RETURNDATACOPY, TSTORE, MCOPY,
             // SSTORE, RETURN, SELFDESTRUCT, LOG0, LOG1, LOG2, LOG3, LOG4
             // we simply want to add the operation to the function's logic
-            0x37 | 0x39 | 0x3c | 0x3e | 0x55 | 0x5d | 0xf0 | 0xf1 | 0xf2 | 0xf4 | 0xf5 | 0xfa |
-            0xff | 0xA0 | 0xA1 | 0xA2 | 0xA3 | 0xA4 => {
+            0x37 | 0x39 | 0x3c | 0x3e | 0x55 | 0x5d | 0x5e | 0xf0 | 0xf1 | 0xf2 | 0xf4 | 0xf5 |
+            0xfa | 0xff | 0xA0 | 0xA1 | 0xA2 | 0xA3 | 0xA4 => {
                 function.logic.push(format!(
                     "{}({})",
                     opcode_name(instruction.opcode).to_lowercase(),
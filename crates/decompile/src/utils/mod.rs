@@ -2,3 +2,4 @@ pub(crate) mod constants;
 pub(crate) mod heuristics;
 pub(crate) mod postprocessors;
 pub(crate) mod precompile;
+pub(crate) mod selectors;
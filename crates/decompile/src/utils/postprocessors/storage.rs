@@ -34,7 +34,16 @@ pub(crate) fn storage_postprocessor(
                 let i = state.storage_map.len() + 1;
 
                 // get the variable name
-                if storage_loc.contains("keccak256") {
+                if let Some((base_loc, member)) = struct_member_location(&storage_loc) {
+                    // members of a mapping's struct value are accessed through the mapping, e.g.
+                    // `storage[keccak256(var_a) + 0x01]` => `storage_map_a[var_a].field_1`
+                    let mut base_line = base_loc;
+                    storage_postprocessor(&mut base_line, state)?;
+
+                    let variable_name = format!("{base_line}.field_{member}");
+                    state.storage_map.insert(storage_loc.clone(), variable_name.clone());
+                    variable_name
+                } else if storage_loc.contains("keccak256") {
                     let keccak_range = find_balanced_encapsulator(&storage_loc, ('(', ')'))
                         .map_err(|_| eyre!("failed to extract keccak256 range"))?;
 
@@ -145,5 +154,40 @@ pub(crate) fn storage_postprocessor(
     Ok(())
 }
 
+/// If the storage location is a member of a mapping's struct value, e.g.
+/// `storage[keccak256(var_a) + 0x01]`, returns the location of the value and the member's index.
+fn struct_member_location(storage_loc: &str) -> Option<(String, usize)> {
+    let slot = storage_loc.strip_prefix("storage[keccak256")?.strip_suffix(']')?;
+    let keccak_range = find_balanced_encapsulator(slot, ('(', ')')).ok()?;
+    if keccak_range.start != 1 {
+        return None;
+    }
+
+    let member = slot.get(keccak_range.end + 1..)?.strip_prefix(" + 0x")?;
+    let member = usize::from_str_radix(member, 16).ok()?;
+    Some((format!("storage[keccak256{}]", &slot[..keccak_range.end + 1]), member))
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_struct_member_location() {
+        assert_eq!(
+            struct_member_location("storage[keccak256(var_a) + 0x04]"),
+            Some(("storage[keccak256(var_a)]".to_string(), 4))
+        );
+        assert_eq!(struct_member_location("storage[keccak256(var_a)]"), None);
+        assert_eq!(struct_member_location("storage[keccak256(var_a) + arg0]"), None);
+        assert_eq!(struct_member_location("storage[0x01]"), None);
+    }
+
+    #[test]
+    fn test_storage_postprocessor_struct_members() {
+        let mut state = PostprocessorState::default();
+        let mut line = "storage[keccak256(var_a) + 0x01] = storage[keccak256(var_a)];".to_string();
+        storage_postprocessor(&mut line, &mut state).unwrap();
+        assert_eq!(line, "storage_map_a[var_a].field_1 = storage_map_a[var_a];");
+    }
+}
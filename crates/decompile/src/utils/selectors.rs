@@ -0,0 +1,28 @@
+use alloy::primitives::U256;
+use heimdall_common::utils::strings::encode_hex;
+
+/// The zero-padded, 4-byte hex selector of a custom error, as stored in
+/// [`crate::interfaces::AnalyzedFunction::errors`]. Errors are resolved and named by this
+/// selector.
+pub(crate) fn error_selector(selector: &U256) -> String {
+    encode_hex(&selector.to_be_bytes::<32>()[28..])
+}
+
+/// The zero-padded, 32-byte hex topic of an event, as stored in
+/// [`crate::interfaces::AnalyzedFunction::events`]. Events are resolved by this topic, and named
+/// by its first 4 bytes.
+pub(crate) fn event_selector(selector: &U256) -> String {
+    encode_hex(&selector.to_be_bytes::<32>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selectors_are_zero_padded() {
+        assert_eq!(error_selector(&U256::from(0x00c379a0u64)), "00c379a0");
+        assert_eq!(event_selector(&U256::from(1u64)), format!("{}1", "0".repeat(63)));
+        assert_eq!(event_selector(&U256::from_be_slice(&[0xdd, 0xf2, 0x52, 0xad])).len(), 64);
+    }
+}
@@ -0,0 +1,134 @@
+use heimdall_vm::core::opcodes::{EQ, JUMP, JUMPDEST, JUMPI, PUSH1, PUSH32, PUSH4, XOR};
+use std::collections::{BTreeMap, HashSet};
+
+use super::Instruction;
+
+/// A function found in the dispatcher, by the pc it's dispatched to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Function {
+    /// The function's selector, as hex without a `0x` prefix
+    pub(crate) selector: String,
+    /// The pc of the `JUMPDEST` the dispatcher jumps to for the function
+    pub(crate) entry: usize,
+    /// The function's signature, if it was resolved
+    pub(crate) signature: Option<String>,
+}
+
+impl Function {
+    /// The name of the function's signature, or its selector if it wasn't resolved.
+    fn name(&self) -> &str {
+        self.signature
+            .as_deref()
+            .and_then(|signature| signature.split('(').next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.selector)
+    }
+}
+
+/// Annotates the instructions with synthetic labels and notes. Each `JUMPDEST` is labeled as a
+/// function's entry, e.g. `func_transfer_entry`, the head of a loop if a later jump targets it,
+/// e.g. `loop_head_0x1a3`, or `label_0x1a3` otherwise. Each jump whose target is pushed right
+/// before it is noted with the target's label, and each dispatch comparison with the function
+/// its selector matches. The byte of each unknown opcode is written as its immediate, so the
+/// disassembly can be assembled back into the bytecode.
+pub(crate) fn annotate(instructions: &mut [Instruction], functions: &[Function]) {
+    for instruction in instructions.iter_mut().filter(|instruction| instruction.name == "unknown") {
+        instruction.immediate = Some(format!("{:02x}", instruction.opcode));
+    }
+
+    let jumpdests = instructions
+        .iter()
+        .filter(|instruction| instruction.opcode == JUMPDEST)
+        .map(|instruction| instruction.pc)
+        .collect::<HashSet<_>>();
+
+    // the jumps whose target is a constant, by their index
+    let jumps = instructions
+        .windows(2)
+        .enumerate()
+        .filter_map(|(i, pair)| {
+            let [push, jump] = pair else { return None };
+            if !matches!(push.opcode, PUSH1..=PUSH32) || !matches!(jump.opcode, JUMP | JUMPI) {
+                return None;
+            }
+            let target = usize::from_str_radix(push.immediate.as_deref()?, 16).ok()?;
+            jumpdests.contains(&target).then_some((i + 1, target))
+        })
+        .collect::<Vec<_>>();
+
+    let mut labels = BTreeMap::new();
+    let mut names = HashSet::new();
+    for function in functions {
+        // overloaded functions share a name, so they're told apart by their selector
+        let label = match names.insert(function.name().to_string()) {
+            true => format!("func_{}_entry", function.name()),
+            false => format!("func_{}_{}_entry", function.name(), function.selector),
+        };
+        labels.entry(function.entry).or_insert(label);
+    }
+    for (i, target) in &jumps {
+        if instructions[*i].pc > *target {
+            labels.entry(*target).or_insert_with(|| format!("loop_head_{target:#x}"));
+        }
+    }
+    for jumpdest in &jumpdests {
+        labels.entry(*jumpdest).or_insert_with(|| format!("label_{jumpdest:#x}"));
+    }
+
+    for instruction in instructions.iter_mut() {
+        if let Some(label) = labels.get(&instruction.pc) {
+            instruction.label = Some(label.clone());
+        }
+    }
+    for (i, target) in jumps {
+        instructions[i].annotation = labels.get(&target).map(|label| format!("-> {label}"));
+    }
+
+    // a dispatcher compares the calldata's selector to each function's
+    for i in 0..instructions.len() {
+        let instruction = &instructions[i];
+        if instruction.opcode != PUSH4 ||
+            !instructions[i + 1..].iter().take(2).any(|next| matches!(next.opcode, EQ | XOR))
+        {
+            continue;
+        }
+        let Some(function) = functions.iter().find(|function| {
+            Some(function.selector.trim_start_matches("0x")) == instruction.immediate.as_deref()
+        }) else {
+            continue;
+        };
+        instructions[i].annotation = Some(format!(
+            "selector of {}",
+            function.signature.clone().unwrap_or_else(|| format!("0x{}", function.selector))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{disassemble_instructions, format::to_asm};
+    use heimdall_common::utils::strings::decode_hex;
+    use heimdall_vm::core::hardfork::HardFork;
+
+    #[test]
+    fn test_annotate() {
+        // dispatches 0xa9059cbb to 0x0d, whose body loops back to 0x0e
+        let bytecode =
+            decode_hex("63a9059cbb14600d57000000005b5b600e56").expect("invalid bytecode");
+        let mut instructions = disassemble_instructions(&bytecode, HardFork::Latest);
+        annotate(
+            &mut instructions,
+            &[Function {
+                selector: "a9059cbb".to_string(),
+                entry: 0x0d,
+                signature: Some("transfer(address,uint256)".to_string()),
+            }],
+        );
+
+        assert_eq!(
+            to_asm(&instructions, true),
+            "0 PUSH4 a9059cbb ; selector of transfer(address,uint256)\n5 EQ \n6 PUSH1 0d\n8 JUMPI  ; -> func_transfer_entry\n9 STOP \n10 STOP \n11 STOP \n12 STOP \nfunc_transfer_entry:\n13 JUMPDEST \nloop_head_0xe:\n14 JUMPDEST \n15 PUSH1 0e\n17 JUMP  ; -> loop_head_0xe\n"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use eyre::{eyre, Result};
+use heimdall_common::utils::strings::{decode_hex, encode_hex};
+use heimdall_vm::core::opcodes::{opcode_name, JUMP, JUMPDEST, JUMPI, PUSH1, PUSH32};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::{error::Error, interfaces::AssemblerArgs};
+
+/// An instruction of the disassembly being assembled.
+#[derive(Debug)]
+struct Line {
+    /// The line's number in the disassembly, for errors
+    number: usize,
+    /// The instruction's program counter in the disassembly, if it was given
+    pc: Option<usize>,
+    /// The instruction's opcode
+    opcode: u8,
+    /// The operand of a `PUSH`, either hex or the name of a label
+    operand: Option<String>,
+    /// The label a jump is annotated with, e.g. `; -> loop_head_0x1a3`
+    target: Option<String>,
+    /// The labels written just before the instruction
+    labels: Vec<String>,
+}
+
+/// The number of bytes the opcode pushes.
+fn push_size(opcode: u8) -> usize {
+    match opcode {
+        PUSH1..=PUSH32 => (opcode - PUSH1 + 1) as usize,
+        _ => 0,
+    }
+}
+
+/// Parses a program counter written by the disassembler, in base-10 or as hex.
+fn parse_counter(token: &str, decimal_counter: bool) -> Option<usize> {
+    match decimal_counter {
+        true => token.parse().ok(),
+        false => usize::from_str_radix(token.trim_start_matches("0x"), 16).ok(),
+    }
+}
+
+/// Parses a hex operand into bytes, e.g. `0x1` or `0001`.
+fn parse_hex(operand: &str) -> Option<Vec<u8>> {
+    let digits = operand.trim_start_matches("0x");
+    let digits = match digits.len() % 2 {
+        0 => digits.to_string(),
+        _ => format!("0{digits}"),
+    };
+    decode_hex(&digits).ok()
+}
+
+/// Parses the instructions of the disassembly. Comments after a `;` are ignored, except for the
+/// labels jumps are annotated with, and `label:` lines label the next instruction.
+fn parse(source: &str, decimal_counter: bool) -> Result<Vec<Line>> {
+    let mnemonics = (0..=u8::MAX)
+        .map(|opcode| (opcode_name(opcode), opcode))
+        .filter(|(name, _)| *name != "unknown")
+        .collect::<HashMap<_, _>>();
+
+    let mut lines = Vec::new();
+    let mut labels = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let number = i + 1;
+        let (code, comment) = line.split_once(';').unwrap_or((line, ""));
+        let mut tokens = code.split_whitespace().collect::<Vec<_>>();
+
+        match tokens.as_slice() {
+            [] => continue,
+            [label] if label.ends_with(':') => {
+                labels.push(label.trim_end_matches(':').to_string());
+                continue;
+            }
+            _ => {}
+        }
+
+        // the program counter is optional, as new instructions are written without one
+        let pc = match tokens.as_slice() {
+            [counter, _, ..] => parse_counter(counter, decimal_counter),
+            _ => None,
+        };
+        if pc.is_some() {
+            tokens.remove(0);
+        }
+
+        let (opcode, operand) = match tokens.as_slice() {
+            // the byte of an opcode which isn't active in the hardfork is written after it
+            [unknown, byte] if unknown.eq_ignore_ascii_case("unknown") => {
+                match parse_hex(byte).as_deref() {
+                    Some([opcode]) => (*opcode, None),
+                    _ => return Err(eyre!("line {number}: '{byte}' isn't a byte")),
+                }
+            }
+            [unknown] if unknown.eq_ignore_ascii_case("unknown") => {
+                return Err(eyre!(
+                    "line {number}: the byte of this unknown opcode isn't known. disassemble with '--annotate' to write it, or replace it with its byte, e.g. '0xfe'"
+                ))
+            }
+            [byte] if byte.starts_with("0x") => match parse_hex(byte).as_deref() {
+                Some([opcode]) => (*opcode, None),
+                _ => return Err(eyre!("line {number}: '{byte}' isn't a byte")),
+            },
+            [mnemonic, operand @ ..] => {
+                let opcode = mnemonics
+                    .get(mnemonic.to_uppercase().as_str())
+                    .ok_or_else(|| eyre!("line {number}: unknown instruction '{mnemonic}'"))?;
+                match operand {
+                    [] => (*opcode, None),
+                    [operand] => (*opcode, Some(operand.to_string())),
+                    _ => return Err(eyre!("line {number}: too many operands")),
+                }
+            }
+            [] => return Err(eyre!("line {number}: missing instruction")),
+        };
+        if push_size(opcode) == 0 && operand.is_some() {
+            return Err(eyre!("line {number}: {} takes no operand", opcode_name(opcode)));
+        }
+
+        let target = comment.split(';').find_map(|note| {
+            note.trim().strip_prefix("->").and_then(|target| target.split_whitespace().next())
+        });
+        lines.push(Line {
+            number,
+            pc,
+            opcode,
+            operand,
+            target: target.map(str::to_string),
+            labels: std::mem::take(&mut labels),
+        });
+    }
+
+    // only the last `PUSH` may have been cut off before its operand by the end of the code
+    if let Some(line) =
+        lines.iter().rev().skip(1).find(|line| push_size(line.opcode) > 0 && line.operand.is_none())
+    {
+        return Err(eyre!("line {}: {} needs an operand", line.number, opcode_name(line.opcode)));
+    }
+
+    Ok(lines)
+}
+
+/// Assembles the disassembly written by `heimdall disassemble`, which may have been edited, back
+/// into bytecode. Program counters are optional and recomputed, so instructions can be inserted
+/// and removed. A `PUSH` whose operand names a label pushes the label's new program counter, as
+/// does the `PUSH` before a jump annotated with the label it targets. The `PUSH` before any other
+/// jump is relocated if it pushes the original program counter of a `JUMPDEST`.
+pub fn assemble_source(source: &str, decimal_counter: bool) -> Result<Vec<u8>, Error> {
+    let lines = parse(source, decimal_counter)?;
+
+    // lay out the instructions at their new program counters
+    let mut pcs = Vec::with_capacity(lines.len());
+    let mut pc = 0;
+    for line in &lines {
+        pcs.push(pc);
+        pc += 1 + push_size(line.opcode);
+    }
+    let mut labels = HashMap::new();
+    for (line, pc) in lines.iter().zip(&pcs) {
+        for label in &line.labels {
+            if labels.insert(label.as_str(), *pc).is_some() {
+                return Err(eyre!("line {}: label '{label}' is defined twice", line.number).into());
+            }
+        }
+    }
+    let jumpdests = lines
+        .iter()
+        .zip(&pcs)
+        .filter(|(line, _)| line.opcode == JUMPDEST)
+        .filter_map(|(line, pc)| line.pc.map(|original| (original, *pc)))
+        .collect::<HashMap<_, _>>();
+
+    let mut bytecode = Vec::with_capacity(pc);
+    for (i, line) in lines.iter().enumerate() {
+        bytecode.push(line.opcode);
+        let Some(operand) = &line.operand else { continue };
+        let size = push_size(line.opcode);
+
+        let jump = lines.get(i + 1).filter(|next| matches!(next.opcode, JUMP | JUMPI));
+        let label = jump
+            .and_then(|jump| jump.target.as_deref())
+            .filter(|target| labels.contains_key(target))
+            .or_else(|| labels.contains_key(operand.as_str()).then_some(operand.as_str()));
+        let value = match label {
+            Some(label) => labels[label].to_be_bytes().to_vec(),
+            None => {
+                let value = parse_hex(operand).ok_or_else(|| {
+                    eyre!("line {}: '{operand}' is neither hex nor a label", line.number)
+                })?;
+                let target = value.iter().try_fold(0usize, |target, byte| {
+                    target.checked_mul(256)?.checked_add(*byte as usize)
+                });
+                let relocated = jump.and(target).and_then(|target| jumpdests.get(&target));
+                match relocated {
+                    Some(target) => target.to_be_bytes().to_vec(),
+                    None => value,
+                }
+            }
+        };
+
+        // the value is left-padded to the push's size, and must fit in it. the last `PUSH` may
+        // have been cut off by the end of the code, so its operand is emitted as written
+        // instead. it never executes, so its value doesn't matter
+        let truncated = label.is_none() && i + 1 == lines.len();
+        let start = match truncated {
+            true => 0,
+            false => value.iter().position(|byte| *byte != 0).unwrap_or(value.len()),
+        };
+        let value = &value[start..];
+        if value.len() > size {
+            return Err(eyre!(
+                "line {}: 0x{} doesn't fit in {}",
+                line.number,
+                encode_hex(value),
+                opcode_name(line.opcode)
+            )
+            .into());
+        }
+        if !truncated {
+            bytecode.extend(std::iter::repeat_n(0, size - value.len()));
+        }
+        bytecode.extend_from_slice(value);
+    }
+    debug!("assembled {} instructions", lines.len());
+
+    Ok(bytecode)
+}
+
+/// Assembles the disassembly given by `args` back into bytecode, returning it as hex with a `0x`
+/// prefix.
+pub fn assemble(args: AssemblerArgs) -> Result<String, Error> {
+    let source = args.get_source()?;
+    let bytecode = assemble_source(&source, args.decimal_counter)?;
+    info!("assembled {} bytes successfully", bytecode.len());
+    Ok(format!("0x{}", encode_hex(&bytecode)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{annotate::annotate, disassemble_instructions, format::to_asm};
+    use heimdall_vm::core::hardfork::HardFork;
+
+    #[test]
+    fn test_assemble_round_trip() {
+        let bytecode =
+            decode_hex("63a9059cbb14600d57000000005b5b600e560c").expect("invalid bytecode");
+        let mut instructions = disassemble_instructions(&bytecode, HardFork::Latest);
+        annotate(&mut instructions, &[]);
+
+        let asm = to_asm(&instructions, false);
+        assert_eq!(assemble_source(&asm, false).expect("failed to assemble"), bytecode);
+    }
+
+    #[test]
+    fn test_assemble_round_trip_truncated_push() {
+        // the code ends in a PUSH3 with only two of its three bytes, and in a PUSH2 with none
+        for bytecode in ["600162000012", "600161"] {
+            let bytecode = decode_hex(bytecode).expect("invalid bytecode");
+            let asm = to_asm(&disassemble_instructions(&bytecode, HardFork::Latest), false);
+            assert_eq!(assemble_source(&asm, false).expect("failed to assemble"), bytecode);
+        }
+    }
+
+    #[test]
+    fn test_assemble_relocates_jumps() {
+        // the JUMPDEST is moved by the inserted instructions, and labels are pushed by name and
+        // by the jump's annotation
+        let asm = "000000 CALLVALUE\nCALLVALUE\nPOP\n000001 PUSH1 06\n000003 JUMPI\n000004 STOP\n000005 STOP\n000006 JUMPDEST\nloop:\nPUSH2 loop\nPUSH1 00\nJUMP ; -> end\nend:\nSTOP\n";
+        assert_eq!(
+            encode_hex(&assemble_source(asm, false).expect("failed to assemble")),
+            "34345060085700005b610009600f5600"
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert!(assemble_source("000000 unknown", false).is_err());
+        assert!(assemble_source("PUSH1 0100", false).is_err());
+        assert!(assemble_source("ADD 01", false).is_err());
+        assert!(assemble_source("PUSH1\nSTOP", false).is_err());
+        assert!(assemble_source("FOO", false).is_err());
+    }
+}
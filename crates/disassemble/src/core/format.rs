@@ -0,0 +1,255 @@
+use serde_json::json;
+
+use super::Instruction;
+
+// extracting strings is only done when disassembling a target, which isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    super::sections::{Extracted, ExtractedKind, Section},
+    crate::interfaces::DisassemblerFormat,
+};
+
+/// The program counter of the instruction, in base-10 or as zero-padded hex.
+fn program_counter(instruction: &Instruction, decimal_counter: bool) -> String {
+    match decimal_counter {
+        true => instruction.pc.to_string(),
+        false => format!("{:06x}", instruction.pc),
+    }
+}
+
+/// Returns the instructions as assembly, one per line, prefixed by their program counter. Labels
+/// are written on their own line before their `JUMPDEST`, and annotations after a `;`.
+pub(crate) fn to_asm(instructions: &[Instruction], decimal_counter: bool) -> String {
+    instructions
+        .iter()
+        .map(|instruction| {
+            let label =
+                instruction.label.as_ref().map(|label| format!("{label}:\n")).unwrap_or_default();
+            let annotation = instruction
+                .annotation
+                .as_ref()
+                .map(|annotation| format!(" ; {annotation}"))
+                .unwrap_or_default();
+            format!(
+                "{label}{} {} {}{annotation}\n",
+                program_counter(instruction, decimal_counter),
+                instruction.name,
+                instruction.immediate.as_deref().unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+/// Returns the instructions as a JSON array of records.
+pub(crate) fn to_json(instructions: &[Instruction]) -> String {
+    let records = instructions
+        .iter()
+        .map(|instruction| {
+            json!({
+                "pc": instruction.pc,
+                "opcode": instruction.name,
+                "byte": format!("0x{:02x}", instruction.opcode),
+                "immediate": instruction.immediate.as_ref().map(|immediate| format!("0x{immediate}")),
+                "stack_inputs": instruction.inputs,
+                "stack_outputs": instruction.outputs,
+                "gas": instruction.gas,
+                "label": instruction.label,
+                "annotation": instruction.annotation,
+                "section": instruction.section,
+            })
+        })
+        .collect::<Vec<_>>();
+    format!("{:#}", json!(records))
+}
+
+/// Returns the instructions as a CSV table, with a header row.
+pub(crate) fn to_csv(instructions: &[Instruction]) -> String {
+    let mut lines =
+        vec!["pc,opcode,byte,immediate,stack_inputs,stack_outputs,gas,label,annotation,section"
+            .to_string()];
+    lines.extend(instructions.iter().map(|instruction| {
+        format!(
+            "{},{},0x{:02x},{},{},{},{},{},{},{}",
+            instruction.pc,
+            instruction.name,
+            instruction.opcode,
+            instruction
+                .immediate
+                .as_ref()
+                .map(|immediate| format!("0x{immediate}"))
+                .unwrap_or_default(),
+            instruction.inputs,
+            instruction.outputs,
+            instruction.gas,
+            instruction.label.as_deref().unwrap_or_default(),
+            csv_field(instruction.annotation.as_deref().unwrap_or_default()),
+            instruction.section.map(|section| section.to_string()).unwrap_or_default(),
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Quotes a CSV field if it contains a delimiter, a quote or a newline.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// Returns the instructions as a table with aligned columns, for reading. If the disassembly was
+/// annotated, a column notes each instruction's label and annotation.
+pub(crate) fn to_table(instructions: &[Instruction], decimal_counter: bool) -> String {
+    let annotated = instructions
+        .iter()
+        .any(|instruction| instruction.label.is_some() || instruction.annotation.is_some());
+    let mut header = ["PC", "OPCODE", "IMMEDIATE", "STACK", "GAS"].map(String::from).to_vec();
+    if annotated {
+        header.push("NOTE".to_string());
+    }
+    let rows = instructions
+        .iter()
+        .map(|instruction| {
+            let mut row = vec![
+                program_counter(instruction, decimal_counter),
+                instruction.name.to_string(),
+                instruction
+                    .immediate
+                    .as_ref()
+                    .map(|immediate| format!("0x{immediate}"))
+                    .unwrap_or_default(),
+                format!("{} -> {}", instruction.inputs, instruction.outputs),
+                instruction.gas.to_string(),
+            ];
+            if annotated {
+                let label = instruction.label.as_ref().map(|label| format!("{label}:"));
+                row.push(
+                    label
+                        .into_iter()
+                        .chain(instruction.annotation.clone())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = header.iter().map(|column| column.len()).collect::<Vec<_>>();
+    for row in &rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.len());
+        }
+    }
+
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(column, width)| format!("{column:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the bytecode's sections and the strings and constants extracted from them in the
+/// given format. JSON lists both, CSV lists the extracted values with the kind of the section
+/// each is in, and the other formats list both as text, one per line.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn format_strings(
+    sections: &[Section],
+    extracted: &[Extracted],
+    format: DisassemblerFormat,
+) -> String {
+    let section_of = |offset: usize| {
+        sections
+            .iter()
+            .find(|section| (section.start..section.end).contains(&offset))
+            .map(|section| section.kind.to_string())
+            .unwrap_or_default()
+    };
+
+    match format {
+        DisassemblerFormat::Json => format!(
+            "{:#}",
+            json!({
+                "sections": sections.iter().map(|section| json!({
+                    "start": section.start,
+                    "end": section.end,
+                    "kind": section.kind.to_string(),
+                })).collect::<Vec<_>>(),
+                "strings": extracted.iter().map(|value| json!({
+                    "offset": value.offset,
+                    "kind": value.kind.to_string(),
+                    "section": section_of(value.offset),
+                    "value": value.value,
+                })).collect::<Vec<_>>(),
+            })
+        ),
+        DisassemblerFormat::Csv => std::iter::once("offset,kind,section,value".to_string())
+            .chain(extracted.iter().map(|value| {
+                format!(
+                    "{},{},{},{}",
+                    value.offset,
+                    value.kind,
+                    section_of(value.offset),
+                    csv_field(&value.value)
+                )
+            }))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        DisassemblerFormat::Asm | DisassemblerFormat::Table => {
+            let mut lines = vec!["; sections".to_string()];
+            lines.extend(sections.iter().map(|section| {
+                format!(
+                    "{:06x}-{:06x} {:<8} {} bytes",
+                    section.start,
+                    section.end,
+                    section.kind.to_string(),
+                    section.end - section.start
+                )
+            }));
+            lines.push("; strings and constants".to_string());
+            lines.extend(extracted.iter().map(|value| {
+                let text = match value.kind {
+                    ExtractedKind::String => format!("{:?}", value.value),
+                    ExtractedKind::Bytes4 | ExtractedKind::Bytes32 => value.value.clone(),
+                };
+                format!("{:06x} {:<8} {text}", value.offset, value.kind.to_string())
+            }));
+            lines.join("\n") + "\n"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::disassemble_instructions;
+    use heimdall_vm::core::hardfork::HardFork;
+
+    #[test]
+    fn test_formats() {
+        let instructions = disassemble_instructions(&[0x60, 0x80, 0x80, 0x01], HardFork::Latest);
+
+        assert_eq!(to_asm(&instructions, true), "0 PUSH1 80\n2 DUP1 \n3 ADD \n");
+        assert_eq!(
+            to_csv(&instructions),
+            "pc,opcode,byte,immediate,stack_inputs,stack_outputs,gas,label,annotation,section\n0,PUSH1,0x60,0x80,0,1,3,,,\n2,DUP1,0x80,,1,2,3,,,\n3,ADD,0x01,,2,1,3,,,"
+        );
+        assert_eq!(
+            to_table(&instructions, false),
+            "PC      OPCODE  IMMEDIATE  STACK   GAS\n000000  PUSH1   0x80       0 -> 1  3\n000002  DUP1               1 -> 2  3\n000003  ADD                2 -> 1  3"
+        );
+
+        let records: serde_json::Value =
+            serde_json::from_str(&to_json(&instructions)).expect("invalid json");
+        assert_eq!(records[0]["immediate"], "0x80");
+        assert_eq!(records[2]["stack_inputs"], 2);
+    }
+}
@@ -1,10 +1,39 @@
-use std::time::Instant;
+// annotating is only done when disassembling a target, which isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod annotate;
+pub(crate) mod assemble;
+pub(crate) mod format;
+pub(crate) mod sections;
+pub(crate) mod similar;
 
-use crate::{error::Error, interfaces::DisassemblerArgs};
-use eyre::eyre;
 use heimdall_common::utils::strings::encode_hex;
-use heimdall_vm::core::opcodes::OpCodeInfo;
-use tracing::{debug, info};
+use heimdall_vm::core::{
+    eof::{eof_opcode_info, EofContainer, NON_RETURNING},
+    hardfork::HardFork,
+    opcodes::OpCodeInfo,
+};
+
+use crate::interfaces::DisassemblerFormat;
+
+// fetching the target's bytecode isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::{error::Error, interfaces::DisassemblerArgs},
+    alloy::primitives::Address,
+    annotate::{annotate, Function},
+    eyre::eyre,
+    heimdall_common::{
+        ether::signatures::{score_signature, ResolvedFunction},
+        utils::metrics::record_phase,
+    },
+    heimdall_vm::{
+        core::{eof::is_eof, vm::VM},
+        ext::selectors::{find_function_selectors, resolve_selectors},
+    },
+    sections::{annotate_sections, extract_strings, find_sections},
+    std::time::Instant,
+    tracing::{debug, info},
+};
 
 /// Disassembles EVM bytecode into readable assembly instructions
 ///
@@ -18,12 +47,11 @@ use tracing::{debug, info};
 ///
 /// # Returns
 ///
-/// A string containing the disassembled bytecode in assembly format
+/// A string containing the disassembled bytecode in the format given by `args.format`
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn disassemble(args: DisassemblerArgs) -> Result<String, Error> {
     // init
     let start_time = Instant::now();
-    let mut program_counter = 0;
-    let mut asm = String::new();
 
     // Resolve hardfork (handles Auto detection if needed)
     let start_hardfork_resolve = Instant::now();
@@ -35,48 +63,261 @@ pub async fn disassemble(args: DisassemblerArgs) -> Result<String, Error> {
     let contract_bytecode =
         args.get_bytecode().await.map_err(|e| eyre!("fetching target bytecode failed: {}", e))?;
     debug!("fetching target bytecode took {:?}", start_fetch_time.elapsed());
+    record_phase("disassemble.fetch_bytecode", start_fetch_time.elapsed());
+
+    // list the bytecode's sections and the strings and constants in them, if requested
+    if args.strings {
+        let sections = find_sections(&contract_bytecode, hardfork);
+        let extracted = extract_strings(&contract_bytecode, &sections, hardfork);
+        info!(
+            "extracted {} strings and constants from {} sections",
+            extracted.len(),
+            sections.len()
+        );
+        record_phase("disassemble", start_time.elapsed());
+        return Ok(format::format_strings(&sections, &extracted, args.format));
+    }
 
     // iterate over the bytecode, disassembling each instruction
     let start_disassemble_time = Instant::now();
-    while program_counter < contract_bytecode.len() {
-        let opcode = contract_bytecode[program_counter];
-        let mut pushed_bytes = String::new();
+    let mut instructions = disassemble_instructions(&contract_bytecode, hardfork);
+    debug!("disassembly took {:?}", start_disassemble_time.elapsed());
+
+    // label the jump destinations, resolving the selectors of the functions they dispatch to.
+    // an EOF container's code sections are already labeled, and have no dispatcher to execute
+    if args.annotate && !is_eof(&contract_bytecode) {
+        let start_annotate_time = Instant::now();
+        let evm = VM::new(
+            &contract_bytecode,
+            &[],
+            Address::default(),
+            Address::default(),
+            Address::default(),
+            0,
+            u128::MAX,
+        )
+        .with_hardfork(hardfork);
+        let selectors = find_function_selectors(&evm, &format::to_asm(&instructions, false));
+        let signatures = match args.skip_resolving {
+            true => Default::default(),
+            false => {
+                resolve_selectors::<ResolvedFunction>(selectors.keys().cloned().collect()).await
+            }
+        };
+
+        let functions = selectors
+            .iter()
+            .map(|(selector, entry)| Function {
+                selector: selector.clone(),
+                entry: *entry as usize,
+                signature: signatures.get(selector).and_then(|functions| {
+                    functions
+                        .iter()
+                        .max_by_key(|function| score_signature(&function.signature, None))
+                        .map(|function| function.signature.clone())
+                }),
+            })
+            .collect::<Vec<_>>();
+        annotate(&mut instructions, &functions);
+        annotate_sections(&mut instructions, &find_sections(&contract_bytecode, hardfork));
+        debug!("annotating disassembly took {:?}", start_annotate_time.elapsed());
+    }
+    let mut asm = format_instructions(&instructions, args.format, args.decimal_counter);
+
+    // describe an EOF container's sections around its code
+    if let (DisassemblerFormat::Asm | DisassemblerFormat::Table, Ok(container)) =
+        (args.format, EofContainer::parse(&contract_bytecode))
+    {
+        asm = format!(
+            "; EOF container, version {}, {} code sections, {} containers, {} bytes of data\n{asm}; data at {:#x}: {}\n",
+            container.version,
+            container.code_sections.len(),
+            container.containers.len(),
+            container.declared_data_size,
+            container.data_offset,
+            encode_hex(&container.data)
+        );
+    }
+
+    info!("disassembled {} bytes successfully", contract_bytecode.len());
+    debug!("disassembly took {:?}", start_time.elapsed());
+    record_phase("disassemble", start_time.elapsed());
+    Ok(asm)
+}
+
+/// An instruction of disassembled bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    /// The program counter of the instruction
+    pub pc: usize,
+    /// The instruction's opcode
+    pub opcode: u8,
+    /// The name of the opcode, or `unknown` if it isn't active in the hardfork
+    pub name: &'static str,
+    /// The bytes a `PUSH` instruction pushes, as hex without a `0x` prefix
+    pub immediate: Option<String>,
+    /// The number of stack items the instruction pops
+    pub inputs: u8,
+    /// The number of stack items the instruction pushes
+    pub outputs: u8,
+    /// The minimum gas the instruction costs
+    pub gas: u16,
+    /// The synthetic label of a `JUMPDEST`, if the disassembly was annotated
+    pub label: Option<String>,
+    /// A note on the instruction, such as the label a jump targets, if the disassembly was
+    /// annotated
+    pub annotation: Option<String>,
+    /// The index of the code section the instruction is in, if the bytecode is an EOF container
+    pub section: Option<usize>,
+}
+
+/// Disassembles the given bytecode into assembly instructions, one per line, recognizing the
+/// opcodes active in the given hardfork.
+///
+/// Unlike [`disassemble`], this doesn't fetch the target's bytecode or detect its hardfork, so it
+/// doesn't require network access.
+pub fn disassemble_bytecode(bytecode: &[u8], hardfork: HardFork, decimal_counter: bool) -> String {
+    format_instructions(
+        &disassemble_instructions(bytecode, hardfork),
+        DisassemblerFormat::Asm,
+        decimal_counter,
+    )
+}
+
+/// Disassembles the given bytecode into its instructions, recognizing the opcodes active in the
+/// given hardfork. A `PUSH` whose immediate is cut off by the end of the bytecode keeps only the
+/// bytes which are there.
+///
+/// The code sections of an EOF container are disassembled with EOF's instructions, each labeled
+/// by its index and noted with its type, and relative jumps and calls are noted with their
+/// targets. Bytecode which starts with the EOF magic but isn't a valid container is disassembled
+/// as legacy bytecode.
+pub fn disassemble_instructions(bytecode: &[u8], hardfork: HardFork) -> Vec<Instruction> {
+    if let Ok(container) = EofContainer::parse(bytecode) {
+        return disassemble_container(&container);
+    }
+
+    let mut program_counter = 0;
+    let mut instructions = Vec::new();
+
+    // iterate over the bytecode, disassembling each instruction
+    while program_counter < bytecode.len() {
+        let opcode = bytecode[program_counter];
+        let mut immediate = None;
 
         // handle PUSH0 -> PUSH32, which require us to push the next N bytes
         // onto the stack
         let mut byte_count_to_push_offset = 0;
         if (0x5f..=0x7f).contains(&opcode) {
             let byte_count_to_push: u8 = opcode - 0x5f;
-            pushed_bytes = match contract_bytecode
-                .get(program_counter + 1..program_counter + 1 + byte_count_to_push as usize)
-            {
-                Some(bytes) => encode_hex(bytes),
-                None => break,
-            };
+            let end = bytecode.len().min(program_counter + 1 + byte_count_to_push as usize);
+            let bytes = &bytecode[program_counter + 1..end];
+            immediate = (!bytes.is_empty()).then(|| encode_hex(bytes));
             byte_count_to_push_offset += byte_count_to_push as usize;
         }
 
         // Get the opcode name, respecting hardfork activation
-        let opcode_name = match OpCodeInfo::for_fork(opcode, hardfork) {
-            Some(info) => info.name(),
-            None => "unknown",
-        };
-
-        let offset = program_counter;
-        asm.push_str(
-            format!(
-                "{} {} {}\n",
-                if args.decimal_counter { offset.to_string() } else { format!("{offset:06x}") },
-                opcode_name,
-                pushed_bytes
-            )
-            .as_str(),
-        );
+        let info = OpCodeInfo::for_fork(opcode, hardfork);
+        instructions.push(Instruction {
+            pc: program_counter,
+            opcode,
+            name: info.map(|info| info.name()).unwrap_or("unknown"),
+            immediate,
+            inputs: info.map(|info| info.inputs()).unwrap_or_default(),
+            outputs: info.map(|info| info.outputs()).unwrap_or_default(),
+            gas: info.map(|info| info.min_gas()).unwrap_or_default(),
+            label: None,
+            annotation: None,
+            section: None,
+        });
         program_counter += 1 + byte_count_to_push_offset;
     }
-    debug!("disassembly took {:?}", start_disassemble_time.elapsed());
 
-    info!("disassembled {} bytes successfully", program_counter);
-    debug!("disassembly took {:?}", start_time.elapsed());
-    Ok(asm)
+    instructions
+}
+
+/// Disassembles the code sections of the EOF container, whose pcs are offsets in the container.
+fn disassemble_container(container: &EofContainer) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    for (index, section) in container.code_sections.iter().enumerate() {
+        for (i, instruction) in section.instructions().into_iter().enumerate() {
+            let info = eof_opcode_info(instruction.opcode);
+            let targets = instruction
+                .relative_targets()
+                .iter()
+                .map(|target| format!("{target:#x}"))
+                .chain(instruction.section_target().map(|target| format!("code_section_{target}")))
+                .collect::<Vec<_>>();
+
+            let outputs = match section.code_type.outputs {
+                NON_RETURNING => "non-returning".to_string(),
+                outputs => format!("outputs {outputs}"),
+            };
+            let notes = [
+                (i == 0).then(|| {
+                    format!(
+                        "inputs {}, {outputs}, max stack {}",
+                        section.code_type.inputs, section.code_type.max_stack_height
+                    )
+                }),
+                (!targets.is_empty()).then(|| format!("-> {}", targets.join(", "))),
+            ]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+            instructions.push(Instruction {
+                pc: instruction.pc,
+                opcode: instruction.opcode,
+                name: info.map(|info| info.name()).unwrap_or("unknown"),
+                immediate: (!instruction.immediate.is_empty())
+                    .then(|| encode_hex(&instruction.immediate)),
+                inputs: info.map(|info| info.inputs()).unwrap_or_default(),
+                outputs: info.map(|info| info.outputs()).unwrap_or_default(),
+                gas: info.map(|info| info.min_gas()).unwrap_or_default(),
+                label: (i == 0).then(|| format!("code_section_{index}")),
+                annotation: (!notes.is_empty()).then(|| notes.join("; ")),
+                section: Some(index),
+            });
+        }
+    }
+    instructions
+}
+
+/// Writes the instructions in the given format. The program counters of the assembly and table
+/// are in base-10 if `decimal_counter` is set, while those of the JSON and CSV always are.
+pub fn format_instructions(
+    instructions: &[Instruction],
+    format: DisassemblerFormat,
+    decimal_counter: bool,
+) -> String {
+    match format {
+        DisassemblerFormat::Asm => format::to_asm(instructions, decimal_counter),
+        DisassemblerFormat::Json => format::to_json(instructions),
+        DisassemblerFormat::Csv => format::to_csv(instructions),
+        DisassemblerFormat::Table => format::to_table(instructions, decimal_counter),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::format::to_asm;
+    use heimdall_common::utils::strings::decode_hex;
+
+    #[test]
+    fn test_disassemble_container() {
+        // two code sections: the first calls the second and loops back, the second returns
+        let bytecode = decode_hex(
+            "ef0001010008020002000900020400020000800002010100016001e30001e1fff8005be4aabb",
+        )
+        .expect("invalid bytecode");
+        let instructions = disassemble_instructions(&bytecode, HardFork::Latest);
+
+        assert!(instructions.iter().all(|instruction| instruction.section.is_some()));
+        assert_eq!(
+            to_asm(&instructions, true),
+            "code_section_0:\n25 PUSH1 01 ; inputs 0, non-returning, max stack 2\n27 CALLF 0001 ; -> code_section_1\n30 RJUMPI fff8 ; -> 0x19\n33 STOP \ncode_section_1:\n34 JUMPDEST  ; inputs 1, outputs 1, max stack 1\n35 RETF \n"
+        );
+    }
 }
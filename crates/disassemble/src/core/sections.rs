@@ -0,0 +1,351 @@
+use heimdall_common::{ether::metadata::decode_metadata, utils::strings::encode_hex};
+use heimdall_vm::core::{
+    hardfork::HardFork,
+    opcodes::{OpCodeInfo, JUMP, JUMPDEST, MSTORE, PUSH1, PUSH32},
+};
+use std::fmt::{Display, Formatter};
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::Instruction;
+
+/// The fewest characters a run of text must have to be extracted as a string.
+const MIN_STRING_LENGTH: usize = 4;
+
+/// The fewest characters a pushed constant must have to be extracted as a string, which is more
+/// than a run of data needs as constants such as left-aligned selectors are often printable.
+const MIN_PUSHED_STRING_LENGTH: usize = 5;
+
+/// What a section of the bytecode holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    /// Instructions which can be reached by executing the bytecode
+    Code,
+    /// Bytes which can't be reached, such as string constants and immutables
+    Data,
+    /// The bytecode of a contract deployed with `CREATE` or `CREATE2`, which starts with the
+    /// free memory pointer's initialization
+    Contract,
+    /// The CBOR-encoded metadata the compiler appended to the bytecode
+    Metadata,
+}
+
+impl Display for SectionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionKind::Code => write!(f, "code"),
+            SectionKind::Data => write!(f, "data"),
+            SectionKind::Contract => write!(f, "contract"),
+            SectionKind::Metadata => write!(f, "metadata"),
+        }
+    }
+}
+
+/// A contiguous section of the bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+    /// The offset of the section's first byte
+    pub start: usize,
+    /// The offset just past the section's last byte
+    pub end: usize,
+    /// What the section holds
+    pub kind: SectionKind,
+}
+
+/// What a value extracted from the bytecode is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractedKind {
+    /// A run of printable UTF-8 text
+    String,
+    /// A 4-byte constant pushed by the code, such as a selector
+    Bytes4,
+    /// A 32-byte constant pushed by the code, such as an event's topic or a storage slot
+    Bytes32,
+}
+
+impl Display for ExtractedKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractedKind::String => write!(f, "string"),
+            ExtractedKind::Bytes4 => write!(f, "bytes4"),
+            ExtractedKind::Bytes32 => write!(f, "bytes32"),
+        }
+    }
+}
+
+/// A string or constant extracted from the bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Extracted {
+    /// The offset of the value's first byte in the bytecode
+    pub offset: usize,
+    /// What the value is
+    pub kind: ExtractedKind,
+    /// The text of a string, or the hex of a constant with a `0x` prefix
+    pub value: String,
+}
+
+/// Whether the bytes start like a contract compiled by solc or vyper, which initializes the free
+/// memory pointer first, i.e. `PUSH1 0x80 PUSH1 0x40 MSTORE`.
+fn is_contract(bytes: &[u8]) -> bool {
+    matches!(bytes, [PUSH1, _, PUSH1, 0x40, MSTORE, ..])
+}
+
+/// Splits the bytecode into the code which can be reached by executing it from its first
+/// instruction, and the data embedded in it. Execution can't continue past a terminating
+/// instruction, a `JUMP` or an opcode which isn't active in the hardfork, so the bytes after
+/// one are data until the next `JUMPDEST`. Data which starts like a contract is treated as an
+/// embedded contract up to the end of its own metadata, and the metadata at the end of the
+/// bytecode is split off.
+pub fn find_sections(bytecode: &[u8], hardfork: HardFork) -> Vec<Section> {
+    let metadata_start = decode_metadata(bytecode).map(|_| {
+        let length =
+            u16::from_be_bytes([bytecode[bytecode.len() - 2], bytecode[bytecode.len() - 1]]);
+        bytecode.len() - 2 - length as usize
+    });
+    let code_end = metadata_start.unwrap_or(bytecode.len());
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut push = |start: usize, end: usize, kind: SectionKind| match sections.last_mut() {
+        Some(last) if last.kind == kind && last.end == start => last.end = end,
+        _ if start < end => sections.push(Section { start, end, kind }),
+        _ => {}
+    };
+
+    let mut pc = 0;
+    let mut reachable = true;
+    while pc < code_end {
+        let opcode = bytecode[pc];
+        match (reachable, OpCodeInfo::for_fork(opcode, hardfork)) {
+            (true, Some(info)) => {
+                let size = match opcode {
+                    PUSH1..=PUSH32 => (opcode - PUSH1 + 1) as usize,
+                    _ => 0,
+                };
+                let end = (pc + 1 + size).min(code_end);
+                push(pc, end, SectionKind::Code);
+                reachable = !info.terminating() && opcode != JUMP;
+                pc = end;
+            }
+            (true, None) => reachable = false,
+            (false, _) if opcode == JUMPDEST => reachable = true,
+            (false, _) => {
+                // an embedded contract ends with its own metadata, past which the sweep resumes
+                let contract_end = is_contract(&bytecode[pc..code_end])
+                    .then(|| {
+                        (pc + 1..=code_end)
+                            .find(|end| decode_metadata(&bytecode[pc..*end]).is_some())
+                    })
+                    .flatten();
+                match contract_end {
+                    Some(end) => {
+                        push(pc, end, SectionKind::Contract);
+                        pc = end;
+                    }
+                    None => {
+                        push(pc, pc + 1, SectionKind::Data);
+                        pc += 1;
+                    }
+                }
+            }
+        }
+    }
+    if let Some(start) = metadata_start {
+        push(start, bytecode.len(), SectionKind::Metadata);
+    }
+
+    sections
+}
+
+/// Extracts the strings of printable UTF-8 text in the bytecode's data and the constants its
+/// code pushes. A `PUSH` whose bytes are text, once the zeros right-padding them are trimmed, is
+/// extracted as a string, and the other `PUSH4`s and `PUSH32`s as constants. The strings and
+/// constants of embedded contracts are extracted in the same way, while strings in the compiler's
+/// metadata aren't extracted, as it's decoded separately.
+pub fn extract_strings(
+    bytecode: &[u8],
+    sections: &[Section],
+    hardfork: HardFork,
+) -> Vec<Extracted> {
+    let mut extracted = Vec::new();
+    for section in sections {
+        let bytes = &bytecode[section.start..section.end];
+        match section.kind {
+            SectionKind::Data => {
+                extracted.extend(text_runs(bytes, section.start, MIN_STRING_LENGTH));
+            }
+            SectionKind::Contract => {
+                let sections = find_sections(bytes, hardfork);
+                extracted.extend(
+                    extract_strings(bytes, &sections, hardfork)
+                        .into_iter()
+                        .map(|value| Extracted { offset: section.start + value.offset, ..value }),
+                );
+            }
+            SectionKind::Code => {
+                let mut pc = 0;
+                while pc < bytes.len() {
+                    let size = match bytes[pc] {
+                        PUSH1..=PUSH32 => (bytes[pc] - PUSH1 + 1) as usize,
+                        _ => 0,
+                    };
+                    let Some(immediate) = bytes.get(pc + 1..pc + 1 + size) else { break };
+                    let offset = section.start + pc + 1;
+                    let text = std::str::from_utf8(immediate_text(immediate))
+                        .ok()
+                        .filter(|text| is_text(text, MIN_PUSHED_STRING_LENGTH));
+                    match (text, size) {
+                        (Some(text), _) => extracted.push(Extracted {
+                            offset,
+                            kind: ExtractedKind::String,
+                            value: text.to_string(),
+                        }),
+                        (_, 4) => extracted.push(Extracted {
+                            offset,
+                            kind: ExtractedKind::Bytes4,
+                            value: format!("0x{}", encode_hex(immediate)),
+                        }),
+                        (_, 32) => extracted.push(Extracted {
+                            offset,
+                            kind: ExtractedKind::Bytes32,
+                            value: format!("0x{}", encode_hex(immediate)),
+                        }),
+                        _ => {}
+                    }
+                    pc += 1 + size;
+                }
+            }
+            SectionKind::Metadata => {}
+        }
+    }
+    extracted
+}
+
+/// The bytes of an immediate without the zeros solc pads strings on the right with.
+fn immediate_text(immediate: &[u8]) -> &[u8] {
+    let length = immediate.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+    &immediate[..length]
+}
+
+/// Whether the text has at least `min_length` characters, none of which are control characters.
+fn is_text(text: &str, min_length: usize) -> bool {
+    text.chars().count() >= min_length && !text.chars().any(char::is_control)
+}
+
+/// The runs of at least `min_length` printable characters in the valid UTF-8 of the bytes, with
+/// their offsets, given the offset of the bytes.
+fn text_runs(bytes: &[u8], base: usize, min_length: usize) -> Vec<Extracted> {
+    let mut runs = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let (valid, skipped) = match std::str::from_utf8(&bytes[position..]) {
+            Ok(valid) => (valid, 0),
+            Err(e) => (
+                std::str::from_utf8(&bytes[position..position + e.valid_up_to()])
+                    .expect("valid up to the error"),
+                e.error_len().unwrap_or_else(|| bytes.len() - position - e.valid_up_to()),
+            ),
+        };
+
+        let mut run: Option<(usize, String)> = None;
+        for (i, character) in valid.char_indices().chain(std::iter::once((valid.len(), '\0'))) {
+            match (character.is_control(), &mut run) {
+                (false, Some((_, text))) => text.push(character),
+                (false, None) => run = Some((i, character.to_string())),
+                (true, _) => {
+                    if let Some((start, text)) = run.take() {
+                        if is_text(&text, min_length) {
+                            runs.push(Extracted {
+                                offset: base + position + start,
+                                kind: ExtractedKind::String,
+                                value: text,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        position += valid.len() + skipped.max(1);
+    }
+    runs
+}
+
+/// Labels the first instruction of each data section with the section's kind and offset, and
+/// notes its size.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn annotate_sections(instructions: &mut [Instruction], sections: &[Section]) {
+    for section in sections.iter().filter(|section| section.kind != SectionKind::Code) {
+        let Some(instruction) =
+            instructions.iter_mut().find(|instruction| instruction.pc == section.start)
+        else {
+            continue;
+        };
+        instruction.label = Some(match section.kind {
+            SectionKind::Metadata => "metadata".to_string(),
+            kind => format!("{kind}_{:#x}", section.start),
+        });
+        instruction.annotation =
+            Some(format!("{}, {} bytes", section.kind, section.end - section.start));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::strings::decode_hex;
+
+    #[test]
+    fn test_find_sections() {
+        // code which returns, the string "heimdall!", an embedded contract ending with its
+        // metadata, code resumed at a JUMPDEST, and the metadata {"solc": 0x00081c}
+        let bytecode = decode_hex(concat!(
+            "600160005260206000f3",
+            "6865696d64616c6c21",
+            "6080604052",
+            "00a164736f6c634300081c000a",
+            "5b00",
+            "a164736f6c634300081c000a",
+        ))
+        .expect("invalid bytecode");
+        let sections = find_sections(&bytecode, HardFork::Latest);
+
+        assert_eq!(
+            sections,
+            vec![
+                Section { start: 0, end: 10, kind: SectionKind::Code },
+                Section { start: 10, end: 19, kind: SectionKind::Data },
+                Section { start: 19, end: 37, kind: SectionKind::Contract },
+                Section { start: 37, end: 39, kind: SectionKind::Code },
+                Section { start: 39, end: 51, kind: SectionKind::Metadata },
+            ]
+        );
+        assert_eq!(
+            extract_strings(&bytecode, &sections, HardFork::Latest),
+            vec![Extracted { offset: 10, kind: ExtractedKind::String, value: "heimdall!".into() }]
+        );
+    }
+
+    #[test]
+    fn test_extract_pushed_strings() {
+        // PUSH4 a9059cbb, PUSH32 "Ownable" right-padded, PUSH32 1, STOP
+        let bytecode = decode_hex(concat!(
+            "63a9059cbb",
+            "7f4f776e61626c6500000000000000000000000000000000000000000000000000",
+            "7f0000000000000000000000000000000000000000000000000000000000000001",
+            "00",
+        ))
+        .expect("invalid bytecode");
+        let sections = find_sections(&bytecode, HardFork::Latest);
+
+        assert_eq!(
+            extract_strings(&bytecode, &sections, HardFork::Latest),
+            vec![
+                Extracted { offset: 1, kind: ExtractedKind::Bytes4, value: "0xa9059cbb".into() },
+                Extracted { offset: 6, kind: ExtractedKind::String, value: "Ownable".into() },
+                Extracted {
+                    offset: 39,
+                    kind: ExtractedKind::Bytes32,
+                    value: format!("0x{}01", "00".repeat(31)),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use alloy::primitives::Address;
+use heimdall_vm::{
+    core::{
+        hardfork::HardFork,
+        opcodes::{INVALID, JUMP, JUMPDEST, JUMPI, RETURN, REVERT, SELFDESTRUCT, STOP},
+        vm::VM,
+    },
+    ext::selectors::find_function_selectors,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use super::{
+    disassemble_instructions,
+    format::to_asm,
+    sections::{find_sections, SectionKind},
+    Instruction,
+};
+
+// fetching the target's bytecode and reading the corpus isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    crate::{error::Error, interfaces::SimilarArgs},
+    eyre::eyre,
+    heimdall_common::ether::bytecode::get_bytecode_from_target,
+    std::{path::Path, time::Instant},
+    tracing::{debug, info, warn},
+};
+
+/// The number of consecutive basic blocks in each of a fingerprint's shingles.
+const SHINGLE_SIZE: usize = 2;
+
+/// The number of consecutive opcodes in each of a fingerprint's n-grams.
+const NGRAM_SIZE: usize = 4;
+
+/// How much each part of a fingerprint weighs in the similarity score, as the selectors, the
+/// shingles and the n-grams.
+const WEIGHTS: [f64; 3] = [0.4, 0.3, 0.3];
+
+/// A normalized fingerprint of a contract's bytecode. Immediates are dropped from its blocks and
+/// n-grams, so contracts which only differ by their constants, immutables, or where their code
+/// was laid out still fingerprint alike, and its metadata and embedded data are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fingerprint {
+    /// The selectors of the functions the contract's dispatcher jumps to
+    pub selectors: BTreeSet<String>,
+    /// The hashes of each run of [`SHINGLE_SIZE`] consecutive basic blocks, as their opcodes
+    pub shingles: HashSet<u64>,
+    /// The hashes of each run of [`NGRAM_SIZE`] consecutive opcodes
+    pub ngrams: HashSet<u64>,
+}
+
+/// How similar two fingerprints are, from 0 to 1. Each part is their Jaccard similarity, or
+/// `None` if neither fingerprint has any.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Similarity {
+    /// The weighted mean of the parts both fingerprints have
+    pub score: f64,
+    /// The similarity of the fingerprints' selectors
+    pub selectors: Option<f64>,
+    /// The similarity of the fingerprints' basic-block shingles
+    pub shingles: Option<f64>,
+    /// The similarity of the fingerprints' opcode n-grams
+    pub ngrams: Option<f64>,
+}
+
+/// A bytecode of the corpus and how similar it is to the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    /// The name of the bytecode's file, relative to the corpus
+    pub name: String,
+    /// How similar the bytecode is to the target
+    pub similarity: Similarity,
+}
+
+/// Hashes the value with a fixed key, so fingerprints of the same build can be compared.
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The Jaccard similarity of the sets, or `None` if both are empty.
+fn jaccard<T: Eq + Hash + Ord>(
+    a: impl Iterator<Item = T>,
+    b: impl Iterator<Item = T>,
+) -> Option<f64> {
+    let a = a.collect::<BTreeSet<_>>();
+    let b = b.collect::<BTreeSet<_>>();
+    let union = a.union(&b).count();
+    (union > 0).then(|| a.intersection(&b).count() as f64 / union as f64)
+}
+
+/// Whether execution doesn't fall through the instruction to the next one's block.
+fn ends_block(instruction: &Instruction) -> bool {
+    instruction.name == "unknown" ||
+        matches!(
+            instruction.opcode,
+            JUMP | JUMPI | STOP | RETURN | REVERT | INVALID | SELFDESTRUCT
+        )
+}
+
+impl Fingerprint {
+    /// Fingerprints the bytecode, recognizing the opcodes active in the given hardfork. Only the
+    /// code which is reached by executing the bytecode is fingerprinted, and the contracts it
+    /// embeds to deploy aren't.
+    pub fn new(bytecode: &[u8], hardfork: HardFork) -> Self {
+        let mut instructions = disassemble_instructions(bytecode, hardfork);
+
+        // an EOF container's code sections hold only code
+        if instructions.iter().all(|instruction| instruction.section.is_none()) {
+            let code = find_sections(bytecode, hardfork)
+                .into_iter()
+                .filter(|section| section.kind == SectionKind::Code)
+                .collect::<Vec<_>>();
+            instructions.retain(|instruction| {
+                code.iter().any(|section| (section.start..section.end).contains(&instruction.pc))
+            });
+        }
+
+        let evm = VM::new(
+            bytecode,
+            &[],
+            Address::default(),
+            Address::default(),
+            Address::default(),
+            0,
+            u128::MAX,
+        )
+        .with_hardfork(hardfork);
+        let selectors =
+            find_function_selectors(&evm, &to_asm(&instructions, false)).into_keys().collect();
+
+        // split the code into basic blocks at each JUMPDEST and after each jump or terminator,
+        // and wherever data was skipped
+        let mut blocks: Vec<Vec<u8>> = Vec::new();
+        let mut next_pc = None;
+        for instruction in &instructions {
+            let contiguous = next_pc == Some(instruction.pc);
+            match blocks.last_mut() {
+                Some(block) if contiguous && instruction.opcode != JUMPDEST => {
+                    block.push(instruction.opcode)
+                }
+                _ => blocks.push(vec![instruction.opcode]),
+            }
+            next_pc = match ends_block(instruction) {
+                true => None,
+                false => Some(
+                    instruction.pc +
+                        1 +
+                        instruction
+                            .immediate
+                            .as_ref()
+                            .map(|hex| hex.len() / 2)
+                            .unwrap_or_default(),
+                ),
+            };
+        }
+
+        let shingles = match blocks.len() {
+            0 => HashSet::new(),
+            1..SHINGLE_SIZE => HashSet::from([hash(&blocks)]),
+            _ => blocks.windows(SHINGLE_SIZE).map(|window| hash(&window)).collect(),
+        };
+        let opcodes = instructions.iter().map(|instruction| instruction.opcode).collect::<Vec<_>>();
+        let ngrams = match opcodes.len() {
+            0 => HashSet::new(),
+            1..NGRAM_SIZE => HashSet::from([hash(&opcodes)]),
+            _ => opcodes.windows(NGRAM_SIZE).map(|window| hash(&window)).collect(),
+        };
+
+        Self { selectors, shingles, ngrams }
+    }
+
+    /// How similar the fingerprint is to the other, from 0 for nothing in common to 1 for the
+    /// same fingerprint.
+    pub fn similarity(&self, other: &Fingerprint) -> Similarity {
+        let selectors = jaccard(self.selectors.iter(), other.selectors.iter());
+        let shingles = jaccard(self.shingles.iter(), other.shingles.iter());
+        let ngrams = jaccard(self.ngrams.iter(), other.ngrams.iter());
+
+        let parts = [selectors, shingles, ngrams]
+            .into_iter()
+            .zip(WEIGHTS)
+            .filter_map(|(part, weight)| part.map(|part| (part, weight)))
+            .collect::<Vec<_>>();
+        let weight = parts.iter().map(|(_, weight)| weight).sum::<f64>();
+        let score = match weight > 0.0 {
+            true => parts.iter().map(|(part, weight)| part * weight).sum::<f64>() / weight,
+            false => 0.0,
+        };
+
+        Similarity { score, selectors, shingles, ngrams }
+    }
+}
+
+/// Lists the files of the corpus, searching its subdirectories too, in a stable order.
+#[cfg(not(target_arch = "wasm32"))]
+fn corpus_files(directory: &Path) -> Result<Vec<std::path::PathBuf>, Error> {
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(directory)
+        .map_err(|e| eyre!("failed to read corpus '{}': {}", directory.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| eyre!("failed to read corpus: {}", e))?.path();
+        match path.is_dir() {
+            true => files.extend(corpus_files(&path)?),
+            false => files.push(path),
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Fingerprints the target and each bytecode of the corpus, and returns the bytecodes most
+/// similar to the target, most similar first. Files of the corpus which don't hold bytecode are
+/// skipped.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn similar(args: SimilarArgs) -> Result<Vec<Match>, Error> {
+    let start_time = Instant::now();
+
+    let bytecode =
+        args.get_bytecode().await.map_err(|e| eyre!("fetching target bytecode failed: {}", e))?;
+    let fingerprint = Fingerprint::new(&bytecode, args.hardfork);
+    debug!(
+        "fingerprinted target with {} selectors, {} shingles and {} n-grams",
+        fingerprint.selectors.len(),
+        fingerprint.shingles.len(),
+        fingerprint.ngrams.len()
+    );
+
+    let corpus = Path::new(&args.corpus);
+    let mut matches = Vec::new();
+    for path in corpus_files(corpus)? {
+        let Some(target) = path.to_str() else { continue };
+        let bytecode = match get_bytecode_from_target(target, "", "").await {
+            Ok(bytecode) if !bytecode.is_empty() => bytecode,
+            _ => {
+                warn!("skipping '{}', which doesn't hold bytecode", path.display());
+                continue;
+            }
+        };
+
+        matches.push(Match {
+            name: path.strip_prefix(corpus).unwrap_or(&path).display().to_string(),
+            similarity: fingerprint.similarity(&Fingerprint::new(&bytecode, args.hardfork)),
+        });
+    }
+    info!("compared target against {} bytecodes", matches.len());
+
+    matches.sort_by(|a, b| b.similarity.score.total_cmp(&a.similarity.score));
+    matches.truncate(args.count);
+    debug!("similarity search took {:?}", start_time.elapsed());
+    Ok(matches)
+}
+
+/// Formats the matches as an aligned table, most similar first. Parts neither bytecode has are
+/// written as `-`.
+pub fn format_matches(matches: &[Match]) -> String {
+    let part =
+        |part: Option<f64>| part.map(|part| format!("{part:.3}")).unwrap_or_else(|| "-".into());
+    let mut table =
+        format!("{:<8}{:<11}{:<10}{:<9}name\n", "score", "selectors", "shingles", "n-grams");
+    for found in matches {
+        table.push_str(&format!(
+            "{:<8.3}{:<11}{:<10}{:<9}{}\n",
+            found.similarity.score,
+            part(found.similarity.selectors),
+            part(found.similarity.shingles),
+            part(found.similarity.ngrams),
+            found.name
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::strings::decode_hex;
+
+    #[test]
+    fn test_fingerprint_ignores_immediates_and_metadata() {
+        // the same dispatcher, with its functions laid out at different pcs and different
+        // metadata appended
+        let a = decode_hex(
+            "5f3560e01c8063aabbccdd14601c5780631122334414601e575f5ffd5b005b00fea2646970667358221220aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa64736f6c63430008140033",
+        )
+        .expect("invalid bytecode");
+        let b = decode_hex(
+            "5f3560e01c8063aabbccdd14601d5780631122334414601f575f5ffd5b5b005b00fea2646970667358221220bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb64736f6c63430008140033",
+        )
+        .expect("invalid bytecode");
+
+        let a = Fingerprint::new(&a, HardFork::Latest);
+        let b = Fingerprint::new(&b, HardFork::Latest);
+        assert_eq!(a.selectors, BTreeSet::from(["aabbccdd".to_string(), "11223344".to_string()]));
+        assert_eq!(a.selectors, b.selectors);
+
+        assert_eq!(a.similarity(&a).score, 1.0);
+        let similarity = a.similarity(&b);
+        assert_eq!(similarity.selectors, Some(1.0));
+        assert!(similarity.score > 0.5 && similarity.score < 1.0);
+    }
+
+    #[test]
+    fn test_similarity_of_unrelated_bytecode() {
+        let token = Fingerprint::new(
+            &decode_hex("5f3560e01c8063a9059cbb1460115760015b005b00").expect("invalid bytecode"),
+            HardFork::Latest,
+        );
+        let other = Fingerprint::new(
+            &decode_hex("3415600957600051565b6001600255").expect("invalid bytecode"),
+            HardFork::Latest,
+        );
+
+        let similarity = token.similarity(&other);
+        assert_eq!(similarity.selectors, Some(0.0));
+        assert!(similarity.score < 0.2);
+        assert_eq!(Fingerprint::default().similarity(&Fingerprint::default()).score, 0.0);
+    }
+}
@@ -1,10 +1,15 @@
-use alloy::primitives::Address;
-use clap::Parser;
-use eyre::Result;
-use heimdall_common::ether::bytecode::get_bytecode_from_target;
+use alloy::eips::BlockId;
+use clap::{Parser, ValueEnum};
 use heimdall_config::parse_url_arg;
 use heimdall_vm::core::hardfork::HardFork;
 
+// fetching the target's bytecode isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    alloy::primitives::Address, eyre::Result,
+    heimdall_common::ether::bytecode::get_bytecode_from_target_at,
+};
+
 #[derive(Debug, Clone, Parser)]
 #[clap(
     about = "Disassembles EVM bytecode to assembly",
@@ -21,10 +26,17 @@ pub struct DisassemblerArgs {
     pub target: String,
 
     /// The RPC provider to use for fetching target bytecode.
-    /// This can be an explicit URL or a reference to a MESC endpoint.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
     #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
     pub rpc_url: String,
 
+    /// The block to read the target's bytecode at, either a block number, block hash, or tag
+    /// (e.g. 'latest'). Useful for targets which were since redeployed or self-destructed.
+    /// Defaults to the latest block.
+    #[clap(long, short = 'b')]
+    pub block: Option<BlockId>,
+
     /// Whether to use base-10 for the program counter.
     #[clap(long = "decimal-counter", short = 'd')]
     pub decimal_counter: bool,
@@ -33,7 +45,8 @@ pub struct DisassemblerArgs {
     #[clap(long, short, default_value = "", hide_default_value = true)]
     pub name: String,
 
-    /// The output directory to write the output to or 'print' to print to the console
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
     #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
     pub output: String,
 
@@ -46,6 +59,55 @@ pub struct DisassemblerArgs {
     /// If provided, uses Etherscan API instead of binary search.
     #[clap(long, short = 'e', default_value = "", hide_default_value = true)]
     pub etherscan_api_key: String,
+
+    /// The format to write the disassembly in.
+    #[clap(long, value_enum, default_value = "asm")]
+    pub format: DisassemblerFormat,
+
+    /// Annotate the disassembly: label each JUMPDEST as a function's entry, a loop's head, or
+    /// otherwise by its pc, note the label each jump targets, and note the function each
+    /// dispatch comparison's selector matches. The byte of each unknown opcode is written after
+    /// it, so the disassembly can be reassembled with `heimdall assemble`.
+    #[clap(long)]
+    pub annotate: bool,
+
+    /// Whether to skip resolving the selectors of the functions the annotations name.
+    #[clap(long = "skip-resolving", requires = "annotate")]
+    pub skip_resolving: bool,
+
+    /// List the sections of code and embedded data in the bytecode, and the strings and 4 and
+    /// 32-byte constants extracted from them with their offsets, instead of disassembling it.
+    #[clap(long, conflicts_with = "annotate")]
+    pub strings: bool,
+}
+
+/// The formats the disassembly can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum DisassemblerFormat {
+    /// One instruction per line, prefixed by its program counter.
+    #[default]
+    Asm,
+    /// A JSON array with a record of each instruction's pc, opcode, immediate, stack effect and
+    /// minimum gas.
+    Json,
+    /// A CSV table with a row of each instruction's pc, opcode, immediate, stack effect and
+    /// minimum gas.
+    Csv,
+    /// An aligned table of each instruction's pc, opcode, immediate, stack effect and minimum
+    /// gas, for reading.
+    Table,
+}
+
+impl DisassemblerFormat {
+    /// The extension of files written in the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DisassemblerFormat::Asm => "asm",
+            DisassemblerFormat::Json => "json",
+            DisassemblerFormat::Csv => "csv",
+            DisassemblerFormat::Table => "txt",
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -60,13 +122,17 @@ pub struct DisassemblerArgsBuilder {
     /// The RPC provider to use for fetching target bytecode.
     rpc_url: Option<String>,
 
+    /// The block to read the target's bytecode at.
+    block: Option<Option<BlockId>>,
+
     /// Whether to use base-10 for the program counter.
     decimal_counter: Option<bool>,
 
     /// Name of the output file.
     name: Option<String>,
 
-    /// The output directory to write the output to or 'print' to print to the console
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
     output: Option<String>,
 
     /// The hardfork to use for opcode recognition.
@@ -74,8 +140,21 @@ pub struct DisassemblerArgsBuilder {
 
     /// Etherscan API key for fetching contract creation block.
     etherscan_api_key: Option<String>,
+
+    /// The format to write the disassembly in.
+    format: Option<DisassemblerFormat>,
+
+    /// Whether to annotate the disassembly.
+    annotate: Option<bool>,
+
+    /// Whether to skip resolving the selectors of the functions the annotations name.
+    skip_resolving: Option<bool>,
+
+    /// Whether to list the bytecode's sections, strings and constants instead.
+    strings: Option<bool>,
 }
 
+#[cfg(not(target_arch = "wasm32"))]
 impl DisassemblerArgs {
     /// Retrieves the bytecode for the specified target
     ///
@@ -85,7 +164,7 @@ impl DisassemblerArgs {
     /// # Returns
     /// The raw bytecode as a vector of bytes
     pub async fn get_bytecode(&self) -> Result<Vec<u8>> {
-        get_bytecode_from_target(&self.target, &self.rpc_url, "").await
+        get_bytecode_from_target_at(&self.target, self.block, &self.rpc_url, "").await
     }
 
     /// Gets the hardfork to use for disassembly.
@@ -169,11 +248,16 @@ impl DisassemblerArgsBuilder {
         Self {
             target: Some(String::new()),
             rpc_url: Some(String::new()),
+            block: Some(None),
             decimal_counter: Some(false),
             name: Some(String::new()),
             output: Some(String::new()),
             hardfork: Some(HardFork::Latest),
             etherscan_api_key: Some(String::new()),
+            format: Some(DisassemblerFormat::Asm),
+            annotate: Some(false),
+            skip_resolving: Some(false),
+            strings: Some(false),
         }
     }
 
@@ -189,6 +273,12 @@ impl DisassemblerArgsBuilder {
         self
     }
 
+    /// Sets the block to read the target's bytecode at
+    pub fn block(&mut self, block: Option<BlockId>) -> &mut Self {
+        self.block = Some(block);
+        self
+    }
+
     /// Sets whether to use decimal (true) or hexadecimal (false) for program counter
     pub fn decimal_counter(&mut self, decimal_counter: bool) -> &mut Self {
         self.decimal_counter = Some(decimal_counter);
@@ -219,6 +309,31 @@ impl DisassemblerArgsBuilder {
         self
     }
 
+    /// Sets the format to write the disassembly in
+    pub fn format(&mut self, format: DisassemblerFormat) -> &mut Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Sets whether to annotate the disassembly with labels and selectors
+    pub fn annotate(&mut self, annotate: bool) -> &mut Self {
+        self.annotate = Some(annotate);
+        self
+    }
+
+    /// Sets whether to skip resolving selectors when annotating the disassembly
+    pub fn skip_resolving(&mut self, skip_resolving: bool) -> &mut Self {
+        self.skip_resolving = Some(skip_resolving);
+        self
+    }
+
+    /// Sets whether to list the bytecode's sections, strings and constants instead of
+    /// disassembling it
+    pub fn strings(&mut self, strings: bool) -> &mut Self {
+        self.strings = Some(strings);
+        self
+    }
+
     /// Builds the DisassemblerArgs from the builder
     ///
     /// # Returns
@@ -227,6 +342,7 @@ impl DisassemblerArgsBuilder {
         Ok(DisassemblerArgs {
             target: self.target.clone().ok_or_else(|| eyre::eyre!("target is required"))?,
             rpc_url: self.rpc_url.clone().ok_or_else(|| eyre::eyre!("rpc_url is required"))?,
+            block: self.block.ok_or_else(|| eyre::eyre!("block is required"))?,
             decimal_counter: self
                 .decimal_counter
                 .ok_or_else(|| eyre::eyre!("decimal_counter is required"))?,
@@ -237,6 +353,12 @@ impl DisassemblerArgsBuilder {
                 .etherscan_api_key
                 .clone()
                 .ok_or_else(|| eyre::eyre!("etherscan_api_key is required"))?,
+            format: self.format.ok_or_else(|| eyre::eyre!("format is required"))?,
+            annotate: self.annotate.ok_or_else(|| eyre::eyre!("annotate is required"))?,
+            skip_resolving: self
+                .skip_resolving
+                .ok_or_else(|| eyre::eyre!("skip_resolving is required"))?,
+            strings: self.strings.ok_or_else(|| eyre::eyre!("strings is required"))?,
         })
     }
 }
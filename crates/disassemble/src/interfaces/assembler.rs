@@ -0,0 +1,122 @@
+use clap::Parser;
+
+#[derive(Debug, Clone, Parser)]
+#[clap(
+    about = "Assembles disassembled EVM bytecode back into bytecode",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall assemble <TARGET> [OPTIONS]"
+)]
+/// Arguments for the assembly operation
+///
+/// This struct contains all the configuration parameters needed to assemble the disassembly
+/// written by `heimdall disassemble`, which may have been edited by hand, back into bytecode.
+pub struct AssemblerArgs {
+    /// The disassembly to assemble, either a file or the assembly itself.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Whether the program counters of the disassembly are in base-10, i.e. whether it was
+    /// disassembled with `--decimal-counter`.
+    #[clap(long = "decimal-counter", short = 'd')]
+    pub decimal_counter: bool,
+
+    /// Name of the output file.
+    #[clap(long, short, default_value = "", hide_default_value = true)]
+    pub name: String,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
+    #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
+    pub output: String,
+}
+
+#[derive(Debug, Clone)]
+/// Builder for AssemblerArgs
+///
+/// This struct provides a builder pattern for creating AssemblerArgs instances
+/// with a fluent API.
+pub struct AssemblerArgsBuilder {
+    /// The disassembly to assemble, either a file or the assembly itself.
+    target: Option<String>,
+
+    /// Whether the program counters of the disassembly are in base-10.
+    decimal_counter: Option<bool>,
+
+    /// Name of the output file.
+    name: Option<String>,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
+    output: Option<String>,
+}
+
+impl AssemblerArgs {
+    /// Retrieves the disassembly to assemble
+    ///
+    /// The target is read as a file if one exists at its path, and is otherwise the disassembly
+    /// itself.
+    pub fn get_source(&self) -> eyre::Result<String> {
+        match std::path::Path::new(&self.target).is_file() {
+            true => std::fs::read_to_string(&self.target)
+                .map_err(|e| eyre::eyre!("failed to read '{}': {}", self.target, e)),
+            false => Ok(self.target.clone()),
+        }
+    }
+}
+
+impl Default for AssemblerArgsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AssemblerArgsBuilder {
+    /// Creates a new AssemblerArgsBuilder with default values
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            decimal_counter: Some(false),
+            name: Some(String::new()),
+            output: Some(String::new()),
+        }
+    }
+
+    /// Sets the disassembly to assemble (a file or the assembly itself)
+    pub fn target(&mut self, target: String) -> &mut Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Sets whether the program counters of the disassembly are in base-10
+    pub fn decimal_counter(&mut self, decimal_counter: bool) -> &mut Self {
+        self.decimal_counter = Some(decimal_counter);
+        self
+    }
+
+    /// Sets the name for the output file
+    pub fn name(&mut self, name: String) -> &mut Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the output directory
+    pub fn output(&mut self, output: String) -> &mut Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Builds the AssemblerArgs from the builder
+    ///
+    /// # Returns
+    /// A Result containing the built AssemblerArgs or an error if required fields are missing
+    pub fn build(&self) -> eyre::Result<AssemblerArgs> {
+        Ok(AssemblerArgs {
+            target: self.target.clone().ok_or_else(|| eyre::eyre!("target is required"))?,
+            decimal_counter: self
+                .decimal_counter
+                .ok_or_else(|| eyre::eyre!("decimal_counter is required"))?,
+            name: self.name.clone().ok_or_else(|| eyre::eyre!("name is required"))?,
+            output: self.output.clone().ok_or_else(|| eyre::eyre!("output is required"))?,
+        })
+    }
+}
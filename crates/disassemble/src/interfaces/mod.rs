@@ -1,4 +1,8 @@
 mod args;
+mod assembler;
+mod similar;
 
 // re-export the public interface
-pub use args::{DisassemblerArgs, DisassemblerArgsBuilder};
+pub use args::{DisassemblerArgs, DisassemblerArgsBuilder, DisassemblerFormat};
+pub use assembler::{AssemblerArgs, AssemblerArgsBuilder};
+pub use similar::{SimilarArgs, SimilarArgsBuilder};
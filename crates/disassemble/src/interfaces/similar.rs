@@ -0,0 +1,190 @@
+use alloy::eips::BlockId;
+use clap::Parser;
+use heimdall_config::parse_url_arg;
+use heimdall_vm::core::hardfork::HardFork;
+
+// fetching the target's bytecode isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {eyre::Result, heimdall_common::ether::bytecode::get_bytecode_from_target_at};
+
+#[derive(Debug, Clone, Parser)]
+#[clap(
+    about = "Finds the bytecodes in a local corpus which are most similar to the target",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall similar <TARGET> --corpus <DIR> [OPTIONS]"
+)]
+/// Arguments for the similarity search
+///
+/// This struct contains all the configuration parameters needed to fingerprint a contract's
+/// bytecode and compare it against a local corpus of bytecodes.
+pub struct SimilarArgs {
+    /// The target to compare, either a file, bytecode, contract address, or ENS name.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// The directory of bytecodes to compare the target against. Each file holds one contract's
+    /// bytecode as hex, and is named by its file name. Subdirectories are searched too.
+    #[clap(long, short = 'c', required = true)]
+    pub corpus: String,
+
+    /// The RPC provider to use for fetching target bytecode.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
+    #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// The block to read the target's bytecode at, either a block number, block hash, or tag
+    /// (e.g. 'latest'). Defaults to the latest block.
+    #[clap(long, short = 'b')]
+    pub block: Option<BlockId>,
+
+    /// The number of closest matches to report.
+    #[clap(long, default_value = "10")]
+    pub count: usize,
+
+    /// The hardfork to use for opcode recognition. Defaults to 'latest'.
+    #[clap(long, short = 'f', default_value = "latest")]
+    pub hardfork: HardFork,
+
+    /// Name of the output file.
+    #[clap(long, short, default_value = "", hide_default_value = true)]
+    pub name: String,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
+    #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
+    pub output: String,
+}
+
+#[derive(Debug, Clone)]
+/// Builder for SimilarArgs
+///
+/// This struct provides a builder pattern for creating SimilarArgs instances
+/// with a fluent API.
+pub struct SimilarArgsBuilder {
+    /// The target to compare, either a file, bytecode, contract address, or ENS name.
+    target: Option<String>,
+
+    /// The directory of bytecodes to compare the target against.
+    corpus: Option<String>,
+
+    /// The RPC provider to use for fetching target bytecode.
+    rpc_url: Option<String>,
+
+    /// The block to read the target's bytecode at.
+    block: Option<Option<BlockId>>,
+
+    /// The number of closest matches to report.
+    count: Option<usize>,
+
+    /// The hardfork to use for opcode recognition.
+    hardfork: Option<HardFork>,
+
+    /// Name of the output file.
+    name: Option<String>,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
+    output: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SimilarArgs {
+    /// Retrieves the bytecode for the specified target
+    ///
+    /// This method fetches the bytecode from a file, address, or directly from a hex string,
+    /// depending on the target type provided in the arguments.
+    ///
+    /// # Returns
+    /// The raw bytecode as a vector of bytes
+    pub async fn get_bytecode(&self) -> Result<Vec<u8>> {
+        get_bytecode_from_target_at(&self.target, self.block, &self.rpc_url, "").await
+    }
+}
+
+impl Default for SimilarArgsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimilarArgsBuilder {
+    /// Creates a new SimilarArgsBuilder with default values
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            corpus: Some(String::new()),
+            rpc_url: Some(String::new()),
+            block: Some(None),
+            count: Some(10),
+            hardfork: Some(HardFork::Latest),
+            name: Some(String::new()),
+            output: Some(String::new()),
+        }
+    }
+
+    /// Sets the target to compare (address, file, or bytecode)
+    pub fn target(&mut self, target: String) -> &mut Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Sets the directory of bytecodes to compare the target against
+    pub fn corpus(&mut self, corpus: String) -> &mut Self {
+        self.corpus = Some(corpus);
+        self
+    }
+
+    /// Sets the RPC URL for fetching bytecode if the target is an address
+    pub fn rpc_url(&mut self, rpc_url: String) -> &mut Self {
+        self.rpc_url = Some(rpc_url);
+        self
+    }
+
+    /// Sets the block to read the target's bytecode at
+    pub fn block(&mut self, block: Option<BlockId>) -> &mut Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Sets the number of closest matches to report
+    pub fn count(&mut self, count: usize) -> &mut Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Sets the hardfork for opcode recognition
+    pub fn hardfork(&mut self, hardfork: HardFork) -> &mut Self {
+        self.hardfork = Some(hardfork);
+        self
+    }
+
+    /// Sets the name for the output file
+    pub fn name(&mut self, name: String) -> &mut Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the output directory or 'print' to print to console
+    pub fn output(&mut self, output: String) -> &mut Self {
+        self.output = Some(output);
+        self
+    }
+
+    /// Builds the SimilarArgs from the builder
+    ///
+    /// # Returns
+    /// A Result containing the built SimilarArgs or an error if required fields are missing
+    pub fn build(&self) -> eyre::Result<SimilarArgs> {
+        Ok(SimilarArgs {
+            target: self.target.clone().ok_or_else(|| eyre::eyre!("target is required"))?,
+            corpus: self.corpus.clone().ok_or_else(|| eyre::eyre!("corpus is required"))?,
+            rpc_url: self.rpc_url.clone().ok_or_else(|| eyre::eyre!("rpc_url is required"))?,
+            block: self.block.ok_or_else(|| eyre::eyre!("block is required"))?,
+            count: self.count.ok_or_else(|| eyre::eyre!("count is required"))?,
+            hardfork: self.hardfork.ok_or_else(|| eyre::eyre!("hardfork is required"))?,
+            name: self.name.clone().ok_or_else(|| eyre::eyre!("name is required"))?,
+            output: self.output.clone().ok_or_else(|| eyre::eyre!("output is required"))?,
+        })
+    }
+}
@@ -11,7 +11,18 @@ mod core;
 mod interfaces;
 
 // re-export the public interface
-pub use core::disassemble;
+pub use core::{
+    assemble::{assemble, assemble_source},
+    disassemble_bytecode, disassemble_instructions, format_instructions,
+    sections::{extract_strings, find_sections, Extracted, ExtractedKind, Section, SectionKind},
+    similar::{format_matches, Fingerprint, Match, Similarity},
+    Instruction,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use core::{disassemble, similar::similar};
 pub use error::Error;
 pub use heimdall_vm::core::hardfork::HardFork;
-pub use interfaces::{DisassemblerArgs, DisassemblerArgsBuilder};
+pub use interfaces::{
+    AssemblerArgs, AssemblerArgsBuilder, DisassemblerArgs, DisassemblerArgsBuilder,
+    DisassemblerFormat, SimilarArgs, SimilarArgsBuilder,
+};
@@ -0,0 +1,121 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::primitives::{Address, FixedBytes};
+use eyre::eyre;
+use heimdall_common::utils::io::file::read_file;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use super::provenance::SlotProvenance;
+use crate::error::Error;
+
+/// The progress of a storage dump, which is saved to the `--resume` file periodically, so that
+/// an interrupted dump doesn't have to trace its blocks again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    /// The contract whose storage is being dumped
+    target: Address,
+    /// The block the dump started from
+    from_block: u128,
+    /// The last block whose state diffs were applied. Every block from `from_block` up to and
+    /// including it has been applied, in order.
+    pub last_block: Option<u128>,
+    /// The value of each storage slot the applied blocks wrote
+    pub storage: BTreeMap<FixedBytes<32>, FixedBytes<32>>,
+    /// The words observed in the applied blocks' traces, which may be mapping keys
+    #[serde(default)]
+    pub keys: BTreeSet<FixedBytes<32>>,
+    /// The first and last writes to each slot, if the dump records provenance
+    #[serde(default)]
+    pub provenance: BTreeMap<FixedBytes<32>, SlotProvenance>,
+}
+
+impl Checkpoint {
+    /// Creates an empty checkpoint for dumping the target's storage from the given block.
+    pub(crate) fn new(target: Address, from_block: u128) -> Self {
+        Self {
+            target,
+            from_block,
+            last_block: None,
+            storage: BTreeMap::new(),
+            keys: BTreeSet::new(),
+            provenance: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the checkpoint saved at `path`. If there's none, or it was saved while dumping
+    /// another target or from another block, an empty checkpoint is returned instead.
+    pub(crate) fn load(path: &str, target: Address, from_block: u128) -> Self {
+        let checkpoint = Self::new(target, from_block);
+        let Ok(contents) = read_file(path) else {
+            return checkpoint;
+        };
+
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(saved) if saved.target == target && saved.from_block == from_block => {
+                info!(
+                    "resuming {} storage slots dumped up to block {} from '{}'",
+                    saved.storage.len(),
+                    saved.last_block.map(|block| block.to_string()).unwrap_or_default(),
+                    path
+                );
+                saved
+            }
+            Ok(_) => {
+                warn!("the checkpoint at '{}' is for another dump, starting over", path);
+                checkpoint
+            }
+            Err(e) => {
+                warn!("failed to parse the checkpoint at '{}', starting over: {}", path, e);
+                checkpoint
+            }
+        }
+    }
+
+    /// The first block which hasn't been applied yet.
+    pub(crate) fn next_block(&self) -> u128 {
+        self.last_block.map(|block| block + 1).unwrap_or(self.from_block)
+    }
+
+    /// Saves the checkpoint to `path`. The checkpoint is written to a temporary file which
+    /// replaces it, so that it's never left partially written.
+    pub(crate) fn save(&self, path: &str) -> Result<(), Error> {
+        let contents = serde_json::to_string(self)
+            .map_err(|e| Error::Eyre(eyre!("failed to serialize checkpoint: {}", e)))?;
+        let partial = format!("{path}.partial");
+        std::fs::write(&partial, contents)
+            .and_then(|_| std::fs::rename(&partial, path))
+            .map_err(|e| Error::Eyre(eyre!("failed to write checkpoint to '{}': {}", path, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let path = std::env::temp_dir().join("heimdall_test_dump_checkpoint.json");
+        let path = path.to_str().expect("invalid path");
+        let _ = std::fs::remove_file(path);
+
+        let target = Address::repeat_byte(0x11);
+        let mut checkpoint = Checkpoint::new(target, 100);
+        assert_eq!(checkpoint.next_block(), 100);
+
+        checkpoint.last_block = Some(150);
+        checkpoint.storage.insert(FixedBytes::with_last_byte(1), FixedBytes::repeat_byte(0xff));
+        checkpoint.keys.insert(FixedBytes::repeat_byte(0xff));
+        checkpoint.save(path).expect("failed to save checkpoint");
+
+        let restored = Checkpoint::load(path, target, 100);
+        assert_eq!(restored, checkpoint);
+        assert_eq!(restored.next_block(), 151);
+
+        // checkpoints of other targets, or dumped from another block, aren't resumed
+        assert_eq!(Checkpoint::load(path, Address::ZERO, 100).next_block(), 100);
+        assert_eq!(Checkpoint::load(path, target, 0).next_block(), 0);
+
+        std::fs::remove_file(path).expect("failed to remove checkpoint");
+    }
+}
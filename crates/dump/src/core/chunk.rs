@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::{
+    primitives::{Address, FixedBytes},
+    rpc::types::trace::parity::{Delta, TraceResultsWithTransactionHash},
+};
+
+use super::provenance::{merge_provenance, merge_slot, slot_write, SlotProvenance};
+
+/// The storage writes of a contiguous chunk of blocks. Each slot holds the value the chunk's last
+/// write to it left, or `None` if the slot was cleared. Chunks are traced independently, then
+/// merged in block order, so that later chunks overwrite earlier ones.
+///
+/// The words observed in the chunk's traces, which may be the keys of the target's mappings, are
+/// collected alongside the writes, as are the transactions which wrote each slot when
+/// recording provenance.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ChangeSet {
+    /// The last value written to each slot within the chunk
+    pub changes: BTreeMap<FixedBytes<32>, Option<FixedBytes<32>>>,
+    /// The accounts touched by the chunk's transactions and the values written to the target's
+    /// storage, as words
+    pub keys: BTreeSet<FixedBytes<32>>,
+    /// The first and last writes to each slot within the chunk, if provenance is recorded
+    pub provenance: Option<BTreeMap<FixedBytes<32>, SlotProvenance>>,
+}
+
+impl ChangeSet {
+    /// Creates an empty change set which records the transactions which wrote each slot.
+    pub(crate) fn with_provenance() -> Self {
+        Self { provenance: Some(BTreeMap::new()), ..Default::default() }
+    }
+
+    /// Applies the target's storage writes in a block's traces, which must be applied in block
+    /// order.
+    pub(crate) fn apply_block(
+        &mut self,
+        block: u128,
+        block_trace: &[TraceResultsWithTransactionHash],
+        target: &Address,
+    ) {
+        block_trace.iter().for_each(|trace| {
+            if let Some(diff) = trace.full_trace.state_diff.as_ref() {
+                self.keys.extend(diff.0.keys().map(|addr| addr.into_word()));
+                diff.0
+                    .iter()
+                    .filter(|(addr, _)| addr == &target)
+                    .flat_map(|(_, value)| value.storage.iter())
+                    .for_each(|(slot, diff)| {
+                        self.apply(*slot, diff);
+                        self.record(*slot, block, trace, diff);
+                    });
+            }
+        });
+    }
+
+    /// Records a transaction's write to `slot` in the slot's provenance, if it's recorded.
+    fn record(
+        &mut self,
+        slot: FixedBytes<32>,
+        block: u128,
+        trace: &TraceResultsWithTransactionHash,
+        diff: &Delta<FixedBytes<32>>,
+    ) {
+        let (Some(provenance), Some(write)) =
+            (self.provenance.as_mut(), slot_write(block, trace.transaction_hash, diff))
+        else {
+            return;
+        };
+        merge_slot(provenance, slot, SlotProvenance::new(write));
+    }
+
+    /// Applies a single write to `slot`.
+    pub(crate) fn apply(&mut self, slot: FixedBytes<32>, diff: &Delta<FixedBytes<32>>) {
+        match diff {
+            Delta::Added(v) => {
+                self.changes.insert(slot, Some(*v));
+                self.keys.insert(*v);
+            }
+            Delta::Changed(v) => {
+                self.changes.insert(slot, Some(v.to));
+                self.keys.insert(v.to);
+            }
+            Delta::Removed(_) => {
+                self.changes.insert(slot, None);
+            }
+            _ => {}
+        }
+    }
+
+    /// Merges the chunk's writes into `storage`, which must hold every earlier chunk's writes,
+    /// its observed words into `keys`, and its slots' provenance into `provenance`.
+    pub(crate) fn merge_into(
+        self,
+        storage: &mut BTreeMap<FixedBytes<32>, FixedBytes<32>>,
+        keys: &mut BTreeSet<FixedBytes<32>>,
+        provenance: &mut BTreeMap<FixedBytes<32>, SlotProvenance>,
+    ) {
+        keys.extend(self.keys);
+        if let Some(later) = self.provenance {
+            merge_provenance(provenance, later);
+        }
+        for (slot, value) in self.changes {
+            match value {
+                Some(value) => {
+                    storage.insert(slot, value);
+                }
+                None => {
+                    storage.remove(&slot);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::types::trace::parity::ChangedType;
+
+    use super::*;
+
+    #[test]
+    fn test_change_sets_merge_in_order() {
+        let slot = |n: u8| FixedBytes::with_last_byte(n);
+        let value = |n: u8| FixedBytes::repeat_byte(n);
+
+        let mut first = ChangeSet::default();
+        first.apply(slot(1), &Delta::Added(value(1)));
+        first.apply(slot(2), &Delta::Added(value(2)));
+        first.apply(slot(1), &Delta::Changed(ChangedType { from: value(1), to: value(3) }));
+
+        let mut second = ChangeSet::default();
+        second.apply(slot(2), &Delta::Removed(value(2)));
+        second.apply(slot(3), &Delta::Added(value(4)));
+        second.apply(slot(4), &Delta::Added(value(5)));
+        second.apply(slot(4), &Delta::Removed(value(5)));
+
+        let mut storage = BTreeMap::new();
+        let mut keys = BTreeSet::new();
+        first.merge_into(&mut storage, &mut keys, &mut BTreeMap::new());
+        second.merge_into(&mut storage, &mut keys, &mut BTreeMap::new());
+
+        // the last write to each slot wins, and cleared slots are dropped
+        assert_eq!(storage, BTreeMap::from([(slot(1), value(3)), (slot(3), value(4))]));
+        assert_eq!(keys, BTreeSet::from([value(1), value(2), value(3), value(4), value(5)]));
+    }
+}
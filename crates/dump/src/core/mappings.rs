@@ -0,0 +1,424 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::primitives::{keccak256, Address, FixedBytes, U256};
+use hashbrown::{HashMap, HashSet};
+use heimdall_decompiler::{StorageLayout, StorageLayoutEntry, StorageType};
+use tracing::debug;
+
+use super::values::{decode, untyped, StorageVariable};
+
+/// The state variables whose slots are searched for mapping keys, besides those in the layout.
+pub(crate) const MAX_BASE_SLOT: u64 = 64;
+/// The number of consecutive slots a mapping's value is assumed to span, for struct values.
+const MAX_STRUCT_MEMBERS: u64 = 8;
+/// The largest index of a dynamic array element which is named.
+const MAX_ARRAY_INDEX: u64 = 1 << 20;
+/// The most hashes computed when recovering the keys of nested mappings.
+const MAX_NESTED_HASHES: usize = 1 << 24;
+
+/// The target's state variables, keyed by slot, as recovered by decompiling it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Variables {
+    /// The variables stored at each slot, more than one if they're packed
+    pub entries: BTreeMap<U256, Vec<StorageLayoutEntry>>,
+}
+
+impl Variables {
+    /// Collects the variables in a decompiled storage layout.
+    pub(crate) fn from_layout(layout: &StorageLayout) -> Self {
+        let mut variables = Self::default();
+        for entry in &layout.entries {
+            variables.entries.entry(entry.slot).or_default().push(entry.clone());
+        }
+        variables
+    }
+
+    /// The name of the variable at `slot`, or `slot_<slot>` if it's unknown. Packed variables
+    /// are joined by `, `.
+    fn name(&self, slot: &U256) -> String {
+        match self.entries.get(slot) {
+            Some(entries) => {
+                entries.iter().map(|entry| entry.label.as_str()).collect::<Vec<_>>().join(", ")
+            }
+            None => format!("slot_{slot}"),
+        }
+    }
+
+    /// The type of the variable at `slot`, if a single variable is stored there.
+    fn typ(&self, slot: &U256) -> Option<&StorageType> {
+        match self.entries.get(slot).map(Vec::as_slice) {
+            Some([entry]) => Some(&entry.typ),
+            _ => None,
+        }
+    }
+
+    /// The type of the values of the mapping at `slot`, after `depth` keys.
+    fn mapping_value(&self, slot: &U256, depth: usize) -> Option<&StorageType> {
+        let mut typ = self.typ(slot)?;
+        for _ in 0..depth {
+            let StorageType::Mapping(_, value) = typ else { return None };
+            typ = value;
+        }
+        Some(typ)
+    }
+
+    /// Whether the mapping at `slot` is known to hold mappings.
+    fn is_nested(&self, slot: &U256) -> bool {
+        matches!(self.mapping_value(slot, 1), Some(StorageType::Mapping(..)))
+    }
+}
+
+/// Finds the state variables stored in each dumped slot, recovering the keys of mapping values
+/// and the indices of dynamic array elements, e.g. `balanceOf[0xabc…]`, and decodes the values
+/// by their recovered types.
+///
+/// A mapping's value for `key` is stored at `keccak(key . slot)`, so the keys are recovered by
+/// hashing each of the `keys` observed while dumping with the slot of each candidate mapping,
+/// and matching the hashes against the dumped slots. The values of nested mappings are found
+/// by hashing the keys again with those hashes, and the members of struct values by the slots
+/// following them. A dynamic array's elements are stored from `keccak(slot)` onwards.
+///
+/// Slots which couldn't be attributed to a variable are left out.
+pub(crate) fn find_variables(
+    storage: &BTreeMap<FixedBytes<32>, FixedBytes<32>>,
+    keys: &BTreeSet<FixedBytes<32>>,
+    variables: &Variables,
+) -> HashMap<FixedBytes<32>, Vec<StorageVariable>> {
+    let bases = (0..MAX_BASE_SLOT)
+        .map(U256::from)
+        .chain(variables.entries.keys().copied())
+        .collect::<BTreeSet<_>>();
+
+    // the locations a mapping's value, or the first member of a struct value, must be stored at
+    // for one of the dumped slots to belong to it. only the hashes matching these are kept
+    let locations = storage
+        .keys()
+        .flat_map(|slot| {
+            let value = U256::from_be_bytes(slot.0);
+            (0..MAX_STRUCT_MEMBERS).filter_map(move |member| value.checked_sub(U256::from(member)))
+        })
+        .map(|location| FixedBytes::from(location.to_be_bytes()))
+        .collect::<HashSet<_>>();
+
+    // the values of the candidate mappings for each observed key, with the mapping's slot, the
+    // keys' access path, and the number of keys
+    let mut mapping_values: HashMap<FixedBytes<32>, (U256, String, usize)> = HashMap::new();
+    for base in &bases {
+        let base_slot = FixedBytes::from(base.to_be_bytes::<32>());
+        for key in keys {
+            let location = mapping_slot(key, &base_slot);
+            if locations.contains(&location) {
+                mapping_values.insert(location, (*base, format!("[{}]", key_label(key)), 1));
+            }
+        }
+    }
+
+    // the values of nested mappings, if there are few enough keys to hash them all. without a
+    // layout, every candidate mapping may be nested
+    let nested = bases
+        .iter()
+        .filter(|base| variables.entries.is_empty() || variables.is_nested(base))
+        .collect::<Vec<_>>();
+    if nested.len().saturating_mul(keys.len()).saturating_mul(keys.len()) <= MAX_NESTED_HASHES {
+        for base in nested {
+            let base_slot = FixedBytes::from(base.to_be_bytes::<32>());
+            for outer in keys {
+                let outer_location = mapping_slot(outer, &base_slot);
+                for inner in keys {
+                    let location = mapping_slot(inner, &outer_location);
+                    if locations.contains(&location) {
+                        let path = format!("[{}][{}]", key_label(outer), key_label(inner));
+                        mapping_values.insert(location, (*base, path, 2));
+                    }
+                }
+            }
+        }
+    } else {
+        debug!("skipping nested mapping key recovery, {} keys were observed", keys.len());
+    }
+
+    // the first slot of each candidate dynamic array's elements
+    let arrays = bases
+        .iter()
+        .map(|base| (U256::from_be_bytes(keccak256(base.to_be_bytes::<32>()).0), *base))
+        .collect::<BTreeMap<_, _>>();
+
+    storage
+        .iter()
+        .filter_map(|(slot, word)| {
+            let value = U256::from_be_bytes(slot.0);
+            let found = if value < U256::from(MAX_BASE_SLOT) ||
+                variables
+                    .entries
+                    .range(..=value)
+                    .next_back()
+                    .is_some_and(|(base, _)| value - base < U256::from(MAX_STRUCT_MEMBERS))
+            {
+                state_variables(&value, word, variables)
+            } else if let Some(found) = mapping_variables(&value, word, &mapping_values, variables)
+            {
+                found
+            } else {
+                array_variables(&value, word, &arrays, variables)
+            };
+            (!found.is_empty()).then_some((*slot, found))
+        })
+        .collect()
+}
+
+/// The state variables stored directly at `slot`, or the members of a struct variable stored in
+/// the slots before it.
+fn state_variables(
+    slot: &U256,
+    word: &FixedBytes<32>,
+    variables: &Variables,
+) -> Vec<StorageVariable> {
+    if let Some(entries) = variables.entries.get(slot) {
+        return entries
+            .iter()
+            .flat_map(|entry| {
+                values_at(
+                    &entry.label,
+                    entry.label.clone(),
+                    Some(&entry.typ),
+                    0,
+                    entry.offset,
+                    word,
+                )
+            })
+            .collect();
+    }
+
+    match variables.entries.range(..slot).next_back() {
+        Some((base, entries)) => match entries.as_slice() {
+            [entry]
+                if matches!(entry.typ, StorageType::Struct(..)) &&
+                    U256::from(entry.typ.size() / 32) > slot - base =>
+            {
+                let member = (slot - base).to::<u64>();
+                values_at(&entry.label, entry.label.clone(), Some(&entry.typ), member, 0, word)
+            }
+            _ => Vec::new(),
+        },
+        None => Vec::new(),
+    }
+}
+
+/// The mapping value, or member of a struct mapping value, stored at `slot`.
+fn mapping_variables(
+    slot: &U256,
+    word: &FixedBytes<32>,
+    mapping_values: &HashMap<FixedBytes<32>, (U256, String, usize)>,
+    variables: &Variables,
+) -> Option<Vec<StorageVariable>> {
+    (0..MAX_STRUCT_MEMBERS).find_map(|member| {
+        let location = slot.checked_sub(U256::from(member))?;
+        let (base, path, depth) = mapping_values.get(&FixedBytes::from(location.to_be_bytes()))?;
+        let variable = variables.name(base);
+        let typ = variables.mapping_value(base, *depth);
+        Some(values_at(&variable, format!("{variable}{path}"), typ, member, 0, word))
+    })
+}
+
+/// The dynamic array elements stored at `slot`. Elements of up to 16 bytes are packed into
+/// each slot, and `struct` elements span consecutive slots.
+fn array_variables(
+    slot: &U256,
+    word: &FixedBytes<32>,
+    arrays: &BTreeMap<U256, U256>,
+    variables: &Variables,
+) -> Vec<StorageVariable> {
+    let Some((start, base)) = arrays.range(..=slot).next_back() else { return Vec::new() };
+    let offset = slot - start;
+    if offset >= U256::from(MAX_ARRAY_INDEX) {
+        return Vec::new();
+    }
+    let offset = offset.to::<u64>();
+    let variable = variables.name(base);
+
+    match variables.typ(base) {
+        Some(StorageType::Array(element)) => match element.as_ref() {
+            StorageType::Struct(..) => {
+                let slots = (element.size() / 32).max(1) as u64;
+                let name = format!("{variable}[{}]", offset / slots);
+                values_at(&variable, name, Some(element), offset % slots, 0, word)
+            }
+            element if element.size() <= 16 => {
+                let size = element.size().max(1);
+                let per_slot = (32 / size) as u64;
+                (0..per_slot)
+                    .map(|i| {
+                        let name = format!("{variable}[{}]", offset * per_slot + i);
+                        decode(&variable, name, element, i as usize * size, word)
+                    })
+                    .collect()
+            }
+            element => vec![decode(&variable, format!("{variable}[{offset}]"), element, 0, word)],
+        },
+        _ => vec![untyped(&variable, format!("{variable}[{offset}]"), word)],
+    }
+}
+
+/// The values stored in the `member`th slot of a value of type `typ`, which starts at byte
+/// `offset` of its first slot.
+fn values_at(
+    variable: &str,
+    name: String,
+    typ: Option<&StorageType>,
+    member: u64,
+    offset: usize,
+    word: &FixedBytes<32>,
+) -> Vec<StorageVariable> {
+    match (typ, member) {
+        (Some(StorageType::Struct(_, members)), _) => {
+            let values = members
+                .iter()
+                .filter(|field| field.slot as u64 == member)
+                .map(|field| {
+                    decode(
+                        variable,
+                        format!("{name}.{}", field.label),
+                        &field.typ,
+                        field.offset,
+                        word,
+                    )
+                })
+                .collect::<Vec<_>>();
+            match values.is_empty() {
+                true => vec![untyped(variable, format!("{name}.field_{member}"), word)],
+                false => values,
+            }
+        }
+        (Some(typ), 0) => vec![decode(variable, name, typ, offset, word)],
+        (_, 0) => vec![untyped(variable, name, word)],
+        (_, member) => vec![untyped(variable, format!("{name}.field_{member}"), word)],
+    }
+}
+
+/// The slot of a mapping's value for `key`, where the mapping is stored at `base`.
+pub(crate) fn mapping_slot(key: &FixedBytes<32>, base: &FixedBytes<32>) -> FixedBytes<32> {
+    keccak256([key.as_slice(), base.as_slice()].concat())
+}
+
+/// Renders a mapping key as a number if it's small, an address if it's shaped like one, and as
+/// a word otherwise.
+fn key_label(key: &FixedBytes<32>) -> String {
+    let value = U256::from_be_bytes(key.0);
+    if value <= U256::from(u64::MAX) {
+        value.to_string()
+    } else if key[..12].iter().all(|byte| *byte == 0) {
+        Address::from_slice(&key[12..]).to_string()
+    } else {
+        key.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str, slot: u64, offset: usize, typ: StorageType) -> StorageLayoutEntry {
+        StorageLayoutEntry { label: label.to_string(), slot: U256::from(slot), offset, typ }
+    }
+
+    fn elementary(typ: &str) -> Box<StorageType> {
+        Box::new(StorageType::Elementary(typ.to_string()))
+    }
+
+    fn offset(slot: FixedBytes<32>, by: u64) -> FixedBytes<32> {
+        FixedBytes::from((U256::from_be_bytes(slot.0) + U256::from(by)).to_be_bytes())
+    }
+
+    #[test]
+    fn test_find_variables() {
+        let holder = Address::repeat_byte(0xab).into_word();
+        let spender = Address::repeat_byte(0xcd).into_word();
+        let keys = BTreeSet::from([holder, spender]);
+        let layout = StorageLayout {
+            entries: vec![
+                entry(
+                    "balanceOf",
+                    0,
+                    0,
+                    StorageType::Mapping(elementary("address"), elementary("uint256")),
+                ),
+                entry(
+                    "allowance",
+                    1,
+                    0,
+                    StorageType::Mapping(
+                        elementary("address"),
+                        Box::new(StorageType::Mapping(
+                            elementary("address"),
+                            elementary("uint256"),
+                        )),
+                    ),
+                ),
+                entry("owner", 2, 0, *elementary("address")),
+                entry("paused", 2, 20, *elementary("bool")),
+                entry("holders", 4, 0, StorageType::Array(elementary("uint128"))),
+            ],
+        };
+        let variables = Variables::from_layout(&layout);
+
+        let balance = mapping_slot(&holder, &FixedBytes::with_last_byte(0));
+        let allowance =
+            mapping_slot(&spender, &mapping_slot(&holder, &FixedBytes::with_last_byte(1)));
+        let member = offset(mapping_slot(&spender, &FixedBytes::with_last_byte(3)), 1);
+        let element = offset(keccak256(FixedBytes::<32>::with_last_byte(4)), 2);
+        let unknown = FixedBytes::repeat_byte(0x11);
+
+        let mut packed = FixedBytes::<32>::ZERO;
+        packed[12..].copy_from_slice(Address::repeat_byte(0x01).as_slice());
+        packed[11] = 1;
+        let storage = BTreeMap::from([
+            (FixedBytes::with_last_byte(2), packed),
+            (balance, FixedBytes::with_last_byte(100)),
+            (allowance, FixedBytes::with_last_byte(5)),
+            (member, FixedBytes::with_last_byte(1)),
+            (element, FixedBytes::with_last_byte(7)),
+            (unknown, FixedBytes::with_last_byte(1)),
+        ]);
+
+        let found = find_variables(&storage, &keys, &variables);
+        let summary = |slot: &FixedBytes<32>| {
+            found[slot]
+                .iter()
+                .map(|variable| format!("{} = {}", variable.name, variable.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summary(&FixedBytes::with_last_byte(2)),
+            vec!["owner = 0x0101010101010101010101010101010101010101", "paused = true"]
+        );
+        assert_eq!(
+            summary(&balance),
+            vec!["balanceOf[0xABaBaBaBABabABabAbAbABAbABabababaBaBABaB] = 100"]
+        );
+        assert_eq!(
+            summary(&allowance),
+            vec!["allowance[0xABaBaBaBABabABabAbAbABAbABabababaBaBABaB][0xCdCDCdCdcdcdcdCdcDcDCdcDcDCdCdcdCdcDCDcD] = 5"]
+        );
+        assert_eq!(
+            summary(&member),
+            vec![format!(
+                "slot_3[0xCdCDCdCdcdcdcdCdcDcDCdcDcDCdCdcdCdcDCDcD].field_1 = 0x{}01",
+                "00".repeat(31)
+            )]
+        );
+        assert_eq!(summary(&element), vec!["holders[4] = 7", "holders[5] = 0"]);
+        assert_eq!(found[&balance][0].variable, "balanceOf");
+        assert_eq!(found[&balance][0].typ.as_deref(), Some("uint256"));
+        assert!(!found.contains_key(&unknown));
+    }
+
+    #[test]
+    fn test_key_label() {
+        assert_eq!(key_label(&FixedBytes::with_last_byte(42)), "42");
+        assert_eq!(
+            key_label(&Address::repeat_byte(0x01).into_word()),
+            "0x0101010101010101010101010101010101010101"
+        );
+        assert_eq!(key_label(&FixedBytes::repeat_byte(0x11)), format!("0x{}", "11".repeat(32)));
+    }
+}
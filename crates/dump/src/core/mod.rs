@@ -1,25 +1,73 @@
-use alloy::{
-    primitives::{Address, FixedBytes},
-    rpc::types::trace::parity::Delta,
-};
+pub(crate) mod checkpoint;
+pub(crate) mod chunk;
+pub(crate) mod mappings;
+pub(crate) mod provenance;
+pub(crate) mod token;
+pub(crate) mod values;
+pub(crate) mod watch;
+
+use alloy::primitives::{Address, FixedBytes};
+use checkpoint::Checkpoint;
+use chunk::ChangeSet;
 use eyre::eyre;
-use futures::future::try_join_all;
+use futures::{stream, StreamExt};
 use hashbrown::HashMap;
 use heimdall_common::{
-    ether::rpc::{get_block_state_diff, latest_block_number},
-    utils::time::{calculate_eta, format_eta},
+    ether::rpc::{capabilities, get_block_state_diff, latest_block_number},
+    utils::{
+        metrics::record_phase,
+        progress::report_progress,
+        time::{calculate_eta, format_eta},
+    },
 };
+use heimdall_decompiler::{decompile, DecompilerArgsBuilder, StorageLayout};
+use mappings::{find_variables, Variables};
+use provenance::resolve_callers;
 
-use std::{sync::Arc, time::Instant};
-use tokio::sync::{Mutex, Semaphore};
-use tracing::{debug, info};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+use tracing::{debug, info, warn};
 
 use crate::{error::Error, interfaces::DumpArgs};
 
+pub use provenance::{SlotProvenance, SlotWrite};
+pub use token::{snapshot_token, TokenHolder, TokenOwner, TokenSnapshot};
+pub use values::StorageVariable;
+pub use watch::{watch, StorageChange};
+
+/// The storage of a contract, as dumped by [`dump`].
+#[derive(Debug, Clone, Default)]
+pub struct StorageDump {
+    /// The value of each storage slot accessed by the contract
+    pub storage: HashMap<FixedBytes<32>, FixedBytes<32>>,
+    /// The state variables stored in each slot, decoded by their recovered types, where the
+    /// variables the slot belongs to could be recovered. A slot holds several variables if
+    /// they're packed into it.
+    pub variables: HashMap<FixedBytes<32>, Vec<StorageVariable>>,
+    /// The first block whose storage writes aren't included in the dump
+    pub next_block: u128,
+    /// The words observed in the dumped blocks' traces, which may be the keys of the target's
+    /// mappings
+    pub keys: BTreeSet<FixedBytes<32>>,
+    /// The first and last transactions which wrote each dumped slot. Empty unless `--provenance`
+    /// was passed.
+    pub provenance: HashMap<FixedBytes<32>, SlotProvenance>,
+    /// The target's storage layout, recovered by decompiling it. Empty if `--skip-layout` was
+    /// passed, or the target couldn't be decompiled.
+    pub layout: StorageLayout,
+}
+
 /// Dumps the storage slots for a contract
 ///
 /// This function retrieves storage slots from a contract by analyzing state differences
-/// across multiple blocks. It uses parallel processing to efficiently handle large block ranges.
+/// across multiple blocks. The block range is split into chunks of `--chunk-size` blocks, up to
+/// `--threads` of which are traced at once, and their storage writes are merged in block order.
 ///
 /// # Arguments
 ///
@@ -27,87 +75,199 @@ use crate::{error::Error, interfaces::DumpArgs};
 ///
 /// # Returns
 ///
-/// A HashMap containing the storage slots (keys) and their values
-pub async fn dump(args: DumpArgs) -> Result<HashMap<FixedBytes<32>, FixedBytes<32>>, Error> {
+/// The storage slots and their values, and the state variables the slots belong to
+pub async fn dump(args: DumpArgs) -> Result<StorageDump, Error> {
     let start_time = Instant::now();
-    let storage = Arc::new(Mutex::new(HashMap::new()));
-    let completed_count = Arc::new(Mutex::new(0));
     let target =
         args.target.parse::<Address>().map_err(|e| eyre!("invalid target address: {e}"))?;
 
+    // resume from the checkpoint, if there's one
+    let mut checkpoint = match &args.resume {
+        Some(path) => Checkpoint::load(path, target, args.from_block),
+        None => Checkpoint::new(target, args.from_block),
+    };
+
     // build block range
-    let start_block = args.from_block;
+    let start_block = checkpoint.next_block();
     let to_block = match args.to_block {
         Some(to_block) => to_block,
         None => latest_block_number(&args.rpc_url).await.map_err(|e| eyre!("rpc error: {e}"))?,
     };
+    if start_block > to_block {
+        info!("every block up to {} was already dumped", to_block);
+        return Ok(name_storage(&args, checkpoint).await);
+    }
     let block_range = start_block..=to_block;
-    let block_count = block_range.end() - block_range.start() + 1;
+    let block_count = to_block - args.from_block + 1;
+    let resumed_count = start_block - args.from_block;
     debug!("dumping storage from block range: {:?}", block_range);
 
-    // a quick check to see if the rpc supports trace_ namespace
-    // TODO: dump support via `debug_traceBlockByNumber` w/ prestateTracer as another option
+    // the storage dump is built from state diffs, which require the trace_ or debug_ namespace
+    let capabilities = capabilities(&args.rpc_url).await.map_err(|e| eyre!("rpc error: {e}"))?;
+    if capabilities.trace_namespace().is_none() {
+        return Err(Error::Eyre(eyre!(
+            "dumping storage requires an rpc which supports `trace_replayBlockTransactions` or `debug_traceBlockByNumber`, but the rpc supports neither"
+        )));
+    }
+    if !capabilities.archive {
+        warn!("rpc is not an archive node, so tracing historical blocks may fail");
+    }
+
+    // a quick check to see if the start block can be traced
     let _ = get_block_state_diff(
         (*block_range.start()).try_into().expect("block number overflow"),
         &args.rpc_url,
     )
     .await
-    .map_err(|_| eyre!("failed to `trace_replayBlockTransactions`. does your rpc support it?"))?;
-
-    // create a semaphore with the correct number of permits
-    let semaphore = Arc::new(Semaphore::new(args.threads));
-    let handles = block_range.map(|block_number| {
-        let semaphore = semaphore.clone();
-        let storage = storage.clone();
-        let args = args.clone();
-        let completed_count = completed_count.clone();
-        tokio::spawn(async move {
-            let _permit = semaphore.acquire().await.expect("failed to acquire semaphore permit");
-            let block_trace = get_block_state_diff(block_number as u64, &args.rpc_url)
-                .await
-                .map_err(|e| eyre!("rpc error: {e}"))?;
-
-            // update storage
-            let mut storage = storage.lock().await;
-            block_trace.iter().for_each(|trace| {
-                if let Some(diff) = trace.full_trace.state_diff.as_ref() {
-                    diff.0
-                        .iter()
-                        .filter(|(addr, _)| addr == &&target)
-                        .flat_map(|(_, value)| value.storage.iter())
-                        .for_each(|(slot, diff)| match diff {
-                            Delta::Added(v) => {
-                                storage.insert(*slot, v.to_owned());
-                            }
-                            Delta::Changed(v) => {
-                                storage.insert(*slot, v.to);
-                            }
-                            Delta::Removed(_) => {
-                                storage.remove(slot);
-                            }
-                            _ => {}
-                        });
+    .map_err(|e| eyre!("failed to trace block {}: {e}", block_range.start()))?;
+
+    // trace up to `threads` chunks at once, each of which collects the storage writes of its
+    // blocks. the chunks are merged in order, so that a slot written by several blocks ends with
+    // the last block's value, and the blocks merged so far are always the start of the range
+    let chunk_size = args.chunk_size.max(1);
+    let traced_count = Arc::new(AtomicU64::new(resumed_count as u64));
+    let record_provenance = args.provenance;
+    let mut chunks = stream::iter((start_block..=to_block).step_by(chunk_size as usize))
+        .map(|chunk_start| {
+            let chunk_end = to_block.min(chunk_start + chunk_size - 1);
+            let rpc_url = args.rpc_url.clone();
+            let progress = args.progress.clone();
+            let traced_count = traced_count.clone();
+            async move {
+                let mut change_set = match record_provenance {
+                    true => ChangeSet::with_provenance(),
+                    false => ChangeSet::default(),
+                };
+                for block_number in chunk_start..=chunk_end {
+                    let block_trace = get_block_state_diff(block_number as u64, &rpc_url)
+                        .await
+                        .map_err(|e| eyre!("failed to trace block {block_number}: {e}"))?;
+                    change_set.apply_block(block_number, &block_trace, &target);
+
+                    // print progress
+                    let completed_count = traced_count.fetch_add(1, Ordering::Relaxed) + 1;
+                    let remaining = block_count as u64 - completed_count;
+                    let completed_per_second = (completed_count - resumed_count as u64) as f64 /
+                        start_time.elapsed().as_secs_f64();
+                    info!(
+                        "completed={}  remaining={}  eta={}",
+                        completed_count,
+                        remaining,
+                        format_eta(calculate_eta(completed_per_second, remaining as usize))
+                    );
+                    report_progress(
+                        progress.as_ref(),
+                        completed_count,
+                        block_count as u64,
+                        block_number.to_string(),
+                    );
                 }
-            });
-
-            // print progress
-            let mut completed_count = completed_count.lock().await;
-            *completed_count += 1;
-            let remaining = block_count - *completed_count;
-            let completed_per_second = *completed_count as f64 / start_time.elapsed().as_secs_f64();
-            info!(
-                "completed={}  remaining={}  eta={}",
-                *completed_count,
-                remaining,
-                format_eta(calculate_eta(completed_per_second, remaining as usize))
-            );
-            Ok::<_, Error>(())
+                Ok::<_, eyre::Report>((chunk_end, change_set))
+            }
         })
-    });
+        .buffered(args.threads.max(1));
+
+    while let Some(chunk) = chunks.next().await {
+        let (chunk_end, change_set) = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                // keep the chunks which were merged, so the dump can be resumed from the failure
+                if let Some(path) = &args.resume {
+                    checkpoint.save(path)?;
+                    warn!(
+                        "checkpointed the dump up to block {}, pass `--resume {}` to resume it",
+                        checkpoint.next_block().saturating_sub(1),
+                        path
+                    );
+                }
+                return Err(e.into());
+            }
+        };
 
-    // execute all the tasks
-    try_join_all(handles).await.map_err(|e| eyre!("failed to join tasks: {e}"))?;
+        change_set.merge_into(
+            &mut checkpoint.storage,
+            &mut checkpoint.keys,
+            &mut checkpoint.provenance,
+        );
+        checkpoint.last_block = Some(chunk_end);
+        if let Some(path) = &args.resume {
+            checkpoint.save(path)?;
+        }
+    }
+
+    let storage_dump = name_storage(&args, checkpoint).await;
 
     debug!("storage dump took {:?}", start_time.elapsed());
-    Ok(storage.to_owned().lock().await.to_owned())
+    record_phase("dump", start_time.elapsed());
+    Ok(storage_dump)
+}
+
+/// Finds the state variables stored in the dumped slots, recovering the keys of the mappings
+/// they're stored in from the words observed while dumping. Unless `--skip-layout` is passed,
+/// the target is decompiled to name the variables and decode them by their types, otherwise
+/// they're named by their slot numbers and left undecoded.
+async fn name_storage(args: &DumpArgs, mut checkpoint: Checkpoint) -> StorageDump {
+    let layout = match args.skip_layout {
+        true => StorageLayout::default(),
+        false => recover_layout(&args.target, &args.rpc_url, args.skip_resolving).await,
+    };
+
+    let variables =
+        find_variables(&checkpoint.storage, &checkpoint.keys, &Variables::from_layout(&layout));
+    debug!(
+        "found the variables of {} of {} dumped slots",
+        variables.len(),
+        checkpoint.storage.len()
+    );
+    // the writers of each slot are only looked up once the dump is complete
+    if args.provenance {
+        resolve_callers(&mut checkpoint.provenance, &args.rpc_url, args.threads).await;
+    }
+
+    StorageDump {
+        next_block: checkpoint.next_block(),
+        provenance: checkpoint.provenance.into_iter().collect(),
+        storage: checkpoint.storage.into_iter().collect(),
+        variables,
+        keys: checkpoint.keys,
+        layout,
+    }
+}
+
+/// Decompiles `target` to recover its storage layout, or an empty layout if it can't be
+/// decompiled.
+async fn recover_layout(target: &str, rpc_url: &str, skip_resolving: bool) -> StorageLayout {
+    let layout = match DecompilerArgsBuilder::new()
+        .target(target.to_string())
+        .rpc_url(rpc_url.to_string())
+        .skip_resolving(skip_resolving)
+        .build()
+    {
+        Ok(decompile_args) => decompile(decompile_args)
+            .await
+            .map(|result| result.storage_layout)
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    layout.unwrap_or_else(|e| {
+        warn!("failed to decompile {} to name its storage: {}", target, e);
+        StorageLayout::default()
+    })
+}
+
+/// Finds the state variables of `target` stored in the given slots, as [`dump`] does, by
+/// decompiling it to recover its storage layout. The keys of mapping values are recovered from
+/// the `keys` given, e.g. the words observed in a transaction's trace. Unless `skip_resolving`
+/// is set, variables are named after the getters whose selectors are resolved.
+///
+/// Slots which couldn't be attributed to a variable are left out.
+pub async fn label_slots(
+    target: &str,
+    rpc_url: &str,
+    skip_resolving: bool,
+    storage: &BTreeMap<FixedBytes<32>, FixedBytes<32>>,
+    keys: &BTreeSet<FixedBytes<32>>,
+) -> HashMap<FixedBytes<32>, Vec<StorageVariable>> {
+    let layout = recover_layout(target, rpc_url, skip_resolving).await;
+    find_variables(storage, keys, &Variables::from_layout(&layout))
 }
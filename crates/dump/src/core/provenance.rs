@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, HashMap};
+
+use alloy::{
+    network::TransactionResponse,
+    primitives::{Address, FixedBytes, TxHash},
+    rpc::types::trace::parity::Delta,
+};
+use futures::{stream, StreamExt};
+use heimdall_common::ether::rpc::get_transaction;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// A transaction's write to a storage slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotWrite {
+    /// The block the transaction was included in
+    pub block: u128,
+    /// The transaction which wrote the slot
+    pub transaction: TxHash,
+    /// The sender of the transaction, or `None` if it couldn't be fetched
+    #[serde(default)]
+    pub caller: Option<Address>,
+    /// The slot's value before the write, or `None` if it was empty
+    pub from: Option<FixedBytes<32>>,
+    /// The slot's value after the write, or `None` if it was cleared
+    pub to: Option<FixedBytes<32>>,
+}
+
+/// The transactions which wrote a storage slot within the dumped blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotProvenance {
+    /// The first write to the slot
+    pub first: SlotWrite,
+    /// The last write to the slot, which left its dumped value
+    pub last: SlotWrite,
+    /// The number of writes to the slot
+    pub writes: u64,
+}
+
+impl SlotProvenance {
+    /// The provenance of a slot written once.
+    pub(crate) fn new(write: SlotWrite) -> Self {
+        Self { first: write.clone(), last: write, writes: 1 }
+    }
+
+    /// The writes of both `self`, and the later writes in `other`.
+    pub(crate) fn merge(mut self, other: SlotProvenance) -> Self {
+        self.last = other.last;
+        self.writes += other.writes;
+        self
+    }
+}
+
+/// A transaction's write to a slot, from the slot's state diff.
+pub(crate) fn slot_write(
+    block: u128,
+    transaction: TxHash,
+    diff: &Delta<FixedBytes<32>>,
+) -> Option<SlotWrite> {
+    let (from, to) = match diff {
+        Delta::Added(v) => (None, Some(*v)),
+        Delta::Changed(v) => (Some(v.from), Some(v.to)),
+        Delta::Removed(v) => (Some(*v), None),
+        _ => return None,
+    };
+    Some(SlotWrite { block, transaction, caller: None, from, to })
+}
+
+/// Merges the provenance of a later chunk of blocks into `provenance`.
+pub(crate) fn merge_provenance(
+    provenance: &mut BTreeMap<FixedBytes<32>, SlotProvenance>,
+    later: BTreeMap<FixedBytes<32>, SlotProvenance>,
+) {
+    for (slot, later) in later {
+        merge_slot(provenance, slot, later);
+    }
+}
+
+/// Merges later writes to `slot` into its provenance.
+pub(crate) fn merge_slot(
+    provenance: &mut BTreeMap<FixedBytes<32>, SlotProvenance>,
+    slot: FixedBytes<32>,
+    later: SlotProvenance,
+) {
+    let merged = match provenance.remove(&slot) {
+        Some(earlier) => earlier.merge(later),
+        None => later,
+    };
+    provenance.insert(slot, merged);
+}
+
+/// Fills in the caller of each write, by fetching the transactions which wrote the slots, up to
+/// `threads` at once. Transactions which can't be fetched are left without a caller.
+pub(crate) async fn resolve_callers(
+    provenance: &mut BTreeMap<FixedBytes<32>, SlotProvenance>,
+    rpc_url: &str,
+    threads: usize,
+) {
+    let mut transactions = provenance
+        .values()
+        .flat_map(|slot| [slot.first.transaction, slot.last.transaction])
+        .collect::<Vec<_>>();
+    transactions.sort();
+    transactions.dedup();
+
+    let callers = stream::iter(transactions)
+        .map(|transaction| async move {
+            let caller = get_transaction(transaction, rpc_url)
+                .await
+                .map(|tx| tx.from())
+                .map_err(|e| debug!("failed to fetch transaction {}: {}", transaction, e))
+                .ok();
+            (transaction, caller)
+        })
+        .buffer_unordered(threads.max(1))
+        .collect::<HashMap<_, _>>()
+        .await;
+
+    for slot in provenance.values_mut() {
+        for write in [&mut slot.first, &mut slot.last] {
+            write.caller = callers.get(&write.transaction).copied().flatten();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::types::trace::parity::ChangedType;
+
+    use super::*;
+
+    #[test]
+    fn test_provenance_merges_in_order() {
+        let (slot, value) = (FixedBytes::with_last_byte(1), FixedBytes::repeat_byte);
+        let write = |block: u128, diff: Delta<FixedBytes<32>>| {
+            slot_write(block, TxHash::with_last_byte(block as u8), &diff).expect("no write")
+        };
+
+        let mut provenance =
+            BTreeMap::from([(slot, SlotProvenance::new(write(1, Delta::Added(value(1)))))]);
+        merge_provenance(
+            &mut provenance,
+            BTreeMap::from([(
+                slot,
+                SlotProvenance::new(write(
+                    5,
+                    Delta::Changed(ChangedType { from: value(1), to: value(2) }),
+                ))
+                .merge(SlotProvenance::new(write(7, Delta::Removed(value(2))))),
+            )]),
+        );
+
+        let slot = &provenance[&slot];
+        assert_eq!(slot.writes, 3);
+        assert_eq!((slot.first.block, slot.first.from, slot.first.to), (1, None, Some(value(1))));
+        assert_eq!((slot.last.block, slot.last.from, slot.last.to), (7, Some(value(2)), None));
+        assert_eq!(slot.last.transaction, TxHash::with_last_byte(7));
+    }
+}
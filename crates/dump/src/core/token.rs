@@ -0,0 +1,561 @@
+use std::{collections::BTreeMap, time::Instant};
+
+use alloy::{
+    eips::BlockId,
+    primitives::{keccak256, Address, FixedBytes, I256, U256},
+    rpc::types::{Filter, Log},
+    sol,
+    sol_types::SolEvent,
+};
+use eyre::eyre;
+use futures::{stream, StreamExt};
+use heimdall_common::{
+    ether::rpc::{get_contract_creation_block, get_logs, get_storage_at, latest_block_number},
+    utils::metrics::record_phase,
+};
+use tracing::{debug, info, warn};
+
+use super::mappings::{mapping_slot, MAX_BASE_SLOT};
+use crate::{
+    error::Error,
+    interfaces::{SnapshotTokenArgs, TokenStandard},
+};
+
+sol! {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+}
+
+/// The number of holders (or tokens) whose slots are read at each candidate base slot, when
+/// probing for the token's balance mapping.
+const PROBE_SAMPLES: usize = 4;
+
+/// The ERC-7201 namespaces of OpenZeppelin's upgradeable tokens, and the offset of the balance
+/// (or owner) mapping within each namespace's struct.
+const NAMESPACED_MAPPINGS: [(&str, u64); 2] =
+    [("openzeppelin.storage.ERC20", 0), ("openzeppelin.storage.ERC721", 2)];
+
+/// A token's balances (or owners) at a block, as replayed from its Transfer logs and read from
+/// its storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenSnapshot {
+    /// The token's standard
+    pub standard: TokenStandard,
+    /// The block the token was snapshotted at
+    pub block: u128,
+    /// The slot of the token's balance (or owner) mapping, or `None` if no mapping in its
+    /// storage agreed with its Transfer logs, in which case the snapshot is from the logs alone
+    pub balance_slot: Option<U256>,
+    /// The balance of each holder of an ERC-20, largest first
+    pub holders: Vec<TokenHolder>,
+    /// The owner of each token of an ERC-721, by token id
+    pub owners: Vec<TokenOwner>,
+}
+
+/// An ERC-20 holder's balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenHolder {
+    /// The holder
+    pub holder: Address,
+    /// The holder's balance: the balance in storage, or the log balance if it couldn't be read
+    pub balance: U256,
+    /// The amounts transferred to the holder less those transferred from it, in the Transfer
+    /// logs. Negative where the logs under-report the holder's receipts.
+    pub log_balance: I256,
+    /// The holder's balance read from the token's balance mapping
+    pub storage_balance: Option<U256>,
+}
+
+impl TokenHolder {
+    /// Whether the holder's storage balance differs from its log balance, e.g. because the
+    /// token takes a fee on transfers, or rebases its balances.
+    pub fn is_discrepancy(&self) -> bool {
+        self.storage_balance.is_some_and(|balance| I256::try_from(balance) != Ok(self.log_balance))
+    }
+}
+
+/// An ERC-721 token's owner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenOwner {
+    /// The token
+    pub token_id: U256,
+    /// The token's owner: the owner in storage, or the log owner if it couldn't be read
+    pub owner: Address,
+    /// The recipient of the token's last Transfer log, or the zero address if it was burned
+    pub log_owner: Address,
+    /// The token's owner read from the token's owner mapping. `None` where the token's slot is
+    /// empty, as tokens minted in batches (e.g. by ERC721A) may only store their first token's
+    /// owner.
+    pub storage_owner: Option<Address>,
+}
+
+impl TokenOwner {
+    /// Whether the token's storage owner differs from its log owner.
+    pub fn is_discrepancy(&self) -> bool {
+        self.storage_owner.is_some_and(|owner| owner != self.log_owner)
+    }
+}
+
+/// A Transfer log of an ERC-20's amount, or an ERC-721's token id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TokenTransfer {
+    standard: TokenStandard,
+    from: Address,
+    to: Address,
+    /// The amount transferred, or the token id
+    value: U256,
+}
+
+/// Snapshots the balances of an ERC-20, or the owners of an ERC-721's tokens, at a block.
+///
+/// The token's Transfer logs are replayed into a holder→balance (or tokenId→owner) table, which
+/// is cross-checked against the token's storage. The balance mapping is found by probing the
+/// token's storage for the mapping which agrees with the logs, and each holder's balance is read
+/// from it. Where the storage disagrees with the logs, such as for fee-on-transfer or rebasing
+/// tokens, the storage balance is reported, and the holder is marked as a discrepancy.
+pub async fn snapshot_token(args: SnapshotTokenArgs) -> Result<TokenSnapshot, Error> {
+    let start_time = Instant::now();
+    let target =
+        args.target.parse::<Address>().map_err(|e| eyre!("invalid target address: {e}"))?;
+
+    // build block range
+    let block = match args.block {
+        Some(block) => block,
+        None => latest_block_number(&args.rpc_url).await.map_err(|e| eyre!("rpc error: {e}"))?,
+    };
+    let from_block = match args.from_block {
+        Some(from_block) => from_block,
+        None => get_contract_creation_block(target, &args.rpc_url)
+            .await
+            .map(|block| block as u128)
+            .unwrap_or_else(|e| {
+                warn!("failed to find the token's creation block, scanning from block 0: {}", e);
+                0
+            }),
+    };
+
+    let transfers = fetch_transfers(&args, target, from_block, block).await?;
+    let standard = match args.standard {
+        Some(standard) => standard,
+        None => detect_standard(&transfers)?,
+    };
+    let transfers =
+        transfers.into_iter().filter(|transfer| transfer.standard == standard).collect::<Vec<_>>();
+    info!("replaying {} Transfer logs from block {} to {}", transfers.len(), from_block, block);
+
+    let snapshot = match standard {
+        TokenStandard::Erc20 => snapshot_balances(&args, target, block, &transfers).await,
+        TokenStandard::Erc721 => snapshot_owners(&args, target, block, &transfers).await,
+    };
+
+    debug!("token snapshot took {:?}", start_time.elapsed());
+    record_phase("snapshot_token", start_time.elapsed());
+    Ok(snapshot)
+}
+
+/// Fetches the token's Transfer logs from `from_block` to `to_block`, in the order they were
+/// emitted. The logs are requested in chunks of `--chunk-size` blocks, up to `--threads` at once.
+async fn fetch_transfers(
+    args: &SnapshotTokenArgs,
+    target: Address,
+    from_block: u128,
+    to_block: u128,
+) -> Result<Vec<TokenTransfer>, Error> {
+    let chunk_size = args.chunk_size.max(1);
+    let mut chunks = stream::iter((from_block..=to_block).step_by(chunk_size as usize))
+        .map(|chunk_start| {
+            let chunk_end = to_block.min(chunk_start + chunk_size - 1);
+            let filter = Filter::new()
+                .address(target)
+                .event_signature(Transfer::SIGNATURE_HASH)
+                .from_block(chunk_start as u64)
+                .to_block(chunk_end as u64);
+            async move {
+                get_logs(&filter, &args.rpc_url).await.map_err(|e| {
+                    eyre!(
+                        "failed to get Transfer logs from block {chunk_start} to {chunk_end}: {e}"
+                    )
+                })
+            }
+        })
+        .buffered(args.threads.max(1));
+
+    let mut transfers = Vec::new();
+    while let Some(logs) = chunks.next().await {
+        transfers.extend(logs?.iter().filter_map(parse_transfer));
+    }
+    Ok(transfers)
+}
+
+/// Parses a Transfer log, which is an ERC-721's if it indexes the token id, and an ERC-20's if
+/// it logs the amount.
+fn parse_transfer(log: &Log) -> Option<TokenTransfer> {
+    let topics = log.topics();
+    if topics.first() != Some(&Transfer::SIGNATURE_HASH) {
+        return None;
+    }
+    let from = Address::from_word(*topics.get(1)?);
+    let to = Address::from_word(*topics.get(2)?);
+
+    match (topics.get(3), log.data().data.len()) {
+        (Some(token_id), 0) => Some(TokenTransfer {
+            standard: TokenStandard::Erc721,
+            from,
+            to,
+            value: U256::from_be_bytes(token_id.0),
+        }),
+        (None, 32) => Some(TokenTransfer {
+            standard: TokenStandard::Erc20,
+            from,
+            to,
+            value: U256::from_be_slice(&log.data().data),
+        }),
+        _ => None,
+    }
+}
+
+/// The standard most of the token's Transfer logs follow.
+fn detect_standard(transfers: &[TokenTransfer]) -> Result<TokenStandard, Error> {
+    let erc721 = transfers.iter().filter(|t| t.standard == TokenStandard::Erc721).count();
+    match (transfers.is_empty(), erc721 * 2 > transfers.len()) {
+        (true, _) => Err(Error::Eyre(eyre!(
+            "the target emitted no Transfer logs in the block range, pass `--standard` to snapshot it anyway"
+        ))),
+        (false, true) => Ok(TokenStandard::Erc721),
+        (false, false) => Ok(TokenStandard::Erc20),
+    }
+}
+
+/// Replays ERC-20 Transfer logs into each address's log balance. Mints and burns are transfers
+/// from and to the zero address, whose balance isn't tracked.
+fn replay_balances(transfers: &[TokenTransfer]) -> BTreeMap<Address, I256> {
+    let mut balances: BTreeMap<Address, I256> = BTreeMap::new();
+    for transfer in transfers {
+        let amount = I256::try_from(transfer.value).unwrap_or(I256::MAX);
+        if !transfer.from.is_zero() {
+            let balance = balances.entry(transfer.from).or_default();
+            *balance = balance.saturating_sub(amount);
+        }
+        if !transfer.to.is_zero() {
+            let balance = balances.entry(transfer.to).or_default();
+            *balance = balance.saturating_add(amount);
+        }
+    }
+    balances
+}
+
+/// Replays ERC-721 Transfer logs into each token's log owner, which is the zero address for
+/// burned tokens.
+fn replay_owners(transfers: &[TokenTransfer]) -> BTreeMap<U256, Address> {
+    transfers.iter().map(|transfer| (transfer.value, transfer.to)).collect()
+}
+
+/// The slots a token's balance (or owner) mapping may be stored at: the first state variables,
+/// and the mappings in OpenZeppelin's ERC-7201 namespaces.
+fn candidate_bases() -> Vec<U256> {
+    (0..MAX_BASE_SLOT)
+        .map(U256::from)
+        .chain(
+            NAMESPACED_MAPPINGS
+                .iter()
+                .map(|(namespace, offset)| erc7201_slot(namespace) + U256::from(*offset)),
+        )
+        .collect()
+}
+
+/// The slot of an ERC-7201 namespace: `keccak(keccak(namespace) - 1) & ~0xff`.
+fn erc7201_slot(namespace: &str) -> U256 {
+    let id = U256::from_be_bytes(keccak256(namespace.as_bytes()).0) - U256::from(1);
+    U256::from_be_bytes(keccak256(id.to_be_bytes::<32>()).0) & !U256::from(0xff)
+}
+
+/// Finds the base slot of the mapping which holds each key's expected value, by reading each
+/// sample key's slot under every candidate base. The base which holds the most expected values
+/// is chosen, breaking ties by the most non-empty values, and `None` is returned if every
+/// candidate's slots are empty.
+async fn find_balance_slot(
+    args: &SnapshotTokenArgs,
+    target: Address,
+    block: u128,
+    samples: &[(FixedBytes<32>, U256)],
+) -> Option<U256> {
+    let bases = candidate_bases();
+    let reads = bases
+        .iter()
+        .flat_map(|base| samples.iter().map(move |(key, expected)| (*base, *key, *expected)))
+        .collect::<Vec<_>>();
+    let values =
+        read_slots(args, target, block, reads.iter().map(|(base, key, _)| (*key, *base))).await;
+
+    let mut scores: BTreeMap<U256, (usize, usize)> = BTreeMap::new();
+    for ((base, _, expected), value) in reads.iter().zip(values) {
+        let score = scores.entry(*base).or_default();
+        match value {
+            Some(value) if value == *expected => *score = (score.0 + 1, score.1 + 1),
+            Some(value) if !value.is_zero() => score.1 += 1,
+            _ => {}
+        }
+    }
+    let best = bases
+        .iter()
+        .filter_map(|base| scores.get(base).map(|score| (*base, *score)))
+        .filter(|(_, (_, non_empty))| *non_empty > 0)
+        .fold(None, |best: Option<(U256, (usize, usize))>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate),
+        });
+    debug!("probed {} base slots for the balance mapping, found {:?}", bases.len(), best);
+    best.map(|(base, _)| base)
+}
+
+/// Reads the value of each `(key, base)` mapping slot at `block`, up to `--threads` at once.
+/// Slots which couldn't be read are `None`.
+async fn read_slots(
+    args: &SnapshotTokenArgs,
+    target: Address,
+    block: u128,
+    slots: impl Iterator<Item = (FixedBytes<32>, U256)>,
+) -> Vec<Option<U256>> {
+    stream::iter(slots)
+        .map(|(key, base)| async move {
+            let slot = mapping_slot(&key, &FixedBytes::from(base.to_be_bytes::<32>()));
+            get_storage_at(
+                target,
+                U256::from_be_bytes(slot.0),
+                Some(BlockId::number(block as u64)),
+                &args.rpc_url,
+            )
+            .await
+            .map_err(|e| debug!("failed to read slot {}: {}", slot, e))
+            .ok()
+        })
+        .buffered(args.threads.max(1))
+        .collect()
+        .await
+}
+
+/// Snapshots an ERC-20's holders, whose balances are read from storage and cross-checked
+/// against their log balances.
+async fn snapshot_balances(
+    args: &SnapshotTokenArgs,
+    target: Address,
+    block: u128,
+    transfers: &[TokenTransfer],
+) -> TokenSnapshot {
+    let log_balances = replay_balances(transfers);
+
+    // the largest holders are the likeliest to hold their log balance in storage
+    let mut samples = log_balances
+        .iter()
+        .filter(|(_, balance)| balance.is_positive())
+        .map(|(holder, balance)| (holder.into_word(), balance.into_raw()))
+        .collect::<Vec<_>>();
+    samples.sort_by(|a, b| b.1.cmp(&a.1));
+    samples.truncate(PROBE_SAMPLES);
+
+    let balance_slot = match args.balance_slot {
+        Some(slot) => Some(slot),
+        None => find_balance_slot(args, target, block, &samples).await,
+    };
+    let storage_balances = match balance_slot {
+        Some(base) => {
+            read_slots(args, target, block, log_balances.keys().map(|h| (h.into_word(), base)))
+                .await
+        }
+        None => {
+            warn!("no mapping in the token's storage agrees with its Transfer logs, so its balances are from the logs alone");
+            vec![None; log_balances.len()]
+        }
+    };
+
+    let mut holders = log_balances
+        .into_iter()
+        .zip(storage_balances)
+        .map(|((holder, log_balance), storage_balance)| TokenHolder {
+            holder,
+            balance: storage_balance.unwrap_or_else(|| match log_balance.is_positive() {
+                true => log_balance.into_raw(),
+                false => U256::ZERO,
+            }),
+            log_balance,
+            storage_balance,
+        })
+        .filter(|holder| !holder.balance.is_zero() || !holder.log_balance.is_zero())
+        .collect::<Vec<_>>();
+    holders.sort_by(|a, b| b.balance.cmp(&a.balance).then(a.holder.cmp(&b.holder)));
+    info!(
+        "found {} holders, {} of whose storage balances differ from their logs",
+        holders.len(),
+        holders.iter().filter(|holder| holder.is_discrepancy()).count()
+    );
+
+    TokenSnapshot {
+        standard: TokenStandard::Erc20,
+        block,
+        balance_slot,
+        holders,
+        owners: Vec::new(),
+    }
+}
+
+/// Snapshots an ERC-721's tokens, whose owners are read from storage and cross-checked against
+/// their log owners.
+async fn snapshot_owners(
+    args: &SnapshotTokenArgs,
+    target: Address,
+    block: u128,
+    transfers: &[TokenTransfer],
+) -> TokenSnapshot {
+    let log_owners = replay_owners(transfers);
+    let samples = log_owners
+        .iter()
+        .filter(|(_, owner)| !owner.is_zero())
+        .take(PROBE_SAMPLES)
+        .map(|(token_id, owner)| {
+            (FixedBytes::from(token_id.to_be_bytes::<32>()), owner_word(owner))
+        })
+        .collect::<Vec<_>>();
+
+    let balance_slot = match args.balance_slot {
+        Some(slot) => Some(slot),
+        None => find_balance_slot(args, target, block, &samples).await,
+    };
+    let storage_owners = match balance_slot {
+        Some(base) => {
+            let keys = log_owners.keys().map(|id| (FixedBytes::from(id.to_be_bytes::<32>()), base));
+            read_slots(args, target, block, keys).await
+        }
+        None => {
+            warn!("no mapping in the token's storage agrees with its Transfer logs, so its owners are from the logs alone");
+            vec![None; log_owners.len()]
+        }
+    };
+
+    let owners = log_owners
+        .into_iter()
+        .zip(storage_owners)
+        .map(|((token_id, log_owner), storage_owner)| {
+            // owners may be packed with other data, as ERC721A does, in the slot's lowest bytes
+            let storage_owner = storage_owner
+                .filter(|value| !value.is_zero())
+                .map(|value| Address::from_word(value.to_be_bytes::<32>().into()));
+            TokenOwner {
+                token_id,
+                owner: storage_owner.unwrap_or(log_owner),
+                log_owner,
+                storage_owner,
+            }
+        })
+        .filter(|token| !token.owner.is_zero())
+        .collect::<Vec<_>>();
+    info!(
+        "found {} tokens, {} of whose storage owners differ from their logs",
+        owners.len(),
+        owners.iter().filter(|token| token.is_discrepancy()).count()
+    );
+
+    TokenSnapshot {
+        standard: TokenStandard::Erc721,
+        block,
+        balance_slot,
+        holders: Vec::new(),
+        owners,
+    }
+}
+
+/// An owner as the value of its token's slot.
+fn owner_word(owner: &Address) -> U256 {
+    U256::from_be_bytes(owner.into_word().0)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{b256, Bytes, LogData};
+
+    use super::*;
+
+    fn transfer_log(topics: Vec<FixedBytes<32>>, data: Vec<u8>) -> Log {
+        Log {
+            inner: alloy::primitives::Log {
+                address: Address::ZERO,
+                data: LogData::new_unchecked(topics, Bytes::from(data)),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_transfer() {
+        let (from, to) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        let erc20 = transfer_log(
+            vec![Transfer::SIGNATURE_HASH, from.into_word(), to.into_word()],
+            U256::from(5).to_be_bytes::<32>().to_vec(),
+        );
+        let erc721 = transfer_log(
+            vec![
+                Transfer::SIGNATURE_HASH,
+                from.into_word(),
+                to.into_word(),
+                FixedBytes::with_last_byte(7),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(
+            parse_transfer(&erc20),
+            Some(TokenTransfer { standard: TokenStandard::Erc20, from, to, value: U256::from(5) })
+        );
+        assert_eq!(
+            parse_transfer(&erc721),
+            Some(TokenTransfer { standard: TokenStandard::Erc721, from, to, value: U256::from(7) })
+        );
+        assert_eq!(parse_transfer(&transfer_log(vec![Transfer::SIGNATURE_HASH], Vec::new())), None);
+    }
+
+    #[test]
+    fn test_replay_transfers() {
+        let (alice, bob) = (Address::repeat_byte(0x01), Address::repeat_byte(0x02));
+        let transfer = |from, to, value: u64| TokenTransfer {
+            standard: TokenStandard::Erc20,
+            from,
+            to,
+            value: U256::from(value),
+        };
+
+        // a mint, a transfer, and a transfer which logs more than the sender received
+        let balances = replay_balances(&[
+            transfer(Address::ZERO, alice, 10),
+            transfer(alice, bob, 4),
+            transfer(bob, alice, 6),
+        ]);
+        assert_eq!(balances.get(&alice), Some(&I256::try_from(12).unwrap()));
+        assert_eq!(balances.get(&bob), Some(&I256::try_from(-2).unwrap()));
+        assert_eq!(balances.get(&Address::ZERO), None);
+
+        // a mint, a transfer and a burn
+        let owners = replay_owners(&[
+            transfer(Address::ZERO, alice, 1),
+            transfer(Address::ZERO, alice, 2),
+            transfer(alice, bob, 1),
+            transfer(alice, Address::ZERO, 2),
+        ]);
+        assert_eq!(owners, BTreeMap::from([(U256::from(1), bob), (U256::from(2), Address::ZERO)]));
+
+        let holder = TokenHolder {
+            holder: alice,
+            balance: U256::from(11),
+            log_balance: I256::try_from(12).unwrap(),
+            storage_balance: Some(U256::from(11)),
+        };
+        assert!(holder.is_discrepancy());
+    }
+
+    #[test]
+    fn test_erc7201_slot() {
+        assert_eq!(
+            erc7201_slot("openzeppelin.storage.ERC20"),
+            U256::from_be_bytes(
+                b256!("52c63247e1f47db19d5ce0460030c497f067ca4cebf71ba98eeadabe20bace00").0
+            )
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use alloy::{
+    hex,
+    primitives::{Address, FixedBytes, I256, U256},
+};
+use heimdall_common::utils::hex::ToLowerHex;
+use heimdall_decompiler::StorageType;
+
+/// A state variable, or part of one, stored in a dumped slot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageVariable {
+    /// The state variable the value belongs to, e.g. `balanceOf`
+    pub variable: String,
+    /// The value's access path from the state variable, e.g. `balanceOf[0xabc…]` for a mapping's
+    /// value, or `config.owner` for a struct's member
+    pub name: String,
+    /// The value's type, if it was recovered, e.g. `address`
+    pub typ: Option<String>,
+    /// The value decoded according to its type, e.g. `true` for a `bool`, or the slot's raw value
+    /// if its type wasn't recovered
+    pub value: String,
+}
+
+/// The value of `name`, whose part of the slot is `word` at byte `offset` (counted from the
+/// word's least significant byte, as solc does).
+pub(crate) fn decode(
+    variable: &str,
+    name: String,
+    typ: &StorageType,
+    offset: usize,
+    word: &FixedBytes<32>,
+) -> StorageVariable {
+    let size = typ.size().min(32);
+    let end = 32usize.saturating_sub(offset);
+    let bytes = &word[end.saturating_sub(size)..end];
+
+    let (name, value) = match typ {
+        StorageType::Elementary(typ) => (name, decode_elementary(typ, bytes, word)),
+
+        // a dynamic array's slot holds its length, its elements are stored from keccak(slot)
+        StorageType::Array(_) => (format!("{name}.length"), U256::from_be_slice(bytes).to_string()),
+        StorageType::Mapping(..) | StorageType::Struct(..) => (name, word.to_lower_hex()),
+    };
+    StorageVariable { variable: variable.to_string(), name, typ: Some(typ.label()), value }
+}
+
+/// A value whose type wasn't recovered, shown as the slot's raw value.
+pub(crate) fn untyped(variable: &str, name: String, word: &FixedBytes<32>) -> StorageVariable {
+    StorageVariable { variable: variable.to_string(), name, typ: None, value: word.to_lower_hex() }
+}
+
+/// Decodes a value type from the `bytes` it occupies in `word`.
+fn decode_elementary(typ: &str, bytes: &[u8], word: &FixedBytes<32>) -> String {
+    match typ {
+        "address" => Address::from_slice(&bytes[bytes.len().saturating_sub(20)..]).to_string(),
+        "bool" => (bytes.iter().any(|byte| *byte != 0)).to_string(),
+        "string" | "bytes" => decode_short_bytes(typ, word),
+        typ if typ.starts_with("uint") => U256::from_be_slice(bytes).to_string(),
+        typ if typ.starts_with("int") => {
+            let bits = bytes.len() * 8;
+            let raw = U256::from_be_slice(bytes);
+            match bits < 256 && raw.bit(bits - 1) {
+                true => I256::from_raw(raw | (U256::MAX << bits)).to_string(),
+                false => I256::from_raw(raw).to_string(),
+            }
+        }
+        _ => hex::encode_prefixed(bytes),
+    }
+}
+
+/// Decodes a `string` or `bytes` stored in its slot. Values of up to 31 bytes are stored in the
+/// slot itself, with twice their length in the lowest byte. Longer values store `length * 2 + 1`
+/// in the slot, and their contents from `keccak(slot)`, which aren't decoded.
+fn decode_short_bytes(typ: &str, word: &FixedBytes<32>) -> String {
+    if word[31] & 1 == 1 {
+        let length = (U256::from_be_bytes(word.0) - U256::from(1)) / U256::from(2);
+        return format!("<{length} bytes>");
+    }
+
+    let contents = &word[..(word[31] as usize / 2).min(31)];
+    match typ {
+        "string" => String::from_utf8_lossy(contents).to_string(),
+        _ => hex::encode_prefixed(contents),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn elementary(typ: &str) -> StorageType {
+        StorageType::Elementary(typ.to_string())
+    }
+
+    #[test]
+    fn test_decode_packed_slot() {
+        // `address owner; bool paused; int16 delta` packed into one slot
+        let mut word = FixedBytes::<32>::ZERO;
+        word[12..32].copy_from_slice(Address::repeat_byte(0x01).as_slice());
+        word[11] = 1;
+        word[9..11].copy_from_slice(&(-2i16).to_be_bytes());
+
+        let owner = decode("owner", "owner".to_string(), &elementary("address"), 0, &word);
+        assert_eq!(owner.value, "0x0101010101010101010101010101010101010101");
+        assert_eq!(owner.typ.as_deref(), Some("address"));
+        assert_eq!(
+            decode("paused", "paused".to_string(), &elementary("bool"), 20, &word).value,
+            "true"
+        );
+        assert_eq!(
+            decode("delta", "delta".to_string(), &elementary("int16"), 21, &word).value,
+            "-2"
+        );
+    }
+
+    #[test]
+    fn test_decode_strings_and_arrays() {
+        let mut word = FixedBytes::<32>::ZERO;
+        word[..5].copy_from_slice(b"hello");
+        word[31] = 10;
+        assert_eq!(
+            decode("name", "name".to_string(), &elementary("string"), 0, &word).value,
+            "hello"
+        );
+
+        let long = FixedBytes::with_last_byte(201);
+        assert_eq!(
+            decode("name", "name".to_string(), &elementary("string"), 0, &long).value,
+            "<100 bytes>"
+        );
+
+        let array = StorageType::Array(Box::new(elementary("uint256")));
+        let length =
+            decode("holders", "holders".to_string(), &array, 0, &FixedBytes::with_last_byte(3));
+        assert_eq!((length.name.as_str(), length.value.as_str()), ("holders.length", "3"));
+    }
+}
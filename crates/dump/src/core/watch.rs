@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use alloy::{
+    primitives::{Address, FixedBytes, TxHash},
+    rpc::types::trace::parity::Delta,
+};
+use eyre::eyre;
+use futures::StreamExt;
+use heimdall_common::ether::rpc::{get_block_state_diff, subscribe_block_numbers};
+use tracing::{debug, info, warn};
+
+use super::{
+    mappings::{find_variables, Variables},
+    StorageDump, StorageVariable,
+};
+use crate::{error::Error, interfaces::DumpArgs};
+
+/// A change to one of the target's storage slots in a block, observed while watching for new
+/// blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageChange {
+    /// The block the slot was written in
+    pub block: u128,
+    /// The slot which was written
+    pub slot: FixedBytes<32>,
+    /// The slot's value before the block, or `None` if it was empty
+    pub from: Option<FixedBytes<32>>,
+    /// The slot's value after the block, or `None` if it was cleared
+    pub to: Option<FixedBytes<32>>,
+    /// The transactions which wrote the slot, in order
+    pub transactions: Vec<TxHash>,
+    /// The state variables stored in the slot after the block, decoded by their recovered types
+    pub variables: Vec<StorageVariable>,
+}
+
+/// Watches for blocks after those already in `storage_dump`, applying the target's storage
+/// writes in each block to the dump, and passing each changed slot to `on_change` as the block
+/// is traced. Blocks are traced in order, so none are missed if several are added at once.
+///
+/// New blocks are subscribed to over websocket rpc urls, and polled for otherwise. Watching runs
+/// until the subscription ends, tracing a block fails, or `on_change` fails.
+pub async fn watch(
+    args: &DumpArgs,
+    storage_dump: &mut StorageDump,
+    mut on_change: impl FnMut(StorageChange) -> eyre::Result<()>,
+) -> Result<(), Error> {
+    let target =
+        args.target.parse::<Address>().map_err(|e| eyre!("invalid target address: {e}"))?;
+    let variables = Variables::from_layout(&storage_dump.layout);
+    let mut blocks = subscribe_block_numbers(&args.rpc_url)
+        .await
+        .map_err(|e| eyre!("failed to subscribe to new blocks: {e}"))?;
+    info!("watching for storage changes from block {}", storage_dump.next_block);
+
+    while let Some(latest_block) = blocks.next().await {
+        let latest_block = match latest_block {
+            Ok(latest_block) => latest_block as u128,
+            Err(e) => {
+                warn!("failed to get the latest block: {}", e);
+                continue;
+            }
+        };
+
+        while storage_dump.next_block <= latest_block {
+            let block = storage_dump.next_block;
+            let block_trace = get_block_state_diff(block as u64, &args.rpc_url)
+                .await
+                .map_err(|e| eyre!("failed to trace block {block}: {e}"))?;
+
+            // the first and last values of each slot the block wrote, and the transactions
+            // which wrote it
+            let mut changes: BTreeMap<FixedBytes<32>, StorageChange> = BTreeMap::new();
+            for trace in &block_trace {
+                let Some(diff) = trace.full_trace.state_diff.as_ref() else { continue };
+                storage_dump.keys.extend(diff.0.keys().map(|addr| addr.into_word()));
+
+                let Some(account) = diff.0.get(&target) else { continue };
+                for (slot, delta) in &account.storage {
+                    let (from, to) = match delta {
+                        Delta::Added(v) => (None, Some(*v)),
+                        Delta::Changed(v) => (Some(v.from), Some(v.to)),
+                        Delta::Removed(v) => (Some(*v), None),
+                        _ => continue,
+                    };
+                    storage_dump.keys.extend(to);
+                    let change = changes.entry(*slot).or_insert_with(|| StorageChange {
+                        block,
+                        slot: *slot,
+                        from,
+                        to,
+                        transactions: Vec::new(),
+                        variables: Vec::new(),
+                    });
+                    change.to = to;
+                    change.transactions.push(trace.transaction_hash);
+                }
+            }
+
+            // apply the block's writes, and find the variables stored in the written slots
+            for change in changes.values() {
+                match change.to {
+                    Some(value) => storage_dump.storage.insert(change.slot, value),
+                    None => storage_dump.storage.remove(&change.slot),
+                };
+            }
+            let written = changes
+                .values()
+                .filter_map(|change| change.to.map(|value| (change.slot, value)))
+                .collect::<BTreeMap<_, _>>();
+            let mut found = find_variables(&written, &storage_dump.keys, &variables);
+            for change in changes.values_mut() {
+                change.variables = found.remove(&change.slot).unwrap_or_default();
+                match change.variables.is_empty() {
+                    true => storage_dump.variables.remove(&change.slot),
+                    false => storage_dump.variables.insert(change.slot, change.variables.clone()),
+                };
+            }
+
+            debug!("block {} changed {} storage slots", block, changes.len());
+            for change in changes.into_values() {
+                on_change(change)?;
+            }
+            storage_dump.next_block += 1;
+        }
+    }
+
+    Err(Error::Eyre(eyre!("the subscription to new blocks ended")))
+}
@@ -1,5 +1,6 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use derive_builder::Builder;
+use heimdall_common::utils::progress::ProgressSender;
 use heimdall_config::parse_url_arg;
 
 #[derive(Debug, Clone, Parser, Builder)]
@@ -17,19 +18,27 @@ pub struct DumpArgs {
     #[clap(required = true)]
     pub target: String,
 
-    /// The output directory to write the output to or 'print' to print to the console
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
     #[clap(long = "output", short, default_value = "output", hide_default_value = true)]
     pub output: String,
 
     /// The RPC URL to use for fetching data.
-    /// This can be an explicit URL or a reference to a MESC endpoint.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
     #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
     pub rpc_url: String,
 
-    /// The number of threads to use when fetching data.
+    /// The number of threads to use when fetching data. Each thread traces one chunk of the
+    /// block range at a time.
     #[clap(long, default_value = "4", hide_default_value = true)]
     pub threads: usize,
 
+    /// The number of consecutive blocks each thread traces before its storage writes are merged
+    /// into the dump. When resuming, the dump is checkpointed after each merged chunk.
+    #[clap(long, default_value = "100")]
+    pub chunk_size: u128,
+
     /// The block number to start dumping from.
     #[clap(long, short, default_value = "0", hide_default_value = true, alias = "start_block")]
     pub from_block: u128,
@@ -38,9 +47,50 @@ pub struct DumpArgs {
     #[clap(long, short, alias = "end_block")]
     pub to_block: Option<u128>,
 
+    /// A checkpoint file to resume dumping from. The blocks dumped so far and the storage they
+    /// wrote are checkpointed to the file after each chunk and when tracing a block fails, so an
+    /// interrupted dump can be resumed by passing the same file. A checkpoint of another target,
+    /// or of a dump from another block, is ignored.
+    #[clap(long)]
+    pub resume: Option<String>,
+
+    /// Whether to skip decompiling the target to name the dumped slots by its state variables
+    /// and decode their values by type. Mapping keys are still recovered, but the variables are
+    /// named by their slot numbers and left undecoded.
+    #[clap(long = "skip-layout")]
+    pub skip_layout: bool,
+
+    /// Whether to skip resolving function selectors while decompiling the target. The names of
+    /// state variables read by public getters, such as `balanceOf`, are recovered from the
+    /// getters' resolved signatures.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+
+    /// Whether to keep watching for new blocks after dumping, printing each change to the
+    /// target's storage to stdout as a line of JSON, with the block, slot, its values before and
+    /// after the block, the transactions which wrote it, and its decoded variables. New blocks
+    /// are subscribed to over websocket rpc urls, and polled for otherwise.
+    #[clap(long)]
+    pub watch: bool,
+
+    /// Whether to record the first and last transactions which wrote each dumped slot, with
+    /// their senders and the slot's values before and after each write. The provenance report is
+    /// written beside the dump, and included in its JSON output.
+    #[clap(long)]
+    pub provenance: bool,
+
     /// The name for the output file
     #[clap(long, short, default_value = "", hide_default_value = true)]
     pub name: String,
+
+    /// The format to write the dump in. Parquet and SQLite dumps can't be printed.
+    #[clap(long, value_enum, default_value = "csv")]
+    pub format: DumpFormat,
+
+    /// Receives a progress update as each block is traced. Only available when dumping
+    /// programmatically.
+    #[clap(skip)]
+    pub progress: Option<ProgressSender>,
 }
 
 impl DumpArgsBuilder {
@@ -51,9 +101,42 @@ impl DumpArgsBuilder {
             output: Some(String::new()),
             rpc_url: Some(String::new()),
             threads: Some(4),
+            chunk_size: Some(100),
             from_block: Some(0),
             to_block: Some(None),
+            resume: Some(None),
+            skip_layout: Some(false),
+            skip_resolving: Some(false),
+            watch: Some(false),
+            provenance: Some(false),
             name: Some(String::new()),
+            format: Some(DumpFormat::Csv),
+            progress: Some(None),
+        }
+    }
+}
+
+/// The formats the storage dump can be written in. Each has a row for each state variable stored
+/// in each slot, with the slot, its value, the label of the address it holds, and the variable's
+/// name, type and decoded value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpFormat {
+    /// A CSV file.
+    Csv,
+    /// An Apache Parquet file, which loads efficiently into DuckDB or pandas, even for millions
+    /// of slots.
+    Parquet,
+    /// A SQLite database, with the rows in its `storage` table.
+    Sqlite,
+}
+
+impl DumpFormat {
+    /// The extension of files written in the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DumpFormat::Csv => "csv",
+            DumpFormat::Parquet => "parquet",
+            DumpFormat::Sqlite => "sqlite",
         }
     }
 }
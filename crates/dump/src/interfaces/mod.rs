@@ -1,4 +1,6 @@
 mod args;
+mod token;
 
 // re-export the public interface
-pub use args::{DumpArgs, DumpArgsBuilder};
+pub use args::{DumpArgs, DumpArgsBuilder, DumpFormat};
+pub use token::{SnapshotTokenArgs, SnapshotTokenArgsBuilder, TokenStandard};
@@ -0,0 +1,90 @@
+use alloy::primitives::U256;
+use clap::{Parser, ValueEnum};
+use derive_builder::Builder;
+use heimdall_config::parse_url_arg;
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Snapshot the balances of an ERC-20, or the owners of an ERC-721's tokens, at a block",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall snapshot-token <TARGET> [OPTIONS]"
+)]
+/// Arguments for the token snapshot operation
+///
+/// This struct contains all the configuration parameters needed to replay a token's Transfer
+/// logs into a holder→balance (or tokenId→owner) table, and cross-check it against the token's
+/// storage.
+pub struct SnapshotTokenArgs {
+    /// The token to snapshot.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout
+    #[clap(long = "output", short, default_value = "output", hide_default_value = true)]
+    pub output: String,
+
+    /// The RPC URL to use for fetching data.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
+    #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// The block to snapshot the token at. Defaults to the latest block.
+    #[clap(long, short)]
+    pub block: Option<u128>,
+
+    /// The block to start scanning Transfer logs from. Defaults to the block the token was
+    /// created in.
+    #[clap(long, short)]
+    pub from_block: Option<u128>,
+
+    /// The token's standard. Detected from its Transfer logs by default, as ERC-721 Transfers
+    /// index the token id, while ERC-20 Transfers log the amount.
+    #[clap(long, value_enum)]
+    pub standard: Option<TokenStandard>,
+
+    /// The slot of the token's balance (or owner) mapping. Found by probing the token's storage
+    /// for the mapping which agrees with its Transfer logs by default.
+    #[clap(long)]
+    pub balance_slot: Option<U256>,
+
+    /// The number of blocks to request Transfer logs for at once.
+    #[clap(long, default_value = "10000")]
+    pub chunk_size: u128,
+
+    /// The number of log and storage requests to make at once.
+    #[clap(long, default_value = "4", hide_default_value = true)]
+    pub threads: usize,
+
+    /// The name for the output file
+    #[clap(long, short, default_value = "", hide_default_value = true)]
+    pub name: String,
+}
+
+impl SnapshotTokenArgsBuilder {
+    /// Creates a new SnapshotTokenArgsBuilder with default values
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            output: Some(String::new()),
+            rpc_url: Some(String::new()),
+            block: Some(None),
+            from_block: Some(None),
+            standard: Some(None),
+            balance_slot: Some(None),
+            chunk_size: Some(10000),
+            threads: Some(4),
+            name: Some(String::new()),
+        }
+    }
+}
+
+/// The token standards which can be snapshotted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TokenStandard {
+    /// A fungible token, snapshotted as each holder's balance.
+    Erc20,
+    /// A non-fungible token, snapshotted as each token's owner.
+    Erc721,
+}
@@ -8,6 +8,12 @@ mod core;
 mod interfaces;
 
 // re-export the public interface
-pub use core::dump;
+pub use core::{
+    dump, label_slots, snapshot_token, watch, SlotProvenance, SlotWrite, StorageChange,
+    StorageDump, StorageVariable, TokenHolder, TokenOwner, TokenSnapshot,
+};
 pub use error::Error;
-pub use interfaces::{DumpArgs, DumpArgsBuilder};
+pub use interfaces::{
+    DumpArgs, DumpArgsBuilder, DumpFormat, SnapshotTokenArgs, SnapshotTokenArgsBuilder,
+    TokenStandard,
+};
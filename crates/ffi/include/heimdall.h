@@ -0,0 +1,42 @@
+/*
+ * C bindings for heimdall's decompiler, decoder, disassembler and control flow graph generator.
+ *
+ * Each operation takes the raw bytecode or calldata, and returns a nul-terminated JSON string,
+ * which is `{"result": ...}` if the operation succeeded, or `{"error": "..."}` if it failed. The
+ * returned string is owned by the caller, and must be freed with `heimdall_free_string`.
+ */
+
+#ifndef HEIMDALL_H
+#define HEIMDALL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Decompiles the runtime bytecode, returning its Solidity source, ABI and storage layout. */
+char *heimdall_decompile(const uint8_t *bytecode, size_t len);
+
+/* Decodes the calldata, returning the decoded function and its arguments. */
+char *heimdall_decode(const uint8_t *calldata, size_t len);
+
+/* Disassembles the bytecode, returning its assembly instructions. */
+char *heimdall_disassemble(const uint8_t *bytecode, size_t len);
+
+/* Generates the control flow graph of the bytecode, returning its nodes and edges. */
+char *heimdall_cfg(const uint8_t *bytecode, size_t len);
+
+/* Enables or disables offline mode, in which heimdall doesn't make any network requests. */
+void heimdall_set_offline(bool offline);
+
+/* Frees a string returned by heimdall. */
+void heimdall_free_string(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* HEIMDALL_H */
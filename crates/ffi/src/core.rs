@@ -0,0 +1,105 @@
+use eyre::eyre;
+use heimdall_common::utils::strings::encode_hex;
+use heimdall_core::{
+    heimdall_cfg::{cfg, CfgArgsBuilder},
+    heimdall_decoder::{decode, DecodeArgsBuilder},
+    heimdall_decompiler::{decompile, DecompilerArgsBuilder},
+    heimdall_disassembler::{disassemble, DisassemblerArgsBuilder},
+};
+use lazy_static::lazy_static;
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+use crate::error::Error;
+
+lazy_static! {
+    /// The runtime the bindings block on, which is shared so that each call doesn't start one.
+    static ref RUNTIME: Runtime = Runtime::new().expect("failed to start the tokio runtime");
+}
+
+/// Decompiles the bytecode, returning its Solidity source, ABI and storage layout.
+pub fn decompile_json(bytecode: &[u8]) -> Result<Value, Error> {
+    let args = DecompilerArgsBuilder::new()
+        .target(encode_hex(bytecode))
+        .include_solidity(true)
+        .build()
+        .map_err(|e| eyre!("failed to build decompiler args: {}", e))?;
+    let result = RUNTIME.block_on(decompile(args))?;
+
+    Ok(json!({
+        "source": result.source,
+        "abi": result.abi,
+        "storage_layout": result.storage_layout.to_solc_json(),
+    }))
+}
+
+/// Decodes the calldata, returning the decoded function and its arguments.
+pub fn decode_json(calldata: &[u8]) -> Result<Value, Error> {
+    let args = DecodeArgsBuilder::new()
+        .target(encode_hex(calldata))
+        .raw(true)
+        .build()
+        .map_err(|e| eyre!("failed to build decode args: {}", e))?;
+    let result = RUNTIME.block_on(decode(args))?;
+
+    serde_json::from_str(&result.to_json()?)
+        .map_err(|e| Error::Eyre(eyre!("failed to parse decode result: {}", e)))
+}
+
+/// Disassembles the bytecode, returning its assembly instructions.
+pub fn disassemble_json(bytecode: &[u8]) -> Result<Value, Error> {
+    let args = DisassemblerArgsBuilder::new().target(encode_hex(bytecode)).build()?;
+    let assembly = RUNTIME.block_on(disassemble(args))?;
+
+    Ok(json!({ "assembly": assembly }))
+}
+
+/// Generates the control flow graph of the bytecode, returning its nodes and edges, as well as
+/// the graph in graphviz format.
+pub fn cfg_json(bytecode: &[u8]) -> Result<Value, Error> {
+    let args = CfgArgsBuilder::new()
+        .target(encode_hex(bytecode))
+        .build()
+        .map_err(|e| eyre!("failed to build cfg args: {}", e))?;
+    let result = RUNTIME.block_on(cfg(args))?;
+
+    let mut graph = result.to_json();
+    graph["dot"] = json!(result.as_dot(false));
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::{offline::set_offline, strings::decode_hex};
+
+    #[test]
+    fn test_bindings_json() {
+        set_offline(true);
+        let bytecode = decode_hex("0x60003560e01c80631111111114610020578063222222221461002d57600080fd5b61002b600435610042565b005b61003b600435600101610042565b6002600155005b806005141561005057600080fd5b806000555056").expect("invalid bytecode");
+
+        let decompiled = decompile_json(&bytecode).expect("failed to decompile");
+        assert!(decompiled["source"]
+            .as_str()
+            .is_some_and(|source| source.contains("store_b = 0x02;")));
+        assert_eq!(decompiled["abi"].as_array().map(Vec::len), Some(2));
+
+        let disassembled = disassemble_json(&bytecode).expect("failed to disassemble");
+        assert!(disassembled["assembly"]
+            .as_str()
+            .is_some_and(|asm| asm.starts_with("000000 PUSH1 00\n")));
+
+        let graph = cfg_json(&bytecode).expect("failed to generate cfg");
+        assert!(!graph["nodes"].as_array().expect("nodes aren't an array").is_empty());
+        assert!(graph["dot"].as_str().is_some_and(|dot| dot.starts_with("digraph G {")));
+
+        let decoded = decode_json(
+            &decode_hex(
+                "0x1111111100000000000000000000000000000000000000000000000000000000000000ff",
+            )
+            .expect("invalid calldata"),
+        )
+        .expect("failed to decode");
+        assert_eq!(decoded["name"], "Unresolved_11111111");
+    }
+}
@@ -0,0 +1,19 @@
+/// Error type for the FFI module
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Error when decompiling the bytecode
+    #[error("Decompile error: {0}")]
+    DecompileError(#[from] heimdall_core::heimdall_decompiler::Error),
+    /// Error when decoding the calldata
+    #[error("Decode error: {0}")]
+    DecodeError(#[from] heimdall_core::heimdall_decoder::Error),
+    /// Error when disassembling the bytecode
+    #[error("Disassembly error: {0}")]
+    DisassemblyError(#[from] heimdall_core::heimdall_disassembler::Error),
+    /// Error when generating the control flow graph of the bytecode
+    #[error("CFG error: {0}")]
+    CfgError(#[from] heimdall_core::heimdall_cfg::Error),
+    /// Generic internal error
+    #[error("Internal error: {0}")]
+    Eyre(#[from] eyre::Report),
+}
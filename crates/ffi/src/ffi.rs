@@ -0,0 +1,145 @@
+use std::{
+    ffi::{c_char, CString},
+    panic::{catch_unwind, UnwindSafe},
+};
+
+use heimdall_common::utils::offline::set_offline;
+use serde_json::{json, Value};
+
+use crate::{
+    core::{cfg_json, decode_json, decompile_json, disassemble_json},
+    error::Error,
+};
+
+/// Runs the operation, returning its result as a JSON string owned by the caller, which must be
+/// freed with [`heimdall_free_string`]. The string is `{"result": ...}` if the operation
+/// succeeded, or `{"error": "..."}` if it failed or panicked.
+fn to_json_string(operation: impl FnOnce() -> Result<Value, Error> + UnwindSafe) -> *mut c_char {
+    let output = match catch_unwind(operation) {
+        Ok(Ok(result)) => json!({ "result": result }),
+        Ok(Err(e)) => json!({ "error": e.to_string() }),
+        Err(_) => json!({ "error": "heimdall panicked" }),
+    };
+
+    // serialized JSON escapes any interior nul bytes, so this can't fail
+    CString::new(output.to_string()).expect("json contains a nul byte").into_raw()
+}
+
+/// Reads the `len` bytes at `data` as a slice. A null `data` is read as empty.
+///
+/// # Safety
+///
+/// `data` must be null, or valid for reads of `len` bytes for the lifetime `'a`.
+unsafe fn slice_from_raw<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    match data.is_null() {
+        true => &[],
+        false => std::slice::from_raw_parts(data, len),
+    }
+}
+
+/// Decompiles the `len` bytes of runtime bytecode at `bytecode`, returning its Solidity source,
+/// ABI and storage layout as a JSON string, which must be freed with [`heimdall_free_string`].
+///
+/// # Safety
+///
+/// `bytecode` must be null, or valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn heimdall_decompile(bytecode: *const u8, len: usize) -> *mut c_char {
+    let bytecode = slice_from_raw(bytecode, len);
+    to_json_string(|| decompile_json(bytecode))
+}
+
+/// Decodes the `len` bytes of calldata at `calldata`, returning the decoded function and its
+/// arguments as a JSON string, which must be freed with [`heimdall_free_string`].
+///
+/// # Safety
+///
+/// `calldata` must be null, or valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn heimdall_decode(calldata: *const u8, len: usize) -> *mut c_char {
+    let calldata = slice_from_raw(calldata, len);
+    to_json_string(|| decode_json(calldata))
+}
+
+/// Disassembles the `len` bytes of bytecode at `bytecode`, returning its assembly instructions as
+/// a JSON string, which must be freed with [`heimdall_free_string`].
+///
+/// # Safety
+///
+/// `bytecode` must be null, or valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn heimdall_disassemble(bytecode: *const u8, len: usize) -> *mut c_char {
+    let bytecode = slice_from_raw(bytecode, len);
+    to_json_string(|| disassemble_json(bytecode))
+}
+
+/// Generates the control flow graph of the `len` bytes of bytecode at `bytecode`, returning its
+/// nodes and edges as a JSON string, which must be freed with [`heimdall_free_string`].
+///
+/// # Safety
+///
+/// `bytecode` must be null, or valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn heimdall_cfg(bytecode: *const u8, len: usize) -> *mut c_char {
+    let bytecode = slice_from_raw(bytecode, len);
+    to_json_string(|| cfg_json(bytecode))
+}
+
+/// Enables or disables offline mode, in which heimdall doesn't make any network requests, e.g. to
+/// resolve selectors.
+#[no_mangle]
+pub extern "C" fn heimdall_set_offline(offline: bool) {
+    set_offline(offline);
+}
+
+/// Frees a string returned by heimdall.
+///
+/// # Safety
+///
+/// `string` must be null, or a string returned by heimdall which wasn't already freed.
+#[no_mangle]
+pub unsafe extern "C" fn heimdall_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    #[test]
+    fn test_heimdall_disassemble() {
+        let bytecode = [0x60, 0x80, 0x60, 0x40, 0x52];
+        unsafe {
+            let output = heimdall_disassemble(bytecode.as_ptr(), bytecode.len());
+            let json: Value = serde_json::from_str(
+                CStr::from_ptr(output).to_str().expect("output isn't valid utf-8"),
+            )
+            .expect("output isn't valid json");
+            heimdall_free_string(output);
+
+            assert_eq!(
+                json["result"]["assembly"],
+                "000000 PUSH1 80\n000002 PUSH1 40\n000004 MSTORE \n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_heimdall_decode_error() {
+        unsafe {
+            let output = heimdall_decode(std::ptr::null(), 0);
+            let json: Value = serde_json::from_str(
+                CStr::from_ptr(output).to_str().expect("output isn't valid utf-8"),
+            )
+            .expect("output isn't valid json");
+            heimdall_free_string(output);
+
+            assert!(json["error"].is_string());
+            assert!(json.get("result").is_none());
+        }
+    }
+}
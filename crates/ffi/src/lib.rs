@@ -0,0 +1,21 @@
+//! The FFI module exposes heimdall's decompiler, decoder, disassembler and control flow graph
+//! generator over a C ABI, as well as a Python module when built with the `python` feature, so
+//! that heimdall can be embedded without shelling out to the CLI.
+//!
+//! Each operation takes the raw bytecode or calldata, and returns its result as JSON.
+
+/// Error types for the FFI module
+pub mod error;
+
+mod core;
+mod ffi;
+#[cfg(feature = "python")]
+mod python;
+
+// re-export the public interface
+pub use core::{cfg_json, decode_json, decompile_json, disassemble_json};
+pub use error::Error;
+pub use ffi::{
+    heimdall_cfg, heimdall_decode, heimdall_decompile, heimdall_disassemble, heimdall_free_string,
+    heimdall_set_offline,
+};
@@ -0,0 +1,61 @@
+use heimdall_common::utils::offline;
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use serde_json::Value;
+
+use crate::{
+    core::{cfg_json, decode_json, decompile_json, disassemble_json},
+    error::Error,
+};
+
+/// Runs the operation without holding the GIL, returning its result as a JSON string, or raising
+/// a `RuntimeError` if it failed.
+fn to_json_string(
+    py: Python<'_>,
+    operation: impl FnOnce() -> Result<Value, Error> + Send,
+) -> PyResult<String> {
+    py.allow_threads(operation)
+        .map(|result| result.to_string())
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+/// Decompiles the runtime bytecode, returning its Solidity source, ABI and storage layout as JSON.
+#[pyfunction]
+fn decompile(py: Python<'_>, bytecode: &[u8]) -> PyResult<String> {
+    to_json_string(py, || decompile_json(bytecode))
+}
+
+/// Decodes the calldata, returning the decoded function and its arguments as JSON.
+#[pyfunction]
+fn decode(py: Python<'_>, calldata: &[u8]) -> PyResult<String> {
+    to_json_string(py, || decode_json(calldata))
+}
+
+/// Disassembles the bytecode, returning its assembly instructions as JSON.
+#[pyfunction]
+fn disassemble(py: Python<'_>, bytecode: &[u8]) -> PyResult<String> {
+    to_json_string(py, || disassemble_json(bytecode))
+}
+
+/// Generates the control flow graph of the bytecode, returning its nodes and edges as JSON.
+#[pyfunction(name = "cfg")]
+fn control_flow_graph(py: Python<'_>, bytecode: &[u8]) -> PyResult<String> {
+    to_json_string(py, || cfg_json(bytecode))
+}
+
+/// Enables or disables offline mode, in which heimdall doesn't make any network requests, e.g. to
+/// resolve selectors.
+#[pyfunction]
+fn set_offline(offline: bool) {
+    offline::set_offline(offline);
+}
+
+/// Heimdall's Python bindings.
+#[pymodule]
+fn heimdall(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(decompile, m)?)?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    m.add_function(wrap_pyfunction!(disassemble, m)?)?;
+    m.add_function(wrap_pyfunction!(control_flow_graph, m)?)?;
+    m.add_function(wrap_pyfunction!(set_offline, m)?)?;
+    Ok(())
+}
@@ -1,25 +1,45 @@
 use alloy::{
-    consensus::Transaction,
-    network::TransactionResponse,
-    primitives::TxHash,
-    rpc::types::{trace::parity::TransactionTrace, Log},
+    consensus::{transaction::SignerRecoverable, Transaction, TxEnvelope},
+    eips::eip2718::Decodable2718,
+    network::{TransactionBuilder, TransactionResponse},
+    primitives::{Address, Bytes, FixedBytes, TxHash, U256, U64},
+    rpc::types::{
+        trace::parity::{
+            Action, CallAction, CallType, CreateAction, CreationMethod, TraceResults,
+            TransactionTrace,
+        },
+        Log, TransactionRequest,
+    },
 };
 use eyre::eyre;
 use futures::future::try_join_all;
+use hashbrown::HashMap;
 use std::{collections::VecDeque, time::Instant};
 use tracing::{debug, info, trace, warn};
 
 use heimdall_common::{
     ether::{
-        rpc::{get_block_logs, get_trace, get_transaction},
-        signatures::cache_signatures_from_abi,
+        fork::default_fork,
+        humanize::{format_units, get_token, Token},
+        rpc::{
+            capabilities, get_block_logs, get_trace, get_transaction, get_vm_trace, trace_call,
+            TraceNamespace,
+        },
+        signatures::cache_signatures_from_abis,
+    },
+    utils::{
+        env::set_env, hex::ToLowerHex, io::logging::TraceFactory, metrics::record_phase,
+        strings::decode_hex,
     },
-    utils::{env::set_env, hex::ToLowerHex, io::logging::TraceFactory},
 };
 
 use crate::{
     error::Error,
-    interfaces::{Contracts, DecodedLog, DecodedTransactionTrace, InspectArgs},
+    interfaces::{
+        balance_deltas, label_state_changes, opcode_classes, state_changes, token_transfers,
+        AccountChange, Asset, AssetTransfer, BalanceDelta, Contracts, DecodedLog,
+        DecodedTransactionTrace, GasProfile, InspectArgs, ValueChange, ValueFlow,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -31,6 +51,20 @@ use crate::{
 pub struct InspectResult {
     /// The decoded transaction trace containing all the execution steps
     pub decoded_trace: DecodedTransactionTrace,
+    /// The ether transferred within the transaction, in the order it was transferred
+    pub value_flows: Vec<ValueFlow>,
+    /// The ERC-20, ERC-721 and ERC-1155 tokens transferred within the transaction, in the order
+    /// their transfer events were emitted
+    pub token_transfers: Vec<AssetTransfer>,
+    /// The net change in each account's balance of each asset, from the ether and token
+    /// transfers
+    pub balance_deltas: Vec<BalanceDelta>,
+    /// Every change the transaction made to the state, in order of address. Empty unless
+    /// `--state-diff` was passed.
+    pub state_changes: Vec<AccountChange>,
+    /// The gas used by each call frame and class of operations. `None` unless `--gas-profile` or
+    /// `--flamegraph` was passed.
+    pub gas_profile: Option<GasProfile>,
     _trace: TraceFactory,
 }
 
@@ -60,41 +94,18 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
     set_env("SKIP_RESOLVING", &args.skip_resolving.to_string());
 
     // parse and cache signatures from the ABI, if provided
-    if let Some(abi_path) = args.abi.as_ref() {
-        cache_signatures_from_abi(abi_path.into())
-            .map_err(|e| Error::Eyre(eyre!("caching signatures from ABI failed: {}", e)))?;
-    }
+    cache_signatures_from_abis(&args.abi)
+        .map_err(|e| Error::Eyre(eyre!("caching signatures from ABI failed: {}", e)))?;
 
-    // get calldata from RPC
-    let start_fetch_time = Instant::now();
-    let transaction = get_transaction(
-        args.target
-            .parse::<TxHash>()
-            .map_err(|_| eyre!("invalid transaction hash: '{}'", args.target))?,
-        &args.rpc_url,
-    )
-    .await
-    .map_err(|e| Error::Eyre(eyre!("fetching transaction failed: {}", e)))?;
-    debug!("fetching transaction took {:?}", start_fetch_time.elapsed());
-
-    let block_number = transaction.block_number.unwrap_or(0);
-
-    // get block traces
+    // fetch the mined transaction and its trace, or simulate it from its parameters
     let start_fetch_time = Instant::now();
-    let block_trace = get_trace(&args.target, &args.rpc_url)
-        .await
-        .map_err(|e| Error::Eyre(eyre!("fetching block trace failed: {}", e)))?;
-    debug!("fetching block trace took {:?}", start_fetch_time.elapsed());
-
-    // get transaction logs
-    let start_fetch_time = Instant::now();
-    let transaction_logs = get_block_logs(block_number, &args.rpc_url)
-        .await
-        .map_err(|e| Error::Eyre(eyre!("fetching block logs failed: {}", e)))?
-        .into_iter()
-        .filter(|log| log.transaction_hash == Some(transaction.tx_hash()))
-        .collect::<Vec<_>>();
-    debug!("fetching transaction logs took {:?}", start_fetch_time.elapsed());
+    let Execution { transaction, trace: block_trace, logs: transaction_logs, namespace } =
+        match simulated_transaction(&args)? {
+            Some(transaction) => simulate_transaction(transaction, &args).await?,
+            None => fetch_transaction(&args).await?,
+        };
+    debug!("fetching transaction trace took {:?}", start_fetch_time.elapsed());
+    record_phase("inspect.fetch_trace", start_fetch_time.elapsed());
 
     // convert Vec<Log> to Vec<DecodedLog>
     let decode_log_time = Instant::now();
@@ -103,6 +114,8 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
     let mut decoded_logs = try_join_all(handles).await?;
     decoded_logs
         .sort_by(|a, b| a.log_index.unwrap_or_default().cmp(&b.log_index.unwrap_or_default()));
+    let token_transfers = token_transfers(&decoded_logs);
+    let topics = decoded_logs.iter().flat_map(|log| log.topics.clone()).collect::<Vec<_>>();
     let mut decoded_logs = VecDeque::from(decoded_logs);
     info!("decoded {} logs successfully", decoded_logs.len());
     debug!("decoding logs took {:?}", decode_log_time.elapsed());
@@ -114,6 +127,16 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
     >>::try_from(block_trace.trace)
     .await?;
 
+    // explain why the transaction reverted, from the revert data it returned
+    if let Err(e) = decoded_trace.decode_revert(&block_trace.output).await {
+        warn!("failed to decode revert data: {}", e);
+    }
+
+    // annotate the decoded values in a human-friendly way, if requested
+    if args.humanize {
+        decoded_trace.humanize(&args.rpc_url).await;
+    }
+
     trace!("resolving address contract labels");
 
     // get contracts client
@@ -124,7 +147,7 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
         .map_err(|e| Error::Eyre(eyre!("fetching contracts failed: {}", e)))?;
 
     // extend with addresses from state diff
-    if let Some(state_diff) = block_trace.state_diff {
+    if let Some(state_diff) = &block_trace.state_diff {
         contracts
             .extend(state_diff.0.keys().cloned().collect())
             .await
@@ -133,6 +156,51 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
         warn!("no state diff found for transaction. skipping state diff label resolution");
     }
 
+    // list every change the transaction made to the state, naming the changed slots
+    let mut changes = Vec::new();
+    if args.state_diff {
+        match &block_trace.state_diff {
+            Some(state_diff) => {
+                changes = state_changes(state_diff);
+                let calldata = transaction.input.get(4..).unwrap_or_default();
+                let words = calldata.chunks_exact(32).map(FixedBytes::from_slice).chain(topics);
+                label_state_changes(&mut changes, words, &args.rpc_url, args.skip_resolving).await;
+            }
+            None => warn!("no state diff found for transaction. skipping state diff"),
+        }
+    }
+
+    // profile the gas used by each call frame and, with a full vm trace, each class of operations
+    let gas_profile = match args.gas_profile || args.flamegraph.is_some() {
+        true => {
+            let label = |address: &Address| {
+                contracts.get(*address).cloned().unwrap_or_else(|| address.to_lower_hex())
+            };
+            let mut profile =
+                GasProfile { frames: decoded_trace.frame_gas(&label), ..Default::default() };
+            // geth's call tracer only traces the operations which log or call, so the full vm
+            // trace is replayed, which is only possible for mined transactions
+            let vm_trace = match (namespace, transaction.hash) {
+                (Some(TraceNamespace::Parity), _) => block_trace.vm_trace.clone(),
+                (Some(TraceNamespace::Geth), Some(hash)) => {
+                    get_vm_trace(&hash.to_lower_hex(), &args.rpc_url)
+                        .await
+                        .map_err(|e| warn!("failed to fetch the vm trace to profile gas: {}", e))
+                        .ok()
+                }
+                _ => None,
+            };
+            match (vm_trace, profile.frames.first()) {
+                (Some(vm_trace), Some(root)) => {
+                    profile.classes = opcode_classes(&vm_trace, root.gas_used, &transaction.input);
+                }
+                _ => warn!("no vm trace found for transaction. skipping gas profile of operations"),
+            }
+            Some(profile)
+        }
+        false => None,
+    };
+
     trace!("joining {} decoded logs to trace", decoded_logs.len());
 
     if let Some(vm_trace) = block_trace.vm_trace {
@@ -140,6 +208,9 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
         let _ = decoded_trace.join_logs(&mut decoded_logs, &vm_trace, Vec::new()).await;
         // build state diffs within trace
         let _ = decoded_trace.build_state_diffs(vm_trace, Vec::new()).await;
+    } else if namespace.is_none() {
+        // without a trace, the logs can't be attributed to sub-calls
+        decoded_trace.logs.extend(std::mem::take(&mut decoded_logs));
     } else {
         warn!("no vm trace found for transaction. skipping joining logs");
     }
@@ -148,16 +219,399 @@ pub async fn inspect(args: InspectArgs) -> Result<InspectResult, Error> {
     let mut trace = TraceFactory::default();
     let inspect_call = trace.add_call(
         0,
-        transaction.inner.gas_limit().try_into().unwrap_or_default(),
+        transaction.gas_limit.try_into().unwrap_or_default(),
         "heimdall".to_string(),
         "inspect".to_string(),
-        vec![transaction.tx_hash().to_lower_hex()],
+        vec![transaction
+            .hash
+            .map(|hash| hash.to_lower_hex())
+            .unwrap_or_else(|| "simulation".to_string())],
         "()".to_string(),
     );
     decoded_trace.add_to_trace(&contracts, &mut trace, inspect_call);
 
+    // summarize the ether and tokens transferred within the transaction after its call tree
+    let value_flows = decoded_trace.value_flows();
+    let deltas = balance_deltas(&value_flows, &token_transfers);
+    let tokens = match args.humanize {
+        true => fetch_tokens(&deltas, &args.rpc_url).await,
+        false => HashMap::new(),
+    };
+    if !value_flows.is_empty() || !deltas.is_empty() || !changes.is_empty() || gas_profile.is_some()
+    {
+        trace.br(0);
+    }
+    add_value_flows(&value_flows, &contracts, &mut trace);
+    add_balance_deltas(&deltas, &tokens, &contracts, &mut trace);
+    add_state_changes(&changes, &contracts, &mut trace);
+    if let Some(gas_profile) = &gas_profile {
+        add_gas_profile(gas_profile, &mut trace);
+    }
+
     info!("decoded raw trace successfully");
     debug!("inspection took {:?}", start_time.elapsed());
+    record_phase("inspect", start_time.elapsed());
+
+    Ok(InspectResult {
+        decoded_trace,
+        value_flows,
+        token_transfers,
+        balance_deltas: deltas,
+        state_changes: changes,
+        gas_profile,
+        _trace: trace,
+    })
+}
+
+/// Adds a summary of the ether transferred within the transaction to the trace.
+fn add_value_flows(value_flows: &[ValueFlow], contracts: &Contracts, trace: &mut TraceFactory) {
+    if value_flows.is_empty() {
+        return;
+    }
+    let name = |address: &Address| {
+        contracts.get(*address).cloned().unwrap_or_else(|| address.to_lower_hex())
+    };
+
+    let flows = trace.add_message(0, 0, vec!["value flows:".to_string()]);
+    for flow in value_flows {
+        trace.add_message(
+            flows,
+            0,
+            vec![format!(
+                "{} → {}: {} ether",
+                name(&flow.from),
+                name(&flow.to),
+                format_units(flow.value, 18)
+            )],
+        );
+    }
+}
+
+/// The transaction being inspected, either mined or simulated.
+struct InspectedTransaction {
+    /// The transaction's hash, or `None` if it's simulated rather than mined
+    hash: Option<TxHash>,
+    from: Address,
+    /// The recipient, or `None` for contract creations
+    to: Option<Address>,
+    input: Bytes,
+    value: U256,
+    gas_limit: u64,
+}
+
+impl InspectedTransaction {
+    /// The request to simulate the transaction with. A zero gas limit is left for the node to
+    /// choose.
+    fn to_request(&self) -> TransactionRequest {
+        let request = TransactionRequest::default()
+            .with_from(self.from)
+            .with_input(self.input.clone())
+            .with_value(self.value);
+        let request = match self.to {
+            Some(to) => request.with_to(to),
+            None => request.into_create(),
+        };
+        match self.gas_limit {
+            0 => request,
+            gas_limit => request.with_gas_limit(gas_limit),
+        }
+    }
+}
+
+/// A transaction's trace, and the logs it emitted.
+struct Execution {
+    transaction: InspectedTransaction,
+    trace: TraceResults,
+    logs: Vec<Log>,
+    /// The namespace the trace was fetched with, or `None` if the rpc can't trace transactions
+    namespace: Option<TraceNamespace>,
+}
+
+/// Fetches the mined transaction with the target hash, along with its trace and logs. If the rpc
+/// can't trace it, only its top-level call is traced.
+async fn fetch_transaction(args: &InspectArgs) -> Result<Execution, Error> {
+    let hash = args
+        .target
+        .parse::<TxHash>()
+        .map_err(|_| eyre!("invalid transaction hash: '{}'", args.target))?;
+    let fetched = get_transaction(hash, &args.rpc_url)
+        .await
+        .map_err(|e| Error::Eyre(eyre!("fetching transaction failed: {}", e)))?;
+    let transaction = InspectedTransaction {
+        hash: Some(hash),
+        from: fetched.from(),
+        to: fetched.to(),
+        input: fetched.input().clone(),
+        value: fetched.value(),
+        gas_limit: fetched.gas_limit(),
+    };
+
+    // get block traces, falling back to the transaction itself if the rpc can't trace it
+    let capabilities = capabilities(&args.rpc_url)
+        .await
+        .map_err(|e| Error::Eyre(eyre!("probing rpc capabilities failed: {}", e)))?;
+    let namespace = capabilities.trace_namespace();
+    let trace = match namespace {
+        Some(_) => get_trace(&args.target, &args.rpc_url)
+            .await
+            .map_err(|e| Error::Eyre(eyre!("fetching block trace failed: {}", e)))?,
+        None => {
+            warn!("rpc supports neither `trace_*` nor `debug_trace*` methods, only the top-level call and its receipt logs will be decoded");
+            top_level_trace(&transaction)
+        }
+    };
 
-    Ok(InspectResult { decoded_trace, _trace: trace })
+    // get transaction logs
+    let start_fetch_time = Instant::now();
+    let logs = get_block_logs(fetched.block_number.unwrap_or(0), &args.rpc_url)
+        .await
+        .map_err(|e| Error::Eyre(eyre!("fetching block logs failed: {}", e)))?
+        .into_iter()
+        .filter(|log| log.transaction_hash == Some(hash))
+        .collect::<Vec<_>>();
+    debug!("fetching transaction logs took {:?}", start_fetch_time.elapsed());
+
+    Ok(Execution { transaction, trace, logs, namespace })
+}
+
+/// The transaction to simulate, if it's given by its parameters or as a raw signed transaction
+/// rather than by the hash of a mined one.
+fn simulated_transaction(args: &InspectArgs) -> Result<Option<InspectedTransaction>, Error> {
+    if !args.is_simulation() {
+        return Ok(None);
+    }
+    if args.to.is_some() || args.data.is_some() {
+        return Ok(Some(InspectedTransaction {
+            hash: None,
+            from: args.from.unwrap_or_default(),
+            to: args.to,
+            input: args.data.clone().unwrap_or_default(),
+            value: args.value.unwrap_or_default(),
+            gas_limit: 0,
+        }));
+    }
+    let raw = decode_hex(&args.target)
+        .map_err(|_| eyre!("invalid transaction hash or raw transaction: '{}'", args.target))?;
+    let mut buf = raw.as_slice();
+    let envelope = TxEnvelope::decode_2718(&mut buf)
+        .ok()
+        .filter(|_| buf.is_empty())
+        .ok_or_else(|| eyre!("invalid transaction hash or raw transaction: '{}'", args.target))?;
+    let signer = envelope
+        .recover_signer()
+        .map_err(|e| eyre!("recovering the raw transaction's signer failed: {}", e))?;
+    Ok(Some(InspectedTransaction {
+        hash: None,
+        from: signer,
+        to: envelope.to(),
+        input: envelope.input().clone(),
+        value: envelope.value(),
+        gas_limit: envelope.gas_limit(),
+    }))
+}
+
+/// Simulates the transaction on top of the state at `--block`, without sending it. The
+/// simulation runs on the fork set with `--fork` or `--fork-url`, if any, and otherwise on the
+/// rpc itself.
+async fn simulate_transaction(
+    transaction: InspectedTransaction,
+    args: &InspectArgs,
+) -> Result<Execution, Error> {
+    let fork = default_fork();
+    let rpc_url = fork.as_ref().map(|fork| fork.rpc_url()).unwrap_or(&args.rpc_url);
+    debug!("simulating transaction from {} on {}", transaction.from, rpc_url);
+
+    let (trace, logs) = trace_call(&transaction.to_request(), args.block, rpc_url)
+        .await
+        .map_err(|e| Error::Eyre(eyre!("simulating transaction failed: {}. the rpc must support `debug_traceCall` or `trace_call`, or the simulation can be run on a local fork with `--fork`", e)))?;
+    let namespace = capabilities(rpc_url).await.ok().and_then(|c| c.trace_namespace());
+    if logs.is_empty() && namespace == Some(TraceNamespace::Parity) {
+        warn!("rpc's `trace_call` doesn't return logs, so the simulated transaction's logs can't be decoded. simulate it on a local fork with `--fork` to decode them");
+    }
+
+    Ok(Execution { transaction, trace, logs, namespace })
+}
+
+/// Build a [`TraceResults`] containing only the top-level call or create of the given
+/// transaction, for rpcs which can't trace it.
+fn top_level_trace(transaction: &InspectedTransaction) -> TraceResults {
+    let action = match transaction.to {
+        Some(to) => Action::Call(CallAction {
+            from: transaction.from,
+            call_type: CallType::Call,
+            gas: transaction.gas_limit,
+            input: transaction.input.clone(),
+            to,
+            value: transaction.value,
+        }),
+        None => Action::Create(CreateAction {
+            from: transaction.from,
+            gas: transaction.gas_limit,
+            init: transaction.input.clone(),
+            value: transaction.value,
+            creation_method: CreationMethod::Create,
+        }),
+    };
+
+    TraceResults {
+        output: Default::default(),
+        state_diff: None,
+        trace: vec![TransactionTrace {
+            action,
+            error: None,
+            result: None,
+            subtraces: 0,
+            trace_address: Vec::new(),
+        }],
+        vm_trace: None,
+    }
+}
+
+/// Adds the net change in each account's balance of each asset to the trace, as a table with a
+/// row for each account. ERC-20 amounts are scaled by the `tokens`' decimals, where known.
+fn add_balance_deltas(
+    deltas: &[BalanceDelta],
+    tokens: &HashMap<Address, Token>,
+    contracts: &Contracts,
+    trace: &mut TraceFactory,
+) {
+    if deltas.is_empty() {
+        return;
+    }
+    let name = |address: &Address| {
+        contracts.get(*address).cloned().unwrap_or_else(|| address.to_lower_hex())
+    };
+
+    let table = trace.add_message(0, 0, vec!["balance deltas:".to_string()]);
+    let mut row = None;
+    for (i, delta) in deltas.iter().enumerate() {
+        // deltas are ordered by account, so each account's rows are adjacent
+        if i == 0 || deltas[i - 1].address != delta.address {
+            row = Some(trace.add_message(table, 0, vec![name(&delta.address)]));
+        }
+        let sign = if delta.delta.is_negative() { "-" } else { "+" };
+        let amount = delta.delta.unsigned_abs();
+        let formatted = match (&delta.asset, delta.asset.token().and_then(|t| tokens.get(&t))) {
+            (Asset::Ether, _) => format!("{} ether", format_units(amount, 18)),
+            (Asset::Erc20 { .. }, Some(token)) => token.format_amount(amount),
+            (Asset::Erc20 { token }, None) => format!("{amount} {}", name(token)),
+            (Asset::Erc721 { token, id } | Asset::Erc1155 { token, id }, _) => {
+                format!("{amount} {} #{id}", name(token))
+            }
+        };
+        trace.add_message(row.unwrap_or(table), 0, vec![format!("{sign}{formatted}")]);
+    }
+}
+
+/// Adds every change the transaction made to the state to the trace, with a row for each
+/// account listing how its balance, nonce, code and storage changed. Changed slots are followed
+/// by the state variables stored in them, where they could be named.
+fn add_state_changes(changes: &[AccountChange], contracts: &Contracts, trace: &mut TraceFactory) {
+    if changes.is_empty() {
+        return;
+    }
+    let name = |address: &Address| {
+        contracts.get(*address).cloned().unwrap_or_else(|| address.to_lower_hex())
+    };
+    fn show<T>(change: &ValueChange<T>, format: impl Fn(&T) -> String) -> String {
+        let side =
+            |value: &Option<T>| value.as_ref().map(&format).unwrap_or_else(|| "none".to_string());
+        format!("{} → {}", side(&change.from), side(&change.to))
+    }
+
+    let table = trace.add_message(0, 0, vec!["state diff:".to_string()]);
+    for account in changes {
+        let row = trace.add_message(table, 0, vec![name(&account.address)]);
+        if let Some(balance) = &account.balance {
+            let balance = show(balance, |value| format!("{} ether", format_units(*value, 18)));
+            trace.add_message(row, 0, vec![format!("balance: {balance}")]);
+        }
+        if let Some(nonce) = &account.nonce {
+            trace.add_message(row, 0, vec![format!("nonce: {}", show(nonce, U64::to_string))]);
+        }
+        if let Some(code) = &account.code {
+            let code = show(code, |code| format!("{} bytes", code.len()));
+            trace.add_message(row, 0, vec![format!("code: {code}")]);
+        }
+        for slot in &account.storage {
+            let change = ValueChange { from: slot.from, to: slot.to };
+            let slot_row = trace.add_message(
+                row,
+                0,
+                vec![format!(
+                    "{}: {}",
+                    slot.slot.to_lower_hex(),
+                    show(&change, |v| v.to_lower_hex())
+                )],
+            );
+            for variable in &slot.variables {
+                let typ = variable.typ.as_ref().map(|typ| format!(" ({typ})")).unwrap_or_default();
+                trace.add_message(
+                    slot_row,
+                    0,
+                    vec![format!("{}{typ} = {}", variable.name, variable.value)],
+                );
+            }
+        }
+    }
+}
+
+/// Adds the gas profile to the trace: the gas used by each call frame, nested as they were
+/// called, and the share of the gas attributed to each class of operations.
+fn add_gas_profile(profile: &GasProfile, trace: &mut TraceFactory) {
+    let total = profile.frames.first().map(|root| root.gas_used).unwrap_or_default().max(1);
+    let share = |gas: u64| gas as f64 * 100.0 / total as f64;
+
+    let table = trace.add_message(0, 0, vec!["gas profile:".to_string()]);
+    let mut parents = vec![table];
+    for frame in &profile.frames {
+        // frames are in execution order, so each frame's parent is the last one a level up
+        parents.truncate(frame.stack.len());
+        let parent = parents.last().copied().unwrap_or(table);
+        let row = trace.add_message(
+            parent,
+            0,
+            vec![format!(
+                "{}: {} gas ({:.2}%), {} self",
+                frame.stack.last().map(String::as_str).unwrap_or_default(),
+                frame.gas_used,
+                share(frame.gas_used),
+                frame.self_gas
+            )],
+        );
+        parents.push(row);
+    }
+
+    if !profile.classes.is_empty() {
+        let classes = trace.add_message(table, 0, vec!["by operation:".to_string()]);
+        for class in &profile.classes {
+            trace.add_message(
+                classes,
+                0,
+                vec![format!(
+                    "{}: {} gas ({:.2}%)",
+                    class.class.label(),
+                    class.gas,
+                    share(class.gas)
+                )],
+            );
+        }
+    }
+}
+
+/// Fetches the symbol and decimals of each ERC-20 token whose balances changed, to scale its
+/// amounts by. Tokens which can't be fetched, such as when offline, are left unscaled.
+async fn fetch_tokens(deltas: &[BalanceDelta], rpc_url: &str) -> HashMap<Address, Token> {
+    let mut tokens = HashMap::new();
+    for delta in deltas {
+        if let Asset::Erc20 { token } = delta.asset {
+            if tokens.contains_key(&token) {
+                continue;
+            }
+            if let Some(metadata) = get_token(token, rpc_url).await {
+                tokens.insert(token, metadata);
+            }
+        }
+    }
+    tokens
 }
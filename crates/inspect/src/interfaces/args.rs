@@ -1,3 +1,7 @@
+use alloy::{
+    eips::BlockId,
+    primitives::{Address, Bytes, TxHash, U256},
+};
 use clap::Parser;
 use derive_builder::Builder;
 use heimdall_config::parse_url_arg;
@@ -6,19 +10,44 @@ use heimdall_config::parse_url_arg;
 #[clap(
     about = "Detailed inspection of Ethereum transactions, including calldata & trace decoding, log visualization, and more.",
     after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
-    override_usage = "heimdall inspect <TARGET> [OPTIONS]"
+    override_usage = "heimdall inspect <TARGET|--to <ADDRESS>|--data <DATA>> [OPTIONS]"
 )]
 /// Arguments for the inspect operation
 ///
 /// This struct contains all the configuration parameters needed to inspect
 /// a transaction and decode its trace, logs, and state changes.
 pub struct InspectArgs {
-    /// The target transaction hash to inspect.
-    #[clap(required = true)]
+    /// The target to inspect, either a transaction hash, or a raw signed transaction which is
+    /// simulated without being sent.
+    #[clap(required_unless_present_any = ["to", "data"], default_value = "", hide_default_value = true)]
     pub target: String,
 
+    /// The sender of a transaction to simulate instead of inspecting a mined one. Defaults to
+    /// the zero address.
+    #[clap(long, value_name = "ADDRESS", conflicts_with = "target")]
+    pub from: Option<Address>,
+
+    /// The recipient of a transaction to simulate instead of inspecting a mined one. If omitted,
+    /// the simulated transaction creates a contract from its data.
+    #[clap(long, value_name = "ADDRESS", conflicts_with = "target")]
+    pub to: Option<Address>,
+
+    /// The calldata, or init code for contract creations, of a transaction to simulate.
+    #[clap(long, value_name = "DATA", conflicts_with = "target")]
+    pub data: Option<Bytes>,
+
+    /// The ether value, in wei, of a transaction to simulate.
+    #[clap(long, value_name = "WEI", conflicts_with = "target")]
+    pub value: Option<U256>,
+
+    /// The block to simulate the transaction on top of, either a block number, block hash, or
+    /// tag (e.g. 'latest'). Defaults to the latest block.
+    #[clap(long, short = 'b')]
+    pub block: Option<BlockId>,
+
     /// The RPC provider to use for fetching target calldata.
-    /// This can be an explicit URL or a reference to a MESC endpoint.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
     #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
     pub rpc_url: String,
 
@@ -34,7 +63,8 @@ pub struct InspectArgs {
     #[clap(long, short, default_value = "", hide_default_value = true)]
     pub name: String,
 
-    /// The output directory to write the output to, or 'print' to print to the console.
+    /// The output directory to write the output to, 'print' to print to the console, or 'json'
+    /// to print machine-readable JSON to stdout.
     #[clap(long = "output", short = 'o', default_value = "output", hide_default_value = true)]
     pub output: String,
 
@@ -42,9 +72,31 @@ pub struct InspectArgs {
     #[clap(long = "skip-resolving")]
     pub skip_resolving: bool,
 
-    /// Path to an optional ABI file to use for resolving errors, functions, and events.
-    #[clap(long, short, default_value = None, hide_default_value = true)]
-    pub abi: Option<String>,
+    /// Path to an ABI file to use for resolving errors, functions, and events, which may be given
+    /// more than once. Signatures in the ABIs take precedence over resolved ones, so only
+    /// selectors which aren't in any of them are resolved or guessed.
+    #[clap(long, short, value_name = "ABI")]
+    pub abi: Vec<String>,
+
+    /// Whether to annotate decoded values in a human-friendly way, such as token amounts scaled
+    /// by the called token's decimals, UNIX timestamps, durations and `type(uintN).max`.
+    #[clap(long)]
+    pub humanize: bool,
+
+    /// Whether to print every balance, nonce, code and storage change the transaction made, with
+    /// the changed slots named by recovering each contract's storage layout.
+    #[clap(long = "state-diff")]
+    pub state_diff: bool,
+
+    /// Whether to profile the gas used by each call frame and, if the rpc returns a full VM
+    /// trace, by each class of operations, such as storage access, calls and memory expansion.
+    #[clap(long = "gas-profile")]
+    pub gas_profile: bool,
+
+    /// Write the gas profile as a flamegraph to the given path: an SVG if the path ends with
+    /// '.svg', otherwise folded stacks for flamegraph tools. Implies `--gas-profile`.
+    #[clap(long, value_name = "PATH")]
+    pub flamegraph: Option<String>,
 }
 
 impl InspectArgsBuilder {
@@ -58,7 +110,24 @@ impl InspectArgsBuilder {
             name: Some(String::new()),
             output: Some(String::from("output")),
             skip_resolving: Some(false),
-            abi: Some(None),
+            abi: Some(Vec::new()),
+            humanize: Some(false),
+            state_diff: Some(false),
+            gas_profile: Some(false),
+            flamegraph: Some(None),
+            from: Some(None),
+            to: Some(None),
+            data: Some(None),
+            value: Some(None),
+            block: Some(None),
         }
     }
 }
+
+impl InspectArgs {
+    /// Whether the transaction is simulated, from its parameters or as a raw signed transaction,
+    /// rather than fetched from the chain by its hash.
+    pub fn is_simulation(&self) -> bool {
+        self.to.is_some() || self.data.is_some() || self.target.parse::<TxHash>().is_err()
+    }
+}
@@ -4,7 +4,9 @@ use alloy::primitives::Address;
 use futures::future::try_join_all;
 
 use crate::{error::Error, InspectArgs};
-use heimdall_common::{resources::transpose::get_label, utils::hex::ToLowerHex};
+use heimdall_common::{
+    ether::labels::lookup_label, resources::transpose::get_label, utils::hex::ToLowerHex,
+};
 
 #[derive(Debug, Clone)]
 pub struct Contracts {
@@ -35,6 +37,12 @@ impl Contracts {
             return Ok(());
         }
 
+        // prefer local labels, which don't require a request
+        if let Some(label) = lookup_label(&address) {
+            self.contracts.insert(address, label);
+            return Ok(());
+        }
+
         if !self.transpose_api_key.is_empty() {
             self.contracts.insert(
                 address,
@@ -57,6 +65,20 @@ impl Contracts {
             return Ok(());
         }
 
+        // prefer local labels, which don't require a request
+        let mut unlabeled = HashSet::new();
+        for address in addresses {
+            match lookup_label(&address) {
+                Some(label) => {
+                    self.contracts.insert(address, label);
+                }
+                None => {
+                    unlabeled.insert(address);
+                }
+            }
+        }
+        let addresses = unlabeled;
+
         // for each address, get the label
         if !self.transpose_api_key.is_empty() {
             let transpose_api_key = self.transpose_api_key.clone();
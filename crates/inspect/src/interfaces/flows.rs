@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+use alloy::{
+    primitives::{Address, I256, U256},
+    rpc::types::trace::parity::CallType,
+};
+use serde::{Deserialize, Serialize};
+
+use super::traces::{DecodedAction, DecodedRes, DecodedTransactionTrace};
+
+/// A transfer of ether between two accounts within a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueFlow {
+    /// The position of the frame which transferred the ether in the call tree
+    #[serde(rename = "traceAddress")]
+    pub trace_address: Vec<usize>,
+    /// The sender
+    pub from: Address,
+    /// The recipient
+    pub to: Address,
+    /// The amount transferred, in wei
+    pub value: U256,
+}
+
+impl DecodedTransactionTrace {
+    /// The ether transferred by the call tree's frames, in the order they were executed. Frames
+    /// which reverted, and those within them, are skipped, as their transfers were undone.
+    /// Delegate and static calls can't transfer ether, so they're skipped too.
+    pub fn value_flows(&self) -> Vec<ValueFlow> {
+        let mut flows = Vec::new();
+        self.collect_value_flows(&mut flows);
+        flows
+    }
+
+    fn collect_value_flows(&self, flows: &mut Vec<ValueFlow>) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let flow = match &self.action {
+            DecodedAction::Call(call) if call.call_type == CallType::Call => {
+                Some((call.from, call.to, call.value))
+            }
+            DecodedAction::Create(create) => match &self.result {
+                Some(DecodedRes::Create(result)) => {
+                    Some((create.from, result.address, create.value))
+                }
+                _ => None,
+            },
+            DecodedAction::SelfDestruct(suicide) => {
+                Some((suicide.address, suicide.refund_address, suicide.balance))
+            }
+            _ => None,
+        };
+        if let Some((from, to, value)) =
+            flow.filter(|(from, to, value)| !value.is_zero() && from != to)
+        {
+            flows.push(ValueFlow { trace_address: self.trace_address.clone(), from, to, value });
+        }
+
+        for subtrace in &self.subtraces {
+            subtrace.collect_value_flows(flows);
+        }
+    }
+}
+
+/// The net change in each account's ether balance from the given transfers, excluding gas
+/// fees. Accounts whose transfers cancel out are omitted.
+pub fn balance_changes(flows: &[ValueFlow]) -> Vec<(Address, I256)> {
+    let mut changes: BTreeMap<Address, I256> = BTreeMap::new();
+    for flow in flows {
+        let value = I256::try_from(flow.value).unwrap_or(I256::MAX);
+        let from = changes.entry(flow.from).or_default();
+        *from = from.saturating_sub(value);
+        let to = changes.entry(flow.to).or_default();
+        *to = to.saturating_add(value);
+    }
+    changes.into_iter().filter(|(_, change)| !change.is_zero()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::types::trace::parity::SelfdestructAction;
+
+    use super::*;
+    use crate::interfaces::traces::DecodedCall;
+
+    fn frame(action: DecodedAction, error: Option<&str>) -> DecodedTransactionTrace {
+        DecodedTransactionTrace {
+            trace_address: Vec::new(),
+            action,
+            result: None,
+            error: error.map(str::to_string),
+            revert_reason: None,
+            subtraces: Vec::new(),
+            logs: Vec::new(),
+            diff: Vec::new(),
+        }
+    }
+
+    fn call(from: Address, to: Address, value: u64, call_type: CallType) -> DecodedAction {
+        let mut call = DecodedCall::default();
+        (call.from, call.to, call.value, call.call_type) = (from, to, U256::from(value), call_type);
+        DecodedAction::Call(call)
+    }
+
+    #[test]
+    fn test_value_flows() {
+        let (alice, router, pool) =
+            (Address::repeat_byte(0x01), Address::repeat_byte(0x02), Address::repeat_byte(0x03));
+
+        // alice sends 10 wei to the router, which forwards 7 to the pool, and delegates with
+        // its value, and the pool's refund of 2 reverts
+        let mut refund = frame(call(pool, alice, 2, CallType::Call), Some("Reverted"));
+        refund.trace_address = vec![0, 0];
+        let mut forward = frame(call(router, pool, 7, CallType::Call), None);
+        forward.trace_address = vec![0];
+        forward.subtraces.push(refund);
+        let mut delegate = frame(call(router, router, 10, CallType::DelegateCall), None);
+        delegate.trace_address = vec![1];
+        let mut destruct = frame(
+            DecodedAction::SelfDestruct(SelfdestructAction {
+                address: router,
+                refund_address: alice,
+                balance: U256::from(3),
+            }),
+            None,
+        );
+        destruct.trace_address = vec![2];
+        let mut root = frame(call(alice, router, 10, CallType::Call), None);
+        root.subtraces = vec![forward, delegate, destruct];
+
+        let flows = root.value_flows();
+        assert_eq!(
+            flows
+                .iter()
+                .map(|flow| (flow.from, flow.to, flow.value.to::<u64>()))
+                .collect::<Vec<_>>(),
+            vec![(alice, router, 10), (router, pool, 7), (router, alice, 3)]
+        );
+        assert_eq!(flows[1].trace_address, vec![0]);
+
+        let change = |value: i64| I256::try_from(value).expect("invalid change");
+        assert_eq!(balance_changes(&flows), vec![(alice, change(-7)), (pool, change(7))]);
+    }
+}
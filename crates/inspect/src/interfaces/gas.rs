@@ -0,0 +1,433 @@
+use alloy::{primitives::Address, rpc::types::trace::parity::VmTrace};
+use hashbrown::HashMap;
+use heimdall_vm::core::opcodes::{opcode_name, OpCodeInfo};
+use serde::{Deserialize, Serialize};
+
+use super::traces::{DecodedAction, DecodedRes, DecodedTransactionTrace};
+
+/// The width of a rendered flamegraph, in pixels.
+const FLAMEGRAPH_WIDTH: f64 = 1200.0;
+/// The height of each of a rendered flamegraph's frames, in pixels.
+const FLAMEGRAPH_ROW: f64 = 16.0;
+/// The margin around a rendered flamegraph, in pixels.
+const FLAMEGRAPH_MARGIN: f64 = 10.0;
+/// The height of a rendered flamegraph's title, and the margins around it, in pixels.
+const FLAMEGRAPH_HEADER: f64 = 2.0 * FLAMEGRAPH_MARGIN + 24.0;
+/// The width of the frames of a rendered flamegraph, within its margins, in pixels.
+const FLAMEGRAPH_PLOT_WIDTH: f64 = FLAMEGRAPH_WIDTH - 2.0 * FLAMEGRAPH_MARGIN;
+
+/// The gas used by a frame of a transaction's call tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameGas {
+    /// The position of the frame in the call tree
+    #[serde(rename = "traceAddress")]
+    pub trace_address: Vec<usize>,
+    /// The names of the frames the frame was called within, from the root, followed by its own,
+    /// e.g. `WETH9::deposit`
+    pub stack: Vec<String>,
+    /// The gas used by the frame, including that used by the frames it called
+    #[serde(rename = "gasUsed")]
+    pub gas_used: u64,
+    /// The gas used by the frame itself, excluding that used by the frames it called
+    #[serde(rename = "selfGas")]
+    pub self_gas: u64,
+}
+
+/// A class of operations which a transaction's gas is attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpcodeClass {
+    /// Storage reads and writes: `SLOAD`, `SSTORE`, `TLOAD` and `TSTORE`
+    Storage,
+    /// Calls, creations and self-destructs, excluding the gas used by the frames they start
+    Calls,
+    /// Memory expansion by `MLOAD`, `MSTORE`, `MSTORE8`, `RETURN` and `REVERT`. Memory expanded
+    /// by copies, hashing and logs is attributed to those operations
+    Memory,
+    /// `LOG0` to `LOG4`
+    Logs,
+    /// The intrinsic cost of the transaction's calldata
+    Calldata,
+    /// Every other operation
+    Computation,
+}
+
+impl OpcodeClass {
+    /// A short description of the class.
+    pub fn label(&self) -> &'static str {
+        match self {
+            OpcodeClass::Storage => "storage (SLOAD/SSTORE)",
+            OpcodeClass::Calls => "calls & creations",
+            OpcodeClass::Memory => "memory expansion",
+            OpcodeClass::Logs => "logs",
+            OpcodeClass::Calldata => "calldata",
+            OpcodeClass::Computation => "computation",
+        }
+    }
+}
+
+/// The gas attributed to a class of operations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassGas {
+    /// The class of operations
+    pub class: OpcodeClass,
+    /// The gas attributed to the class
+    pub gas: u64,
+}
+
+/// The gas a transaction used, attributed to each frame of its call tree and, with a full VM
+/// trace, to classes of operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfile {
+    /// The gas used by each frame of the call tree, in the order they were executed
+    pub frames: Vec<FrameGas>,
+    /// The gas attributed to each class of operations, or empty if the RPC returned no full VM
+    /// trace
+    pub classes: Vec<ClassGas>,
+}
+
+impl DecodedTransactionTrace {
+    /// The gas used by each frame of the call tree, in the order they were executed, naming the
+    /// called contracts with `label`. Frames which failed without a result are charged all the
+    /// gas they were given.
+    pub(crate) fn frame_gas(&self, label: &impl Fn(&Address) -> String) -> Vec<FrameGas> {
+        let mut frames = Vec::new();
+        self.collect_frame_gas(label, &[], &mut frames);
+        frames
+    }
+
+    fn collect_frame_gas(
+        &self,
+        label: &impl Fn(&Address) -> String,
+        parents: &[String],
+        frames: &mut Vec<FrameGas>,
+    ) {
+        let name = match &self.action {
+            DecodedAction::Call(call) => format!(
+                "{}::{}",
+                label(&call.to),
+                call.resolved_function.as_ref().map(|f| f.name.as_str()).unwrap_or("fallback")
+            ),
+            DecodedAction::Create(_) => match &self.result {
+                Some(DecodedRes::Create(result)) => format!("new {}", label(&result.address)),
+                _ => "new NewContract".to_string(),
+            },
+            DecodedAction::SelfDestruct(suicide) => {
+                format!("{}::selfdestruct", label(&suicide.address))
+            }
+            DecodedAction::Reward(_) => return,
+        };
+        let mut stack = parents.to_vec();
+        stack.push(name.replace(';', ","));
+
+        let index = frames.len();
+        frames.push(FrameGas {
+            trace_address: self.trace_address.clone(),
+            stack: stack.clone(),
+            gas_used: self.gas_used(),
+            self_gas: 0,
+        });
+        for subtrace in &self.subtraces {
+            subtrace.collect_frame_gas(label, &stack, frames);
+        }
+
+        let children = self.subtraces.iter().map(|subtrace| subtrace.gas_used()).sum::<u64>();
+        frames[index].self_gas = frames[index].gas_used.saturating_sub(children);
+    }
+
+    /// The gas used by the frame, including that used by the frames it called.
+    fn gas_used(&self) -> u64 {
+        match (&self.result, &self.action) {
+            (Some(DecodedRes::Call(result)), _) => result.gas_used.to(),
+            (Some(DecodedRes::Create(result)), _) => result.gas_used,
+            (_, DecodedAction::Call(call)) => call.gas.to(),
+            (_, DecodedAction::Create(create)) => create.gas,
+            _ => 0,
+        }
+    }
+}
+
+/// Attributes the gas the transaction used to classes of operations, from its full VM trace.
+/// `execution_gas` is the gas used by the root frame, and `calldata` the transaction's input.
+///
+/// The gas charged for a call includes the gas it forwards, so the gas of calls and creations
+/// is what remains of `execution_gas` once every other operation is accounted for.
+pub(crate) fn opcode_classes(
+    vm_trace: &VmTrace,
+    execution_gas: u64,
+    calldata: &[u8],
+) -> Vec<ClassGas> {
+    let opcodes = (0..=u8::MAX).map(|opcode| (opcode_name(opcode), opcode)).collect();
+    let mut gas = HashMap::new();
+    classify_frame(vm_trace, &opcodes, &mut gas);
+
+    let executed = gas.values().sum::<u64>();
+    gas.insert(OpcodeClass::Calls, execution_gas.saturating_sub(executed));
+    gas.insert(
+        OpcodeClass::Calldata,
+        calldata.iter().map(|byte| if *byte == 0 { 4 } else { 16 }).sum(),
+    );
+
+    [
+        OpcodeClass::Storage,
+        OpcodeClass::Calls,
+        OpcodeClass::Memory,
+        OpcodeClass::Logs,
+        OpcodeClass::Calldata,
+        OpcodeClass::Computation,
+    ]
+    .into_iter()
+    .map(|class| ClassGas { class, gas: gas.get(&class).copied().unwrap_or_default() })
+    .collect()
+}
+
+/// Adds the gas of each operation of the frame and its sub-calls to its class, except calls'.
+fn classify_frame(
+    trace: &VmTrace,
+    opcodes: &HashMap<&str, u8>,
+    gas: &mut HashMap<OpcodeClass, u64>,
+) {
+    for op in &trace.ops {
+        // frames whose code isn't known, such as creations traced by geth, are named by the op
+        let opcode = trace
+            .code
+            .get(op.pc)
+            .copied()
+            .or_else(|| op.op.as_deref().and_then(|name| opcodes.get(name).copied()));
+        if let Some(opcode) = opcode {
+            let min_gas = OpCodeInfo::from(opcode).min_gas() as u64;
+            match opcode_name(opcode) {
+                "SLOAD" | "SSTORE" | "TLOAD" | "TSTORE" => {
+                    *gas.entry(OpcodeClass::Storage).or_default() += op.cost;
+                }
+                "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL" | "CREATE" | "CREATE2" |
+                "SELFDESTRUCT" => {}
+                "MLOAD" | "MSTORE" | "MSTORE8" | "RETURN" | "REVERT" => {
+                    *gas.entry(OpcodeClass::Memory).or_default() += op.cost.saturating_sub(min_gas);
+                    *gas.entry(OpcodeClass::Computation).or_default() += op.cost.min(min_gas);
+                }
+                name if name.starts_with("LOG") => {
+                    *gas.entry(OpcodeClass::Logs).or_default() += op.cost;
+                }
+                _ => *gas.entry(OpcodeClass::Computation).or_default() += op.cost,
+            }
+        }
+        if let Some(sub) = &op.sub {
+            classify_frame(sub, opcodes, gas);
+        }
+    }
+}
+
+impl GasProfile {
+    /// The call frames' gas as folded stacks, the input format of flamegraph tools such as
+    /// `flamegraph.pl` and `inferno`: a line for each frame, with the frames it was called within
+    /// and its own gas, e.g. `Router::swap;Pair::swap 41220`.
+    pub fn folded(&self) -> String {
+        self.frames
+            .iter()
+            .filter(|frame| frame.self_gas > 0)
+            .map(|frame| format!("{} {}", frame.stack.join(";"), frame.self_gas))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// The call frames' gas as an SVG flamegraph, in which each frame is as wide as the gas it
+    /// used, and is stacked on the frame which called it.
+    pub fn flamegraph(&self, title: &str) -> String {
+        let total = self
+            .frames
+            .iter()
+            .filter(|frame| frame.stack.len() == 1)
+            .map(|frame| frame.gas_used)
+            .sum::<u64>()
+            .max(1);
+        let depth = self.frames.iter().map(|frame| frame.stack.len()).max().unwrap_or(1);
+        let height = FLAMEGRAPH_ROW.mul_add(depth as f64, FLAMEGRAPH_HEADER);
+        let scale = FLAMEGRAPH_PLOT_WIDTH / total as f64;
+
+        let mut svg = vec![
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="{FLAMEGRAPH_WIDTH}" height="{height}" font-family="monospace" font-size="11">"#
+            ),
+            r##"<rect width="100%" height="100%" fill="#f8f8f8"/>"##.to_string(),
+            format!(
+                r#"<text x="{}" y="{}" text-anchor="middle" font-size="14">{}</text>"#,
+                FLAMEGRAPH_WIDTH / 2.0,
+                FLAMEGRAPH_MARGIN + 12.0,
+                escape(title)
+            ),
+        ];
+
+        // frames are in execution order, so each frame starts where its previous sibling ended,
+        // and its first child starts where it does
+        let mut cursors = vec![0u64];
+        for frame in &self.frames {
+            let level = frame.stack.len() - 1;
+            cursors.truncate(level + 1);
+            let start = cursors.get(level).copied().unwrap_or_default();
+            cursors.resize(level + 1, start);
+            cursors[level] = start + frame.gas_used;
+            cursors.push(start);
+
+            let name = frame.stack.last().map(String::as_str).unwrap_or_default();
+            let (x, width) =
+                ((start as f64).mul_add(scale, FLAMEGRAPH_MARGIN), frame.gas_used as f64 * scale);
+            let y = FLAMEGRAPH_ROW.mul_add(-((level + 1) as f64), height - FLAMEGRAPH_MARGIN);
+            let share = frame.gas_used as f64 * 100.0 / total as f64;
+            let chars = (width / 7.0) as usize;
+            let text = match name.chars().count() {
+                _ if chars < 3 => String::new(),
+                len if len > chars => {
+                    format!("{}..", name.chars().take(chars - 2).collect::<String>())
+                }
+                _ => name.to_string(),
+            };
+            svg.push(format!(
+                r#"<g><title>{} ({} gas, {share:.2}%)</title><rect x="{x:.2}" y="{y:.2}" width="{width:.2}" height="{}" fill="{}" rx="2"/><text x="{:.2}" y="{:.2}">{}</text></g>"#,
+                escape(name),
+                frame.gas_used,
+                FLAMEGRAPH_ROW - 1.0,
+                color(name),
+                x + 3.0,
+                y + FLAMEGRAPH_ROW - 4.0,
+                escape(&text)
+            ));
+        }
+
+        svg.push("</svg>".to_string());
+        svg.join("\n")
+    }
+}
+
+/// A warm color for a flamegraph frame, which is the same for frames with the same name.
+fn color(name: &str) -> String {
+    // fnv-1a, so colors are stable across runs
+    let hash = name
+        .bytes()
+        .fold(0x811c9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193));
+    format!("#{:02x}{:02x}{:02x}", 205 + hash % 50, 80 + (hash >> 8) % 150, 30 + (hash >> 16) % 40)
+}
+
+/// Escapes text for use in SVG markup.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Bytes, U256, U64},
+        rpc::types::trace::parity::{CallType, CreateAction, CreateOutput, VmInstruction},
+    };
+
+    use super::*;
+    use crate::interfaces::traces::{DecodedCall, DecodedCallResult};
+
+    fn call(
+        to: Address,
+        gas_used: u64,
+        subtraces: Vec<DecodedTransactionTrace>,
+    ) -> DecodedTransactionTrace {
+        let mut call = DecodedCall::default();
+        (call.to, call.call_type) = (to, CallType::Call);
+        let mut result = DecodedCallResult::default();
+        result.gas_used = U64::from(gas_used);
+        DecodedTransactionTrace {
+            trace_address: Vec::new(),
+            action: DecodedAction::Call(call),
+            result: Some(DecodedRes::Call(result)),
+            error: None,
+            revert_reason: None,
+            subtraces,
+            logs: Vec::new(),
+            diff: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_frame_gas() {
+        let (router, pair, token, created) = (
+            Address::repeat_byte(0x01),
+            Address::repeat_byte(0x02),
+            Address::repeat_byte(0x03),
+            Address::repeat_byte(0x04),
+        );
+        let mut create = call(created, 0, Vec::new());
+        create.action = DecodedAction::Create(CreateAction {
+            from: router,
+            value: U256::ZERO,
+            gas: 50,
+            init: Bytes::new(),
+            creation_method: Default::default(),
+        });
+        create.result = Some(DecodedRes::Create(CreateOutput {
+            address: created,
+            code: Bytes::new(),
+            gas_used: 20,
+        }));
+        let root =
+            call(router, 100, vec![call(pair, 30, vec![call(token, 10, Vec::new())]), create]);
+
+        let label = |address: &Address| format!("c{}", address[19]);
+        let profile = GasProfile { frames: root.frame_gas(&label), classes: Vec::new() };
+        assert_eq!(
+            profile.frames.iter().map(|frame| (frame.gas_used, frame.self_gas)).collect::<Vec<_>>(),
+            [(100, 50), (30, 20), (10, 10), (20, 20)]
+        );
+        assert_eq!(
+            profile.folded(),
+            "c1::fallback 50\nc1::fallback;c2::fallback 20\nc1::fallback;c2::fallback;c3::fallback \
+             10\nc1::fallback;new c4 20"
+        );
+
+        let svg = profile.flamegraph("<tx>");
+        assert!(svg.contains("&lt;tx&gt;"));
+        assert_eq!(svg.matches("<g>").count(), 4);
+        // the creation starts where its sibling ended
+        assert!(svg.contains(r#"<rect x="364.00""#));
+    }
+
+    #[test]
+    fn test_opcode_classes() {
+        let op = |pc: usize, cost: u64, name: Option<&str>, sub: Option<VmTrace>| VmInstruction {
+            pc,
+            cost,
+            ex: None,
+            sub,
+            op: name.map(str::to_string),
+            idx: None,
+        };
+        // SLOAD, MSTORE expanding memory, LOG0, CALL into code which adds, ADD
+        let trace = VmTrace {
+            code: Bytes::from_static(&[0x54, 0x52, 0xa0, 0xf1, 0x01]),
+            ops: vec![
+                op(0, 2100, None, None),
+                op(1, 9, None, None),
+                op(2, 375, None, None),
+                op(
+                    3,
+                    50000,
+                    None,
+                    Some(VmTrace { code: Bytes::new(), ops: vec![op(0, 3, Some("ADD"), None)] }),
+                ),
+                op(4, 3, None, None),
+            ],
+        };
+
+        let classes = opcode_classes(&trace, 3000, &[0x00, 0x01])
+            .into_iter()
+            .map(|class| (class.class, class.gas))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            classes,
+            [
+                (OpcodeClass::Storage, 2100),
+                (OpcodeClass::Calls, 510),
+                (OpcodeClass::Memory, 6),
+                (OpcodeClass::Logs, 375),
+                (OpcodeClass::Calldata, 20),
+                (OpcodeClass::Computation, 9),
+            ]
+        );
+    }
+}
@@ -1,10 +1,20 @@
 mod args;
 mod contracts;
+mod flows;
+mod gas;
 mod logs;
+mod state;
 mod traces;
+mod transfers;
 
 // re-export the public interface
 pub use args::{InspectArgs, InspectArgsBuilder};
 pub(crate) use contracts::*;
+pub use flows::{balance_changes, ValueFlow};
+pub(crate) use gas::opcode_classes;
+pub use gas::{ClassGas, FrameGas, GasProfile, OpcodeClass};
 pub(crate) use logs::*;
+pub(crate) use state::label_state_changes;
+pub use state::{state_changes, AccountChange, SlotChange, ValueChange};
 pub(crate) use traces::*;
+pub use transfers::{balance_deltas, token_transfers, Asset, AssetTransfer, BalanceDelta};
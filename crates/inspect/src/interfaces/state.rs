@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::{
+    primitives::{Address, Bytes, FixedBytes, U256, U64},
+    rpc::types::trace::parity::{Delta, StateDiff},
+};
+use heimdall_common::utils::hex::ToLowerHex;
+use heimdall_dump::{label_slots, StorageVariable};
+use tracing::debug;
+
+/// How a value of an account changed within a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueChange<T> {
+    /// The value before the transaction, or `None` if the account didn't exist
+    pub from: Option<T>,
+    /// The value after the transaction, or `None` if the account was destroyed
+    pub to: Option<T>,
+}
+
+impl<T: Clone> ValueChange<T> {
+    /// The change described by a state diff's delta, or `None` if the value didn't change.
+    fn from_delta(delta: &Delta<T>) -> Option<Self> {
+        match delta {
+            Delta::Added(v) => Some(Self { from: None, to: Some(v.clone()) }),
+            Delta::Changed(v) => Some(Self { from: Some(v.from.clone()), to: Some(v.to.clone()) }),
+            Delta::Removed(v) => Some(Self { from: Some(v.clone()), to: None }),
+            Delta::Unchanged => None,
+        }
+    }
+}
+
+/// A change to one of an account's storage slots within a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotChange {
+    /// The slot which was written
+    pub slot: FixedBytes<32>,
+    /// The slot's value before the transaction, or `None` if it was empty
+    pub from: Option<FixedBytes<32>>,
+    /// The slot's value after the transaction, or `None` if it was cleared
+    pub to: Option<FixedBytes<32>>,
+    /// The state variables stored in the slot, decoded by their recovered types from its value
+    /// after the transaction (or before, if it was cleared). Empty if the contract's storage
+    /// layout couldn't be recovered.
+    pub variables: Vec<StorageVariable>,
+}
+
+/// The changes a transaction made to an account's balance, nonce, code and storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChange {
+    /// The account
+    pub address: Address,
+    /// How the account's balance changed, in wei
+    pub balance: Option<ValueChange<U256>>,
+    /// How the account's nonce changed
+    pub nonce: Option<ValueChange<U64>>,
+    /// How the account's code changed
+    pub code: Option<ValueChange<Bytes>>,
+    /// The storage slots the transaction changed, in order of slot
+    pub storage: Vec<SlotChange>,
+}
+
+/// The changes in a transaction's state diff, in order of address. Accounts which were touched
+/// but not changed are omitted.
+pub fn state_changes(diff: &StateDiff) -> Vec<AccountChange> {
+    diff.0
+        .iter()
+        .map(|(address, account)| AccountChange {
+            address: *address,
+            balance: ValueChange::from_delta(&account.balance),
+            nonce: ValueChange::from_delta(&account.nonce),
+            code: ValueChange::from_delta(&account.code),
+            storage: account
+                .storage
+                .iter()
+                .filter_map(|(slot, delta)| {
+                    let change = ValueChange::from_delta(delta)?;
+                    Some(SlotChange {
+                        slot: *slot,
+                        from: change.from,
+                        to: change.to,
+                        variables: Vec::new(),
+                    })
+                })
+                .collect(),
+        })
+        .filter(|account| {
+            account.balance.is_some() ||
+                account.nonce.is_some() ||
+                account.code.is_some() ||
+                !account.storage.is_empty()
+        })
+        .collect()
+}
+
+/// Names the state variables stored in each changed slot, by decompiling each contract whose
+/// storage changed to recover its storage layout. The keys of mapping values are recovered from
+/// the given words observed in the transaction, e.g. its calldata and log topics, and the
+/// addresses and values in its state diff.
+pub(crate) async fn label_state_changes(
+    changes: &mut [AccountChange],
+    words: impl IntoIterator<Item = FixedBytes<32>>,
+    rpc_url: &str,
+    skip_resolving: bool,
+) {
+    let mut keys = words.into_iter().collect::<BTreeSet<_>>();
+    for account in changes.iter() {
+        keys.insert(account.address.into_word());
+        keys.extend(account.storage.iter().flat_map(|slot| slot.from.into_iter().chain(slot.to)));
+    }
+
+    for account in changes.iter_mut().filter(|account| !account.storage.is_empty()) {
+        let storage = account
+            .storage
+            .iter()
+            .filter_map(|slot| slot.to.or(slot.from).map(|value| (slot.slot, value)))
+            .collect::<BTreeMap<_, _>>();
+        let mut variables =
+            label_slots(&account.address.to_lower_hex(), rpc_url, skip_resolving, &storage, &keys)
+                .await;
+        debug!(
+            "found the variables of {} of {} changed slots of {}",
+            variables.len(),
+            storage.len(),
+            account.address
+        );
+        for slot in &mut account.storage {
+            slot.variables = variables.remove(&slot.slot).unwrap_or_default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::types::trace::parity::{AccountDiff, ChangedType};
+
+    use super::*;
+
+    #[test]
+    fn test_state_changes() {
+        let (sender, token, untouched) =
+            (Address::repeat_byte(0x01), Address::repeat_byte(0x02), Address::repeat_byte(0x03));
+        let word = FixedBytes::<32>::with_last_byte;
+
+        let diff = StateDiff(BTreeMap::from([
+            (
+                sender,
+                AccountDiff {
+                    balance: Delta::Changed(ChangedType {
+                        from: U256::from(10),
+                        to: U256::from(7),
+                    }),
+                    code: Delta::Unchanged,
+                    nonce: Delta::Changed(ChangedType { from: U64::from(4), to: U64::from(5) }),
+                    storage: BTreeMap::new(),
+                },
+            ),
+            (
+                token,
+                AccountDiff {
+                    balance: Delta::Unchanged,
+                    code: Delta::Unchanged,
+                    nonce: Delta::Unchanged,
+                    storage: BTreeMap::from([
+                        (word(1), Delta::Added(word(9))),
+                        (word(2), Delta::Removed(word(8))),
+                        (word(3), Delta::Unchanged),
+                    ]),
+                },
+            ),
+            (
+                untouched,
+                AccountDiff {
+                    balance: Delta::Unchanged,
+                    code: Delta::Unchanged,
+                    nonce: Delta::Unchanged,
+                    storage: BTreeMap::new(),
+                },
+            ),
+        ]));
+
+        let changes = state_changes(&diff);
+        assert_eq!(
+            changes.iter().map(|account| account.address).collect::<Vec<_>>(),
+            [sender, token]
+        );
+        assert_eq!(
+            changes[0].balance,
+            Some(ValueChange { from: Some(U256::from(10)), to: Some(U256::from(7)) })
+        );
+        assert_eq!(changes[0].code, None);
+        assert_eq!(
+            changes[1]
+                .storage
+                .iter()
+                .map(|slot| (slot.slot, slot.from, slot.to))
+                .collect::<Vec<_>>(),
+            [(word(1), None, Some(word(9))), (word(2), Some(word(8)), None)]
+        );
+    }
+}
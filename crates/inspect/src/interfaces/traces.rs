@@ -12,7 +12,11 @@ use alloy::{
 use async_recursion::async_recursion;
 use eyre::eyre;
 use heimdall_common::{
-    ether::{signatures::ResolvedFunction, types::DynSolValueExt},
+    ether::{
+        humanize::{format_units, humanize_inputs, humanize_outputs},
+        signatures::ResolvedFunction,
+        types::DynSolValueExt,
+    },
     utils::{
         env::get_env,
         hex::ToLowerHex,
@@ -38,6 +42,9 @@ pub struct DecodedTransactionTrace {
     pub action: DecodedAction,
     pub result: Option<DecodedRes>,
     pub error: Option<String>,
+    /// Why the call reverted, decoded from its revert data, if it failed
+    #[serde(default, rename = "revertReason", skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
     pub subtraces: Vec<DecodedTransactionTrace>,
     pub logs: Vec<DecodedLog>,
     pub diff: Vec<StorageDelta>,
@@ -81,6 +88,10 @@ pub struct DecodedCall {
     pub decoded_inputs: Vec<DynSolValue>,
     #[serde(rename = "decodedInputs")]
     decoded_inputs_serializeable: Vec<Value>,
+    /// A human-friendly rendering of each decoded input, or `None` for inputs without one, if
+    /// requested with `--humanize`
+    #[serde(default, rename = "humanizedInputs", skip_serializing_if = "Vec::is_empty")]
+    pub humanized_inputs: Vec<Option<String>>,
 }
 
 /// Decoded Response
@@ -109,6 +120,10 @@ pub struct DecodedCallResult {
     pub decoded_outputs: Vec<DynSolValue>,
     #[serde(rename = "decodedOutputs")]
     decoded_outputs_serializeable: Vec<Value>,
+    /// A human-friendly rendering of each decoded output, or `None` for outputs without one, if
+    /// requested with `--humanize`
+    #[serde(default, rename = "humanizedOutputs", skip_serializing_if = "Vec::is_empty")]
+    pub humanized_outputs: Vec<Option<String>>,
 }
 
 #[async_trait]
@@ -186,9 +201,10 @@ impl TryFrom<TransactionTrace> for DecodedTransactionTrace {
             action,
             result,
             error: value.error,
+            revert_reason: None, // only the revert data of the top-level call is known
             subtraces: Vec::new(), // we will build this later
-            logs: Vec::new(),      // we will build this later
-            diff: Vec::new(),      // we will build this later
+            logs: Vec::new(),    // we will build this later
+            diff: Vec::new(),    // we will build this later
         })
     }
 }
@@ -231,6 +247,7 @@ impl TryFrom<CallAction> for DecodedCall {
             resolved_function,
             decoded_inputs_serializeable: decoded_inputs.iter().map(|v| v.serialize()).collect(),
             decoded_inputs,
+            humanized_inputs: Vec::new(),
         })
     }
 }
@@ -260,6 +277,7 @@ impl TryFrom<CallOutput> for DecodedCallResult {
             output: value.output,
             decoded_outputs_serializeable: decoded_outputs.iter().map(|v| v.serialize()).collect(),
             decoded_outputs,
+            humanized_outputs: Vec::new(),
         })
     }
 }
@@ -323,6 +341,63 @@ impl DecodedTransactionTrace {
         addresses
     }
 
+    /// Decodes why the call reverted from its revert data, i.e. the output of the failed call,
+    /// if it failed.
+    pub async fn decode_revert(&mut self, revert_data: &Bytes) -> Result<(), Error> {
+        if self.error.is_none() {
+            return Ok(());
+        }
+        if revert_data.is_empty() {
+            self.revert_reason = Some("reverted without a reason".to_string());
+            return Ok(());
+        }
+
+        let result = decode(
+            DecodeArgsBuilder::new()
+                .target(revert_data.to_string())
+                .revert(true)
+                .skip_resolving(
+                    get_env("SKIP_RESOLVING")
+                        .unwrap_or_else(|| "false".to_string())
+                        .parse::<bool>()
+                        .unwrap_or(false),
+                )
+                .build()
+                .expect("failed to build DecodeArgs"),
+        )
+        .await?;
+        self.revert_reason = result.revert_reason;
+        Ok(())
+    }
+
+    /// Annotates the decoded inputs and outputs of each call in a human-friendly way, scaling
+    /// amounts of the called tokens by their decimals, which are fetched from `rpc_url`.
+    #[async_recursion]
+    pub async fn humanize(&mut self, rpc_url: &str) {
+        if let DecodedAction::Call(call) = &mut self.action {
+            if let Some(function) = &call.resolved_function {
+                let inputs = function.decoded_inputs.as_deref().unwrap_or_default();
+                let humanized_inputs =
+                    humanize_inputs(&function.name, inputs, Some(call.to), rpc_url).await;
+                if let Some(DecodedRes::Call(call_result)) = &mut self.result {
+                    call_result.humanized_outputs = humanize_outputs(
+                        &function.name,
+                        inputs.len(),
+                        &call_result.decoded_outputs,
+                        Some(call.to),
+                        rpc_url,
+                    )
+                    .await;
+                }
+                call.humanized_inputs = humanized_inputs;
+            }
+        }
+
+        for subtrace in self.subtraces.iter_mut() {
+            subtrace.humanize(rpc_url).await;
+        }
+    }
+
     #[async_recursion]
     pub async fn join_logs(
         &mut self,
@@ -433,7 +508,8 @@ impl DecodedTransactionTrace {
                         .as_ref()
                         .unwrap_or(&vec![])
                         .iter()
-                        .map(|token| token.parameterize())
+                        .enumerate()
+                        .map(|(i, token)| humanized(token, call.humanized_inputs.get(i)))
                         .collect(),
                     None => vec![],
                 },
@@ -442,7 +518,10 @@ impl DecodedTransactionTrace {
                         let outputs = call_result
                             .decoded_outputs
                             .iter()
-                            .map(|token| token.parameterize())
+                            .enumerate()
+                            .map(|(i, token)| {
+                                humanized(token, call_result.humanized_outputs.get(i))
+                            })
                             .collect::<Vec<String>>();
 
                         if outputs.is_empty() {
@@ -453,10 +532,7 @@ impl DecodedTransactionTrace {
                     }
                     _ => "".to_string(),
                 },
-                vec![
-                    format!("{:?}", call.call_type).to_lowercase(),
-                    format!("value: {} ether", wei_to_ether(call.value)),
-                ],
+                self.call_extras(call),
             ),
             DecodedAction::Create(create) => trace.add_creation(
                 parent_trace_index,
@@ -492,6 +568,15 @@ impl DecodedTransactionTrace {
             ),
         };
 
+        // explain why the call reverted, if it's known
+        if let Some(revert_reason) = &self.revert_reason {
+            trace.add_message(
+                parent_trace_index,
+                line!(),
+                vec![format!("revert: {revert_reason}")],
+            );
+        }
+
         // for each log, add to trace
         for log in &self.logs {
             if let Some(event) = &log.resolved_event {
@@ -538,6 +623,30 @@ impl DecodedTransactionTrace {
     }
 }
 
+impl DecodedTransactionTrace {
+    /// The call's type, the ether it transferred and the gas it used, shown beside it in the
+    /// call tree.
+    fn call_extras(&self, call: &DecodedCall) -> Vec<String> {
+        let mut extras = vec![
+            format!("{:?}", call.call_type).to_lowercase(),
+            format!("value: {} ether", format_units(call.value, 18)),
+        ];
+        if let Some(DecodedRes::Call(call_result)) = &self.result {
+            extras.push(format!("gas used: {}", call_result.gas_used));
+        }
+        extras
+    }
+}
+
+/// Displays the parameter with its human-friendly rendering in place of its value, if it has one.
+fn humanized(token: &DynSolValue, humanized: Option<&Option<String>>) -> String {
+    let parameter = token.parameterize();
+    match (humanized.and_then(Option::as_ref), parameter.split_once(": ")) {
+        (Some(humanized), Some((ty, _))) => format!("{ty}: {humanized}"),
+        _ => parameter,
+    }
+}
+
 fn wei_to_ether(wei: U256) -> f64 {
     // convert U256 to u64 safely
     let wei_u64: u64 = wei.min(U256::from(u64::MAX)).try_into().unwrap_or(0);
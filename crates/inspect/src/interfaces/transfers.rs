@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use alloy::{
+    primitives::{Address, I256, U256},
+    sol,
+    sol_types::SolEvent,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{flows::ValueFlow, logs::DecodedLog};
+
+sol! {
+    event Transfer(address indexed from, address indexed to, uint256 value);
+    event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value);
+    event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values);
+}
+
+/// An asset whose balances a transaction may change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "standard", rename_all = "lowercase")]
+pub enum Asset {
+    /// The chain's native currency
+    Ether,
+    /// An ERC-20 token
+    Erc20 {
+        /// The token's contract
+        token: Address,
+    },
+    /// A token of an ERC-721 collection
+    Erc721 {
+        /// The collection's contract
+        token: Address,
+        /// The token's id
+        id: U256,
+    },
+    /// A token of an ERC-1155 contract
+    Erc1155 {
+        /// The token's contract
+        token: Address,
+        /// The token's id
+        id: U256,
+    },
+}
+
+impl Asset {
+    /// The contract of the token, or `None` for ether.
+    pub fn token(&self) -> Option<Address> {
+        match self {
+            Asset::Ether => None,
+            Asset::Erc20 { token } | Asset::Erc721 { token, .. } | Asset::Erc1155 { token, .. } => {
+                Some(*token)
+            }
+        }
+    }
+}
+
+/// A transfer of an asset between two accounts within a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetTransfer {
+    /// The asset transferred
+    pub asset: Asset,
+    /// The sender, or the zero address for mints
+    pub from: Address,
+    /// The recipient, or the zero address for burns
+    pub to: Address,
+    /// The amount transferred, which is 1 for ERC-721 tokens
+    pub amount: U256,
+}
+
+/// The net change in an account's balance of an asset within a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceDelta {
+    /// The account
+    pub address: Address,
+    /// The asset
+    pub asset: Asset,
+    /// The change in the account's balance
+    pub delta: I256,
+}
+
+/// The token transfers announced by the given logs, in the order they were emitted: ERC-20 and
+/// ERC-721 `Transfer`s, and ERC-1155 `TransferSingle`s and `TransferBatch`es.
+pub fn token_transfers<'a>(logs: impl IntoIterator<Item = &'a DecodedLog>) -> Vec<AssetTransfer> {
+    logs.into_iter().flat_map(log_transfers).collect()
+}
+
+/// The token transfers announced by a log, if it's a transfer event.
+fn log_transfers(log: &DecodedLog) -> Vec<AssetTransfer> {
+    let token = log.address;
+    let topic = |index: usize| log.topics.get(index).map(|topic| Address::from_word(*topic));
+    match log.topics.first() {
+        // ERC-721 transfers index the token id, while ERC-20 transfers log the amount
+        Some(&Transfer::SIGNATURE_HASH) => {
+            let (Some(from), Some(to)) = (topic(1), topic(2)) else { return Vec::new() };
+            match (log.topics.get(3), log.data.len()) {
+                (Some(id), 0) => vec![AssetTransfer {
+                    asset: Asset::Erc721 { token, id: U256::from_be_bytes(id.0) },
+                    from,
+                    to,
+                    amount: U256::from(1),
+                }],
+                (None, 32) => vec![AssetTransfer {
+                    asset: Asset::Erc20 { token },
+                    from,
+                    to,
+                    amount: U256::from_be_slice(&log.data),
+                }],
+                _ => Vec::new(),
+            }
+        }
+        Some(&TransferSingle::SIGNATURE_HASH) => {
+            match TransferSingle::decode_raw_log(log.topics.iter().copied(), &log.data) {
+                Ok(event) => vec![AssetTransfer {
+                    asset: Asset::Erc1155 { token, id: event.id },
+                    from: event.from,
+                    to: event.to,
+                    amount: event.value,
+                }],
+                Err(_) => Vec::new(),
+            }
+        }
+        Some(&TransferBatch::SIGNATURE_HASH) => {
+            match TransferBatch::decode_raw_log(log.topics.iter().copied(), &log.data) {
+                Ok(event) => event
+                    .ids
+                    .iter()
+                    .zip(event.values.iter())
+                    .map(|(id, value)| AssetTransfer {
+                        asset: Asset::Erc1155 { token, id: *id },
+                        from: event.from,
+                        to: event.to,
+                        amount: *value,
+                    })
+                    .collect(),
+                Err(_) => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The net change in each account's balance of each asset from the given ether and token
+/// transfers, ordered by account and then asset. Ether balances exclude gas fees, and the zero
+/// address, which tokens are minted from and burned to, is omitted, as are changes which cancel
+/// out.
+pub fn balance_deltas(value_flows: &[ValueFlow], transfers: &[AssetTransfer]) -> Vec<BalanceDelta> {
+    let ether = value_flows.iter().map(|flow| AssetTransfer {
+        asset: Asset::Ether,
+        from: flow.from,
+        to: flow.to,
+        amount: flow.value,
+    });
+
+    let mut deltas: BTreeMap<(Address, Asset), I256> = BTreeMap::new();
+    for transfer in ether.chain(transfers.iter().cloned()) {
+        let amount = I256::try_from(transfer.amount).unwrap_or(I256::MAX);
+        let from = deltas.entry((transfer.from, transfer.asset)).or_default();
+        *from = from.saturating_sub(amount);
+        let to = deltas.entry((transfer.to, transfer.asset)).or_default();
+        *to = to.saturating_add(amount);
+    }
+    deltas
+        .into_iter()
+        .filter(|((address, _), delta)| !address.is_zero() && !delta.is_zero())
+        .map(|((address, asset), delta)| BalanceDelta { address, asset, delta })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Bytes, B256},
+        sol_types::SolValue,
+    };
+
+    use super::*;
+
+    fn log(token: Address, topics: Vec<B256>, data: Vec<u8>) -> DecodedLog {
+        DecodedLog {
+            address: token,
+            topics,
+            data: Bytes::from(data),
+            resolved_event: None,
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            removed: false,
+        }
+    }
+
+    #[test]
+    fn test_token_transfers_and_balance_deltas() {
+        let (alice, bob, operator) =
+            (Address::repeat_byte(0x01), Address::repeat_byte(0x02), Address::repeat_byte(0x03));
+        let (usdc, punks, items) =
+            (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb), Address::repeat_byte(0xcc));
+        let word = |n: u64| U256::from(n).to_be_bytes::<32>().to_vec();
+
+        let logs = vec![
+            // alice pays bob 5 usdc, and 2 of it back
+            log(usdc, vec![Transfer::SIGNATURE_HASH, alice.into_word(), bob.into_word()], word(5)),
+            log(usdc, vec![Transfer::SIGNATURE_HASH, bob.into_word(), alice.into_word()], word(2)),
+            // bob is minted punk #7
+            log(
+                punks,
+                vec![
+                    Transfer::SIGNATURE_HASH,
+                    Address::ZERO.into_word(),
+                    bob.into_word(),
+                    B256::from(U256::from(7)),
+                ],
+                Vec::new(),
+            ),
+            // alice sends bob 3 of item #1 and 4 of item #2
+            log(
+                items,
+                vec![
+                    TransferBatch::SIGNATURE_HASH,
+                    operator.into_word(),
+                    alice.into_word(),
+                    bob.into_word(),
+                ],
+                (vec![U256::from(1), U256::from(2)], vec![U256::from(3), U256::from(4)])
+                    .abi_encode_params(),
+            ),
+        ];
+        let transfers = token_transfers(&logs);
+        assert_eq!(transfers.len(), 5);
+        assert_eq!(transfers[2].asset, Asset::Erc721 { token: punks, id: U256::from(7) });
+        assert_eq!(transfers[4].amount, U256::from(4));
+
+        let flows = vec![ValueFlow {
+            trace_address: Vec::new(),
+            from: alice,
+            to: bob,
+            value: U256::from(9),
+        }];
+        let deltas = balance_deltas(&flows, &transfers)
+            .into_iter()
+            .map(|delta| (delta.address, delta.asset, delta.delta.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deltas,
+            vec![
+                (alice, Asset::Ether, "-9".to_string()),
+                (alice, Asset::Erc20 { token: usdc }, "-3".to_string()),
+                (alice, Asset::Erc1155 { token: items, id: U256::from(1) }, "-3".to_string()),
+                (alice, Asset::Erc1155 { token: items, id: U256::from(2) }, "-4".to_string()),
+                (bob, Asset::Ether, "9".to_string()),
+                (bob, Asset::Erc20 { token: usdc }, "3".to_string()),
+                (bob, Asset::Erc721 { token: punks, id: U256::from(7) }, "1".to_string()),
+                (bob, Asset::Erc1155 { token: items, id: U256::from(1) }, "3".to_string()),
+                (bob, Asset::Erc1155 { token: items, id: U256::from(2) }, "4".to_string()),
+            ]
+        );
+    }
+}
@@ -14,4 +14,8 @@ mod utils;
 // re-export the public interface
 pub use core::{inspect, InspectResult};
 pub use error::Error;
-pub use interfaces::{InspectArgs, InspectArgsBuilder};
+pub use interfaces::{
+    balance_changes, balance_deltas, state_changes, token_transfers, AccountChange, Asset,
+    AssetTransfer, BalanceDelta, ClassGas, FrameGas, GasProfile, InspectArgs, InspectArgsBuilder,
+    OpcodeClass, SlotChange, ValueChange, ValueFlow,
+};
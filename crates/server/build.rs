@@ -0,0 +1,10 @@
+//! Compiles the gRPC service in `proto/heimdall.proto`.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/heimdall.proto");
+
+    // protox compiles the proto in-process, so building doesn't require protoc
+    let descriptors = protox::compile(["proto/heimdall.proto"], ["proto"])?;
+    tonic_build::configure().compile_fds(descriptors)?;
+    Ok(())
+}
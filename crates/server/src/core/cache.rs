@@ -0,0 +1,90 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+/// The responses to recent requests, shared by all of the server's handlers, so that repeated
+/// requests are served without running them again.
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    /// The cached responses, keyed by their request, along with when they were cached
+    responses: Mutex<HashMap<String, (Instant, Value)>>,
+    /// The maximum number of responses to cache
+    capacity: usize,
+    /// How long cached responses are served for
+    ttl: Duration,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache holding up to `capacity` responses for `ttl` each.
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self { responses: Mutex::new(HashMap::new()), capacity, ttl }
+    }
+
+    /// Gets the cached response to the request, if it hasn't expired.
+    pub(crate) fn get(&self, request: &str) -> Option<Value> {
+        let responses = self.responses.lock().expect("response cache lock poisoned");
+        responses
+            .get(request)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// Caches the response to the request. If the cache is full, expired responses are evicted,
+    /// or the oldest response if none have expired.
+    pub(crate) fn insert(&self, request: String, response: Value) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut responses = self.responses.lock().expect("response cache lock poisoned");
+        if responses.len() >= self.capacity && !responses.contains_key(&request) {
+            responses.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+            if responses.len() >= self.capacity {
+                if let Some(oldest) = responses
+                    .iter()
+                    .min_by_key(|(_, (cached_at, _))| *cached_at)
+                    .map(|(request, _)| request.clone())
+                {
+                    responses.remove(&oldest);
+                }
+            }
+        }
+        responses.insert(request, (Instant::now(), response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_response_cache_evicts_oldest() {
+        let cache = ResponseCache::new(2, Duration::from_secs(60));
+        for (i, request) in ["a", "b", "c"].into_iter().enumerate() {
+            cache.insert(request.to_string(), json!(i));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(json!(1)));
+        assert_eq!(cache.get("c"), Some(json!(2)));
+    }
+
+    #[test]
+    fn test_response_cache_expires() {
+        let cache = ResponseCache::new(2, Duration::ZERO);
+        cache.insert("a".to_string(), json!(1));
+        assert_eq!(cache.get("a"), None);
+
+        // a disabled cache doesn't hold anything
+        let cache = ResponseCache::new(0, Duration::from_secs(60));
+        cache.insert("a".to_string(), json!(1));
+        assert_eq!(cache.get("a"), None);
+    }
+}
@@ -0,0 +1,359 @@
+use std::{future::Future, str::FromStr, sync::Arc, time::Duration};
+
+use alloy::eips::BlockId;
+use heimdall_common::utils::progress::{Progress, ProgressSender};
+use heimdall_core::{
+    heimdall_decompiler::{decompile, DecompilerArgsBuilder, FunctionResult},
+    heimdall_dump::{dump, DumpArgsBuilder},
+    heimdall_inspect::{inspect, InspectArgsBuilder},
+};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+use tracing::warn;
+
+use crate::core::{
+    grpc::proto::{
+        decompile_update, dump_update, heimdall_server::Heimdall, DecompileRequest,
+        DecompileResult, DecompileUpdate, DumpRequest, DumpResult, DumpUpdate, InspectRequest,
+        InspectResponse, StorageSlot,
+    },
+    ServerState,
+};
+
+/// The messages and service generated from `proto/heimdall.proto`.
+#[allow(missing_docs, unreachable_pub, clippy::all, clippy::derive_partial_eq_without_eq)]
+pub(crate) mod proto {
+    tonic::include_proto!("heimdall");
+}
+
+/// Serves heimdall's long-running operations over gRPC, streaming their progress as they run.
+#[derive(Debug)]
+pub(crate) struct GrpcService {
+    /// The state shared with the HTTP server
+    state: Arc<ServerState>,
+}
+
+impl GrpcService {
+    pub(crate) fn new(state: Arc<ServerState>) -> Self {
+        Self { state }
+    }
+}
+
+impl From<Progress> for proto::Progress {
+    fn from(progress: Progress) -> Self {
+        Self { completed: progress.completed, total: progress.total, step: progress.step }
+    }
+}
+
+impl TryFrom<FunctionResult> for proto::FunctionResult {
+    type Error = eyre::Report;
+
+    fn try_from(function: FunctionResult) -> eyre::Result<Self> {
+        Ok(Self {
+            selector: function.selector,
+            signature: function.signature,
+            abi_json: serde_json::to_string(&function.abi)?,
+        })
+    }
+}
+
+/// Runs the operation once one of the server's permits is available, streaming each of its
+/// progress updates and partial results, followed by its result, or its error as the stream's
+/// status.
+fn stream_operation<U, F, Fut>(
+    state: Arc<ServerState>,
+    name: &'static str,
+    to_update: fn(proto::Progress) -> U,
+    operation: F,
+) -> ReceiverStream<Result<U, Status>>
+where
+    U: Send + 'static,
+    F: FnOnce(ProgressSender, mpsc::UnboundedSender<U>) -> Fut + Send + 'static,
+    Fut: Future<Output = eyre::Result<U>> + Send + 'static, {
+    let (updates, receiver) = mpsc::channel(16);
+    tokio::spawn(async move {
+        let _permit = state.permits.acquire().await.expect("semaphore closed");
+        let timeout = Duration::from_secs(state.args.request_timeout);
+        let (progress_sender, mut progress) = mpsc::unbounded_channel();
+        let (partial_sender, mut partial) = mpsc::unbounded_channel();
+        let operation = tokio::time::timeout(timeout, operation(progress_sender, partial_sender));
+        tokio::pin!(operation);
+
+        let result = loop {
+            tokio::select! {
+                biased;
+                Some(update) = progress.recv() => {
+                    // the client stopped listening, so the operation is cancelled
+                    if updates.send(Ok(to_update(update.into()))).await.is_err() {
+                        return;
+                    }
+                }
+                Some(update) = partial.recv() => {
+                    if updates.send(Ok(update)).await.is_err() {
+                        return;
+                    }
+                }
+                result = &mut operation => break result,
+            }
+        };
+        while let Ok(update) = progress.try_recv() {
+            let _ = updates.send(Ok(to_update(update.into()))).await;
+        }
+        while let Ok(update) = partial.try_recv() {
+            let _ = updates.send(Ok(update)).await;
+        }
+
+        let result = match result {
+            Ok(Ok(update)) => Ok(update),
+            Ok(Err(e)) => {
+                warn!("{} request failed: {}", name, e);
+                Err(Status::internal(e.to_string()))
+            }
+            Err(_) => {
+                warn!("{} request timed out after {:?}", name, timeout);
+                Err(Status::deadline_exceeded(format!("request timed out after {timeout:?}")))
+            }
+        };
+        let _ = updates.send(result).await;
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+#[tonic::async_trait]
+impl Heimdall for GrpcService {
+    type DumpStream = ReceiverStream<Result<DumpUpdate, Status>>;
+    type DecompileStream = ReceiverStream<Result<DecompileUpdate, Status>>;
+
+    /// Dumps the target's storage, streaming progress as each block is traced.
+    async fn dump(
+        &self,
+        request: Request<DumpRequest>,
+    ) -> Result<Response<Self::DumpStream>, Status> {
+        let request = request.into_inner();
+        if self.state.args.rpc_url.is_empty() {
+            return Err(Status::failed_precondition(
+                "dumping storage requires the server's --rpc-url",
+            ));
+        }
+
+        let rpc_url = self.state.args.rpc_url.clone();
+        let stream = stream_operation(
+            self.state.clone(),
+            "dump",
+            |progress| DumpUpdate { update: Some(dump_update::Update::Progress(progress)) },
+            move |progress, _| async move {
+                let args = DumpArgsBuilder::new()
+                    .target(request.target)
+                    .rpc_url(rpc_url)
+                    .from_block(request.from_block.into())
+                    .to_block(request.to_block.map(u128::from))
+                    .threads(request.threads.map_or(4, |threads| threads.max(1) as usize))
+                    .progress(Some(progress))
+                    .build()?;
+                let mut storage_dump = dump(args).await?;
+
+                let mut slots = storage_dump
+                    .storage
+                    .into_iter()
+                    .map(|(slot, value)| StorageSlot {
+                        slot: slot.to_string(),
+                        value: value.to_string(),
+                        variable: storage_dump.variables.remove(&slot).map(|variables| {
+                            variables
+                                .into_iter()
+                                .map(|variable| format!("{} = {}", variable.name, variable.value))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        }),
+                    })
+                    .collect::<Vec<_>>();
+                slots.sort_by(|a, b| a.slot.cmp(&b.slot));
+                Ok(DumpUpdate { update: Some(dump_update::Update::Result(DumpResult { slots })) })
+            },
+        );
+
+        Ok(Response::new(stream))
+    }
+
+    /// Decompiles the target, streaming progress as each function is symbolically executed, and
+    /// each function's result as it's analyzed.
+    async fn decompile(
+        &self,
+        request: Request<DecompileRequest>,
+    ) -> Result<Response<Self::DecompileStream>, Status> {
+        let request = request.into_inner();
+        let block = request
+            .block
+            .as_deref()
+            .map(BlockId::from_str)
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("invalid block: {e}")))?;
+
+        let args = self.state.args.clone();
+        let stream = stream_operation(
+            self.state.clone(),
+            "decompile",
+            |progress| DecompileUpdate {
+                update: Some(decompile_update::Update::Progress(progress)),
+            },
+            move |progress, partial| async move {
+                let (function_results, mut analyzed) = mpsc::unbounded_channel();
+                let args = DecompilerArgsBuilder::new()
+                    .target(request.target)
+                    .rpc_url(args.rpc_url)
+                    .block(block)
+                    .include_solidity(!request.include_yul)
+                    .include_yul(request.include_yul)
+                    .skip_resolving(request.skip_resolving)
+                    .etherscan_api_key(args.etherscan_api_key)
+                    .explorer_api_url(args.explorer_api_url)
+                    .progress(Some(progress))
+                    .function_results(Some(function_results))
+                    .build()?;
+
+                // the function results are forwarded until the decompiler drops its sender
+                let forward_function_results = async {
+                    while let Some(function) = analyzed.recv().await {
+                        let _ = partial.send(DecompileUpdate {
+                            update: Some(decompile_update::Update::Function(function.try_into()?)),
+                        });
+                    }
+                    Ok::<_, eyre::Report>(())
+                };
+                let (result, forwarded) = tokio::join!(decompile(args), forward_function_results);
+                let result = result?;
+                forwarded?;
+
+                Ok(DecompileUpdate {
+                    update: Some(decompile_update::Update::Result(DecompileResult {
+                        source: result.source.unwrap_or_default(),
+                        abi_json: serde_json::to_string(&result.abi)?,
+                        storage_layout_json: result.storage_layout.to_solc_json().to_string(),
+                    })),
+                })
+            },
+        );
+
+        Ok(Response::new(stream))
+    }
+
+    /// Inspects the target transaction, responding with its decoded trace.
+    async fn inspect(
+        &self,
+        request: Request<InspectRequest>,
+    ) -> Result<Response<InspectResponse>, Status> {
+        let request = request.into_inner();
+        if self.state.args.rpc_url.is_empty() {
+            return Err(Status::failed_precondition(
+                "inspecting transactions requires the server's --rpc-url",
+            ));
+        }
+
+        let _permit = self.state.permits.acquire().await.expect("semaphore closed");
+        let args = InspectArgsBuilder::new()
+            .target(request.target)
+            .rpc_url(self.state.args.rpc_url.clone())
+            .skip_resolving(request.skip_resolving)
+            .transpose_api_key(self.state.args.transpose_api_key.clone())
+            .build()
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let timeout = Duration::from_secs(self.state.args.request_timeout);
+        let result = tokio::time::timeout(timeout, inspect(args))
+            .await
+            .map_err(|_| Status::deadline_exceeded(format!("request timed out after {timeout:?}")))?
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let decoded_trace_json = serde_json::to_string(&result.decoded_trace)
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(InspectResponse { decoded_trace_json }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use heimdall_common::utils::offline::set_offline;
+    use tokio::net::TcpListener;
+    use tokio_stream::{wrappers::TcpListenerStream, StreamExt};
+    use tonic::transport::Server;
+
+    use super::*;
+    use crate::{
+        core::grpc::proto::{heimdall_client::HeimdallClient, heimdall_server::HeimdallServer},
+        interfaces::ServeArgsBuilder,
+    };
+
+    #[tokio::test]
+    async fn test_grpc_decompile_streams_progress() {
+        set_offline(true);
+        let args = ServeArgsBuilder::new().build().expect("failed to build args");
+        let state = Arc::new(ServerState::new(args));
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("failed to bind");
+        let address = listener.local_addr().expect("failed to get address");
+        tokio::spawn(
+            Server::builder()
+                .add_service(HeimdallServer::new(GrpcService::new(state)))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut client =
+            HeimdallClient::connect(format!("http://{address}")).await.expect("failed to connect");
+        let updates = client
+            .decompile(DecompileRequest {
+                target: "0x60003560e01c80631111111114610020578063222222221461002d57600080fd5b61002b600435610042565b005b61003b600435600101610042565b6002600155005b806005141561005057600080fd5b806000555056".to_string(),
+                skip_resolving: true,
+                ..Default::default()
+            })
+            .await
+            .expect("failed to decompile")
+            .into_inner()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .expect("decompile stream failed");
+
+        // each function's progress is streamed before the result
+        let steps = updates
+            .iter()
+            .filter_map(|update| match &update.update {
+                Some(decompile_update::Update::Progress(progress)) => Some(progress.step.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(steps.len(), 2);
+        assert!(steps.contains(&"11111111".to_string()));
+
+        // and each function's result as it's analyzed, also before the result
+        let functions = updates[..updates.len() - 1]
+            .iter()
+            .filter_map(|update| match &update.update {
+                Some(decompile_update::Update::Function(function)) => Some(function),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(functions.len(), 2);
+        let function = functions
+            .iter()
+            .find(|function| function.selector == "11111111")
+            .expect("no result for '11111111'");
+        assert_eq!(function.signature, None);
+        let abi: serde_json::Value =
+            serde_json::from_str(&function.abi_json).expect("invalid abi entry");
+        assert_eq!(abi["name"], "Unresolved_11111111");
+        assert_eq!(abi["inputs"][0]["type"], "uint256");
+        match &updates.last().expect("no updates").update {
+            Some(decompile_update::Update::Result(result)) => {
+                assert!(result.source.contains("function Unresolved_11111111"))
+            }
+            update => panic!("expected the result last, got {update:?}"),
+        }
+
+        // dumping requires an rpc
+        let status = client
+            .dump(DumpRequest { target: "0x".to_string(), ..Default::default() })
+            .await
+            .expect_err("dump should fail without an rpc");
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    }
+}
@@ -0,0 +1,299 @@
+pub(crate) mod cache;
+pub(crate) mod grpc;
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use eyre::eyre;
+use heimdall_core::{
+    heimdall_cfg::{cfg, CfgArgsBuilder},
+    heimdall_decoder::{decode, DecodeArgsBuilder},
+    heimdall_decompiler::{decompile, DecompilerArgsBuilder},
+    heimdall_disassembler::{disassemble, DisassemblerArgsBuilder},
+    heimdall_inspect::{inspect, InspectArgsBuilder},
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::Semaphore};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tracing::{debug, info, warn};
+
+use crate::{
+    core::{
+        cache::ResponseCache,
+        grpc::{proto::heimdall_server::HeimdallServer, GrpcService},
+    },
+    error::Error,
+    interfaces::{
+        CfgRequest, DecodeRequest, DecompileRequest, DisassembleRequest, InspectRequest, ServeArgs,
+    },
+};
+
+/// The state shared by all of the server's handlers.
+#[derive(Debug)]
+pub(crate) struct ServerState {
+    /// The arguments the server was started with
+    args: ServeArgs,
+    /// Limits the number of requests handled at once
+    permits: Semaphore,
+    /// The responses to recent requests
+    cache: ResponseCache,
+}
+
+impl ServerState {
+    fn new(args: ServeArgs) -> Self {
+        Self {
+            permits: Semaphore::new(args.max_concurrency.max(1)),
+            cache: ResponseCache::new(args.cache_size, Duration::from_secs(args.cache_ttl)),
+            args,
+        }
+    }
+}
+
+/// Serves heimdall's core operations over HTTP until the server is shut down. Each operation is
+/// served as a `POST` endpoint taking a JSON request, and responding with its result as JSON, or
+/// `{"error": "..."}` if it failed.
+///
+/// If `--grpc-port` is set, the gRPC API is served alongside, sharing the server's permits.
+pub async fn serve(args: ServeArgs) -> Result<(), Error> {
+    let state = Arc::new(ServerState::new(args));
+    let address = format!("{}:{}", state.args.host, state.args.port);
+    let listener = TcpListener::bind(&address).await?;
+    info!("serving heimdall on http://{}", address);
+    let http = async { axum::serve(listener, router(state.clone())).await.map_err(Error::from) };
+
+    match state.args.grpc_port {
+        Some(port) => {
+            let address = format!("{}:{}", state.args.host, port);
+            let listener = TcpListener::bind(&address).await?;
+            info!("serving heimdall's grpc api on {}", address);
+            let grpc = Server::builder()
+                .add_service(HeimdallServer::new(GrpcService::new(state.clone())))
+                .serve_with_incoming(TcpListenerStream::new(listener));
+
+            tokio::try_join!(http, async { grpc.await.map_err(Error::from) })?;
+        }
+        None => http.await?,
+    }
+    Ok(())
+}
+
+/// Builds the router serving each of heimdall's core operations.
+fn router(state: Arc<ServerState>) -> Router {
+    Router::new()
+        .route("/health", get(|| async { Json(json!({ "status": "ok" })) }))
+        .route("/disassemble", post(handle_disassemble))
+        .route("/decode", post(handle_decode))
+        .route("/decompile", post(handle_decompile))
+        .route("/cfg", post(handle_cfg))
+        .route("/inspect", post(handle_inspect))
+        .with_state(state)
+}
+
+/// Runs the operation for the request, once one of the server's permits is available, and
+/// responds with its result. Results are cached, so that repeated requests are served from the
+/// cache rather than running the operation again.
+async fn respond<R, F>(state: &ServerState, endpoint: &str, request: &R, operation: F) -> Response
+where
+    R: Serialize,
+    F: Future<Output = eyre::Result<Value>>, {
+    let key = format!("{endpoint}:{}", serde_json::to_string(request).unwrap_or_default());
+    if let Some(response) = state.cache.get(&key) {
+        debug!("serving cached response for {}", key);
+        return Json(response).into_response();
+    }
+
+    let _permit = state.permits.acquire().await.expect("semaphore closed");
+    let timeout = Duration::from_secs(state.args.request_timeout);
+    match tokio::time::timeout(timeout, operation).await {
+        Ok(Ok(response)) => {
+            state.cache.insert(key, response.clone());
+            Json(response).into_response()
+        }
+        Ok(Err(e)) => {
+            warn!("{} request failed: {}", endpoint, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+                .into_response()
+        }
+        Err(_) => {
+            warn!("{} request timed out after {:?}", endpoint, timeout);
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(json!({ "error": format!("request timed out after {timeout:?}") })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Disassembles the target's bytecode, responding with its assembly.
+async fn handle_disassemble(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<DisassembleRequest>,
+) -> Response {
+    let operation = async {
+        let args = DisassemblerArgsBuilder::new()
+            .target(request.target.clone())
+            .rpc_url(state.args.rpc_url.clone())
+            .block(request.block)
+            .decimal_counter(request.decimal_counter)
+            .etherscan_api_key(state.args.etherscan_api_key.clone())
+            .build()?;
+        let assembly = disassemble(args).await?;
+        Ok(json!({ "assembly": assembly }))
+    };
+
+    respond(&state, "disassemble", &request, operation).await
+}
+
+/// Decodes the target's calldata, responding with the decoded function and its arguments.
+async fn handle_decode(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<DecodeRequest>,
+) -> Response {
+    let operation = async {
+        let args = DecodeArgsBuilder::new()
+            .target(request.target.clone())
+            .rpc_url(state.args.rpc_url.clone())
+            .raw(request.raw)
+            .skip_resolving(request.skip_resolving)
+            .truncate_calldata(request.truncate_calldata)
+            .etherscan_api_key(state.args.etherscan_api_key.clone())
+            .explorer_api_url(state.args.explorer_api_url.clone())
+            .build()?;
+        let result = decode(args).await?;
+        Ok(serde_json::from_str(&result.to_json()?)?)
+    };
+
+    respond(&state, "decode", &request, operation).await
+}
+
+/// Decompiles the target, responding with its source, ABI and storage layout.
+async fn handle_decompile(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<DecompileRequest>,
+) -> Response {
+    let operation = async {
+        let args = DecompilerArgsBuilder::new()
+            .target(request.target.clone())
+            .rpc_url(state.args.rpc_url.clone())
+            .block(request.block)
+            .include_solidity(!request.include_yul)
+            .include_yul(request.include_yul)
+            .skip_resolving(request.skip_resolving)
+            .etherscan_api_key(state.args.etherscan_api_key.clone())
+            .explorer_api_url(state.args.explorer_api_url.clone())
+            .build()?;
+        let result = decompile(args).await?;
+        Ok(json!({
+            "source": result.source,
+            "abi": result.abi,
+            "storage_layout": result.storage_layout.to_solc_json(),
+        }))
+    };
+
+    respond(&state, "decompile", &request, operation).await
+}
+
+/// Generates the target's control flow graph, responding with its nodes and edges, as well as the
+/// graph in graphviz format.
+async fn handle_cfg(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<CfgRequest>,
+) -> Response {
+    let operation = async {
+        let args = CfgArgsBuilder::new()
+            .target(request.target.clone())
+            .rpc_url(state.args.rpc_url.clone())
+            .etherscan_api_key(state.args.etherscan_api_key.clone())
+            .build()?;
+        let result = cfg(args).await?;
+        let mut graph = result.to_json();
+        graph["dot"] = json!(result.as_dot(false));
+        Ok(graph)
+    };
+
+    respond(&state, "cfg", &request, operation).await
+}
+
+/// Inspects the target transaction, responding with its decoded trace.
+async fn handle_inspect(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<InspectRequest>,
+) -> Response {
+    let operation = async {
+        if state.args.rpc_url.is_empty() {
+            return Err(eyre!("inspecting transactions requires the server's --rpc-url"));
+        }
+
+        let args = InspectArgsBuilder::new()
+            .target(request.target.clone())
+            .rpc_url(state.args.rpc_url.clone())
+            .skip_resolving(request.skip_resolving)
+            .transpose_api_key(state.args.transpose_api_key.clone())
+            .build()?;
+        let result = inspect(args).await?;
+        Ok(serde_json::to_value(&result.decoded_trace)?)
+    };
+
+    respond(&state, "inspect", &request, operation).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use heimdall_common::utils::offline::set_offline;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::interfaces::ServeArgsBuilder;
+
+    async fn post(router: &Router, endpoint: &str, body: Value) -> (StatusCode, Value) {
+        let response = router
+            .clone()
+            .oneshot(
+                Request::post(endpoint)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .expect("failed to build request"),
+            )
+            .await
+            .expect("failed to send request");
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("failed to read response");
+        (status, serde_json::from_slice(&body).expect("response isn't json"))
+    }
+
+    #[tokio::test]
+    async fn test_serve_disassemble_and_decode() {
+        set_offline(true);
+        let args = ServeArgsBuilder::new().build().expect("failed to build args");
+        let router = router(Arc::new(ServerState::new(args)));
+
+        let (status, response) =
+            post(&router, "/disassemble", json!({ "target": "0x6080604052" })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["assembly"], "000000 PUSH1 80\n000002 PUSH1 40\n000004 MSTORE \n");
+
+        let calldata = "0x1111111100000000000000000000000000000000000000000000000000000000000000ff";
+        let (status, response) =
+            post(&router, "/decode", json!({ "target": calldata, "skip_resolving": true })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["name"], "Unresolved_11111111");
+
+        // failed operations respond with their error
+        let (status, response) = post(&router, "/decode", json!({ "target": "0x" })).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(response["error"].is_string());
+    }
+}
@@ -0,0 +1,13 @@
+/// Error type for the Server module
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Error when binding to or serving on the given address
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    /// Error when serving the gRPC API
+    #[error("gRPC error: {0}")]
+    TransportError(#[from] tonic::transport::Error),
+    /// Generic internal error
+    #[error("Internal error: {0}")]
+    Eyre(#[from] eyre::Report),
+}
@@ -0,0 +1,84 @@
+use clap::Parser;
+use derive_builder::Builder;
+use heimdall_config::parse_url_arg;
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Serve heimdall's core operations over HTTP, with JSON responses, and optionally gRPC",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    override_usage = "heimdall serve [OPTIONS]"
+)]
+/// Arguments for the serve operation
+///
+/// This struct contains all the configuration parameters needed to serve heimdall's core
+/// operations over HTTP.
+pub struct ServeArgs {
+    /// The address to listen on.
+    #[clap(long, default_value = "127.0.0.1")]
+    pub host: String,
+
+    /// The port to listen on.
+    #[clap(long, short, default_value = "8080")]
+    pub port: u16,
+
+    /// The port to serve the gRPC API on, which streams the progress of long-running operations,
+    /// such as dumps and decompilations. The gRPC API isn't served unless this is set.
+    #[clap(long = "grpc-port")]
+    pub grpc_port: Option<u16>,
+
+    /// The RPC provider to use for fetching targets.
+    /// This can be an explicit URL or a reference to a MESC endpoint. Multiple comma-separated
+    /// endpoints may be given, in which case requests will fail over to the next endpoint.
+    #[clap(long, short, value_parser = parse_url_arg, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// The maximum number of requests to handle at once. Further requests wait until one
+    /// finishes.
+    #[clap(long = "max-concurrency", default_value = "4")]
+    pub max_concurrency: usize,
+
+    /// The maximum time to spend handling a request, in seconds.
+    #[clap(long = "request-timeout", default_value = "120")]
+    pub request_timeout: u64,
+
+    /// The maximum number of responses to cache, so that repeated requests are served without
+    /// running them again. Set to 0 to disable caching.
+    #[clap(long = "cache-size", default_value = "1024")]
+    pub cache_size: usize,
+
+    /// How long cached responses are served for, in seconds.
+    #[clap(long = "cache-ttl", default_value = "300")]
+    pub cache_ttl: u64,
+
+    /// Your Etherscan API key, used to look up the verified ABIs of targets.
+    #[clap(long, short, default_value = "", hide_default_value = true)]
+    pub etherscan_api_key: String,
+
+    /// The URL of an Etherscan-compatible block explorer API to use instead of the chain's
+    /// default.
+    #[clap(long = "explorer-api-url", default_value = "", hide_default_value = true)]
+    pub explorer_api_url: String,
+
+    /// Your Transpose API key, used to look up contract labels when inspecting transactions.
+    #[clap(long, short, default_value = "", hide_default_value = true)]
+    pub transpose_api_key: String,
+}
+
+impl ServeArgsBuilder {
+    /// Creates a new ServeArgsBuilder with default values
+    pub fn new() -> Self {
+        Self {
+            host: Some(String::from("127.0.0.1")),
+            port: Some(8080),
+            grpc_port: Some(None),
+            rpc_url: Some(String::new()),
+            max_concurrency: Some(4),
+            request_timeout: Some(120),
+            cache_size: Some(1024),
+            cache_ttl: Some(300),
+            etherscan_api_key: Some(String::new()),
+            explorer_api_url: Some(String::new()),
+            transpose_api_key: Some(String::new()),
+        }
+    }
+}
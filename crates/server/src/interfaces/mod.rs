@@ -0,0 +1,6 @@
+mod args;
+mod requests;
+
+// re-export the public interface
+pub use args::{ServeArgs, ServeArgsBuilder};
+pub(crate) use requests::*;
@@ -0,0 +1,64 @@
+use alloy::eips::BlockId;
+use serde::{Deserialize, Serialize};
+
+/// A request to disassemble the target's bytecode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DisassembleRequest {
+    /// The target to disassemble, either bytecode or a contract address.
+    pub target: String,
+    /// The block to read the target's bytecode at. Defaults to the latest block.
+    #[serde(default)]
+    pub block: Option<BlockId>,
+    /// Whether to use base-10 for the program counter.
+    #[serde(default)]
+    pub decimal_counter: bool,
+}
+
+/// A request to decode the target's calldata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DecodeRequest {
+    /// The target to decode, either calldata or a transaction hash.
+    pub target: String,
+    /// Whether to treat the target as calldata, even if it looks like a transaction hash.
+    #[serde(default)]
+    pub raw: bool,
+    /// Whether to skip resolving the selector, inferring the arguments from the raw calldata.
+    #[serde(default)]
+    pub skip_resolving: bool,
+    /// Whether to truncate nonstandard sized calldata.
+    #[serde(default)]
+    pub truncate_calldata: bool,
+}
+
+/// A request to decompile the target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct DecompileRequest {
+    /// The target to decompile, either bytecode or a contract address.
+    pub target: String,
+    /// The block to read the target's bytecode at. Defaults to the latest block.
+    #[serde(default)]
+    pub block: Option<BlockId>,
+    /// Whether to decompile to Yul rather than Solidity.
+    #[serde(default)]
+    pub include_yul: bool,
+    /// Whether to skip resolving selectors.
+    #[serde(default)]
+    pub skip_resolving: bool,
+}
+
+/// A request to generate the target's control flow graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CfgRequest {
+    /// The target to generate the control flow graph of, either bytecode or a contract address.
+    pub target: String,
+}
+
+/// A request to inspect the target transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct InspectRequest {
+    /// The transaction hash of the target to inspect.
+    pub target: String,
+    /// Whether to skip resolving selectors.
+    #[serde(default)]
+    pub skip_resolving: bool,
+}
@@ -0,0 +1,16 @@
+//! The Server module serves heimdall's core operations, such as decoding calldata, decompiling
+//! contracts, generating control flow graphs and inspecting transactions, over HTTP and gRPC.
+//!
+//! The server is long-running, so the caches of resolved signatures, rpc responses and recent
+//! responses are shared across requests, and the number of requests handled at once is limited.
+
+/// Error types for the server module
+pub mod error;
+
+mod core;
+mod interfaces;
+
+// re-export the public interface
+pub use core::serve;
+pub use error::Error;
+pub use interfaces::{ServeArgs, ServeArgsBuilder};
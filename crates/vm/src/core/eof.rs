@@ -0,0 +1,356 @@
+use eyre::{bail, eyre, Result};
+
+use super::opcodes::{
+    min_gas, stack_io, terminating, OpCodeInfo, CALL, CALLCODE, CODECOPY, CODESIZE, CREATE,
+    CREATE2, DELEGATECALL, EXTCODECOPY, EXTCODEHASH, EXTCODESIZE, GAS, JUMP, JUMPI, PC,
+    SELFDESTRUCT, STATICCALL,
+};
+
+/// The bytes every EOF container starts with.
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+
+/// The header kind of the types section.
+const KIND_TYPES: u8 = 0x01;
+/// The header kind of the code sections.
+const KIND_CODE: u8 = 0x02;
+/// The header kind of the subcontainer sections (EIP-7620).
+const KIND_CONTAINER: u8 = 0x03;
+/// The header kind of the data section.
+const KIND_DATA: u8 = 0x04;
+/// The byte the header ends with.
+const TERMINATOR: u8 = 0x00;
+
+/// The `outputs` of a code section which never returns to its caller.
+pub const NON_RETURNING: u8 = 0x80;
+
+/// The `0xd0` ("DATALOAD") opcode.
+pub const DATALOAD: u8 = 0xd0;
+/// The `0xd1` ("DATALOADN") opcode.
+pub const DATALOADN: u8 = 0xd1;
+/// The `0xd2` ("DATASIZE") opcode.
+pub const DATASIZE: u8 = 0xd2;
+/// The `0xd3` ("DATACOPY") opcode.
+pub const DATACOPY: u8 = 0xd3;
+/// The `0xe0` ("RJUMP") opcode.
+pub const RJUMP: u8 = 0xe0;
+/// The `0xe1` ("RJUMPI") opcode.
+pub const RJUMPI: u8 = 0xe1;
+/// The `0xe2` ("RJUMPV") opcode.
+pub const RJUMPV: u8 = 0xe2;
+/// The `0xe3` ("CALLF") opcode.
+pub const CALLF: u8 = 0xe3;
+/// The `0xe4` ("RETF") opcode.
+pub const RETF: u8 = 0xe4;
+/// The `0xe5` ("JUMPF") opcode.
+pub const JUMPF: u8 = 0xe5;
+/// The `0xe6` ("DUPN") opcode.
+pub const DUPN: u8 = 0xe6;
+/// The `0xe7` ("SWAPN") opcode.
+pub const SWAPN: u8 = 0xe7;
+/// The `0xe8` ("EXCHANGE") opcode.
+pub const EXCHANGE: u8 = 0xe8;
+/// The `0xec` ("EOFCREATE") opcode.
+pub const EOFCREATE: u8 = 0xec;
+/// The `0xee` ("RETURNCONTRACT") opcode.
+pub const RETURNCONTRACT: u8 = 0xee;
+/// The `0xf7` ("RETURNDATALOAD") opcode.
+pub const RETURNDATALOAD: u8 = 0xf7;
+/// The `0xf8` ("EXTCALL") opcode.
+pub const EXTCALL: u8 = 0xf8;
+/// The `0xf9` ("EXTDELEGATECALL") opcode.
+pub const EXTDELEGATECALL: u8 = 0xf9;
+/// The `0xfb` ("EXTSTATICCALL") opcode.
+pub const EXTSTATICCALL: u8 = 0xfb;
+
+/// The inputs, outputs and stack height of a code section, from the types section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeType {
+    /// The number of stack items the section takes
+    pub inputs: u8,
+    /// The number of stack items the section returns, or [`NON_RETURNING`]
+    pub outputs: u8,
+    /// The most stack items the section uses
+    pub max_stack_height: u16,
+}
+
+/// A code section of an EOF container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeSection {
+    /// The offset of the section's code in the container
+    pub offset: usize,
+    /// The section's code
+    pub code: Vec<u8>,
+    /// The section's inputs, outputs and stack height
+    pub code_type: CodeType,
+}
+
+/// An EVM Object Format container (EIP-3540), with its code sections (EIP-4750), its
+/// subcontainers (EIP-7620) and its data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EofContainer {
+    /// The container's version
+    pub version: u8,
+    /// The container's code sections, the first of which is its entry point
+    pub code_sections: Vec<CodeSection>,
+    /// The containers which `EOFCREATE` and `RETURNCONTRACT` refer to by their index
+    pub containers: Vec<Vec<u8>>,
+    /// The offset of the data section in the container
+    pub data_offset: usize,
+    /// The data section, which may be shorter than declared in an initcode container
+    pub data: Vec<u8>,
+    /// The size of the data section declared in the header
+    pub declared_data_size: u16,
+}
+
+/// An instruction of an EOF code section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EofInstruction {
+    /// The offset of the instruction in the container
+    pub pc: usize,
+    /// The instruction's opcode
+    pub opcode: u8,
+    /// The bytes of the instruction's immediate, if it has one
+    pub immediate: Vec<u8>,
+}
+
+impl EofInstruction {
+    /// The pcs the instruction jumps to, if it's a relative jump. The offsets of relative jumps
+    /// are relative to the end of the instruction.
+    pub fn relative_targets(&self) -> Vec<usize> {
+        let end = self.pc + 1 + self.immediate.len();
+        let offsets = match self.opcode {
+            RJUMP | RJUMPI => self.immediate.get(..2).into_iter().collect::<Vec<_>>(),
+            RJUMPV => {
+                self.immediate.get(1..).map(|table| table.chunks(2).collect()).unwrap_or_default()
+            }
+            _ => return Vec::new(),
+        };
+        offsets
+            .into_iter()
+            .filter_map(|offset| {
+                let offset = i16::from_be_bytes(offset.try_into().ok()?);
+                end.checked_add_signed(offset as isize)
+            })
+            .collect()
+    }
+
+    /// The code section the instruction calls or jumps to, if it's a `CALLF` or `JUMPF`.
+    pub fn section_target(&self) -> Option<usize> {
+        match (self.opcode, self.immediate.as_slice()) {
+            (CALLF | JUMPF, [high, low]) => Some(u16::from_be_bytes([*high, *low]) as usize),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the bytecode is an EOF container, i.e. it starts with [`EOF_MAGIC`].
+pub fn is_eof(bytecode: &[u8]) -> bool {
+    bytecode.starts_with(&EOF_MAGIC)
+}
+
+/// The info of the opcode in an EOF code section. The opcodes EOF removed, such as `JUMP` and
+/// `SELFDESTRUCT`, aren't valid in it.
+pub fn eof_opcode_info(opcode: u8) -> Option<OpCodeInfo> {
+    match opcode {
+        DATALOAD => Some(min_gas(stack_io(OpCodeInfo::new("DATALOAD"), 1, 1), 4)),
+        DATALOADN => Some(min_gas(stack_io(OpCodeInfo::new("DATALOADN"), 0, 1), 3)),
+        DATASIZE => Some(min_gas(stack_io(OpCodeInfo::new("DATASIZE"), 0, 1), 2)),
+        DATACOPY => Some(min_gas(stack_io(OpCodeInfo::new("DATACOPY"), 3, 0), 3)),
+        RJUMP => Some(min_gas(OpCodeInfo::new("RJUMP"), 2)),
+        RJUMPI => Some(min_gas(stack_io(OpCodeInfo::new("RJUMPI"), 1, 0), 4)),
+        RJUMPV => Some(min_gas(stack_io(OpCodeInfo::new("RJUMPV"), 1, 0), 4)),
+        CALLF => Some(min_gas(OpCodeInfo::new("CALLF"), 5)),
+        RETF => Some(terminating(min_gas(OpCodeInfo::new("RETF"), 3))),
+        JUMPF => Some(terminating(min_gas(OpCodeInfo::new("JUMPF"), 5))),
+        DUPN => Some(min_gas(stack_io(OpCodeInfo::new("DUPN"), 0, 1), 3)),
+        SWAPN => Some(min_gas(OpCodeInfo::new("SWAPN"), 3)),
+        EXCHANGE => Some(min_gas(OpCodeInfo::new("EXCHANGE"), 3)),
+        EOFCREATE => Some(min_gas(stack_io(OpCodeInfo::new("EOFCREATE"), 4, 1), 32000)),
+        RETURNCONTRACT => Some(terminating(stack_io(OpCodeInfo::new("RETURNCONTRACT"), 2, 0))),
+        RETURNDATALOAD => Some(min_gas(stack_io(OpCodeInfo::new("RETURNDATALOAD"), 1, 1), 3)),
+        EXTCALL => Some(min_gas(stack_io(OpCodeInfo::new("EXTCALL"), 4, 1), 100)),
+        EXTDELEGATECALL => Some(min_gas(stack_io(OpCodeInfo::new("EXTDELEGATECALL"), 3, 1), 100)),
+        EXTSTATICCALL => Some(min_gas(stack_io(OpCodeInfo::new("EXTSTATICCALL"), 3, 1), 100)),
+        CALLCODE | SELFDESTRUCT | JUMP | JUMPI | PC | CREATE | CREATE2 | CODESIZE | CODECOPY |
+        EXTCODESIZE | EXTCODECOPY | EXTCODEHASH | GAS | CALL | DELEGATECALL | STATICCALL => None,
+        _ => OpCodeInfo::for_fork(opcode, super::hardfork::HardFork::Latest),
+    }
+}
+
+/// The size of the immediate of the opcode at the start of `code`, which for `RJUMPV` depends
+/// on the size of its jump table.
+fn immediate_size(code: &[u8]) -> usize {
+    match code[0] {
+        opcode @ 0x60..=0x7f => (opcode - 0x5f) as usize,
+        DUPN | SWAPN | EXCHANGE | EOFCREATE | RETURNCONTRACT => 1,
+        DATALOADN | RJUMP | RJUMPI | CALLF | JUMPF => 2,
+        RJUMPV => code.get(1).map(|max_index| 1 + (*max_index as usize + 1) * 2).unwrap_or(1),
+        _ => 0,
+    }
+}
+
+impl EofContainer {
+    /// Parses the container's header and sections.
+    pub fn parse(bytecode: &[u8]) -> Result<Self> {
+        if !is_eof(bytecode) {
+            bail!("bytecode doesn't start with the EOF magic");
+        }
+        let mut reader = Reader { bytes: bytecode, position: 2 };
+        let version = reader.u8()?;
+
+        reader.expect(KIND_TYPES)?;
+        let types_size = reader.u16()? as usize;
+        reader.expect(KIND_CODE)?;
+        let code_sizes = (0..reader.u16()?).map(|_| reader.u16()).collect::<Result<Vec<_>>>()?;
+        let container_sizes = match reader.peek() {
+            Some(KIND_CONTAINER) => {
+                reader.u8()?;
+                (0..reader.u16()?).map(|_| reader.u32()).collect::<Result<Vec<_>>>()?
+            }
+            _ => Vec::new(),
+        };
+        reader.expect(KIND_DATA)?;
+        let declared_data_size = reader.u16()?;
+        reader.expect(TERMINATOR)?;
+
+        if types_size != code_sizes.len() * 4 {
+            bail!(
+                "types section of {} bytes doesn't match {} code sections",
+                types_size,
+                code_sizes.len()
+            );
+        }
+        let types = (0..code_sizes.len())
+            .map(|_| {
+                Ok(CodeType {
+                    inputs: reader.u8()?,
+                    outputs: reader.u8()?,
+                    max_stack_height: reader.u16()?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let code_sections = code_sizes
+            .iter()
+            .zip(types)
+            .map(|(size, code_type)| {
+                let offset = reader.position;
+                Ok(CodeSection { offset, code: reader.take(*size as usize)?.to_vec(), code_type })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let containers = container_sizes
+            .iter()
+            .map(|size| Ok(reader.take(*size as usize)?.to_vec()))
+            .collect::<Result<Vec<_>>>()?;
+
+        // an initcode container's data may be truncated, and filled in by `RETURNCONTRACT`
+        let data_offset = reader.position;
+        let data = bytecode[data_offset..].to_vec();
+        if data.len() > declared_data_size as usize {
+            bail!("data section is longer than the {} bytes declared", declared_data_size);
+        }
+
+        Ok(Self { version, code_sections, containers, data_offset, data, declared_data_size })
+    }
+}
+
+impl CodeSection {
+    /// The instructions of the section, whose pcs are offsets in the container.
+    pub fn instructions(&self) -> Vec<EofInstruction> {
+        let mut instructions = Vec::new();
+        let mut position = 0;
+        while position < self.code.len() {
+            let size = immediate_size(&self.code[position..]);
+            let end = (position + 1 + size).min(self.code.len());
+            instructions.push(EofInstruction {
+                pc: self.offset + position,
+                opcode: self.code[position],
+                immediate: self.code[position + 1..end].to_vec(),
+            });
+            position = end;
+        }
+        instructions
+    }
+}
+
+/// Reads the big-endian integers of a container's header.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, size: usize) -> Result<&'a [u8]> {
+        let bytes = self.bytes.get(self.position..self.position + size).ok_or_else(|| {
+            eyre!(
+                "container ends at {} bytes, while reading {}",
+                self.bytes.len(),
+                self.position + size
+            )
+        })?;
+        self.position += size;
+        Ok(bytes)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn expect(&mut self, kind: u8) -> Result<()> {
+        let position = self.position;
+        match self.u8()? {
+            found if found == kind => Ok(()),
+            found => {
+                bail!("expected section kind {:#04x} at {}, found {:#04x}", kind, position, found)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use heimdall_common::utils::strings::decode_hex;
+
+    // two code sections: the first calls the second, then loops back with RJUMPI while the
+    // second returns its input. the data section holds 0xaabb
+    const CONTAINER: &str =
+        "ef0001010008020002000900020400020000800002010100016001e30001e1fff8005be4aabb";
+
+    #[test]
+    fn test_parse_container() {
+        let container = EofContainer::parse(&decode_hex(CONTAINER).expect("invalid bytecode"))
+            .expect("failed to parse container");
+
+        assert_eq!(container.version, 1);
+        assert_eq!(container.code_sections.len(), 2);
+        assert_eq!(
+            container.code_sections[1].code_type,
+            CodeType { inputs: 1, outputs: 1, max_stack_height: 1 }
+        );
+        assert_eq!(container.data, vec![0xaa, 0xbb]);
+
+        let instructions = container.code_sections[0].instructions();
+        assert_eq!(instructions[1].section_target(), Some(1));
+        let rjumpi = instructions.iter().find(|instruction| instruction.opcode == RJUMPI).unwrap();
+        assert_eq!(rjumpi.relative_targets(), vec![container.code_sections[0].offset]);
+    }
+
+    #[test]
+    fn test_reject_truncated_container() {
+        assert!(EofContainer::parse(&decode_hex("ef00010100").expect("invalid bytecode")).is_err());
+        assert!(EofContainer::parse(&[0x60, 0x80]).is_err());
+    }
+}
@@ -4,6 +4,9 @@ pub mod chains;
 /// Constants used throughout the VM implementation
 pub mod constants;
 
+/// EVM Object Format container parsing
+pub mod eof;
+
 /// Ethereum hard fork definitions
 pub mod hardfork;
 
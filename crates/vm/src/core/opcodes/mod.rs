@@ -346,7 +346,7 @@ opcodes! {
 
     0x20 => SHA3 => stack_io(2, 1), min_gas(30);
 
-    0x30 => ADDRESS => stack_io(0, 1), min_gas(2);
+    0x30 => ADDRESS => stack_io(0, 1), min_gas(2), non_pure;
     0x31 => BALANCE => stack_io(1, 1), min_gas(100), non_pure;
     0x32 => ORIGIN => stack_io(0, 1), min_gas(2), non_pure;
     0x33 => CALLER => stack_io(0, 1), min_gas(2), non_pure;
@@ -377,7 +377,7 @@ opcodes! {
     // London (EIP-3198)
     0x48 => BASEFEE => stack_io(0, 1), min_gas(2), non_pure, activated(HardFork::London);
     // Cancun (EIP-4844)
-    0x49 => BLOBHASH => stack_io(0, 1), min_gas(3), non_pure, activated(HardFork::Cancun);
+    0x49 => BLOBHASH => stack_io(1, 1), min_gas(3), non_pure, activated(HardFork::Cancun);
     0x4a => BLOBBASEFEE => stack_io(0, 1), min_gas(2), non_pure, activated(HardFork::Cancun);
 
     0x50 => POP => stack_io(1, 0), min_gas(2);
@@ -393,8 +393,8 @@ opcodes! {
     0x5a => GAS => stack_io(0, 1), min_gas(2);
     0x5b => JUMPDEST => min_gas(1);
     // Cancun (EIP-1153)
-    0x5c => TLOAD => stack_io(1, 1), min_gas(100), activated(HardFork::Cancun);
-    0x5d => TSTORE => stack_io(2, 0), min_gas(100), activated(HardFork::Cancun);
+    0x5c => TLOAD => stack_io(1, 1), min_gas(100), non_pure, activated(HardFork::Cancun);
+    0x5d => TSTORE => stack_io(2, 0), min_gas(100), non_pure, non_view, activated(HardFork::Cancun);
     // Cancun (EIP-5656)
     0x5e => MCOPY => stack_io(3, 0), min_gas(3), activated(HardFork::Cancun);
 
@@ -467,11 +467,11 @@ opcodes! {
     0x9e => SWAP15 => stack_io(16, 16), min_gas(3);
     0x9f => SWAP16 => stack_io(17, 17), min_gas(3);
 
-    0xa0 => LOG0 => stack_io(2, 0), min_gas(375);
-    0xa1 => LOG1 => stack_io(3, 0), min_gas(750);
-    0xa2 => LOG2 => stack_io(4, 0), min_gas(1125);
-    0xa3 => LOG3 => stack_io(5, 0), min_gas(1500);
-    0xa4 => LOG4 => stack_io(6, 0), min_gas(1875);
+    0xa0 => LOG0 => stack_io(2, 0), min_gas(375), non_pure, non_view;
+    0xa1 => LOG1 => stack_io(3, 0), min_gas(750), non_pure, non_view;
+    0xa2 => LOG2 => stack_io(4, 0), min_gas(1125), non_pure, non_view;
+    0xa3 => LOG3 => stack_io(5, 0), min_gas(1500), non_pure, non_view;
+    0xa4 => LOG4 => stack_io(6, 0), min_gas(1875), non_pure, non_view;
 
     0xf0 => CREATE => stack_io(3, 1), min_gas(32000), non_pure, non_view;
     0xf1 => CALL => stack_io(7, 1), min_gas(100), non_pure, non_view;
@@ -482,7 +482,7 @@ opcodes! {
     // Constantinople (EIP-1014)
     0xf5 => CREATE2 => stack_io(4, 1), min_gas(32000), non_pure, non_view, activated(HardFork::Constantinople);
     // Byzantium (EIP-214)
-    0xfa => STATICCALL => stack_io(6, 1), min_gas(100), non_pure, non_view, activated(HardFork::Byzantium);
+    0xfa => STATICCALL => stack_io(6, 1), min_gas(100), non_pure, activated(HardFork::Byzantium);
     // Byzantium (EIP-140)
     0xfd => REVERT => stack_io(2, 0), terminating, activated(HardFork::Byzantium);
     0xfe => INVALID => terminating;
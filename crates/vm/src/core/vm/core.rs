@@ -4,14 +4,17 @@ use std::sync::Arc;
 use alloy::primitives::{Address, I256, U256};
 use eyre::{OptionExt, Result};
 
-#[cfg(feature = "step-tracing")]
-use std::time::Instant;
 #[cfg(feature = "step-tracing")]
 use tracing::trace;
-
-use crate::core::{
-    hardfork::HardFork,
-    opcodes::{self, OpCodeInfo, WrappedInput, WrappedOpcode},
+#[cfg(feature = "step-tracing")]
+use web_time::Instant;
+
+use crate::{
+    core::{
+        hardfork::HardFork,
+        opcodes::{self, OpCodeInfo, WrappedInput, WrappedOpcode},
+    },
+    ext::exec::Truncation,
 };
 
 use super::super::{
@@ -82,6 +85,18 @@ pub struct VM {
     /// The hard fork to use for opcode activation.
     pub hardfork: HardFork,
 
+    /// The number of branches symbolic execution may create before it stops exploring, if any.
+    /// Unlike a timeout, this truncates the same paths on every run.
+    pub branch_limit: Option<u32>,
+
+    /// The number of bytes of memory a branch may expand to before symbolic execution stops
+    /// exploring it, if any.
+    pub memory_limit: Option<u128>,
+
+    /// Why the last symbolic execution stopped exploring before it covered every branch, if it
+    /// did.
+    pub truncation: Option<Truncation>,
+
     /// Counter for operations executed (only available with step-tracing feature).
     #[cfg(feature = "step-tracing")]
     pub operation_count: u128,
@@ -136,6 +151,9 @@ impl VM {
             exitcode: 255,
             address_access_set: HashSet::new(),
             hardfork: HardFork::default(),
+            branch_limit: None,
+            memory_limit: None,
+            truncation: None,
             #[cfg(feature = "step-tracing")]
             operation_count: 0,
             #[cfg(feature = "step-tracing")]
@@ -149,6 +167,19 @@ impl VM {
         self
     }
 
+    /// Sets the number of branches symbolic execution may create before it stops exploring.
+    pub fn with_branch_limit(mut self, branch_limit: u32) -> Self {
+        self.branch_limit = Some(branch_limit);
+        self
+    }
+
+    /// Sets the number of bytes of memory a branch may expand to before symbolic execution stops
+    /// exploring it.
+    pub fn with_memory_limit(mut self, memory_limit: u128) -> Self {
+        self.memory_limit = Some(memory_limit);
+        self
+    }
+
     /// Exits current execution with the given code and returndata.
     ///
     /// ```
@@ -440,6 +471,7 @@ impl VM {
 
             opcodes::COINBASE => handlers::block::coinbase(self, operation)?,
             opcodes::TIMESTAMP => handlers::block::timestamp(self, operation)?,
+            opcodes::BLOBHASH => handlers::block::blobhash(self, operation)?,
             (opcodes::NUMBER..=opcodes::BLOBBASEFEE) => {
                 handlers::block::block_info_stub(self, operation)?
             }
@@ -1504,6 +1536,17 @@ mod tests {
         assert_eq!(vm.exitcode, 10);
     }
 
+    #[test]
+    fn test_vm_blobhash_pops_index() {
+        // PUSH1 0x2a, PUSH1 0x00, BLOBHASH, STOP: the index is replaced by the zero hash
+        let mut vm = new_test_vm_with_fork("0x602a60004900", HardFork::Cancun);
+        vm.execute().expect("execution failed!");
+
+        assert_eq!(vm.stack.size(), 2);
+        assert_eq!(vm.stack.peek(0).value, U256::ZERO);
+        assert_eq!(vm.stack.peek(1).value, U256::from(0x2a));
+    }
+
     #[test]
     fn test_vm_tload_unknown_before_cancun() {
         // TLOAD (0x5c) should be unknown before Cancun
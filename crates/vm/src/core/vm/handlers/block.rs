@@ -1,4 +1,4 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use web_time::{SystemTime, UNIX_EPOCH};
 
 use alloy::primitives::U256;
 use eyre::Result;
@@ -20,8 +20,16 @@ pub fn timestamp(vm: &mut VM, operation: WrappedOpcode) -> Result<()> {
     Ok(())
 }
 
+/// BLOBHASH - Get the versioned hash of one of the transaction's blobs. There are no blobs to
+/// index outside of a blob transaction, so the hash is always zero
+pub fn blobhash(vm: &mut VM, operation: WrappedOpcode) -> Result<()> {
+    vm.stack.pop()?;
+    vm.stack.push(U256::ZERO, operation);
+    Ok(())
+}
+
 /// Generic handler for block info opcodes that return 1
-/// (NUMBER, PREVRANDAO, GASLIMIT, CHAINID, SELFBALANCE, BASEFEE, BLOBHASH, BLOBBASEFEE)
+/// (NUMBER, PREVRANDAO, GASLIMIT, CHAINID, SELFBALANCE, BASEFEE, BLOBBASEFEE)
 pub fn block_info_stub(vm: &mut VM, operation: WrappedOpcode) -> Result<()> {
     vm.stack.push(U256::from(1u8), operation);
     Ok(())
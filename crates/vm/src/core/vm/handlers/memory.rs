@@ -91,7 +91,7 @@ pub fn mcopy(vm: &mut VM, #[cfg(feature = "experimental")] operation: WrappedOpc
 
     // consume dynamic gas
     let minimum_word_size = size.div_ceil(32) as u128;
-    let gas_cost = 3 * minimum_word_size + vm.memory.expansion_cost(offset, size);
+    let gas_cost = 3 * minimum_word_size + vm.memory.expansion_cost(offset.max(dest_offset), size);
     vm.consume_gas(gas_cost);
 
     vm.memory.store_with_opcode(
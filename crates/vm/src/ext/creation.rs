@@ -0,0 +1,197 @@
+//! Splitting creation bytecode into its constructor, the runtime bytecode it deploys, and the
+//! constructor arguments appended to it.
+
+use web_time::{Duration, Instant};
+
+use alloy::primitives::{Address, U256};
+use tracing::debug;
+
+use crate::core::{
+    hardfork::HardFork,
+    opcodes::{WrappedOpcode, CODECOPY, MSTORE},
+    vm::VM,
+};
+
+/// Zeroed constructor arguments appended to creation bytecode which doesn't include any, so that
+/// constructors which decode arguments don't revert. Zero words decode as both static values and
+/// empty dynamic values.
+pub const PLACEHOLDER_ARGUMENTS: [u8; 512] = [0; 512];
+
+/// Creation bytecode, split into the constructor and the runtime bytecode it deploys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreationCode {
+    /// The creation bytecode, including any constructor arguments
+    pub bytecode: Vec<u8>,
+    /// The runtime bytecode returned by the constructor, with its immutables assigned
+    pub runtime: Vec<u8>,
+    /// The offset the runtime bytecode is copied from
+    pub runtime_offset: usize,
+    /// The memory offset the runtime bytecode is copied to
+    pub runtime_memory: usize,
+    /// The ABI-encoded constructor arguments appended to the creation bytecode, if it includes
+    /// them
+    pub arguments: Option<Vec<u8>>,
+    /// The memory offset the constructor arguments are copied to, if they're decoded
+    pub arguments_memory: Option<usize>,
+    /// The offsets of the runtime bytecode each immutable is written to
+    pub immutables: Vec<Vec<usize>>,
+}
+
+/// Splits creation bytecode into the constructor and the runtime bytecode it deploys, by
+/// executing it. Returns `None` if the bytecode isn't creation bytecode, i.e. it doesn't return a
+/// copy of its own code.
+pub fn split_creation_code(
+    bytecode: &[u8],
+    hardfork: HardFork,
+    timeout: u64,
+) -> Option<CreationCode> {
+    if let Some(creation) = deploy(bytecode, hardfork, timeout) {
+        return Some(creation);
+    }
+
+    // the bytecode may be missing its constructor arguments
+    let padded = [bytecode, &PLACEHOLDER_ARGUMENTS].concat();
+    deploy(&padded, hardfork, timeout).map(|creation| CreationCode {
+        bytecode: bytecode.to_vec(),
+        arguments: None,
+        ..creation
+    })
+}
+
+/// Executes the bytecode as a constructor, returning the code it deploys if it returns a copy of
+/// its own code.
+fn deploy(bytecode: &[u8], hardfork: HardFork, timeout: u64) -> Option<CreationCode> {
+    let mut evm = VM::new(
+        bytecode,
+        &[],
+        Address::default(),
+        Address::default(),
+        Address::default(),
+        0,
+        u128::MAX,
+    )
+    .with_hardfork(hardfork);
+    let deadline =
+        Instant::now().checked_add(Duration::from_millis(timeout)).expect("invalid timeout");
+    let to_usize = |value: U256| usize::try_from(value).unwrap_or(usize::MAX);
+
+    // (step, destination, offset, size) of each copy of the bytecode into memory, and
+    // (step, destination, value) of each word written to memory
+    let mut copies = Vec::new();
+    let mut writes: Vec<(usize, usize, WrappedOpcode)> = Vec::new();
+    let mut step = 0;
+    while evm.bytecode.len() >= evm.instruction as usize && Instant::now() < deadline {
+        let instruction = evm.step().ok()?.last_instruction;
+        match instruction.opcode {
+            CODECOPY => copies.push((
+                step,
+                to_usize(instruction.inputs[0]),
+                to_usize(instruction.inputs[1]),
+                to_usize(instruction.inputs[2]),
+            )),
+            MSTORE => writes.push((
+                step,
+                to_usize(instruction.inputs[0]),
+                instruction.input_operations[1].clone(),
+            )),
+            _ => {}
+        }
+        step += 1;
+
+        if evm.exitcode != 255 || !evm.returndata.is_empty() {
+            break;
+        }
+    }
+
+    // the deployed code is copied from the creation bytecode in one piece
+    if evm.exitcode != 0 || evm.returndata.is_empty() {
+        return None;
+    }
+    let runtime_len = evm.returndata.len();
+    let (copy_step, runtime_memory, runtime_offset, _) =
+        copies.iter().rev().copied().find(|(_, _, offset, size)| {
+            *size == runtime_len && offset.saturating_add(*size) <= bytecode.len()
+        })?;
+    debug!(
+        "target is creation bytecode deploying {} bytes of runtime bytecode from offset {}",
+        runtime_len, runtime_offset
+    );
+
+    // constructor arguments are appended to the creation bytecode, after the deployed code
+    let arguments_offset = runtime_offset + runtime_len;
+    let arguments_memory = copies
+        .iter()
+        .find(|(_, _, offset, size)| *offset == arguments_offset && *size > 0)
+        .map(|(_, dest, ..)| *dest);
+
+    // immutables are written over their placeholders in the copy of the deployed code
+    let mut immutables: Vec<(WrappedOpcode, Vec<usize>)> = Vec::new();
+    for (_, dest, value) in writes.into_iter().filter(|(step, dest, _)| {
+        *step > copy_step &&
+            *dest >= runtime_memory &&
+            dest.saturating_add(32) <= runtime_memory + runtime_len
+    }) {
+        let offset = dest - runtime_memory;
+        match immutables.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, offsets)) => offsets.push(offset),
+            None => immutables.push((value, vec![offset])),
+        }
+    }
+
+    Some(CreationCode {
+        bytecode: bytecode.to_vec(),
+        runtime: evm.returndata,
+        runtime_offset,
+        runtime_memory,
+        arguments: Some(bytecode[arguments_offset..].to_vec()),
+        arguments_memory,
+        immutables: immutables.into_iter().map(|(_, offsets)| offsets).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use heimdall_common::utils::strings::decode_hex;
+
+    use super::*;
+
+    /// `constructor(address owner, uint256 value)`, which stores both arguments and assigns
+    /// `value` to an immutable read by `value()`
+    const CREATION_BYTECODE: &str = "608060405234801561001057600080fd5b506100a43803806100a4608039604090106100485760805160005560a05160015561005661004e60003960a05161002e526100566000f35b600080fdfe60003560e01c80638da5cb5b146100205780633fa4f2451461002c57600080fd5b60005460005260206000f35b7f000000000000000000000000000000000000000000000000000000000000000060005260206000f3";
+
+    /// The abi-encoded arguments `(0x00000000000000000000000000000000000000aa, 42)`
+    const ARGUMENTS: &str = "00000000000000000000000000000000000000000000000000000000000000aa000000000000000000000000000000000000000000000000000000000000002a";
+
+    #[test]
+    fn test_split_creation_code() {
+        let bytecode = decode_hex(&format!("{CREATION_BYTECODE}{ARGUMENTS}")).expect("invalid hex");
+        let creation =
+            split_creation_code(&bytecode, HardFork::Latest, 1000).expect("not creation bytecode");
+
+        assert_eq!(creation.runtime_offset, 78);
+        assert_eq!(creation.runtime.len(), 86);
+        assert_eq!(creation.runtime_memory, 0);
+        assert_eq!(creation.arguments, Some(decode_hex(ARGUMENTS).expect("invalid hex")));
+        assert_eq!(creation.arguments_memory, Some(0x80));
+        assert_eq!(creation.immutables, vec![vec![46]]);
+        assert_eq!(creation.runtime[46..78], U256::from(42).to_be_bytes::<32>());
+    }
+
+    #[test]
+    fn test_split_creation_code_without_arguments() {
+        let bytecode = decode_hex(CREATION_BYTECODE).expect("invalid hex");
+        let creation =
+            split_creation_code(&bytecode, HardFork::Latest, 1000).expect("not creation bytecode");
+
+        assert_eq!(creation.bytecode, bytecode);
+        assert_eq!(creation.arguments, None);
+        assert_eq!(creation.arguments_memory, Some(0x80));
+        assert_eq!(creation.immutables, vec![vec![46]]);
+    }
+
+    #[test]
+    fn test_split_runtime_code() {
+        let runtime = decode_hex("60003560e01c80638da5cb5b146100205780633fa4f2451461002c57600080fd5b60005460005260206000f35b7f000000000000000000000000000000000000000000000000000000000000000060005260206000f3").expect("invalid hex");
+        assert_eq!(split_creation_code(&runtime, HardFork::Latest, 1000), None);
+    }
+}
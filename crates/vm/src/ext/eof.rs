@@ -0,0 +1,335 @@
+//! Lowering EOF containers to equivalent legacy bytecode, so that their code sections can be
+//! symbolically executed and decompiled like any other contract.
+//!
+//! EOF's jumps are static, so each is lowered to a `PUSH2` of its target's offset in the legacy
+//! bytecode, followed by a `JUMP` or `JUMPI`, and a `JUMPDEST` is emitted at each target. Code
+//! sections are called like solidity's internal functions: `CALLF` pushes the offset it returns
+//! to beneath the section's inputs, and `RETF` jumps back to it from beneath the section's
+//! outputs. The data section is appended to the code, so that `DATACOPY` can be lowered to
+//! `CODECOPY`.
+
+use eyre::{bail, Result};
+use hashbrown::{HashMap, HashSet};
+
+use crate::core::{
+    eof::{
+        eof_opcode_info, EofContainer, EofInstruction, CALLF, DATACOPY, DATALOAD, DATALOADN,
+        DATASIZE, DUPN, EOFCREATE, EXCHANGE, EXTCALL, EXTDELEGATECALL, EXTSTATICCALL, JUMPF, RETF,
+        RETURNCONTRACT, RETURNDATALOAD, RJUMP, RJUMPI, RJUMPV, SWAPN,
+    },
+    opcodes::{
+        ADD, CALL, CODECOPY, DELEGATECALL, DUP1, EQ, GAS, ISZERO, JUMP, JUMPDEST, JUMPI, POP,
+        PUSH0, PUSH1, PUSH2, PUSH32, STATICCALL, SWAP1,
+    },
+};
+
+/// A position in the legacy bytecode which is only known once all of it is emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Label {
+    /// The instruction at the given offset in the container
+    Instruction(usize),
+    /// The stub which pops the index of the `RJUMPV` at the given offset, then jumps to the
+    /// target of the given index
+    Stub(usize, usize),
+    /// The start of the data section
+    Data,
+}
+
+/// Lowers the container's code sections, and its data, to legacy bytecode which behaves the same
+/// when executed. The entry point of the first code section is the start of the bytecode.
+///
+/// Instructions with no legacy equivalent, such as `DATALOAD` and `RETURNDATALOAD`, or which
+/// reach deeper than legacy's sixteen stack items, can't be lowered.
+pub fn lower_to_legacy(container: &EofContainer) -> Result<Vec<u8>> {
+    let sections =
+        container.code_sections.iter().map(|section| section.instructions()).collect::<Vec<_>>();
+
+    // every instruction which is jumped or returned to is preceded by a JUMPDEST
+    let mut targets = container.code_sections.iter().map(|s| s.offset).collect::<HashSet<_>>();
+    for instructions in &sections {
+        for (instruction, next) in instructions.iter().zip(instructions.iter().skip(1)) {
+            if instruction.opcode == CALLF {
+                targets.insert(next.pc);
+            }
+        }
+        targets.extend(instructions.iter().flat_map(EofInstruction::relative_targets));
+    }
+
+    let mut lowering = Lowering::default();
+    let mut stubs = Vec::new();
+    for (section, instructions) in container.code_sections.iter().zip(&sections) {
+        for instruction in instructions {
+            if targets.contains(&instruction.pc) {
+                lowering.labels.insert(Label::Instruction(instruction.pc), lowering.code.len());
+                lowering.code.push(JUMPDEST);
+            }
+
+            match instruction.opcode {
+                RJUMP | RJUMPI => {
+                    let target = instruction.relative_targets()[0];
+                    lowering.push_label(Label::Instruction(target));
+                    lowering.code.push(if instruction.opcode == RJUMP { JUMP } else { JUMPI });
+                }
+                RJUMPV => {
+                    // each index is compared in turn, falling through if none match
+                    for (index, target) in instruction.relative_targets().into_iter().enumerate() {
+                        lowering.code.extend([DUP1, PUSH1, index as u8, EQ]);
+                        lowering.push_label(Label::Stub(instruction.pc, index));
+                        lowering.code.push(JUMPI);
+                        stubs.push((instruction.pc, index, target));
+                    }
+                    lowering.code.push(POP);
+                }
+                CALLF => {
+                    let callee = section_start(container, instruction)?;
+                    let inputs = container.code_sections[callee].code_type.inputs;
+                    let next = instruction.pc + 1 + instruction.immediate.len();
+                    lowering.push_label(Label::Instruction(next));
+                    for depth in (1..=u16::from(inputs)).rev() {
+                        lowering.code.push(swap(depth, instruction)?);
+                    }
+                    lowering.push_label(Label::Instruction(container.code_sections[callee].offset));
+                    lowering.code.push(JUMP);
+                }
+                RETF => {
+                    for depth in 1..=u16::from(section.code_type.outputs) {
+                        lowering.code.push(swap(depth, instruction)?);
+                    }
+                    lowering.code.push(JUMP);
+                }
+                JUMPF => {
+                    let callee = section_start(container, instruction)?;
+                    lowering.push_label(Label::Instruction(container.code_sections[callee].offset));
+                    lowering.code.push(JUMP);
+                }
+                DUPN => lowering.code.push(dup(immediate(instruction) + 1, instruction)?),
+                SWAPN => lowering.code.push(swap(immediate(instruction) + 1, instruction)?),
+                EXCHANGE => {
+                    // the items n and n + m deep are swapped by way of the top of the stack
+                    let n = (immediate(instruction) >> 4) + 1;
+                    let m = (immediate(instruction) & 0x0f) + 1;
+                    let deeper = n + m;
+                    lowering.code.extend([
+                        swap(n, instruction)?,
+                        swap(deeper, instruction)?,
+                        swap(n, instruction)?,
+                    ]);
+                }
+                DATALOADN => {
+                    let offset = u16::from_be_bytes(
+                        instruction.immediate.as_slice().try_into().unwrap_or_default(),
+                    ) as usize;
+                    let mut word = container.data.get(offset..).unwrap_or_default().to_vec();
+                    word.resize(32, 0);
+                    lowering.code.push(PUSH32);
+                    lowering.code.extend(&word[..32]);
+                }
+                DATASIZE => {
+                    lowering.code.push(PUSH2);
+                    lowering.code.extend(container.declared_data_size.to_be_bytes());
+                }
+                DATACOPY => {
+                    // the data is copied from where it's appended to the code
+                    lowering.code.push(SWAP1);
+                    lowering.push_label(Label::Data);
+                    lowering.code.extend([ADD, SWAP1, CODECOPY]);
+                }
+                EXTCALL | EXTDELEGATECALL | EXTSTATICCALL => {
+                    lower_external_call(&mut lowering.code, instruction.opcode)
+                }
+                opcode if eof_opcode_info(opcode).is_none() || is_unlowerable(opcode) => {
+                    let name = eof_opcode_info(opcode).map(|info| info.name()).unwrap_or("INVALID");
+                    bail!(
+                        "{} at {} has no legacy equivalent, so the container can't be lifted",
+                        name,
+                        instruction.pc
+                    );
+                }
+                opcode => {
+                    lowering.code.push(opcode);
+                    lowering.code.extend(&instruction.immediate);
+                }
+            }
+        }
+    }
+
+    // the index of the RJUMPV is popped before jumping to its target
+    for (pc, index, target) in stubs {
+        lowering.labels.insert(Label::Stub(pc, index), lowering.code.len());
+        lowering.code.extend([JUMPDEST, POP]);
+        lowering.push_label(Label::Instruction(target));
+        lowering.code.push(JUMP);
+    }
+
+    lowering.labels.insert(Label::Data, lowering.code.len());
+    lowering.code.extend(&container.data);
+    lowering.resolve()
+}
+
+/// The legacy bytecode as it's emitted, with the labels whose offsets are patched in once
+/// they're all known.
+#[derive(Debug, Default)]
+struct Lowering {
+    /// The legacy bytecode emitted so far
+    code: Vec<u8>,
+    /// The offset of each label in the legacy bytecode
+    labels: HashMap<Label, usize>,
+    /// The offsets of the `PUSH2` immediates which are patched with each label's offset
+    fixups: Vec<(usize, Label)>,
+}
+
+impl Lowering {
+    /// Emits a `PUSH2` of the label's offset, which is patched in by [`Lowering::resolve`].
+    fn push_label(&mut self, label: Label) {
+        self.code.push(PUSH2);
+        self.fixups.push((self.code.len(), label));
+        self.code.extend([0, 0]);
+    }
+
+    /// Patches each label's offset into the `PUSH2`s which push it.
+    fn resolve(mut self) -> Result<Vec<u8>> {
+        for (position, label) in &self.fixups {
+            let offset = match self.labels.get(label) {
+                Some(offset) => *offset,
+                None => bail!("the container jumps to {:?}, which isn't an instruction", label),
+            };
+            let offset = match u16::try_from(offset) {
+                Ok(offset) => offset,
+                Err(_) => bail!("the lowered container is too large to address with PUSH2"),
+            };
+            self.code[*position..*position + 2].copy_from_slice(&offset.to_be_bytes());
+        }
+        Ok(self.code)
+    }
+}
+
+/// Lowers `EXTCALL`, `EXTDELEGATECALL` or `EXTSTATICCALL` to the legacy call taking the same
+/// target, input and value, which forwards all remaining gas and doesn't copy any returndata. The
+/// inputs are duplicated into the legacy call's order, and the originals popped from beneath its
+/// result, which is inverted, since EOF's calls push zero on success.
+fn lower_external_call(code: &mut Vec<u8>, opcode: u8) {
+    let (call, inputs) = match opcode {
+        EXTCALL => (CALL, 4),
+        EXTDELEGATECALL => (DELEGATECALL, 3),
+        _ => (STATICCALL, 3),
+    };
+
+    // (target, input offset, input size[, value]) becomes
+    // (gas, target[, value], input offset, input size, 0, 0)
+    code.extend([PUSH0, PUSH0, DUP1 + 4, DUP1 + 4]);
+    if opcode == EXTCALL {
+        code.extend([DUP1 + 7, DUP1 + 5]);
+    } else {
+        code.push(DUP1 + 4);
+    }
+    code.extend([GAS, call, SWAP1 + inputs - 1]);
+    code.extend(std::iter::repeat_n(POP, inputs as usize));
+    code.push(ISZERO);
+}
+
+/// Whether the opcode is valid in EOF, but reads from somewhere legacy bytecode can't, or
+/// deploys a container.
+fn is_unlowerable(opcode: u8) -> bool {
+    matches!(opcode, DATALOAD | RETURNDATALOAD | EOFCREATE | RETURNCONTRACT)
+}
+
+/// The code section called or jumped to by a `CALLF` or `JUMPF`.
+fn section_start(container: &EofContainer, instruction: &EofInstruction) -> Result<usize> {
+    match instruction.section_target() {
+        Some(section) if section < container.code_sections.len() => Ok(section),
+        _ => bail!("the instruction at {} calls a section which doesn't exist", instruction.pc),
+    }
+}
+
+/// The single byte immediate of a `DUPN`, `SWAPN` or `EXCHANGE`, widened so that the depths
+/// computed from it can't overflow.
+fn immediate(instruction: &EofInstruction) -> u16 {
+    instruction.immediate.first().copied().map(u16::from).unwrap_or_default()
+}
+
+/// The legacy `DUP` of the item `depth` deep, if it's one of the top sixteen.
+fn dup(depth: u16, instruction: &EofInstruction) -> Result<u8> {
+    match depth {
+        1..=16 => Ok(DUP1 + depth as u8 - 1),
+        _ => bail!("the instruction at {} reaches {} items deep", instruction.pc, depth),
+    }
+}
+
+/// The legacy `SWAP` of the top of the stack with the item `depth` beneath it, if it's one of the
+/// sixteen beneath the top.
+fn swap(depth: u16, instruction: &EofInstruction) -> Result<u8> {
+    match depth {
+        1..=16 => Ok(SWAP1 + depth as u8 - 1),
+        _ => bail!("the instruction at {} reaches {} items deep", instruction.pc, depth + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, U256};
+    use heimdall_common::utils::strings::decode_hex;
+
+    use super::*;
+    use crate::core::vm::VM;
+
+    // `0x11111111(x)` stores `double(x)` in slot 0, calling the second code section, which
+    // returns its input doubled, and `0x22222222()` returns slot 0. other selectors revert
+    const CONTAINER: &str = "ef0001010008020002002d0003040000000080000301010002\
+        5f3560e01c80631111111114e1000c632222222214e1000d5f5ffd\
+        50600435e300015f55005f545f5260205ff3\
+        8001e4";
+
+    fn lower(container: &str) -> Result<Vec<u8>> {
+        lower_to_legacy(&EofContainer::parse(&decode_hex(container).expect("invalid bytecode"))?)
+    }
+
+    fn new_vm(bytecode: &[u8]) -> VM {
+        VM::new(
+            bytecode,
+            &[],
+            Address::default(),
+            Address::default(),
+            Address::default(),
+            0,
+            u128::MAX,
+        )
+    }
+
+    #[test]
+    fn test_lowered_container_calls_sections() {
+        let mut vm = new_vm(&lower(CONTAINER).expect("failed to lower container"));
+
+        let mut calldata = decode_hex("11111111").unwrap();
+        calldata.extend(U256::from(21).to_be_bytes::<32>());
+        let result = vm.call(&calldata, 0).expect("call failed");
+        assert_eq!(result.exitcode, 10);
+        assert_eq!(vm.storage.storage.get(&U256::ZERO), Some(&U256::from(42)));
+
+        vm.call(&decode_hex("22222222").unwrap(), 0).expect("call failed");
+        assert_eq!(vm.exitcode, 0);
+        assert_eq!(U256::from_be_slice(&vm.returndata), U256::from(42));
+
+        vm.call(&decode_hex("33333333").unwrap(), 0).expect("call failed");
+        assert_eq!(vm.exitcode, 1);
+    }
+
+    #[test]
+    fn test_reject_unlowerable_container() {
+        // a single section which reads DATALOAD(0), then stops
+        let error = lower("ef0001010004020001000304000000008000015fd000")
+            .expect_err("DATALOAD has no legacy equivalent");
+        assert!(error.to_string().contains("DATALOAD"));
+    }
+
+    #[test]
+    fn test_reject_deep_stack_access() {
+        // a single section which runs DUPN 0xff or SWAPN 0xff, then stops
+        for (container, depth) in [
+            ("ef000101000402000100030400000000800001e6ff00", 256),
+            ("ef000101000402000100030400000000800001e7ff00", 257),
+        ] {
+            let error = lower(container).expect_err("the stack access is deeper than 16 items");
+            assert!(error.to_string().contains(&format!("reaches {depth} items deep")));
+        }
+    }
+}
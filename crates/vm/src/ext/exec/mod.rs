@@ -21,8 +21,8 @@ use crate::{
 use eyre::Result;
 use hashbrown::HashMap;
 use heimdall_common::utils::strings::decode_hex;
-use std::time::Instant;
-use tracing::{trace, warn};
+use tracing::{debug, trace, warn};
+use web_time::Instant;
 
 /// Represents a trace of virtual machine execution including operations and child calls
 ///
@@ -43,8 +43,30 @@ pub struct VMTrace {
     pub children: Vec<VMTrace>,
 }
 
+/// The budget which stopped symbolic execution from exploring every branch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Truncation {
+    /// The timeout was reached
+    Timeout,
+    /// The branch limit was reached
+    BranchLimit,
+    /// A branch expanded memory past the memory limit
+    MemoryLimit,
+}
+
+impl std::fmt::Display for Truncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Truncation::Timeout => write!(f, "the timeout"),
+            Truncation::BranchLimit => write!(f, "the branch limit"),
+            Truncation::MemoryLimit => write!(f, "the memory limit"),
+        }
+    }
+}
+
 impl VM {
-    /// Run symbolic execution on a given function selector within a contract
+    /// Run symbolic execution on a given function selector within a contract. If a budget stops
+    /// it from exploring every branch, [`VM::truncation`] is set to the budget which did.
     pub fn symbolic_exec_selector(
         &mut self,
         selector: &str,
@@ -74,7 +96,14 @@ impl VM {
 
         // the VM is at the function entry point, begin tracing
         let mut branch_count = 0;
-        let trace = match self.recursive_map(&mut branch_count, &mut HashMap::new(), &timeout)? {
+        let mut truncation = None;
+        let trace = match self.recursive_map(
+            &mut branch_count,
+            &mut truncation,
+            &mut HashMap::new(),
+            &[],
+            &timeout,
+        )? {
             Some(trace) => trace,
             None => {
                 warn!("symbolic execution returned no valid traces for selector 0x{}", selector);
@@ -86,6 +115,7 @@ impl VM {
                 }
             }
         };
+        self.truncation = truncation;
         Ok((trace, branch_count))
     }
 
@@ -107,7 +137,14 @@ impl VM {
 
         // the VM is at the function entry point, begin tracing
         let mut branch_count = 0;
-        let trace = match self.recursive_map(&mut branch_count, &mut HashMap::new(), &timeout)? {
+        let mut truncation = None;
+        let trace = match self.recursive_map(
+            &mut branch_count,
+            &mut truncation,
+            &mut HashMap::new(),
+            &[],
+            &timeout,
+        )? {
             Some(trace) => trace,
             None => {
                 warn!("symbolic execution returned no valid traces");
@@ -119,16 +156,26 @@ impl VM {
                 }
             }
         };
+        self.truncation = truncation;
         Ok((trace, branch_count))
     }
 
+    /// Maps the branches reachable from the current instruction. `loop_path` holds the JUMPIs
+    /// taken to reach it, with the size of the stack after each, so that a JUMPI which matches the
+    /// loop-detection heuristics can be told apart as a loop's back-edge: such branches are kept,
+    /// ending at the loop's header, rather than discarded. A JUMPI is dropped from the path once
+    /// the stack shrinks below its size, such as when an internal function returns. The first
+    /// budget to cut a branch short is recorded in `truncation`.
     fn recursive_map(
         &mut self,
         branch_count: &mut u32,
+        truncation: &mut Option<Truncation>,
         handled_jumps: &mut HashMap<JumpFrame, Vec<Stack>>,
+        loop_path: &[(u128, usize)],
         timeout_at: &Instant,
     ) -> Result<Option<VMTrace>> {
         let vm = self;
+        let mut loop_path = loop_path.to_vec();
 
         // create a new VMTrace object
         // this will essentially be a tree of executions, with each branch being a different path
@@ -142,8 +189,18 @@ impl VM {
 
         // step through the bytecode until we find a JUMPI instruction
         while vm.bytecode.len() >= vm.instruction as usize {
-            // if we have reached the timeout, return None
-            if Instant::now() >= *timeout_at {
+            // if we have reached the timeout or a limit, return None
+            let exceeded = if Instant::now() >= *timeout_at {
+                Some(Truncation::Timeout)
+            } else if vm.branch_limit.is_some_and(|limit| *branch_count >= limit) {
+                Some(Truncation::BranchLimit)
+            } else if vm.memory_limit.is_some_and(|limit| vm.memory.size() > limit) {
+                Some(Truncation::MemoryLimit)
+            } else {
+                None
+            };
+            if let Some(exceeded) = exceeded {
+                truncation.get_or_insert(exceeded);
                 return Ok(None);
             }
 
@@ -156,6 +213,7 @@ impl VM {
                 }
             };
             let last_instruction = state.last_instruction.clone();
+            loop_path.retain(|(_, stack_size)| vm.stack.size() >= *stack_size);
 
             // update vm_trace
             vm_trace.operations.push(state);
@@ -266,7 +324,10 @@ impl VM {
 
                             // this key exists, but the stack is different, so the jump is new
                             historical_stacks.push(vm.stack.clone());
-                            return Ok(None);
+                            return Ok(loop_path
+                                .iter()
+                                .any(|(pc, _)| *pc == last_instruction.instruction)
+                                .then_some(vm_trace));
                         }
 
                         // check if any stack position shows a consistent pattern
@@ -281,7 +342,10 @@ impl VM {
 
                             // this key exists, but the stack is different, so the jump is new
                             historical_stacks.push(vm.stack.clone());
-                            return Ok(None);
+                            return Ok(loop_path
+                                .iter()
+                                .any(|(pc, _)| *pc == last_instruction.instruction)
+                                .then_some(vm_trace));
                         }
 
                         if historical_diffs_approximately_equal(&vm.stack, historical_stacks) {
@@ -294,7 +358,10 @@ impl VM {
 
                             // this key exists, but the stack is different, so the jump is new
                             historical_stacks.push(vm.stack.clone());
-                            return Ok(None);
+                            return Ok(loop_path
+                                .iter()
+                                .any(|(pc, _)| *pc == last_instruction.instruction)
+                                .then_some(vm_trace));
                         }
 
                         {
@@ -327,6 +394,9 @@ impl VM {
 
                 // we didnt break out, so now we crate branching paths to cover all possibilities
                 *branch_count += 1;
+                if branch_count.is_multiple_of(100) {
+                    debug!(branches = *branch_count, "explored {} branches", branch_count);
+                }
                 trace!(
                     "creating branching paths at instructions {} (JUMPDEST) and {} (CONTINUE)",
                     last_instruction.inputs[0],
@@ -334,12 +404,19 @@ impl VM {
                 );
 
                 // we need to create a trace for the path that wasn't taken.
+                loop_path.push((last_instruction.instruction, vm.stack.size()));
                 if !jump_taken {
                     // push a new vm trace to the children
                     let mut trace_vm = vm.clone();
                     trace_vm.instruction =
                         last_instruction.inputs[0].try_into().unwrap_or(u128::MAX) + 1;
-                    match trace_vm.recursive_map(branch_count, handled_jumps, timeout_at) {
+                    match trace_vm.recursive_map(
+                        branch_count,
+                        truncation,
+                        handled_jumps,
+                        &loop_path,
+                        timeout_at,
+                    ) {
                         Ok(Some(child_trace)) => vm_trace.children.push(child_trace),
                         Ok(None) => {}
                         Err(e) => {
@@ -349,7 +426,13 @@ impl VM {
                     }
 
                     // push the current path onto the stack
-                    match vm.recursive_map(branch_count, handled_jumps, timeout_at) {
+                    match vm.recursive_map(
+                        branch_count,
+                        truncation,
+                        handled_jumps,
+                        &loop_path,
+                        timeout_at,
+                    ) {
                         Ok(Some(child_trace)) => vm_trace.children.push(child_trace),
                         Ok(None) => {}
                         Err(e) => {
@@ -362,7 +445,13 @@ impl VM {
                     // push a new vm trace to the children
                     let mut trace_vm = vm.clone();
                     trace_vm.instruction = last_instruction.instruction + 1;
-                    match trace_vm.recursive_map(branch_count, handled_jumps, timeout_at) {
+                    match trace_vm.recursive_map(
+                        branch_count,
+                        truncation,
+                        handled_jumps,
+                        &loop_path,
+                        timeout_at,
+                    ) {
                         Ok(Some(child_trace)) => vm_trace.children.push(child_trace),
                         Ok(None) => {}
                         Err(e) => {
@@ -372,7 +461,13 @@ impl VM {
                     }
 
                     // push the current path onto the stack
-                    match vm.recursive_map(branch_count, handled_jumps, timeout_at) {
+                    match vm.recursive_map(
+                        branch_count,
+                        truncation,
+                        handled_jumps,
+                        &loop_path,
+                        timeout_at,
+                    ) {
                         Ok(Some(child_trace)) => vm_trace.children.push(child_trace),
                         Ok(None) => {}
                         Err(e) => {
@@ -396,5 +491,69 @@ impl VM {
 
 #[cfg(test)]
 mod tests {
-    // TODO: add tests for symbolic execution & recursive_map
+    use std::time::{Duration, Instant};
+
+    use alloy::primitives::Address;
+
+    use super::Truncation;
+    use crate::core::vm::VM;
+
+    // two calldata-dependent JUMPIs in sequence, each reverting if not taken
+    const BYTECODE: &str = "60003561000b57600080fd5b60203561001757600080fd5b00";
+
+    fn new_vm() -> VM {
+        VM::new(
+            &alloy::hex::decode(BYTECODE).expect("failed to decode bytecode"),
+            &[],
+            Address::default(),
+            Address::default(),
+            Address::default(),
+            0,
+            u128::MAX,
+        )
+    }
+
+    #[test]
+    fn test_symbolic_exec_branch_limit() {
+        let timeout = Instant::now() + Duration::from_secs(10);
+        let (_, branch_count) = new_vm().symbolic_exec(timeout).expect("symbolic execution failed");
+        assert_eq!(branch_count, 2);
+
+        let (trace, branch_count) = new_vm()
+            .with_branch_limit(1)
+            .symbolic_exec(timeout)
+            .expect("symbolic execution failed");
+        assert_eq!(branch_count, 1);
+        assert!(trace.children.is_empty());
+    }
+
+    #[test]
+    fn test_symbolic_exec_truncation() {
+        let timeout = Instant::now() + Duration::from_secs(10);
+        let mut vm = new_vm();
+        vm.symbolic_exec(timeout).expect("symbolic execution failed");
+        assert_eq!(vm.truncation, None);
+
+        let mut vm = new_vm().with_branch_limit(1);
+        vm.symbolic_exec(timeout).expect("symbolic execution failed");
+        assert_eq!(vm.truncation, Some(Truncation::BranchLimit));
+
+        // storing a word expands memory past a limit of 16 bytes
+        let mut vm = VM::new(
+            &alloy::hex::decode("600160005200").expect("failed to decode bytecode"),
+            &[],
+            Address::default(),
+            Address::default(),
+            Address::default(),
+            0,
+            u128::MAX,
+        )
+        .with_memory_limit(16);
+        vm.symbolic_exec(timeout).expect("symbolic execution failed");
+        assert_eq!(vm.truncation, Some(Truncation::MemoryLimit));
+
+        let mut vm = new_vm();
+        vm.symbolic_exec(Instant::now()).expect("symbolic execution failed");
+        assert_eq!(vm.truncation, Some(Truncation::Timeout));
+    }
 }
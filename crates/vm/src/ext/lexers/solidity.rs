@@ -7,12 +7,12 @@ use heimdall_common::{
 };
 
 use crate::core::opcodes::{
-    WrappedInput, WrappedOpcode, ADD, ADDMOD, ADDRESS, AND, BALANCE, BASEFEE, BLOCKHASH, BYTE,
-    CALL, CALLCODE, CALLDATALOAD, CALLDATASIZE, CALLER, CALLVALUE, CHAINID, CLZ, CODESIZE,
-    COINBASE, DELEGATECALL, DIV, EQ, EXP, EXTCODEHASH, EXTCODESIZE, GAS, GASLIMIT, GASPRICE, GT,
-    ISZERO, LT, MLOAD, MOD, MSIZE, MUL, MULMOD, NOT, NUMBER, OR, ORIGIN, PREVRANDAO, PUSH0,
-    RETURNDATASIZE, SAR, SDIV, SELFBALANCE, SGT, SHA3, SHL, SHR, SLOAD, SLT, SMOD, STATICCALL, SUB,
-    TIMESTAMP, TLOAD, XOR,
+    WrappedInput, WrappedOpcode, ADD, ADDMOD, ADDRESS, AND, BALANCE, BASEFEE, BLOBBASEFEE,
+    BLOBHASH, BLOCKHASH, BYTE, CALL, CALLCODE, CALLDATALOAD, CALLDATASIZE, CALLER, CALLVALUE,
+    CHAINID, CLZ, CODESIZE, COINBASE, DELEGATECALL, DIV, EQ, EXP, EXTCODEHASH, EXTCODESIZE, GAS,
+    GASLIMIT, GASPRICE, GT, ISZERO, LT, MLOAD, MOD, MSIZE, MUL, MULMOD, NOT, NUMBER, OR, ORIGIN,
+    PREVRANDAO, PUSH0, RETURNDATASIZE, SAR, SDIV, SELFBALANCE, SGT, SHA3, SHL, SHR, SLOAD, SLT,
+    SMOD, STATICCALL, SUB, TIMESTAMP, TLOAD, XOR,
 };
 
 /// Checks if a given address is a supported precompiled contract address
@@ -303,6 +303,13 @@ impl WrappedOpcode {
             BASEFEE => {
                 solidified_wrapped_opcode.push_str("block.basefee");
             }
+            BLOBBASEFEE => {
+                solidified_wrapped_opcode.push_str("block.blobbasefee");
+            }
+            BLOBHASH => {
+                solidified_wrapped_opcode
+                    .push_str(format!("blobhash({})", self.inputs[0]._solidify()).as_str());
+            }
             GAS => {
                 solidified_wrapped_opcode.push_str("gasleft()");
             }
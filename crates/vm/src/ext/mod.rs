@@ -1,3 +1,9 @@
+/// Splitting creation bytecode into its constructor, runtime bytecode and arguments
+pub mod creation;
+
+/// Lowering EOF containers to legacy bytecode, so that they can be symbolically executed
+pub mod eof;
+
 /// Execution utilities for running and analyzing VM operations
 pub mod exec;
 
@@ -1,19 +1,25 @@
 use hashbrown::{HashMap, HashSet};
-use std::{
-    sync::{Arc, Mutex},
-    time::Instant,
-};
 
-use eyre::Result;
-use heimdall_common::{
-    ether::signatures::{ResolveSelector, ResolvedFunction},
-    utils::strings::decode_hex,
-};
-use tokio::task;
-use tracing::{debug, error, info, trace, warn};
+use heimdall_common::utils::strings::decode_hex;
+use tracing::{info, trace};
 
 use crate::core::vm::VM;
 
+// resolving selectors isn't available on wasm
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    eyre::Result,
+    heimdall_common::ether::signatures::{
+        signature_concurrency, ResolveSelector, ResolvedFunction,
+    },
+    std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    },
+    tokio::{sync::Semaphore, task},
+    tracing::{debug, error, warn},
+};
+
 /// Finds and resolves function selectors from disassembled bytecode
 ///
 /// This function analyzes disassembled EVM bytecode to extract function selectors
@@ -28,6 +34,7 @@ use crate::core::vm::VM;
 /// * A Result containing a tuple with:
 ///   - A HashMap mapping selector strings to their instruction offsets
 ///   - A HashMap mapping selector strings to their resolved function information
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn get_resolved_selectors(
     disassembled_bytecode: &str,
     skip_resolving: &bool,
@@ -107,8 +114,9 @@ pub fn find_function_selectors(evm: &VM, assembly: &str) -> HashMap<String, u128
     function_selectors
 }
 
-/// resolve a selector's function entry point from the EVM bytecode
-// TODO: update resolve_entry_point logic to support vyper
+/// resolve a selector's function entry point from the EVM bytecode. both solidity's dispatcher,
+/// which jumps to the function if the selector is equal, and vyper's, which jumps to the next
+/// selector check if the selector differs (i.e. its `XOR` is non-zero), are supported.
 pub fn resolve_entry_point(vm: &mut VM, selector: &str) -> u128 {
     let mut handled_jumps = HashSet::new();
 
@@ -131,6 +139,13 @@ pub fn resolve_entry_point(vm: &mut VM, selector: &str) -> u128 {
                 jump_taken == 1
             {
                 return call.last_instruction.inputs[0].try_into().unwrap_or(0);
+            } else if jump_condition.contains(selector) &&
+                jump_condition.contains("msg.data[0]") &&
+                jump_condition.contains(" ^ ") &&
+                jump_taken == 0
+            {
+                // vyper falls through into the function, so it begins right after the JUMPI
+                return call.last_instruction.instruction;
             } else if jump_taken == 1 {
                 // if handled_jumps contains the jumpi, we have already handled this jump.
                 // loops aren't supported in the dispatcher, so we can just return 0
@@ -152,6 +167,10 @@ pub fn resolve_entry_point(vm: &mut VM, selector: &str) -> u128 {
 }
 
 /// Resolve a list of selectors to their function signatures.
+///
+/// Selectors are resolved concurrently, at most [`signature_concurrency`] at a time, and
+/// duplicate selectors are only resolved once.
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn resolve_selectors<T>(selectors: Vec<String>) -> HashMap<String, Vec<T>>
 where
     T: ResolveSelector + Send + Clone + 'static, {
@@ -162,15 +181,19 @@ where
 
     let resolved_functions: Arc<Mutex<HashMap<String, Vec<T>>>> =
         Arc::new(Mutex::new(HashMap::new()));
+    let permits = Arc::new(Semaphore::new(signature_concurrency()));
     let mut threads = Vec::new();
     let start_time = Instant::now();
+    let selectors = selectors.into_iter().collect::<HashSet<_>>();
     let selector_count = selectors.len();
 
     for selector in selectors {
         let function_clone = resolved_functions.clone();
+        let permits = permits.clone();
 
-        // create a new thread for each selector
+        // create a new thread for each selector, which waits for a permit before resolving
         threads.push(task::spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("semaphore is never closed");
             if let Ok(Some(function)) = T::resolve(&selector).await {
                 let mut _resolved_functions =
                     function_clone.lock().expect("Could not obtain lock on function_clone.");
@@ -196,3 +219,10 @@ where
     debug!("signature resolution took {:?}", start_time.elapsed());
     signatures
 }
+
+/// Selectors can't be resolved on wasm, where network access isn't available, so none of them
+/// are.
+#[cfg(target_arch = "wasm32")]
+pub async fn resolve_selectors<T>(_selectors: Vec<String>) -> HashMap<String, Vec<T>> {
+    HashMap::new()
+}
@@ -0,0 +1,180 @@
+//! The wasm module provides JavaScript bindings for decoding calldata, and disassembling and
+//! decompiling EVM bytecode, so that they can run client-side, e.g. in block explorers and browser
+//! tooling.
+//!
+//! Network access isn't available on wasm, so the target's calldata or bytecode must be given
+//! directly. Calldata selectors are resolved by a resolver injected from JavaScript, while the
+//! decompiler leaves selectors unresolved.
+
+use clap::ValueEnum;
+use heimdall_common::{
+    ether::signatures::{builtin_error, FromTextSignature, ResolvedFunction},
+    utils::strings::{decode_hex, encode_hex},
+};
+use heimdall_decoder::decode_calldata;
+use heimdall_decompiler::{DecompilerArgsBuilder, OutputFormat};
+use heimdall_disassembler::{disassemble_bytecode, HardFork};
+use js_sys::{Array, Function, Promise};
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+/// Disassembles the given hex-encoded bytecode into assembly instructions, one per line.
+///
+/// If `decimal_counter` is set, the program counter is printed in base-10 rather than hex.
+#[wasm_bindgen]
+pub fn disassemble(bytecode: &str, decimal_counter: bool) -> Result<String, JsError> {
+    disassemble_hex(bytecode, decimal_counter).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Decodes the given hex-encoded calldata or revert data, returning the decoded function and its
+/// arguments as JSON.
+///
+/// The selector is resolved by calling `resolve` with it, e.g. `"0xa9059cbb"`, which returns the
+/// candidate text signatures, e.g. `["transfer(address,uint256)"]`, or a promise of them. Without
+/// a resolver, the arguments are inferred from the raw calldata.
+#[wasm_bindgen]
+pub async fn decode(calldata: &str, resolve: Option<Function>) -> Result<String, JsError> {
+    let selector = calldata_selector(calldata).map_err(|e| JsError::new(&e.to_string()))?;
+    let signatures = match (builtin_error(&selector), resolve) {
+        // revert data can't be anything but the builtin error, so it isn't resolved
+        (Some(_), _) | (None, None) => Vec::new(),
+        (None, Some(resolve)) => {
+            let returned = resolve
+                .call1(&JsValue::NULL, &JsValue::from_str(&format!("0x{selector}")))
+                .map_err(|e| JsError::new(&format!("resolving selector failed: {e:?}")))?;
+            let resolved = JsFuture::from(Promise::resolve(&returned))
+                .await
+                .map_err(|e| JsError::new(&format!("resolving selector failed: {e:?}")))?;
+            Array::from(&resolved).iter().filter_map(|signature| signature.as_string()).collect()
+        }
+    };
+
+    decode_hex_calldata(calldata, &signatures).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Decompiles the given hex-encoded runtime or creation bytecode, returning its decompiled source,
+/// ABI and storage layout as JSON.
+///
+/// `format` is the source's format, e.g. `"sol"`, `"yul"` or `"vy"`, or `"abi"` for only the ABI.
+/// Selectors aren't resolved, so functions, events and errors are named after their selectors.
+#[wasm_bindgen]
+pub async fn decompile(bytecode: &str, format: &str) -> Result<String, JsError> {
+    decompile_hex(bytecode, format).await.map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Disassembles the hex-encoded bytecode, recognizing the opcodes of the latest hardfork.
+fn disassemble_hex(bytecode: &str, decimal_counter: bool) -> eyre::Result<String> {
+    let bytecode = decode_hex(bytecode)?;
+    Ok(disassemble_bytecode(&bytecode, HardFork::Latest, decimal_counter))
+}
+
+/// Decompiles the hex-encoded bytecode into the given source format, without resolving its
+/// selectors, returning the result as JSON.
+async fn decompile_hex(bytecode: &str, format: &str) -> eyre::Result<String> {
+    let output_format = OutputFormat::from_str(format, true)
+        .map_err(|_| eyre::eyre!("unsupported source format '{format}'"))?;
+    let result = heimdall_decompiler::decompile(
+        DecompilerArgsBuilder::new()
+            .target(bytecode.to_string())
+            .output_format(Some(output_format))
+            .skip_resolving(true)
+            .build()?,
+    )
+    .await?;
+
+    Ok(serde_json::to_string_pretty(&json!({
+        "source": result.source,
+        "source_format": result
+            .source_format
+            .to_possible_value()
+            .map(|format| format.get_name().to_string()),
+        "abi": result.abi,
+        "storage_layout": result.storage_layout.to_solc_json(),
+    }))?)
+}
+
+/// Gets the hex-encoded selector of the hex-encoded calldata, without a `0x` prefix.
+fn calldata_selector(calldata: &str) -> eyre::Result<String> {
+    let calldata = decode_hex(calldata)?;
+    calldata
+        .get(0..4)
+        .map(encode_hex)
+        .ok_or_else(|| eyre::eyre!("calldata is too short to contain a selector"))
+}
+
+/// Decodes the hex-encoded calldata with the best of the candidate text signatures for its
+/// selector, returning the result as JSON. Revert data is decoded as the builtin error instead.
+fn decode_hex_calldata(calldata: &str, signatures: &[String]) -> eyre::Result<String> {
+    let calldata = decode_hex(calldata)?;
+    if calldata.is_empty() {
+        return Err(eyre::eyre!("calldata is empty. is this a value transfer?"));
+    }
+
+    let selector = encode_hex(calldata.get(0..4).unwrap_or_default());
+    let potential_matches = match builtin_error(&selector) {
+        Some(error) => vec![ResolvedFunction {
+            name: error.name,
+            signature: error.signature,
+            inputs: error.inputs,
+            decoded_inputs: None,
+        }],
+        None => signatures
+            .iter()
+            .filter_map(|signature| ResolvedFunction::from_text_signature(signature))
+            .collect(),
+    };
+
+    Ok(decode_calldata(&calldata, potential_matches)?.to_json()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disassemble_hex() {
+        assert_eq!(
+            disassemble_hex("0x6080604052", false).expect("failed to disassemble"),
+            "000000 PUSH1 80\n000002 PUSH1 40\n000004 MSTORE \n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decompile_hex() {
+        // `0x33333333` reverts with `0xdddddddd` if its argument is zero
+        let bytecode = "0x60003560e01c806333333333146100165760006000fd5b6004351561002057005b63dddddddd60e01b5f5260045ffd";
+        let decompiled = decompile_hex(bytecode, "sol").await.expect("failed to decompile");
+        assert!(decompiled.contains("\"source_format\": \"sol\""));
+        assert!(decompiled.contains("Unresolved_33333333"));
+
+        let decompiled = decompile_hex(bytecode, "abi").await.expect("failed to decompile");
+        assert!(decompiled.contains("\"source\": null"));
+
+        assert!(decompile_hex(bytecode, "java").await.is_err());
+    }
+
+    #[test]
+    fn test_decode_hex_calldata() {
+        let calldata = "0xa9059cbb000000000000000000000000000000000000000000000000000000000000dead0000000000000000000000000000000000000000000000000000000000000001";
+        assert_eq!(calldata_selector(calldata).expect("failed to get selector"), "a9059cbb");
+
+        let decoded = decode_hex_calldata(calldata, &["transfer(address,uint256)".to_string()])
+            .expect("failed to decode calldata");
+        assert!(decoded.contains("\"signature\": \"transfer(address,uint256)\""));
+
+        // without candidate signatures, the arguments are inferred from the raw calldata
+        let decoded = decode_hex_calldata(calldata, &[]).expect("failed to decode calldata");
+        assert!(decoded.contains("Unresolved_a9059cbb"));
+    }
+
+    #[test]
+    fn test_decode_hex_calldata_revert() {
+        // Error("a"), which is decoded as the builtin error regardless of the candidates
+        let revert = "0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000016100000000000000000000000000000000000000000000000000000000000000";
+        let decoded = decode_hex_calldata(revert, &["foo(bytes)".to_string()])
+            .expect("failed to decode revert data");
+        assert!(decoded.contains("\"signature\": \"Error(string)\""));
+        assert!(decoded.contains("\"revert\": true"));
+    }
+}
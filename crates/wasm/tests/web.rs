@@ -0,0 +1,30 @@
+//! Tests the JavaScript bindings in a wasm runtime, with `wasm-pack test --node crates/wasm`.
+
+#![cfg(target_arch = "wasm32")]
+
+use heimdall_wasm::{decompile, disassemble};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// `0x33333333` reverts with `0xdddddddd` if its argument is zero.
+const BYTECODE: &str = "0x60003560e01c806333333333146100165760006000fd5b6004351561002057005b63dddddddd60e01b5f5260045ffd";
+
+#[wasm_bindgen_test]
+fn test_disassemble() {
+    let assembly =
+        disassemble("0x6080604052", false).map_err(JsValue::from).expect("failed to disassemble");
+    assert_eq!(assembly, "000000 PUSH1 80\n000002 PUSH1 40\n000004 MSTORE \n");
+}
+
+#[wasm_bindgen_test]
+async fn test_decompile() {
+    let decompiled =
+        decompile(BYTECODE, "sol").await.map_err(JsValue::from).expect("failed to decompile");
+    assert!(decompiled.contains("Unresolved_33333333"));
+
+    let decompiled =
+        decompile(BYTECODE, "abi").await.map_err(JsValue::from).expect("failed to decompile");
+    assert!(decompiled.contains("\"source\": null"));
+
+    assert!(decompile(BYTECODE, "java").await.is_err());
+}